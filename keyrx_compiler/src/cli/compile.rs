@@ -6,12 +6,15 @@
 use std::fmt;
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::ParseError;
 use crate::error::SerializeError;
+use crate::error::WarningKind;
 use crate::parser::Parser;
 use crate::serialize::serialize;
+use crate::stats::config_stats;
+use keyrx_core::config::ConfigRoot;
 
 /// Errors that can occur during the compile subcommand.
 #[derive(Debug)]
@@ -24,8 +27,14 @@ pub enum CompileError {
     /// Failed to serialize configuration.
     SerializeError(SerializeError),
 
+    /// Failed to serialize configuration to JSON.
+    JsonError(serde_json::Error),
+
     /// I/O error during file operations.
     IoError(io::Error),
+
+    /// `--strict` turned this many warnings into errors.
+    WarningsAsErrors(usize),
 }
 
 impl fmt::Display for CompileError {
@@ -40,7 +49,13 @@ impl fmt::Display for CompileError {
                 )
             }
             Self::SerializeError(err) => write!(f, "{}", err),
+            Self::JsonError(err) => write!(f, "JSON serialization error: {}", err),
             Self::IoError(err) => write!(f, "I/O error: {}", err),
+            Self::WarningsAsErrors(count) => write!(
+                f,
+                "{} warning(s) treated as errors (--strict); pass --allow <name> to keep one as a warning",
+                count
+            ),
         }
     }
 }
@@ -59,12 +74,34 @@ impl From<ParseError> for CompileError {
     }
 }
 
+impl From<serde_json::Error> for CompileError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::JsonError(err)
+    }
+}
+
 impl From<SerializeError> for CompileError {
     fn from(err: SerializeError) -> Self {
         Self::SerializeError(err)
     }
 }
 
+/// How `compile` treats parser warnings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarningPolicy {
+    /// Fail compilation on any warning whose kind is not in `allowed`.
+    pub strict: bool,
+    /// Warning kinds that stay warnings under `strict`.
+    pub allowed: Vec<WarningKind>,
+}
+
+impl WarningPolicy {
+    /// Returns `true` if warnings of `kind` fail compilation.
+    pub fn is_error(&self, kind: WarningKind) -> bool {
+        self.strict && !self.allowed.contains(&kind)
+    }
+}
+
 /// Handles the compile subcommand.
 ///
 /// # Arguments
@@ -77,11 +114,72 @@ impl From<SerializeError> for CompileError {
 /// `Ok(())` on success, or `CompileError` on failure.
 #[allow(dead_code)] // Will be used in task 17
 pub fn handle_compile(input: &Path, output: &Path) -> Result<(), CompileError> {
-    eprintln!("Parsing {}...", input.display());
+    handle_compile_with_deps(input, output, None)
+}
 
-    // Parse the Rhai script
-    let mut parser = Parser::new();
-    let config = parser.parse_script(input)?;
+/// Handles the compile subcommand, optionally writing a dependency file.
+///
+/// When `deps_file` is set, a Makefile-style rule listing `input` and every
+/// file it transitively loads is written there after the .krx file.
+pub fn handle_compile_with_deps(
+    input: &Path,
+    output: &Path,
+    deps_file: Option<&Path>,
+) -> Result<(), CompileError> {
+    handle_compile_with_base(
+        input,
+        None,
+        output,
+        deps_file,
+        false,
+        &WarningPolicy::default(),
+    )
+}
+
+/// Handles the compile subcommand, optionally overlaying `input` on a base
+/// script.
+///
+/// When `base` is set, both scripts are parsed and `input` is merged on top
+/// with [`ConfigRoot::merge`], so its mappings win for the same key. The
+/// dependency file then lists the files of both scripts. With `emit_stats`,
+/// a summary of the configuration (see [`crate::stats`]) is printed last.
+///
+/// Warnings of both scripts are printed; those `warnings` promotes to errors
+/// fail compilation before anything is written.
+pub fn handle_compile_with_base(
+    input: &Path,
+    base: Option<&Path>,
+    output: &Path,
+    deps_file: Option<&Path>,
+    emit_stats: bool,
+    warnings: &WarningPolicy,
+) -> Result<(), CompileError> {
+    compile_krx(
+        input,
+        base,
+        output,
+        deps_file,
+        emit_stats,
+        warnings,
+        &mut Vec::new(),
+    )
+}
+
+/// Like [`handle_compile_with_base`], also recording in `dependencies` every
+/// file read, even when compilation fails.
+///
+/// `compile --watch` uses the list to know which files to watch.
+pub fn compile_krx(
+    input: &Path,
+    base: Option<&Path>,
+    output: &Path,
+    deps_file: Option<&Path>,
+    emit_stats: bool,
+    warnings: &WarningPolicy,
+    dependencies: &mut Vec<PathBuf>,
+) -> Result<(), CompileError> {
+    dependencies.clear();
+    let config = parse_over_base(input, base, dependencies, warnings)?;
 
     eprintln!("Serializing configuration...");
 
@@ -93,6 +191,11 @@ pub fn handle_compile(input: &Path, output: &Path) -> Result<(), CompileError> {
     // Write to output file
     fs::write(output, &bytes)?;
 
+    if let Some(deps_file) = deps_file {
+        eprintln!("Writing dependencies to {}...", deps_file.display());
+        fs::write(deps_file, format_dep_rule(output, dependencies))?;
+    }
+
     // Extract hash from bytes (bytes 8-40 contain the SHA256 hash)
     let hash = &bytes[8..40];
     let hash_hex = hex::encode(hash);
@@ -108,5 +211,172 @@ pub fn handle_compile(input: &Path, output: &Path) -> Result<(), CompileError> {
     eprintln!("  Size: {} bytes", file_size);
     eprintln!("  SHA256: {}", hash_hex);
 
+    if emit_stats {
+        print_stats(&config)?;
+    }
+
     Ok(())
 }
+
+/// Handles `compile --target wasm-json`.
+///
+/// Writes the configuration as JSON (the `parse --json` format) for the WASM
+/// `load_config_json` entry point, so simulation pages can load it with
+/// `JSON.parse` instead of the rkyv binary path. `base`, `deps_file`,
+/// `emit_stats` and `warnings` behave as in [`handle_compile_with_base`].
+#[allow(dead_code)] // The binary calls compile_wasm_json directly
+pub fn handle_compile_wasm_json(
+    input: &Path,
+    base: Option<&Path>,
+    output: &Path,
+    deps_file: Option<&Path>,
+    emit_stats: bool,
+    warnings: &WarningPolicy,
+) -> Result<(), CompileError> {
+    compile_wasm_json(
+        input,
+        base,
+        output,
+        deps_file,
+        emit_stats,
+        warnings,
+        &mut Vec::new(),
+    )
+}
+
+/// Like [`handle_compile_wasm_json`], also recording in `dependencies` every
+/// file read, even when compilation fails.
+pub fn compile_wasm_json(
+    input: &Path,
+    base: Option<&Path>,
+    output: &Path,
+    deps_file: Option<&Path>,
+    emit_stats: bool,
+    warnings: &WarningPolicy,
+    dependencies: &mut Vec<PathBuf>,
+) -> Result<(), CompileError> {
+    dependencies.clear();
+    let config = parse_over_base(input, base, dependencies, warnings)?;
+
+    eprintln!("Serializing configuration to JSON...");
+    let json = serde_json::to_string_pretty(&config)?;
+
+    eprintln!("Writing to {}...", output.display());
+    fs::write(output, &json)?;
+
+    if let Some(deps_file) = deps_file {
+        eprintln!("Writing dependencies to {}...", deps_file.display());
+        fs::write(deps_file, format_dep_rule(output, dependencies))?;
+    }
+
+    println!(
+        "Successfully compiled {} to {}",
+        input.display(),
+        output.display()
+    );
+    eprintln!("  Size: {} bytes", json.len());
+
+    if emit_stats {
+        print_stats(&config)?;
+    }
+
+    Ok(())
+}
+
+/// Prints the `--emit-stats` summary of a compiled configuration.
+fn print_stats(config: &ConfigRoot) -> Result<(), CompileError> {
+    let stats = config_stats(config)?;
+    println!();
+    println!("{}", stats);
+    Ok(())
+}
+
+/// Parses `input`, merged over `base` when set, recording the files both load.
+///
+/// Fails once both scripts are parsed if `warnings` promoted any of their
+/// warnings to errors, so every promoted warning is reported.
+fn parse_over_base(
+    input: &Path,
+    base: Option<&Path>,
+    dependencies: &mut Vec<PathBuf>,
+    warnings: &WarningPolicy,
+) -> Result<ConfigRoot, CompileError> {
+    let mut promoted = 0;
+    let base_config = match base {
+        Some(base) => Some(parse_with_warnings(
+            base,
+            dependencies,
+            warnings,
+            &mut promoted,
+        )?),
+        None => None,
+    };
+    let mut config = parse_with_warnings(input, dependencies, warnings, &mut promoted)?;
+
+    if promoted > 0 {
+        return Err(CompileError::WarningsAsErrors(promoted));
+    }
+
+    if let Some(base_config) = base_config {
+        eprintln!("Merging {} over base...", input.display());
+        config = ConfigRoot::merge(base_config, config);
+    }
+
+    Ok(config)
+}
+
+/// Parses `path`, printing its warnings and recording the files it loads.
+///
+/// The files are recorded even if parsing fails, as far as the parser got.
+/// Warnings that `warnings` promotes are printed as errors and counted in
+/// `promoted`.
+fn parse_with_warnings(
+    path: &Path,
+    dependencies: &mut Vec<PathBuf>,
+    warnings: &WarningPolicy,
+    promoted: &mut usize,
+) -> Result<ConfigRoot, CompileError> {
+    eprintln!("Parsing {}...", path.display());
+
+    let mut parser = Parser::new();
+    let result = parser.parse_script(path);
+
+    for dep in parser.dependencies() {
+        if !dependencies.contains(&dep) {
+            dependencies.push(dep);
+        }
+    }
+    let config = result?;
+
+    for warning in parser.warnings() {
+        if warnings.is_error(warning.kind) {
+            *promoted += 1;
+            eprintln!(
+                "{}:{}:{}: Error: {} [{}]",
+                warning.file.display(),
+                warning.line,
+                warning.column,
+                warning.message,
+                warning.kind
+            );
+        } else {
+            eprintln!("{}", warning);
+        }
+    }
+
+    Ok(config)
+}
+
+/// Formats a Makefile-style dependency rule (`target: dep1 dep2`).
+///
+/// Spaces in paths are escaped with a backslash as make expects.
+pub fn format_dep_rule(target: &Path, deps: &[PathBuf]) -> String {
+    let escape = |path: &Path| path.display().to_string().replace(' ', "\\ ");
+    let mut rule = format!("{}:", escape(target));
+    for dep in deps {
+        rule.push(' ');
+        rule.push_str(&escape(dep));
+    }
+    rule.push('\n');
+    rule
+}
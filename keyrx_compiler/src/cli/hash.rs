@@ -1,7 +1,10 @@
 //! Hash subcommand handler.
 //!
 //! Handles the `hash` subcommand which extracts and verifies SHA256 hashes
-//! from .krx binary files.
+//! from .krx binary files. Besides the integrity check, the embedded hash can
+//! be compared against a known value and the file's recorded source hash
+//! against a reviewed .rhai script, so CI can check where a shipped .krx
+//! came from.
 
 use std::fmt;
 use std::fs;
@@ -10,6 +13,9 @@ use std::path::Path;
 
 use sha2::{Digest, Sha256};
 
+use crate::error::DeserializeError;
+use crate::serialize::deserialize;
+
 /// Errors that can occur during the hash subcommand.
 #[derive(Debug)]
 #[allow(dead_code)] // Will be used when integrated into main.rs in task 17
@@ -20,6 +26,21 @@ pub enum HashError {
         computed: [u8; 32],
     },
 
+    /// Embedded hash differs from the expected value given on the command line.
+    ExpectedHashMismatch {
+        expected: String,
+        embedded: [u8; 32],
+    },
+
+    /// Expected hash is not 64 hexadecimal characters.
+    InvalidExpectedHash(String),
+
+    /// Source file hash differs from the source hash recorded in the .krx file.
+    SourceHashMismatch { recorded: String, computed: String },
+
+    /// The .krx file could not be deserialized to read its metadata.
+    InvalidKrx(DeserializeError),
+
     /// File is too small to contain a valid .krx header.
     FileTooSmall { size: usize, min_size: usize },
 
@@ -37,6 +58,29 @@ impl fmt::Display for HashError {
                     embedded, computed
                 )
             }
+            Self::ExpectedHashMismatch { expected, embedded } => {
+                write!(
+                    f,
+                    "Hash mismatch: expected={}, embedded={}",
+                    expected,
+                    hex::encode(embedded)
+                )
+            }
+            Self::InvalidExpectedHash(value) => {
+                write!(
+                    f,
+                    "Invalid expected hash '{}': must be 64 hexadecimal characters",
+                    value
+                )
+            }
+            Self::SourceHashMismatch { recorded, computed } => {
+                write!(
+                    f,
+                    "Source hash mismatch: recorded={}, computed={}",
+                    recorded, computed
+                )
+            }
+            Self::InvalidKrx(err) => write!(f, "Invalid .krx file: {}", err),
             Self::FileTooSmall { size, min_size } => {
                 write!(
                     f,
@@ -63,17 +107,25 @@ impl From<io::Error> for HashError {
 ///
 /// * `file` - Path to the .krx binary file.
 /// * `verify` - If true, compute the hash and verify it matches the embedded hash.
+/// * `expected` - Hex-encoded hash the embedded hash must equal.
+/// * `source` - Rhai script whose SHA256 must equal the source hash recorded
+///   in the .krx metadata.
 ///
 /// # Returns
 ///
 /// `Ok(())` on success, or `HashError` on failure.
 #[allow(dead_code)] // Will be used when integrated into main.rs in task 17
-pub fn handle_hash(file: &Path, verify: bool) -> Result<(), HashError> {
+pub fn handle_hash(
+    file: &Path,
+    verify: bool,
+    expected: Option<&str>,
+    source: Option<&Path>,
+) -> Result<(), HashError> {
     // Read the .krx file
     let bytes = fs::read(file)?;
 
-    // Minimum size is 48 bytes (header)
-    const MIN_SIZE: usize = 48;
+    // Minimum size is 48 bytes (header of files older than version 3)
+    const MIN_SIZE: usize = crate::serialize::LEGACY_HEADER_SIZE;
     if bytes.len() < MIN_SIZE {
         return Err(HashError::FileTooSmall {
             size: bytes.len(),
@@ -94,8 +146,14 @@ pub fn handle_hash(file: &Path, verify: bool) -> Result<(), HashError> {
 
     // If verify flag is set, compute hash of data section and compare
     if verify {
-        // Data section starts at byte 48
-        let data = &bytes[48..];
+        // Data section starts after the header, whose size depends on the version
+        // SAFETY: Slice bounds checked above: file size >= 48 bytes guarantees [4..8] is valid
+        #[allow(clippy::expect_used)]
+        let format_version =
+            u32::from_le_bytes(bytes[4..8].try_into().expect("Version slice is 4 bytes"));
+        let data = bytes
+            .get(crate::serialize::header_size(format_version)..)
+            .unwrap_or_default();
 
         // Compute SHA256 hash of data section
         let mut hasher = Sha256::new();
@@ -116,9 +174,51 @@ pub fn handle_hash(file: &Path, verify: bool) -> Result<(), HashError> {
         }
     }
 
+    if let Some(expected) = expected {
+        let normalized = expected.trim().to_ascii_lowercase();
+        if normalized.len() != 64 || !normalized.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(HashError::InvalidExpectedHash(expected.to_string()));
+        }
+
+        if normalized == hash_hex {
+            eprintln!("✓ Hash matches expected value");
+        } else {
+            eprintln!("✗ Hash does not match expected value");
+            eprintln!("  Expected:  {}", normalized);
+            eprintln!("  Embedded:  {}", hash_hex);
+            return Err(HashError::ExpectedHashMismatch {
+                expected: normalized,
+                embedded: embedded_hash,
+            });
+        }
+    }
+
+    if let Some(source) = source {
+        verify_source_hash(&bytes, source)?;
+    }
+
     Ok(())
 }
 
+/// Checks that `source` hashes to the source hash recorded in the .krx metadata.
+fn verify_source_hash(bytes: &[u8], source: &Path) -> Result<(), HashError> {
+    let config = deserialize(bytes).map_err(HashError::InvalidKrx)?;
+    let recorded = config.metadata.source_hash.as_str().to_string();
+
+    let script = fs::read(source)?;
+    let computed = hex::encode(Sha256::digest(&script));
+
+    if computed == recorded {
+        eprintln!("✓ Source hash matches {}", source.display());
+        Ok(())
+    } else {
+        eprintln!("✗ Source hash mismatch for {}", source.display());
+        eprintln!("  Recorded:  {}", recorded);
+        eprintln!("  Computed:  {}", computed);
+        Err(HashError::SourceHashMismatch { recorded, computed })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +229,11 @@ mod tests {
 
     /// Helper to create a valid .krx file for testing
     fn create_test_krx_file() -> NamedTempFile {
+        create_test_krx_file_with_source().0
+    }
+
+    /// Helper returning a valid .krx file together with its Rhai source
+    fn create_test_krx_file_with_source() -> (NamedTempFile, NamedTempFile) {
         // Create a simple Rhai script
         let script = r#"
 device_start("Test Device");
@@ -154,20 +259,26 @@ device_end();
         temp_file
             .write_all(&krx_bytes)
             .expect("Failed to write to temp file");
-        temp_file
+        (temp_file, script_file)
+    }
+
+    /// Helper to read the embedded hash of a .krx file as hex
+    fn embedded_hash_hex(path: &Path) -> String {
+        let bytes = fs::read(path).expect("Failed to read file");
+        hex::encode(&bytes[8..40])
     }
 
     #[test]
     fn test_handle_hash_extract_only() {
         let temp_file = create_test_krx_file();
-        let result = handle_hash(temp_file.path(), false);
+        let result = handle_hash(temp_file.path(), false, None, None);
         assert!(result.is_ok(), "Hash extraction should succeed");
     }
 
     #[test]
     fn test_handle_hash_verify_valid() {
         let temp_file = create_test_krx_file();
-        let result = handle_hash(temp_file.path(), true);
+        let result = handle_hash(temp_file.path(), true, None, None);
         assert!(result.is_ok(), "Hash verification should succeed");
     }
 
@@ -179,16 +290,17 @@ device_end();
         let path = temp_file.path().to_path_buf();
         let mut bytes = fs::read(&path).expect("Failed to read file");
 
-        // Corrupt data section (after byte 48)
-        if bytes.len() > 50 {
-            bytes[50] = !bytes[50];
+        // Corrupt data section (after the header)
+        let offset = crate::serialize::HEADER_SIZE + 2;
+        if bytes.len() > offset {
+            bytes[offset] = !bytes[offset];
         }
 
         // Write corrupted data back
         fs::write(&path, bytes).expect("Failed to write corrupted file");
 
         // Verify should fail
-        let result = handle_hash(&path, true);
+        let result = handle_hash(&path, true, None, None);
         assert!(result.is_err(), "Hash verification should fail");
         assert!(
             matches!(result.unwrap_err(), HashError::HashMismatch { .. }),
@@ -196,6 +308,60 @@ device_end();
         );
     }
 
+    #[test]
+    fn test_handle_hash_expected_matches() {
+        let temp_file = create_test_krx_file();
+        let expected = embedded_hash_hex(temp_file.path()).to_ascii_uppercase();
+
+        let result = handle_hash(temp_file.path(), false, Some(&expected), None);
+        assert!(result.is_ok(), "Expected hash should match");
+    }
+
+    #[test]
+    fn test_handle_hash_expected_mismatch() {
+        let temp_file = create_test_krx_file();
+        let expected = "0".repeat(64);
+
+        let result = handle_hash(temp_file.path(), false, Some(&expected), None);
+        assert!(
+            matches!(result, Err(HashError::ExpectedHashMismatch { .. })),
+            "Error should be ExpectedHashMismatch"
+        );
+    }
+
+    #[test]
+    fn test_handle_hash_expected_invalid() {
+        let temp_file = create_test_krx_file();
+
+        let result = handle_hash(temp_file.path(), false, Some("abc123"), None);
+        assert!(
+            matches!(result, Err(HashError::InvalidExpectedHash(_))),
+            "Error should be InvalidExpectedHash"
+        );
+    }
+
+    #[test]
+    fn test_handle_hash_source_matches() {
+        let (temp_file, script_file) = create_test_krx_file_with_source();
+
+        let result = handle_hash(temp_file.path(), false, None, Some(script_file.path()));
+        assert!(result.is_ok(), "Source hash should match");
+    }
+
+    #[test]
+    fn test_handle_hash_source_mismatch() {
+        let (temp_file, mut script_file) = create_test_krx_file_with_source();
+        script_file
+            .write_all(b"// edited after compilation\n")
+            .expect("Failed to edit script");
+
+        let result = handle_hash(temp_file.path(), false, None, Some(script_file.path()));
+        assert!(
+            matches!(result, Err(HashError::SourceHashMismatch { .. })),
+            "Error should be SourceHashMismatch"
+        );
+    }
+
     #[test]
     fn test_handle_hash_file_too_small() {
         let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
@@ -203,7 +369,7 @@ device_end();
             .write_all(&[0u8; 10])
             .expect("Failed to write to temp file");
 
-        let result = handle_hash(temp_file.path(), false);
+        let result = handle_hash(temp_file.path(), false, None, None);
         assert!(result.is_err(), "Should fail on too small file");
         assert!(
             matches!(result.unwrap_err(), HashError::FileTooSmall { .. }),
@@ -213,7 +379,7 @@ device_end();
 
     #[test]
     fn test_handle_hash_missing_file() {
-        let result = handle_hash(Path::new("/nonexistent/file.krx"), false);
+        let result = handle_hash(Path::new("/nonexistent/file.krx"), false, None, None);
         assert!(result.is_err(), "Should fail on missing file");
         assert!(
             matches!(result.unwrap_err(), HashError::IoError(_)),
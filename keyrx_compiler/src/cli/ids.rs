@@ -0,0 +1,35 @@
+//! Ids subcommand handler.
+//!
+//! Handles the `ids` subcommand which lists every modifier, lock and layer
+//! ID a script uses, with the keys that set it and the conditions that test
+//! it.
+
+use std::path::Path;
+
+use crate::cli::parse::ParseCommandError;
+use crate::ids::config_ids;
+use crate::parser::Parser;
+
+/// Handles the ids subcommand.
+///
+/// # Arguments
+///
+/// * `input` - Path to the input .rhai script file.
+/// * `json` - If true, output JSON format; otherwise, output one block per device.
+///
+/// # Returns
+///
+/// `Ok(())` on success, or `ParseCommandError` on failure.
+pub fn handle_ids(input: &Path, json: bool) -> Result<(), ParseCommandError> {
+    let mut parser = Parser::new();
+    let config = parser.parse_script(input)?;
+    let ids = config_ids(&config);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&ids)?);
+    } else {
+        println!("{}", ids);
+    }
+
+    Ok(())
+}
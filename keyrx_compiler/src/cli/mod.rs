@@ -5,19 +5,30 @@
 //! - `verify`: Verify .krx binary file integrity
 //! - `hash`: Extract and verify SHA256 hash from .krx files
 //! - `parse`: Parse Rhai scripts and display configuration structure
+//! - `ids`: List the modifier, lock and layer IDs a script uses
+//! - `watch`: Recompile on source changes (`compile --watch`)
 
 pub mod compile;
 pub mod hash;
+pub mod ids;
 pub mod parse;
 pub mod verify;
 pub mod view;
+pub mod watch;
 
 // Re-export handler functions for easy access
 #[allow(unused_imports)]
 pub use compile::handle_compile;
 #[allow(unused_imports)]
+pub use compile::{
+    compile_krx, compile_wasm_json, handle_compile_wasm_json, handle_compile_with_base,
+    handle_compile_with_deps,
+};
+#[allow(unused_imports)]
 pub use hash::handle_hash;
 #[allow(unused_imports)]
+pub use ids::handle_ids;
+#[allow(unused_imports)]
 pub use parse::handle_parse;
 #[allow(unused_imports)]
 pub use verify::handle_verify;
@@ -97,6 +97,18 @@ fn print_summary(config: &keyrx_core::config::ConfigRoot) {
             device.identifier.pattern,
             device.mappings.len()
         );
+        if !device.identifier.excludes.is_empty() {
+            println!("      Excludes: {}", device.identifier.excludes.join(", "));
+        }
+        if device.priority != 0 {
+            println!("      Priority: {}", device.priority);
+        }
+        if let Some(layer) = device.default_layer {
+            println!("      Default layer: MD_{:02X}", layer);
+        }
+        if let Some(threshold_ms) = device.tap_hold_threshold_ms {
+            println!("      Tap-hold threshold: {}ms", threshold_ms);
+        }
 
         // Show detailed breakdown of mapping types
         let mut simple = 0;
@@ -104,6 +116,11 @@ fn print_summary(config: &keyrx_core::config::ConfigRoot) {
         let mut lock = 0;
         let mut tap_hold = 0;
         let mut modified_output = 0;
+        let mut on_release = 0;
+        let mut layer_toggle = 0;
+        let mut cycle = 0;
+        let mut disable = 0;
+        let mut macros = 0;
         let mut conditional = 0;
 
         for mapping in &device.mappings {
@@ -116,6 +133,11 @@ fn print_summary(config: &keyrx_core::config::ConfigRoot) {
                     keyrx_core::config::BaseKeyMapping::ModifiedOutput { .. } => {
                         modified_output += 1
                     }
+                    keyrx_core::config::BaseKeyMapping::OnRelease { .. } => on_release += 1,
+                    keyrx_core::config::BaseKeyMapping::LayerToggle { .. } => layer_toggle += 1,
+                    keyrx_core::config::BaseKeyMapping::Cycle { .. } => cycle += 1,
+                    keyrx_core::config::BaseKeyMapping::Disable { .. } => disable += 1,
+                    keyrx_core::config::BaseKeyMapping::Macro { .. } => macros += 1,
                 },
                 keyrx_core::config::KeyMapping::Conditional { .. } => conditional += 1,
             }
@@ -137,6 +159,21 @@ fn print_summary(config: &keyrx_core::config::ConfigRoot) {
         if modified_output > 0 {
             details.push(format!("ModifiedOutput: {}", modified_output));
         }
+        if on_release > 0 {
+            details.push(format!("OnRelease: {}", on_release));
+        }
+        if layer_toggle > 0 {
+            details.push(format!("LayerToggle: {}", layer_toggle));
+        }
+        if cycle > 0 {
+            details.push(format!("Cycle: {}", cycle));
+        }
+        if disable > 0 {
+            details.push(format!("Disable: {}", disable));
+        }
+        if macros > 0 {
+            details.push(format!("Macro: {}", macros));
+        }
         if conditional > 0 {
             details.push(format!("Conditional: {}", conditional));
         }
@@ -1,13 +1,28 @@
 //! Verify subcommand handler.
 //!
 //! Handles the `verify` subcommand which validates .krx binary files.
+//!
+//! `verify --deep` also checks what the runtime assumes about a config but
+//! the file format cannot express: IDs within MD_00-MD_FE / LK_00-LK_FE,
+//! time windows that exist, and cycles with at least one output.
 
 use std::fmt;
 use std::io;
 use std::path::Path;
 
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, ConfigRoot, DeviceConfig, KeyMapping,
+};
+use serde::Serialize;
+
 use crate::error::DeserializeError;
 
+/// Highest modifier, lock, layer or time window ID scripts can name.
+const MAX_ID: u8 = 0xFE;
+
+/// Minutes in a day; time window bounds must be below this.
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
 /// Errors that can occur during the verify subcommand.
 #[derive(Debug)]
 pub enum VerifyError {
@@ -16,6 +31,10 @@ pub enum VerifyError {
 
     /// I/O error during file operations.
     IoError(io::Error),
+
+    /// The file decodes but its contents break runtime invariants
+    /// (one message per fault, see [`structural_faults`]).
+    StructuralFaults(Vec<String>),
 }
 
 impl fmt::Display for VerifyError {
@@ -23,6 +42,9 @@ impl fmt::Display for VerifyError {
         match self {
             Self::DeserializeError(err) => write!(f, "Deserialization error: {:?}", err),
             Self::IoError(err) => write!(f, "I/O error: {}", err),
+            Self::StructuralFaults(faults) => {
+                write!(f, "Invalid configuration: {}", faults.join("; "))
+            }
         }
     }
 }
@@ -41,16 +63,181 @@ impl From<DeserializeError> for VerifyError {
     }
 }
 
+/// Summary of a verified .krx file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KrxSummary {
+    /// Config format version (e.g. "1.2.0")
+    pub version: String,
+    pub devices: Vec<DeviceSummary>,
+    /// Mappings across all devices, counting a `when` block as one
+    pub total_mappings: usize,
+    pub metadata: MetadataSummary,
+}
+
+/// One device block of a [`KrxSummary`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceSummary {
+    /// Device pattern (e.g. "*")
+    pub pattern: String,
+    pub mappings: usize,
+}
+
+/// Build metadata embedded in a .krx file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetadataSummary {
+    pub compiler_version: String,
+    pub source_hash: String,
+    pub compilation_timestamp: u64,
+}
+
+impl KrxSummary {
+    pub fn new(config: &ConfigRoot) -> Self {
+        let devices: Vec<DeviceSummary> = config
+            .devices
+            .iter()
+            .map(|d| DeviceSummary {
+                pattern: d.identifier.pattern.clone(),
+                mappings: d.mappings.len(),
+            })
+            .collect();
+        Self {
+            version: config.version.to_string(),
+            total_mappings: devices.iter().map(|d| d.mappings).sum(),
+            devices,
+            metadata: MetadataSummary {
+                compiler_version: config.metadata.compiler_version.clone(),
+                source_hash: config.metadata.source_hash.clone(),
+                compilation_timestamp: config.metadata.compilation_timestamp,
+            },
+        }
+    }
+}
+
+/// Runs the `verify --deep` checks on the bytes of a .krx file.
+///
+/// The header, hash and rkyv archive are validated first, then the decoded
+/// config is checked for [`structural_faults`].
+pub fn verify_deep(bytes: &[u8]) -> Result<KrxSummary, VerifyError> {
+    use rkyv::Deserialize;
+
+    let archived = crate::serialize::deserialize(bytes)?;
+    let config: ConfigRoot = archived
+        .deserialize(&mut rkyv::Infallible)
+        .expect("ConfigRoot deserialization is infallible");
+
+    let faults = structural_faults(&config);
+    if !faults.is_empty() {
+        return Err(VerifyError::StructuralFaults(faults));
+    }
+    Ok(KrxSummary::new(&config))
+}
+
+/// Lists the ways `config` breaks what the runtime assumes, each naming the
+/// device and mapping at fault. Empty if the config is sound.
+pub fn structural_faults(config: &ConfigRoot) -> Vec<String> {
+    let mut faults = Vec::new();
+    for (index, device) in config.devices.iter().enumerate() {
+        let at = format!("device {} ('{}')", index, device.identifier.pattern);
+        device_faults(device, &at, &mut faults);
+    }
+    faults
+}
+
+fn device_faults(device: &DeviceConfig, at: &str, faults: &mut Vec<String>) {
+    if let Some(layer) = device.default_layer {
+        check_id(layer, "MD", &format!("{}: default layer", at), faults);
+    }
+
+    for (index, window) in device.time_windows.iter().enumerate() {
+        if window.start_minute >= MINUTES_PER_DAY || window.end_minute >= MINUTES_PER_DAY {
+            faults.push(format!(
+                "{}: time window TW_{:02X} spans minutes {}-{}, past the end of the day",
+                at, index, window.start_minute, window.end_minute
+            ));
+        }
+    }
+
+    for (index, mapping) in device.mappings.iter().enumerate() {
+        let at = format!("{}, mapping {}", at, index);
+        match mapping {
+            KeyMapping::Base(base) => mapping_faults(base, &at, faults),
+            KeyMapping::Conditional {
+                condition,
+                mappings,
+            } => {
+                condition_faults(condition, device.time_windows.len(), &at, faults);
+                for base in mappings {
+                    mapping_faults(base, &at, faults);
+                }
+            }
+        }
+    }
+}
+
+fn mapping_faults(mapping: &BaseKeyMapping, at: &str, faults: &mut Vec<String>) {
+    let at = format!("{} ({:?})", at, mapping.from_key());
+    match mapping {
+        BaseKeyMapping::Modifier { modifier_id, .. } => check_id(*modifier_id, "MD", &at, faults),
+        BaseKeyMapping::TapHold { hold_modifier, .. } => {
+            check_id(*hold_modifier, "MD", &at, faults)
+        }
+        BaseKeyMapping::LayerToggle { layer_id, .. } => check_id(*layer_id, "MD", &at, faults),
+        BaseKeyMapping::Lock { lock_id, .. } => check_id(*lock_id, "LK", &at, faults),
+        BaseKeyMapping::Cycle { outputs, .. } if outputs.is_empty() => {
+            faults.push(format!("{}: cycle has no outputs", at));
+        }
+        _ => {}
+    }
+}
+
+fn condition_faults(condition: &Condition, windows: usize, at: &str, faults: &mut Vec<String>) {
+    let mut item = |item: &ConditionItem| match item {
+        ConditionItem::ModifierActive(id) => check_id(*id, "MD", at, faults),
+        ConditionItem::LockActive(id) => check_id(*id, "LK", at, faults),
+        ConditionItem::TimeWindowActive(id) => check_window(*id, windows, at, faults),
+    };
+    match condition {
+        Condition::ModifierActive(id) => item(&ConditionItem::ModifierActive(*id)),
+        Condition::LockActive(id) => item(&ConditionItem::LockActive(*id)),
+        Condition::TimeWindowActive(id) => item(&ConditionItem::TimeWindowActive(*id)),
+        Condition::DeviceMatches(_) => {}
+        Condition::AllActive(items) | Condition::NotActive(items) | Condition::AnyActive(items) => {
+            items.iter().for_each(item)
+        }
+        Condition::Composite { all, none } => all.iter().chain(none).for_each(item),
+        Condition::Negate(inner) => condition_faults(inner, windows, at, faults),
+    }
+}
+
+fn check_id(id: u8, prefix: &str, at: &str, faults: &mut Vec<String>) {
+    if id > MAX_ID {
+        faults.push(format!(
+            "{}: {}_{:02X} is out of range ({}_00-{}_{:02X})",
+            at, prefix, id, prefix, prefix, MAX_ID
+        ));
+    }
+}
+
+fn check_window(id: u8, windows: usize, at: &str, faults: &mut Vec<String>) {
+    if id as usize >= windows {
+        faults.push(format!(
+            "{}: TW_{:02X} is not defined ({} time window(s) on this device)",
+            at, id, windows
+        ));
+    }
+}
+
 /// Handles the verify subcommand.
 ///
 /// # Arguments
 ///
 /// * `file` - Path to the .krx binary file to verify.
+/// * `deep` - Also check the decoded config for [`structural_faults`].
 ///
 /// # Returns
 ///
 /// `Ok(())` on success, or `VerifyError` on failure.
-pub fn handle_verify(file: &Path) -> Result<(), VerifyError> {
+pub fn handle_verify(file: &Path, deep: bool) -> Result<(), VerifyError> {
     use crate::serialize::deserialize;
 
     // Read .krx file bytes
@@ -79,6 +266,21 @@ pub fn handle_verify(file: &Path) -> Result<(), VerifyError> {
                 config.metadata.compilation_timestamp
             );
 
+            if deep {
+                match verify_deep(&bytes) {
+                    Ok(_) => eprintln!("✓ IDs, time windows and cycles valid"),
+                    Err(VerifyError::StructuralFaults(faults)) => {
+                        eprintln!("\n✗ Structural faults:");
+                        for fault in &faults {
+                            eprintln!("  - {}", fault);
+                        }
+                        eprintln!("\n✗ Verification failed");
+                        return Err(VerifyError::StructuralFaults(faults));
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+
             eprintln!("\n✓ Verification passed");
             Ok(())
         }
@@ -95,6 +297,21 @@ pub fn handle_verify(file: &Path) -> Result<(), VerifyError> {
                     eprintln!("  Expected: {}", expected);
                     eprintln!("  Got: {}", got);
                 }
+                DeserializeError::OutdatedFormat { got } => {
+                    eprintln!("✗ Outdated .krx format");
+                    eprintln!("  Expected: {}", crate::serialize::KRX_VERSION);
+                    eprintln!("  Got: {}", got);
+                    eprintln!("  Recompile this config with your installed compiler");
+                }
+                DeserializeError::IncompatibleVersion { config, runtime } => {
+                    eprintln!("✗ Unsupported config version");
+                    eprintln!("  Supported: {}.x up to {}", runtime.major, runtime);
+                    eprintln!("  Got: {}", config);
+                }
+                DeserializeError::UnsupportedFeatures { features } => {
+                    eprintln!("✗ Config uses features this build does not support");
+                    eprintln!("  Unsupported: {}", features);
+                }
                 DeserializeError::HashMismatch { expected, computed } => {
                     eprintln!("✗ SHA256 hash mismatch (data corruption)");
                     eprintln!("  Expected: {}", hex::encode(expected));
@@ -128,3 +345,98 @@ pub fn handle_verify(file: &Path) -> Result<(), VerifyError> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::serialize;
+    use keyrx_core::config::test_util::ConfigRootBuilder;
+    use keyrx_core::config::{KeyCode, TimeWindow};
+
+    #[test]
+    fn test_verify_deep_returns_summary() {
+        let config = ConfigRootBuilder::new()
+            .device(DeviceConfig {
+                time_windows: vec![TimeWindow {
+                    start_minute: 9 * 60,
+                    end_minute: 17 * 60,
+                }],
+                ..DeviceConfig::new(
+                    "*",
+                    vec![
+                        KeyMapping::simple(KeyCode::A, KeyCode::B),
+                        KeyMapping::conditional(
+                            Condition::TimeWindowActive(0),
+                            vec![BaseKeyMapping::Simple {
+                                from: KeyCode::C,
+                                to: KeyCode::D,
+                            }],
+                        ),
+                    ],
+                )
+            })
+            .compilation_timestamp(42)
+            .build();
+
+        let summary = verify_deep(&serialize(&config).unwrap()).unwrap();
+        assert_eq!(summary.devices[0].pattern, "*");
+        assert_eq!(summary.devices[0].mappings, 2);
+        assert_eq!(summary.total_mappings, 2);
+        assert_eq!(summary.metadata.compilation_timestamp, 42);
+    }
+
+    #[test]
+    fn test_verify_deep_reports_structural_faults() {
+        let config = ConfigRootBuilder::new()
+            .device(DeviceConfig {
+                time_windows: vec![TimeWindow {
+                    start_minute: 0,
+                    end_minute: 1440,
+                }],
+                ..DeviceConfig::new(
+                    "*",
+                    vec![
+                        KeyMapping::modifier(KeyCode::CapsLock, 0xFF),
+                        KeyMapping::cycle(KeyCode::F14, Vec::new(), 500),
+                        KeyMapping::conditional(
+                            Condition::AllActive(vec![
+                                ConditionItem::LockActive(0xFF),
+                                ConditionItem::TimeWindowActive(1),
+                            ]),
+                            vec![BaseKeyMapping::Simple {
+                                from: KeyCode::H,
+                                to: KeyCode::Left,
+                            }],
+                        ),
+                    ],
+                )
+            })
+            .compilation_timestamp(42)
+            .build();
+
+        let Err(VerifyError::StructuralFaults(faults)) = verify_deep(&serialize(&config).unwrap())
+        else {
+            panic!("expected structural faults");
+        };
+        assert_eq!(
+            faults,
+            vec![
+                "device 0 ('*'): time window TW_00 spans minutes 0-1440, past the end of the day",
+                "device 0 ('*'), mapping 0 (CapsLock): MD_FF is out of range (MD_00-MD_FE)",
+                "device 0 ('*'), mapping 1 (F14): cycle has no outputs",
+                "device 0 ('*'), mapping 2: LK_FF is out of range (LK_00-LK_FE)",
+                "device 0 ('*'), mapping 2: TW_01 is not defined (1 time window(s) on this device)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_deep_rejects_corrupted_bytes() {
+        let mut bytes = serialize(&ConfigRootBuilder::new().build()).unwrap();
+        bytes[0] = 0xFF;
+        assert!(matches!(
+            verify_deep(&bytes),
+            Err(VerifyError::DeserializeError(_))
+        ));
+    }
+}
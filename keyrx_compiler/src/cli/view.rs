@@ -99,11 +99,15 @@ fn get_layer_name(condition: &Condition) -> String {
     match condition {
         Condition::ModifierActive(id) => format!("MD_{:02X}", id),
         Condition::LockActive(id) => format!("LK_{:02X}", id),
+        Condition::TimeWindowActive(id) => format!("TW_{:02X}", id),
         Condition::AllActive(items) => {
             // For complex conditions, just use the first item
             format!("MULTI_{}", items.len())
         }
+        Condition::AnyActive(items) => format!("ANY_{}", items.len()),
         Condition::NotActive(_) => "NOT".to_string(),
+        Condition::Negate(inner) => format!("NOT_{}", get_layer_name(inner)),
+        Condition::Composite { all, none } => format!("MULTI_{}_NOT_{}", all.len(), none.len()),
         Condition::DeviceMatches(pattern) => {
             // Truncate long patterns for display
             if pattern.len() > 15 {
@@ -233,6 +237,7 @@ fn keycode_to_label(keycode: &KeyCode) -> &'static str {
         KeyCode::Yen => "¥",
         KeyCode::Ro => "ろ",
         KeyCode::KatakanaHiragana => "カナ",
+        KeyCode::NumpadJpComma => ",",
         // Korean keys
         KeyCode::Hangeul => "한글",
         KeyCode::Hanja => "한자",
@@ -284,6 +289,18 @@ fn get_base_mapping_info(base: &BaseKeyMapping) -> (KeyCode, String, &'static st
                 "modified",
             )
         }
+        BaseKeyMapping::OnRelease { from, to, .. } => {
+            (*from, format!("↑{}", keycode_to_label(to)), "onrelease")
+        }
+        BaseKeyMapping::LayerToggle { from, layer_id } => {
+            (*from, format!("T{:X}", layer_id), "layertoggle")
+        }
+        BaseKeyMapping::Cycle { from, outputs, .. } => {
+            let first = outputs.first().map(keycode_to_label).unwrap_or("?");
+            (*from, format!("↻{}", first), "cycle")
+        }
+        BaseKeyMapping::Disable { from } => (*from, "∅".to_string(), "disabled"),
+        BaseKeyMapping::Macro { from, .. } => (*from, "▶".to_string(), "macro"),
     }
 }
 
@@ -635,6 +652,11 @@ h1 {{ color: #00d9ff; margin-bottom: 5px; }}
 .key.lock.remapped {{ border-color: #a78bfa; background: rgba(167, 139, 250, 0.15); }}
 .key.taphold.remapped {{ border-color: #ff6b6b; background: rgba(255, 107, 107, 0.15); }}
 .key.modified.remapped {{ border-color: #4ade80; background: rgba(74, 222, 128, 0.15); }}
+.key.onrelease.remapped {{ border-color: #f472b6; background: rgba(244, 114, 182, 0.15); }}
+.key.layertoggle.remapped {{ border-color: #fbbf24; background: rgba(251, 191, 36, 0.15); }}
+.key.cycle.remapped {{ border-color: #a78bfa; background: rgba(167, 139, 250, 0.15); }}
+.key.disabled.remapped {{ border-color: #6b7280; background: rgba(107, 114, 128, 0.15); }}
+.key.macro.remapped {{ border-color: #34d399; background: rgba(52, 211, 153, 0.15); }}
 .key.layer-active {{ border-color: #fbbf24 !important; background: rgba(251, 191, 36, 0.2) !important; }}
 .spacer {{ height: 50px; }}
 
@@ -669,6 +691,7 @@ body.show-code .key::after {{ content: attr(data-id); color: #888; font-size: 9p
     <div class="legend-item"><div class="legend-color" style="background: #a78bfa;"></div> Lock</div>
     <div class="legend-item"><div class="legend-color" style="background: #ff6b6b;"></div> TapHold</div>
     <div class="legend-item"><div class="legend-color" style="background: #4ade80;"></div> Modified</div>
+    <div class="legend-item"><div class="legend-color" style="background: #f472b6;"></div> OnRelease</div>
     <div class="legend-item"><div class="legend-color" style="background: #fbbf24;"></div> Layer Active</div>
     <span style="color: #666; margin-left: 20px;">Bordered = Remapped</span>
 </div>
@@ -0,0 +1,243 @@
+//! `compile --watch`: recompile whenever the input or a file it loads changes.
+//!
+//! The watcher follows the parent directories of the watched files rather
+//! than the files themselves, so editors that save by writing a temporary
+//! file and renaming it over the original are still noticed. Events for other
+//! files in those directories (including the compiler's own output) are
+//! ignored.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::cli::compile::CompileError;
+
+/// How long the watched files must stay quiet before recompiling, so a burst
+/// of saves triggers a single compile.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Compiles once, then again after every change to the input or a file it
+/// loads, until the watcher shuts down.
+///
+/// `compile` must record the files it read in the given list, as
+/// [`compile_krx`](crate::cli::compile::compile_krx) does. Failed compiles
+/// are reported and leave the previous output in place; the loop carries on
+/// watching so the next save can fix them.
+pub fn handle_watch<F>(input: &Path, mut compile: F) -> Result<(), notify::Error>
+where
+    F: FnMut(&mut Vec<PathBuf>) -> Result<(), CompileError>,
+{
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+
+    let mut targets = watch_targets([input]);
+    let mut dirs = HashSet::new();
+
+    loop {
+        let mut dependencies = Vec::new();
+        let compiled = match compile(&mut dependencies) {
+            Ok(()) => {
+                eprintln!("[watch] Compiled {} successfully", input.display());
+                true
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                eprintln!("[watch] Keeping the last successful output");
+                false
+            }
+        };
+
+        targets = next_targets(input, &targets, &dependencies, compiled);
+
+        let wanted: HashSet<PathBuf> = targets
+            .iter()
+            .filter_map(|t| t.parent().map(Path::to_path_buf))
+            .collect();
+        for dir in dirs.difference(&wanted) {
+            let _ = watcher.unwatch(dir);
+        }
+        for dir in wanted.difference(&dirs) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        }
+        dirs = wanted;
+
+        eprintln!(
+            "[watch] Watching {} file(s) for changes (Ctrl+C to stop)...",
+            targets.len()
+        );
+        if !wait_for_change(&rx, &targets, DEBOUNCE) {
+            return Ok(());
+        }
+    }
+}
+
+/// Normalizes `paths` for matching against watcher events.
+pub fn watch_targets<'a, I>(paths: I) -> HashSet<PathBuf>
+where
+    I: IntoIterator<Item = &'a Path>,
+{
+    paths.into_iter().map(normalize).collect()
+}
+
+/// Files to watch after a compile that read `dependencies`.
+///
+/// A failed compile may stop before reaching some imports, so after a failure
+/// the previous set is kept as well. The input is always watched.
+fn next_targets(
+    input: &Path,
+    previous: &HashSet<PathBuf>,
+    dependencies: &[PathBuf],
+    compiled: bool,
+) -> HashSet<PathBuf> {
+    let mut targets = watch_targets(dependencies.iter().map(PathBuf::as_path));
+    targets.insert(normalize(input));
+    if !compiled {
+        targets.extend(previous.iter().cloned());
+    }
+    targets
+}
+
+/// Blocks until an event touches one of `targets`, then until no event has
+/// arrived for `debounce`.
+///
+/// Returns `false` if the watcher went away before anything changed.
+pub fn wait_for_change(
+    rx: &Receiver<notify::Result<Event>>,
+    targets: &HashSet<PathBuf>,
+    debounce: Duration,
+) -> bool {
+    loop {
+        match rx.recv() {
+            Ok(Ok(event)) if touches(&event, targets) => break,
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("[watch] Watcher error: {}", e),
+            Err(_) => return false,
+        }
+    }
+
+    loop {
+        match rx.recv_timeout(debounce) {
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => return true,
+        }
+    }
+}
+
+fn touches(event: &Event, targets: &HashSet<PathBuf>) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|p| targets.contains(&normalize(p)))
+}
+
+/// Canonicalizes the parent directory and keeps the file name, so the path
+/// still resolves while the file itself is briefly missing mid-save.
+fn normalize(path: &Path) -> PathBuf {
+    let (Some(name), parent) = (path.file_name(), path.parent()) else {
+        return path.to_path_buf();
+    };
+    let parent = match parent {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    parent
+        .canonicalize()
+        .map(|dir| dir.join(name))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn event(kind: EventKind, path: &Path) -> notify::Result<Event> {
+        Ok(Event::new(kind).add_path(path.to_path_buf()))
+    }
+
+    #[test]
+    fn test_wait_for_change_ignores_unrelated_files() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("main.rhai");
+        let output = dir.path().join("main.krx");
+        fs::write(&input, "").unwrap();
+        let targets = watch_targets([input.as_path()]);
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(event(EventKind::Modify(ModifyKind::Any), &output))
+            .unwrap();
+        tx.send(event(EventKind::Access(AccessKind::Any), &input))
+            .unwrap();
+        drop(tx);
+
+        assert!(!wait_for_change(&rx, &targets, Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_wait_for_change_debounces_bursts() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("main.rhai");
+        fs::write(&input, "").unwrap();
+        let targets = watch_targets([input.as_path()]);
+
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..5 {
+            tx.send(event(EventKind::Modify(ModifyKind::Any), &input))
+                .unwrap();
+        }
+
+        assert!(wait_for_change(&rx, &targets, Duration::from_millis(10)));
+        // The whole burst was consumed by a single change.
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_wait_for_change_matches_relative_spelling() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("lib");
+        fs::create_dir(&nested).unwrap();
+        let import = nested.join("keys.rhai");
+        fs::write(&import, "").unwrap();
+        let targets = watch_targets([nested.join("..").join("lib/keys.rhai").as_path()]);
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(event(
+            EventKind::Remove(notify::event::RemoveKind::Any),
+            &import,
+        ))
+        .unwrap();
+
+        assert!(wait_for_change(&rx, &targets, Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn test_next_targets_keeps_imports_after_failed_parse() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("main.rhai");
+        let import = dir.path().join("keys.rhai");
+
+        let previous = watch_targets([input.as_path(), import.as_path()]);
+        // A syntax error in the input stops before the import is loaded.
+        let targets = next_targets(&input, &previous, std::slice::from_ref(&input), false);
+
+        assert_eq!(targets, previous);
+    }
+
+    #[test]
+    fn test_next_targets_drops_removed_import() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("main.rhai");
+        let old = dir.path().join("old.rhai");
+        let new = dir.path().join("new.rhai");
+
+        let previous = watch_targets([input.as_path(), old.as_path()]);
+        let targets = next_targets(&input, &previous, &[input.clone(), new.clone()], true);
+
+        assert_eq!(targets, watch_targets([input.as_path(), new.as_path()]));
+    }
+}
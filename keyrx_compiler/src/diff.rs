@@ -0,0 +1,362 @@
+//! Semantic diff between two parsed configurations.
+//!
+//! Mappings are matched by device pattern, activation condition and input key
+//! rather than by source line, so reordering or reformatting a script does not
+//! show up as a change.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, ConfigRoot, KeyMapping, MacroStep, TapHoldPolicy,
+};
+
+/// How a single mapping differs between two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Mapping exists only in the new configuration
+    Added,
+    /// Mapping exists only in the old configuration
+    Removed,
+    /// Same input key and condition, different behavior
+    Changed,
+}
+
+/// One differing mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingChange {
+    /// Device pattern the mapping belongs to
+    pub device: String,
+    /// Activation condition, or `None` for unconditional mappings
+    pub condition: Option<String>,
+    /// Input key (e.g. "CapsLock")
+    pub key: String,
+    pub kind: ChangeKind,
+    /// Behavior in the old configuration
+    pub before: Option<String>,
+    /// Behavior in the new configuration
+    pub after: Option<String>,
+}
+
+impl fmt::Display for MappingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = match self.kind {
+            ChangeKind::Added => '+',
+            ChangeKind::Removed => '-',
+            ChangeKind::Changed => '~',
+        };
+        write!(f, "{} [{}]", marker, self.device)?;
+        if let Some(condition) = &self.condition {
+            write!(f, " when {}", condition)?;
+        }
+        write!(f, " {}: ", self.key)?;
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => write!(f, "{} -> {}", before, after),
+            (Some(only), None) | (None, Some(only)) => write!(f, "{}", only),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// (device, condition, key) -> behavior
+type MappingIndex = BTreeMap<(String, Option<String>, String), String>;
+
+/// Compares the mappings of two configurations.
+///
+/// Changes are sorted by device, condition and key. An empty result means
+/// both configurations behave identically.
+pub fn diff_configs(old: &ConfigRoot, new: &ConfigRoot) -> Vec<MappingChange> {
+    let old_index = index_mappings(old);
+    let new_index = index_mappings(new);
+
+    let mut changes = Vec::new();
+    for ((device, condition, key), before) in &old_index {
+        let change = match new_index.get(&(device.clone(), condition.clone(), key.clone())) {
+            Some(after) if after == before => continue,
+            Some(after) => (
+                ChangeKind::Changed,
+                Some(before.clone()),
+                Some(after.clone()),
+            ),
+            None => (ChangeKind::Removed, Some(before.clone()), None),
+        };
+        changes.push(MappingChange {
+            device: device.clone(),
+            condition: condition.clone(),
+            key: key.clone(),
+            kind: change.0,
+            before: change.1,
+            after: change.2,
+        });
+    }
+    for ((device, condition, key), after) in &new_index {
+        if !old_index.contains_key(&(device.clone(), condition.clone(), key.clone())) {
+            changes.push(MappingChange {
+                device: device.clone(),
+                condition: condition.clone(),
+                key: key.clone(),
+                kind: ChangeKind::Added,
+                before: None,
+                after: Some(after.clone()),
+            });
+        }
+    }
+
+    changes
+        .sort_by(|a, b| (&a.device, &a.condition, &a.key).cmp(&(&b.device, &b.condition, &b.key)));
+    changes
+}
+
+fn index_mappings(config: &ConfigRoot) -> MappingIndex {
+    let mut index = MappingIndex::new();
+    for device in &config.devices {
+        let pattern = &device.identifier.pattern;
+        for mapping in &device.mappings {
+            match mapping {
+                KeyMapping::Base(base) => {
+                    let (key, behavior) = describe_mapping(base);
+                    index.insert((pattern.clone(), None, key), behavior);
+                }
+                KeyMapping::Conditional {
+                    condition,
+                    mappings,
+                } => {
+                    let condition = describe_condition(condition);
+                    for base in mappings {
+                        let (key, behavior) = describe_mapping(base);
+                        index.insert((pattern.clone(), Some(condition.clone()), key), behavior);
+                    }
+                }
+            }
+        }
+    }
+    index
+}
+
+/// Returns the input key and a short description of what the mapping does.
+pub fn describe_mapping(mapping: &BaseKeyMapping) -> (String, String) {
+    match mapping {
+        BaseKeyMapping::Simple { from, to } => (format!("{:?}", from), format!("{:?}", to)),
+        BaseKeyMapping::Modifier { from, modifier_id } => {
+            (format!("{:?}", from), format!("MD_{:02X}", modifier_id))
+        }
+        BaseKeyMapping::Lock { from, lock_id } => {
+            (format!("{:?}", from), format!("LK_{:02X}", lock_id))
+        }
+        BaseKeyMapping::TapHold {
+            from,
+            tap,
+            hold_modifier,
+            threshold_ms,
+            policy,
+        } => {
+            let policy = match policy {
+                TapHoldPolicy::Permissive => "",
+                TapHoldPolicy::Timeout => ", timeout",
+            };
+            (
+                format!("{:?}", from),
+                format!(
+                    "tap {:?} / hold MD_{:02X} ({}ms{})",
+                    tap, hold_modifier, threshold_ms, policy
+                ),
+            )
+        }
+        BaseKeyMapping::ModifiedOutput {
+            from,
+            to,
+            shift,
+            ctrl,
+            alt,
+            win,
+        } => {
+            let mut parts: Vec<String> = [
+                (*shift, "Shift"),
+                (*ctrl, "Ctrl"),
+                (*alt, "Alt"),
+                (*win, "Win"),
+            ]
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, name)| name.to_string())
+            .collect();
+            parts.push(format!("{:?}", to));
+            (format!("{:?}", from), parts.join("+"))
+        }
+        BaseKeyMapping::OnRelease {
+            from,
+            to,
+            threshold_ms,
+        } => (
+            format!("{:?}", from),
+            format!("{:?} on release ({}ms)", to, threshold_ms),
+        ),
+        BaseKeyMapping::LayerToggle { from, layer_id } => {
+            (format!("{:?}", from), format!("toggle MD_{:02X}", layer_id))
+        }
+        BaseKeyMapping::Cycle {
+            from,
+            outputs,
+            reset_ms,
+        } => {
+            let outputs: Vec<String> = outputs.iter().map(|key| format!("{:?}", key)).collect();
+            (
+                format!("{:?}", from),
+                format!("cycle {} ({}ms)", outputs.join(", "), reset_ms),
+            )
+        }
+        BaseKeyMapping::Disable { from } => (format!("{:?}", from), "disabled".to_string()),
+        BaseKeyMapping::Macro { from, steps } => {
+            let steps: Vec<String> = steps
+                .iter()
+                .map(|step| match step {
+                    MacroStep::Press(key) => format!("press {:?}", key),
+                    MacroStep::Release(key) => format!("release {:?}", key),
+                    MacroStep::Wait(ms) => format!("wait {}ms", ms),
+                })
+                .collect();
+            (format!("{:?}", from), format!("macro {}", steps.join(", ")))
+        }
+    }
+}
+
+/// Returns a short description of a condition, e.g. `MD_00 & !(LK_01)`.
+pub fn describe_condition(condition: &Condition) -> String {
+    match condition {
+        Condition::ModifierActive(id) => format!("MD_{:02X}", id),
+        Condition::LockActive(id) => format!("LK_{:02X}", id),
+        Condition::TimeWindowActive(id) => format!("TW_{:02X}", id),
+        Condition::AllActive(items) => describe_items(items, " & "),
+        Condition::AnyActive(items) => describe_items(items, " | "),
+        Condition::NotActive(items) => format!("!({})", describe_items(items, " & ")),
+        Condition::DeviceMatches(pattern) => format!("device({})", pattern),
+        Condition::Negate(inner) => format!("!({})", describe_condition(inner)),
+        Condition::Composite { all, none } => {
+            let mut parts = Vec::new();
+            if !all.is_empty() {
+                parts.push(describe_items(all, " & "));
+            }
+            if !none.is_empty() {
+                parts.push(format!("!({})", describe_items(none, " | ")));
+            }
+            parts.join(" & ")
+        }
+    }
+}
+
+fn describe_items(items: &[ConditionItem], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| match item {
+            ConditionItem::ModifierActive(id) => format!("MD_{:02X}", id),
+            ConditionItem::LockActive(id) => format!("LK_{:02X}", id),
+            ConditionItem::TimeWindowActive(id) => format!("TW_{:02X}", id),
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::test_util::ConfigRootBuilder;
+    use keyrx_core::config::KeyCode;
+
+    #[test]
+    fn test_diff_identical_ignores_order() {
+        let a = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::modifier(KeyCode::CapsLock, 0),
+            ])
+            .build();
+        let b = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::modifier(KeyCode::CapsLock, 0),
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+            ])
+            .build();
+        assert!(diff_configs(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_diff_added_removed_changed() {
+        let old = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::simple(KeyCode::C, KeyCode::D),
+            ])
+            .build();
+        let new = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::Z),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(1),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                ),
+            ])
+            .build();
+
+        let changes = diff_configs(&old, &new);
+        assert_eq!(changes.len(), 3);
+
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+        assert_eq!(changes[0].to_string(), "~ [*] A: B -> Z");
+        assert_eq!(changes[1].kind, ChangeKind::Removed);
+        assert_eq!(changes[1].to_string(), "- [*] C: D");
+        assert_eq!(changes[2].kind, ChangeKind::Added);
+        assert_eq!(changes[2].to_string(), "+ [*] when MD_01 H: Left");
+    }
+
+    #[test]
+    fn test_describe_any_active_keeps_or_form() {
+        let condition = Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::LockActive(1),
+        ]);
+        assert_eq!(describe_condition(&condition), "MD_00 | LK_01");
+    }
+
+    #[test]
+    fn test_describe_negate_wraps_sub_condition() {
+        let condition = Condition::Negate(Box::new(Condition::AllActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::LockActive(1),
+        ])));
+        assert_eq!(describe_condition(&condition), "!(MD_00 & LK_01)");
+    }
+
+    #[test]
+    fn test_describe_modified_output_and_tap_hold() {
+        let (_, modified) = describe_mapping(&BaseKeyMapping::ModifiedOutput {
+            from: KeyCode::A,
+            to: KeyCode::Num1,
+            shift: true,
+            ctrl: true,
+            alt: false,
+            win: false,
+        });
+        assert_eq!(modified, "Shift+Ctrl+Num1");
+
+        let (_, tap_hold) = describe_mapping(&BaseKeyMapping::TapHold {
+            from: KeyCode::Space,
+            tap: KeyCode::Space,
+            hold_modifier: 0,
+            threshold_ms: 200,
+            policy: TapHoldPolicy::Permissive,
+        });
+        assert_eq!(tap_hold, "tap Space / hold MD_00 (200ms)");
+
+        let (_, timeout) = describe_mapping(&BaseKeyMapping::TapHold {
+            from: KeyCode::Space,
+            tap: KeyCode::Space,
+            hold_modifier: 0,
+            threshold_ms: 200,
+            policy: TapHoldPolicy::Timeout,
+        });
+        assert_eq!(timeout, "tap Space / hold MD_00 (200ms, timeout)");
+    }
+}
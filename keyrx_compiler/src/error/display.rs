@@ -1,7 +1,10 @@
 use crate::error::formatting::hex_encode;
-use crate::error::types::{DeserializeError, ParseError, SerializeError};
+use crate::error::types::{
+    DeserializeError, ParseError, ParseWarning, SerializeError, WarningKind,
+};
 use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -103,12 +106,72 @@ impl fmt::Display for ParseError {
                 limit_type,
                 import_chain: _,
             } => write!(f, "Resource limit exceeded: {}", limit_type),
+
+            ParseError::LimitExceeded {
+                kind,
+                limit,
+                import_chain: _,
+            } => write!(
+                f,
+                "Parser limit exceeded: {} ({})",
+                kind.describe(*limit),
+                kind.field()
+            ),
+
+            ParseError::OutputBudgetExceeded {
+                device,
+                mapping,
+                events,
+                capacity,
+            } => write!(
+                f,
+                "Mapping {} on device '{}' emits up to {} events per input, but the runtime output buffer holds {}",
+                mapping, device, events, capacity
+            ),
         }
     }
 }
 
 impl Error for ParseError {}
 
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: Warning: {} [{}]",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.message,
+            self.kind
+        )
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for WarningKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        WarningKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = WarningKind::ALL.iter().map(|kind| kind.name()).collect();
+                format!(
+                    "unknown warning '{}' (expected one of: {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
 impl fmt::Display for SerializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -139,6 +202,24 @@ impl fmt::Display for DeserializeError {
                 write!(f, "Version mismatch: expected {}, got {}", expected, got)
             }
 
+            DeserializeError::OutdatedFormat { got } => write!(
+                f,
+                ".krx format version {} is from an older keyrx (this build reads version {}); recompile this config with your installed compiler",
+                got,
+                crate::serialize::KRX_VERSION
+            ),
+
+            DeserializeError::IncompatibleVersion { config, runtime } => {
+                write!(f, "{}", config.incompatibility_message(*runtime))
+            }
+
+            DeserializeError::UnsupportedFeatures { features } => write!(
+                f,
+                "this config uses {}, unsupported by keyrx v{}; upgrade keyrx to load it",
+                features,
+                env!("CARGO_PKG_VERSION")
+            ),
+
             DeserializeError::HashMismatch { expected, computed } => {
                 write!(
                     f,
@@ -5,7 +5,7 @@
 
 #![allow(dead_code)] // Functions will be used in CLI integration
 
-use crate::error::types::{ImportStep, ParseError};
+use crate::error::types::{ImportStep, LimitKind, ParseError};
 use colored::*;
 use std::path::Path;
 
@@ -126,6 +126,17 @@ pub fn format_error(error: &ParseError, _file: &Path, source: &str) -> String {
             limit_type,
             import_chain,
         } => format_resource_limit_error(limit_type, import_chain),
+        ParseError::LimitExceeded {
+            kind,
+            limit,
+            import_chain,
+        } => format_limit_exceeded_error(*kind, *limit, import_chain),
+        ParseError::OutputBudgetExceeded {
+            device,
+            mapping,
+            events,
+            capacity,
+        } => format_output_budget_error(device, mapping, *events, *capacity),
     }
 }
 
@@ -441,6 +452,55 @@ fn format_resource_limit_error(limit_type: &str, import_chain: &[ImportStep]) ->
     output
 }
 
+/// Formats a LimitExceeded error.
+fn format_limit_exceeded_error(kind: LimitKind, limit: u64, import_chain: &[ImportStep]) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format_import_chain(import_chain));
+
+    output.push_str(&format!(
+        "{} Parser limit exceeded: {}\n",
+        "Error:".red().bold(),
+        kind.describe(limit).yellow()
+    ));
+    output.push_str(&format!(
+        "\n{} Simplify the script, or raise the parser's `{}` limit if the\n",
+        "help:".green().bold(),
+        kind.field()
+    ));
+    output.push_str("      configuration is trusted and really this large.\n");
+
+    output
+}
+
+/// Formats an OutputBudgetExceeded error.
+fn format_output_budget_error(
+    device: &str,
+    mapping: &str,
+    events: usize,
+    capacity: usize,
+) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{} Mapping emits too many events per input on device '{}':\n",
+        "Error:".red().bold(),
+        device
+    ));
+    output.push_str(&format!("  {}\n", mapping.yellow()));
+    output.push_str(&format!(
+        "\n  It emits up to {} events, but the runtime output buffer holds {}.\n",
+        events, capacity
+    ));
+    output.push_str(&format!(
+        "\n{} Extra events would be dropped at runtime. Reduce the number of\n",
+        "help:".green().bold()
+    ));
+    output.push_str("      modifiers or keys this mapping emits.\n");
+
+    output
+}
+
 /// Formats a ParseError in a user-friendly format with code snippets and suggestions.
 ///
 /// This is a legacy function kept for backwards compatibility.
@@ -546,6 +606,30 @@ pub fn format_error_user_friendly(error: &ParseError) -> String {
                 limit_type
             )
         }
+        ParseError::LimitExceeded {
+            kind,
+            limit,
+            import_chain: _,
+        } => {
+            format!(
+                "Parser limit exceeded: {}\n\n\
+                 Help: Simplify the script, or raise the parser's `{}` limit if the configuration is trusted.",
+                kind.describe(*limit),
+                kind.field()
+            )
+        }
+        ParseError::OutputBudgetExceeded {
+            device,
+            mapping,
+            events,
+            capacity,
+        } => {
+            format!(
+                "Mapping {} on device '{}' emits up to {} events per input, but the runtime output buffer holds {}\n\n\
+                 Help: Reduce the number of modifiers or keys this mapping emits.",
+                mapping, device, events, capacity
+            )
+        }
     }
 }
 
@@ -752,6 +836,39 @@ fn format_remaining_error_json(error: &ParseError) -> String {
             })
             .to_string()
         }
+        ParseError::LimitExceeded {
+            kind,
+            limit,
+            import_chain: _,
+        } => {
+            serde_json::json!({
+                "error_code": "E011",
+                "error_type": "LimitExceeded",
+                "message": format!("Parser limit exceeded: {}", kind.describe(*limit)),
+                "limit": limit,
+                "kind": kind.field(),
+                "suggestion": format!("Simplify your script or raise {}", kind.field())
+            })
+            .to_string()
+        }
+        ParseError::OutputBudgetExceeded {
+            device,
+            mapping,
+            events,
+            capacity,
+        } => {
+            serde_json::json!({
+                "error_code": "E010",
+                "error_type": "OutputBudgetExceeded",
+                "message": format!("Mapping {} emits up to {} events per input, but the runtime output buffer holds {}", mapping, events, capacity),
+                "device": device,
+                "mapping": mapping,
+                "events": events,
+                "capacity": capacity,
+                "suggestion": "Reduce the number of modifiers or keys this mapping emits"
+            })
+            .to_string()
+        }
         _ => unreachable!(),
     }
 }
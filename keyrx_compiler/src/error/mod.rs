@@ -5,4 +5,6 @@ pub mod types;
 #[allow(unused_imports)] // Will be used in CLI integration
 pub use formatting::format_error;
 #[allow(unused_imports)] // ImportStep is used in formatting module internally
-pub use types::{DeserializeError, ImportStep, ParseError, SerializeError};
+pub use types::{
+    DeserializeError, ImportStep, LimitKind, ParseError, ParseWarning, SerializeError, WarningKind,
+};
@@ -1,3 +1,4 @@
+use keyrx_core::config::{Features, Version};
 use std::path::PathBuf;
 
 /// Represents a single step in the import chain.
@@ -79,6 +80,117 @@ pub enum ParseError {
         /// Import chain leading to this error (empty if error is in main file)
         import_chain: Vec<ImportStep>,
     },
+
+    /// The script exceeded one of the parser's resource limits.
+    LimitExceeded {
+        kind: LimitKind,
+        /// Value of the limit that was exceeded
+        limit: u64,
+        /// Import chain leading to this error (empty if error is in main file)
+        import_chain: Vec<ImportStep>,
+    },
+
+    /// A mapping emits more events per input than the runtime buffer holds.
+    OutputBudgetExceeded {
+        /// Pattern of the device the mapping belongs to
+        device: String,
+        /// The offending mapping
+        mapping: String,
+        /// Most events the mapping emits for one input event
+        events: usize,
+        /// Capacity of the runtime output buffer
+        capacity: usize,
+    },
+}
+
+/// Resource limit of the Rhai engine reported by [`ParseError::LimitExceeded`].
+///
+/// Each kind matches a field of
+/// [`ParserLimits`](crate::parser::ParserLimits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// Total operations the script may run
+    Operations,
+    /// Nesting depth of a single expression
+    ExpressionDepth,
+    /// Nesting depth of function calls
+    CallDepth,
+}
+
+impl LimitKind {
+    /// Name of the [`ParserLimits`](crate::parser::ParserLimits) field
+    /// holding this limit.
+    pub fn field(self) -> &'static str {
+        match self {
+            LimitKind::Operations => "max_operations",
+            LimitKind::ExpressionDepth => "max_expr_depth",
+            LimitKind::CallDepth => "max_call_levels",
+        }
+    }
+
+    /// Describes what went over `limit`, e.g. "more than 10000 operations".
+    pub fn describe(self, limit: u64) -> String {
+        match self {
+            LimitKind::Operations => format!("the script ran more than {} operations", limit),
+            LimitKind::ExpressionDepth => {
+                format!("an expression is nested more than {} levels deep", limit)
+            }
+            LimitKind::CallDepth => {
+                format!("function calls are nested more than {} levels deep", limit)
+            }
+        }
+    }
+}
+
+/// Non-fatal diagnostic reported while parsing a Rhai script.
+///
+/// Warnings never prevent compilation unless `compile --strict` promotes
+/// them; they point at configuration that is valid but almost certainly not
+/// doing what the author intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWarning {
+    pub kind: WarningKind,
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// Category of a [`ParseWarning`].
+///
+/// Each kind has a stable name (see [`WarningKind::name`]) used by
+/// `compile --allow <name>` and shown after every warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A conditional block's modifier or lock is never activated on its
+    /// device, so the block never fires (or is always active when negated).
+    UnreachableCondition,
+
+    /// A mapping emits a physical modifier key that the same device remaps
+    /// to a custom modifier, which the emitted key does not activate.
+    ModifierOutput,
+
+    /// Keys on a device remap into each other through a chain that involves
+    /// a conditional or non-simple mapping, so the chain could loop.
+    RemapLoop,
+}
+
+impl WarningKind {
+    /// Every warning kind, in the order they are documented.
+    pub const ALL: [WarningKind; 3] = [
+        WarningKind::UnreachableCondition,
+        WarningKind::ModifierOutput,
+        WarningKind::RemapLoop,
+    ];
+
+    /// The kebab-case name used on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            WarningKind::UnreachableCondition => "unreachable-condition",
+            WarningKind::ModifierOutput => "modifier-output",
+            WarningKind::RemapLoop => "remap-loop",
+        }
+    }
 }
 
 /// Errors that can occur during serialization.
@@ -102,6 +214,16 @@ pub enum DeserializeError {
     /// Version mismatch.
     VersionMismatch { expected: u32, got: u32 },
 
+    /// The file was written by an older compiler in a format whose layout
+    /// this build cannot read; it must be recompiled.
+    OutdatedFormat { got: u32 },
+
+    /// The config schema version is not supported by this build.
+    IncompatibleVersion { config: Version, runtime: Version },
+
+    /// The config uses runtime features this build does not have.
+    UnsupportedFeatures { features: Features },
+
     /// Hash mismatch (data corruption detected).
     HashMismatch {
         expected: [u8; 32],
@@ -0,0 +1,305 @@
+//! Overview of the modifier, lock and layer IDs a configuration uses.
+//!
+//! For every ID this lists the keys that turn it on and the `when`
+//! conditions that test it, so "which key is MD_03?" can be answered without
+//! reading the script. Like [`crate::stats`], everything is computed from the
+//! [`ConfigRoot`] alone.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, ConfigRoot, DeviceConfig, KeyMapping, MappingKind,
+};
+use serde::Serialize;
+
+use crate::diff::describe_condition;
+
+/// Kind of state an ID names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdKind {
+    /// `MD_XX`, set by modifier, tap-hold and layer-toggle mappings
+    Modifier,
+    /// `LK_XX`, toggled by lock mappings
+    Lock,
+}
+
+impl IdKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            IdKind::Modifier => "MD",
+            IdKind::Lock => "LK",
+        }
+    }
+}
+
+/// A mapping that turns an ID on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IdSetter {
+    /// Input key (e.g. "CapsLock")
+    pub key: String,
+    /// How the key sets the ID: modifier, lock, tap_hold or layer_toggle
+    pub mapping: MappingKind,
+    /// Condition of the `when` block the mapping is in, if any
+    pub condition: Option<String>,
+}
+
+/// One ID used on a device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeclaredId {
+    /// Device pattern (e.g. "*")
+    pub device: String,
+    pub kind: IdKind,
+    pub id: u8,
+    /// Name as written in scripts (e.g. "MD_03")
+    pub name: String,
+    /// Mappings that turn the ID on; empty if only conditions test it
+    pub set_by: Vec<IdSetter>,
+    /// Conditions of the `when` blocks that test the ID
+    pub referenced_by: Vec<String>,
+    /// The device starts with this layer on (`default_layer`)
+    pub default_layer: bool,
+}
+
+/// Every ID a configuration uses, grouped by device.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigIds {
+    /// Sorted by device (in config order), then kind, then ID
+    pub ids: Vec<DeclaredId>,
+}
+
+/// Lists the IDs each device sets or tests.
+pub fn config_ids(config: &ConfigRoot) -> ConfigIds {
+    let mut ids = Vec::new();
+    for device in &config.devices {
+        ids.extend(device_ids(device));
+    }
+    ConfigIds { ids }
+}
+
+fn device_ids(device: &DeviceConfig) -> Vec<DeclaredId> {
+    let mut by_id: BTreeMap<(IdKind, u8), DeclaredId> = BTreeMap::new();
+
+    for mapping in &device.mappings {
+        let (condition, mappings) = match mapping {
+            KeyMapping::Base(base) => (None, std::slice::from_ref(base)),
+            KeyMapping::Conditional {
+                condition,
+                mappings,
+            } => (Some(condition), mappings.as_slice()),
+        };
+        let description = condition.map(describe_condition);
+
+        if let (Some(condition), Some(description)) = (condition, &description) {
+            for (kind, id) in tested_ids(condition) {
+                let declared = entry(&mut by_id, device, kind, id);
+                if !declared.referenced_by.contains(description) {
+                    declared.referenced_by.push(description.clone());
+                }
+            }
+        }
+
+        for base in mappings {
+            let Some((kind, id)) = set_id(base) else {
+                continue;
+            };
+            entry(&mut by_id, device, kind, id).set_by.push(IdSetter {
+                key: format!("{:?}", base.from_key()),
+                mapping: base.kind(),
+                condition: description.clone(),
+            });
+        }
+    }
+
+    if let Some(layer) = device.default_layer {
+        entry(&mut by_id, device, IdKind::Modifier, layer).default_layer = true;
+    }
+
+    by_id.into_values().collect()
+}
+
+/// Returns the entry for an ID of `device`, adding an empty one if needed.
+fn entry<'a>(
+    by_id: &'a mut BTreeMap<(IdKind, u8), DeclaredId>,
+    device: &DeviceConfig,
+    kind: IdKind,
+    id: u8,
+) -> &'a mut DeclaredId {
+    by_id.entry((kind, id)).or_insert_with(|| DeclaredId {
+        device: device.identifier.pattern.clone(),
+        kind,
+        id,
+        name: format!("{}_{:02X}", kind.prefix(), id),
+        set_by: Vec::new(),
+        referenced_by: Vec::new(),
+        default_layer: false,
+    })
+}
+
+/// Returns the ID `mapping` turns on, if any.
+fn set_id(mapping: &BaseKeyMapping) -> Option<(IdKind, u8)> {
+    match mapping {
+        BaseKeyMapping::Modifier { modifier_id, .. } => Some((IdKind::Modifier, *modifier_id)),
+        BaseKeyMapping::TapHold { hold_modifier, .. } => Some((IdKind::Modifier, *hold_modifier)),
+        BaseKeyMapping::LayerToggle { layer_id, .. } => Some((IdKind::Modifier, *layer_id)),
+        BaseKeyMapping::Lock { lock_id, .. } => Some((IdKind::Lock, *lock_id)),
+        _ => None,
+    }
+}
+
+/// Returns the modifier and lock IDs `condition` tests, descending into
+/// negations. Time windows and device matches are left out.
+fn tested_ids(condition: &Condition) -> Vec<(IdKind, u8)> {
+    let item = |item: &ConditionItem| match item {
+        ConditionItem::ModifierActive(id) => Some((IdKind::Modifier, *id)),
+        ConditionItem::LockActive(id) => Some((IdKind::Lock, *id)),
+        ConditionItem::TimeWindowActive(_) => None,
+    };
+    match condition {
+        Condition::ModifierActive(id) => vec![(IdKind::Modifier, *id)],
+        Condition::LockActive(id) => vec![(IdKind::Lock, *id)],
+        Condition::TimeWindowActive(_) | Condition::DeviceMatches(_) => Vec::new(),
+        Condition::AllActive(items) | Condition::NotActive(items) | Condition::AnyActive(items) => {
+            items.iter().filter_map(item).collect()
+        }
+        Condition::Composite { all, none } => all.iter().chain(none).filter_map(item).collect(),
+        Condition::Negate(inner) => tested_ids(inner),
+    }
+}
+
+impl fmt::Display for ConfigIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ids.is_empty() {
+            return write!(f, "No modifier, lock or layer IDs used");
+        }
+
+        let mut device: Option<&str> = None;
+        for (index, declared) in self.ids.iter().enumerate() {
+            if device != Some(declared.device.as_str()) {
+                if index > 0 {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[{}]", declared.device)?;
+                device = Some(&declared.device);
+            }
+
+            write!(f, "  {}", declared.name)?;
+            if declared.default_layer {
+                write!(f, " (default layer)")?;
+            }
+            writeln!(f)?;
+
+            if declared.set_by.is_empty() {
+                writeln!(f, "    set by: nothing")?;
+            }
+            for setter in &declared.set_by {
+                write!(
+                    f,
+                    "    set by: {} ({})",
+                    setter.key,
+                    setter.mapping.as_str()
+                )?;
+                if let Some(condition) = &setter.condition {
+                    write!(f, " when {}", condition)?;
+                }
+                writeln!(f)?;
+            }
+            for condition in &declared.referenced_by {
+                writeln!(f, "    tested by: when {}", condition)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::test_util::ConfigRootBuilder;
+    use keyrx_core::config::{KeyCode, TapHoldPolicy};
+
+    #[test]
+    fn test_lists_setters_and_conditions() {
+        let config = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::modifier(KeyCode::CapsLock, 0x01),
+                KeyMapping::lock(KeyCode::ScrollLock, 0x02),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x01),
+                    vec![
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::H,
+                            to: KeyCode::Left,
+                        },
+                        BaseKeyMapping::TapHold {
+                            from: KeyCode::Space,
+                            tap: KeyCode::Space,
+                            hold_modifier: 0x03,
+                            threshold_ms: 200,
+                            policy: TapHoldPolicy::Permissive,
+                        },
+                    ],
+                ),
+                KeyMapping::conditional(
+                    Condition::AllActive(vec![
+                        ConditionItem::ModifierActive(0x01),
+                        ConditionItem::LockActive(0x02),
+                    ]),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::J,
+                        to: KeyCode::Down,
+                    }],
+                ),
+            ])
+            .build();
+
+        let ids = config_ids(&config).ids;
+        let names: Vec<&str> = ids.iter().map(|id| id.name.as_str()).collect();
+        assert_eq!(names, vec!["MD_01", "MD_03", "LK_02"]);
+
+        assert_eq!(ids[0].set_by.len(), 1);
+        assert_eq!(ids[0].set_by[0].key, "CapsLock");
+        assert_eq!(ids[0].set_by[0].mapping, MappingKind::Modifier);
+        assert_eq!(ids[0].referenced_by, vec!["MD_01", "MD_01 & LK_02"]);
+
+        assert_eq!(ids[1].set_by[0].mapping, MappingKind::TapHold);
+        assert_eq!(ids[1].set_by[0].condition.as_deref(), Some("MD_01"));
+        assert!(ids[1].referenced_by.is_empty());
+
+        assert_eq!(ids[2].kind, IdKind::Lock);
+        assert_eq!(ids[2].referenced_by, vec!["MD_01 & LK_02"]);
+    }
+
+    #[test]
+    fn test_reports_tested_but_unset_ids_and_default_layer() {
+        let config = ConfigRootBuilder::new()
+            .device(DeviceConfig {
+                default_layer: Some(0x05),
+                ..DeviceConfig::new(
+                    "*",
+                    vec![
+                        KeyMapping::layer_toggle(KeyCode::F1, 0x05),
+                        KeyMapping::conditional(
+                            Condition::ModifierActive(0x07),
+                            vec![BaseKeyMapping::Simple {
+                                from: KeyCode::A,
+                                to: KeyCode::B,
+                            }],
+                        ),
+                    ],
+                )
+            })
+            .build();
+
+        let ids = config_ids(&config);
+        assert!(ids.ids[0].default_layer);
+        assert_eq!(ids.ids[0].set_by[0].mapping, MappingKind::LayerToggle);
+        assert!(ids.ids[1].set_by.is_empty());
+
+        let text = ids.to_string();
+        assert!(text.contains("MD_05 (default layer)"));
+        assert!(text.contains("set by: F1 (layer_toggle)"));
+        assert!(text.contains("MD_07\n    set by: nothing\n    tested by: when MD_07"));
+    }
+}
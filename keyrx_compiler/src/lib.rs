@@ -6,10 +6,13 @@
 use std::path::Path;
 
 pub mod cli;
+pub mod diff;
 pub mod error;
+pub mod ids;
 pub mod import_resolver;
 pub mod parser;
 pub mod serialize;
+pub mod stats;
 
 // Re-export common types
 pub use cli::compile::CompileError;
@@ -2,17 +2,21 @@
 //!
 //! This binary compiles Rhai DSL configuration scripts into static .krx binary files.
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::env;
 use std::path::PathBuf;
 use std::process;
 
 mod cli;
 mod dfa_gen;
+#[allow(dead_code)] // The binary only uses describe_condition (for `ids`)
+mod diff;
 mod error;
+mod ids;
 mod import_resolver;
 mod parser;
 mod serialize;
+mod stats;
 
 #[derive(Parser)]
 #[command(name = "keyrx_compiler")]
@@ -32,15 +36,54 @@ enum Commands {
         /// Input Rhai configuration file
         input: PathBuf,
 
-        /// Output .krx binary file (defaults to input file with .krx extension)
+        /// Output file (defaults to input file with .krx, or .json for
+        /// --target wasm-json)
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = CompileTarget::Krx)]
+        target: CompileTarget,
+
+        /// Write a Makefile-style dependency rule listing the input and every
+        /// file it loads
+        #[arg(long, value_name = "FILE")]
+        emit_deps: Option<PathBuf>,
+
+        /// Base Rhai script to overlay the input on; the input's mappings win
+        /// for the same key and its new device patterns are appended
+        #[arg(long, value_name = "FILE")]
+        base: Option<PathBuf>,
+
+        /// Print a summary of the compiled configuration: devices, mappings
+        /// per type, modifier/lock IDs and an estimated .krx size breakdown
+        #[arg(long)]
+        emit_stats: bool,
+
+        /// Treat every warning as an error
+        #[arg(long)]
+        strict: bool,
+
+        /// Keep warnings of this kind as warnings under --strict
+        /// (unreachable-condition, modifier-output, remap-loop); may be
+        /// repeated
+        #[arg(long, value_name = "WARNING")]
+        allow: Vec<error::WarningKind>,
+
+        /// Keep running and recompile whenever the input or a file it loads
+        /// changes; a failed compile leaves the last good output in place
+        #[arg(long)]
+        watch: bool,
     },
 
     /// Verify a .krx binary file
     Verify {
         /// .krx binary file to verify
         file: PathBuf,
+
+        /// Also check IDs, time windows and cycles in the decoded config
+        #[arg(long)]
+        deep: bool,
     },
 
     /// Extract and display the SHA256 hash from a .krx file
@@ -51,6 +94,15 @@ enum Commands {
         /// Verify hash matches computed hash of data section
         #[arg(long)]
         verify: bool,
+
+        /// Hex-encoded SHA256 the embedded hash must equal
+        #[arg(long, value_name = "HEX")]
+        expected: Option<String>,
+
+        /// Rhai source file whose SHA256 must match the source hash recorded
+        /// in the .krx file
+        #[arg(long, value_name = "FILE")]
+        source: Option<PathBuf>,
     },
 
     /// Parse a Rhai script and display the configuration
@@ -63,6 +115,17 @@ enum Commands {
         json: bool,
     },
 
+    /// List the modifier, lock and layer IDs a script uses, the keys that
+    /// set each one and the conditions that test it
+    Ids {
+        /// Input Rhai configuration file
+        input: PathBuf,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Generate HTML visualization of key mappings
     View {
         /// Input Rhai configuration file
@@ -78,6 +141,15 @@ enum Commands {
     },
 }
 
+/// Output format of the `compile` subcommand.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CompileTarget {
+    /// rkyv .krx binary loaded by the daemon
+    Krx,
+    /// JSON for the WASM simulator's load_config_json
+    WasmJson,
+}
+
 fn main() {
     // Check NO_COLOR environment variable to disable colored output
     // This is a standard convention: https://no-color.org/
@@ -94,22 +166,72 @@ fn main() {
     let cli = Cli::parse();
 
     let result = match cli.command {
-        Commands::Compile { input, output } => {
-            // Determine output path (default to input with .krx extension)
+        Commands::Compile {
+            input,
+            output,
+            target,
+            emit_deps,
+            base,
+            emit_stats,
+            strict,
+            allow,
+            watch,
+        } => {
+            let warnings = cli::compile::WarningPolicy {
+                strict,
+                allowed: allow,
+            };
+            // Determine output path (default to input with the target's extension)
             let output_path = output.unwrap_or_else(|| {
                 let mut path = input.clone();
-                path.set_extension("krx");
+                path.set_extension(match target {
+                    CompileTarget::Krx => "krx",
+                    CompileTarget::WasmJson => "json",
+                });
                 path
             });
-            cli::compile::handle_compile(&input, &output_path).map_err(|e| e.to_string())
+            let compile = |dependencies: &mut Vec<PathBuf>| match target {
+                CompileTarget::Krx => cli::compile::compile_krx(
+                    &input,
+                    base.as_deref(),
+                    &output_path,
+                    emit_deps.as_deref(),
+                    emit_stats,
+                    &warnings,
+                    dependencies,
+                ),
+                CompileTarget::WasmJson => cli::compile::compile_wasm_json(
+                    &input,
+                    base.as_deref(),
+                    &output_path,
+                    emit_deps.as_deref(),
+                    emit_stats,
+                    &warnings,
+                    dependencies,
+                ),
+            };
+            if watch {
+                cli::watch::handle_watch(&input, compile).map_err(|e| e.to_string())
+            } else {
+                compile(&mut Vec::new()).map_err(|e| e.to_string())
+            }
         }
-        Commands::Verify { file } => cli::verify::handle_verify(&file).map_err(|e| e.to_string()),
-        Commands::Hash { file, verify } => {
-            cli::hash::handle_hash(&file, verify).map_err(|e| e.to_string())
+        Commands::Verify { file, deep } => {
+            cli::verify::handle_verify(&file, deep).map_err(|e| e.to_string())
         }
+        Commands::Hash {
+            file,
+            verify,
+            expected,
+            source,
+        } => cli::hash::handle_hash(&file, verify, expected.as_deref(), source.as_deref())
+            .map_err(|e| e.to_string()),
         Commands::Parse { input, json } => {
             cli::parse::handle_parse(&input, json).map_err(|e| e.to_string())
         }
+        Commands::Ids { input, json } => {
+            cli::ids::handle_ids(&input, json).map_err(|e| e.to_string())
+        }
         Commands::View {
             input,
             output,
@@ -0,0 +1,422 @@
+//! Static analysis of parsed configurations.
+//!
+//! The checks in this module never reject a configuration. They report
+//! mappings that compile fine but can never behave the way they are written,
+//! such as a `when_start("MD_05")` block on a device where no key ever
+//! activates `MD_05`, or a key mapped to `VK_LShift` on a device that also
+//! turns the physical LShift into a custom modifier, or keys that remap into
+//! each other in a loop.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, DeviceConfig, KeyCode, KeyMapping, MacroStep,
+};
+
+use crate::error::{ParseWarning, WarningKind};
+use crate::parser::core::ParserState;
+
+/// Source location of a conditional block, recorded when the block starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionSite {
+    /// Index of the owning device in `ParserState::devices`
+    pub device_index: usize,
+    pub condition: Condition,
+    /// Loaded file the block is in, or None for the top-level script
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Source location of a `map()` whose output is a physical modifier key,
+/// recorded for [`check_modifier_outputs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifierOutputSite {
+    /// Index of the owning device in `ParserState::devices`
+    pub device_index: usize,
+    pub from: KeyCode,
+    pub to: KeyCode,
+    /// Loaded file the call is in, or None for the top-level script
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Source location of a `device_start()` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceSite {
+    /// Loaded file the call is in, or None for the top-level script
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Returns whether `key` is one of the eight physical modifier keys.
+pub fn is_physical_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::LShift
+            | KeyCode::RShift
+            | KeyCode::LCtrl
+            | KeyCode::RCtrl
+            | KeyCode::LAlt
+            | KeyCode::RAlt
+            | KeyCode::LMeta
+            | KeyCode::RMeta
+    )
+}
+
+/// Modifier and lock IDs that some mapping on a device can turn on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToggleableIds {
+    pub modifiers: BTreeSet<u8>,
+    pub locks: BTreeSet<u8>,
+    /// Number of time windows defined on the device (TW_00 onwards)
+    pub time_windows: usize,
+}
+
+impl ToggleableIds {
+    /// Collects every modifier and lock ID activated by the device's mappings,
+    /// including mappings nested inside conditional blocks.
+    pub fn from_device_config(device: &DeviceConfig) -> Self {
+        let mut ids = Self {
+            time_windows: device.time_windows.len(),
+            ..Self::default()
+        };
+        for mapping in &device.mappings {
+            match mapping {
+                KeyMapping::Base(base) => ids.collect(base),
+                KeyMapping::Conditional { mappings, .. } => {
+                    for base in mappings {
+                        ids.collect(base);
+                    }
+                }
+            }
+        }
+        ids
+    }
+
+    fn collect(&mut self, mapping: &BaseKeyMapping) {
+        match mapping {
+            BaseKeyMapping::Modifier { modifier_id, .. } => {
+                self.modifiers.insert(*modifier_id);
+            }
+            BaseKeyMapping::TapHold { hold_modifier, .. } => {
+                self.modifiers.insert(*hold_modifier);
+            }
+            BaseKeyMapping::LayerToggle { layer_id, .. } => {
+                self.modifiers.insert(*layer_id);
+            }
+            BaseKeyMapping::Lock { lock_id, .. } => {
+                self.locks.insert(*lock_id);
+            }
+            BaseKeyMapping::Simple { .. }
+            | BaseKeyMapping::ModifiedOutput { .. }
+            | BaseKeyMapping::OnRelease { .. }
+            | BaseKeyMapping::Cycle { .. }
+            | BaseKeyMapping::Disable { .. }
+            | BaseKeyMapping::Macro { .. } => {}
+        }
+    }
+
+    fn declares(&self, item: &ConditionItem) -> bool {
+        match item {
+            ConditionItem::ModifierActive(id) => self.modifiers.contains(id),
+            ConditionItem::LockActive(id) => self.locks.contains(id),
+            ConditionItem::TimeWindowActive(id) => (*id as usize) < self.time_windows,
+        }
+    }
+}
+
+/// Returns the names (`MD_XX` / `LK_XX` / `TW_XX`) of IDs referenced by
+/// `condition` that no mapping in `ids` can activate, or that name an
+/// undefined time window.
+pub fn undeclared_condition_ids(condition: &Condition, ids: &ToggleableIds) -> Vec<String> {
+    condition_items(condition)
+        .iter()
+        .filter(|item| !ids.declares(item))
+        .map(|item| match item {
+            ConditionItem::ModifierActive(id) => format!("MD_{:02X}", id),
+            ConditionItem::LockActive(id) => format!("LK_{:02X}", id),
+            ConditionItem::TimeWindowActive(id) => format!("TW_{:02X}", id),
+        })
+        .collect()
+}
+
+/// Collects the items `condition` references, descending into negations.
+fn condition_items(condition: &Condition) -> Vec<ConditionItem> {
+    match condition {
+        Condition::ModifierActive(id) => vec![ConditionItem::ModifierActive(*id)],
+        Condition::LockActive(id) => vec![ConditionItem::LockActive(*id)],
+        Condition::TimeWindowActive(id) => vec![ConditionItem::TimeWindowActive(*id)],
+        Condition::AllActive(items) | Condition::NotActive(items) | Condition::AnyActive(items) => {
+            items.clone()
+        }
+        Condition::Composite { all, none } => all.iter().chain(none).cloned().collect(),
+        Condition::DeviceMatches(_) => Vec::new(),
+        Condition::Negate(inner) => condition_items(inner),
+    }
+}
+
+/// Reports conditional blocks whose guarding condition references modifier
+/// or lock IDs that are never activated on the same device.
+///
+/// A positive condition (`when_start`) on such an ID never fires, while a
+/// negated one (`when_not_start`) is always true.
+pub fn check_condition_reachability(state: &ParserState, file: &Path) -> Vec<ParseWarning> {
+    let toggleable: Vec<ToggleableIds> = state
+        .devices
+        .iter()
+        .map(ToggleableIds::from_device_config)
+        .collect();
+
+    let mut warnings = Vec::new();
+    for site in &state.condition_sites {
+        let Some(ids) = toggleable.get(site.device_index) else {
+            continue;
+        };
+        let undeclared = undeclared_condition_ids(&site.condition, ids);
+        if undeclared.is_empty() {
+            continue;
+        }
+
+        let names = undeclared.join(", ");
+        let message = match &site.condition {
+            Condition::NotActive(_) => format!(
+                "when_not condition is always true: {} is never activated by any mapping on this device",
+                names
+            ),
+            // The inner condition may be unsatisfiable, leaving not() always true
+            Condition::Negate(_) => format!(
+                "not() condition may always be true: {} is never activated by any mapping on this device",
+                names
+            ),
+            // Only the none_of() side is undeclared, so that side is redundant
+            Condition::Composite { all, .. } if all.iter().all(|item| ids.declares(item)) => {
+                format!(
+                    "none_of() clause is always satisfied: {} is never activated by any mapping on this device",
+                    names
+                )
+            }
+            // Some alternatives are declared, so the block can still fire
+            Condition::AnyActive(items) if items.iter().any(|item| ids.declares(item)) => {
+                format!(
+                    "Condition alternative never matches: {} is never activated by any mapping on this device",
+                    names
+                )
+            }
+            _ => format!(
+                "Conditional mappings never fire: {} is never activated by any mapping on this device",
+                names
+            ),
+        };
+
+        warnings.push(ParseWarning {
+            kind: WarningKind::UnreachableCondition,
+            file: site.file.as_deref().unwrap_or(file).to_path_buf(),
+            line: site.line,
+            column: site.column,
+            message,
+        });
+    }
+    warnings
+}
+
+/// Returns the custom modifier the physical `key` activates on `device`,
+/// either directly (`map("LShift", "MD_00")`) or as a tap_hold hold action.
+fn custom_modifier_for(device: &DeviceConfig, key: KeyCode) -> Option<u8> {
+    device
+        .mappings
+        .iter()
+        .flat_map(|mapping| match mapping {
+            KeyMapping::Base(base) => core::slice::from_ref(base),
+            KeyMapping::Conditional { mappings, .. } => mappings.as_slice(),
+        })
+        .find_map(|mapping| match mapping {
+            BaseKeyMapping::Modifier { from, modifier_id } if *from == key => Some(*modifier_id),
+            BaseKeyMapping::TapHold {
+                from,
+                hold_modifier,
+                ..
+            } if *from == key => Some(*hold_modifier),
+            _ => None,
+        })
+}
+
+/// Reports mappings that emit a physical modifier key which the same device
+/// also turns into a custom modifier.
+///
+/// Conditions only see the daemon's internal modifier state. Emitting
+/// `VK_LShift` injects a Shift press but never sets the `MD_XX` bit the
+/// physical LShift is mapped to, which is rarely what the author meant.
+pub fn check_modifier_outputs(state: &ParserState, file: &Path) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+    for site in &state.modifier_output_sites {
+        let Some(device) = state.devices.get(site.device_index) else {
+            continue;
+        };
+        let Some(modifier_id) = custom_modifier_for(device, site.to) else {
+            continue;
+        };
+
+        warnings.push(ParseWarning {
+            kind: WarningKind::ModifierOutput,
+            file: site.file.as_deref().unwrap_or(file).to_path_buf(),
+            line: site.line,
+            column: site.column,
+            message: format!(
+                "{:?} emits {:?}, but {:?} is remapped to MD_{:02X} on this device: \
+                 the emitted key is injected as plain {:?} and does not activate MD_{:02X} \
+                 (map {:?} to \"MD_{:02X}\" to activate it)",
+                site.from,
+                site.to,
+                site.to,
+                modifier_id,
+                site.to,
+                modifier_id,
+                site.from,
+                modifier_id
+            ),
+        });
+    }
+    warnings
+}
+
+/// A mapping on which pressing `from` emits `to`.
+#[derive(Debug, Clone, Copy)]
+struct RemapEdge {
+    from: KeyCode,
+    to: KeyCode,
+    /// The mapping is conditional or more than a plain `map()` remap
+    risky: bool,
+}
+
+/// Collects the keys each mapping on `device` emits.
+///
+/// Mappings that emit their own trigger (`map("A", "VK_A")`, the tap of
+/// `tap_hold("Space", "VK_Space", ...)`) are left out: they pass the key
+/// through rather than chaining into another mapping.
+fn remap_edges(device: &DeviceConfig) -> Vec<RemapEdge> {
+    let mut edges = Vec::new();
+    let mut push = |mapping: &BaseKeyMapping, conditional: bool| {
+        let pressed_by_macro: Vec<KeyCode>;
+        let (from, outputs, simple): (KeyCode, &[KeyCode], bool) = match mapping {
+            BaseKeyMapping::Simple { from, to } => (*from, core::slice::from_ref(to), true),
+            BaseKeyMapping::ModifiedOutput { from, to, .. }
+            | BaseKeyMapping::OnRelease { from, to, .. } => {
+                (*from, core::slice::from_ref(to), false)
+            }
+            BaseKeyMapping::TapHold { from, tap, .. } => (*from, core::slice::from_ref(tap), false),
+            BaseKeyMapping::Cycle { from, outputs, .. } => (*from, outputs.as_slice(), false),
+            BaseKeyMapping::Macro { from, steps } => {
+                pressed_by_macro = steps
+                    .iter()
+                    .filter_map(|step| match step {
+                        MacroStep::Press(key) => Some(*key),
+                        MacroStep::Release(_) | MacroStep::Wait(_) => None,
+                    })
+                    .collect();
+                (*from, pressed_by_macro.as_slice(), false)
+            }
+            BaseKeyMapping::Modifier { .. }
+            | BaseKeyMapping::Lock { .. }
+            | BaseKeyMapping::LayerToggle { .. }
+            | BaseKeyMapping::Disable { .. } => return,
+        };
+        for &to in outputs {
+            if to != from {
+                edges.push(RemapEdge {
+                    from,
+                    to,
+                    risky: conditional || !simple,
+                });
+            }
+        }
+    };
+    for mapping in &device.mappings {
+        match mapping {
+            KeyMapping::Base(base) => push(base, false),
+            KeyMapping::Conditional { mappings, .. } => {
+                for base in mappings {
+                    push(base, true);
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Returns the shortest chain of `edges` leading from `start` to `goal`,
+/// including both ends.
+fn remap_path(edges: &[RemapEdge], start: KeyCode, goal: KeyCode) -> Option<Vec<KeyCode>> {
+    let mut previous: HashMap<KeyCode, KeyCode> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(key) = queue.pop_front() {
+        if key == goal {
+            let mut path = vec![goal];
+            let mut key = goal;
+            while key != start {
+                key = previous[&key];
+                path.push(key);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for edge in edges.iter().filter(|edge| edge.from == key) {
+            if edge.to != start && !previous.contains_key(&edge.to) {
+                previous.insert(edge.to, key);
+                queue.push_back(edge.to);
+            }
+        }
+    }
+    None
+}
+
+/// Reports keys that remap into each other in a cycle on the same device.
+///
+/// The daemon never re-captures the events it injects, so each press is
+/// remapped exactly once and no cycle can actually feed back. Plain swaps
+/// such as `map("A", "VK_B")` with `map("B", "VK_A")` are a common idiom
+/// and are not reported. A cycle that goes through a conditional, tap-hold,
+/// modified-output, on-release or cycle mapping is, since it usually means
+/// a layer or macro emits a key whose own mapping the author did not expect
+/// to apply. Each cycle is reported once, at its device's `device_start()`.
+pub fn check_remap_loops(state: &ParserState, file: &Path) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+    for (device_index, device) in state.devices.iter().enumerate() {
+        let edges = remap_edges(device);
+        let mut reported: Vec<HashSet<KeyCode>> = Vec::new();
+        for edge in edges.iter().filter(|edge| edge.risky) {
+            let Some(mut chain) = remap_path(&edges, edge.to, edge.from) else {
+                continue;
+            };
+            chain.insert(0, edge.from);
+            let keys: HashSet<KeyCode> = chain.iter().copied().collect();
+            if reported.contains(&keys) {
+                continue;
+            }
+            reported.push(keys);
+
+            let site = state.device_sites.get(device_index);
+            let chain: Vec<String> = chain.iter().map(|key| format!("{:?}", key)).collect();
+            warnings.push(ParseWarning {
+                kind: WarningKind::RemapLoop,
+                file: site
+                    .and_then(|site| site.file.as_deref())
+                    .unwrap_or(file)
+                    .to_path_buf(),
+                line: site.map_or(0, |site| site.line),
+                column: site.map_or(0, |site| site.column),
+                message: format!(
+                    "Possible remap loop on device '{}': {}. Each key in the chain is \
+                     emitted by one mapping and remapped by another; emitted keys are \
+                     never remapped again, so a press only follows the first step",
+                    device.identifier.pattern,
+                    chain.join(" -> ")
+                ),
+            });
+        }
+    }
+    warnings
+}
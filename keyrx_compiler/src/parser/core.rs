@@ -4,10 +4,15 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::error::ParseError;
+use crate::error::{LimitKind, ParseError, ParseWarning};
+use crate::parser::analysis::{
+    check_condition_reachability, check_modifier_outputs, check_remap_loops, ConditionSite,
+    DeviceSite, ModifierOutputSite,
+};
 use keyrx_core::config::{ConfigRoot, DeviceConfig, Metadata, Version};
 
 use keyrx_core::config::{BaseKeyMapping, Condition};
+use keyrx_core::runtime::find_output_overflow;
 
 /// Parser state shared across Rhai custom functions
 #[derive(Debug, Clone, Default)]
@@ -17,6 +22,18 @@ pub struct ParserState {
     /// Stack of (Condition, mappings) pairs being collected for conditional blocks
     /// When non-empty, map() adds to the top of this stack instead of current_device
     pub conditional_stack: Vec<(Condition, Vec<BaseKeyMapping>)>,
+    /// Source positions of every conditional block, used for post-parse diagnostics
+    pub condition_sites: Vec<ConditionSite>,
+    /// Source positions of map() calls that emit a physical modifier key
+    pub modifier_output_sites: Vec<ModifierOutputSite>,
+    /// Source position of each device_start(), indexed like `devices`
+    pub device_sites: Vec<DeviceSite>,
+    /// Every file pulled in through load(), in the order it was resolved
+    pub imported_files: Vec<PathBuf>,
+    /// File load() is running, or None while the top-level script runs
+    pub loading_file: Option<PathBuf>,
+    /// Threshold set by tap_hold_threshold() outside any device block
+    pub tap_hold_threshold_ms: Option<u16>,
 }
 
 impl ParserState {
@@ -25,29 +42,117 @@ impl ParserState {
     }
 }
 
+/// Resource limits applied to the Rhai engine while parsing.
+///
+/// The defaults are deliberately tight so a runaway script fails fast.
+/// Large generated configurations can raise them with
+/// [`Parser::with_limits`]. A value of 0 disables the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// Total operations a script (or each loaded file) may run
+    pub max_operations: u64,
+    /// Maximum nesting depth of a single expression
+    pub max_expr_depth: usize,
+    /// Maximum nesting depth of function calls
+    pub max_call_levels: usize,
+}
+
+impl ParserLimits {
+    /// Current value of the limit `kind`.
+    pub fn get(&self, kind: LimitKind) -> u64 {
+        match kind {
+            LimitKind::Operations => self.max_operations,
+            LimitKind::ExpressionDepth => self.max_expr_depth as u64,
+            LimitKind::CallDepth => self.max_call_levels as u64,
+        }
+    }
+
+    /// Applies these limits to `engine`.
+    pub(crate) fn apply(&self, engine: &mut Engine) {
+        engine.set_max_operations(self.max_operations);
+        engine.set_max_expr_depths(self.max_expr_depth, self.max_expr_depth);
+        engine.set_max_call_levels(self.max_call_levels);
+    }
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 10_000,
+            max_expr_depth: 100,
+            max_call_levels: 100,
+        }
+    }
+}
+
+/// Returns which parser limit `err` reports, looking through the function
+/// call (e.g. `load()`) that raised it.
+pub(crate) fn limit_kind(err: &EvalAltResult) -> Option<LimitKind> {
+    match err {
+        EvalAltResult::ErrorTooManyOperations(_) => Some(LimitKind::Operations),
+        EvalAltResult::ErrorStackOverflow(_) => Some(LimitKind::CallDepth),
+        EvalAltResult::ErrorParsing(rhai::ParseErrorType::ExprTooDeep, _) => {
+            Some(LimitKind::ExpressionDepth)
+        }
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _) => limit_kind(inner),
+        _ => None,
+    }
+}
+
 /// Main parser for Rhai DSL
 pub struct Parser {
     pub engine: Engine,
     pub state: Arc<Mutex<ParserState>>,
     /// Current source file being parsed (for import resolution)
     source_file: Arc<Mutex<PathBuf>>,
+    /// Warnings produced by the most recent successful parse
+    warnings: Vec<ParseWarning>,
+    /// Resource limits the engine enforces
+    limits: ParserLimits,
 }
 
 impl Parser {
     pub fn new() -> Self {
+        Self::with_limits(ParserLimits::default())
+    }
+
+    /// Creates a parser that enforces `limits` instead of the defaults.
+    ///
+    /// The limits also apply to every file pulled in through `load()`.
+    pub fn with_limits(limits: ParserLimits) -> Self {
         let mut engine = Engine::new();
         let state = Arc::new(Mutex::new(ParserState::new()));
         let source_file = Arc::new(Mutex::new(PathBuf::new()));
 
-        engine.set_max_operations(10_000);
-        engine.set_max_expr_depths(100, 100);
-        engine.set_max_call_levels(100);
+        limits.apply(&mut engine);
 
         crate::parser::functions::map::register_map_function(&mut engine, Arc::clone(&state));
+        crate::parser::functions::map_range::register_map_range_function(
+            &mut engine,
+            Arc::clone(&state),
+        );
         crate::parser::functions::tap_hold::register_tap_hold_function(
             &mut engine,
             Arc::clone(&state),
         );
+        crate::parser::functions::on_release::register_on_release_function(
+            &mut engine,
+            Arc::clone(&state),
+        );
+        crate::parser::functions::layer_toggle::register_layer_toggle_function(
+            &mut engine,
+            Arc::clone(&state),
+        );
+        crate::parser::functions::cycle::register_cycle_function(&mut engine, Arc::clone(&state));
+        crate::parser::functions::disable::register_disable_function(
+            &mut engine,
+            Arc::clone(&state),
+        );
+        crate::parser::functions::macros::register_macro_functions(&mut engine, Arc::clone(&state));
+        crate::parser::functions::time_window::register_time_window_function(
+            &mut engine,
+            Arc::clone(&state),
+        );
         crate::parser::functions::conditional::register_when_functions(
             &mut engine,
             Arc::clone(&state),
@@ -58,15 +163,56 @@ impl Parser {
             &mut engine,
             Arc::clone(&state),
             Arc::clone(&source_file),
+            limits,
         );
 
         Self {
             engine,
             state,
             source_file,
+            warnings: Vec::new(),
+            limits,
         }
     }
 
+    /// Makes `load()` fail instead of reading files.
+    ///
+    /// For scripts from untrusted callers, such as a web editor, which must
+    /// not be able to make this process open arbitrary paths.
+    #[allow(dead_code)] // Used by the daemon's simulator, not the binary
+    pub fn without_load(mut self) -> Self {
+        self.engine.register_fn(
+            "load",
+            |_: rhai::ImmutableString| -> Result<(), Box<EvalAltResult>> {
+                Err(Box::new(EvalAltResult::ErrorRuntime(
+                    "load() is not available in this script".into(),
+                    rhai::Position::NONE,
+                )))
+            },
+        );
+        self
+    }
+
+    /// Files the most recent parse read: the top-level script followed by
+    /// every transitively loaded file, without duplicates.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+        #[allow(clippy::unwrap_used)]
+        let mut deps = vec![self.source_file.lock().unwrap().clone()];
+        #[allow(clippy::unwrap_used)]
+        for path in &self.state.lock().unwrap().imported_files {
+            if !deps.contains(path) {
+                deps.push(path.clone());
+            }
+        }
+        deps
+    }
+
+    /// Non-fatal diagnostics from the most recent successful parse.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
     pub fn parse_script(&mut self, path: &Path) -> Result<ConfigRoot, ParseError> {
         // Update the source file path for import resolution
         // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
@@ -94,13 +240,25 @@ impl Parser {
         let mut scope = Scope::new();
         self.engine
             .run_with_scope(&mut scope, script)
-            .map_err(|e| Self::convert_rhai_error(e, source_path))?;
+            .map_err(|e| self.convert_rhai_error(e, source_path))?;
 
         self.validate_timeout(start_time)?;
 
         // Hash the script content for traceability
         let source_bytes = script.as_bytes();
-        self.finalize_config(source_path, source_bytes)
+        let config = self.finalize_config(source_path, source_bytes)?;
+
+        // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+        #[allow(clippy::unwrap_used)]
+        {
+            let state = self.state.lock().unwrap();
+            self.warnings = check_condition_reachability(&state, source_path);
+            self.warnings
+                .extend(check_modifier_outputs(&state, source_path));
+            self.warnings.extend(check_remap_loops(&state, source_path));
+        }
+
+        Ok(config)
     }
 
     fn validate_timeout(&self, start_time: SystemTime) -> Result<(), ParseError> {
@@ -136,6 +294,18 @@ impl Parser {
             });
         }
 
+        // Reject mappings whose outputs would be truncated by the runtime
+        for device in &state.devices {
+            if let Some(overflow) = find_output_overflow(device) {
+                return Err(ParseError::OutputBudgetExceeded {
+                    device: device.identifier.pattern.clone(),
+                    mapping: format!("{:?}", overflow.mapping),
+                    events: overflow.events,
+                    capacity: overflow.capacity,
+                });
+            }
+        }
+
         // Calculate SHA256 hash of source script for traceability
         let mut hasher = Sha256::new();
         hasher.update(source_bytes);
@@ -162,14 +332,28 @@ impl Parser {
             source_hash,
         };
 
+        // Cheap condition checks first, so AND/OR lists short-circuit early
+        let mut devices = state.devices.clone();
+        for device in &mut devices {
+            device.order_conditions_for_evaluation();
+        }
+
         Ok(ConfigRoot {
             version: Version::current(),
-            devices: state.devices.clone(),
+            devices,
             metadata,
         })
     }
 
-    fn convert_rhai_error(err: Box<EvalAltResult>, path: &Path) -> ParseError {
+    fn convert_rhai_error(&self, err: Box<EvalAltResult>, path: &Path) -> ParseError {
+        if let Some(kind) = limit_kind(&err) {
+            return ParseError::LimitExceeded {
+                kind,
+                limit: self.limits.get(kind),
+                import_chain: Vec::new(),
+            };
+        }
+
         let position = err.position();
         ParseError::SyntaxError {
             file: path.to_path_buf(),
@@ -1,7 +1,8 @@
 use keyrx_core::config::{Condition, ConditionItem, KeyMapping};
-use rhai::{Array, Engine, EvalAltResult};
+use rhai::{Array, Engine, EvalAltResult, NativeCallContext, Position};
 use std::sync::{Arc, Mutex};
 
+use crate::parser::analysis::ConditionSite;
 use crate::parser::core::ParserState;
 use crate::parser::validators::parse_condition_string;
 
@@ -10,10 +11,10 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     let state_clone_single = Arc::clone(&state);
     engine.register_fn(
         "when_start",
-        move |cond: &str| -> Result<(), Box<EvalAltResult>> {
+        move |ctx: NativeCallContext, cond: &str| -> Result<(), Box<EvalAltResult>> {
             let condition =
                 parse_condition_string(cond).map_err(|e| format!("Invalid condition: {}", e))?;
-            start_conditional_block(&state_clone_single, condition)
+            start_conditional_block(&state_clone_single, condition, ctx.call_position())
         },
     );
 
@@ -21,25 +22,133 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     let state_clone_multi = Arc::clone(&state);
     engine.register_fn(
         "when_start",
-        move |conds: Array| -> Result<(), Box<EvalAltResult>> {
-            let mut condition_items = Vec::new();
-            for cond_dyn in conds {
-                let cond_str = cond_dyn
-                    .into_string()
-                    .map_err(|_| "Condition must be a string")?;
-                let cond = parse_condition_string(&cond_str)
-                    .map_err(|e| format!("Invalid condition: {}", e))?;
-                match cond {
-                    Condition::ModifierActive(id) => {
-                        condition_items.push(ConditionItem::ModifierActive(id))
-                    }
-                    Condition::LockActive(id) => {
-                        condition_items.push(ConditionItem::LockActive(id))
-                    }
-                    _ => return Err("Only single modifiers/locks allowed in array".into()),
-                }
+        move |ctx: NativeCallContext, conds: Array| -> Result<(), Box<EvalAltResult>> {
+            let condition_items = parse_condition_items(conds)?;
+            start_conditional_block(
+                &state_clone_multi,
+                Condition::AllActive(condition_items),
+                ctx.call_position(),
+            )
+        },
+    );
+
+    // all_of([...]) / none_of([...]) build a CompositeCondition, which can be
+    // extended with .and_none_of([...]) / .and_all_of([...])
+    engine.register_type::<CompositeCondition>();
+    engine.register_fn(
+        "all_of",
+        |conds: Array| -> Result<CompositeCondition, Box<EvalAltResult>> {
+            Ok(CompositeCondition {
+                all: parse_condition_items(conds)?,
+                none: Vec::new(),
+            })
+        },
+    );
+    engine.register_fn(
+        "none_of",
+        |conds: Array| -> Result<CompositeCondition, Box<EvalAltResult>> {
+            Ok(CompositeCondition {
+                all: Vec::new(),
+                none: parse_condition_items(conds)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "and_all_of",
+        |mut composite: CompositeCondition,
+         conds: Array|
+         -> Result<CompositeCondition, Box<EvalAltResult>> {
+            composite.all.extend(parse_condition_items(conds)?);
+            Ok(composite)
+        },
+    );
+    engine.register_fn(
+        "and_none_of",
+        |mut composite: CompositeCondition,
+         conds: Array|
+         -> Result<CompositeCondition, Box<EvalAltResult>> {
+            composite.none.extend(parse_condition_items(conds)?);
+            Ok(composite)
+        },
+    );
+
+    // any_of([...]) builds an OR condition, same as "MD_00 | LK_01"
+    engine.register_type::<AnyOfCondition>();
+    engine.register_fn(
+        "any_of",
+        |conds: Array| -> Result<AnyOfCondition, Box<EvalAltResult>> {
+            let items = parse_condition_items(conds)?;
+            if items.is_empty() {
+                return Err("any_of() needs at least one modifier, lock or time window".into());
             }
-            start_conditional_block(&state_clone_multi, Condition::AllActive(condition_items))
+            Ok(AnyOfCondition { items })
+        },
+    );
+
+    // when_start() for a composite condition (all of ... AND none of ...)
+    let state_clone_composite = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |ctx: NativeCallContext,
+              composite: CompositeCondition|
+              -> Result<(), Box<EvalAltResult>> {
+            let condition = composite.into_condition()?;
+            start_conditional_block(&state_clone_composite, condition, ctx.call_position())
+        },
+    );
+
+    // when_start() for an any_of() condition
+    let state_clone_any = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |ctx: NativeCallContext, any: AnyOfCondition| -> Result<(), Box<EvalAltResult>> {
+            start_conditional_block(
+                &state_clone_any,
+                Condition::AnyActive(any.items),
+                ctx.call_position(),
+            )
+        },
+    );
+
+    // not(...) inverts a whole condition, e.g. not(all_of(["MD_00", "LK_01"]))
+    engine.register_type::<NegatedCondition>();
+    engine.register_fn(
+        "not",
+        |cond: &str| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            let condition =
+                parse_condition_string(cond).map_err(|e| format!("Invalid condition: {}", e))?;
+            Ok(NegatedCondition::of(condition))
+        },
+    );
+    engine.register_fn(
+        "not",
+        |conds: Array| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            Ok(NegatedCondition::of(Condition::AllActive(
+                parse_condition_items(conds)?,
+            )))
+        },
+    );
+    engine.register_fn(
+        "not",
+        |composite: CompositeCondition| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            Ok(NegatedCondition::of(composite.into_condition()?))
+        },
+    );
+    engine.register_fn("not", |any: AnyOfCondition| -> NegatedCondition {
+        NegatedCondition::of(Condition::AnyActive(any.items))
+    });
+    engine.register_fn("not", |negated: NegatedCondition| -> NegatedCondition {
+        NegatedCondition::of(negated.condition)
+    });
+
+    // when_start() for a not() condition
+    let state_clone_negated = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |ctx: NativeCallContext,
+              negated: NegatedCondition|
+              -> Result<(), Box<EvalAltResult>> {
+            start_conditional_block(&state_clone_negated, negated.condition, ctx.call_position())
         },
     );
 
@@ -53,15 +162,20 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     let state_clone_not = Arc::clone(&state);
     engine.register_fn(
         "when_not_start",
-        move |cond: &str| -> Result<(), Box<EvalAltResult>> {
+        move |ctx: NativeCallContext, cond: &str| -> Result<(), Box<EvalAltResult>> {
             let condition =
                 parse_condition_string(cond).map_err(|e| format!("Invalid condition: {}", e))?;
             let item = match condition {
                 Condition::ModifierActive(id) => ConditionItem::ModifierActive(id),
                 Condition::LockActive(id) => ConditionItem::LockActive(id),
+                Condition::TimeWindowActive(id) => ConditionItem::TimeWindowActive(id),
                 _ => return Err("Only single modifiers/locks allowed in when_not".into()),
             };
-            start_conditional_block(&state_clone_not, Condition::NotActive(vec![item]))
+            start_conditional_block(
+                &state_clone_not,
+                Condition::NotActive(vec![item]),
+                ctx.call_position(),
+            )
         },
     );
 
@@ -87,13 +201,14 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     let state_clone_device = Arc::clone(&state);
     engine.register_fn(
         "when_device_start",
-        move |pattern: &str| -> Result<(), Box<EvalAltResult>> {
+        move |ctx: NativeCallContext, pattern: &str| -> Result<(), Box<EvalAltResult>> {
             if pattern.is_empty() {
                 return Err("Device pattern cannot be empty".into());
             }
             start_conditional_block(
                 &state_clone_device,
                 Condition::DeviceMatches(pattern.to_string()),
+                ctx.call_position(),
             )
         },
     );
@@ -108,10 +223,76 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     );
 }
 
+/// Builder for a composite condition, returned by all_of() and none_of().
+/// Consumed by the when_start() overload to create a `Condition::Composite`.
+#[derive(Clone, Debug, Default)]
+pub struct CompositeCondition {
+    pub all: Vec<ConditionItem>,
+    pub none: Vec<ConditionItem>,
+}
+
+impl CompositeCondition {
+    fn into_condition(self) -> Result<Condition, Box<EvalAltResult>> {
+        if self.all.is_empty() && self.none.is_empty() {
+            return Err("Composite condition needs at least one modifier or lock".into());
+        }
+        Ok(Condition::Composite {
+            all: self.all,
+            none: self.none,
+        })
+    }
+}
+
+/// OR condition returned by any_of().
+/// Consumed by the when_start() overload to create a `Condition::AnyActive`.
+#[derive(Clone, Debug)]
+pub struct AnyOfCondition {
+    pub items: Vec<ConditionItem>,
+}
+
+/// Inverted condition returned by not().
+/// Consumed by the when_start() overload; holds the complete `Condition::Negate`.
+#[derive(Clone, Debug)]
+pub struct NegatedCondition {
+    pub condition: Condition,
+}
+
+impl NegatedCondition {
+    fn of(condition: Condition) -> Self {
+        Self {
+            condition: Condition::Negate(Box::new(condition)),
+        }
+    }
+}
+
+/// Parse an array of "MD_XX"/"LK_XX"/"TW_XX" strings into condition items
+fn parse_condition_items(conds: Array) -> Result<Vec<ConditionItem>, Box<EvalAltResult>> {
+    let mut condition_items = Vec::new();
+    for cond_dyn in conds {
+        let cond_str = cond_dyn
+            .into_string()
+            .map_err(|_| "Condition must be a string")?;
+        let cond =
+            parse_condition_string(&cond_str).map_err(|e| format!("Invalid condition: {}", e))?;
+        match cond {
+            Condition::ModifierActive(id) => {
+                condition_items.push(ConditionItem::ModifierActive(id))
+            }
+            Condition::LockActive(id) => condition_items.push(ConditionItem::LockActive(id)),
+            Condition::TimeWindowActive(id) => {
+                condition_items.push(ConditionItem::TimeWindowActive(id))
+            }
+            _ => return Err("Only single modifiers/locks allowed in array".into()),
+        }
+    }
+    Ok(condition_items)
+}
+
 /// Start a conditional block - push a new conditional stack entry
 fn start_conditional_block(
     state: &Arc<Mutex<ParserState>>,
     condition: Condition,
+    position: Position,
 ) -> Result<(), Box<EvalAltResult>> {
     // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
     #[allow(clippy::unwrap_used)]
@@ -120,6 +301,19 @@ fn start_conditional_block(
         return Err("Conditional blocks must be called inside a device() block".into());
     }
 
+    // Remember where the block starts for reachability diagnostics. The
+    // current device is pushed to `devices` when it ends, so its index is
+    // the current length.
+    let device_index = state.devices.len();
+    let file = state.loading_file.clone();
+    state.condition_sites.push(ConditionSite {
+        device_index,
+        condition: condition.clone(),
+        file,
+        line: position.line().unwrap_or(0),
+        column: position.position().unwrap_or(0),
+    });
+
     // Push (condition, empty mappings Vec) onto the stack
     state.conditional_stack.push((condition, Vec::new()));
 
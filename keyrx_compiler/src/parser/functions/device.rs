@@ -1,14 +1,16 @@
 use keyrx_core::config::{DeviceConfig, DeviceIdentifier};
-use rhai::{Engine, EvalAltResult};
+use rhai::{Engine, EvalAltResult, NativeCallContext};
 use std::sync::{Arc, Mutex};
 
+use crate::parser::analysis::DeviceSite;
 use crate::parser::core::ParserState;
+use crate::parser::validators::parse_modifier_id;
 
 pub fn register_device_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
     let state_clone_start = Arc::clone(&state);
     engine.register_fn(
         "device_start",
-        move |pattern: &str| -> Result<(), Box<EvalAltResult>> {
+        move |ctx: NativeCallContext, pattern: &str| -> Result<(), Box<EvalAltResult>> {
             // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
             #[allow(clippy::unwrap_used)]
             let mut state = state_clone_start.lock().unwrap();
@@ -17,17 +19,90 @@ pub fn register_device_function(engine: &mut Engine, state: Arc<Mutex<ParserStat
                 state.devices.push(device);
             }
 
+            let position = ctx.call_position();
+            let file = state.loading_file.clone();
+            state.device_sites.push(DeviceSite {
+                file,
+                line: position.line().unwrap_or(0),
+                column: position.position().unwrap_or(0),
+            });
+
             state.current_device = Some(DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: pattern.to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: Vec::new(),
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             });
 
             Ok(())
         },
     );
 
+    let state_clone_exclude = Arc::clone(&state);
+    engine.register_fn(
+        "exclude",
+        move |pattern: &str| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone_exclude.lock().unwrap();
+
+            if let Some(ref mut device) = state.current_device {
+                device.identifier.excludes.push(pattern.to_string());
+                Ok(())
+            } else {
+                Err("exclude() must be called inside a device() block".into())
+            }
+        },
+    );
+
+    let state_clone_priority = Arc::clone(&state);
+    engine.register_fn(
+        "priority",
+        move |priority: i64| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone_priority.lock().unwrap();
+
+            let Ok(priority) = i32::try_from(priority) else {
+                return Err(
+                    format!("priority() must fit in a 32-bit integer, got {}", priority).into(),
+                );
+            };
+
+            if let Some(ref mut device) = state.current_device {
+                device.priority = priority;
+                Ok(())
+            } else {
+                Err("priority() must be called inside a device() block".into())
+            }
+        },
+    );
+
+    let state_clone_default_layer = Arc::clone(&state);
+    engine.register_fn(
+        "default_layer",
+        move |layer: &str| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone_default_layer.lock().unwrap();
+
+            let layer_id =
+                parse_modifier_id(layer).map_err(|e| format!("Invalid default layer: {}", e))?;
+
+            if let Some(ref mut device) = state.current_device {
+                device.default_layer = Some(layer_id);
+                Ok(())
+            } else {
+                Err("default_layer() must be called inside a device() block".into())
+            }
+        },
+    );
+
     let state_clone_end = Arc::clone(&state);
     engine.register_fn("device_end", move || -> Result<(), Box<EvalAltResult>> {
         // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
@@ -0,0 +1,33 @@
+use keyrx_core::config::{BaseKeyMapping, KeyMapping};
+use rhai::{Engine, EvalAltResult};
+use std::sync::{Arc, Mutex};
+
+use crate::parser::core::ParserState;
+use crate::parser::validators::parse_physical_key;
+
+pub fn register_disable_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "disable",
+        move |key: &str| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone.lock().unwrap();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+            let base_mapping = BaseKeyMapping::Disable { from: from_key };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("disable() must be called inside a device() block".into())
+            }
+        },
+    );
+}
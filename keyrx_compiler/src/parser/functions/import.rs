@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use crate::import_resolver::ImportResolver;
-use crate::parser::core::ParserState;
+use crate::parser::core::{limit_kind, ParserLimits, ParserState};
 
 /// Registers the load() function in the Rhai engine.
 ///
@@ -21,6 +21,7 @@ pub fn register_import_function(
     engine: &mut Engine,
     state: Arc<Mutex<ParserState>>,
     source_file: Arc<Mutex<PathBuf>>,
+    limits: ParserLimits,
 ) {
     let import_state = Arc::clone(&state);
     let import_source = Arc::clone(&source_file);
@@ -54,6 +55,15 @@ pub fn register_import_function(
                     ))
                 })?;
 
+            // Record the dependency for `compile --emit-deps`
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            import_state
+                .lock()
+                .unwrap()
+                .imported_files
+                .push(resolved_path.clone());
+
             // Read the imported file
             let imported_script = std::fs::read_to_string(&resolved_path).map_err(|e| {
                 Box::new(EvalAltResult::ErrorRuntime(
@@ -69,19 +79,45 @@ pub fn register_import_function(
 
             // Create a new engine instance that shares the same state
             let mut import_engine = Engine::new();
-            import_engine.set_max_operations(10_000);
-            import_engine.set_max_expr_depths(100, 100);
-            import_engine.set_max_call_levels(100);
+            limits.apply(&mut import_engine);
 
             // Register all the same functions
             crate::parser::functions::map::register_map_function(
                 &mut import_engine,
                 Arc::clone(&import_state),
             );
+            crate::parser::functions::map_range::register_map_range_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
             crate::parser::functions::tap_hold::register_tap_hold_function(
                 &mut import_engine,
                 Arc::clone(&import_state),
             );
+            crate::parser::functions::on_release::register_on_release_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
+            crate::parser::functions::layer_toggle::register_layer_toggle_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
+            crate::parser::functions::cycle::register_cycle_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
+            crate::parser::functions::disable::register_disable_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
+            crate::parser::functions::macros::register_macro_functions(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
+            crate::parser::functions::time_window::register_time_window_function(
+                &mut import_engine,
+                Arc::clone(&import_state),
+            );
             crate::parser::functions::conditional::register_when_functions(
                 &mut import_engine,
                 Arc::clone(&import_state),
@@ -97,10 +133,29 @@ pub fn register_import_function(
                 &mut import_engine,
                 Arc::clone(&import_state),
                 Arc::new(Mutex::new(resolved_path.clone())),
+                limits,
             );
 
-            // Execute the imported script
-            import_engine.run(&imported_script).map_err(|e| {
+            // Diagnostics recorded while the file runs point into it
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let parent_file = import_state
+                .lock()
+                .unwrap()
+                .loading_file
+                .replace(resolved_path.clone());
+            let result = import_engine.run(&imported_script);
+            #[allow(clippy::unwrap_used)]
+            {
+                import_state.lock().unwrap().loading_file = parent_file;
+            }
+
+            // Limit errors pass through unchanged so the parser can report
+            // which limit was hit.
+            result.map_err(|e| {
+                if limit_kind(&e).is_some() {
+                    return e;
+                }
                 Box::new(EvalAltResult::ErrorRuntime(
                     format!(
                         "Error executing imported file {}: {}",
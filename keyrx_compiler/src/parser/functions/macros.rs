@@ -0,0 +1,103 @@
+use keyrx_core::config::{BaseKeyMapping, KeyCode, KeyMapping, MacroStep};
+use rhai::{Array, Engine, EvalAltResult};
+use std::sync::{Arc, Mutex};
+
+use crate::parser::core::ParserState;
+use crate::parser::validators::{parse_physical_key, parse_virtual_key};
+
+pub fn register_macro_functions(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    engine.register_type_with_name::<MacroStep>("MacroStep");
+
+    engine.register_fn(
+        "press",
+        |key: &str| -> Result<MacroStep, Box<EvalAltResult>> {
+            Ok(MacroStep::Press(parse_step_key("press", key)?))
+        },
+    );
+    engine.register_fn(
+        "release",
+        |key: &str| -> Result<MacroStep, Box<EvalAltResult>> {
+            Ok(MacroStep::Release(parse_step_key("release", key)?))
+        },
+    );
+    engine.register_fn("wait", |ms: i64| -> Result<MacroStep, Box<EvalAltResult>> {
+        let ms = u16::try_from(ms)
+            .map_err(|_| format!("wait() must be between 0 and {} ms, got: {}", u16::MAX, ms))?;
+        Ok(MacroStep::Wait(ms))
+    });
+
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "macro",
+        move |key: &str, steps: Array| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone.lock().unwrap();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+            let steps = parse_steps(steps)?;
+
+            let base_mapping = BaseKeyMapping::Macro {
+                from: from_key,
+                steps,
+            };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("macro() must be called inside a device() block".into())
+            }
+        },
+    );
+}
+
+/// Parses the VK_ key of a press() or release() step.
+fn parse_step_key(function: &str, key: &str) -> Result<KeyCode, String> {
+    if !key.starts_with("VK_") {
+        return Err(format!(
+            "{}() key must have VK_ prefix, got: {}",
+            function, key
+        ));
+    }
+    parse_virtual_key(key).map_err(|e| format!("Invalid key in {}(): {}", function, e))
+}
+
+/// Checks the steps passed to macro().
+///
+/// A macro must emit at least one key and release every key it presses, so
+/// it never leaves a key held once it finishes.
+fn parse_steps(steps: Array) -> Result<Vec<MacroStep>, String> {
+    let steps = steps
+        .into_iter()
+        .map(|step| {
+            step.try_cast::<MacroStep>()
+                .ok_or_else(|| "macro steps must be press(), release() or wait()".to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !steps.iter().any(|step| !matches!(step, MacroStep::Wait(_))) {
+        return Err("macro steps must press or release at least one key".into());
+    }
+
+    let mut held: Vec<KeyCode> = Vec::new();
+    for step in &steps {
+        match *step {
+            MacroStep::Press(key) if !held.contains(&key) => held.push(key),
+            MacroStep::Release(key) => held.retain(|&k| k != key),
+            _ => {}
+        }
+    }
+    if let Some(key) = held.first() {
+        return Err(format!(
+            "macro leaves {:?} pressed; add a matching release()",
+            key
+        ));
+    }
+
+    Ok(steps)
+}
@@ -1,7 +1,8 @@
 use keyrx_core::config::{BaseKeyMapping, KeyMapping};
-use rhai::{Engine, EvalAltResult};
+use rhai::{Engine, EvalAltResult, NativeCallContext};
 use std::sync::{Arc, Mutex};
 
+use crate::parser::analysis::{is_physical_modifier, ModifierOutputSite};
 use crate::parser::core::ParserState;
 use crate::parser::functions::modifiers::ModifiedKey;
 use crate::parser::validators::{
@@ -12,7 +13,7 @@ pub fn register_map_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>
     let state_clone = Arc::clone(&state);
     engine.register_fn(
         "map",
-        move |from: &str, to: &str| -> Result<(), Box<EvalAltResult>> {
+        move |ctx: NativeCallContext, from: &str, to: &str| -> Result<(), Box<EvalAltResult>> {
             // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
             #[allow(clippy::unwrap_used)]
             let mut state = state_clone.lock().unwrap();
@@ -22,6 +23,20 @@ pub fn register_map_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>
             let base_mapping = if to.starts_with("VK_") {
                 let to_key =
                     parse_virtual_key(to).map_err(|e| format!("Invalid 'to' key: {}", e))?;
+                if is_physical_modifier(to_key) {
+                    // The current device is pushed to `devices` when it ends
+                    let position = ctx.call_position();
+                    let device_index = state.devices.len();
+                    let file = state.loading_file.clone();
+                    state.modifier_output_sites.push(ModifierOutputSite {
+                        device_index,
+                        from: from_key,
+                        to: to_key,
+                        file,
+                        line: position.line().unwrap_or(0),
+                        column: position.position().unwrap_or(0),
+                    });
+                }
                 BaseKeyMapping::Simple {
                     from: from_key,
                     to: to_key,
@@ -0,0 +1,138 @@
+//! `map_range(from, to)`: map a range of keys onto another, key by key.
+//!
+//! Ranges are written `"1".."5"`; the `..` operator is overloaded for strings
+//! to build a [`KeyRange`]. Both ends are included and a range covers every
+//! key code in between, so the expansion is plain `Simple` mappings, the same
+//! as writing the `map()` calls out.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use keyrx_core::config::{BaseKeyMapping, KeyCode, KeyMapping};
+use rhai::{Engine, EvalAltResult, NativeCallContext};
+
+use crate::error::ParseError;
+use crate::parser::analysis::{is_physical_modifier, ModifierOutputSite};
+use crate::parser::core::ParserState;
+use crate::parser::validators::{key_from_code, parse_physical_key, parse_virtual_key};
+
+/// Range of keys written as `"first".."last"`.
+/// Returned by the `..` operator on strings, consumed by map_range().
+#[derive(Clone, Debug)]
+pub struct KeyRange {
+    pub first: String,
+    pub last: String,
+}
+
+impl KeyRange {
+    /// Returns every key from `first` to `last`, parsing both ends with
+    /// `parse`.
+    ///
+    /// Fails if an end isn't a valid key, if `last` comes before `first`, or
+    /// if a key code between them has no key.
+    pub fn expand(
+        &self,
+        parse: fn(&str) -> Result<KeyCode, ParseError>,
+    ) -> Result<Vec<KeyCode>, String> {
+        let first = parse(&self.first).map_err(|e| e.to_string())?;
+        let last = parse(&self.last).map_err(|e| e.to_string())?;
+        if (last as u16) < (first as u16) {
+            return Err(format!("range {} runs backwards", self));
+        }
+
+        let mut keys = vec![first];
+        for code in (first as u16 + 1)..=(last as u16) {
+            let Some(key) = key_from_code(code) else {
+                return Err(format!(
+                    "range {} is not contiguous: no key follows {:?}",
+                    self,
+                    keys[keys.len() - 1]
+                ));
+            };
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+impl fmt::Display for KeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"..\"{}\"", self.first, self.last)
+    }
+}
+
+pub fn register_map_range_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    engine.register_type_with_name::<KeyRange>("KeyRange");
+
+    // "first".."last" - builds a KeyRange
+    engine.register_fn("..", |first: &str, last: &str| KeyRange {
+        first: first.to_string(),
+        last: last.to_string(),
+    });
+
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "map_range",
+        move |ctx: NativeCallContext,
+              from: KeyRange,
+              to: KeyRange|
+              -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone.lock().unwrap();
+            let from_keys = from
+                .expand(parse_physical_key)
+                .map_err(|e| format!("Invalid 'from' range: {}", e))?;
+            let to_keys = to
+                .expand(parse_virtual_key)
+                .map_err(|e| format!("Invalid 'to' range: {}", e))?;
+
+            if from_keys.len() != to_keys.len() {
+                return Err(format!(
+                    "map_range() ranges differ in size: {} has {} keys, {} has {}",
+                    from,
+                    from_keys.len(),
+                    to,
+                    to_keys.len()
+                )
+                .into());
+            }
+
+            // The current device is pushed to `devices` when it ends
+            let position = ctx.call_position();
+            let device_index = state.devices.len();
+            let file = state.loading_file.clone();
+            let mut base_mappings = Vec::with_capacity(from_keys.len());
+            for (from_key, to_key) in from_keys.into_iter().zip(to_keys) {
+                if is_physical_modifier(to_key) {
+                    state.modifier_output_sites.push(ModifierOutputSite {
+                        device_index,
+                        from: from_key,
+                        to: to_key,
+                        file: file.clone(),
+                        line: position.line().unwrap_or(0),
+                        column: position.position().unwrap_or(0),
+                    });
+                }
+                base_mappings.push(BaseKeyMapping::Simple {
+                    from: from_key,
+                    to: to_key,
+                });
+            }
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.extend(base_mappings);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device
+                    .mappings
+                    .extend(base_mappings.into_iter().map(KeyMapping::Base));
+                Ok(())
+            } else {
+                Err("map_range() must be called inside a device() block".into())
+            }
+        },
+    );
+}
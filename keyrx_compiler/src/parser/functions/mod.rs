@@ -1,6 +1,13 @@
 pub mod conditional;
+pub mod cycle;
 pub mod device;
+pub mod disable;
 pub mod import;
+pub mod layer_toggle;
+pub mod macros;
 pub mod map;
+pub mod map_range;
 pub mod modifiers;
+pub mod on_release;
 pub mod tap_hold;
+pub mod time_window;
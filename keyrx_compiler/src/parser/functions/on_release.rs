@@ -0,0 +1,58 @@
+use keyrx_core::config::{BaseKeyMapping, KeyMapping};
+use rhai::{Engine, EvalAltResult};
+use std::sync::{Arc, Mutex};
+
+use crate::parser::core::ParserState;
+use crate::parser::validators::{parse_physical_key, parse_virtual_key};
+
+pub fn register_on_release_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "on_release",
+        move |key: &str, output: &str, threshold_ms: i64| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone.lock().unwrap();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+            if !output.starts_with("VK_") {
+                return Err(format!(
+                    "on_release output parameter must have VK_ prefix, got: {}",
+                    output
+                )
+                .into());
+            }
+            let to_key =
+                parse_virtual_key(output).map_err(|e| format!("Invalid output key: {}", e))?;
+
+            let threshold_ms = u16::try_from(threshold_ms)
+                .ok()
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| {
+                    format!(
+                        "on_release threshold must be between 1 and {} ms, got: {}",
+                        u16::MAX,
+                        threshold_ms
+                    )
+                })?;
+
+            let base_mapping = BaseKeyMapping::OnRelease {
+                from: from_key,
+                to: to_key,
+                threshold_ms,
+            };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("on_release() must be called inside a device() block".into())
+            }
+        },
+    );
+}
@@ -1,4 +1,6 @@
-use keyrx_core::config::{BaseKeyMapping, KeyMapping};
+use keyrx_core::config::{
+    BaseKeyMapping, KeyMapping, TapHoldPolicy, DEFAULT_TAP_HOLD_THRESHOLD_MS,
+};
 use rhai::{Engine, EvalAltResult};
 use std::sync::{Arc, Mutex};
 
@@ -6,6 +8,21 @@ use crate::parser::core::ParserState;
 use crate::parser::validators::{parse_modifier_id, parse_physical_key, parse_virtual_key};
 
 pub fn register_tap_hold_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "tap_hold",
+        move |key: &str, tap: &str, hold: &str| -> Result<(), Box<EvalAltResult>> {
+            add_tap_hold(
+                &state_clone,
+                key,
+                tap,
+                hold,
+                None,
+                TapHoldPolicy::Permissive,
+            )
+        },
+    );
+
     let state_clone = Arc::clone(&state);
     engine.register_fn(
         "tap_hold",
@@ -14,46 +31,141 @@ pub fn register_tap_hold_function(engine: &mut Engine, state: Arc<Mutex<ParserSt
               hold: &str,
               threshold_ms: i64|
               -> Result<(), Box<EvalAltResult>> {
+            add_tap_hold(
+                &state_clone,
+                key,
+                tap,
+                hold,
+                Some(threshold_ms),
+                TapHoldPolicy::Permissive,
+            )
+        },
+    );
+
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "tap_hold",
+        move |key: &str, tap: &str, hold: &str, policy: &str| -> Result<(), Box<EvalAltResult>> {
+            add_tap_hold(&state_clone, key, tap, hold, None, parse_policy(policy)?)
+        },
+    );
+
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "tap_hold",
+        move |key: &str,
+              tap: &str,
+              hold: &str,
+              threshold_ms: i64,
+              policy: &str|
+              -> Result<(), Box<EvalAltResult>> {
+            add_tap_hold(
+                &state_clone,
+                key,
+                tap,
+                hold,
+                Some(threshold_ms),
+                parse_policy(policy)?,
+            )
+        },
+    );
+
+    // Inside a device block this sets the device's default, otherwise the
+    // default for every device
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "tap_hold_threshold",
+        move |threshold_ms: i64| -> Result<(), Box<EvalAltResult>> {
             // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
             #[allow(clippy::unwrap_used)]
             let mut state = state_clone.lock().unwrap();
-            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
 
-            if !tap.starts_with("VK_") {
-                return Err(
-                    format!("tap_hold tap parameter must have VK_ prefix, got: {}", tap).into(),
-                );
-            }
-            let tap_key = parse_virtual_key(tap).map_err(|e| format!("Invalid tap key: {}", e))?;
-
-            if !hold.starts_with("MD_") {
-                return Err(format!(
-                    "tap_hold hold parameter must have MD_ prefix, got: {}",
-                    hold
-                )
-                .into());
-            }
-            let hold_modifier =
-                parse_modifier_id(hold).map_err(|e| format!("Invalid hold modifier: {}", e))?;
-
-            let base_mapping = BaseKeyMapping::TapHold {
-                from: from_key,
-                tap: tap_key,
-                hold_modifier,
-                threshold_ms: threshold_ms as u16,
-            };
-
-            // If we're inside a conditional block, add to the conditional stack
-            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
-                mappings.push(base_mapping);
-                Ok(())
-            } else if let Some(ref mut device) = state.current_device {
-                // Otherwise, add to current device
-                device.mappings.push(KeyMapping::Base(base_mapping));
-                Ok(())
-            } else {
-                Err("tap_hold() must be called inside a device() block".into())
+            let threshold_ms = u16::try_from(threshold_ms)
+                .ok()
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| {
+                    format!(
+                        "tap_hold_threshold must be between 1 and {} ms, got: {}",
+                        u16::MAX,
+                        threshold_ms
+                    )
+                })?;
+
+            match state.current_device {
+                Some(ref mut device) => device.tap_hold_threshold_ms = Some(threshold_ms),
+                None => state.tap_hold_threshold_ms = Some(threshold_ms),
             }
+            Ok(())
         },
     );
 }
+
+fn parse_policy(policy: &str) -> Result<TapHoldPolicy, Box<EvalAltResult>> {
+    match policy {
+        "permissive" => Ok(TapHoldPolicy::Permissive),
+        "timeout" => Ok(TapHoldPolicy::Timeout),
+        _ => Err(format!(
+            "tap_hold policy must be \"permissive\" or \"timeout\", got: {}",
+            policy
+        )
+        .into()),
+    }
+}
+
+/// Adds a tap_hold mapping. Without an explicit `threshold_ms` the mapping
+/// inherits the device default, then the global default, then
+/// [`DEFAULT_TAP_HOLD_THRESHOLD_MS`].
+fn add_tap_hold(
+    state: &Mutex<ParserState>,
+    key: &str,
+    tap: &str,
+    hold: &str,
+    threshold_ms: Option<i64>,
+    policy: TapHoldPolicy,
+) -> Result<(), Box<EvalAltResult>> {
+    // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+    #[allow(clippy::unwrap_used)]
+    let mut state = state.lock().unwrap();
+    let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+    if !tap.starts_with("VK_") {
+        return Err(format!("tap_hold tap parameter must have VK_ prefix, got: {}", tap).into());
+    }
+    let tap_key = parse_virtual_key(tap).map_err(|e| format!("Invalid tap key: {}", e))?;
+
+    if !hold.starts_with("MD_") {
+        return Err(format!(
+            "tap_hold hold parameter must have MD_ prefix, got: {}",
+            hold
+        )
+        .into());
+    }
+    let hold_modifier =
+        parse_modifier_id(hold).map_err(|e| format!("Invalid hold modifier: {}", e))?;
+
+    let threshold_ms = match (threshold_ms, &state.current_device) {
+        (Some(ms), _) => ms as u16,
+        (None, Some(device)) => device.tap_hold_threshold(state.tap_hold_threshold_ms),
+        (None, None) => DEFAULT_TAP_HOLD_THRESHOLD_MS,
+    };
+
+    let base_mapping = BaseKeyMapping::TapHold {
+        from: from_key,
+        tap: tap_key,
+        hold_modifier,
+        threshold_ms,
+        policy,
+    };
+
+    // If we're inside a conditional block, add to the conditional stack
+    if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+        mappings.push(base_mapping);
+        Ok(())
+    } else if let Some(ref mut device) = state.current_device {
+        // Otherwise, add to current device
+        device.mappings.push(KeyMapping::Base(base_mapping));
+        Ok(())
+    } else {
+        Err("tap_hold() must be called inside a device() block".into())
+    }
+}
@@ -0,0 +1,55 @@
+use keyrx_core::config::TimeWindow;
+use rhai::{Engine, EvalAltResult};
+use std::sync::{Arc, Mutex};
+
+use crate::parser::core::ParserState;
+use crate::parser::validators::parse_time_window_id;
+
+pub fn register_time_window_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "time_window",
+        move |id: &str, start: &str, end: &str| -> Result<(), Box<EvalAltResult>> {
+            // SAFETY: Mutex cannot be poisoned - no panic paths while lock is held
+            #[allow(clippy::unwrap_used)]
+            let mut state = state_clone.lock().unwrap();
+            let window_id =
+                parse_time_window_id(id).map_err(|e| format!("Invalid time window: {}", e))?;
+            let window = TimeWindow {
+                start_minute: parse_time_of_day(start)?,
+                end_minute: parse_time_of_day(end)?,
+            };
+
+            let Some(ref mut device) = state.current_device else {
+                return Err("time_window() must be called inside a device() block".into());
+            };
+
+            // Windows are stored by index, so IDs must be defined in order
+            let expected = device.time_windows.len();
+            if window_id as usize != expected {
+                return Err(format!(
+                    "time_window() IDs must be defined in order: expected TW_{:02X}, got {}",
+                    expected, id
+                )
+                .into());
+            }
+            device.time_windows.push(window);
+            Ok(())
+        },
+    );
+}
+
+/// Parses an "HH:MM" 24-hour time into minutes since midnight.
+fn parse_time_of_day(s: &str) -> Result<u16, Box<EvalAltResult>> {
+    let invalid = || format!("Invalid time '{}': expected HH:MM (00:00-23:59)", s);
+    let (hours, minutes) = s.split_once(':').ok_or_else(invalid)?;
+    if minutes.len() != 2 {
+        return Err(invalid().into());
+    }
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid().into());
+    }
+    Ok(hours * 60 + minutes)
+}
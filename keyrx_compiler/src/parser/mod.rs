@@ -1,5 +1,8 @@
+pub mod analysis;
 pub mod core;
 pub mod functions;
 pub mod validators;
 
 pub use core::Parser;
+#[allow(unused_imports)] // Library API; the binary uses the default limits
+pub use core::ParserLimits;
@@ -3,7 +3,7 @@
 //! This module handles serialization of compiled configuration to .krx binary format
 //! using rkyv for zero-copy deserialization at runtime.
 
-use keyrx_core::config::ConfigRoot;
+use keyrx_core::config::{ConfigRoot, Features, Version};
 use sha2::{Digest, Sha256};
 
 use crate::error::{DeserializeError, SerializeError};
@@ -13,12 +13,32 @@ use crate::error::{DeserializeError, SerializeError};
 pub const KRX_MAGIC: [u8; 4] = [0x4B, 0x52, 0x58, 0x0A];
 
 /// Current KRX format version
+///
+/// Version 2 added exclusion patterns to device identifiers, which changes
+/// the archive layout; version 1 files must be recompiled.
+///
+/// Version 3 added the feature stamp. The archive layout has changed since
+/// version 1, so files of any older version are refused with
+/// [`DeserializeError::OutdatedFormat`] and must be recompiled.
 #[allow(dead_code)] // Will be used by CLI in task 18
-pub const KRX_VERSION: u32 = 1;
+pub const KRX_VERSION: u32 = 3;
 
 /// Size of the KRX file header in bytes
 #[allow(dead_code)] // Will be used by CLI in task 18
-pub const HEADER_SIZE: usize = 48;
+pub const HEADER_SIZE: usize = 56;
+
+/// Size of the header of files older than version 3, which have no feature
+/// stamp
+pub const LEGACY_HEADER_SIZE: usize = 48;
+
+/// Returns the header size of a file with the given format version.
+pub fn header_size(format_version: u32) -> usize {
+    if format_version < 3 {
+        LEGACY_HEADER_SIZE
+    } else {
+        HEADER_SIZE
+    }
+}
 
 /// Serializes a ConfigRoot to the .krx binary format.
 ///
@@ -27,6 +47,7 @@ pub const HEADER_SIZE: usize = 48;
 /// - 4 bytes: Format version (KRX_VERSION)
 /// - 32 bytes: SHA256 hash of data section
 /// - 8 bytes: Size of data section (u64, little-endian)
+/// - 8 bytes: [`Features`] the config requires (u64 bitmask, little-endian)
 /// - N bytes: rkyv-serialized ConfigRoot data
 ///
 /// # Arguments
@@ -51,7 +72,7 @@ pub fn serialize(config: &ConfigRoot) -> Result<Vec<u8>, SerializeError> {
     // Get data size as u64
     let size = data.len() as u64;
 
-    // Build header (48 bytes total)
+    // Build header (56 bytes total)
     let mut output = Vec::with_capacity(HEADER_SIZE + data.len());
 
     // Write magic bytes (4 bytes)
@@ -66,6 +87,10 @@ pub fn serialize(config: &ConfigRoot) -> Result<Vec<u8>, SerializeError> {
     // Write size (8 bytes, little-endian)
     output.extend_from_slice(&size.to_le_bytes());
 
+    // Write required features (8 bytes, little-endian)
+    let features = Features::required_by(config);
+    output.extend_from_slice(&features.bits().to_le_bytes());
+
     // Write data
     output.extend_from_slice(&data);
 
@@ -78,7 +103,9 @@ pub fn serialize(config: &ConfigRoot) -> Result<Vec<u8>, SerializeError> {
 /// 1. Verifies magic bytes match KRX_MAGIC
 /// 2. Verifies version matches KRX_VERSION
 /// 3. Computes SHA256 hash of data and compares with embedded hash
-/// 4. Validates rkyv archive structure
+/// 4. Verifies this build supports every feature the config requires
+/// 5. Validates rkyv archive structure
+/// 6. Verifies the config version is compatible with this build
 ///
 /// # Arguments
 /// * `bytes` - The complete .krx file data
@@ -90,16 +117,18 @@ pub fn serialize(config: &ConfigRoot) -> Result<Vec<u8>, SerializeError> {
 /// Returns DeserializeError if:
 /// - File is too small to contain header
 /// - Magic bytes don't match
-/// - Version doesn't match
+/// - Version is unknown, or older and needs recompiling
 /// - Hash doesn't match (data corruption)
+/// - The config requires features this build does not support
 /// - rkyv validation fails
+/// - The config was compiled for an incompatible config version
 #[allow(dead_code)] // Will be used by CLI in task 18
 pub fn deserialize(bytes: &[u8]) -> Result<&rkyv::Archived<ConfigRoot>, DeserializeError> {
     // Verify minimum size
-    if bytes.len() < HEADER_SIZE {
+    if bytes.len() < LEGACY_HEADER_SIZE {
         return Err(DeserializeError::RkyvError(format!(
             "File too small: expected at least {} bytes, got {}",
-            HEADER_SIZE,
+            LEGACY_HEADER_SIZE,
             bytes.len()
         )));
     }
@@ -107,13 +136,22 @@ pub fn deserialize(bytes: &[u8]) -> Result<&rkyv::Archived<ConfigRoot>, Deserial
     // Validate magic bytes
     validate_magic(&bytes[0..4])?;
 
-    // Validate version
+    // Validate version before reading the rest of the header, so an old
+    // file is named as such rather than failing on its layout
     validate_version(&bytes[4..8])?;
+    if bytes.len() < HEADER_SIZE {
+        return Err(DeserializeError::RkyvError(format!(
+            "File too small: expected at least {} bytes, got {}",
+            HEADER_SIZE,
+            bytes.len()
+        )));
+    }
 
     // Extract header fields after validation
     let embedded_hash = &bytes[8..40];
     let size_bytes = &bytes[40..48];
-    let data = &bytes[48..];
+    let features = read_features(&bytes[48..HEADER_SIZE])?;
+    let data = &bytes[HEADER_SIZE..];
 
     // Verify size matches actual data length
     validate_size(size_bytes, 8, "size field")?;
@@ -162,6 +200,15 @@ pub fn deserialize(bytes: &[u8]) -> Result<&rkyv::Archived<ConfigRoot>, Deserial
         });
     }
 
+    // A config using a feature added after this build would fail archive
+    // validation with an opaque error, so name the missing features first
+    let unsupported = features.unsupported_by(Features::SUPPORTED);
+    if !unsupported.is_empty() {
+        return Err(DeserializeError::UnsupportedFeatures {
+            features: unsupported,
+        });
+    }
+
     // Deserialize using rkyv's safe check_archived_root
     //
     // SECURITY: This function uses check_archived_root which validates all archive
@@ -182,9 +229,21 @@ pub fn deserialize(bytes: &[u8]) -> Result<&rkyv::Archived<ConfigRoot>, Deserial
     //
     // Performance: CheckBytes validation has minimal overhead (linear scan of data)
     // and is only paid during deserialization, not during runtime access.
-    rkyv::check_archived_root::<ConfigRoot>(data).map_err(|e| {
+    let config = rkyv::check_archived_root::<ConfigRoot>(data).map_err(|e| {
         DeserializeError::RkyvError(format!("Failed to validate rkyv archive structure: {}", e))
-    })
+    })?;
+
+    // Reject configs compiled for a schema this build does not understand
+    let config_version = Version::from(&config.version);
+    let runtime_version = Version::current();
+    if !config_version.is_compatible_with(runtime_version) {
+        return Err(DeserializeError::IncompatibleVersion {
+            config: config_version,
+            runtime: runtime_version,
+        });
+    }
+
+    Ok(config)
 }
 
 /// Validates magic number in binary format.
@@ -222,7 +281,8 @@ fn validate_magic(bytes: &[u8]) -> Result<(), DeserializeError> {
 /// # Errors
 ///
 /// Returns `DeserializeError::InvalidSize` if buffer too small.
-/// Returns `DeserializeError::VersionMismatch` if version doesn't match.
+/// Returns `DeserializeError::OutdatedFormat` if the file predates KRX_VERSION.
+/// Returns `DeserializeError::VersionMismatch` if the version is unknown.
 /// Returns `DeserializeError::CorruptedData` if slice conversion fails.
 fn validate_version(bytes: &[u8]) -> Result<(), DeserializeError> {
     if bytes.len() < 4 {
@@ -238,6 +298,9 @@ fn validate_version(bytes: &[u8]) -> Result<(), DeserializeError> {
     })?;
 
     let found_version = u32::from_le_bytes(version_bytes);
+    if (1..KRX_VERSION).contains(&found_version) {
+        return Err(DeserializeError::OutdatedFormat { got: found_version });
+    }
     if found_version != KRX_VERSION {
         return Err(DeserializeError::VersionMismatch {
             expected: KRX_VERSION,
@@ -248,6 +311,18 @@ fn validate_version(bytes: &[u8]) -> Result<(), DeserializeError> {
     Ok(())
 }
 
+/// Reads the feature stamp.
+///
+/// # Errors
+///
+/// Returns `DeserializeError::CorruptedData` if the stamp is truncated.
+fn read_features(bytes: &[u8]) -> Result<Features, DeserializeError> {
+    let bits: [u8; 8] = bytes
+        .try_into()
+        .map_err(|_| DeserializeError::CorruptedData("Failed to read feature stamp".to_string()))?;
+    Ok(Features::from_bits(u64::from_le_bytes(bits)))
+}
+
 /// Validates that buffer has expected size.
 ///
 /// # Errors
@@ -278,8 +353,13 @@ mod tests {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "Test Device".to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -326,7 +406,7 @@ mod tests {
         let archived = deserialize(&bytes).expect("Deserialization failed");
 
         // Verify data matches
-        assert_eq!(archived.version.major, 1);
+        assert_eq!(archived.version.major, 2);
         assert_eq!(archived.version.minor, 0);
         assert_eq!(archived.version.patch, 0);
         assert_eq!(archived.devices.len(), 1);
@@ -375,6 +455,44 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_deserialize_rejects_newer_config_version() {
+        let mut config = create_test_config();
+        config.version = Version {
+            major: 3,
+            minor: 0,
+            patch: 0,
+        };
+        let bytes = serialize(&config).unwrap();
+
+        let Err(err) = deserialize(&bytes) else {
+            panic!("expected an incompatible version error");
+        };
+        assert!(matches!(err, DeserializeError::IncompatibleVersion { .. }));
+        let message = err.to_string();
+        assert!(message.contains("config version 3.0.0 requires a newer keyrx"));
+        assert!(message.contains("recompile"));
+    }
+
+    #[test]
+    fn test_deserialize_hints_recompiling_1_x_configs() {
+        let mut config = create_test_config();
+        config.version = Version {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        };
+        let bytes = serialize(&config).unwrap();
+
+        let Err(err) = deserialize(&bytes) else {
+            panic!("expected an incompatible version error");
+        };
+        assert!(matches!(err, DeserializeError::IncompatibleVersion { .. }));
+        let message = err.to_string();
+        assert!(message.contains("config version 1.0.0 is from an older keyrx"));
+        assert!(message.contains("recompile it with your installed compiler"));
+    }
+
     #[test]
     fn test_deserialize_validates_hash() {
         let config = create_test_config();
@@ -430,8 +548,64 @@ mod tests {
     #[test]
     fn test_header_constants() {
         assert_eq!(KRX_MAGIC, [0x4B, 0x52, 0x58, 0x0A]);
-        assert_eq!(KRX_VERSION, 1);
-        assert_eq!(HEADER_SIZE, 48);
+        assert_eq!(KRX_VERSION, 3);
+        assert_eq!(HEADER_SIZE, 56);
+        assert_eq!(header_size(2), 48);
+    }
+
+    #[test]
+    fn test_serialize_stamps_required_features() {
+        let mut config = create_test_config();
+        let bytes = serialize(&config).unwrap();
+        assert_eq!(u64::from_le_bytes(bytes[48..56].try_into().unwrap()), 0);
+
+        config.devices[0]
+            .mappings
+            .push(KeyMapping::Base(BaseKeyMapping::Cycle {
+                from: KeyCode::F1,
+                outputs: vec![KeyCode::A, KeyCode::B],
+                reset_ms: 500,
+            }));
+        let bytes = serialize(&config).unwrap();
+        let stamp = Features::from_bits(u64::from_le_bytes(bytes[48..56].try_into().unwrap()));
+        assert_eq!(stamp, Features::CYCLE);
+        assert!(deserialize(&bytes).is_ok());
+    }
+
+    #[test]
+    fn test_deserialize_names_unsupported_features() {
+        let config = create_test_config();
+        let mut bytes = serialize(&config).unwrap();
+
+        // Stamp a feature from a newer compiler
+        bytes[48..56].copy_from_slice(&(1u64 << 40).to_le_bytes());
+
+        let Err(err) = deserialize(&bytes) else {
+            panic!("expected an unsupported features error");
+        };
+        assert_eq!(
+            err,
+            DeserializeError::UnsupportedFeatures {
+                features: Features::from_bits(1 << 40),
+            }
+        );
+        let message = err.to_string();
+        assert!(message.contains("this config uses unknown feature 40"));
+        assert!(message.contains("upgrade keyrx"));
+    }
+
+    #[test]
+    fn test_deserialize_rejects_files_from_older_compilers() {
+        // Compiled from uat_tests/test1_simple.rhai before the archive
+        // layout changed
+        let v1 = include_bytes!("../tests/fixtures/v1_simple.krx");
+        assert_eq!(v1[4..8], 1u32.to_le_bytes());
+
+        let Err(err) = deserialize(v1) else {
+            panic!("expected an outdated format error");
+        };
+        assert_eq!(err, DeserializeError::OutdatedFormat { got: 1 });
+        assert!(err.to_string().contains("recompile this config"));
     }
 
     /// Test serialization roundtrip for conditional ModifiedOutput mapping
@@ -443,6 +617,7 @@ mod tests {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "Test Device".to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![
                     KeyMapping::modifier(KeyCode::CapsLock, 0),
@@ -458,6 +633,10 @@ mod tests {
                         }],
                     ),
                 ],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -517,4 +696,159 @@ mod tests {
             _ => panic!("Expected Conditional mapping"),
         }
     }
+
+    /// Test serialization roundtrip for a composite (all AND none) condition
+    #[test]
+    fn test_round_trip_composite_condition() {
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: vec![DeviceConfig {
+                identifier: DeviceIdentifier {
+                    pattern: "*".to_string(),
+                    excludes: Vec::new(),
+                },
+                mappings: vec![KeyMapping::conditional(
+                    Condition::Composite {
+                        all: vec![ConditionItem::ModifierActive(0)],
+                        none: vec![ConditionItem::LockActive(1), ConditionItem::LockActive(2)],
+                    },
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                )],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
+            }],
+            metadata: Metadata {
+                compilation_timestamp: 1234567890,
+                compiler_version: "1.0.0".to_string(),
+                source_hash: "test_hash".to_string(),
+            },
+        };
+
+        let bytes = serialize(&config).expect("Serialization failed");
+        let archived = deserialize(&bytes).expect("Deserialization failed");
+
+        match &archived.devices[0].mappings[0] {
+            rkyv::Archived::<KeyMapping>::Conditional { condition, .. } => match condition {
+                rkyv::Archived::<Condition>::Composite { all, none } => {
+                    assert_eq!(all.len(), 1);
+                    assert_eq!(none.len(), 2);
+                    assert!(matches!(
+                        none[1],
+                        rkyv::Archived::<ConditionItem>::LockActive(2)
+                    ));
+                }
+                _ => panic!("Expected Composite condition"),
+            },
+            _ => panic!("Expected Conditional mapping"),
+        }
+    }
+
+    /// Test serialization roundtrip for an OR (any active) condition
+    #[test]
+    fn test_round_trip_any_active_condition() {
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: vec![DeviceConfig {
+                identifier: DeviceIdentifier {
+                    pattern: "*".to_string(),
+                    excludes: Vec::new(),
+                },
+                mappings: vec![KeyMapping::conditional(
+                    Condition::AnyActive(vec![
+                        ConditionItem::ModifierActive(0),
+                        ConditionItem::LockActive(1),
+                    ]),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                )],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
+            }],
+            metadata: Metadata {
+                compilation_timestamp: 1234567890,
+                compiler_version: "1.0.0".to_string(),
+                source_hash: "test_hash".to_string(),
+            },
+        };
+
+        let bytes = serialize(&config).expect("Serialization failed");
+        let archived = deserialize(&bytes).expect("Deserialization failed");
+
+        match &archived.devices[0].mappings[0] {
+            rkyv::Archived::<KeyMapping>::Conditional { condition, .. } => match condition {
+                rkyv::Archived::<Condition>::AnyActive(items) => {
+                    assert_eq!(items.len(), 2);
+                    assert!(matches!(
+                        items[1],
+                        rkyv::Archived::<ConditionItem>::LockActive(1)
+                    ));
+                }
+                _ => panic!("Expected AnyActive condition"),
+            },
+            _ => panic!("Expected Conditional mapping"),
+        }
+    }
+
+    /// Test serialization roundtrip for a nested negation (validates the boxed variant)
+    #[test]
+    fn test_round_trip_negate_condition() {
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: vec![DeviceConfig {
+                identifier: DeviceIdentifier {
+                    pattern: "*".to_string(),
+                    excludes: Vec::new(),
+                },
+                mappings: vec![KeyMapping::conditional(
+                    Condition::Negate(Box::new(Condition::Negate(Box::new(Condition::AllActive(
+                        vec![
+                            ConditionItem::ModifierActive(0),
+                            ConditionItem::LockActive(1),
+                        ],
+                    ))))),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                )],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
+            }],
+            metadata: Metadata {
+                compilation_timestamp: 1234567890,
+                compiler_version: "1.0.0".to_string(),
+                source_hash: "test_hash".to_string(),
+            },
+        };
+
+        let bytes = serialize(&config).expect("Serialization failed");
+        let archived = deserialize(&bytes).expect("Deserialization failed");
+
+        match &archived.devices[0].mappings[0] {
+            rkyv::Archived::<KeyMapping>::Conditional { condition, .. } => match condition {
+                rkyv::Archived::<Condition>::Negate(outer) => match &**outer {
+                    rkyv::Archived::<Condition>::Negate(inner) => match &**inner {
+                        rkyv::Archived::<Condition>::AllActive(items) => {
+                            assert_eq!(items.len(), 2);
+                        }
+                        _ => panic!("Expected AllActive inside the negations"),
+                    },
+                    _ => panic!("Expected nested Negate condition"),
+                },
+                _ => panic!("Expected Negate condition"),
+            },
+            _ => panic!("Expected Conditional mapping"),
+        }
+    }
 }
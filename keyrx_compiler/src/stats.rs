@@ -0,0 +1,357 @@
+//! Summary statistics of a parsed configuration.
+//!
+//! Everything is computed from the [`ConfigRoot`] alone, before it is
+//! written anywhere, so the same numbers can back `compile --emit-stats` and
+//! a web endpoint.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use keyrx_core::config::{BaseKeyMapping, ConfigRoot, DeviceConfig, KeyMapping};
+use serde::Serialize;
+
+use crate::error::SerializeError;
+use crate::serialize::HEADER_SIZE;
+
+/// Number of mappings of each kind.
+///
+/// The per-kind counts include mappings inside conditional blocks;
+/// `conditional` counts those mappings once more.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MappingCounts {
+    pub simple: usize,
+    pub modifier: usize,
+    pub lock: usize,
+    pub tap_hold: usize,
+    pub modified_output: usize,
+    pub on_release: usize,
+    pub layer_toggle: usize,
+    pub cycle: usize,
+    pub disable: usize,
+    #[serde(rename = "macro")]
+    pub macros: usize,
+    /// Mappings inside conditional (`when`) blocks
+    pub conditional: usize,
+    /// Conditional blocks
+    pub conditional_blocks: usize,
+}
+
+impl MappingCounts {
+    /// Total number of mappings.
+    pub fn total(&self) -> usize {
+        self.simple
+            + self.modifier
+            + self.lock
+            + self.tap_hold
+            + self.modified_output
+            + self.on_release
+            + self.layer_toggle
+            + self.cycle
+            + self.disable
+            + self.macros
+    }
+
+    fn add_mapping(&mut self, mapping: &BaseKeyMapping) {
+        match mapping {
+            BaseKeyMapping::Simple { .. } => self.simple += 1,
+            BaseKeyMapping::Modifier { .. } => self.modifier += 1,
+            BaseKeyMapping::Lock { .. } => self.lock += 1,
+            BaseKeyMapping::TapHold { .. } => self.tap_hold += 1,
+            BaseKeyMapping::ModifiedOutput { .. } => self.modified_output += 1,
+            BaseKeyMapping::OnRelease { .. } => self.on_release += 1,
+            BaseKeyMapping::LayerToggle { .. } => self.layer_toggle += 1,
+            BaseKeyMapping::Cycle { .. } => self.cycle += 1,
+            BaseKeyMapping::Disable { .. } => self.disable += 1,
+            BaseKeyMapping::Macro { .. } => self.macros += 1,
+        }
+    }
+
+    fn add(&mut self, other: &MappingCounts) {
+        self.simple += other.simple;
+        self.modifier += other.modifier;
+        self.lock += other.lock;
+        self.tap_hold += other.tap_hold;
+        self.modified_output += other.modified_output;
+        self.on_release += other.on_release;
+        self.layer_toggle += other.layer_toggle;
+        self.cycle += other.cycle;
+        self.disable += other.disable;
+        self.macros += other.macros;
+        self.conditional += other.conditional;
+        self.conditional_blocks += other.conditional_blocks;
+    }
+}
+
+/// Statistics of one device block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceStats {
+    /// Device pattern (e.g. "*")
+    pub pattern: String,
+    pub mappings: MappingCounts,
+    pub time_windows: usize,
+    /// Size of the device block serialized on its own
+    pub estimated_bytes: usize,
+}
+
+/// Estimated `.krx` size breakdown in bytes.
+///
+/// `total` is exact; the device sizes are measured by serializing each
+/// device on its own, so `other` (metadata, version and alignment) absorbs
+/// the difference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeEstimate {
+    pub header: usize,
+    pub devices: usize,
+    pub other: usize,
+    pub total: usize,
+}
+
+/// Summary of a configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigStats {
+    pub devices: Vec<DeviceStats>,
+    /// Mapping counts over all devices
+    pub mappings: MappingCounts,
+    /// Modifier IDs set by modifier, tap-hold and layer-toggle mappings
+    pub modifier_ids: Vec<u8>,
+    /// Lock IDs toggled by lock mappings
+    pub lock_ids: Vec<u8>,
+    pub size: SizeEstimate,
+}
+
+/// Computes the statistics of a configuration.
+///
+/// # Errors
+///
+/// Returns `SerializeError::RkyvError` if the configuration cannot be
+/// serialized to measure its size.
+pub fn config_stats(config: &ConfigRoot) -> Result<ConfigStats, SerializeError> {
+    let mut devices = Vec::with_capacity(config.devices.len());
+    let mut mappings = MappingCounts::default();
+    let mut modifier_ids = BTreeSet::new();
+    let mut lock_ids = BTreeSet::new();
+
+    for device in &config.devices {
+        let mut counts = MappingCounts::default();
+        for mapping in &device.mappings {
+            match mapping {
+                KeyMapping::Base(base) => counts.add_mapping(base),
+                KeyMapping::Conditional { mappings, .. } => {
+                    counts.conditional_blocks += 1;
+                    counts.conditional += mappings.len();
+                    for base in mappings {
+                        counts.add_mapping(base);
+                    }
+                }
+            }
+        }
+        for base in base_mappings(device) {
+            match base {
+                BaseKeyMapping::Modifier { modifier_id, .. } => {
+                    modifier_ids.insert(*modifier_id);
+                }
+                BaseKeyMapping::TapHold { hold_modifier, .. } => {
+                    modifier_ids.insert(*hold_modifier);
+                }
+                BaseKeyMapping::LayerToggle { layer_id, .. } => {
+                    modifier_ids.insert(*layer_id);
+                }
+                BaseKeyMapping::Lock { lock_id, .. } => {
+                    lock_ids.insert(*lock_id);
+                }
+                _ => {}
+            }
+        }
+
+        mappings.add(&counts);
+        devices.push(DeviceStats {
+            pattern: device.identifier.pattern.clone(),
+            mappings: counts,
+            time_windows: device.time_windows.len(),
+            estimated_bytes: archived_size(device)?,
+        });
+    }
+
+    let total = HEADER_SIZE + archived_size(config)?;
+    let device_bytes: usize = devices.iter().map(|device| device.estimated_bytes).sum();
+    let size = SizeEstimate {
+        header: HEADER_SIZE,
+        devices: device_bytes,
+        other: total.saturating_sub(HEADER_SIZE + device_bytes),
+        total,
+    };
+
+    Ok(ConfigStats {
+        devices,
+        mappings,
+        modifier_ids: modifier_ids.into_iter().collect(),
+        lock_ids: lock_ids.into_iter().collect(),
+        size,
+    })
+}
+
+/// Iterates over all base mappings of a device, including conditional ones.
+fn base_mappings(device: &DeviceConfig) -> impl Iterator<Item = &BaseKeyMapping> {
+    device.mappings.iter().flat_map(|mapping| match mapping {
+        KeyMapping::Base(base) => std::slice::from_ref(base).iter(),
+        KeyMapping::Conditional { mappings, .. } => mappings.iter(),
+    })
+}
+
+fn archived_size<T>(value: &T) -> Result<usize, SerializeError>
+where
+    T: rkyv::Serialize<rkyv::ser::serializers::AllocSerializer<1024>>,
+{
+    rkyv::to_bytes::<_, 1024>(value)
+        .map(|bytes| bytes.len())
+        .map_err(|e| SerializeError::RkyvError(e.to_string()))
+}
+
+impl fmt::Display for ConfigStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Devices: {}", self.devices.len())?;
+        for device in &self.devices {
+            writeln!(
+                f,
+                "  [{}] {} mappings, {} time windows, ~{} bytes",
+                device.pattern,
+                device.mappings.total(),
+                device.time_windows,
+                device.estimated_bytes
+            )?;
+        }
+
+        let counts = &self.mappings;
+        writeln!(f, "Mappings: {}", counts.total())?;
+        for (name, count) in [
+            ("simple", counts.simple),
+            ("modifier", counts.modifier),
+            ("lock", counts.lock),
+            ("tap-hold", counts.tap_hold),
+            ("modified output", counts.modified_output),
+            ("on-release", counts.on_release),
+            ("layer toggle", counts.layer_toggle),
+            ("cycle", counts.cycle),
+            ("disable", counts.disable),
+            ("macro", counts.macros),
+        ] {
+            if count > 0 {
+                writeln!(f, "  {}: {}", name, count)?;
+            }
+        }
+        writeln!(
+            f,
+            "  conditional: {} in {} blocks",
+            counts.conditional, counts.conditional_blocks
+        )?;
+
+        let ids = |prefix: &str, ids: &[u8]| {
+            if ids.is_empty() {
+                return "none".to_string();
+            }
+            ids.iter()
+                .map(|id| format!("{}_{:02X}", prefix, id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "Modifier IDs: {}", ids("MD", &self.modifier_ids))?;
+        writeln!(f, "Lock IDs: {}", ids("LK", &self.lock_ids))?;
+
+        writeln!(f, "Estimated .krx size: {} bytes", self.size.total)?;
+        writeln!(f, "  header: {}", self.size.header)?;
+        writeln!(f, "  devices: {}", self.size.devices)?;
+        write!(f, "  metadata and padding: {}", self.size.other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::test_util::ConfigRootBuilder;
+    use keyrx_core::config::{Condition, KeyCode, TapHoldPolicy};
+
+    #[test]
+    fn test_counts_mappings_and_ids() {
+        let config = ConfigRootBuilder::new()
+            .devices(vec![
+                DeviceConfig::new(
+                    "*",
+                    vec![
+                        KeyMapping::simple(KeyCode::A, KeyCode::B),
+                        KeyMapping::modifier(KeyCode::CapsLock, 1),
+                        KeyMapping::lock(KeyCode::ScrollLock, 2),
+                        KeyMapping::conditional(
+                            Condition::ModifierActive(1),
+                            vec![
+                                BaseKeyMapping::Simple {
+                                    from: KeyCode::H,
+                                    to: KeyCode::Left,
+                                },
+                                BaseKeyMapping::TapHold {
+                                    from: KeyCode::Space,
+                                    tap: KeyCode::Space,
+                                    hold_modifier: 0,
+                                    threshold_ms: 200,
+                                    policy: TapHoldPolicy::Permissive,
+                                },
+                            ],
+                        ),
+                    ],
+                ),
+                DeviceConfig::new(
+                    "usb-numpad*",
+                    vec![KeyMapping::simple(KeyCode::Numpad1, KeyCode::F13)],
+                ),
+            ])
+            .build();
+
+        let stats = config_stats(&config).unwrap();
+        assert_eq!(stats.devices.len(), 2);
+        assert_eq!(stats.devices[0].mappings.total(), 5);
+        assert_eq!(stats.devices[1].mappings.total(), 1);
+
+        assert_eq!(stats.mappings.simple, 3);
+        assert_eq!(stats.mappings.modifier, 1);
+        assert_eq!(stats.mappings.lock, 1);
+        assert_eq!(stats.mappings.tap_hold, 1);
+        assert_eq!(stats.mappings.conditional, 2);
+        assert_eq!(stats.mappings.conditional_blocks, 1);
+
+        assert_eq!(stats.modifier_ids, vec![0, 1]);
+        assert_eq!(stats.lock_ids, vec![2]);
+    }
+
+    #[test]
+    fn test_size_total_matches_serialized_file() {
+        let config = ConfigRootBuilder::new()
+            .devices(vec![DeviceConfig::new(
+                "*",
+                vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+            )])
+            .build();
+
+        let stats = config_stats(&config).unwrap();
+        let bytes = crate::serialize::serialize(&config).unwrap();
+        assert_eq!(stats.size.total, bytes.len());
+        assert!(stats.size.devices > 0);
+        assert_eq!(
+            stats.size.header + stats.size.devices + stats.size.other,
+            stats.size.total
+        );
+    }
+
+    #[test]
+    fn test_display_lists_ids() {
+        let config = ConfigRootBuilder::new()
+            .devices(vec![DeviceConfig::new(
+                "*",
+                vec![KeyMapping::modifier(KeyCode::CapsLock, 0x0A)],
+            )])
+            .build();
+
+        let text = config_stats(&config).unwrap().to_string();
+        assert!(text.contains("Modifier IDs: MD_0A"));
+        assert!(text.contains("Lock IDs: none"));
+        assert!(text.contains("  modifier: 1"));
+    }
+}
@@ -145,6 +145,30 @@ fn test_compile_default_output() {
     );
 }
 
+#[test]
+fn test_compile_wasm_json_target() {
+    let temp_dir = setup_test_dir();
+    let input = create_simple_rhai_config(&temp_dir, "config.rhai");
+    let default_output = temp_dir.path().join("config.json");
+
+    get_binary()
+        .arg("compile")
+        .arg(&input)
+        .arg("--target")
+        .arg("wasm-json")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully compiled"));
+
+    // Output is the ConfigRoot as plain JSON, defaulting to a .json file
+    let json = fs::read_to_string(&default_output).expect("Default .json output should exist");
+    let config: keyrx_core::config::ConfigRoot =
+        serde_json::from_str(&json).expect("Output should be ConfigRoot JSON");
+    assert_eq!(config.devices.len(), 1);
+    assert!(!temp_dir.path().join("config.krx").exists());
+}
+
 #[test]
 fn test_compile_advanced_config() {
     let temp_dir = setup_test_dir();
@@ -264,6 +288,32 @@ fn test_verify_valid_file() {
         ));
 }
 
+#[test]
+fn test_verify_deep_valid_file() {
+    let temp_dir = setup_test_dir();
+    let input = create_simple_rhai_config(&temp_dir, "config.rhai");
+    let krx_file = temp_dir.path().join("config.krx");
+
+    get_binary()
+        .arg("compile")
+        .arg(&input)
+        .arg("-o")
+        .arg(&krx_file)
+        .assert()
+        .success();
+
+    get_binary()
+        .arg("verify")
+        .arg("--deep")
+        .arg(&krx_file)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "✓ IDs, time windows and cycles valid",
+        ))
+        .stderr(predicate::str::contains("✓ Verification passed"));
+}
+
 #[test]
 fn test_verify_missing_file() {
     let temp_dir = setup_test_dir();
@@ -505,6 +555,101 @@ fn test_hash_verify_valid() {
         .stderr(predicate::str::contains("✓ Hash matches"));
 }
 
+#[test]
+fn test_hash_verify_flag_before_file() {
+    let temp_dir = setup_test_dir();
+    let input = create_simple_rhai_config(&temp_dir, "config.rhai");
+    let krx_file = temp_dir.path().join("config.krx");
+
+    get_binary()
+        .arg("compile")
+        .arg(&input)
+        .arg("-o")
+        .arg(&krx_file)
+        .assert()
+        .success();
+
+    // `--verify` takes no value, so the file path after it stays positional
+    get_binary()
+        .arg("hash")
+        .arg("--verify")
+        .arg(&krx_file)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("✓ Hash matches"));
+}
+
+#[test]
+fn test_hash_verify_expected_value() {
+    let temp_dir = setup_test_dir();
+    let input = create_simple_rhai_config(&temp_dir, "config.rhai");
+    let krx_file = temp_dir.path().join("config.krx");
+
+    get_binary()
+        .arg("compile")
+        .arg(&input)
+        .arg("-o")
+        .arg(&krx_file)
+        .assert()
+        .success();
+
+    let bytes = fs::read(&krx_file).expect("Failed to read file");
+    let expected = hex::encode(&bytes[8..40]);
+
+    get_binary()
+        .arg("hash")
+        .arg(&krx_file)
+        .arg("--verify")
+        .arg("--expected")
+        .arg(&expected)
+        .arg("--source")
+        .arg(&input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("✓ Hash matches expected value"))
+        .stderr(predicate::str::contains("✓ Source hash matches"));
+
+    get_binary()
+        .arg("hash")
+        .arg(&krx_file)
+        .arg("--expected")
+        .arg("0".repeat(64))
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "✗ Hash does not match expected value",
+        ));
+}
+
+#[test]
+fn test_hash_source_mismatch() {
+    let temp_dir = setup_test_dir();
+    let input = create_simple_rhai_config(&temp_dir, "config.rhai");
+    let krx_file = temp_dir.path().join("config.krx");
+
+    get_binary()
+        .arg("compile")
+        .arg(&input)
+        .arg("-o")
+        .arg(&krx_file)
+        .assert()
+        .success();
+
+    // A different script than the one that was compiled
+    let other = create_advanced_rhai_config(&temp_dir, "other.rhai");
+
+    get_binary()
+        .arg("hash")
+        .arg(&krx_file)
+        .arg("--source")
+        .arg(&other)
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("✗ Source hash mismatch"));
+}
+
 #[test]
 fn test_hash_verify_corrupted() {
     let temp_dir = setup_test_dir();
@@ -522,8 +667,9 @@ fn test_hash_verify_corrupted() {
 
     // Corrupt the data section (not the hash)
     let mut bytes = fs::read(&krx_file).expect("Failed to read file");
-    if bytes.len() > 48 {
-        bytes[50] = !bytes[50]; // Flip a bit in the data section
+    // The data section starts after the 56-byte header
+    if bytes.len() > 60 {
+        bytes[60] = !bytes[60]; // Flip a bit in the data section
         fs::write(&krx_file, bytes).expect("Failed to write corrupted file");
     }
 
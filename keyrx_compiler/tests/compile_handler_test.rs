@@ -1,9 +1,16 @@
 //! Integration test for the compile subcommand handler.
 
 use std::fs;
+use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
-use keyrx_compiler::cli::compile::handle_compile;
+use keyrx_compiler::cli::compile::{
+    compile_krx, format_dep_rule, handle_compile, handle_compile_with_base,
+    handle_compile_with_deps, CompileError, WarningPolicy,
+};
+use keyrx_compiler::serialize::deserialize;
+use keyrx_core::config::{ConfigRoot, KeyCode, KeyMapping};
+use rkyv::Deserialize;
 
 #[test]
 fn test_handle_compile_success() {
@@ -234,3 +241,248 @@ device_end();
     let bytes = fs::read(&output_path).unwrap();
     assert!(bytes.len() > 48, "Output file should contain header + data");
 }
+
+#[test]
+fn test_handle_compile_emit_deps_lists_transitive_loads() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_path = temp_dir.path().join("main.rhai");
+    let output_path = temp_dir.path().join("main.krx");
+    let deps_path = temp_dir.path().join("main.d");
+
+    fs::write(
+        &input_path,
+        r#"
+device_start("*");
+load("nav.rhai");
+device_end();
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("nav.rhai"),
+        r#"
+map("VK_H", "VK_Left");
+load("stdlib/arrows.rhai");
+"#,
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("stdlib")).unwrap();
+    fs::write(
+        temp_dir.path().join("stdlib").join("arrows.rhai"),
+        r#"map("VK_J", "VK_Down");"#,
+    )
+    .unwrap();
+
+    let result = handle_compile_with_deps(&input_path, &output_path, Some(&deps_path));
+    assert!(
+        result.is_ok(),
+        "Compilation should succeed: {:?}",
+        result.err()
+    );
+
+    let rule = fs::read_to_string(&deps_path).unwrap();
+    let (target, deps) = rule.trim_end().split_once(": ").unwrap();
+    assert_eq!(target, output_path.display().to_string());
+    let deps: Vec<&str> = deps.split(' ').collect();
+    assert_eq!(deps.len(), 3, "Unexpected dependencies: {}", rule);
+    assert_eq!(deps[0], input_path.display().to_string());
+    assert!(deps[1].ends_with("nav.rhai"));
+    assert!(deps[2].ends_with("arrows.rhai"));
+}
+
+#[test]
+fn test_compile_krx_failure_keeps_output_and_records_loads() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_path = temp_dir.path().join("main.rhai");
+    let nav_path = temp_dir.path().join("nav.rhai");
+    let output_path = temp_dir.path().join("main.krx");
+
+    fs::write(
+        &input_path,
+        r#"
+device_start("*");
+load("nav.rhai");
+device_end();
+"#,
+    )
+    .unwrap();
+    fs::write(&nav_path, r#"map("VK_H", "VK_Left");"#).unwrap();
+
+    let mut dependencies = Vec::new();
+    let warnings = WarningPolicy::default();
+    compile_krx(
+        &input_path,
+        None,
+        &output_path,
+        None,
+        false,
+        &warnings,
+        &mut dependencies,
+    )
+    .unwrap();
+    assert_eq!(dependencies.len(), 2);
+    let good = fs::read(&output_path).unwrap();
+
+    // Break the imported file: the error is reported, the previous output
+    // stays in place and the files read so far are still recorded.
+    fs::write(&nav_path, r#"map("VK_H", "#).unwrap();
+    let result = compile_krx(
+        &input_path,
+        None,
+        &output_path,
+        None,
+        false,
+        &warnings,
+        &mut dependencies,
+    );
+    assert!(result.is_err());
+    assert_eq!(fs::read(&output_path).unwrap(), good);
+    assert_eq!(dependencies[0], input_path);
+    assert!(dependencies.iter().any(|d| d.ends_with("nav.rhai")));
+}
+
+#[test]
+fn test_format_dep_rule_escapes_spaces() {
+    let rule = format_dep_rule(
+        Path::new("out dir/config.krx"),
+        &[PathBuf::from("my config.rhai"), PathBuf::from("nav.rhai")],
+    );
+    assert_eq!(rule, "out\\ dir/config.krx: my\\ config.rhai nav.rhai\n");
+}
+
+/// Compiles `overlay` over `base` and returns the resulting configuration.
+fn compile_with_base(base: &str, overlay: &str) -> ConfigRoot {
+    let temp_dir = TempDir::new().unwrap();
+    let base_path = temp_dir.path().join("shared.rhai");
+    let input_path = temp_dir.path().join("overlay.rhai");
+    let output_path = temp_dir.path().join("overlay.krx");
+    fs::write(&base_path, base).unwrap();
+    fs::write(&input_path, overlay).unwrap();
+
+    let result = handle_compile_with_base(
+        &input_path,
+        Some(&base_path),
+        &output_path,
+        None,
+        false,
+        &WarningPolicy::default(),
+    );
+    assert!(
+        result.is_ok(),
+        "Compilation should succeed: {:?}",
+        result.err()
+    );
+
+    let bytes = fs::read(&output_path).unwrap();
+    deserialize(&bytes)
+        .unwrap()
+        .deserialize(&mut rkyv::Infallible)
+        .unwrap()
+}
+
+#[test]
+fn test_handle_compile_with_base_overrides_key() {
+    let config = compile_with_base(
+        r#"
+device_start("*");
+map("VK_A", "VK_B");
+map("VK_C", "VK_D");
+device_end();
+"#,
+        r#"
+device_start("*");
+map("VK_A", "VK_Z");
+device_end();
+"#,
+    );
+
+    assert_eq!(config.devices.len(), 1);
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::Z),
+            KeyMapping::simple(KeyCode::C, KeyCode::D),
+        ]
+    );
+}
+
+#[test]
+fn test_handle_compile_with_base_appends_new_keys_and_devices() {
+    let config = compile_with_base(
+        r#"
+device_start("*");
+map("VK_A", "VK_B");
+device_end();
+"#,
+        r#"
+device_start("*");
+map("VK_C", "VK_D");
+device_end();
+
+device_start("*Numpad*");
+map("VK_Numpad1", "VK_F13");
+device_end();
+"#,
+    );
+
+    assert_eq!(config.devices.len(), 2);
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+            KeyMapping::simple(KeyCode::C, KeyCode::D),
+        ]
+    );
+    assert_eq!(config.devices[1].identifier.pattern, "*Numpad*");
+}
+
+/// Compiles a script with a when block on a never-activated modifier.
+fn compile_unreachable_condition(warnings: &WarningPolicy) -> (Result<(), CompileError>, bool) {
+    let temp_dir = TempDir::new().unwrap();
+    let input_path = temp_dir.path().join("test.rhai");
+    let output_path = temp_dir.path().join("test.krx");
+    fs::write(
+        &input_path,
+        r#"
+device_start("*");
+when_start("MD_05");
+map("VK_H", "VK_Left");
+when_end();
+device_end();
+"#,
+    )
+    .unwrap();
+
+    let result = handle_compile_with_base(&input_path, None, &output_path, None, false, warnings);
+    (result, output_path.exists())
+}
+
+#[test]
+fn test_handle_compile_strict_fails_on_warnings() {
+    let (result, written) = compile_unreachable_condition(&WarningPolicy::default());
+    assert!(result.is_ok());
+    assert!(written);
+
+    let strict = WarningPolicy {
+        strict: true,
+        allowed: Vec::new(),
+    };
+    let (result, written) = compile_unreachable_condition(&strict);
+    assert!(matches!(result, Err(CompileError::WarningsAsErrors(1))));
+    assert!(!written, "Nothing should be written when --strict fails");
+}
+
+#[test]
+fn test_handle_compile_strict_allows_named_warning() {
+    let warnings = WarningPolicy {
+        strict: true,
+        allowed: vec!["unreachable-condition".parse().unwrap()],
+    };
+    let (result, written) = compile_unreachable_condition(&warnings);
+    assert!(result.is_ok(), "Allowed warning failed: {:?}", result.err());
+    assert!(written);
+
+    assert!("shadowed-mapping"
+        .parse::<keyrx_compiler::error::WarningKind>()
+        .is_err());
+}
@@ -8,7 +8,7 @@
 //! - Colored vs non-colored output (NO_COLOR)
 
 use keyrx_compiler::error::formatting::format_error;
-use keyrx_compiler::error::types::{ImportStep, ParseError};
+use keyrx_compiler::error::types::{ImportStep, LimitKind, ParseError};
 use std::path::PathBuf;
 
 /// Helper function to strip ANSI escape codes from a string.
@@ -458,6 +458,44 @@ fn test_resource_limit_exceeded() {
     assert!(formatted.contains("simplifying"));
 }
 
+#[test]
+fn test_limit_exceeded() {
+    let error = ParseError::LimitExceeded {
+        kind: LimitKind::Operations,
+        limit: 10_000,
+        import_chain: Vec::new(),
+    };
+
+    let formatted = strip_ansi_codes(&format_error(&error, &PathBuf::from("test.rhai"), ""));
+
+    assert!(formatted.contains("Parser limit exceeded"));
+    assert!(formatted.contains("more than 10000 operations"));
+    assert!(formatted.contains("help:"));
+    assert!(formatted.contains("max_operations"));
+}
+
+#[test]
+fn test_output_budget_exceeded() {
+    let error = ParseError::OutputBudgetExceeded {
+        device: "USB Keyboard".to_string(),
+        mapping: "ModifiedOutput { from: A, to: Z }".to_string(),
+        events: 6,
+        capacity: 5,
+    };
+
+    let formatted = format_error(&error, &PathBuf::from("test.rhai"), "");
+
+    assert!(formatted.contains("USB Keyboard"));
+    assert!(formatted.contains("ModifiedOutput { from: A, to: Z }"));
+    assert!(formatted.contains("up to 6 events"));
+    assert!(formatted.contains("holds 5"));
+    assert!(formatted.contains("help:"));
+
+    let display = error.to_string();
+    assert!(display.contains("ModifiedOutput { from: A, to: Z }"));
+    assert!(display.contains("'USB Keyboard'"));
+}
+
 #[test]
 fn test_import_chain_empty() {
     let error = ParseError::SyntaxError {
@@ -717,6 +755,17 @@ fn test_help_text_present_in_all_errors() {
             limit_type: "depth".to_string(),
             import_chain: Vec::new(),
         },
+        ParseError::LimitExceeded {
+            kind: LimitKind::CallDepth,
+            limit: 100,
+            import_chain: Vec::new(),
+        },
+        ParseError::OutputBudgetExceeded {
+            device: "*".to_string(),
+            mapping: "Simple { from: A, to: B }".to_string(),
+            events: 6,
+            capacity: 5,
+        },
     ];
 
     for error in errors {
@@ -787,6 +836,23 @@ fn test_all_errors_contain_suggestions() {
             },
             "simplifying",
         ),
+        (
+            ParseError::LimitExceeded {
+                kind: LimitKind::ExpressionDepth,
+                limit: 100,
+                import_chain: Vec::new(),
+            },
+            "max_expr_depth",
+        ),
+        (
+            ParseError::OutputBudgetExceeded {
+                device: "*".to_string(),
+                mapping: "Simple { from: A, to: B }".to_string(),
+                events: 6,
+                capacity: 5,
+            },
+            "Reduce the number of",
+        ),
     ];
 
     for (error, expected_suggestion) in errors {
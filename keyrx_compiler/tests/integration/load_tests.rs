@@ -283,3 +283,22 @@ device_end();
     // Space (base) + A and B (conditional under MD_00)
     assert!(!config.devices[0].mappings.is_empty()); // At least Space
 }
+
+#[test]
+fn test_without_load_does_not_read_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let secret = create_temp_file(&temp_dir, "secret.rhai", "map(\"A\", \"VK_B\");");
+    let main_content = format!(
+        "device_start(\"*\");\n    load(\"{}\");\ndevice_end();\n",
+        secret.display()
+    );
+
+    let mut parser = Parser::new().without_load();
+    let err = parser
+        .parse_string(&main_content, &temp_dir.path().join("main.rhai"))
+        .unwrap_err();
+
+    let message = format!("{:?}", err);
+    assert!(message.contains("load() is not available"), "{}", message);
+    assert_eq!(parser.dependencies().len(), 1);
+}
@@ -52,7 +52,7 @@ device_end();
     assert!(krx_size > 0, ".krx file should not be empty");
 
     // Step 4: Verify .krx integrity
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Step 5: Deserialize and validate structure
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -106,7 +106,7 @@ device_end();
     compile::handle_compile(&rhai_path, &krx_path).expect("Compilation should succeed");
 
     // Verify
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Deserialize and validate
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -191,7 +191,7 @@ device_end();
     compile::handle_compile(&rhai_path, &krx_path).expect("Compilation should succeed");
 
     // Verify
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Deserialize and validate
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -295,14 +295,14 @@ device_end();
 
     // Corrupt the .krx file by modifying bytes
     let mut krx_bytes = fs::read(&krx_path).expect("Failed to read .krx");
-    if krx_bytes.len() > 50 {
-        // Corrupt data section (skip magic and hash)
-        krx_bytes[50] = krx_bytes[50].wrapping_add(1);
+    if krx_bytes.len() > 60 {
+        // Corrupt data section (skip the 56-byte header)
+        krx_bytes[60] = krx_bytes[60].wrapping_add(1);
         fs::write(&krx_path, &krx_bytes).expect("Failed to write corrupted file");
     }
 
     // Verification should fail
-    let result = verify::handle_verify(&krx_path);
+    let result = verify::handle_verify(&krx_path, false);
     assert!(
         result.is_err(),
         "Verification of corrupted file should fail"
@@ -391,7 +391,7 @@ device_end();
         .expect("Complex config compilation should succeed");
 
     // Verify
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Deserialize and validate
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -425,7 +425,7 @@ fn test_workflow_empty_config() {
         .expect("Empty config compilation should succeed");
 
     // Verify
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Deserialize
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -464,7 +464,7 @@ fn test_workflow_large_config_many_mappings() {
         .expect("Large config compilation should succeed");
 
     // Verify
-    verify::handle_verify(&krx_path).expect("Verification should succeed");
+    verify::handle_verify(&krx_path, false).expect("Verification should succeed");
 
     // Deserialize
     let krx_bytes = fs::read(&krx_path).expect("Failed to read .krx file");
@@ -0,0 +1,230 @@
+//! Tests for unreachable conditional mapping warnings
+
+use super::*;
+
+/// Test that a when block guarded by a declared modifier produces no warnings
+#[test]
+fn test_declared_modifier_has_no_warning() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        when_start("MD_00");
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert!(parser.warnings().is_empty());
+}
+
+/// Test that a when block on a never-activated modifier is reported with its position
+#[test]
+fn test_undeclared_modifier_reports_position() {
+    let mut parser = Parser::new();
+    let script = r#"device_start("Test");
+map("CapsLock", "MD_00");
+when_start("MD_05");
+map("H", "VK_Left");
+when_end();
+device_end();
+"#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].file, PathBuf::from("test.rhai"));
+    assert_eq!(warnings[0].line, 3);
+    assert_eq!(warnings[0].kind, WarningKind::UnreachableCondition);
+    assert!(warnings[0].message.contains("never fire"));
+    assert!(warnings[0].message.contains("MD_05"));
+    assert!(warnings[0].to_string().starts_with("test.rhai:3:"));
+}
+
+/// Test that tap_hold and lock mappings count as declarations
+#[test]
+fn test_tap_hold_and_lock_declare_ids() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        tap_hold("Space", "VK_Space", "MD_01", 200);
+        map("ScrollLock", "LK_02");
+        when_start(["MD_01", "LK_02"]);
+        map("J", "VK_Down");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert!(parser.warnings().is_empty());
+}
+
+/// Test that when_not on a never-activated lock is reported as always true
+#[test]
+fn test_when_not_undeclared_lock_is_always_true() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_not_start("LK_03");
+        map("K", "VK_Up");
+        when_not_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("always true"));
+    assert!(warnings[0].message.contains("LK_03"));
+}
+
+/// Test that a composite condition reports undeclared none_of() items as redundant
+#[test]
+fn test_composite_undeclared_none_of_is_always_satisfied() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        when_start(all_of(["MD_00"]).and_none_of(["LK_04"]));
+        map("K", "VK_Up");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("always satisfied"));
+    assert!(warnings[0].message.contains("LK_04"));
+}
+
+/// Test that an OR condition only warns about its undeclared alternative
+#[test]
+fn test_any_of_partially_undeclared_reports_alternative() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        when_start("MD_00 | LK_05");
+        map("K", "VK_Up");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("alternative never matches"));
+    assert!(warnings[0].message.contains("LK_05"));
+    assert!(!warnings[0].message.contains("MD_00"));
+}
+
+/// Test that an OR condition with no declared alternative never fires
+#[test]
+fn test_any_of_fully_undeclared_never_fires() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start(any_of(["MD_06", "LK_07"]));
+        map("K", "VK_Up");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("never fire"));
+}
+
+/// Test that declarations are scoped to the device that owns the condition
+#[test]
+fn test_declarations_are_per_device() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("First");
+        map("CapsLock", "MD_00");
+        device_end();
+        device_start("Second");
+        when_start("MD_00");
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert_eq!(parser.warnings().len(), 1);
+    // Diagnostics must not change the compiled output
+    assert_eq!(config.devices[1].mappings.len(), 1);
+}
+
+/// Test that device-pattern conditions are never reported
+#[test]
+fn test_when_device_is_not_reported() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        when_device_start("*numpad*");
+        map("Numpad1", "VK_F13");
+        when_device_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert!(parser.warnings().is_empty());
+}
+
+/// Test that a when block in a loaded file is reported in that file, and a
+/// block after the load() in the top-level script still is not
+#[test]
+fn test_undeclared_modifier_in_loaded_file_reports_that_file() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let layer = temp_dir.path().join("layer.rhai");
+    std::fs::write(
+        &layer,
+        "when_start(\"MD_05\");\nmap(\"H\", \"VK_Left\");\nwhen_end();\n",
+    )
+    .unwrap();
+    let main = temp_dir.path().join("main.rhai");
+    std::fs::write(
+        &main,
+        r#"device_start("Test");
+load("layer.rhai");
+when_start("MD_06");
+map("J", "VK_Down");
+when_end();
+device_end();
+"#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser.parse_script(&main).unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].file, layer);
+    assert_eq!(warnings[0].line, 1);
+    assert!(warnings[0].message.contains("MD_05"));
+    assert_eq!(warnings[1].file, main);
+    assert_eq!(warnings[1].line, 3);
+    assert!(warnings[1].message.contains("MD_06"));
+}
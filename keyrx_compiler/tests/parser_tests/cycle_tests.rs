@@ -0,0 +1,103 @@
+//! Tests for cycle() function
+
+use super::*;
+
+/// Test cycle() creates Cycle mapping with outputs in order
+#[test]
+fn test_cycle_creates_cycle_mapping() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        cycle("F14", ["VK_A", "VK_B", "VK_C"], 500);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![KeyMapping::cycle(
+            KeyCode::F14,
+            vec![KeyCode::A, KeyCode::B, KeyCode::C],
+            500
+        )]
+    );
+}
+
+/// Test cycle() inside a when block
+#[test]
+fn test_cycle_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start("MD_00");
+        cycle("Q", ["VK_Num1", "VK_Num2"], 300);
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[0] {
+        KeyMapping::Conditional { mappings, .. } => {
+            assert_eq!(
+                mappings,
+                &vec![BaseKeyMapping::Cycle {
+                    from: KeyCode::Q,
+                    outputs: vec![KeyCode::Num1, KeyCode::Num2],
+                    reset_ms: 300,
+                }]
+            );
+        }
+        other => panic!("Expected Conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test cycle() requires VK_ prefix on every output
+#[test]
+fn test_cycle_rejects_output_without_vk_prefix() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        cycle("F14", ["VK_A", "B"], 500);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("VK_ prefix"));
+}
+
+/// Test cycle() rejects an empty output list
+#[test]
+fn test_cycle_rejects_empty_outputs() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        cycle("F14", [], 500);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("at least one key"));
+}
+
+/// Test cycle() rejects a reset window outside 1..=65535 ms
+#[test]
+fn test_cycle_rejects_invalid_reset() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        cycle("F14", ["VK_A"], 0);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("cycle reset"));
+}
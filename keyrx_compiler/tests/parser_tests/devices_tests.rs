@@ -310,3 +310,151 @@ fn test_sequential_device_start_completes_previous() {
     assert_eq!(config.devices[1].identifier.pattern, "Device 2");
     assert_eq!(config.devices[1].mappings.len(), 1);
 }
+
+/// Test exclude() records exclusion patterns on the current device
+#[test]
+fn test_device_exclude_patterns() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        exclude("*Mouse*");
+        exclude("*Yubikey*");
+        map("A", "VK_B");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(config.devices.len(), 1);
+    assert_eq!(config.devices[0].identifier.pattern, "*");
+    assert_eq!(
+        config.devices[0].identifier.excludes,
+        vec!["*Mouse*".to_string(), "*Yubikey*".to_string()]
+    );
+}
+
+/// Test exclusions are scoped to the device block they appear in
+#[test]
+fn test_device_exclude_scoped_to_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        exclude("*Mouse*");
+        device_end();
+
+        device_start("USB Keyboard");
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+    assert_eq!(config.devices[0].identifier.excludes.len(), 1);
+    assert!(config.devices[1].identifier.excludes.is_empty());
+}
+
+/// Test exclude() outside a device block is an error
+#[test]
+fn test_exclude_outside_device_fails() {
+    let mut parser = Parser::new();
+    let script = r#"
+        exclude("*Mouse*");
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    let err = format!("{:?}", result.unwrap_err());
+    assert!(err.contains("exclude() must be called inside a device() block"));
+}
+
+/// Test priority() is recorded per device and defaults to 0
+#[test]
+fn test_device_priority() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        priority(-10);
+        map("A", "VK_B");
+        device_end();
+
+        device_start("USB Keyboard");
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+    assert_eq!(config.devices[0].priority, -10);
+    assert_eq!(config.devices[1].priority, 0);
+}
+
+/// Test priority() outside a device block is an error
+#[test]
+fn test_priority_outside_device_fails() {
+    let mut parser = Parser::new();
+    let script = r#"
+        priority(5);
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    let err = format!("{:?}", result.unwrap_err());
+    assert!(err.contains("priority() must be called inside a device() block"));
+}
+
+/// Test priority() rejects values outside the 32-bit range
+#[test]
+fn test_priority_out_of_range_fails() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        priority(3000000000);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    let err = format!("{:?}", result.unwrap_err());
+    assert!(err.contains("must fit in a 32-bit integer"));
+}
+
+/// Test default_layer() records the layer and defaults to none
+#[test]
+fn test_device_default_layer() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        default_layer("MD_01");
+        device_end();
+
+        device_start("USB Keyboard");
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+    assert_eq!(config.devices[0].default_layer, Some(0x01));
+    assert_eq!(config.devices[1].default_layer, None);
+}
+
+/// Test default_layer() requires an MD_XX layer inside a device block
+#[test]
+fn test_default_layer_invalid_fails() {
+    let mut parser = Parser::new();
+    let outside = parser.parse_string(r#"default_layer("MD_01");"#, &PathBuf::from("test.rhai"));
+    let err = format!("{:?}", outside.unwrap_err());
+    assert!(err.contains("default_layer() must be called inside a device() block"));
+
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        default_layer("LK_01");
+        device_end();
+    "#;
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    let err = format!("{:?}", result.unwrap_err());
+    assert!(err.contains("Invalid default layer"));
+}
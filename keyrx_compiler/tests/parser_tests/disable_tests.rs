@@ -0,0 +1,70 @@
+//! Tests for disable() function
+
+use super::*;
+
+/// Test disable() creates a Disable mapping
+#[test]
+fn test_disable_creates_disable_mapping() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        disable("Insert");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![KeyMapping::disable(KeyCode::Insert)]
+    );
+}
+
+/// Test disable() inside a when block
+#[test]
+fn test_disable_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start("MD_00");
+        disable("CapsLock");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[0] {
+        KeyMapping::Conditional { mappings, .. } => {
+            assert_eq!(
+                mappings,
+                &vec![BaseKeyMapping::Disable {
+                    from: KeyCode::CapsLock
+                }]
+            );
+        }
+        other => panic!("Expected conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test disable() rejects unknown keys and calls outside a device block
+#[test]
+fn test_disable_errors() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        disable("NotAKey");
+        device_end();
+    "#;
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(format!("{:?}", result.unwrap_err()).contains("Invalid key"));
+
+    let mut parser = Parser::new();
+    let result = parser.parse_string(r#"disable("Insert");"#, &PathBuf::from("test.rhai"));
+    assert!(format!("{:?}", result.unwrap_err())
+        .contains("disable() must be called inside a device() block"));
+}
@@ -0,0 +1,69 @@
+//! Tests for layer_toggle() function
+
+use super::*;
+
+/// Test layer_toggle() creates LayerToggle mapping
+#[test]
+fn test_layer_toggle_creates_layer_toggle_mapping() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        layer_toggle("F1", "MD_02");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![KeyMapping::layer_toggle(KeyCode::F1, 2)]
+    );
+}
+
+/// Test layer_toggle() inside a when block, e.g. to leave the layer from within it
+#[test]
+fn test_layer_toggle_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        layer_toggle("F1", "MD_02");
+        when_start("MD_02");
+        layer_toggle("Escape", "MD_02");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[1] {
+        KeyMapping::Conditional { mappings, .. } => {
+            assert_eq!(
+                mappings,
+                &vec![BaseKeyMapping::LayerToggle {
+                    from: KeyCode::Escape,
+                    layer_id: 2,
+                }]
+            );
+        }
+        other => panic!("Expected Conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test layer_toggle() requires MD_ prefix on the layer
+#[test]
+fn test_layer_toggle_rejects_non_md_layer() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        layer_toggle("F1", "LK_02");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("MD_ prefix"));
+}
@@ -0,0 +1,105 @@
+//! Tests for ParserLimits and limit-exceeded errors
+
+use super::*;
+use keyrx_compiler::error::{LimitKind, ParseError};
+use keyrx_compiler::parser::ParserLimits;
+use std::fs;
+use tempfile::TempDir;
+
+const LOOP_SCRIPT: &str = r#"
+    device_start("Test");
+    for i in 0..200 {
+        map("A", "VK_B");
+    }
+    device_end();
+"#;
+
+/// Test that running out of operations is reported as LimitExceeded
+#[test]
+fn test_operation_limit_reports_limit_exceeded() {
+    let mut parser = Parser::with_limits(ParserLimits {
+        max_operations: 100,
+        ..ParserLimits::default()
+    });
+
+    let result = parser.parse_string(LOOP_SCRIPT, &PathBuf::from("test.rhai"));
+
+    match result {
+        Err(ParseError::LimitExceeded { kind, limit, .. }) => {
+            assert_eq!(kind, LimitKind::Operations);
+            assert_eq!(limit, 100);
+        }
+        other => panic!("Expected LimitExceeded, got {:?}", other),
+    }
+}
+
+/// Test that raising the limit lets the same script parse
+#[test]
+fn test_raised_operation_limit_parses() {
+    let mut parser = Parser::with_limits(ParserLimits {
+        max_operations: 100_000,
+        ..ParserLimits::default()
+    });
+
+    let result = parser.parse_string(LOOP_SCRIPT, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+}
+
+/// Test that deep call recursion is reported as a call depth limit
+#[test]
+fn test_call_depth_limit_reports_limit_exceeded() {
+    let mut parser = Parser::with_limits(ParserLimits {
+        max_call_levels: 5,
+        ..ParserLimits::default()
+    });
+    let script = r#"
+        fn down(n) { if n > 0 { down(n - 1) } }
+        down(20);
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+
+    match result {
+        Err(ParseError::LimitExceeded { kind, limit, .. }) => {
+            assert_eq!(kind, LimitKind::CallDepth);
+            assert_eq!(limit, 5);
+        }
+        other => panic!("Expected LimitExceeded, got {:?}", other),
+    }
+}
+
+/// Test that limits apply to loaded files and their errors pass through load()
+#[test]
+fn test_operation_limit_in_loaded_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("keys.rhai"),
+        "for i in 0..200 { map(\"A\", \"VK_B\"); }",
+    )
+    .unwrap();
+    let main = temp_dir.path().join("main.rhai");
+    fs::write(
+        &main,
+        "device_start(\"Test\");\nload(\"keys.rhai\");\ndevice_end();\n",
+    )
+    .unwrap();
+
+    let mut parser = Parser::with_limits(ParserLimits {
+        max_operations: 100,
+        ..ParserLimits::default()
+    });
+    let result = parser.parse_script(&main);
+
+    assert!(
+        matches!(
+            result,
+            Err(ParseError::LimitExceeded {
+                kind: LimitKind::Operations,
+                limit: 100,
+                ..
+            })
+        ),
+        "Expected LimitExceeded, got {:?}",
+        result
+    );
+}
@@ -0,0 +1,105 @@
+//! Tests for macro() function
+
+use super::*;
+use keyrx_core::config::MacroStep;
+
+/// Test macro() creates a Macro mapping with its steps in order
+#[test]
+fn test_macro_creates_macro_mapping() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        macro("F13", [press("VK_H"), release("VK_H"), wait(120), press("VK_I"), release("VK_I")]);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![KeyMapping::macro_steps(
+            KeyCode::F13,
+            vec![
+                MacroStep::Press(KeyCode::H),
+                MacroStep::Release(KeyCode::H),
+                MacroStep::Wait(120),
+                MacroStep::Press(KeyCode::I),
+                MacroStep::Release(KeyCode::I),
+            ]
+        )]
+    );
+}
+
+/// Test macro() inside a when block
+#[test]
+fn test_macro_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start("MD_00");
+        macro("J", [press("VK_LShift"), press("VK_A"), release("VK_A"), release("VK_LShift")]);
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[0] {
+        KeyMapping::Conditional { mappings, .. } => {
+            assert!(matches!(
+                &mappings[0],
+                BaseKeyMapping::Macro { from: KeyCode::J, steps } if steps.len() == 4
+            ));
+        }
+        other => panic!("Expected conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test macro() rejects steps that would leave a key held or emit nothing
+#[test]
+fn test_macro_errors() {
+    for (steps, expected) in [
+        (r#"[press("VK_A")]"#, "macro leaves A pressed"),
+        (r#"[wait(10)]"#, "must press or release at least one key"),
+        (
+            r#"["VK_A"]"#,
+            "macro steps must be press(), release() or wait()",
+        ),
+        (
+            r#"[press("A"), release("A")]"#,
+            "press() key must have VK_ prefix",
+        ),
+        (r#"[wait(70000)]"#, "wait() must be between 0 and 65535 ms"),
+    ] {
+        let mut parser = Parser::new();
+        let script = format!(
+            r#"
+            device_start("Test");
+            macro("F13", {});
+            device_end();
+            "#,
+            steps
+        );
+        let result = parser.parse_string(&script, &PathBuf::from("test.rhai"));
+        let err = format!("{:?}", result.unwrap_err());
+        assert!(
+            err.contains(expected),
+            "{} should fail with {}: {}",
+            steps,
+            expected,
+            err
+        );
+    }
+
+    let mut parser = Parser::new();
+    let result = parser.parse_string(
+        r#"macro("F13", [press("VK_A"), release("VK_A")]);"#,
+        &PathBuf::from("test.rhai"),
+    );
+    assert!(format!("{:?}", result.unwrap_err())
+        .contains("macro() must be called inside a device() block"));
+}
@@ -0,0 +1,139 @@
+//! Tests for map_range() function
+
+use super::*;
+
+/// Test map_range() expands into one simple mapping per key, in order
+#[test]
+fn test_map_range_expands_to_simple_mappings() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("1".."5", "VK_F1".."VK_F5");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![
+            KeyMapping::simple(KeyCode::Num1, KeyCode::F1),
+            KeyMapping::simple(KeyCode::Num2, KeyCode::F2),
+            KeyMapping::simple(KeyCode::Num3, KeyCode::F3),
+            KeyMapping::simple(KeyCode::Num4, KeyCode::F4),
+            KeyMapping::simple(KeyCode::Num5, KeyCode::F5),
+        ]
+    );
+}
+
+/// Test map_range() inside a when block adds to the conditional mappings
+#[test]
+fn test_map_range_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start("MD_00");
+        map_range("A".."C", "VK_Num1".."VK_Num3");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[0] {
+        KeyMapping::Conditional { mappings, .. } => {
+            let pairs: Vec<(KeyCode, KeyCode)> = mappings
+                .iter()
+                .map(|m| match m {
+                    BaseKeyMapping::Simple { from, to } => (*from, *to),
+                    other => panic!("Expected Simple mapping, got {:?}", other),
+                })
+                .collect();
+            assert_eq!(
+                pairs,
+                vec![
+                    (KeyCode::A, KeyCode::Num1),
+                    (KeyCode::B, KeyCode::Num2),
+                    (KeyCode::C, KeyCode::Num3),
+                ]
+            );
+        }
+        other => panic!("Expected Conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test map_range() rejects ranges of different lengths
+#[test]
+fn test_map_range_rejects_mismatched_lengths() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("1".."5", "VK_F1".."VK_F4");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    let error = format!("{:?}", result.unwrap_err());
+    assert!(error.contains("ranges differ in size"), "{}", error);
+    assert!(error.contains("has 5 keys"), "{}", error);
+}
+
+/// Test map_range() rejects an invalid key at either end of a range
+#[test]
+fn test_map_range_rejects_invalid_key() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("1".."Nope", "VK_F1".."VK_F5");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("Invalid 'from' range"));
+}
+
+/// Test map_range() requires VK_ prefix on the output range
+#[test]
+fn test_map_range_rejects_output_without_vk_prefix() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("1".."5", "F1".."F5");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("Invalid 'to' range"));
+}
+
+/// Test map_range() rejects ranges that skip over unused key codes or run
+/// backwards
+#[test]
+fn test_map_range_rejects_non_contiguous_and_backwards_ranges() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("Y".."Num1", "VK_A".."VK_D");
+        device_end();
+    "#;
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("no key follows Z"));
+
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map_range("5".."1", "VK_F1".."VK_F5");
+        device_end();
+    "#;
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("runs backwards"));
+}
@@ -393,3 +393,74 @@ fn test_map_rejects_out_of_range_lock_id() {
         "Should have failed - LK_FF is out of range"
     );
 }
+
+/// Test map() keeps numpad digits distinct from main-row digits
+#[test]
+fn test_map_numpad_digit_to_main_row_digit() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("KP_1", "VK_1");
+        map("1", "VK_KP_1");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    let mappings: Vec<_> = config.devices[0]
+        .mappings
+        .iter()
+        .map(|mapping| match mapping {
+            KeyMapping::Base(BaseKeyMapping::Simple { from, to }) => (*from, *to),
+            other => panic!("Expected Simple mapping, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        mappings,
+        vec![
+            (KeyCode::Numpad1, KeyCode::Num1),
+            (KeyCode::Num1, KeyCode::Numpad1),
+        ]
+    );
+}
+
+/// Test map() accepts international key names and their W3C-style aliases
+#[test]
+fn test_map_international_key_names() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("IntlBackslash", "VK_LShift");
+        map("IntlYen", "VK_Backspace");
+        map("IntlRo", "VK_RShift");
+        map("Henkan", "VK_Space");
+        map("Muhenkan", "VK_NumpadJpComma");
+        map("NumpadComma", "VK_NumpadDecimal");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let pairs: Vec<(KeyCode, KeyCode)> = result.unwrap().devices[0]
+        .mappings
+        .iter()
+        .map(|mapping| match mapping {
+            KeyMapping::Base(BaseKeyMapping::Simple { from, to }) => (*from, *to),
+            other => panic!("Expected Simple mapping, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            (KeyCode::Iso102nd, KeyCode::LShift),
+            (KeyCode::Yen, KeyCode::Backspace),
+            (KeyCode::Ro, KeyCode::RShift),
+            (KeyCode::Henkan, KeyCode::Space),
+            (KeyCode::Muhenkan, KeyCode::NumpadJpComma),
+            (KeyCode::NumpadComma, KeyCode::NumpadDecimal),
+        ]
+    );
+}
@@ -4,15 +4,49 @@
 //! parser test modules.
 
 // Re-export commonly used types
+pub use keyrx_compiler::error::WarningKind;
 pub use keyrx_compiler::parser::core::Parser;
-pub use keyrx_core::config::{BaseKeyMapping, KeyCode, KeyMapping};
+pub use keyrx_core::config::{BaseKeyMapping, KeyCode, KeyMapping, TapHoldPolicy};
 pub use std::path::PathBuf;
 
+use keyrx_core::config::Condition;
+
+/// Parses `script` and returns the condition of its first conditional
+/// mapping.
+pub fn parse_first_condition(script: &str) -> Condition {
+    let mut parser = Parser::new();
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap_or_else(|e| panic!("Failed to parse: {:?}", e));
+    config.devices[0]
+        .mappings
+        .iter()
+        .find_map(|mapping| match mapping {
+            KeyMapping::Conditional { condition, .. } => Some(condition.clone()),
+            KeyMapping::Base(_) => None,
+        })
+        .expect("Expected a Conditional mapping")
+}
+
 // Declare test modules
+mod condition_reachability_tests;
+mod cycle_tests;
 mod devices_tests;
+mod disable_tests;
+mod layer_toggle_tests;
+mod limits_tests;
+mod macro_tests;
+mod map_range_tests;
 mod maps_tests;
+mod modifier_output_tests;
 mod modifiers_tests;
+mod on_release_tests;
+mod remap_loop_tests;
 mod taps_tests;
+mod time_window_tests;
+mod when_any_tests;
+mod when_composite_tests;
 mod when_device_tests;
+mod when_negate_tests;
 mod when_not_tests;
 mod when_tests;
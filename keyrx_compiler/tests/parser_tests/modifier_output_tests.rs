@@ -0,0 +1,91 @@
+//! Tests for warnings on mappings that emit a physical modifier which is
+//! also declared as a custom modifier
+
+use super::*;
+
+/// Test that emitting a physical modifier the device remaps to MD_XX is reported
+#[test]
+fn test_emitting_declared_modifier_reports_position() {
+    let mut parser = Parser::new();
+    let script = r#"device_start("Test");
+map("LShift", "MD_00");
+map("A", "VK_LShift");
+device_end();
+"#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].line, 3);
+    assert_eq!(warnings[0].kind, WarningKind::ModifierOutput);
+    assert!(warnings[0]
+        .message
+        .contains("A emits LShift, but LShift is remapped to MD_00"));
+    assert!(warnings[0].message.contains("map A to \"MD_00\""));
+}
+
+/// Test that a map() in a loaded file is reported in that file
+#[test]
+fn test_emitting_declared_modifier_in_loaded_file_reports_that_file() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let keys = temp_dir.path().join("keys.rhai");
+    std::fs::write(&keys, "\nmap(\"A\", \"VK_LShift\");\n").unwrap();
+    let main = temp_dir.path().join("main.rhai");
+    std::fs::write(
+        &main,
+        "device_start(\"Test\");\nmap(\"LShift\", \"MD_00\");\nload(\"keys.rhai\");\ndevice_end();\n",
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    parser.parse_script(&main).unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::ModifierOutput);
+    assert_eq!(warnings[0].file, keys);
+    assert_eq!(warnings[0].line, 2);
+}
+
+/// Test that a tap_hold hold modifier counts as a declaration
+#[test]
+fn test_tap_hold_on_emitted_modifier_is_reported() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        tap_hold("LCtrl", "VK_Escape", "MD_03", 200);
+        when_start("MD_03");
+        map("J", "VK_LCtrl");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("MD_03"));
+}
+
+/// Test that emitting a physical modifier is fine when it is not remapped
+#[test]
+fn test_emitting_plain_modifier_has_no_warning() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "VK_LCtrl");
+        map("RShift", "MD_00");
+        device_end();
+
+        device_start("Other");
+        map("A", "VK_RShift");
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert!(parser.warnings().is_empty());
+}
@@ -443,3 +443,22 @@ fn test_realistic_ctrl_c_copy() {
         _ => panic!("Expected ModifiedOutput mapping"),
     }
 }
+
+/// Test that a mapping using every physical modifier fits the runtime output buffer
+#[test]
+fn test_all_modifiers_within_output_budget() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("VK_A", with_mods("VK_Z", true, true, true, true));
+        when_start("MD_00");
+        map("VK_B", with_mods("VK_Y", true, true, true, true));
+        when_end();
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap_or_else(|e| panic!("Failed to parse: {:?}", e));
+    assert_eq!(config.devices[0].mappings.len(), 2);
+}
@@ -0,0 +1,97 @@
+//! Tests for on_release() function
+
+use super::*;
+
+/// Test on_release() creates OnRelease mapping
+#[test]
+fn test_on_release_creates_on_release_mapping() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        on_release("CapsLock", "VK_Escape", 200);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    assert_eq!(
+        config.devices[0].mappings,
+        vec![KeyMapping::on_release(
+            KeyCode::CapsLock,
+            KeyCode::Escape,
+            200
+        )]
+    );
+}
+
+/// Test on_release() inside a when block
+#[test]
+fn test_on_release_in_conditional_block() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        when_start("MD_00");
+        on_release("H", "VK_Left", 150);
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    match &config.devices[0].mappings[1] {
+        KeyMapping::Conditional { mappings, .. } => {
+            assert_eq!(
+                mappings,
+                &vec![BaseKeyMapping::OnRelease {
+                    from: KeyCode::H,
+                    to: KeyCode::Left,
+                    threshold_ms: 150,
+                }]
+            );
+        }
+        other => panic!("Expected Conditional mapping, got {:?}", other),
+    }
+}
+
+/// Test on_release() requires VK_ prefix on output
+#[test]
+fn test_on_release_rejects_non_vk_output() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        on_release("A", "MD_00", 200);
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+    assert!(format!("{:?}", result.unwrap_err()).contains("VK_ prefix"));
+}
+
+/// Test on_release() rejects thresholds outside 1..=65535
+#[test]
+fn test_on_release_rejects_invalid_threshold() {
+    for threshold in ["0", "-5", "70000"] {
+        let mut parser = Parser::new();
+        let script = format!(
+            r#"
+            device_start("Test");
+            on_release("A", "VK_B", {});
+            device_end();
+            "#,
+            threshold
+        );
+
+        let result = parser.parse_string(&script, &PathBuf::from("test.rhai"));
+        assert!(
+            result.is_err(),
+            "threshold {} should be rejected",
+            threshold
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Tests for warnings on keys that remap into each other in a loop
+
+use super::*;
+
+/// Test that a layer mapping back onto a remapped key is reported, and the
+/// config still compiles
+#[test]
+fn test_conditional_chain_back_to_trigger_is_reported() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        map("A", "VK_B");
+        when_start("MD_00");
+        map("B", "VK_A");
+        when_end();
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert_eq!(config.devices.len(), 1);
+
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::RemapLoop);
+    assert_eq!(warnings[0].line, 2);
+    assert!(warnings[0].message.contains("B -> A -> B"));
+}
+
+/// Test that a tap-hold whose tap chains back to its own key is reported
+#[test]
+fn test_tap_hold_chain_is_reported() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        tap_hold("Space", "VK_Enter", "MD_00");
+        map("Enter", "VK_Tab");
+        map("Tab", "VK_Space");
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0]
+        .message
+        .contains("Space -> Enter -> Tab -> Space"));
+}
+
+/// Test that a loop on a device declared in a loaded file is reported at
+/// that file's device_start()
+#[test]
+fn test_remap_loop_in_loaded_device_reports_that_file() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let device = temp_dir.path().join("device.rhai");
+    std::fs::write(
+        &device,
+        r#"
+device_start("Test");
+tap_hold("Space", "VK_Enter", "MD_00");
+map("Enter", "VK_Space");
+device_end();
+"#,
+    )
+    .unwrap();
+    let main = temp_dir.path().join("main.rhai");
+    std::fs::write(&main, "load(\"device.rhai\");\n").unwrap();
+
+    let mut parser = Parser::new();
+    parser.parse_script(&main).unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::RemapLoop);
+    assert_eq!(warnings[0].file, device);
+    assert_eq!(warnings[0].line, 2);
+}
+
+/// Test that plain swaps and keys emitting themselves are not reported
+#[test]
+fn test_plain_swap_and_passthrough_have_no_warning() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        map("A", "VK_B");
+        map("B", "VK_A");
+        tap_hold("Space", "VK_Space", "MD_00");
+        when_start("MD_00");
+        map("J", "VK_J");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    assert!(parser.warnings().is_empty());
+}
@@ -25,11 +25,13 @@ fn test_tap_hold_creates_tap_hold_mapping() {
             tap,
             hold_modifier,
             threshold_ms,
+            policy,
         }) => {
             assert_eq!(*from, KeyCode::Space);
             assert_eq!(*tap, KeyCode::Space);
             assert_eq!(*hold_modifier, 0x00);
             assert_eq!(*threshold_ms, 200);
+            assert_eq!(*policy, TapHoldPolicy::Permissive);
         }
         _ => panic!(
             "Expected TapHold mapping, got {:?}",
@@ -58,6 +60,7 @@ fn test_tap_hold_different_tap_and_hold() {
             tap,
             hold_modifier,
             threshold_ms,
+            ..
         }) => {
             assert_eq!(*from, KeyCode::CapsLock);
             assert_eq!(*tap, KeyCode::Escape);
@@ -68,6 +71,56 @@ fn test_tap_hold_different_tap_and_hold() {
     }
 }
 
+/// Test tap_hold() accepts an explicit decision policy
+#[test]
+fn test_tap_hold_policy_argument() {
+    for (name, expected) in [
+        ("permissive", TapHoldPolicy::Permissive),
+        ("timeout", TapHoldPolicy::Timeout),
+    ] {
+        let mut parser = Parser::new();
+        let script = format!(
+            r#"
+            device_start("Test");
+            tap_hold("Space", "VK_Space", "MD_00", 200, "{}");
+            device_end();
+            "#,
+            name
+        );
+
+        let result = parser.parse_string(&script, &PathBuf::from("test.rhai"));
+        assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+        match &result.unwrap().devices[0].mappings[0] {
+            KeyMapping::Base(BaseKeyMapping::TapHold { policy, .. }) => {
+                assert_eq!(*policy, expected);
+            }
+            _ => panic!("Expected TapHold mapping"),
+        }
+    }
+}
+
+/// Test tap_hold() rejects an unknown policy
+#[test]
+fn test_tap_hold_rejects_unknown_policy() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        tap_hold("Space", "VK_Space", "MD_00", 200, "eager");
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err(), "Should have failed - unknown policy");
+
+    let err_msg = result.unwrap_err().to_string();
+    assert!(
+        err_msg.contains("policy") && err_msg.contains("eager"),
+        "Error should name the bad policy: {}",
+        err_msg
+    );
+}
+
 /// Test tap_hold() rejects tap without VK_ prefix
 #[test]
 fn test_tap_hold_rejects_tap_without_vk_prefix() {
@@ -340,3 +393,132 @@ fn test_tap_hold_rejects_lock_in_hold() {
         err_msg
     );
 }
+
+/// Thresholds of the tap_hold mappings of each device, in declaration order
+fn tap_hold_thresholds(script: &str) -> Vec<Vec<u16>> {
+    let mut parser = Parser::new();
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+
+    config
+        .devices
+        .iter()
+        .map(|device| {
+            device
+                .mappings
+                .iter()
+                .filter_map(|mapping| match mapping {
+                    KeyMapping::Base(BaseKeyMapping::TapHold { threshold_ms, .. }) => {
+                        Some(*threshold_ms)
+                    }
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Test tap_hold() without a threshold uses the built-in 200ms
+#[test]
+fn test_tap_hold_threshold_defaults_to_built_in() {
+    let thresholds = tap_hold_thresholds(
+        r#"
+        device_start("Test");
+        tap_hold("Space", "VK_Space", "MD_00");
+        tap_hold("A", "VK_A", "MD_01", "timeout");
+        device_end();
+    "#,
+    );
+
+    assert_eq!(thresholds, vec![vec![200, 200]]);
+}
+
+/// Test a global tap_hold_threshold() overrides the built-in default
+#[test]
+fn test_tap_hold_threshold_global_default() {
+    let thresholds = tap_hold_thresholds(
+        r#"
+        tap_hold_threshold(150);
+        device_start("Left");
+        tap_hold("Space", "VK_Space", "MD_00");
+        device_end();
+        device_start("Right");
+        tap_hold("Enter", "VK_Enter", "MD_01");
+        device_end();
+    "#,
+    );
+
+    assert_eq!(thresholds, vec![vec![150], vec![150]]);
+}
+
+/// Test a device tap_hold_threshold() overrides the global default for that
+/// device only, including inside conditional blocks
+#[test]
+fn test_tap_hold_threshold_device_default() {
+    let mut parser = Parser::new();
+    let script = r#"
+        tap_hold_threshold(150);
+        device_start("Left");
+        tap_hold_threshold(120);
+        tap_hold("Space", "VK_Space", "MD_00");
+        when_start("MD_00");
+        tap_hold("F", "VK_F", "MD_02");
+        when_end();
+        device_end();
+        device_start("Right");
+        tap_hold("Enter", "VK_Enter", "MD_01");
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+
+    assert_eq!(config.devices[0].tap_hold_threshold_ms, Some(120));
+    assert_eq!(config.devices[1].tap_hold_threshold_ms, None);
+    match &config.devices[0].mappings[1] {
+        KeyMapping::Conditional { mappings, .. } => match &mappings[0] {
+            BaseKeyMapping::TapHold { threshold_ms, .. } => assert_eq!(*threshold_ms, 120),
+            other => panic!("Expected TapHold, got {:?}", other),
+        },
+        other => panic!("Expected Conditional, got {:?}", other),
+    }
+    assert_eq!(tap_hold_thresholds(script), vec![vec![120], vec![150]]);
+}
+
+/// Test an explicit threshold wins over device and global defaults
+#[test]
+fn test_tap_hold_threshold_explicit_wins() {
+    let thresholds = tap_hold_thresholds(
+        r#"
+        tap_hold_threshold(150);
+        device_start("Test");
+        tap_hold_threshold(120);
+        tap_hold("Space", "VK_Space", "MD_00", 300);
+        tap_hold("A", "VK_A", "MD_01", 90, "timeout");
+        device_end();
+    "#,
+    );
+
+    assert_eq!(thresholds, vec![vec![300, 90]]);
+}
+
+/// Test tap_hold_threshold() rejects values outside 1-65535
+#[test]
+fn test_tap_hold_threshold_rejects_out_of_range() {
+    for value in ["0", "-5", "70000"] {
+        let mut parser = Parser::new();
+        let script = format!("tap_hold_threshold({});", value);
+
+        let result = parser.parse_string(&script, &PathBuf::from("test.rhai"));
+        assert!(result.is_err(), "Should have rejected {}", value);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("tap_hold_threshold"),
+            "Error should name the function"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Tests for time_window() function and TW_XX conditions
+
+use super::*;
+use keyrx_core::config::{Condition, ConditionItem, TimeWindow};
+
+/// Test time_window() stores the window on the device and TW_XX guards a block
+#[test]
+fn test_time_window_defines_window_and_condition() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        time_window("TW_00", "18:00", "23:00");
+        time_window("TW_01", "22:30", "06:15");
+        when_start("TW_00");
+        map("A", "VK_B");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
+
+    let config = result.unwrap();
+    let device = &config.devices[0];
+    assert_eq!(
+        device.time_windows,
+        vec![
+            TimeWindow {
+                start_minute: 18 * 60,
+                end_minute: 23 * 60,
+            },
+            TimeWindow {
+                start_minute: 22 * 60 + 30,
+                end_minute: 6 * 60 + 15,
+            },
+        ]
+    );
+    match &device.mappings[0] {
+        KeyMapping::Conditional { condition, .. } => {
+            assert_eq!(condition, &Condition::TimeWindowActive(0));
+        }
+        other => panic!("Expected Conditional mapping, got {:?}", other),
+    }
+    assert!(parser.warnings().is_empty());
+}
+
+/// Test TW_XX can be combined with modifiers and negated
+#[test]
+fn test_time_window_in_array_and_when_not() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        time_window("TW_00", "18:00", "23:00");
+        map("CapsLock", "MD_00");
+        when_start(["MD_00", "TW_00"]);
+        map("A", "VK_B");
+        when_end();
+        when_not_start("TW_00");
+        map("C", "VK_D");
+        when_not_end();
+        device_end();
+    "#;
+
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let conditions: Vec<&Condition> = config.devices[0]
+        .mappings
+        .iter()
+        .filter_map(|m| match m {
+            KeyMapping::Conditional { condition, .. } => Some(condition),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        conditions,
+        vec![
+            &Condition::AllActive(vec![
+                ConditionItem::ModifierActive(0),
+                ConditionItem::TimeWindowActive(0),
+            ]),
+            &Condition::NotActive(vec![ConditionItem::TimeWindowActive(0)]),
+        ]
+    );
+}
+
+/// Test time_window() IDs must be defined in order
+#[test]
+fn test_time_window_rejects_out_of_order_id() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        time_window("TW_01", "18:00", "23:00");
+        device_end();
+    "#;
+
+    let err = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap_err();
+    assert!(err.to_string().contains("expected TW_00"), "{}", err);
+}
+
+/// Test time_window() rejects malformed times
+#[test]
+fn test_time_window_rejects_invalid_time() {
+    for time in ["24:00", "18:60", "1800", "18:0", "ab:cd"] {
+        let mut parser = Parser::new();
+        let script = format!(
+            r#"
+            device_start("Test");
+            time_window("TW_00", "{}", "23:00");
+            device_end();
+        "#,
+            time
+        );
+
+        let result = parser.parse_string(&script, &PathBuf::from("test.rhai"));
+        assert!(result.is_err(), "Expected error for time {}", time);
+    }
+}
+
+/// Test a condition on an undefined window is reported as never firing
+#[test]
+fn test_undefined_time_window_warns() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start("TW_00");
+        map("A", "VK_B");
+        when_end();
+        device_end();
+    "#;
+
+    parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .unwrap();
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("TW_00"));
+}
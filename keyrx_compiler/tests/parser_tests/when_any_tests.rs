@@ -0,0 +1,94 @@
+//! Tests for OR when conditions built with `|` or any_of()
+
+use super::*;
+
+use keyrx_core::config::{Condition, ConditionItem};
+
+/// Test that "a | b" in when_start() creates an AnyActive condition
+#[test]
+fn test_pipe_string_creates_any_active() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        map("ScrollLock", "LK_01");
+        when_start("MD_00 | LK_01");
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0x00),
+            ConditionItem::LockActive(0x01),
+        ])
+    );
+}
+
+/// Test that any_of([...]) creates the same condition as "a | b"
+#[test]
+fn test_any_of_matches_pipe_form() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        map("ScrollLock", "LK_01");
+        when_start(any_of(["MD_00", "LK_01"]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0x00),
+            ConditionItem::LockActive(0x01),
+        ])
+    );
+}
+
+/// Test that an empty any_of() is rejected
+#[test]
+fn test_empty_any_of_is_error() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start(any_of([]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+}
+
+/// Test that OR is not accepted inside when_not_start() or condition arrays
+#[test]
+fn test_pipe_rejected_where_single_items_expected() {
+    for script in [
+        r#"
+        device_start("Test");
+        when_not_start("MD_00 | MD_01");
+        map("H", "VK_Left");
+        when_not_end();
+        device_end();
+    "#,
+        r#"
+        device_start("Test");
+        when_start(["MD_00 | MD_01", "LK_00"]);
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    ] {
+        let mut parser = Parser::new();
+        let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+        assert!(result.is_err(), "Expected error for script: {}", script);
+    }
+}
@@ -0,0 +1,111 @@
+//! Tests for composite when conditions built with all_of() / none_of()
+
+use super::*;
+
+use keyrx_core::config::{Condition, ConditionItem};
+
+/// Test all_of().and_none_of() creates a Composite condition
+#[test]
+fn test_all_of_and_none_of_creates_composite() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        map("ScrollLock", "LK_01");
+        when_start(all_of(["MD_00"]).and_none_of(["LK_01"]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::Composite {
+            all: vec![ConditionItem::ModifierActive(0x00)],
+            none: vec![ConditionItem::LockActive(0x01)],
+        }
+    );
+}
+
+/// Test none_of() alone and chained and_all_of()
+#[test]
+fn test_none_of_and_all_of_chaining() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        when_start(none_of(["LK_01", "LK_02"]).and_all_of(["MD_00", "MD_01"]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::Composite {
+            all: vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::ModifierActive(0x01),
+            ],
+            none: vec![
+                ConditionItem::LockActive(0x01),
+                ConditionItem::LockActive(0x02),
+            ],
+        }
+    );
+}
+
+/// Test that empty composite conditions are rejected
+#[test]
+fn test_empty_composite_is_error() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start(all_of([]).and_none_of([]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+}
+
+/// Test that composite items must be modifiers or locks
+#[test]
+fn test_composite_rejects_invalid_item() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start(all_of(["VK_A"]));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+}
+
+/// Test that the existing array form still creates AllActive
+#[test]
+fn test_array_form_still_creates_all_active() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        when_start(["MD_00", "LK_01"]);
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::AllActive(vec![
+            ConditionItem::ModifierActive(0x00),
+            ConditionItem::LockActive(0x01),
+        ])
+    );
+}
@@ -527,3 +527,52 @@ fn test_device_pattern_special_chars() {
         _ => panic!("Expected Conditional mapping"),
     }
 }
+
+/// The same key maps differently per source device when simulated
+#[test]
+fn test_when_device_simulates_per_device_mappings() {
+    use keyrx_core::runtime::{KeyLookup, VirtualClock};
+    use keyrx_core::simulator::{run_simulation, EventSequence, SimKeyEvent};
+
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("*");
+        when_device_start("*numpad*");
+        map("VK_A", "VK_B");
+        when_device_end();
+        when_device_start("*macropad*");
+        map("VK_A", "VK_C");
+        when_device_end();
+        map("VK_A", "VK_D");
+        device_end();
+    "#;
+    let config = parser
+        .parse_string(script, &PathBuf::from("test.rhai"))
+        .expect("Failed to parse");
+    let device = &config.devices[0];
+    let lookup = KeyLookup::from_device_config(device);
+
+    let press_from = |device_id: Option<&str>, timestamp_us| SimKeyEvent {
+        keycode: "A".to_string(),
+        event_type: "press".to_string(),
+        timestamp_us,
+        device_id: device_id.map(String::from),
+    };
+    let sequence = EventSequence {
+        events: vec![
+            press_from(Some("usb-numpad-1"), 0),
+            press_from(Some("bt-macropad"), 1_000),
+            press_from(Some("usb-keyboard"), 2_000),
+            press_from(None, 3_000),
+        ],
+        seed: 0,
+    };
+
+    let result = run_simulation(&lookup, device, &sequence, &VirtualClock::new()).unwrap();
+    let outputs: Vec<&str> = result
+        .timeline
+        .iter()
+        .map(|entry| entry.outputs[0].keycode.as_str())
+        .collect();
+    assert_eq!(outputs, ["B", "C", "D", "D"]);
+}
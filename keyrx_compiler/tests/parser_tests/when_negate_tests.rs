@@ -0,0 +1,97 @@
+//! Tests for whole-condition negation built with not()
+
+use super::*;
+
+use keyrx_core::config::{Condition, ConditionItem};
+
+/// Test that not(all_of([...])) negates the whole composite condition
+#[test]
+fn test_not_all_of_creates_negate() {
+    let condition = parse_first_condition(
+        r#"
+        device_start("Test");
+        map("CapsLock", "MD_00");
+        map("ScrollLock", "LK_01");
+        when_start(not(all_of(["MD_00", "LK_01"])));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#,
+    );
+
+    assert_eq!(
+        condition,
+        Condition::Negate(Box::new(Condition::Composite {
+            all: vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::LockActive(0x01),
+            ],
+            none: vec![],
+        }))
+    );
+}
+
+/// Test that not() accepts strings, arrays, any_of() and another not()
+#[test]
+fn test_not_accepts_every_condition_form() {
+    let cases = [
+        (
+            r#"not("MD_00 | LK_01")"#,
+            Condition::Negate(Box::new(Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::LockActive(0x01),
+            ]))),
+        ),
+        (
+            r#"not(["MD_00", "LK_01"])"#,
+            Condition::Negate(Box::new(Condition::AllActive(vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::LockActive(0x01),
+            ]))),
+        ),
+        (
+            r#"not(any_of(["MD_00"]))"#,
+            Condition::Negate(Box::new(Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(0x00),
+            ]))),
+        ),
+        (
+            r#"not(not("MD_00"))"#,
+            Condition::Negate(Box::new(Condition::Negate(Box::new(
+                Condition::ModifierActive(0x00),
+            )))),
+        ),
+    ];
+
+    for (expr, expected) in cases {
+        let script = format!(
+            r#"
+            device_start("Test");
+            map("CapsLock", "MD_00");
+            map("ScrollLock", "LK_01");
+            when_start({});
+            map("H", "VK_Left");
+            when_end();
+            device_end();
+        "#,
+            expr
+        );
+        assert_eq!(parse_first_condition(&script), expected, "{}", expr);
+    }
+}
+
+/// Test that not() of an empty composite is rejected
+#[test]
+fn test_not_empty_all_of_is_error() {
+    let mut parser = Parser::new();
+    let script = r#"
+        device_start("Test");
+        when_start(not(all_of([])));
+        map("H", "VK_Left");
+        when_end();
+        device_end();
+    "#;
+
+    let result = parser.parse_string(script, &PathBuf::from("test.rhai"));
+    assert!(result.is_err());
+}
@@ -10,7 +10,7 @@ use keyrx_compiler::parser::Parser;
 use keyrx_compiler::serialize::{deserialize, serialize};
 use keyrx_core::config::{
     BaseKeyMapping, Condition, ConditionItem, ConfigRoot, DeviceConfig, DeviceIdentifier, KeyCode,
-    KeyMapping, Metadata, Version,
+    KeyMapping, MacroStep, Metadata, TapHoldPolicy, Version,
 };
 use proptest::prelude::*;
 use sha2::{Digest, Sha256};
@@ -19,10 +19,11 @@ use sha2::{Digest, Sha256};
 // Proptest Strategies for Generating Arbitrary Configs
 // ============================================================================
 
-/// Strategy for generating arbitrary Version
+/// Strategy for generating a Version this build can load
 fn version_strategy() -> impl Strategy<Value = Version> {
-    (any::<u8>(), any::<u8>(), any::<u8>()).prop_map(|(major, minor, patch)| Version {
-        major,
+    let current = Version::current();
+    (0..=current.minor, any::<u8>()).prop_map(move |(minor, patch)| Version {
+        major: current.major,
         minor,
         patch,
     })
@@ -102,16 +103,21 @@ fn base_key_mapping_strategy() -> impl Strategy<Value = BaseKeyMapping> {
             keycode_strategy(),
             keycode_strategy(),
             0u8..=0xFE,
-            1u16..1000
+            1u16..1000,
+            prop_oneof![
+                Just(TapHoldPolicy::Permissive),
+                Just(TapHoldPolicy::Timeout)
+            ]
         )
-            .prop_map(
-                |(from, tap, hold_modifier, threshold_ms)| BaseKeyMapping::TapHold {
+            .prop_map(|(from, tap, hold_modifier, threshold_ms, policy)| {
+                BaseKeyMapping::TapHold {
                     from,
                     tap,
                     hold_modifier,
-                    threshold_ms
+                    threshold_ms,
+                    policy,
                 }
-            ),
+            }),
         // ModifiedOutput mapping
         (
             keycode_strategy(),
@@ -131,6 +137,45 @@ fn base_key_mapping_strategy() -> impl Strategy<Value = BaseKeyMapping> {
                     win
                 }
             ),
+        // OnRelease mapping
+        (keycode_strategy(), keycode_strategy(), 1u16..1000).prop_map(
+            |(from, to, threshold_ms)| BaseKeyMapping::OnRelease {
+                from,
+                to,
+                threshold_ms
+            }
+        ),
+        // LayerToggle mapping
+        (keycode_strategy(), 0u8..=0xFE)
+            .prop_map(|(from, layer_id)| BaseKeyMapping::LayerToggle { from, layer_id }),
+        // Cycle mapping
+        (
+            keycode_strategy(),
+            prop::collection::vec(keycode_strategy(), 1..5),
+            1u16..1000
+        )
+            .prop_map(|(from, outputs, reset_ms)| BaseKeyMapping::Cycle {
+                from,
+                outputs,
+                reset_ms
+            }),
+        // Disable mapping
+        keycode_strategy().prop_map(|from| BaseKeyMapping::Disable { from }),
+        // Macro mapping
+        (
+            keycode_strategy(),
+            prop::collection::vec(macro_step_strategy(), 1..6)
+        )
+            .prop_map(|(from, steps)| BaseKeyMapping::Macro { from, steps }),
+    ]
+}
+
+/// Strategy for generating arbitrary MacroStep
+fn macro_step_strategy() -> impl Strategy<Value = MacroStep> {
+    prop_oneof![
+        keycode_strategy().prop_map(MacroStep::Press),
+        keycode_strategy().prop_map(MacroStep::Release),
+        (0u16..1000).prop_map(MacroStep::Wait),
     ]
 }
 
@@ -151,14 +196,15 @@ fn key_mapping_strategy() -> impl Strategy<Value = KeyMapping> {
 
 /// Strategy for generating arbitrary DeviceIdentifier
 fn device_identifier_strategy() -> impl Strategy<Value = DeviceIdentifier> {
-    prop_oneof![
+    let pattern = prop_oneof![
         Just("*".to_string()),
         Just("USB Keyboard".to_string()),
         Just("Laptop Keyboard".to_string()),
         Just("External Keyboard".to_string()),
         "[a-zA-Z ]{5,20}".prop_map(|s| s),
-    ]
-    .prop_map(|pattern| DeviceIdentifier { pattern })
+    ];
+    let excludes = prop::collection::vec("\\*[a-zA-Z]{3,10}\\*", 0..3);
+    (pattern, excludes).prop_map(|(pattern, excludes)| DeviceIdentifier { pattern, excludes })
 }
 
 /// Strategy for generating arbitrary DeviceConfig
@@ -170,6 +216,10 @@ fn device_config_strategy() -> impl Strategy<Value = DeviceConfig> {
         .prop_map(|(identifier, mappings)| DeviceConfig {
             identifier,
             mappings,
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         })
 }
 
@@ -384,8 +434,8 @@ proptest! {
             return Ok(());
         }
 
-        // Corrupt the data section (after header, at byte 50)
-        bytes[50] ^= 0xFF;
+        // Corrupt the data section (just after the header)
+        bytes[keyrx_compiler::serialize::HEADER_SIZE + 2] ^= 0xFF;
 
         // Deserialization should fail due to hash mismatch
         let result = deserialize(&bytes);
@@ -433,6 +483,7 @@ mod edge_case_tests {
             devices.push(DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: format!("Device {}", i),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![
                     KeyMapping::simple(KeyCode::A, KeyCode::B),
@@ -444,6 +495,10 @@ mod edge_case_tests {
                         }],
                     ),
                 ],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             });
         }
 
@@ -472,6 +527,7 @@ mod edge_case_tests {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "*".to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![
                     KeyMapping::simple(KeyCode::A, KeyCode::B),
@@ -480,6 +536,10 @@ mod edge_case_tests {
                     KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0x00, 200),
                     KeyMapping::modified_output(KeyCode::A, KeyCode::A, true, false, false, false),
                 ],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -503,6 +563,7 @@ mod edge_case_tests {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "*".to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![
                     KeyMapping::conditional(
@@ -537,6 +598,10 @@ mod edge_case_tests {
                         }],
                     ),
                 ],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -755,7 +820,7 @@ proptest! {
         // Verify the result is valid
         if let Ok(config) = result {
             prop_assert!(!config.devices.is_empty(), "Config should have at least one device");
-            prop_assert_eq!(config.version.major, 1);
+            prop_assert_eq!(config.version.major, 2);
             prop_assert_eq!(config.version.minor, 0);
             prop_assert_eq!(config.version.patch, 0);
         }
@@ -2,7 +2,7 @@ use keyrx_compiler::error::ParseError;
 use keyrx_compiler::parser::validators::{
     parse_condition_string, parse_lock_id, parse_modifier_id, parse_physical_key, parse_virtual_key,
 };
-use keyrx_core::config::{Condition, KeyCode};
+use keyrx_core::config::{Condition, ConditionItem, KeyCode};
 
 #[cfg(test)]
 mod parse_physical_key_tests {
@@ -39,6 +39,42 @@ mod parse_physical_key_tests {
         assert_eq!(parse_physical_key("9").unwrap(), KeyCode::Num9);
     }
 
+    #[test]
+    fn test_parse_physical_key_numpad_distinct_from_main_row() {
+        assert_eq!(parse_physical_key("KP_1").unwrap(), KeyCode::Numpad1);
+        assert_eq!(parse_physical_key("Numpad1").unwrap(), KeyCode::Numpad1);
+        assert_ne!(
+            parse_physical_key("KP_1").unwrap(),
+            parse_physical_key("1").unwrap()
+        );
+        assert_eq!(parse_physical_key("KP_0").unwrap(), KeyCode::Numpad0);
+        assert_eq!(
+            parse_physical_key("KP_Enter").unwrap(),
+            KeyCode::NumpadEnter
+        );
+        assert_ne!(
+            parse_physical_key("KP_Enter").unwrap(),
+            parse_physical_key("Enter").unwrap()
+        );
+        assert_eq!(parse_physical_key("KP_Plus").unwrap(), KeyCode::NumpadAdd);
+        assert_eq!(
+            parse_physical_key("KP_Minus").unwrap(),
+            KeyCode::NumpadSubtract
+        );
+        assert_eq!(
+            parse_physical_key("KP_Asterisk").unwrap(),
+            KeyCode::NumpadMultiply
+        );
+        assert_eq!(
+            parse_physical_key("KP_Slash").unwrap(),
+            KeyCode::NumpadDivide
+        );
+        assert_eq!(
+            parse_physical_key("KP_Dot").unwrap(),
+            KeyCode::NumpadDecimal
+        );
+    }
+
     #[test]
     fn test_parse_physical_key_function_keys() {
         assert_eq!(parse_physical_key("F1").unwrap(), KeyCode::F1);
@@ -84,6 +120,26 @@ mod parse_physical_key_tests {
         );
     }
 
+    #[test]
+    fn test_parse_physical_key_system_keys() {
+        assert_eq!(
+            parse_physical_key("BrightnessDown").unwrap(),
+            KeyCode::BrightnessDown
+        );
+        assert_eq!(
+            parse_physical_key("BrightnessUp").unwrap(),
+            KeyCode::BrightnessUp
+        );
+        assert_eq!(
+            parse_physical_key("PlayPause").unwrap(),
+            KeyCode::MediaPlayPause
+        );
+        assert_eq!(
+            parse_physical_key("MediaPlayPause").unwrap(),
+            KeyCode::MediaPlayPause
+        );
+    }
+
     #[test]
     fn test_parse_physical_key_invalid() {
         let result = parse_physical_key("InvalidKey");
@@ -370,6 +426,39 @@ mod parse_condition_string_tests {
         let result = parse_condition_string("");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_condition_or() {
+        let result = parse_condition_string("MD_00 | LK_01").unwrap();
+        assert_eq!(
+            result,
+            Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::LockActive(0x01),
+            ])
+        );
+
+        // Whitespace around `|` is optional
+        let result = parse_condition_string("MD_00|MD_01|TW_00").unwrap();
+        assert_eq!(
+            result,
+            Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(0x00),
+                ConditionItem::ModifierActive(0x01),
+                ConditionItem::TimeWindowActive(0x00),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_condition_or_invalid_item() {
+        let result = parse_condition_string("MD_00 | VK_A");
+        assert!(matches!(result, Err(ParseError::InvalidPrefix { .. })));
+
+        // Empty alternative
+        let result = parse_condition_string("MD_00 |");
+        assert!(result.is_err());
+    }
 }
 
 #[cfg(test)]
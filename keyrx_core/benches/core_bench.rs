@@ -5,8 +5,10 @@
 //! - State update: <10μs (requirement: sub-microsecond bit vector updates)
 //! - End-to-end event processing: <1ms (requirement: overall latency budget)
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use keyrx_core::config::{DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping,
+};
 use keyrx_core::runtime::{process_event, DeviceState, KeyEvent, KeyLookup};
 
 /// Create a realistic DeviceConfig with 100 mappings for benchmarking
@@ -131,8 +133,13 @@ fn create_realistic_config() -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: "Benchmark Keyboard".to_string(),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -153,6 +160,114 @@ fn benchmark_key_lookup(c: &mut Criterion) {
     });
 }
 
+/// Create a config where H has 50 conditional mappings, one per layer,
+/// ahead of its unconditional fallback
+fn create_conditional_config() -> DeviceConfig {
+    let mut mappings: Vec<KeyMapping> = (0..50)
+        .map(|layer| {
+            KeyMapping::conditional(
+                Condition::AllActive(vec![
+                    ConditionItem::ModifierActive(layer),
+                    ConditionItem::LockActive(layer),
+                ]),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::H,
+                    to: KeyCode::Left,
+                }],
+            )
+        })
+        .collect();
+    mappings.push(KeyMapping::simple(KeyCode::H, KeyCode::J));
+
+    DeviceConfig {
+        identifier: DeviceIdentifier {
+            pattern: "*".to_string(),
+            excludes: Vec::new(),
+        },
+        mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
+    }
+}
+
+/// Benchmark: Lookup through 50 conditional mappings
+///
+/// `cached` repeats the lookup under unchanged state, the common case while
+/// typing; `state_changed` flips a lock between lookups so every lookup
+/// re-evaluates the conditions.
+fn benchmark_key_lookup_conditional(c: &mut Criterion) {
+    let config = create_conditional_config();
+    let lookup = KeyLookup::from_device_config(&config);
+
+    let mut group = c.benchmark_group("key_lookup_50_conditionals");
+    group.bench_function("cached", |b| {
+        let state = DeviceState::new();
+        b.iter(|| black_box(lookup.find_mapping(black_box(KeyCode::H), &state)))
+    });
+    group.bench_function("state_changed", |b| {
+        let mut state = DeviceState::new();
+        b.iter(|| {
+            state.toggle_lock(black_box(200));
+            black_box(lookup.find_mapping(black_box(KeyCode::H), &state))
+        })
+    });
+    group.finish();
+}
+
+/// Create a large config: the realistic 100 mappings repeated in 50 layers
+fn create_large_config() -> DeviceConfig {
+    let mut config = create_realistic_config();
+    let base: Vec<BaseKeyMapping> = config
+        .mappings
+        .iter()
+        .filter_map(|mapping| match mapping {
+            KeyMapping::Base(base) => Some(base.clone()),
+            KeyMapping::Conditional { .. } => None,
+        })
+        .collect();
+    for layer in 0..50 {
+        config.mappings.push(KeyMapping::conditional(
+            Condition::ModifierActive(layer),
+            base.clone(),
+        ));
+    }
+    config
+}
+
+/// Benchmark: Building the lookup table at startup for ~5000 mappings
+///
+/// `deserialize_then_build` is the old load path (archive to owned
+/// `DeviceConfig`, then `from_device_config`); `from_archived` builds the
+/// table over the archive and deserializes no mappings.
+fn benchmark_lookup_construction(c: &mut Criterion) {
+    use rkyv::Deserialize;
+
+    let config = create_large_config();
+    // `from_archived` borrows the archive for 'static, like the daemon's
+    // loaded configs
+    let bytes: &'static rkyv::AlignedVec = Box::leak(Box::new(
+        rkyv::to_bytes::<_, 4096>(&config).expect("Serialization failed"),
+    ));
+    let archived =
+        rkyv::check_archived_root::<DeviceConfig>(&bytes[..]).expect("Validation failed");
+
+    let mut group = c.benchmark_group("lookup_construction_large");
+    group.bench_function("deserialize_then_build", |b| {
+        b.iter(|| {
+            let owned: DeviceConfig = black_box(archived)
+                .deserialize(&mut rkyv::Infallible)
+                .expect("Deserialization failed");
+            black_box(KeyLookup::from_device_config(&owned))
+        })
+    });
+    group.bench_function("from_archived", |b| {
+        b.iter(|| black_box(KeyLookup::from_archived(black_box(archived))))
+    });
+    group.finish();
+}
+
 /// Benchmark: State update time (<10μs target)
 ///
 /// Measures DeviceState::set_modifier and toggle_lock performance
@@ -208,10 +323,107 @@ fn benchmark_process_event(c: &mut Criterion) {
     });
 }
 
+/// Create a home-row-mods style config: tap-hold keys plus a navigation layer
+fn create_layered_config() -> DeviceConfig {
+    let mut mappings = vec![
+        KeyMapping::tap_hold(KeyCode::A, KeyCode::A, 0, 200),
+        KeyMapping::tap_hold(KeyCode::S, KeyCode::S, 1, 200),
+        KeyMapping::tap_hold(KeyCode::D, KeyCode::D, 2, 200),
+        KeyMapping::tap_hold(KeyCode::F, KeyCode::F, 3, 200),
+        KeyMapping::modifier(KeyCode::CapsLock, 4),
+        KeyMapping::lock(KeyCode::ScrollLock, 0),
+    ];
+    mappings.push(KeyMapping::conditional(
+        Condition::ModifierActive(4),
+        vec![
+            BaseKeyMapping::Simple {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+            },
+            BaseKeyMapping::Simple {
+                from: KeyCode::J,
+                to: KeyCode::Down,
+            },
+            BaseKeyMapping::Simple {
+                from: KeyCode::K,
+                to: KeyCode::Up,
+            },
+            BaseKeyMapping::Simple {
+                from: KeyCode::L,
+                to: KeyCode::Right,
+            },
+        ],
+    ));
+    mappings.push(KeyMapping::conditional(
+        Condition::LockActive(0),
+        vec![BaseKeyMapping::ModifiedOutput {
+            from: KeyCode::C,
+            to: KeyCode::C,
+            shift: false,
+            ctrl: true,
+            alt: false,
+            win: false,
+        }],
+    ));
+
+    DeviceConfig {
+        identifier: DeviceIdentifier {
+            pattern: "*".to_string(),
+            excludes: Vec::new(),
+        },
+        mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
+    }
+}
+
+/// Benchmark: Tap sequences through representative configs
+///
+/// Each iteration processes a press/release pair per key, covering simple
+/// remaps, tap-hold resolution, layered lookups, and passthrough.
+fn benchmark_process_event_configs(c: &mut Criterion) {
+    let configs = [
+        ("realistic_100", create_realistic_config()),
+        ("layered_tap_hold", create_layered_config()),
+    ];
+    let keys = [
+        KeyCode::A,
+        KeyCode::F,
+        KeyCode::H,
+        KeyCode::C,
+        KeyCode::Numpad8,
+    ];
+
+    let mut group = c.benchmark_group("process_event_tap_sequence");
+    for (name, config) in &configs {
+        let lookup = KeyLookup::from_device_config(config);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &lookup, |b, lookup| {
+            let mut state = DeviceState::new();
+            let mut timestamp_us = 0u64;
+            b.iter(|| {
+                for &key in &keys {
+                    timestamp_us += 1_000;
+                    let press = KeyEvent::press(key).with_timestamp(timestamp_us);
+                    black_box(process_event(press, lookup, &mut state));
+                    timestamp_us += 1_000;
+                    let release = KeyEvent::release(key).with_timestamp(timestamp_us);
+                    black_box(process_event(release, lookup, &mut state));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_key_lookup,
+    benchmark_key_lookup_conditional,
+    benchmark_lookup_construction,
     benchmark_state_update,
-    benchmark_process_event
+    benchmark_process_event,
+    benchmark_process_event_configs
 );
 criterion_main!(benches);
@@ -85,6 +85,7 @@ fuzz_target!(|data: &[u8]| {
         let config = DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "fuzz-device".to_string(),
+                excludes: Vec::new(),
             },
             mappings: vec![
                 // Simple mapping: A -> B
@@ -1,4 +1,5 @@
 extern crate alloc;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 // CheckBytes is used by #[archive(check_bytes)] derive macro
 #[allow(unused_imports)]
@@ -17,16 +18,36 @@ pub enum ConditionItem {
     ModifierActive(u8),
     /// Custom lock is active (LK_XX)
     LockActive(u8),
+    /// Time window is active (TW_XX), indexing `DeviceConfig::time_windows`
+    TimeWindowActive(u8),
+}
+
+impl ConditionItem {
+    /// Relative runtime cost of evaluating the item
+    ///
+    /// Modifiers and locks are a single bit test; a time window also looks
+    /// up the window and compares the minute of day against it.
+    pub fn evaluation_cost(&self) -> u8 {
+        match self {
+            ConditionItem::ModifierActive(_) | ConditionItem::LockActive(_) => 0,
+            ConditionItem::TimeWindowActive(_) => 1,
+        }
+    }
 }
 
 /// Conditional mapping support for when/when_not blocks
 ///
-/// Supports single conditions, AND combinations, device matching, and negation.
-/// The `#[omit_bounds]` attribute enables recursive NotActive conditions.
+/// Supports single conditions, AND/OR combinations, device matching, and negation.
+/// The `#[omit_bounds]` attribute on `Negate` enables recursive conditions; the
+/// explicit serializer and validator bounds replace the ones it omits.
 #[derive(
     Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug,
 )]
 #[archive(check_bytes)]
+#[archive(bound(serialize = "__S: rkyv::ser::ScratchSpace + rkyv::ser::Serializer"))]
+#[archive_attr(check_bytes(
+    bound = "__C: rkyv::validation::ArchiveContext, <__C as rkyv::Fallible>::Error: rkyv::bytecheck::Error"
+))]
 pub enum Condition {
     /// Single custom modifier active (MD_XX)
     ModifierActive(u8),
@@ -37,8 +58,8 @@ pub enum Condition {
     /// All conditions must be true (AND logic) - for when() with multiple conditions
     AllActive(Vec<ConditionItem>),
 
-    /// Negated condition - NOT(...)
-    /// Currently limited to negating single items or AND combinations
+    /// None of the items are active - NOT(item AND ...)
+    /// Negates plain items only; use `Negate` for a whole sub-condition
     /// Example: NOT(ModifierActive(0x01))
     NotActive(Vec<ConditionItem>),
 
@@ -55,6 +76,106 @@ pub enum Condition {
     ///
     /// If the event has no device_id (None), this condition evaluates to false.
     DeviceMatches(alloc::string::String),
+
+    /// Every item in `all` is active AND no item in `none` is active
+    ///
+    /// Combines `AllActive` and `NotActive` in a single clause, e.g. "Fn held
+    /// and not in gaming lock". Either list may be empty.
+    Composite {
+        all: Vec<ConditionItem>,
+        none: Vec<ConditionItem>,
+    },
+
+    /// Single time window active (TW_XX)
+    ///
+    /// The id indexes `DeviceConfig::time_windows`. The runtime never reads a
+    /// clock itself: the host injects the local minute of day into the device
+    /// state, and the condition is false until it has done so.
+    TimeWindowActive(u8),
+
+    /// At least one condition must be true (OR logic)
+    ///
+    /// Written as `"MD_00 | LK_01"` or `any_of(["MD_00", "LK_01"])`.
+    /// Evaluation stops at the first active item.
+    AnyActive(Vec<ConditionItem>),
+
+    /// Inverts an entire sub-condition - NOT(...)
+    ///
+    /// Unlike `NotActive`, which only negates plain items, this wraps any
+    /// condition, e.g. `not(all_of(["MD_00", "LK_01"]))` for "not (Fn and
+    /// Gaming)". Negations may nest.
+    Negate(
+        #[omit_bounds]
+        #[archive_attr(omit_bounds)]
+        Box<Condition>,
+    ),
+}
+
+impl Condition {
+    /// Orders the items of every list so the cheapest checks run first
+    ///
+    /// `AllActive`, `NotActive`, `AnyActive` and `Composite` stop at the
+    /// first item that decides the result, so single-bit modifier and lock
+    /// tests are moved ahead of time window checks. Items are only
+    /// reordered within a list and the sort is stable, so the condition's
+    /// truth value never changes. Recurses into `Negate`.
+    pub fn order_for_evaluation(&mut self) {
+        match self {
+            Condition::AllActive(items)
+            | Condition::NotActive(items)
+            | Condition::AnyActive(items) => {
+                items.sort_by_key(ConditionItem::evaluation_cost);
+            }
+            Condition::Composite { all, none } => {
+                all.sort_by_key(ConditionItem::evaluation_cost);
+                none.sort_by_key(ConditionItem::evaluation_cost);
+            }
+            Condition::Negate(inner) => inner.order_for_evaluation(),
+            Condition::ModifierActive(_)
+            | Condition::LockActive(_)
+            | Condition::DeviceMatches(_)
+            | Condition::TimeWindowActive(_) => {}
+        }
+    }
+}
+
+/// Time-of-day range used by `TimeWindowActive` conditions
+///
+/// Minutes are counted from local midnight (0-1439). The range is half-open,
+/// `[start_minute, end_minute)`; when `end_minute < start_minute` the window
+/// wraps past midnight (e.g. 22:00-06:00). Equal bounds cover the whole day.
+#[derive(
+    Archive,
+    RkyvSerialize,
+    RkyvDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[archive(check_bytes)]
+#[repr(C)]
+pub struct TimeWindow {
+    /// First minute of the day inside the window
+    pub start_minute: u16,
+    /// First minute of the day after the window
+    pub end_minute: u16,
+}
+
+impl TimeWindow {
+    /// Returns true if `minute_of_day` falls inside this window
+    pub fn contains(&self, minute_of_day: u16) -> bool {
+        if self.start_minute == self.end_minute {
+            true
+        } else if self.start_minute < self.end_minute {
+            (self.start_minute..self.end_minute).contains(&minute_of_day)
+        } else {
+            minute_of_day >= self.start_minute || minute_of_day < self.end_minute
+        }
+    }
 }
 
 #[cfg(test)]
@@ -138,4 +259,33 @@ mod tests {
         let cond4 = Condition::DeviceMatches(String::from("different-device"));
         assert_ne!(cond1, cond4);
     }
+
+    #[test]
+    fn test_time_window_contains() {
+        let evening = TimeWindow {
+            start_minute: 18 * 60,
+            end_minute: 23 * 60,
+        };
+        assert!(!evening.contains(17 * 60 + 59));
+        assert!(evening.contains(18 * 60));
+        assert!(evening.contains(22 * 60 + 59));
+        assert!(!evening.contains(23 * 60));
+
+        let overnight = TimeWindow {
+            start_minute: 22 * 60,
+            end_minute: 6 * 60,
+        };
+        assert!(overnight.contains(23 * 60));
+        assert!(overnight.contains(0));
+        assert!(overnight.contains(5 * 60 + 59));
+        assert!(!overnight.contains(6 * 60));
+        assert!(!overnight.contains(12 * 60));
+
+        let all_day = TimeWindow {
+            start_minute: 0,
+            end_minute: 0,
+        };
+        assert!(all_day.contains(0));
+        assert!(all_day.contains(1439));
+    }
 }
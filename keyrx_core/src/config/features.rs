@@ -0,0 +1,248 @@
+//! Optional runtime features a compiled configuration depends on.
+//!
+//! The compiler stamps the features a config uses into the `.krx` header.
+//! A runtime compares the stamp against [`Features::SUPPORTED`] before
+//! touching the archived data, so a config using a feature added after the
+//! runtime was built fails with a message naming that feature instead of an
+//! opaque validation error.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::config::conditions::Condition;
+use crate::config::mappings::{BaseKeyMapping, ConfigRoot, KeyMapping, TapHoldPolicy};
+
+/// Set of runtime features, stored as a bitmask.
+///
+/// Mappings every runtime understands (simple, modifier, lock, tap-hold,
+/// modified output and plain `when`/`when_not` conditions) have no bit.
+/// Unknown bits are preserved so they can be reported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct Features(u64);
+
+impl Features {
+    /// Tap-hold with the `"timeout"` decision policy
+    pub const TAP_HOLD_TIMEOUT: Self = Self(1 << 0);
+    /// `on_release()` mappings
+    pub const ON_RELEASE: Self = Self(1 << 1);
+    /// `layer_toggle()` mappings
+    pub const LAYER_TOGGLE: Self = Self(1 << 2);
+    /// `cycle()` mappings
+    pub const CYCLE: Self = Self(1 << 3);
+    /// Conditions on `time_window()` windows
+    pub const TIME_WINDOWS: Self = Self(1 << 4);
+    /// `when_device_start()` blocks
+    pub const DEVICE_CONDITIONS: Self = Self(1 << 5);
+    /// `any_of()` conditions
+    pub const ANY_CONDITIONS: Self = Self(1 << 6);
+    /// Composite `all_of()`/`none_of()` conditions
+    pub const COMPOSITE_CONDITIONS: Self = Self(1 << 7);
+    /// `not()` of a whole sub-condition
+    pub const NEGATED_CONDITIONS: Self = Self(1 << 8);
+    /// `disable()` mappings
+    pub const DISABLE: Self = Self(1 << 9);
+    /// `macro()` mappings
+    pub const MACRO: Self = Self(1 << 10);
+
+    /// Every feature this build can run.
+    pub const SUPPORTED: Self = Self((1 << 11) - 1);
+
+    const NAMES: [(Self, &'static str); 11] = [
+        (Self::TAP_HOLD_TIMEOUT, "tap_hold timeout policy"),
+        (Self::ON_RELEASE, "on_release"),
+        (Self::LAYER_TOGGLE, "layer_toggle"),
+        (Self::CYCLE, "cycle"),
+        (Self::TIME_WINDOWS, "time windows"),
+        (Self::DEVICE_CONDITIONS, "device conditions"),
+        (Self::ANY_CONDITIONS, "any_of conditions"),
+        (Self::COMPOSITE_CONDITIONS, "composite conditions"),
+        (Self::NEGATED_CONDITIONS, "not() conditions"),
+        (Self::DISABLE, "disable"),
+        (Self::MACRO, "macro"),
+    ];
+
+    /// Returns the empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Creates a set from raw bits, keeping bits this build does not know.
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits.
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns whether no feature is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns whether every feature in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns the features in `self` that are missing from `runtime`.
+    pub const fn unsupported_by(self, runtime: Self) -> Self {
+        Self(self.0 & !runtime.0)
+    }
+
+    /// Adds the features in `other`.
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Returns the features a configuration uses.
+    pub fn required_by(config: &ConfigRoot) -> Self {
+        let mut features = Self::empty();
+        for device in &config.devices {
+            for mapping in &device.mappings {
+                match mapping {
+                    KeyMapping::Base(base) => features.insert(Self::of_mapping(base)),
+                    KeyMapping::Conditional {
+                        condition,
+                        mappings,
+                    } => {
+                        features.insert(Self::of_condition(condition));
+                        for base in mappings {
+                            features.insert(Self::of_mapping(base));
+                        }
+                    }
+                }
+            }
+        }
+        features
+    }
+
+    fn of_mapping(mapping: &BaseKeyMapping) -> Self {
+        match mapping {
+            BaseKeyMapping::TapHold {
+                policy: TapHoldPolicy::Timeout,
+                ..
+            } => Self::TAP_HOLD_TIMEOUT,
+            BaseKeyMapping::OnRelease { .. } => Self::ON_RELEASE,
+            BaseKeyMapping::LayerToggle { .. } => Self::LAYER_TOGGLE,
+            BaseKeyMapping::Cycle { .. } => Self::CYCLE,
+            BaseKeyMapping::Disable { .. } => Self::DISABLE,
+            BaseKeyMapping::Macro { .. } => Self::MACRO,
+            _ => Self::empty(),
+        }
+    }
+
+    fn of_condition(condition: &Condition) -> Self {
+        match condition {
+            Condition::ModifierActive(_)
+            | Condition::LockActive(_)
+            | Condition::AllActive(_)
+            | Condition::NotActive(_) => Self::empty(),
+            Condition::TimeWindowActive(_) => Self::TIME_WINDOWS,
+            Condition::DeviceMatches(_) => Self::DEVICE_CONDITIONS,
+            Condition::AnyActive(_) => Self::ANY_CONDITIONS,
+            Condition::Composite { .. } => Self::COMPOSITE_CONDITIONS,
+            Condition::Negate(inner) => {
+                let mut features = Self::of_condition(inner);
+                features.insert(Self::NEGATED_CONDITIONS);
+                features
+            }
+        }
+    }
+
+    /// Returns a readable name for every feature in the set.
+    ///
+    /// Bits this build does not know are named by their position.
+    pub fn names(self) -> Vec<String> {
+        let mut names: Vec<String> = Self::NAMES
+            .iter()
+            .filter(|(feature, _)| self.contains(*feature))
+            .map(|(_, name)| String::from(*name))
+            .collect();
+        let unknown = self.unsupported_by(Self::SUPPORTED).0;
+        for bit in 0..u64::BITS {
+            if unknown & (1 << bit) != 0 {
+                names.push(format!("unknown feature {}", bit));
+            }
+        }
+        names
+    }
+}
+
+impl fmt::Display for Features {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "none");
+        }
+        write!(f, "{}", self.names().join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_util::ConfigRootBuilder;
+    use crate::config::{ConditionItem, KeyCode};
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_basic_mappings_need_no_features() {
+        let config = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::modifier(KeyCode::CapsLock, 0),
+                KeyMapping::conditional(
+                    Condition::AllActive(vec![ConditionItem::ModifierActive(0)]),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                ),
+            ])
+            .build();
+        assert_eq!(Features::required_by(&config), Features::empty());
+    }
+
+    #[test]
+    fn test_required_by_collects_mapping_and_condition_features() {
+        let config = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::Base(BaseKeyMapping::Cycle {
+                    from: KeyCode::F1,
+                    outputs: vec![KeyCode::A],
+                    reset_ms: 500,
+                }),
+                KeyMapping::conditional(
+                    Condition::Negate(Box::new(Condition::DeviceMatches("usb-*".to_string()))),
+                    vec![BaseKeyMapping::OnRelease {
+                        from: KeyCode::CapsLock,
+                        to: KeyCode::Escape,
+                        threshold_ms: 200,
+                    }],
+                ),
+            ])
+            .build();
+
+        let features = Features::required_by(&config);
+        assert!(features.contains(Features::CYCLE));
+        assert!(features.contains(Features::ON_RELEASE));
+        assert!(features.contains(Features::NEGATED_CONDITIONS));
+        assert!(features.contains(Features::DEVICE_CONDITIONS));
+        assert!(!features.contains(Features::LAYER_TOGGLE));
+        assert!(features.unsupported_by(Features::SUPPORTED).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_bits_are_kept_and_named() {
+        let features = Features::from_bits(Features::CYCLE.bits() | 1 << 40);
+        let unsupported = features.unsupported_by(Features::SUPPORTED);
+        assert_eq!(unsupported.bits(), 1 << 40);
+        assert_eq!(features.to_string(), "cycle, unknown feature 40");
+        assert_eq!(Features::empty().to_string(), "none");
+    }
+}
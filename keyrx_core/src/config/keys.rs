@@ -169,6 +169,8 @@ pub enum KeyCode {
     Power = 0x260,
     Sleep = 0x261,
     Wake = 0x262,
+    BrightnessDown = 0x263,
+    BrightnessUp = 0x264,
 
     // Browser keys (0x270+)
     BrowserBack = 0x270,
@@ -213,6 +215,8 @@ pub enum KeyCode {
     Ro = 0x306,
     // カタカナ/ひらがな toggle
     KatakanaHiragana = 0x307,
+    // Numpad comma on JIS keyboards
+    NumpadJpComma = 0x308,
 
     // Korean keyboard keys (0x310+)
     // 한글 (Hangeul/Hangul) - Korean input toggle
@@ -223,6 +227,8 @@ pub enum KeyCode {
     // ISO/European keyboard keys (0x320+)
     // Extra key between left shift and Z on ISO keyboards
     Iso102nd = 0x320,
+    // Numpad comma (e.g. Brazilian ABNT2 keypad)
+    NumpadComma = 0x321,
 }
 
 #[cfg(test)]
@@ -4,13 +4,66 @@ use alloc::vec::Vec;
 use rkyv::{Archive, CheckBytes, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 use serde::{Deserialize, Serialize};
 
-use crate::config::conditions::Condition;
+use crate::config::conditions::{Condition, TimeWindow};
 use crate::config::keys::KeyCode;
 use crate::config::types::{Metadata, Version};
 
+/// How a pending tap-hold key decides between tap and hold.
+#[derive(
+    Archive,
+    RkyvSerialize,
+    RkyvDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+    Default,
+)]
+#[archive(check_bytes)]
+#[repr(u8)]
+#[serde(rename_all = "snake_case")]
+pub enum TapHoldPolicy {
+    /// Pressing another key while pending activates the hold immediately
+    /// (permissive hold)
+    #[default]
+    Permissive = 0,
+    /// Only the threshold decides; other keys pressed while pending do not
+    Timeout = 1,
+}
+
+/// One step of a [`BaseKeyMapping::Macro`] sequence
+#[derive(
+    Archive,
+    RkyvSerialize,
+    RkyvDeserialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Debug,
+)]
+#[archive(check_bytes)]
+#[repr(C)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroStep {
+    /// Press a key
+    Press(KeyCode),
+    /// Release a key
+    Release(KeyCode),
+    /// Wait this many milliseconds before the next step
+    Wait(u16),
+}
+
 /// Base key mapping types (non-recursive)
 ///
-/// Contains the 5 fundamental mapping types. This is separated from KeyMapping
+/// Contains the 10 fundamental mapping types. This is separated from KeyMapping
 /// to avoid rkyv recursion depth issues while maintaining ergonomic usage.
 #[derive(
     Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug,
@@ -33,6 +86,9 @@ pub enum BaseKeyMapping {
         tap: KeyCode,
         hold_modifier: u8,
         threshold_ms: u16,
+        /// How a pending key decides between tap and hold
+        #[serde(default)]
+        policy: TapHoldPolicy,
     },
 
     /// Output with physical modifiers (Shift+2, Ctrl+C, etc.)
@@ -44,6 +100,82 @@ pub enum BaseKeyMapping {
         alt: bool,
         win: bool,
     },
+
+    /// Output emitted on release instead of press (A tapped → B)
+    ///
+    /// The press is swallowed. On release, `to` is pressed and released if
+    /// the key was held for less than `threshold_ms`; a longer hold emits
+    /// nothing.
+    OnRelease {
+        from: KeyCode,
+        to: KeyCode,
+        threshold_ms: u16,
+    },
+
+    /// Key toggles a layer on or off (MD_00-MD_FE)
+    ///
+    /// The first press locks the layer on, the next press unlocks it. While
+    /// locked, conditions on the layer's modifier match as if it were held,
+    /// and releasing a momentary key for the same layer does not turn it off.
+    LayerToggle { from: KeyCode, layer_id: u8 },
+
+    /// Key cycles through outputs on repeated taps (F14 → A, B, C, A, ...)
+    ///
+    /// Each press emits the next key in `outputs`, wrapping around. After
+    /// `reset_ms` without a press, the next press starts again at the first
+    /// output.
+    Cycle {
+        from: KeyCode,
+        outputs: Vec<KeyCode>,
+        reset_ms: u16,
+    },
+
+    /// Key is disabled: its press and release emit nothing (Insert → ∅)
+    Disable { from: KeyCode },
+
+    /// Key plays a sequence of presses, releases and waits (F13 → H, i)
+    ///
+    /// The whole sequence plays on press; the release emits nothing. Steps
+    /// up to the first wait are emitted with the press, the rest are
+    /// scheduled on the device state for the caller to inject when due.
+    Macro {
+        from: KeyCode,
+        steps: Vec<MacroStep>,
+    },
+}
+
+/// Variant of a [`BaseKeyMapping`], without its data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingKind {
+    Simple,
+    Modifier,
+    Lock,
+    TapHold,
+    ModifiedOutput,
+    OnRelease,
+    LayerToggle,
+    Cycle,
+    Disable,
+    Macro,
+}
+
+impl MappingKind {
+    /// Returns the snake_case name of the kind (e.g. "tap_hold")
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MappingKind::Simple => "simple",
+            MappingKind::Modifier => "modifier",
+            MappingKind::Lock => "lock",
+            MappingKind::TapHold => "tap_hold",
+            MappingKind::ModifiedOutput => "modified_output",
+            MappingKind::OnRelease => "on_release",
+            MappingKind::LayerToggle => "layer_toggle",
+            MappingKind::Cycle => "cycle",
+            MappingKind::Disable => "disable",
+            MappingKind::Macro => "macro",
+        }
+    }
 }
 
 /// Key mapping configuration with recursive conditional support
@@ -57,7 +189,7 @@ pub enum BaseKeyMapping {
 #[archive(check_bytes)]
 #[repr(C)]
 pub enum KeyMapping {
-    /// Base mapping (one of the 5 fundamental types)
+    /// Base mapping (one of the 10 fundamental types)
     Base(BaseKeyMapping),
 
     /// Conditional mappings (when/when_not blocks) - supports unlimited nesting
@@ -90,9 +222,43 @@ impl KeyMapping {
             tap,
             hold_modifier,
             threshold_ms,
+            policy: TapHoldPolicy::Permissive,
+        })
+    }
+
+    /// Create an emit-on-release mapping
+    pub fn on_release(from: KeyCode, to: KeyCode, threshold_ms: u16) -> Self {
+        KeyMapping::Base(BaseKeyMapping::OnRelease {
+            from,
+            to,
+            threshold_ms,
+        })
+    }
+
+    /// Create a layer toggle mapping
+    pub fn layer_toggle(from: KeyCode, layer_id: u8) -> Self {
+        KeyMapping::Base(BaseKeyMapping::LayerToggle { from, layer_id })
+    }
+
+    /// Create a tap-to-cycle mapping
+    pub fn cycle(from: KeyCode, outputs: Vec<KeyCode>, reset_ms: u16) -> Self {
+        KeyMapping::Base(BaseKeyMapping::Cycle {
+            from,
+            outputs,
+            reset_ms,
         })
     }
 
+    /// Create a mapping that disables a key
+    pub fn disable(from: KeyCode) -> Self {
+        KeyMapping::Base(BaseKeyMapping::Disable { from })
+    }
+
+    /// Create a macro mapping
+    pub fn macro_steps(from: KeyCode, steps: Vec<MacroStep>) -> Self {
+        KeyMapping::Base(BaseKeyMapping::Macro { from, steps })
+    }
+
     /// Create a modified output mapping
     pub fn modified_output(
         from: KeyCode,
@@ -128,6 +294,10 @@ impl KeyMapping {
 /// - "*" matches all devices
 /// - "USB Keyboard" matches devices with that exact name
 /// - Platform-specific patterns may be supported by the daemon
+///
+/// Exclusion patterns reject a device even if it matches `pattern`, e.g.
+/// `pattern: "*"` with `excludes: ["*Mouse*"]` matches every keyboard except
+/// those whose name/serial/path contains "Mouse".
 #[derive(
     Archive, RkyvSerialize, RkyvDeserialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug,
 )]
@@ -136,6 +306,9 @@ impl KeyMapping {
 pub struct DeviceIdentifier {
     /// Pattern string for matching device names/IDs
     pub pattern: alloc::string::String,
+    /// Exclusion patterns (same syntax as `pattern`); any match rejects the device
+    #[serde(default)]
+    pub excludes: Vec<alloc::string::String>,
 }
 
 /// Device-specific configuration
@@ -152,6 +325,208 @@ pub struct DeviceConfig {
     pub identifier: DeviceIdentifier,
     /// List of key mappings for this device
     pub mappings: Vec<KeyMapping>,
+    /// Time windows referenced by `TW_XX` conditions, indexed by id
+    #[serde(default)]
+    pub time_windows: Vec<TimeWindow>,
+    /// Matching priority; higher values are tried first, ties keep declaration order
+    #[serde(default)]
+    pub priority: i32,
+    /// Layer (MD_XX) locked on when the device's state is created, as if
+    /// its `layer_toggle` key had been pressed once
+    #[serde(default)]
+    pub default_layer: Option<u8>,
+    /// Threshold inherited by this device's `tap_hold` mappings that don't
+    /// set one. The compiler resolves it into each mapping, so the runtime
+    /// never reads it.
+    #[serde(default)]
+    pub tap_hold_threshold_ms: Option<u16>,
+}
+
+/// Tap-hold threshold used when neither the mapping, its device nor the
+/// configuration sets one.
+pub const DEFAULT_TAP_HOLD_THRESHOLD_MS: u16 = 200;
+
+impl BaseKeyMapping {
+    /// Returns the kind of this mapping.
+    pub fn kind(&self) -> MappingKind {
+        match self {
+            BaseKeyMapping::Simple { .. } => MappingKind::Simple,
+            BaseKeyMapping::Modifier { .. } => MappingKind::Modifier,
+            BaseKeyMapping::Lock { .. } => MappingKind::Lock,
+            BaseKeyMapping::TapHold { .. } => MappingKind::TapHold,
+            BaseKeyMapping::ModifiedOutput { .. } => MappingKind::ModifiedOutput,
+            BaseKeyMapping::OnRelease { .. } => MappingKind::OnRelease,
+            BaseKeyMapping::LayerToggle { .. } => MappingKind::LayerToggle,
+            BaseKeyMapping::Cycle { .. } => MappingKind::Cycle,
+            BaseKeyMapping::Disable { .. } => MappingKind::Disable,
+            BaseKeyMapping::Macro { .. } => MappingKind::Macro,
+        }
+    }
+
+    /// Returns the input key this mapping is triggered by.
+    pub fn from_key(&self) -> KeyCode {
+        match self {
+            BaseKeyMapping::Simple { from, .. }
+            | BaseKeyMapping::Modifier { from, .. }
+            | BaseKeyMapping::Lock { from, .. }
+            | BaseKeyMapping::TapHold { from, .. }
+            | BaseKeyMapping::ModifiedOutput { from, .. }
+            | BaseKeyMapping::OnRelease { from, .. }
+            | BaseKeyMapping::LayerToggle { from, .. }
+            | BaseKeyMapping::Cycle { from, .. }
+            | BaseKeyMapping::Disable { from }
+            | BaseKeyMapping::Macro { from, .. } => *from,
+        }
+    }
+
+    /// Returns the most key events this mapping emits for a single input
+    /// event (one press or one release).
+    ///
+    /// A modified output presses every enabled modifier plus the key, and a
+    /// tap (tap-hold or emit-on-release) emits both press and release at once.
+    /// A macro counts every press and release, as it may have no waits.
+    pub fn max_output_events(&self) -> usize {
+        match self {
+            BaseKeyMapping::Simple { .. } | BaseKeyMapping::Cycle { .. } => 1,
+            BaseKeyMapping::ModifiedOutput {
+                shift,
+                ctrl,
+                alt,
+                win,
+                ..
+            } => 1 + [*shift, *ctrl, *alt, *win].iter().filter(|m| **m).count(),
+            BaseKeyMapping::TapHold { .. } | BaseKeyMapping::OnRelease { .. } => 2,
+            BaseKeyMapping::Macro { steps, .. } => steps
+                .iter()
+                .filter(|step| !matches!(step, MacroStep::Wait(_)))
+                .count(),
+            BaseKeyMapping::Modifier { .. }
+            | BaseKeyMapping::Lock { .. }
+            | BaseKeyMapping::LayerToggle { .. }
+            | BaseKeyMapping::Disable { .. } => 0,
+        }
+    }
+
+    /// Appends every key code this mapping can emit to `keys`.
+    pub(crate) fn collect_output_keys(&self, keys: &mut Vec<KeyCode>) {
+        match self {
+            BaseKeyMapping::Simple { to, .. } | BaseKeyMapping::OnRelease { to, .. } => {
+                keys.push(*to);
+            }
+            BaseKeyMapping::TapHold { tap, .. } => keys.push(*tap),
+            BaseKeyMapping::Cycle { outputs, .. } => keys.extend(outputs.iter().copied()),
+            BaseKeyMapping::Macro { steps, .. } => {
+                keys.extend(steps.iter().filter_map(|step| match step {
+                    MacroStep::Press(key) | MacroStep::Release(key) => Some(*key),
+                    MacroStep::Wait(_) => None,
+                }));
+            }
+            BaseKeyMapping::ModifiedOutput {
+                to,
+                shift,
+                ctrl,
+                alt,
+                win,
+                ..
+            } => {
+                keys.push(*to);
+                let modifiers = [
+                    (*shift, KeyCode::LShift),
+                    (*ctrl, KeyCode::LCtrl),
+                    (*alt, KeyCode::LAlt),
+                    (*win, KeyCode::LMeta),
+                ];
+                keys.extend(
+                    modifiers
+                        .iter()
+                        .filter(|(enabled, _)| *enabled)
+                        .map(|(_, key)| *key),
+                );
+            }
+            BaseKeyMapping::Modifier { .. }
+            | BaseKeyMapping::Lock { .. }
+            | BaseKeyMapping::LayerToggle { .. }
+            | BaseKeyMapping::Disable { .. } => {}
+        }
+    }
+}
+
+impl DeviceConfig {
+    /// Creates a config for devices matching `pattern`, with no excludes,
+    /// time windows, priority, default layer or tap-hold threshold
+    pub fn new(pattern: &str, mappings: Vec<KeyMapping>) -> Self {
+        Self {
+            identifier: DeviceIdentifier {
+                pattern: alloc::string::String::from(pattern),
+                excludes: Vec::new(),
+            },
+            mappings,
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        }
+    }
+
+    /// Threshold for a `tap_hold` mapping in this device that doesn't set
+    /// one: the device default, then `global`, then
+    /// [`DEFAULT_TAP_HOLD_THRESHOLD_MS`].
+    pub fn tap_hold_threshold(&self, global: Option<u16>) -> u16 {
+        self.tap_hold_threshold_ms
+            .or(global)
+            .unwrap_or(DEFAULT_TAP_HOLD_THRESHOLD_MS)
+    }
+
+    /// Returns every key code the mappings of this device remap.
+    ///
+    /// Includes inputs of mappings nested inside conditional blocks. The
+    /// result is sorted by key code and contains no duplicates.
+    pub fn input_keys(&self) -> Vec<KeyCode> {
+        let mut keys: Vec<KeyCode> = self
+            .mappings
+            .iter()
+            .flat_map(|mapping| match mapping {
+                KeyMapping::Base(base) => core::slice::from_ref(base),
+                KeyMapping::Conditional { mappings, .. } => mappings.as_slice(),
+            })
+            .map(BaseKeyMapping::from_key)
+            .collect();
+        keys.sort_unstable_by_key(|key| *key as u16);
+        keys.dedup();
+        keys
+    }
+
+    /// Returns every key code the mappings of this device can emit.
+    ///
+    /// Includes outputs of mappings nested inside conditional blocks. The
+    /// result is sorted by key code and contains no duplicates. Keys that are
+    /// passed through unmapped are not included.
+    pub fn output_keys(&self) -> Vec<KeyCode> {
+        let mut keys = Vec::new();
+        for mapping in &self.mappings {
+            match mapping {
+                KeyMapping::Base(base) => base.collect_output_keys(&mut keys),
+                KeyMapping::Conditional { mappings, .. } => {
+                    for base in mappings {
+                        base.collect_output_keys(&mut keys);
+                    }
+                }
+            }
+        }
+        keys.sort_unstable_by_key(|key| *key as u16);
+        keys.dedup();
+        keys
+    }
+
+    /// Orders the items of every condition for cheap evaluation, see
+    /// [`Condition::order_for_evaluation`]
+    pub fn order_conditions_for_evaluation(&mut self) {
+        for mapping in &mut self.mappings {
+            if let KeyMapping::Conditional { condition, .. } = mapping {
+                condition.order_for_evaluation();
+            }
+        }
+    }
 }
 
 /// Root configuration structure
@@ -228,17 +603,241 @@ mod tests {
         let device_config = DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
             },
             mappings: alloc::vec![
                 KeyMapping::simple(KeyCode::A, KeyCode::B),
                 KeyMapping::modifier(KeyCode::CapsLock, 0x01),
             ],
+            time_windows: alloc::vec::Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         };
 
         assert_eq!(device_config.identifier.pattern, "*");
         assert_eq!(device_config.mappings.len(), 2);
     }
 
+    #[test]
+    fn test_max_output_events() {
+        let base = |mapping: KeyMapping| match mapping {
+            KeyMapping::Base(base) => base,
+            KeyMapping::Conditional { .. } => unreachable!(),
+        };
+
+        assert_eq!(
+            base(KeyMapping::simple(KeyCode::A, KeyCode::B)).max_output_events(),
+            1
+        );
+        assert_eq!(
+            base(KeyMapping::modifier(KeyCode::CapsLock, 0)).max_output_events(),
+            0
+        );
+        assert_eq!(
+            base(KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0, 200)).max_output_events(),
+            2
+        );
+        assert_eq!(
+            base(KeyMapping::modified_output(
+                KeyCode::A,
+                KeyCode::C,
+                true,
+                true,
+                false,
+                false
+            ))
+            .max_output_events(),
+            3
+        );
+        assert_eq!(
+            base(KeyMapping::modified_output(
+                KeyCode::A,
+                KeyCode::C,
+                true,
+                true,
+                true,
+                true
+            ))
+            .max_output_events(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_output_keys_collects_all_emitted_keys() {
+        let device_config = DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
+            },
+            mappings: alloc::vec![
+                KeyMapping::simple(KeyCode::F1, KeyCode::VolumeUp),
+                KeyMapping::simple(KeyCode::F2, KeyCode::VolumeUp),
+                KeyMapping::modifier(KeyCode::CapsLock, 0x01),
+                KeyMapping::lock(KeyCode::ScrollLock, 0x02),
+                KeyMapping::tap_hold(KeyCode::Space, KeyCode::Enter, 0x00, 200),
+                KeyMapping::on_release(KeyCode::F3, KeyCode::MediaPlayPause, 200),
+                KeyMapping::modified_output(KeyCode::F4, KeyCode::C, false, true, false, false),
+                KeyMapping::cycle(KeyCode::F6, alloc::vec![KeyCode::X, KeyCode::Enter], 500),
+                KeyMapping::disable(KeyCode::Insert),
+                KeyMapping::macro_steps(
+                    KeyCode::F13,
+                    alloc::vec![
+                        MacroStep::Press(KeyCode::H),
+                        MacroStep::Release(KeyCode::H),
+                        MacroStep::Wait(120),
+                    ],
+                ),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x01),
+                    alloc::vec![BaseKeyMapping::Simple {
+                        from: KeyCode::F5,
+                        to: KeyCode::BrightnessDown,
+                    }],
+                ),
+            ],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        };
+
+        let mut expected = alloc::vec![
+            KeyCode::VolumeUp,
+            KeyCode::Enter,
+            KeyCode::MediaPlayPause,
+            KeyCode::C,
+            KeyCode::LCtrl,
+            KeyCode::X,
+            KeyCode::H,
+            KeyCode::BrightnessDown,
+        ];
+        expected.sort_unstable_by_key(|key| *key as u16);
+
+        assert_eq!(device_config.output_keys(), expected);
+    }
+
+    #[test]
+    fn test_input_keys_includes_conditional_mappings() {
+        let device_config = DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
+            },
+            mappings: alloc::vec![
+                KeyMapping::simple(KeyCode::F1, KeyCode::VolumeUp),
+                KeyMapping::tap_hold(KeyCode::Space, KeyCode::Enter, 0x00, 200),
+                KeyMapping::disable(KeyCode::Insert),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x00),
+                    alloc::vec![
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::F1,
+                            to: KeyCode::VolumeDown,
+                        },
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::J,
+                            to: KeyCode::Left,
+                        },
+                    ],
+                ),
+            ],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        };
+
+        let mut expected = alloc::vec![KeyCode::F1, KeyCode::Space, KeyCode::Insert, KeyCode::J];
+        expected.sort_unstable_by_key(|key| *key as u16);
+
+        assert_eq!(device_config.input_keys(), expected);
+    }
+
+    #[test]
+    fn test_device_identifier_excludes_round_trip() {
+        let identifier = DeviceIdentifier {
+            pattern: String::from("*"),
+            excludes: alloc::vec![String::from("*Mouse*"), String::from("*Yubikey*")],
+        };
+
+        let bytes = rkyv::to_bytes::<_, 256>(&identifier).expect("Serialization failed");
+        let archived =
+            rkyv::check_archived_root::<DeviceIdentifier>(&bytes[..]).expect("Validation failed");
+
+        assert_eq!(archived.pattern.as_str(), "*");
+        assert_eq!(archived.excludes.len(), 2);
+        assert_eq!(archived.excludes[0].as_str(), "*Mouse*");
+        assert_eq!(archived.excludes[1].as_str(), "*Yubikey*");
+    }
+
+    #[test]
+    fn test_device_config_priority_round_trip() {
+        let device_config = DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
+            },
+            mappings: alloc::vec::Vec::new(),
+            time_windows: alloc::vec::Vec::new(),
+            priority: -10,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        };
+
+        let bytes = rkyv::to_bytes::<_, 256>(&device_config).expect("Serialization failed");
+        let archived =
+            rkyv::check_archived_root::<DeviceConfig>(&bytes[..]).expect("Validation failed");
+
+        assert_eq!(archived.priority, -10);
+    }
+
+    #[test]
+    fn test_device_config_default_layer_round_trip() {
+        let device_config = DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
+            },
+            mappings: alloc::vec::Vec::new(),
+            time_windows: alloc::vec::Vec::new(),
+            priority: 0,
+            default_layer: Some(0x03),
+            tap_hold_threshold_ms: None,
+        };
+
+        let bytes = rkyv::to_bytes::<_, 256>(&device_config).expect("Serialization failed");
+        let archived =
+            rkyv::check_archived_root::<DeviceConfig>(&bytes[..]).expect("Validation failed");
+
+        assert_eq!(archived.default_layer.as_ref().copied(), Some(0x03));
+    }
+
+    #[test]
+    fn test_device_config_tap_hold_threshold_inheritance() {
+        let mut device_config = DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
+            },
+            mappings: alloc::vec::Vec::new(),
+            time_windows: alloc::vec::Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        };
+
+        assert_eq!(
+            device_config.tap_hold_threshold(None),
+            DEFAULT_TAP_HOLD_THRESHOLD_MS
+        );
+        assert_eq!(device_config.tap_hold_threshold(Some(150)), 150);
+
+        device_config.tap_hold_threshold_ms = Some(120);
+        assert_eq!(device_config.tap_hold_threshold(Some(150)), 120);
+    }
+
     #[test]
     fn test_config_root_serialization_round_trip() {
         let config = ConfigRoot {
@@ -246,8 +845,13 @@ mod tests {
             devices: alloc::vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: String::from("*"),
+                    excludes: alloc::vec::Vec::new(),
                 },
                 mappings: alloc::vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+                time_windows: alloc::vec::Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -263,7 +867,7 @@ mod tests {
         let archived = unsafe { rkyv::archived_root::<ConfigRoot>(&bytes[..]) };
 
         // Verify round-trip
-        assert_eq!(archived.version.major, 1);
+        assert_eq!(archived.version.major, 2);
         assert_eq!(archived.version.minor, 0);
         assert_eq!(archived.version.patch, 0);
         assert_eq!(archived.devices.len(), 1);
@@ -277,6 +881,7 @@ mod tests {
             devices: alloc::vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: String::from("USB Keyboard"),
+                    excludes: alloc::vec::Vec::new(),
                 },
                 mappings: alloc::vec![
                     KeyMapping::simple(KeyCode::A, KeyCode::B),
@@ -288,6 +893,10 @@ mod tests {
                         }],
                     ),
                 ],
+                time_windows: alloc::vec::Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 9999999999,
@@ -0,0 +1,456 @@
+//! Merging of a base configuration with an overlay.
+//!
+//! Lets users keep a shared base configuration plus small per-machine
+//! overlays. Device blocks are matched by pattern; within a matched block the
+//! overlay wins for every input key it maps, and everything else from the
+//! base is kept.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::config::conditions::{Condition, ConditionItem, TimeWindow};
+use crate::config::keys::KeyCode;
+use crate::config::mappings::{BaseKeyMapping, ConfigRoot, DeviceConfig, KeyMapping};
+
+impl ConfigRoot {
+    /// Overlays `overlay` on top of `base`.
+    ///
+    /// - Overlay devices whose pattern equals a base device's pattern are
+    ///   merged into it; other overlay devices are appended in order.
+    /// - Within a merged device, an overlay mapping replaces the base mapping
+    ///   for the same `from` key in the same context (unconditional, or a
+    ///   conditional block with an equal condition). Unmatched mappings and
+    ///   conditional blocks are appended.
+    /// - Exclusions are combined, and a non-zero overlay priority replaces the
+    ///   base priority. An overlay default layer or tap-hold threshold
+    ///   replaces the base's.
+    /// - Overlay time windows are added to the base's (identical windows are
+    ///   shared) and `TW_XX` ids in overlay conditions are renumbered to match.
+    ///
+    /// The version and metadata are taken from the overlay.
+    pub fn merge(base: ConfigRoot, overlay: ConfigRoot) -> ConfigRoot {
+        let mut devices = base.devices;
+
+        for overlay_device in overlay.devices {
+            match devices
+                .iter_mut()
+                .find(|d| d.identifier.pattern == overlay_device.identifier.pattern)
+            {
+                Some(device) => device.merge_from(overlay_device),
+                None => devices.push(overlay_device),
+            }
+        }
+
+        ConfigRoot {
+            version: overlay.version,
+            devices,
+            metadata: overlay.metadata,
+        }
+    }
+}
+
+impl DeviceConfig {
+    /// Merges `overlay` into this device; see [`ConfigRoot::merge`].
+    fn merge_from(&mut self, overlay: DeviceConfig) {
+        for exclude in overlay.identifier.excludes {
+            if !self.identifier.excludes.contains(&exclude) {
+                self.identifier.excludes.push(exclude);
+            }
+        }
+
+        if overlay.priority != 0 {
+            self.priority = overlay.priority;
+        }
+        if overlay.default_layer.is_some() {
+            self.default_layer = overlay.default_layer;
+        }
+        if overlay.tap_hold_threshold_ms.is_some() {
+            self.tap_hold_threshold_ms = overlay.tap_hold_threshold_ms;
+        }
+
+        let window_ids = merge_time_windows(&mut self.time_windows, &overlay.time_windows);
+
+        for mapping in overlay.mappings {
+            match mapping {
+                KeyMapping::Base(base) => {
+                    let from = input_key(&base);
+                    match self.mappings.iter_mut().find(
+                        |m| matches!(m, KeyMapping::Base(existing) if input_key(existing) == from),
+                    ) {
+                        Some(existing) => *existing = KeyMapping::Base(base),
+                        None => self.mappings.push(KeyMapping::Base(base)),
+                    }
+                }
+                KeyMapping::Conditional {
+                    condition,
+                    mappings,
+                } => {
+                    let condition = remap_condition(condition, &window_ids);
+                    let block = self.mappings.iter_mut().find_map(|m| match m {
+                        KeyMapping::Conditional {
+                            condition: existing,
+                            mappings,
+                        } if *existing == condition => Some(mappings),
+                        _ => None,
+                    });
+                    match block {
+                        Some(existing) => merge_base_mappings(existing, mappings),
+                        None => self
+                            .mappings
+                            .push(KeyMapping::conditional(condition, mappings)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replaces or appends each of `overlay` in `base` by input key.
+fn merge_base_mappings(base: &mut Vec<BaseKeyMapping>, overlay: Vec<BaseKeyMapping>) {
+    for mapping in overlay {
+        let from = input_key(&mapping);
+        match base.iter_mut().find(|m| input_key(m) == from) {
+            Some(existing) => *existing = mapping,
+            None => base.push(mapping),
+        }
+    }
+}
+
+/// Adds `overlay` windows to `windows`, reusing identical ones.
+///
+/// Returns the new id of each overlay window, indexed by its old id.
+fn merge_time_windows(windows: &mut Vec<TimeWindow>, overlay: &[TimeWindow]) -> Vec<u8> {
+    overlay
+        .iter()
+        .map(|window| {
+            let idx = match windows.iter().position(|w| w == window) {
+                Some(idx) => idx,
+                None => {
+                    windows.push(*window);
+                    windows.len() - 1
+                }
+            };
+            u8::try_from(idx).unwrap_or(u8::MAX)
+        })
+        .collect()
+}
+
+/// Renumbers time window ids in `condition` using `window_ids`.
+fn remap_condition(condition: Condition, window_ids: &[u8]) -> Condition {
+    let remap_id = |id: u8| window_ids.get(usize::from(id)).copied().unwrap_or(id);
+    let remap_items = |items: Vec<ConditionItem>| -> Vec<ConditionItem> {
+        items
+            .into_iter()
+            .map(|item| match item {
+                ConditionItem::TimeWindowActive(id) => {
+                    ConditionItem::TimeWindowActive(remap_id(id))
+                }
+                other => other,
+            })
+            .collect()
+    };
+
+    match condition {
+        Condition::TimeWindowActive(id) => Condition::TimeWindowActive(remap_id(id)),
+        Condition::AllActive(items) => Condition::AllActive(remap_items(items)),
+        Condition::NotActive(items) => Condition::NotActive(remap_items(items)),
+        Condition::AnyActive(items) => Condition::AnyActive(remap_items(items)),
+        Condition::Composite { all, none } => Condition::Composite {
+            all: remap_items(all),
+            none: remap_items(none),
+        },
+        Condition::Negate(inner) => {
+            Condition::Negate(Box::new(remap_condition(*inner, window_ids)))
+        }
+        other => other,
+    }
+}
+
+/// Returns the input key of a mapping.
+fn input_key(mapping: &BaseKeyMapping) -> KeyCode {
+    match mapping {
+        BaseKeyMapping::Simple { from, .. }
+        | BaseKeyMapping::Modifier { from, .. }
+        | BaseKeyMapping::Lock { from, .. }
+        | BaseKeyMapping::TapHold { from, .. }
+        | BaseKeyMapping::ModifiedOutput { from, .. }
+        | BaseKeyMapping::OnRelease { from, .. }
+        | BaseKeyMapping::LayerToggle { from, .. }
+        | BaseKeyMapping::Cycle { from, .. }
+        | BaseKeyMapping::Disable { from }
+        | BaseKeyMapping::Macro { from, .. } => *from,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_util::ConfigRootBuilder;
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_merge_overlay_overrides_same_key() {
+        let base = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::simple(KeyCode::C, KeyCode::D),
+            ])
+            .source_hash("base")
+            .build();
+        let overlay = ConfigRootBuilder::new()
+            .mappings(vec![KeyMapping::tap_hold(
+                KeyCode::A,
+                KeyCode::Escape,
+                0,
+                200,
+            )])
+            .source_hash("overlay")
+            .build();
+
+        let merged = ConfigRoot::merge(base, overlay);
+
+        assert_eq!(merged.devices.len(), 1);
+        assert_eq!(
+            merged.devices[0].mappings,
+            vec![
+                KeyMapping::tap_hold(KeyCode::A, KeyCode::Escape, 0, 200),
+                KeyMapping::simple(KeyCode::C, KeyCode::D),
+            ]
+        );
+        assert_eq!(merged.metadata.source_hash, "overlay");
+    }
+
+    #[test]
+    fn test_merge_is_additive_for_new_keys_and_patterns() {
+        let base = ConfigRootBuilder::new()
+            .mappings(vec![KeyMapping::simple(KeyCode::A, KeyCode::B)])
+            .source_hash("base")
+            .build();
+        let overlay = ConfigRootBuilder::new()
+            .devices(vec![
+                DeviceConfig::new("*", vec![KeyMapping::simple(KeyCode::C, KeyCode::D)]),
+                DeviceConfig::new(
+                    "*Numpad*",
+                    vec![KeyMapping::simple(KeyCode::Numpad1, KeyCode::F13)],
+                ),
+            ])
+            .source_hash("overlay")
+            .build();
+
+        let merged = ConfigRoot::merge(base, overlay);
+
+        assert_eq!(merged.devices.len(), 2);
+        assert_eq!(
+            merged.devices[0].mappings,
+            vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::simple(KeyCode::C, KeyCode::D),
+            ]
+        );
+        assert_eq!(merged.devices[1].identifier.pattern, "*Numpad*");
+    }
+
+    #[test]
+    fn test_merge_conditional_blocks_by_condition() {
+        let base = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::simple(KeyCode::H, KeyCode::H),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x01),
+                    vec![
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::H,
+                            to: KeyCode::Left,
+                        },
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::L,
+                            to: KeyCode::Right,
+                        },
+                    ],
+                ),
+            ])
+            .source_hash("base")
+            .build();
+        let overlay = ConfigRootBuilder::new()
+            .mappings(vec![
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x01),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Home,
+                    }],
+                ),
+                KeyMapping::conditional(
+                    Condition::LockActive(0x01),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::J,
+                        to: KeyCode::Down,
+                    }],
+                ),
+            ])
+            .source_hash("overlay")
+            .build();
+
+        let merged = ConfigRoot::merge(base, overlay);
+
+        assert_eq!(
+            merged.devices[0].mappings,
+            vec![
+                KeyMapping::simple(KeyCode::H, KeyCode::H),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0x01),
+                    vec![
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::H,
+                            to: KeyCode::Home,
+                        },
+                        BaseKeyMapping::Simple {
+                            from: KeyCode::L,
+                            to: KeyCode::Right,
+                        },
+                    ],
+                ),
+                KeyMapping::conditional(
+                    Condition::LockActive(0x01),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::J,
+                        to: KeyCode::Down,
+                    }],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_renumbers_overlay_time_windows() {
+        let mut base_device = DeviceConfig::new("*", Vec::new());
+        base_device.time_windows = vec![TimeWindow {
+            start_minute: 540,
+            end_minute: 1020,
+        }];
+        let mut overlay_device = DeviceConfig::new(
+            "*",
+            vec![
+                KeyMapping::conditional(
+                    Condition::TimeWindowActive(0),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::A,
+                        to: KeyCode::B,
+                    }],
+                ),
+                KeyMapping::conditional(
+                    Condition::TimeWindowActive(1),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::C,
+                        to: KeyCode::D,
+                    }],
+                ),
+            ],
+        );
+        overlay_device.time_windows = vec![
+            TimeWindow {
+                start_minute: 1320,
+                end_minute: 360,
+            },
+            TimeWindow {
+                start_minute: 540,
+                end_minute: 1020,
+            },
+        ];
+
+        let merged = ConfigRoot::merge(
+            ConfigRootBuilder::new()
+                .devices(vec![base_device])
+                .source_hash("base")
+                .build(),
+            ConfigRootBuilder::new()
+                .devices(vec![overlay_device])
+                .source_hash("overlay")
+                .build(),
+        );
+
+        let merged_device = &merged.devices[0];
+        assert_eq!(merged_device.time_windows.len(), 2);
+        assert_eq!(merged_device.time_windows[1].start_minute, 1320);
+        assert!(matches!(
+            merged_device.mappings[0],
+            KeyMapping::Conditional {
+                condition: Condition::TimeWindowActive(1),
+                ..
+            }
+        ));
+        assert!(matches!(
+            merged_device.mappings[1],
+            KeyMapping::Conditional {
+                condition: Condition::TimeWindowActive(0),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_merge_combines_excludes_and_priority() {
+        let mut base_device = DeviceConfig::new("*", Vec::new());
+        base_device.identifier.excludes = vec![String::from("*Mouse*")];
+        base_device.priority = 5;
+        let mut overlay_device = DeviceConfig::new("*", Vec::new());
+        overlay_device.identifier.excludes =
+            vec![String::from("*Mouse*"), String::from("*Yubikey*")];
+
+        let merged = ConfigRoot::merge(
+            ConfigRootBuilder::new()
+                .devices(vec![base_device])
+                .source_hash("base")
+                .build(),
+            ConfigRootBuilder::new()
+                .devices(vec![overlay_device.clone()])
+                .source_hash("overlay")
+                .build(),
+        );
+        assert_eq!(
+            merged.devices[0].identifier.excludes,
+            vec![String::from("*Mouse*"), String::from("*Yubikey*")]
+        );
+        assert_eq!(merged.devices[0].priority, 5);
+
+        overlay_device.priority = -10;
+        let merged = ConfigRoot::merge(
+            merged,
+            ConfigRootBuilder::new()
+                .devices(vec![overlay_device])
+                .source_hash("overlay")
+                .build(),
+        );
+        assert_eq!(merged.devices[0].priority, -10);
+    }
+
+    #[test]
+    fn test_merge_overlay_default_layer_wins() {
+        let mut base_device = DeviceConfig::new("*", Vec::new());
+        base_device.default_layer = Some(1);
+        let mut overlay_device = DeviceConfig::new("*", Vec::new());
+
+        let merged = ConfigRoot::merge(
+            ConfigRootBuilder::new()
+                .devices(vec![base_device])
+                .source_hash("base")
+                .build(),
+            ConfigRootBuilder::new()
+                .devices(vec![overlay_device.clone()])
+                .source_hash("overlay")
+                .build(),
+        );
+        assert_eq!(merged.devices[0].default_layer, Some(1));
+
+        overlay_device.default_layer = Some(2);
+        let merged = ConfigRoot::merge(
+            merged,
+            ConfigRootBuilder::new()
+                .devices(vec![overlay_device])
+                .source_hash("overlay")
+                .build(),
+        );
+        assert_eq!(merged.devices[0].default_layer, Some(2));
+    }
+}
@@ -60,12 +60,20 @@
 //! ```
 
 pub mod conditions;
+pub mod features;
 pub mod keys;
 pub mod mappings;
+mod merge;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod types;
 
 // Re-export core types
-pub use conditions::{Condition, ConditionItem};
+pub use conditions::{Condition, ConditionItem, TimeWindow};
+pub use features::Features;
 pub use keys::KeyCode;
-pub use mappings::{BaseKeyMapping, ConfigRoot, DeviceConfig, DeviceIdentifier, KeyMapping};
+pub use mappings::{
+    BaseKeyMapping, ConfigRoot, DeviceConfig, DeviceIdentifier, KeyMapping, MacroStep, MappingKind,
+    TapHoldPolicy, DEFAULT_TAP_HOLD_THRESHOLD_MS,
+};
 pub use types::{Metadata, Version};
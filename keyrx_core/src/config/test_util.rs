@@ -0,0 +1,88 @@
+//! Builders for configurations used in tests
+//!
+//! Compiled for keyrx_core's own tests, and for other crates' tests through
+//! the `test-util` feature.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::config::{ConfigRoot, DeviceConfig, KeyMapping, Metadata, Version};
+
+/// Builds a [`ConfigRoot`] of the current version with placeholder metadata
+///
+/// # Example
+///
+/// ```
+/// use keyrx_core::config::test_util::ConfigRootBuilder;
+/// use keyrx_core::config::{KeyCode, KeyMapping};
+///
+/// let config = ConfigRootBuilder::new()
+///     .mappings(vec![KeyMapping::simple(KeyCode::A, KeyCode::B)])
+///     .source_hash("abc")
+///     .build();
+/// assert_eq!(config.devices[0].identifier.pattern, "*");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigRootBuilder {
+    devices: Vec<DeviceConfig>,
+    metadata: Metadata,
+}
+
+impl Default for ConfigRootBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigRootBuilder {
+    /// Starts a config with no devices, compiled at timestamp 0 by compiler
+    /// version "test" from a source with an empty hash
+    pub fn new() -> Self {
+        Self {
+            devices: Vec::new(),
+            metadata: Metadata {
+                compilation_timestamp: 0,
+                compiler_version: String::from("test"),
+                source_hash: String::new(),
+            },
+        }
+    }
+
+    /// Appends a device
+    pub fn device(mut self, device: DeviceConfig) -> Self {
+        self.devices.push(device);
+        self
+    }
+
+    /// Appends devices, in order
+    pub fn devices(mut self, devices: impl IntoIterator<Item = DeviceConfig>) -> Self {
+        self.devices.extend(devices);
+        self
+    }
+
+    /// Appends a device matching every keyboard (`*`) with `mappings`
+    pub fn mappings(self, mappings: Vec<KeyMapping>) -> Self {
+        self.device(DeviceConfig::new("*", mappings))
+    }
+
+    /// Sets the source hash recorded in the metadata
+    pub fn source_hash(mut self, source_hash: &str) -> Self {
+        self.metadata.source_hash = String::from(source_hash);
+        self
+    }
+
+    /// Sets the compilation timestamp recorded in the metadata
+    pub fn compilation_timestamp(mut self, timestamp: u64) -> Self {
+        self.metadata.compilation_timestamp = timestamp;
+        self
+    }
+
+    /// Returns the config
+    pub fn build(self) -> ConfigRoot {
+        ConfigRoot {
+            version: Version::current(),
+            devices: self.devices,
+            metadata: self.metadata,
+        }
+    }
+}
@@ -29,14 +29,59 @@ pub struct Version {
 }
 
 impl Version {
-    /// Returns the current version (1.0.0)
+    /// Returns the current version (2.0.0)
+    ///
+    /// 2.0.0 covers the schema changes since 1.0.0 (device exclusions, time
+    /// windows, device priority, default layers, tap-hold policies and
+    /// thresholds, and the new mapping and condition kinds), which 1.x
+    /// runtimes cannot read.
     pub const fn current() -> Self {
         Self {
-            major: 1,
+            major: 2,
             minor: 0,
             patch: 0,
         }
     }
+
+    /// Returns whether a config with this version can be loaded by a runtime
+    /// that understands `runtime`.
+    ///
+    /// The major versions must match and the config's minor version must not
+    /// be newer than the runtime's, since a newer minor version may contain
+    /// mappings the runtime does not know. Patch versions are ignored.
+    pub const fn is_compatible_with(&self, runtime: Version) -> bool {
+        self.major == runtime.major && self.minor <= runtime.minor
+    }
+
+    /// Explains why a config with this version cannot be loaded by
+    /// `runtime` and how to fix it.
+    ///
+    /// Only meaningful when [`is_compatible_with`](Self::is_compatible_with)
+    /// returns `false`.
+    pub fn incompatibility_message(&self, runtime: Version) -> alloc::string::String {
+        let newer = (self.major, self.minor) > (runtime.major, runtime.minor);
+        if newer {
+            alloc::format!(
+                "config version {} requires a newer keyrx (this build reads config versions up to {}.{}.x); recompile it with your installed compiler",
+                self, runtime.major, runtime.minor
+            )
+        } else {
+            alloc::format!(
+                "config version {} is from an older keyrx (this build reads {}.x configs); recompile it with your installed compiler",
+                self, runtime.major
+            )
+        }
+    }
+}
+
+impl From<&ArchivedVersion> for Version {
+    fn from(archived: &ArchivedVersion) -> Self {
+        Self {
+            major: archived.major,
+            minor: archived.minor,
+            patch: archived.patch,
+        }
+    }
 }
 
 impl fmt::Display for Version {
@@ -72,7 +117,7 @@ mod tests {
     #[test]
     fn test_version_current() {
         let version = Version::current();
-        assert_eq!(version.major, 1);
+        assert_eq!(version.major, 2);
         assert_eq!(version.minor, 0);
         assert_eq!(version.patch, 0);
     }
@@ -80,6 +125,49 @@ mod tests {
     #[test]
     fn test_version_display() {
         let version = Version::current();
-        assert_eq!(version.to_string(), "1.0.0");
+        assert_eq!(version.to_string(), "2.0.0");
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        let runtime = Version {
+            major: 1,
+            minor: 2,
+            patch: 0,
+        };
+        let version = |major, minor, patch| Version {
+            major,
+            minor,
+            patch,
+        };
+
+        assert!(version(1, 0, 0).is_compatible_with(runtime));
+        assert!(version(1, 2, 9).is_compatible_with(runtime));
+        assert!(!version(1, 3, 0).is_compatible_with(runtime));
+        assert!(!version(2, 0, 0).is_compatible_with(runtime));
+        assert!(!version(0, 9, 0).is_compatible_with(runtime));
+    }
+
+    #[test]
+    fn test_version_incompatibility_message() {
+        let runtime = Version::current();
+        let newer = Version {
+            major: 3,
+            minor: 0,
+            patch: 0,
+        };
+        assert_eq!(
+            newer.incompatibility_message(runtime),
+            "config version 3.0.0 requires a newer keyrx (this build reads config versions up to 2.0.x); recompile it with your installed compiler"
+        );
+
+        let older = Version {
+            major: 0,
+            minor: 9,
+            patch: 1,
+        };
+        assert!(older
+            .incompatibility_message(runtime)
+            .starts_with("config version 0.9.1 is from an older keyrx"));
     }
 }
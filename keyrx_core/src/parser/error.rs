@@ -34,10 +34,37 @@ pub enum ParseError {
         column: usize,
         message: String,
     },
+    /// The script exceeded one of the parser's resource limits
+    LimitExceeded { kind: LimitKind, limit: u64 },
     /// Generic error message
     Other(String),
 }
 
+/// Resource limit reported by [`ParseError::LimitExceeded`].
+///
+/// Each kind matches a field of [`ParserLimits`](super::ParserLimits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    /// Total operations the script may run
+    Operations,
+    /// Nesting depth of a single expression
+    ExpressionDepth,
+    /// Nesting depth of function calls
+    CallDepth,
+}
+
+impl LimitKind {
+    /// Name of the [`ParserLimits`](super::ParserLimits) field holding this
+    /// limit.
+    pub fn field(self) -> &'static str {
+        match self {
+            LimitKind::Operations => "max_operations",
+            LimitKind::ExpressionDepth => "max_expr_depth",
+            LimitKind::CallDepth => "max_call_levels",
+        }
+    }
+}
+
 impl core::fmt::Display for ParseError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
@@ -85,6 +112,25 @@ impl core::fmt::Display for ParseError {
             } => {
                 write!(f, "Line {}, column {}: {}", line, column, message)
             }
+            ParseError::LimitExceeded { kind, limit } => {
+                let what = match kind {
+                    LimitKind::Operations => "the script ran more than",
+                    LimitKind::ExpressionDepth => "an expression is nested more than",
+                    LimitKind::CallDepth => "function calls are nested more than",
+                };
+                let unit = match kind {
+                    LimitKind::Operations => "operations",
+                    _ => "levels deep",
+                };
+                write!(
+                    f,
+                    "Parser limit exceeded: {} {} {}. Simplify the configuration, or raise `{}` if it is trusted.",
+                    what,
+                    limit,
+                    unit,
+                    kind.field()
+                )
+            }
             ParseError::Other(msg) => write!(f, "{}", msg),
         }
     }
@@ -1,7 +1,9 @@
 //! Conditional functions for Rhai DSL.
 //!
 //! Provides when_start(), when_end(), when_not_start(), when_not_end(),
-//! when_device_start(), when_device_end() functions.
+//! when_device_start(), when_device_end() functions, the all_of() /
+//! none_of() builders for composite conditions, any_of() for OR, and not()
+//! to invert a whole condition.
 
 use crate::config::{Condition, ConditionItem, KeyMapping};
 use crate::parser::state::ParserState;
@@ -32,27 +34,123 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     engine.register_fn(
         "when_start",
         move |conds: Array| -> Result<(), Box<EvalAltResult>> {
-            let mut condition_items = Vec::new();
-            for cond_dyn in conds {
-                let cond_str = cond_dyn
-                    .into_string()
-                    .map_err(|_| "Condition must be a string")?;
-                let cond = parse_condition_string(&cond_str)
-                    .map_err(|e| format!("Invalid condition: {}", e))?;
-                match cond {
-                    Condition::ModifierActive(id) => {
-                        condition_items.push(ConditionItem::ModifierActive(id))
-                    }
-                    Condition::LockActive(id) => {
-                        condition_items.push(ConditionItem::LockActive(id))
-                    }
-                    _ => return Err("Only single modifiers/locks allowed in array".into()),
-                }
-            }
+            let condition_items = parse_condition_items(conds)?;
             start_conditional_block(&state_clone_multi, Condition::AllActive(condition_items))
         },
     );
 
+    // all_of([...]) / none_of([...]) build a CompositeCondition, which can be
+    // extended with .and_none_of([...]) / .and_all_of([...])
+    engine.register_type::<CompositeCondition>();
+    engine.register_fn(
+        "all_of",
+        |conds: Array| -> Result<CompositeCondition, Box<EvalAltResult>> {
+            Ok(CompositeCondition {
+                all: parse_condition_items(conds)?,
+                none: Vec::new(),
+            })
+        },
+    );
+    engine.register_fn(
+        "none_of",
+        |conds: Array| -> Result<CompositeCondition, Box<EvalAltResult>> {
+            Ok(CompositeCondition {
+                all: Vec::new(),
+                none: parse_condition_items(conds)?,
+            })
+        },
+    );
+    engine.register_fn(
+        "and_all_of",
+        |mut composite: CompositeCondition,
+         conds: Array|
+         -> Result<CompositeCondition, Box<EvalAltResult>> {
+            composite.all.extend(parse_condition_items(conds)?);
+            Ok(composite)
+        },
+    );
+    engine.register_fn(
+        "and_none_of",
+        |mut composite: CompositeCondition,
+         conds: Array|
+         -> Result<CompositeCondition, Box<EvalAltResult>> {
+            composite.none.extend(parse_condition_items(conds)?);
+            Ok(composite)
+        },
+    );
+
+    // any_of([...]) builds an OR condition, same as "MD_00 | LK_01"
+    engine.register_type::<AnyOfCondition>();
+    engine.register_fn(
+        "any_of",
+        |conds: Array| -> Result<AnyOfCondition, Box<EvalAltResult>> {
+            let items = parse_condition_items(conds)?;
+            if items.is_empty() {
+                return Err("any_of() needs at least one modifier, lock or time window".into());
+            }
+            Ok(AnyOfCondition { items })
+        },
+    );
+
+    // when_start() for a composite condition (all of ... AND none of ...)
+    let state_clone_composite = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |composite: CompositeCondition| -> Result<(), Box<EvalAltResult>> {
+            let condition = composite.into_condition()?;
+            start_conditional_block(&state_clone_composite, condition)
+        },
+    );
+
+    // when_start() for an any_of() condition
+    let state_clone_any = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |any: AnyOfCondition| -> Result<(), Box<EvalAltResult>> {
+            start_conditional_block(&state_clone_any, Condition::AnyActive(any.items))
+        },
+    );
+
+    // not(...) inverts a whole condition, e.g. not(all_of(["MD_00", "LK_01"]))
+    engine.register_type::<NegatedCondition>();
+    engine.register_fn(
+        "not",
+        |cond: &str| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            let condition =
+                parse_condition_string(cond).map_err(|e| format!("Invalid condition: {}", e))?;
+            Ok(NegatedCondition::of(condition))
+        },
+    );
+    engine.register_fn(
+        "not",
+        |conds: Array| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            Ok(NegatedCondition::of(Condition::AllActive(
+                parse_condition_items(conds)?,
+            )))
+        },
+    );
+    engine.register_fn(
+        "not",
+        |composite: CompositeCondition| -> Result<NegatedCondition, Box<EvalAltResult>> {
+            Ok(NegatedCondition::of(composite.into_condition()?))
+        },
+    );
+    engine.register_fn("not", |any: AnyOfCondition| -> NegatedCondition {
+        NegatedCondition::of(Condition::AnyActive(any.items))
+    });
+    engine.register_fn("not", |negated: NegatedCondition| -> NegatedCondition {
+        NegatedCondition::of(negated.condition)
+    });
+
+    // when_start() for a not() condition
+    let state_clone_negated = Arc::clone(&state);
+    engine.register_fn(
+        "when_start",
+        move |negated: NegatedCondition| -> Result<(), Box<EvalAltResult>> {
+            start_conditional_block(&state_clone_negated, negated.condition)
+        },
+    );
+
     // when_end() - finalize conditional block
     let state_clone_end = Arc::clone(&state);
     engine.register_fn("when_end", move || -> Result<(), Box<EvalAltResult>> {
@@ -69,6 +167,7 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
             let item = match condition {
                 Condition::ModifierActive(id) => ConditionItem::ModifierActive(id),
                 Condition::LockActive(id) => ConditionItem::LockActive(id),
+                Condition::TimeWindowActive(id) => ConditionItem::TimeWindowActive(id),
                 _ => return Err("Only single modifiers/locks allowed in when_not".into()),
             };
             start_conditional_block(&state_clone_not, Condition::NotActive(alloc::vec![item]))
@@ -106,6 +205,71 @@ pub fn register_when_functions(engine: &mut Engine, state: Arc<Mutex<ParserState
     );
 }
 
+/// Builder for a composite condition, returned by all_of() and none_of().
+/// Consumed by the when_start() overload to create a `Condition::Composite`.
+#[derive(Clone, Debug, Default)]
+pub struct CompositeCondition {
+    pub all: Vec<ConditionItem>,
+    pub none: Vec<ConditionItem>,
+}
+
+impl CompositeCondition {
+    fn into_condition(self) -> Result<Condition, Box<EvalAltResult>> {
+        if self.all.is_empty() && self.none.is_empty() {
+            return Err("Composite condition needs at least one modifier or lock".into());
+        }
+        Ok(Condition::Composite {
+            all: self.all,
+            none: self.none,
+        })
+    }
+}
+
+/// OR condition returned by any_of().
+/// Consumed by the when_start() overload to create a `Condition::AnyActive`.
+#[derive(Clone, Debug)]
+pub struct AnyOfCondition {
+    pub items: Vec<ConditionItem>,
+}
+
+/// Inverted condition returned by not().
+/// Consumed by the when_start() overload; holds the complete `Condition::Negate`.
+#[derive(Clone, Debug)]
+pub struct NegatedCondition {
+    pub condition: Condition,
+}
+
+impl NegatedCondition {
+    fn of(condition: Condition) -> Self {
+        Self {
+            condition: Condition::Negate(Box::new(condition)),
+        }
+    }
+}
+
+/// Parse an array of "MD_XX"/"LK_XX" strings into condition items
+fn parse_condition_items(conds: Array) -> Result<Vec<ConditionItem>, Box<EvalAltResult>> {
+    let mut condition_items = Vec::new();
+    for cond_dyn in conds {
+        let cond_str = cond_dyn
+            .into_string()
+            .map_err(|_| "Condition must be a string")?;
+        let cond =
+            parse_condition_string(&cond_str).map_err(|e| format!("Invalid condition: {}", e))?;
+        match cond {
+            Condition::ModifierActive(id) => {
+                condition_items.push(ConditionItem::ModifierActive(id))
+            }
+            Condition::LockActive(id) => condition_items.push(ConditionItem::LockActive(id)),
+            Condition::TimeWindowActive(id) => {
+                condition_items.push(ConditionItem::TimeWindowActive(id))
+            }
+            _ => return Err("Only single modifiers/locks allowed in array".into()),
+        }
+    }
+    Ok(condition_items)
+}
+
 /// Start a conditional block - push a new conditional stack entry
 fn start_conditional_block(
     state: &Arc<Mutex<ParserState>>,
@@ -0,0 +1,73 @@
+//! Cycle function for Rhai DSL.
+//!
+//! Provides cycle(key, outputs, reset_ms) function.
+
+use crate::config::{BaseKeyMapping, KeyMapping};
+use crate::parser::state::ParserState;
+use crate::parser::validators::{parse_physical_key, parse_virtual_key};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use rhai::{Array, Engine, EvalAltResult};
+use spin::Mutex;
+
+/// Register cycle function with the Rhai engine.
+pub fn register_cycle_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "cycle",
+        move |key: &str, outputs: Array, reset_ms: i64| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone.lock();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+            if outputs.is_empty() {
+                return Err("cycle outputs must contain at least one key".into());
+            }
+            let outputs = outputs
+                .into_iter()
+                .map(|output| {
+                    let output = output
+                        .into_string()
+                        .map_err(|_| "cycle outputs must be strings")?;
+                    if !output.starts_with("VK_") {
+                        return Err(format!(
+                            "cycle output parameter must have VK_ prefix, got: {}",
+                            output
+                        ));
+                    }
+                    parse_virtual_key(&output).map_err(|e| format!("Invalid output key: {}", e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let reset_ms = u16::try_from(reset_ms)
+                .ok()
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| {
+                    format!(
+                        "cycle reset must be between 1 and {} ms, got: {}",
+                        u16::MAX,
+                        reset_ms
+                    )
+                })?;
+
+            let base_mapping = BaseKeyMapping::Cycle {
+                from: from_key,
+                outputs,
+                reset_ms,
+            };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("cycle() must be called inside a device_start() block".into())
+            }
+        },
+    );
+}
@@ -1,16 +1,19 @@
 //! Device block functions for Rhai DSL.
 //!
-//! Provides device_start() and device_end() functions.
+//! Provides device_start(), exclude(), priority(), default_layer() and
+//! device_end() functions.
 
 use crate::config::{DeviceConfig, DeviceIdentifier};
 use crate::parser::state::ParserState;
+use crate::parser::validators::parse_modifier_id;
 use alloc::boxed::Box;
 use alloc::string::ToString;
 use alloc::sync::Arc;
 use rhai::{Engine, EvalAltResult};
 use spin::Mutex;
 
-/// Register device_start and device_end functions with the Rhai engine.
+/// Register device_start, exclude, priority, default_layer and device_end functions with the
+/// Rhai engine.
 pub fn register_device_functions(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
     let state_clone_start = Arc::clone(&state);
     engine.register_fn(
@@ -25,14 +28,75 @@ pub fn register_device_functions(engine: &mut Engine, state: Arc<Mutex<ParserSta
             state.current_device = Some(DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: pattern.to_string(),
+                    excludes: alloc::vec::Vec::new(),
                 },
                 mappings: alloc::vec::Vec::new(),
+                time_windows: alloc::vec::Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             });
 
             Ok(())
         },
     );
 
+    let state_clone_exclude = Arc::clone(&state);
+    engine.register_fn(
+        "exclude",
+        move |pattern: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone_exclude.lock();
+
+            if let Some(ref mut device) = state.current_device {
+                device.identifier.excludes.push(pattern.to_string());
+                Ok(())
+            } else {
+                Err("exclude() must be called inside a device_start() block".into())
+            }
+        },
+    );
+
+    let state_clone_priority = Arc::clone(&state);
+    engine.register_fn(
+        "priority",
+        move |priority: i64| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone_priority.lock();
+
+            let Ok(priority) = i32::try_from(priority) else {
+                return Err(alloc::format!(
+                    "priority() must fit in a 32-bit integer, got {}",
+                    priority
+                )
+                .into());
+            };
+
+            if let Some(ref mut device) = state.current_device {
+                device.priority = priority;
+                Ok(())
+            } else {
+                Err("priority() must be called inside a device_start() block".into())
+            }
+        },
+    );
+
+    let state_clone_default_layer = Arc::clone(&state);
+    engine.register_fn(
+        "default_layer",
+        move |layer: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone_default_layer.lock();
+
+            let layer_id = parse_modifier_id(layer)
+                .map_err(|e| alloc::format!("Invalid default layer: {}", e))?;
+
+            if let Some(ref mut device) = state.current_device {
+                device.default_layer = Some(layer_id);
+                Ok(())
+            } else {
+                Err("default_layer() must be called inside a device_start() block".into())
+            }
+        },
+    );
+
     let state_clone_end = Arc::clone(&state);
     engine.register_fn("device_end", move || -> Result<(), Box<EvalAltResult>> {
         let mut state = state_clone_end.lock();
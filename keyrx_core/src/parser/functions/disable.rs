@@ -0,0 +1,38 @@
+//! Disable function for Rhai DSL.
+//!
+//! Provides disable(key) function.
+
+use crate::config::{BaseKeyMapping, KeyMapping};
+use crate::parser::state::ParserState;
+use crate::parser::validators::parse_physical_key;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::sync::Arc;
+use rhai::{Engine, EvalAltResult};
+use spin::Mutex;
+
+/// Register disable function with the Rhai engine.
+pub fn register_disable_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "disable",
+        move |key: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone.lock();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+            let base_mapping = BaseKeyMapping::Disable { from: from_key };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("disable() must be called inside a device_start() block".into())
+            }
+        },
+    );
+}
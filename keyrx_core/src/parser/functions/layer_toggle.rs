@@ -0,0 +1,50 @@
+//! LayerToggle function for Rhai DSL.
+//!
+//! Provides layer_toggle(key, layer) function.
+
+use crate::config::{BaseKeyMapping, KeyMapping};
+use crate::parser::state::ParserState;
+use crate::parser::validators::{parse_modifier_id, parse_physical_key};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::sync::Arc;
+use rhai::{Engine, EvalAltResult};
+use spin::Mutex;
+
+/// Register layer_toggle function with the Rhai engine.
+pub fn register_layer_toggle_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "layer_toggle",
+        move |key: &str, layer: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone.lock();
+            let from_key = parse_physical_key(key).map_err(|e| format!("Invalid key: {}", e))?;
+
+            if !layer.starts_with("MD_") {
+                return Err(format!(
+                    "layer_toggle layer parameter must have MD_ prefix, got: {}",
+                    layer
+                )
+                .into());
+            }
+            let layer_id = parse_modifier_id(layer).map_err(|e| format!("Invalid layer: {}", e))?;
+
+            let base_mapping = BaseKeyMapping::LayerToggle {
+                from: from_key,
+                layer_id,
+            };
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.push(base_mapping);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.push(KeyMapping::Base(base_mapping));
+                Ok(())
+            } else {
+                Err("layer_toggle() must be called inside a device_start() block".into())
+            }
+        },
+    );
+}
@@ -0,0 +1,113 @@
+//! Map range function for Rhai DSL.
+//!
+//! Provides map_range(from, to) and the `..` operator on strings that builds
+//! the key ranges it takes. Both ends of a range are included.
+
+use crate::config::{BaseKeyMapping, KeyCode, KeyMapping};
+use crate::parser::error::ParseError;
+use crate::parser::state::ParserState;
+use crate::parser::validators::{key_from_code, parse_physical_key, parse_virtual_key};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use rhai::{Engine, EvalAltResult};
+use spin::Mutex;
+
+/// Range of keys written as `"first".."last"`.
+/// Returned by the `..` operator on strings, consumed by map_range().
+#[derive(Clone, Debug)]
+pub struct KeyRange {
+    pub first: String,
+    pub last: String,
+}
+
+impl KeyRange {
+    /// Returns every key from `first` to `last`, parsing both ends with
+    /// `parse`.
+    pub fn expand(
+        &self,
+        parse: fn(&str) -> Result<KeyCode, ParseError>,
+    ) -> Result<Vec<KeyCode>, String> {
+        let first = parse(&self.first).map_err(|e| e.to_string())?;
+        let last = parse(&self.last).map_err(|e| e.to_string())?;
+        if (last as u16) < (first as u16) {
+            return Err(format!("range {} runs backwards", self));
+        }
+
+        let mut keys = vec![first];
+        for code in (first as u16 + 1)..=(last as u16) {
+            let Some(key) = key_from_code(code) else {
+                return Err(format!(
+                    "range {} is not contiguous: no key follows {:?}",
+                    self,
+                    keys[keys.len() - 1]
+                ));
+            };
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+impl fmt::Display for KeyRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"..\"{}\"", self.first, self.last)
+    }
+}
+
+/// Register map_range function with the Rhai engine.
+pub fn register_map_range_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    engine.register_type_with_name::<KeyRange>("KeyRange");
+
+    // "first".."last" - builds a KeyRange
+    engine.register_fn("..", |first: &str, last: &str| KeyRange {
+        first: first.to_string(),
+        last: last.to_string(),
+    });
+
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "map_range",
+        move |from: KeyRange, to: KeyRange| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone.lock();
+            let from_keys = from
+                .expand(parse_physical_key)
+                .map_err(|e| format!("Invalid 'from' range: {}", e))?;
+            let to_keys = to
+                .expand(parse_virtual_key)
+                .map_err(|e| format!("Invalid 'to' range: {}", e))?;
+
+            if from_keys.len() != to_keys.len() {
+                return Err(format!(
+                    "map_range() ranges differ in size: {} has {} keys, {} has {}",
+                    from,
+                    from_keys.len(),
+                    to,
+                    to_keys.len()
+                )
+                .into());
+            }
+
+            let base_mappings = from_keys
+                .into_iter()
+                .zip(to_keys)
+                .map(|(from, to)| BaseKeyMapping::Simple { from, to });
+
+            // If we're inside a conditional block, add to the conditional stack
+            if let Some((_condition, ref mut mappings)) = state.conditional_stack.last_mut() {
+                mappings.extend(base_mappings);
+                Ok(())
+            } else if let Some(ref mut device) = state.current_device {
+                // Otherwise, add to current device
+                device.mappings.extend(base_mappings.map(KeyMapping::Base));
+                Ok(())
+            } else {
+                Err("map_range() must be called inside a device() block".into())
+            }
+        },
+    );
+}
@@ -1,9 +1,16 @@
 //! Rhai function registrations for the DSL parser.
 
 pub mod conditional;
+pub mod cycle;
 pub mod device;
+pub mod disable;
+pub mod layer_toggle;
+pub mod macros;
 pub mod map;
+pub mod map_range;
 pub mod modifiers;
+pub mod on_release;
 pub mod tap_hold;
+pub mod time_window;
 
 pub use modifiers::ModifiedKey;
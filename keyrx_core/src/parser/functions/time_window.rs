@@ -0,0 +1,61 @@
+//! TimeWindow function for Rhai DSL.
+//!
+//! Provides time_window(id, start, end) function.
+
+use crate::config::TimeWindow;
+use crate::parser::state::ParserState;
+use crate::parser::validators::parse_time_window_id;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use rhai::{Engine, EvalAltResult};
+use spin::Mutex;
+
+/// Register time_window function with the Rhai engine.
+pub fn register_time_window_function(engine: &mut Engine, state: Arc<Mutex<ParserState>>) {
+    let state_clone = Arc::clone(&state);
+    engine.register_fn(
+        "time_window",
+        move |id: &str, start: &str, end: &str| -> Result<(), Box<EvalAltResult>> {
+            let mut state = state_clone.lock();
+            let window_id =
+                parse_time_window_id(id).map_err(|e| format!("Invalid time window: {}", e))?;
+            let window = TimeWindow {
+                start_minute: parse_time_of_day(start)?,
+                end_minute: parse_time_of_day(end)?,
+            };
+
+            let Some(ref mut device) = state.current_device else {
+                return Err("time_window() must be called inside a device_start() block".into());
+            };
+
+            // Windows are stored by index, so IDs must be defined in order
+            let expected = device.time_windows.len();
+            if window_id as usize != expected {
+                return Err(format!(
+                    "time_window() IDs must be defined in order: expected TW_{:02X}, got {}",
+                    expected, id
+                )
+                .into());
+            }
+            device.time_windows.push(window);
+            Ok(())
+        },
+    );
+}
+
+/// Parse an "HH:MM" 24-hour time into minutes since midnight.
+fn parse_time_of_day(s: &str) -> Result<u16, Box<EvalAltResult>> {
+    let invalid = || -> String { format!("Invalid time '{}': expected HH:MM (00:00-23:59)", s) };
+    let (hours, minutes) = s.split_once(':').ok_or_else(invalid)?;
+    if minutes.len() != 2 {
+        return Err(invalid().into());
+    }
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid().into());
+    }
+    Ok(hours * 60 + minutes)
+}
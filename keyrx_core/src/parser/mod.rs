@@ -27,38 +27,94 @@ use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
-use rhai::{Engine, Scope};
+use rhai::{Engine, EvalAltResult, Scope};
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use spin::Mutex;
 
 use crate::config::{ConfigRoot, Metadata, Version};
+use error::{LimitKind, ParseError};
 use state::ParserState;
 
+/// Resource limits applied to the Rhai engine while parsing.
+///
+/// Large generated configurations can raise these with
+/// [`Parser::with_limits`]. A value of 0 disables the limit. Fields missing
+/// from a deserialized value keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ParserLimits {
+    /// Total operations the script may run
+    pub max_operations: u64,
+    /// Maximum nesting depth of a single expression
+    pub max_expr_depth: usize,
+    /// Maximum nesting depth of function calls
+    pub max_call_levels: usize,
+}
+
+impl ParserLimits {
+    /// Current value of the limit `kind`.
+    pub fn get(&self, kind: LimitKind) -> u64 {
+        match kind {
+            LimitKind::Operations => self.max_operations,
+            LimitKind::ExpressionDepth => self.max_expr_depth as u64,
+            LimitKind::CallDepth => self.max_call_levels as u64,
+        }
+    }
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            max_expr_depth: 100,
+            max_call_levels: 100,
+        }
+    }
+}
+
 /// Main parser for Rhai DSL.
 pub struct Parser {
     engine: Engine,
     state: Arc<Mutex<ParserState>>,
+    limits: ParserLimits,
 }
 
 impl Parser {
     /// Create a new parser with all functions registered.
     pub fn new() -> Self {
+        Self::with_limits(ParserLimits::default())
+    }
+
+    /// Create a parser that enforces `limits` instead of the defaults.
+    pub fn with_limits(limits: ParserLimits) -> Self {
         let mut engine = Engine::new();
         let state = Arc::new(Mutex::new(ParserState::new()));
 
         // Set resource limits
-        engine.set_max_operations(100_000);
-        engine.set_max_expr_depths(100, 100);
-        engine.set_max_call_levels(100);
+        engine.set_max_operations(limits.max_operations);
+        engine.set_max_expr_depths(limits.max_expr_depth, limits.max_expr_depth);
+        engine.set_max_call_levels(limits.max_call_levels);
 
         // Register all DSL functions
         functions::device::register_device_functions(&mut engine, Arc::clone(&state));
         functions::map::register_map_functions(&mut engine, Arc::clone(&state));
+        functions::map_range::register_map_range_function(&mut engine, Arc::clone(&state));
         functions::tap_hold::register_tap_hold_function(&mut engine, Arc::clone(&state));
+        functions::on_release::register_on_release_function(&mut engine, Arc::clone(&state));
+        functions::layer_toggle::register_layer_toggle_function(&mut engine, Arc::clone(&state));
+        functions::cycle::register_cycle_function(&mut engine, Arc::clone(&state));
+        functions::disable::register_disable_function(&mut engine, Arc::clone(&state));
+        functions::macros::register_macro_functions(&mut engine, Arc::clone(&state));
+        functions::time_window::register_time_window_function(&mut engine, Arc::clone(&state));
         functions::conditional::register_when_functions(&mut engine, Arc::clone(&state));
         functions::modifiers::register_modifier_functions(&mut engine);
 
-        Self { engine, state }
+        Self {
+            engine,
+            state,
+            limits,
+        }
     }
 
     /// Parse a Rhai script string into a ConfigRoot.
@@ -73,7 +129,14 @@ impl Parser {
         let mut scope = Scope::new();
         self.engine
             .run_with_scope(&mut scope, script)
-            .map_err(|e| format!("Parse error: {}", e))?;
+            .map_err(|e| match limit_kind(&e) {
+                Some(kind) => ParseError::LimitExceeded {
+                    kind,
+                    limit: self.limits.get(kind),
+                }
+                .to_string(),
+                None => format!("Parse error: {}", e),
+            })?;
 
         // Finalize the configuration
         self.finalize_config(script)
@@ -93,6 +156,16 @@ impl Parser {
             return Err("Unclosed when_start() block - missing when_end()".to_string());
         }
 
+        // Check for mappings whose outputs would be truncated by the runtime
+        for device in &state.devices {
+            if let Some(overflow) = crate::runtime::find_output_overflow(device) {
+                return Err(format!(
+                    "Mapping {:?} on device '{}' emits up to {} events per input, but the runtime output buffer holds {}",
+                    overflow.mapping, device.identifier.pattern, overflow.events, overflow.capacity
+                ));
+            }
+        }
+
         // Calculate SHA256 hash of source script
         let mut hasher = Sha256::new();
         hasher.update(source.as_bytes());
@@ -114,9 +187,15 @@ impl Parser {
             source_hash,
         };
 
+        // Cheap condition checks first, so AND/OR lists short-circuit early
+        let mut devices = state.devices.clone();
+        for device in &mut devices {
+            device.order_conditions_for_evaluation();
+        }
+
         Ok(ConfigRoot {
             version: Version::current(),
-            devices: state.devices.clone(),
+            devices,
             metadata,
         })
     }
@@ -136,3 +215,17 @@ impl Default for Parser {
         Self::new()
     }
 }
+
+/// Returns which parser limit `err` reports, looking through the function
+/// call that raised it.
+fn limit_kind(err: &EvalAltResult) -> Option<LimitKind> {
+    match err {
+        EvalAltResult::ErrorTooManyOperations(_) => Some(LimitKind::Operations),
+        EvalAltResult::ErrorStackOverflow(_) => Some(LimitKind::CallDepth),
+        EvalAltResult::ErrorParsing(rhai::ParseErrorType::ExprTooDeep, _) => {
+            Some(LimitKind::ExpressionDepth)
+        }
+        EvalAltResult::ErrorInFunctionCall(_, _, inner, _) => limit_kind(inner),
+        _ => None,
+    }
+}
@@ -13,6 +13,8 @@ pub struct ParserState {
     /// Stack of (Condition, mappings) pairs being collected for conditional blocks
     /// When non-empty, map() adds to the top of this stack instead of current_device
     pub conditional_stack: Vec<(Condition, Vec<BaseKeyMapping>)>,
+    /// Threshold set by tap_hold_threshold() outside any device block
+    pub tap_hold_threshold_ms: Option<u16>,
 }
 
 impl ParserState {
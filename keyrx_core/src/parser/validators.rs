@@ -7,7 +7,7 @@ use alloc::vec;
 use alloc::vec::Vec;
 
 use super::error::ParseError;
-use crate::config::{Condition, KeyCode};
+use crate::config::{Condition, ConditionItem, KeyCode};
 
 /// Physical modifier key names that cannot be used as custom MD_ identifiers.
 pub const PHYSICAL_MODIFIERS: &[&str] = &[
@@ -79,17 +79,56 @@ pub fn parse_lock_id(s: &str) -> Result<u8, ParseError> {
     Ok(id as u8)
 }
 
-/// Parse a condition string (MD_XX or LK_XX) into a Condition.
+/// Parse a time window ID (TW_XX format, hex 00-FE).
+pub fn parse_time_window_id(s: &str) -> Result<u8, ParseError> {
+    if !s.starts_with("TW_") {
+        return Err(ParseError::MissingPrefix {
+            key: s.to_string(),
+            context: "time window".to_string(),
+        });
+    }
+    match u8::from_str_radix(&s[3..], 16) {
+        Ok(id) if id <= 0xFE => Ok(id),
+        _ => Err(ParseError::InvalidPrefix {
+            expected: "TW_XX (hex, 00-FE)".to_string(),
+            got: s.to_string(),
+            context: "time window ID".to_string(),
+        }),
+    }
+}
+
+/// Parse a condition string into a Condition.
+///
+/// A single `MD_XX`, `LK_XX` or `TW_XX` gives the matching single-item
+/// condition. Several items joined with `|` (e.g. `"MD_00 | LK_01"`) give
+/// `Condition::AnyActive`, which is true when at least one item is active.
 pub fn parse_condition_string(s: &str) -> Result<Condition, ParseError> {
+    if s.contains('|') {
+        let items = s
+            .split('|')
+            .map(|part| parse_condition_item(part.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Condition::AnyActive(items));
+    }
+
+    Ok(match parse_condition_item(s)? {
+        ConditionItem::ModifierActive(id) => Condition::ModifierActive(id),
+        ConditionItem::LockActive(id) => Condition::LockActive(id),
+        ConditionItem::TimeWindowActive(id) => Condition::TimeWindowActive(id),
+    })
+}
+
+/// Parse a single MD_XX, LK_XX or TW_XX string into a ConditionItem.
+pub fn parse_condition_item(s: &str) -> Result<ConditionItem, ParseError> {
     if s.starts_with("MD_") {
-        let id = parse_modifier_id(s)?;
-        Ok(Condition::ModifierActive(id))
+        Ok(ConditionItem::ModifierActive(parse_modifier_id(s)?))
     } else if s.starts_with("LK_") {
-        let id = parse_lock_id(s)?;
-        Ok(Condition::LockActive(id))
+        Ok(ConditionItem::LockActive(parse_lock_id(s)?))
+    } else if s.starts_with("TW_") {
+        Ok(ConditionItem::TimeWindowActive(parse_time_window_id(s)?))
     } else {
         Err(ParseError::InvalidPrefix {
-            expected: "MD_XX or LK_XX".to_string(),
+            expected: "MD_XX, LK_XX or TW_XX".to_string(),
             got: s.to_string(),
             context: "condition".to_string(),
         })
@@ -236,11 +275,28 @@ fn get_all_key_names() -> Vec<&'static str> {
         "NumpadAdd",
         "NumpadEnter",
         "NumpadDecimal",
+        "KP_0",
+        "KP_1",
+        "KP_2",
+        "KP_3",
+        "KP_4",
+        "KP_5",
+        "KP_6",
+        "KP_7",
+        "KP_8",
+        "KP_9",
+        "KP_Divide",
+        "KP_Multiply",
+        "KP_Subtract",
+        "KP_Add",
+        "KP_Enter",
+        "KP_Decimal",
         // Media keys
         "Mute",
         "VolumeDown",
         "VolumeUp",
         "MediaPlayPause",
+        "PlayPause",
         "MediaStop",
         "MediaPrevious",
         "MediaNext",
@@ -248,6 +304,8 @@ fn get_all_key_names() -> Vec<&'static str> {
         "Power",
         "Sleep",
         "Wake",
+        "BrightnessDown",
+        "BrightnessUp",
         // Browser keys
         "BrowserBack",
         "BrowserForward",
@@ -288,6 +346,7 @@ fn get_all_key_names() -> Vec<&'static str> {
         "Ro",
         "ろ",
         "KatakanaHiragana",
+        "NumpadJpComma",
         // Korean keyboard keys
         "Hangeul",
         "Hangul",
@@ -296,6 +355,8 @@ fn get_all_key_names() -> Vec<&'static str> {
         "한자",
         // ISO keyboard keys
         "Iso102nd",
+        "IntlBackslash",
+        "NumpadComma",
     ]
 }
 
@@ -357,6 +418,14 @@ fn find_suggestions(name: &str) -> Vec<String> {
         .collect()
 }
 
+/// Returns the key whose code is `code`, if there is one.
+pub fn key_from_code(code: u16) -> Option<KeyCode> {
+    get_all_key_names()
+        .into_iter()
+        .filter_map(|name| parse_key_name(name).ok())
+        .find(|key| *key as u16 == code)
+}
+
 /// Parse a key name (without prefix) into a KeyCode.
 pub fn parse_key_name(name: &str) -> Result<KeyCode, ParseError> {
     let keycode = match name {
@@ -466,28 +535,29 @@ pub fn parse_key_name(name: &str) -> Result<KeyCode, ParseError> {
         "Grave" => KeyCode::Grave,
         "Minus" => KeyCode::Minus,
         "Equal" => KeyCode::Equal,
-        // Numpad keys
-        "Numpad0" => KeyCode::Numpad0,
-        "Numpad1" => KeyCode::Numpad1,
-        "Numpad2" => KeyCode::Numpad2,
-        "Numpad3" => KeyCode::Numpad3,
-        "Numpad4" => KeyCode::Numpad4,
-        "Numpad5" => KeyCode::Numpad5,
-        "Numpad6" => KeyCode::Numpad6,
-        "Numpad7" => KeyCode::Numpad7,
-        "Numpad8" => KeyCode::Numpad8,
-        "Numpad9" => KeyCode::Numpad9,
-        "NumpadDivide" => KeyCode::NumpadDivide,
-        "NumpadMultiply" => KeyCode::NumpadMultiply,
-        "NumpadSubtract" => KeyCode::NumpadSubtract,
-        "NumpadAdd" => KeyCode::NumpadAdd,
-        "NumpadEnter" => KeyCode::NumpadEnter,
-        "NumpadDecimal" => KeyCode::NumpadDecimal,
+        // Numpad keys (KP_* are the evdev-style names). These are distinct
+        // from the main-row digits above.
+        "Numpad0" | "KP_0" => KeyCode::Numpad0,
+        "Numpad1" | "KP_1" => KeyCode::Numpad1,
+        "Numpad2" | "KP_2" => KeyCode::Numpad2,
+        "Numpad3" | "KP_3" => KeyCode::Numpad3,
+        "Numpad4" | "KP_4" => KeyCode::Numpad4,
+        "Numpad5" | "KP_5" => KeyCode::Numpad5,
+        "Numpad6" | "KP_6" => KeyCode::Numpad6,
+        "Numpad7" | "KP_7" => KeyCode::Numpad7,
+        "Numpad8" | "KP_8" => KeyCode::Numpad8,
+        "Numpad9" | "KP_9" => KeyCode::Numpad9,
+        "NumpadDivide" | "KP_Divide" | "KP_Slash" => KeyCode::NumpadDivide,
+        "NumpadMultiply" | "KP_Multiply" | "KP_Asterisk" => KeyCode::NumpadMultiply,
+        "NumpadSubtract" | "KP_Subtract" | "KP_Minus" => KeyCode::NumpadSubtract,
+        "NumpadAdd" | "KP_Add" | "KP_Plus" => KeyCode::NumpadAdd,
+        "NumpadEnter" | "KP_Enter" => KeyCode::NumpadEnter,
+        "NumpadDecimal" | "KP_Decimal" | "KP_Dot" => KeyCode::NumpadDecimal,
         // Media keys
         "Mute" => KeyCode::Mute,
         "VolumeDown" => KeyCode::VolumeDown,
         "VolumeUp" => KeyCode::VolumeUp,
-        "MediaPlayPause" => KeyCode::MediaPlayPause,
+        "MediaPlayPause" | "PlayPause" => KeyCode::MediaPlayPause,
         "MediaStop" => KeyCode::MediaStop,
         "MediaPrevious" => KeyCode::MediaPrevious,
         "MediaNext" => KeyCode::MediaNext,
@@ -495,6 +565,8 @@ pub fn parse_key_name(name: &str) -> Result<KeyCode, ParseError> {
         "Power" => KeyCode::Power,
         "Sleep" => KeyCode::Sleep,
         "Wake" => KeyCode::Wake,
+        "BrightnessDown" => KeyCode::BrightnessDown,
+        "BrightnessUp" => KeyCode::BrightnessUp,
         // Browser keys
         "BrowserBack" => KeyCode::BrowserBack,
         "BrowserForward" => KeyCode::BrowserForward,
@@ -524,14 +596,16 @@ pub fn parse_key_name(name: &str) -> Result<KeyCode, ParseError> {
         "Hiragana" | "ひらがな" => KeyCode::Hiragana,
         "Henkan" | "変換" | "Convert" => KeyCode::Henkan,
         "Muhenkan" | "無変換" | "NonConvert" => KeyCode::Muhenkan,
-        "Yen" | "円" | "¥" => KeyCode::Yen,
-        "Ro" | "ろ" => KeyCode::Ro,
+        "Yen" | "円" | "¥" | "IntlYen" => KeyCode::Yen,
+        "Ro" | "ろ" | "IntlRo" => KeyCode::Ro,
         "KatakanaHiragana" | "カタカナひらがな" => KeyCode::KatakanaHiragana,
+        "NumpadJpComma" | "KpJpComma" => KeyCode::NumpadJpComma,
         // Korean keyboard keys
         "Hangeul" | "Hangul" | "한글" => KeyCode::Hangeul,
         "Hanja" | "한자" => KeyCode::Hanja,
         // ISO/European keyboard keys
-        "Iso102nd" | "102nd" => KeyCode::Iso102nd,
+        "Iso102nd" | "102nd" | "IntlBackslash" => KeyCode::Iso102nd,
+        "NumpadComma" | "KpComma" => KeyCode::NumpadComma,
         _ => {
             // Generate suggestions for unknown key name
             let suggestions = find_suggestions(name);
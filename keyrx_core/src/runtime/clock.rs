@@ -42,6 +42,9 @@
 
 use core::sync::atomic::{AtomicU64, Ordering};
 
+const MICROS_PER_MINUTE: u64 = 60_000_000;
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
 /// Trait for abstracting time sources.
 ///
 /// All times are in microseconds (μs) for consistency with evdev timestamps
@@ -180,6 +183,14 @@ impl VirtualClock {
     pub fn reset(&self) {
         self.set(0);
     }
+
+    /// Returns the virtual time as a minute of the day (0-1439).
+    ///
+    /// Time 0 is treated as local midnight, so simulations can exercise
+    /// time-window conditions without reading the wall clock.
+    pub fn minute_of_day(&self) -> u16 {
+        ((self.now() / MICROS_PER_MINUTE) % MINUTES_PER_DAY) as u16
+    }
 }
 
 impl Clock for VirtualClock {
@@ -246,6 +257,19 @@ mod tests {
         assert_eq!(clock.now(), 350);
     }
 
+    #[test]
+    fn test_virtual_clock_minute_of_day() {
+        let clock = VirtualClock::new();
+        assert_eq!(clock.minute_of_day(), 0);
+
+        clock.set(20 * 60 * MICROS_PER_MINUTE + 59_999_999);
+        assert_eq!(clock.minute_of_day(), 20 * 60);
+
+        // Wraps to the next day
+        clock.advance(4 * 60 * MICROS_PER_MINUTE);
+        assert_eq!(clock.minute_of_day(), 0);
+    }
+
     #[test]
     fn test_virtual_clock_reset() {
         let clock = VirtualClock::new();
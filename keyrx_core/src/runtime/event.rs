@@ -2,7 +2,7 @@
 //!
 //! This module provides:
 //! - `KeyEvent`: Type-safe keyboard event representation with timestamps and device ID
-//! - `KeyEventType`: Enum for press/release event types
+//! - `KeyEventType`: Enum for press/release/repeat event types
 //! - `process_event`: Core event processing function
 
 extern crate alloc;
@@ -11,16 +11,18 @@ use alloc::vec::Vec;
 
 use crate::config::KeyCode;
 use crate::runtime::tap_hold::{TapHoldConfig, TapHoldOutput};
-use crate::runtime::{DeviceState, KeyLookup};
+use crate::runtime::{DeviceState, KeyLookup, MatchedMapping};
 use serde::{Deserialize, Serialize};
 
-/// Type of keyboard event (press or release)
+/// Type of keyboard event (press, release, or auto-repeat)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum KeyEventType {
     /// Key press event
     Press,
     /// Key release event
     Release,
+    /// Auto-repeat of a key that is still held down
+    Repeat,
 }
 
 /// Keyboard event representing a key press or release with timestamp and optional device ID
@@ -64,6 +66,7 @@ pub struct KeyEvent {
     timestamp_us: u64,
     /// Optional device identifier for multi-device support
     /// When None, event is treated as coming from default device
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     device_id: Option<String>,
 }
 
@@ -96,6 +99,20 @@ impl KeyEvent {
         }
     }
 
+    /// Creates a new key auto-repeat event
+    ///
+    /// The timestamp defaults to 0 (no timestamp) and device_id defaults to None.
+    /// Use `with_timestamp()` and `with_device_id()` to set specific values.
+    #[must_use]
+    pub fn repeat(keycode: KeyCode) -> Self {
+        Self {
+            event_type: KeyEventType::Repeat,
+            keycode,
+            timestamp_us: 0,
+            device_id: None,
+        }
+    }
+
     /// Legacy constructor for press events (enum-style syntax)
     #[must_use]
     #[allow(non_snake_case)]
@@ -141,7 +158,7 @@ impl KeyEvent {
         self.keycode
     }
 
-    /// Returns the event type (Press or Release)
+    /// Returns the event type (Press, Release, or Repeat)
     #[must_use]
     pub const fn event_type(&self) -> KeyEventType {
         self.event_type
@@ -174,12 +191,20 @@ impl KeyEvent {
         matches!(self.event_type, KeyEventType::Release)
     }
 
+    /// Returns true if this is an auto-repeat event
+    #[must_use]
+    pub const fn is_repeat(&self) -> bool {
+        matches!(self.event_type, KeyEventType::Repeat)
+    }
+
     /// Creates a new event with the same keycode, timestamp, and device_id but opposite type
+    ///
+    /// A repeat means the key is held down, so its opposite is a release.
     #[must_use]
     pub fn opposite(&self) -> Self {
         Self {
             event_type: match self.event_type {
-                KeyEventType::Press => KeyEventType::Release,
+                KeyEventType::Press | KeyEventType::Repeat => KeyEventType::Release,
                 KeyEventType::Release => KeyEventType::Press,
             },
             keycode: self.keycode,
@@ -194,6 +219,78 @@ impl KeyEvent {
         self.keycode = keycode;
         self
     }
+
+    /// Returns true if both events have the same type, keycode and device ID
+    ///
+    /// Unlike `==`, the timestamp is ignored, so assertions on output do not
+    /// depend on when the events were produced.
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        self.event_type == other.event_type
+            && self.keycode == other.keycode
+            && self.device_id == other.device_id
+    }
+}
+
+/// Returns the capacity of the fixed-size buffer a mapping's outputs pass
+/// through, or `None` if they are not bounded.
+///
+/// Simple and modified outputs are tracked per pressed key (so the release
+/// can undo them) in a buffer of `MAX_OUTPUT_KEYS_PER_INPUT`; tap-hold
+/// outputs come from the tap-hold processor's `MAX_OUTPUT_EVENTS` buffer.
+/// Outputs beyond the capacity are silently dropped, so configurations must
+/// stay within [`BaseKeyMapping::max_output_events`] of it.
+///
+/// [`BaseKeyMapping::max_output_events`]: crate::config::BaseKeyMapping::max_output_events
+pub fn output_capacity(mapping: &crate::config::BaseKeyMapping) -> Option<usize> {
+    use crate::config::BaseKeyMapping;
+
+    match mapping {
+        BaseKeyMapping::Simple { .. }
+        | BaseKeyMapping::ModifiedOutput { .. }
+        | BaseKeyMapping::Cycle { .. } => Some(crate::runtime::state::MAX_OUTPUT_KEYS_PER_INPUT),
+        BaseKeyMapping::TapHold { .. } => Some(crate::runtime::tap_hold::MAX_OUTPUT_EVENTS),
+        BaseKeyMapping::OnRelease { .. }
+        | BaseKeyMapping::Modifier { .. }
+        | BaseKeyMapping::Lock { .. }
+        | BaseKeyMapping::LayerToggle { .. }
+        | BaseKeyMapping::Disable { .. }
+        | BaseKeyMapping::Macro { .. } => None,
+    }
+}
+
+/// A mapping that emits more events than its runtime buffer holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOverflow<'a> {
+    /// The offending mapping
+    pub mapping: &'a crate::config::BaseKeyMapping,
+    /// Most events the mapping emits for one input event
+    pub events: usize,
+    /// Capacity of the buffer the events pass through
+    pub capacity: usize,
+}
+
+/// Returns the first mapping of `device` (including conditional mappings)
+/// whose outputs would not fit in the runtime buffer they pass through.
+pub fn find_output_overflow(device: &crate::config::DeviceConfig) -> Option<OutputOverflow<'_>> {
+    use crate::config::KeyMapping;
+
+    device
+        .mappings
+        .iter()
+        .flat_map(|mapping| match mapping {
+            KeyMapping::Base(base) => core::slice::from_ref(base),
+            KeyMapping::Conditional { mappings, .. } => mappings.as_slice(),
+        })
+        .find_map(|mapping| {
+            let capacity = output_capacity(mapping)?;
+            let events = mapping.max_output_events();
+            (events > capacity).then_some(OutputOverflow {
+                mapping,
+                events,
+                capacity,
+            })
+        })
 }
 
 /// Process a keyboard event through the remapping engine
@@ -204,6 +301,14 @@ impl KeyEvent {
 /// - Single event (for simple remapping or passthrough)
 /// - Multiple events (for modified output sequences)
 ///
+/// Repeat events never change state: a repeat of a remapped key repeats its
+/// output key, and repeats of modifier, lock, tap-hold, and emit-on-release
+/// keys are dropped.
+///
+/// A macro returns only its steps up to the first wait. The remaining steps
+/// are left on `state`, stamped with the time they are due; take them with
+/// [`DeviceState::take_scheduled_outputs`].
+///
 /// # Arguments
 ///
 /// * `event` - Input keyboard event
@@ -224,9 +329,45 @@ pub fn process_event(
     event: KeyEvent,
     lookup: &KeyLookup,
     state: &mut DeviceState,
+) -> Vec<KeyEvent> {
+    process_event_into(event, lookup, state, None)
+}
+
+/// Process a keyboard event like `process_event`, also returning the position
+/// and kind of the mapping that handled it
+///
+/// The mapping is `None` when the key passed through unmapped, and for
+/// releases and repeats that follow the tracked press instead of a fresh
+/// lookup. Used by the daemon to show which mapping fired. Requires the
+/// `trace-mapping` feature; `process_event` never builds the mapping.
+#[cfg(feature = "trace-mapping")]
+pub fn process_event_traced(
+    event: KeyEvent,
+    lookup: &KeyLookup,
+    state: &mut DeviceState,
+) -> (Vec<KeyEvent>, Option<MatchedMapping>) {
+    let mut matched = None;
+    let outputs = process_event_into(event, lookup, state, Some(&mut matched));
+    (outputs, matched)
+}
+
+/// Shared body of `process_event` and `process_event_traced`
+///
+/// When `matched` is given, the mapping that handled the event is written
+/// to it. The simulator calls this directly, since it reports mapping
+/// coverage with or without the `trace-mapping` feature.
+pub(crate) fn process_event_into(
+    event: KeyEvent,
+    lookup: &KeyLookup,
+    state: &mut DeviceState,
+    matched: Option<&mut Option<MatchedMapping>>,
 ) -> Vec<KeyEvent> {
     use crate::config::BaseKeyMapping;
 
+    if event.is_repeat() {
+        return process_repeat(event, lookup, state);
+    }
+
     // Cache event properties before event is potentially moved
     let is_press = event.is_press();
     let input_keycode = event.keycode();
@@ -234,6 +375,19 @@ pub fn process_event(
     // For RELEASE events: Check if we have a tracked press mapping
     // This ensures releases match their presses even if mapping changed
     if !is_press {
+        // Emit-on-release keys resolved their output at press time: tap the
+        // output if released within the threshold, otherwise emit nothing
+        if let Some(pending) = state.take_pending_release(input_keycode) {
+            let outputs = match pending.output_for(event.timestamp_us()) {
+                Some(output) => alloc::vec![
+                    event.opposite().with_keycode(output),
+                    event.with_keycode(output),
+                ],
+                None => Vec::new(),
+            };
+            return outputs;
+        }
+
         let tracked_outputs = state.get_release_key(input_keycode);
 
         // Check if we have a real tracking (not just [input_keycode])
@@ -252,8 +406,9 @@ pub fn process_event(
         }
     }
 
-    // Look up the mapping for this key
-    let mapping = lookup.find_mapping(event.keycode(), state);
+    // Look up the mapping for this key; the device ID feeds DeviceMatches
+    // conditions
+    let mapping = lookup.find_positioned_mapping(event.keycode(), state, event.device_id());
 
     // Check for permissive hold: if this is a press event and there are pending
     // tap-hold keys, we need to trigger permissive hold BEFORE processing this key.
@@ -262,7 +417,7 @@ pub fn process_event(
     let mut permissive_hold_triggered = false;
     if event.is_press() {
         // Check if any tap-hold keys are pending and this isn't a tap-hold key itself
-        let is_tap_hold_key = matches!(mapping, Some(BaseKeyMapping::TapHold { .. }));
+        let is_tap_hold_key = matches!(mapping, Some((BaseKeyMapping::TapHold { .. }, _)));
         if !is_tap_hold_key && state.tap_hold_processor_ref().has_pending_keys() {
             // Trigger permissive hold for all pending keys
             let outputs = state
@@ -281,13 +436,13 @@ pub fn process_event(
     // This fixes the bug where fast typing (permissive hold) would use the base layer
     // mapping instead of the conditional layer mapping.
     let mapping = if permissive_hold_triggered {
-        lookup.find_mapping(event.keycode(), state)
+        lookup.find_positioned_mapping(event.keycode(), state, event.device_id())
     } else {
         mapping
     };
 
     // If no mapping found, pass through the original event
-    let Some(mapping) = mapping else {
+    let Some((mapping, position)) = mapping else {
         prefix_events.push(event);
         return prefix_events;
     };
@@ -314,16 +469,37 @@ pub fn process_event(
             }
             Vec::new()
         }
+        BaseKeyMapping::LayerToggle { layer_id, .. } => {
+            // Layer toggle: lock/unlock the layer on press, ignore release
+            if event.is_press() {
+                state.toggle_layer_lock(*layer_id);
+            }
+            Vec::new()
+        }
+        BaseKeyMapping::Disable { .. } => {
+            // Disabled key: swallow both press and release
+            Vec::new()
+        }
+        BaseKeyMapping::Macro { steps, .. } => {
+            // Macro: play the sequence on press, swallow the release
+            if event.is_press() {
+                play_macro(&event, steps, state)
+            } else {
+                Vec::new()
+            }
+        }
         BaseKeyMapping::TapHold {
             from,
             tap,
             hold_modifier,
             threshold_ms,
+            policy,
         } => {
             // Register the tap-hold configuration if not already registered
             let processor = state.tap_hold_processor();
             if !processor.is_tap_hold_key(*from) {
-                let config = TapHoldConfig::from_ms(*tap, *hold_modifier, *threshold_ms);
+                let config = TapHoldConfig::from_ms(*tap, *hold_modifier, *threshold_ms)
+                    .with_policy(*policy);
                 processor.register_tap_hold(*from, config);
             }
 
@@ -338,6 +514,39 @@ pub fn process_event(
             // Convert TapHoldOutput to KeyEvent and apply state changes
             convert_tap_hold_outputs(outputs, state, timestamp)
         }
+        BaseKeyMapping::OnRelease {
+            from,
+            to,
+            threshold_ms,
+        } => {
+            // OnRelease: swallow the press; the release is handled above via
+            // the pending entry. A release without one (e.g. the key was
+            // pressed before a reload) emits nothing.
+            if event.is_press() {
+                state.record_pending_release(*from, *to, event.timestamp_us(), *threshold_ms);
+            }
+            Vec::new()
+        }
+        BaseKeyMapping::Cycle {
+            from,
+            outputs,
+            reset_ms,
+        } => {
+            // Cycle: press the next output on every tap. The press is tracked
+            // below, so the release lifts the same output even if the cycle
+            // has moved on. An untracked release (the output was `from`
+            // itself, or the press predates a reload) passes through.
+            if event.is_press() {
+                let index =
+                    state.next_cycle_index(*from, outputs.len(), event.timestamp_us(), *reset_ms);
+                outputs
+                    .get(index)
+                    .map(|&output| alloc::vec![event.with_keycode(output)])
+                    .unwrap_or_default()
+            } else {
+                alloc::vec![event]
+            }
+        }
         BaseKeyMapping::ModifiedOutput {
             to,
             shift,
@@ -389,8 +598,10 @@ pub fn process_event(
     };
 
     // For PRESS events: Record the mapping for press/release consistency
-    // This must happen AFTER processing, so we know the actual output
-    if is_press && !result.is_empty() {
+    // This must happen AFTER processing, so we know the actual output. A
+    // macro releases its own keys, so there is nothing to release later.
+    let releases_own_outputs = matches!(mapping, BaseKeyMapping::Macro { .. });
+    if is_press && !result.is_empty() && !releases_own_outputs {
         // Collect ALL press event keycodes from the result
         let output_keys: alloc::vec::Vec<KeyCode> = result
             .iter()
@@ -405,6 +616,13 @@ pub fn process_event(
         }
     }
 
+    if let Some(matched) = matched {
+        *matched = Some(MatchedMapping {
+            position,
+            kind: mapping.kind(),
+        });
+    }
+
     // Prepend prefix events (from permissive hold) to the result
     if !prefix_events.is_empty() {
         prefix_events.append(&mut result);
@@ -414,6 +632,84 @@ pub fn process_event(
     }
 }
 
+/// Returns the steps of a macro up to its first wait, and schedules the rest
+/// on `state`
+///
+/// Every output is stamped with the time it is due: the press time plus the
+/// waits before it.
+fn play_macro(
+    event: &KeyEvent,
+    steps: &[crate::config::MacroStep],
+    state: &mut DeviceState,
+) -> Vec<KeyEvent> {
+    use crate::config::MacroStep;
+
+    let pressed_at = event.timestamp_us();
+    let mut due_us = pressed_at;
+    let mut immediate = Vec::new();
+    let mut scheduled = Vec::new();
+    for step in steps {
+        let output = match *step {
+            MacroStep::Press(key) => KeyEvent::press(key),
+            MacroStep::Release(key) => KeyEvent::release(key),
+            MacroStep::Wait(ms) => {
+                due_us = due_us.saturating_add(u64::from(ms) * 1000);
+                continue;
+            }
+        }
+        .with_timestamp(due_us);
+
+        if due_us == pressed_at {
+            immediate.push(output);
+        } else {
+            scheduled.push(output);
+        }
+    }
+
+    state.schedule_outputs(scheduled);
+    immediate
+}
+
+/// Translates an auto-repeat event without touching modifier, lock, or
+/// tap-hold state.
+fn process_repeat(event: KeyEvent, lookup: &KeyLookup, state: &DeviceState) -> Vec<KeyEvent> {
+    use crate::config::BaseKeyMapping;
+
+    let input_keycode = event.keycode();
+
+    // A remapped press is tracked: repeat the key it actually pressed, even if
+    // the active mapping has changed since. For modified output ([LShift, Z])
+    // that is the last key; the modifiers stay held.
+    let tracked_outputs = state.get_release_key(input_keycode);
+    if !(tracked_outputs.len() == 1 && tracked_outputs[0] == input_keycode) {
+        return tracked_outputs
+            .last()
+            .map(|&output| alloc::vec![event.with_keycode(output)])
+            .unwrap_or_default();
+    }
+
+    match lookup.find_mapping_with_device(input_keycode, state, event.device_id()) {
+        None => alloc::vec![event],
+        Some(BaseKeyMapping::Simple { to, .. }) => alloc::vec![event.with_keycode(*to)],
+        // Modifier/lock/layer toggle keys would re-trigger, tap-hold keys have
+        // no output while held, emit-on-release keys output nothing until
+        // released, disabled keys output nothing at all, a macro plays once
+        // per press, and an untracked modified output or cycle was never
+        // pressed (e.g. across a reload)
+        Some(
+            BaseKeyMapping::Modifier { .. }
+            | BaseKeyMapping::Lock { .. }
+            | BaseKeyMapping::LayerToggle { .. }
+            | BaseKeyMapping::TapHold { .. }
+            | BaseKeyMapping::OnRelease { .. }
+            | BaseKeyMapping::ModifiedOutput { .. }
+            | BaseKeyMapping::Cycle { .. }
+            | BaseKeyMapping::Disable { .. }
+            | BaseKeyMapping::Macro { .. },
+        ) => Vec::new(),
+    }
+}
+
 /// Checks for tap-hold timeouts and returns resulting events.
 ///
 /// This function should be called periodically (e.g., every 10-100ms) by the
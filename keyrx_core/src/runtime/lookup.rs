@@ -5,20 +5,176 @@
 
 extern crate alloc;
 use alloc::vec::Vec;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicU64, Ordering};
 use hashbrown::HashMap;
 
-use crate::config::{BaseKeyMapping, Condition, DeviceConfig, KeyCode, KeyMapping};
+use serde::{Deserialize, Serialize};
+
+use crate::config::conditions::ArchivedCondition;
+use crate::config::mappings::{
+    ArchivedBaseKeyMapping, ArchivedDeviceConfig, ArchivedKeyMapping, ArchivedMacroStep,
+};
+use crate::config::{BaseKeyMapping, Condition, DeviceConfig, KeyCode, KeyMapping, MappingKind};
 use crate::runtime::state::DeviceState;
 
+/// Deserializes one value out of an archived configuration
+pub(crate) fn from_archive<T: rkyv::Archive>(archived: &T::Archived) -> T
+where
+    T::Archived: rkyv::Deserialize<T, rkyv::Infallible>,
+{
+    match rkyv::Deserialize::deserialize(archived, &mut rkyv::Infallible) {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
+
+/// Where a mapping sits in `DeviceConfig::mappings`
+///
+/// Identifies the mapping that handled an event, e.g. for coverage reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MappingPosition {
+    /// Index into `DeviceConfig::mappings`
+    pub index: usize,
+    /// Index into the conditional block's mappings, `None` for a base mapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner: Option<usize>,
+}
+
+/// The mapping that handled an event: where it is and what kind it is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MatchedMapping {
+    /// Where the mapping sits in the device configuration
+    pub position: MappingPosition,
+    /// Kind of the mapping
+    pub kind: MappingKind,
+}
+
+/// A mapping held by the table, owned or borrowed from an archive
+enum StoredMapping {
+    Owned(BaseKeyMapping),
+    /// Read in place from the archive. The owned copy that lookups return
+    /// is made the first time the mapping matches, so mappings that never
+    /// fire are never deserialized.
+    Archived {
+        archived: &'static ArchivedBaseKeyMapping,
+        owned: spin::Once<BaseKeyMapping>,
+    },
+}
+
+impl StoredMapping {
+    fn get(&self) -> &BaseKeyMapping {
+        match self {
+            StoredMapping::Owned(mapping) => mapping,
+            StoredMapping::Archived { archived, owned } => {
+                owned.call_once(|| from_archive(*archived))
+            }
+        }
+    }
+}
+
+/// A condition held by the table, owned or borrowed from an archive
+enum StoredCondition {
+    Owned(Condition),
+    /// Evaluated in place, without deserializing
+    Archived(&'static ArchivedCondition),
+}
+
+impl StoredCondition {
+    fn evaluate(&self, state: &DeviceState, device_id: Option<&str>) -> bool {
+        match self {
+            StoredCondition::Owned(condition) => {
+                state.evaluate_condition_with_device(condition, device_id)
+            }
+            StoredCondition::Archived(condition) => {
+                state.evaluate_archived_condition_with_device(condition, device_id)
+            }
+        }
+    }
+}
+
 /// Entry in the lookup table containing a mapping and optional condition
 ///
 /// Conditional mappings have a Some(condition), unconditional have None.
-#[derive(Clone, Debug)]
 struct LookupEntry {
     /// The base key mapping
-    mapping: BaseKeyMapping,
+    mapping: StoredMapping,
     /// Optional condition that must be true for this mapping to apply
-    condition: Option<Condition>,
+    condition: Option<StoredCondition>,
+    /// Where the mapping came from in the device configuration
+    position: MappingPosition,
+    /// Whether the condition reads the event's device ID, which the
+    /// resolved-entry cache cannot capture
+    device_dependent: bool,
+}
+
+/// Bits of a cached resolution holding the entry index
+#[cfg(target_has_atomic = "64")]
+const RESOLVED_INDEX_BITS: u32 = 16;
+#[cfg(target_has_atomic = "64")]
+const RESOLVED_INDEX_MASK: u64 = (1 << RESOLVED_INDEX_BITS) - 1;
+
+/// All mappings for one input key, plus the cached resolution
+struct KeyEntries {
+    /// Conditional mappings first, then unconditional ones
+    entries: Vec<LookupEntry>,
+    /// `condition_stamp << 16 | index` of the first entry that either
+    /// matched under that stamp or depends on the device (`entries.len()`
+    /// if none). 0 means nothing is cached, since stamps start at 1.
+    #[cfg(target_has_atomic = "64")]
+    resolved: AtomicU64,
+}
+
+impl KeyEntries {
+    fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            #[cfg(target_has_atomic = "64")]
+            resolved: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the index to start matching from, re-evaluating conditions
+    /// only if the state's conditions changed since the last call
+    #[cfg(target_has_atomic = "64")]
+    fn resolved_start(&self, state: &DeviceState) -> usize {
+        let stamp = state.condition_stamp() & (u64::MAX >> RESOLVED_INDEX_BITS);
+        let cached = self.resolved.load(Ordering::Relaxed);
+        if cached >> RESOLVED_INDEX_BITS == stamp {
+            return (cached & RESOLVED_INDEX_MASK) as usize;
+        }
+
+        let start = self.first_candidate(state);
+        if start as u64 <= RESOLVED_INDEX_MASK {
+            self.resolved.store(
+                stamp << RESOLVED_INDEX_BITS | start as u64,
+                Ordering::Relaxed,
+            );
+        }
+        start
+    }
+
+    /// Returns the index to start matching from
+    ///
+    /// Without 64-bit atomics the resolution is not cached, since a 32-bit
+    /// stamp could wrap around to a stale entry.
+    #[cfg(not(target_has_atomic = "64"))]
+    fn resolved_start(&self, state: &DeviceState) -> usize {
+        self.first_candidate(state)
+    }
+
+    /// Returns the index of the first entry that matches `state` or depends
+    /// on the device (`entries.len()` if none)
+    fn first_candidate(&self, state: &DeviceState) -> usize {
+        self.entries
+            .iter()
+            .position(|entry| match &entry.condition {
+                Some(_) if entry.device_dependent => true,
+                Some(condition) => condition.evaluate(state, None),
+                None => true,
+            })
+            .unwrap_or(self.entries.len())
+    }
 }
 
 /// Key lookup table for O(1) mapping resolution
@@ -26,6 +182,17 @@ struct LookupEntry {
 /// Groups mappings by input key with conditional mappings ordered before
 /// unconditional mappings to ensure correct precedence.
 ///
+/// # Caching
+///
+/// For each key, the first entry that matches the current modifier, lock,
+/// layer and time state is cached under `DeviceState::condition_stamp`.
+/// Until one of those changes, a lookup is a hash lookup plus an index, no
+/// matter how many conditional mappings the key has. Conditions on the
+/// device ID are still evaluated per lookup. The cache is filled lazily per
+/// key and uses atomics, so a lookup stays `Sync` and may be shared between
+/// states. Targets without 64-bit atomics skip the cache and evaluate
+/// conditions on every lookup.
+///
 /// # Ordering
 ///
 /// Mappings for the same key are stored in order of registration with
@@ -42,9 +209,9 @@ struct LookupEntry {
 /// let lookup = KeyLookup::from_device_config(&config);
 /// ```
 pub struct KeyLookup {
-    /// HashMap mapping KeyCode to Vec of LookupEntry
+    /// HashMap mapping KeyCode to its LookupEntries
     /// Conditional mappings are ordered before unconditional ones
-    table: HashMap<KeyCode, Vec<LookupEntry>>,
+    table: HashMap<KeyCode, KeyEntries>,
 }
 
 impl KeyLookup {
@@ -63,42 +230,146 @@ impl KeyLookup {
     ///
     /// A new `KeyLookup` instance with all mappings indexed by input key
     pub fn from_device_config(config: &DeviceConfig) -> Self {
-        let mut table: HashMap<KeyCode, Vec<LookupEntry>> = HashMap::new();
+        let mut table: HashMap<KeyCode, KeyEntries> = HashMap::new();
 
         // First pass: collect conditional mappings
-        for mapping in &config.mappings {
+        for (index, mapping) in config.mappings.iter().enumerate() {
             if let KeyMapping::Conditional {
                 condition,
                 mappings,
             } = mapping
             {
+                let device_dependent = Self::depends_on_device(condition);
                 // Process each base mapping in the conditional block
-                for base_mapping in mappings {
-                    if let Some(key) = Self::extract_input_key(base_mapping) {
-                        table.entry(key).or_insert_with(Vec::new).push(LookupEntry {
-                            mapping: base_mapping.clone(),
-                            condition: Some(condition.clone()),
-                        });
-                    }
+                for (inner, base_mapping) in mappings.iter().enumerate() {
+                    Self::insert(
+                        &mut table,
+                        Self::extract_input_key(base_mapping),
+                        LookupEntry {
+                            mapping: StoredMapping::Owned(base_mapping.clone()),
+                            condition: Some(StoredCondition::Owned(condition.clone())),
+                            position: MappingPosition {
+                                index,
+                                inner: Some(inner),
+                            },
+                            device_dependent,
+                        },
+                    );
                 }
             }
         }
 
         // Second pass: collect unconditional (base) mappings
-        for mapping in &config.mappings {
+        for (index, mapping) in config.mappings.iter().enumerate() {
             if let KeyMapping::Base(base_mapping) = mapping {
-                if let Some(key) = Self::extract_input_key(base_mapping) {
-                    table.entry(key).or_insert_with(Vec::new).push(LookupEntry {
-                        mapping: base_mapping.clone(),
+                Self::insert(
+                    &mut table,
+                    Self::extract_input_key(base_mapping),
+                    LookupEntry {
+                        mapping: StoredMapping::Owned(base_mapping.clone()),
                         condition: None,
-                    });
+                        position: MappingPosition { index, inner: None },
+                        device_dependent: false,
+                    },
+                );
+            }
+        }
+
+        Self { table }
+    }
+
+    /// Creates a key lookup table over an archived device configuration
+    ///
+    /// The table holds references into the archive instead of copies:
+    /// construction reads only each mapping's input key, conditions are
+    /// evaluated in place, and a mapping is deserialized the first time a
+    /// lookup returns it. The archive must outlive the table, which the
+    /// daemon guarantees by keeping loaded `.krx` buffers for the life of
+    /// the process.
+    pub fn from_archived(config: &'static ArchivedDeviceConfig) -> Self {
+        let mut table: HashMap<KeyCode, KeyEntries> = HashMap::new();
+
+        // Same two passes as `from_device_config`, so precedence matches
+        for (index, mapping) in config.mappings.iter().enumerate() {
+            if let ArchivedKeyMapping::Conditional {
+                condition,
+                mappings,
+            } = mapping
+            {
+                let device_dependent = Self::archived_depends_on_device(condition);
+                for (inner, base_mapping) in mappings.iter().enumerate() {
+                    Self::insert(
+                        &mut table,
+                        Self::archived_input_key(base_mapping),
+                        LookupEntry {
+                            mapping: StoredMapping::Archived {
+                                archived: base_mapping,
+                                owned: spin::Once::new(),
+                            },
+                            condition: Some(StoredCondition::Archived(condition)),
+                            position: MappingPosition {
+                                index,
+                                inner: Some(inner),
+                            },
+                            device_dependent,
+                        },
+                    );
                 }
             }
         }
 
+        for (index, mapping) in config.mappings.iter().enumerate() {
+            if let ArchivedKeyMapping::Base(base_mapping) = mapping {
+                Self::insert(
+                    &mut table,
+                    Self::archived_input_key(base_mapping),
+                    LookupEntry {
+                        mapping: StoredMapping::Archived {
+                            archived: base_mapping,
+                            owned: spin::Once::new(),
+                        },
+                        condition: None,
+                        position: MappingPosition { index, inner: None },
+                        device_dependent: false,
+                    },
+                );
+            }
+        }
+
         Self { table }
     }
 
+    /// Appends an entry to the entries of its input key
+    fn insert(table: &mut HashMap<KeyCode, KeyEntries>, key: Option<KeyCode>, entry: LookupEntry) {
+        let Some(key) = key else {
+            return;
+        };
+        table
+            .entry(key)
+            .or_insert_with(KeyEntries::new)
+            .entries
+            .push(entry);
+    }
+
+    /// Returns every key the table's mappings can emit
+    ///
+    /// Matches `DeviceConfig::output_keys` for the configuration the table
+    /// was built from: sorted by key code, without duplicates.
+    pub fn output_keys(&self) -> Vec<KeyCode> {
+        let mut keys = Vec::new();
+        for entry in self.table.values().flat_map(|key| &key.entries) {
+            match &entry.mapping {
+                StoredMapping::Owned(mapping) => mapping.collect_output_keys(&mut keys),
+                StoredMapping::Archived { archived, .. } => {
+                    Self::collect_archived_output_keys(archived, &mut keys)
+                }
+            }
+        }
+        keys.sort_unstable_by_key(|key| *key as u16);
+        keys.dedup();
+        keys
+    }
+
     /// Finds the appropriate mapping for a key based on current device state
     ///
     /// This is a convenience method that calls `find_mapping_with_device`
@@ -172,19 +443,38 @@ impl KeyLookup {
         state: &DeviceState,
         device_id: Option<&str>,
     ) -> Option<&BaseKeyMapping> {
-        // Get the Vec of entries for this key
-        let entries = self.table.get(&key)?;
+        self.find_positioned_mapping(key, state, device_id)
+            .map(|(mapping, _)| mapping)
+    }
+
+    /// Finds the matching mapping like `find_mapping_with_device`, together
+    /// with its position in the device configuration
+    pub fn find_positioned_mapping(
+        &self,
+        key: KeyCode,
+        state: &DeviceState,
+        device_id: Option<&str>,
+    ) -> Option<(&BaseKeyMapping, MappingPosition)> {
+        // Get the entries for this key
+        let key_entries = self.table.get(&key)?;
+
+        // Entries before the resolved start are known not to match
+        let start = key_entries.resolved_start(state);
+        let remaining = key_entries.entries.get(start..)?;
 
         // Iterate through entries in order (conditionals first, then unconditional)
-        for entry in entries {
-            // If there's a condition, evaluate it with device context
-            if let Some(condition) = &entry.condition {
-                if state.evaluate_condition_with_device(condition, device_id) {
-                    return Some(&entry.mapping);
-                }
-            } else {
+        for (offset, entry) in remaining.iter().enumerate() {
+            let matches = match &entry.condition {
+                // Evaluate device conditions with device context
+                Some(condition) if entry.device_dependent => condition.evaluate(state, device_id),
+                // The resolved start already matched under this state
+                Some(_) if offset == 0 => true,
+                Some(condition) => condition.evaluate(state, device_id),
                 // Unconditional mapping - always matches
-                return Some(&entry.mapping);
+                None => true,
+            };
+            if matches {
+                return Some((entry.mapping.get(), entry.position));
             }
         }
 
@@ -192,6 +482,24 @@ impl KeyLookup {
         None
     }
 
+    /// Returns whether a condition reads the event's device ID
+    fn depends_on_device(condition: &Condition) -> bool {
+        match condition {
+            Condition::DeviceMatches(_) => true,
+            Condition::Negate(inner) => Self::depends_on_device(inner),
+            _ => false,
+        }
+    }
+
+    /// Returns whether an archived condition reads the event's device ID
+    fn archived_depends_on_device(condition: &ArchivedCondition) -> bool {
+        match condition {
+            ArchivedCondition::DeviceMatches(_) => true,
+            ArchivedCondition::Negate(inner) => Self::archived_depends_on_device(inner),
+            _ => false,
+        }
+    }
+
     /// Extracts the input key from a BaseKeyMapping variant
     ///
     /// # Arguments
@@ -208,6 +516,75 @@ impl KeyLookup {
             BaseKeyMapping::Lock { from, .. } => Some(*from),
             BaseKeyMapping::TapHold { from, .. } => Some(*from),
             BaseKeyMapping::ModifiedOutput { from, .. } => Some(*from),
+            BaseKeyMapping::OnRelease { from, .. } => Some(*from),
+            BaseKeyMapping::LayerToggle { from, .. } => Some(*from),
+            BaseKeyMapping::Cycle { from, .. } => Some(*from),
+            BaseKeyMapping::Disable { from } => Some(*from),
+            BaseKeyMapping::Macro { from, .. } => Some(*from),
+        }
+    }
+
+    /// Extracts the input key from an archived mapping without
+    /// deserializing the rest of it
+    fn archived_input_key(mapping: &ArchivedBaseKeyMapping) -> Option<KeyCode> {
+        let from = match mapping {
+            ArchivedBaseKeyMapping::Simple { from, .. }
+            | ArchivedBaseKeyMapping::Modifier { from, .. }
+            | ArchivedBaseKeyMapping::Lock { from, .. }
+            | ArchivedBaseKeyMapping::TapHold { from, .. }
+            | ArchivedBaseKeyMapping::ModifiedOutput { from, .. }
+            | ArchivedBaseKeyMapping::OnRelease { from, .. }
+            | ArchivedBaseKeyMapping::LayerToggle { from, .. }
+            | ArchivedBaseKeyMapping::Cycle { from, .. }
+            | ArchivedBaseKeyMapping::Disable { from }
+            | ArchivedBaseKeyMapping::Macro { from, .. } => from,
+        };
+        Some(from_archive::<KeyCode>(from))
+    }
+
+    /// Archived counterpart of `BaseKeyMapping::collect_output_keys`
+    fn collect_archived_output_keys(mapping: &ArchivedBaseKeyMapping, keys: &mut Vec<KeyCode>) {
+        match mapping {
+            ArchivedBaseKeyMapping::Simple { to, .. }
+            | ArchivedBaseKeyMapping::OnRelease { to, .. } => keys.push(from_archive(to)),
+            ArchivedBaseKeyMapping::TapHold { tap, .. } => keys.push(from_archive(tap)),
+            ArchivedBaseKeyMapping::Cycle { outputs, .. } => {
+                keys.extend(outputs.iter().map(from_archive::<KeyCode>))
+            }
+            ArchivedBaseKeyMapping::Macro { steps, .. } => {
+                keys.extend(steps.iter().filter_map(|step| match step {
+                    ArchivedMacroStep::Press(key) | ArchivedMacroStep::Release(key) => {
+                        Some(from_archive::<KeyCode>(key))
+                    }
+                    ArchivedMacroStep::Wait(_) => None,
+                }))
+            }
+            ArchivedBaseKeyMapping::ModifiedOutput {
+                to,
+                shift,
+                ctrl,
+                alt,
+                win,
+                ..
+            } => {
+                keys.push(from_archive(to));
+                let modifiers = [
+                    (*shift, KeyCode::LShift),
+                    (*ctrl, KeyCode::LCtrl),
+                    (*alt, KeyCode::LAlt),
+                    (*win, KeyCode::LMeta),
+                ];
+                keys.extend(
+                    modifiers
+                        .iter()
+                        .filter(|(enabled, _)| *enabled)
+                        .map(|(_, key)| *key),
+                );
+            }
+            ArchivedBaseKeyMapping::Modifier { .. }
+            | ArchivedBaseKeyMapping::Lock { .. }
+            | ArchivedBaseKeyMapping::LayerToggle { .. }
+            | ArchivedBaseKeyMapping::Disable { .. } => {}
         }
     }
 }
@@ -219,15 +596,20 @@ mod tests {
     use alloc::string::String;
     use alloc::vec;
 
-    use crate::config::{Condition, DeviceIdentifier};
+    use crate::config::{Condition, ConditionItem, DeviceIdentifier, TapHoldPolicy};
 
     /// Helper to create a simple test DeviceConfig
     fn create_test_device_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
         DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: String::from("*"),
+                excludes: alloc::vec::Vec::new(),
             },
             mappings,
+            time_windows: alloc::vec::Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }
     }
 
@@ -249,12 +631,12 @@ mod tests {
         assert_eq!(lookup.table.len(), 1);
 
         // Entry for key A should exist
-        let entries = lookup.table.get(&KeyCode::A).unwrap();
+        let entries = &lookup.table.get(&KeyCode::A).unwrap().entries;
         assert_eq!(entries.len(), 1);
         assert!(entries[0].condition.is_none()); // Unconditional
 
         // Should be a Simple mapping
-        if let BaseKeyMapping::Simple { from, to } = &entries[0].mapping {
+        if let BaseKeyMapping::Simple { from, to } = entries[0].mapping.get() {
             assert_eq!(*from, KeyCode::A);
             assert_eq!(*to, KeyCode::B);
         } else {
@@ -274,12 +656,12 @@ mod tests {
         let lookup = KeyLookup::from_device_config(&config);
 
         // Should have one entry for key H
-        let entries = lookup.table.get(&KeyCode::H).unwrap();
+        let entries = &lookup.table.get(&KeyCode::H).unwrap().entries;
         assert_eq!(entries.len(), 1);
 
         // Should have a condition
         assert!(entries[0].condition.is_some());
-        if let Some(Condition::ModifierActive(id)) = &entries[0].condition {
+        if let Some(StoredCondition::Owned(Condition::ModifierActive(id))) = &entries[0].condition {
             assert_eq!(*id, 0);
         } else {
             panic!("Expected ModifierActive condition");
@@ -301,7 +683,7 @@ mod tests {
         ]);
         let lookup = KeyLookup::from_device_config(&config);
 
-        let entries = lookup.table.get(&KeyCode::H).unwrap();
+        let entries = &lookup.table.get(&KeyCode::H).unwrap().entries;
         assert_eq!(entries.len(), 2);
 
         // First entry should be conditional
@@ -382,6 +764,44 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_find_mapping_any_active() {
+        // H maps to Left when MD_00 OR LK_01 is active
+        let config = create_test_device_config(vec![KeyMapping::conditional(
+            Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(0),
+                ConditionItem::LockActive(1),
+            ]),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+            }],
+        )]);
+        let lookup = KeyLookup::from_device_config(&config);
+
+        for (modifier, lock, expected) in [
+            (false, false, false),
+            (true, false, true),
+            (false, true, true),
+            (true, true, true),
+        ] {
+            let mut state = DeviceState::new();
+            if modifier {
+                state.set_modifier(0);
+            }
+            if lock {
+                state.toggle_lock(1);
+            }
+            assert_eq!(
+                lookup.find_mapping(KeyCode::H, &state).is_some(),
+                expected,
+                "MD_00={} LK_01={}",
+                modifier,
+                lock
+            );
+        }
+    }
+
     #[test]
     fn test_find_mapping_conditional_before_unconditional() {
         // Conditional mapping first, unconditional fallback second
@@ -457,6 +877,7 @@ mod tests {
             tap: KeyCode::Space,
             hold_modifier: 0,
             threshold_ms: 200,
+            policy: TapHoldPolicy::Permissive,
         };
         assert_eq!(
             KeyLookup::extract_input_key(&tap_hold),
@@ -611,4 +1032,167 @@ mod tests {
             assert_eq!(*to, KeyCode::F13);
         }
     }
+
+    #[test]
+    fn test_resolved_cache_follows_state_changes() {
+        let config = create_test_device_config(vec![
+            KeyMapping::conditional(
+                Condition::ModifierActive(0),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::H,
+                    to: KeyCode::Left,
+                }],
+            ),
+            KeyMapping::conditional(
+                Condition::LockActive(1),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::H,
+                    to: KeyCode::Down,
+                }],
+            ),
+            KeyMapping::simple(KeyCode::H, KeyCode::J),
+        ]);
+        let lookup = KeyLookup::from_device_config(&config);
+        let target = |state: &DeviceState| match lookup.find_mapping(KeyCode::H, state) {
+            Some(BaseKeyMapping::Simple { to, .. }) => Some(*to),
+            _ => None,
+        };
+
+        let mut state = DeviceState::new();
+        assert_eq!(target(&state), Some(KeyCode::J));
+        assert_eq!(target(&state), Some(KeyCode::J)); // served from the cache
+
+        state.toggle_lock(1);
+        assert_eq!(target(&state), Some(KeyCode::Down));
+        state.set_modifier(0);
+        assert_eq!(target(&state), Some(KeyCode::Left));
+
+        // A second state sharing the lookup does not see the first one's cache
+        let other = DeviceState::new();
+        assert_eq!(target(&other), Some(KeyCode::J));
+        assert_eq!(target(&state), Some(KeyCode::Left));
+
+        state.clear_modifier(0);
+        state.toggle_lock(1);
+        assert_eq!(target(&state), Some(KeyCode::J));
+    }
+
+    #[test]
+    fn test_resolved_cache_still_checks_device_conditions() {
+        let config = create_test_device_config(vec![
+            KeyMapping::conditional(
+                Condition::Negate(alloc::boxed::Box::new(Condition::DeviceMatches(
+                    String::from("*numpad*"),
+                ))),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::Numpad1,
+                    to: KeyCode::End,
+                }],
+            ),
+            KeyMapping::simple(KeyCode::Numpad1, KeyCode::F13),
+        ]);
+        let lookup = KeyLookup::from_device_config(&config);
+        let state = DeviceState::new();
+
+        for (device, expected) in [
+            (Some("usb-numpad-1"), KeyCode::F13),
+            (Some("usb-keyboard-2"), KeyCode::End),
+            (Some("usb-numpad-1"), KeyCode::F13),
+        ] {
+            match lookup.find_mapping_with_device(KeyCode::Numpad1, &state, device) {
+                Some(BaseKeyMapping::Simple { to, .. }) => assert_eq!(*to, expected),
+                other => panic!("Unexpected mapping {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_archived_matches_from_device_config() {
+        let mut config = create_test_device_config(vec![
+            KeyMapping::simple(KeyCode::H, KeyCode::J),
+            KeyMapping::modifier(KeyCode::CapsLock, 0),
+            KeyMapping::conditional(
+                Condition::ModifierActive(0),
+                vec![
+                    BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    },
+                    BaseKeyMapping::ModifiedOutput {
+                        from: KeyCode::Y,
+                        to: KeyCode::Z,
+                        shift: false,
+                        ctrl: true,
+                        alt: false,
+                        win: false,
+                    },
+                ],
+            ),
+            KeyMapping::conditional(
+                Condition::DeviceMatches(String::from("*numpad*")),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::Numpad1,
+                    to: KeyCode::End,
+                }],
+            ),
+        ]);
+        config.default_layer = Some(0);
+
+        let archived = leak_archive(&config);
+
+        let owned = KeyLookup::from_device_config(&config);
+        let archived_lookup = KeyLookup::from_archived(archived);
+        let state = DeviceState::for_archived_device(archived);
+        assert!(state.is_layer_locked(0));
+
+        for (key, device) in [
+            (KeyCode::H, None),
+            (KeyCode::Y, None),
+            (KeyCode::CapsLock, None),
+            (KeyCode::Numpad1, Some("usb-numpad-1")),
+            (KeyCode::A, None),
+        ] {
+            assert_eq!(
+                archived_lookup.find_positioned_mapping(key, &state, device),
+                owned.find_positioned_mapping(key, &state, device),
+                "{:?}",
+                key
+            );
+        }
+        assert_eq!(archived_lookup.output_keys(), config.output_keys());
+    }
+
+    #[test]
+    fn test_from_archived_deserializes_only_matched_mappings() {
+        let config = create_test_device_config(vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+            KeyMapping::simple(KeyCode::C, KeyCode::D),
+        ]);
+        let lookup = KeyLookup::from_archived(leak_archive(&config));
+        let materialized = |key: KeyCode| match &lookup.table[&key].entries[0].mapping {
+            StoredMapping::Archived { owned, .. } => owned.is_completed(),
+            StoredMapping::Owned(_) => panic!("expected an archived entry"),
+        };
+        assert!(!materialized(KeyCode::A));
+        assert!(!materialized(KeyCode::C));
+
+        let state = DeviceState::new();
+        assert_eq!(
+            lookup.find_mapping(KeyCode::A, &state),
+            Some(&BaseKeyMapping::Simple {
+                from: KeyCode::A,
+                to: KeyCode::B,
+            })
+        );
+        assert!(materialized(KeyCode::A));
+        assert!(!materialized(KeyCode::C));
+    }
+
+    /// Archives a config into a buffer that lives for the rest of the test
+    fn leak_archive(config: &DeviceConfig) -> &'static ArchivedDeviceConfig {
+        let bytes = rkyv::to_bytes::<_, 1024>(config).expect("Serialization failed");
+        let bytes: &'static rkyv::AlignedVec =
+            alloc::boxed::Box::leak(alloc::boxed::Box::new(bytes));
+        rkyv::check_archived_root::<DeviceConfig>(&bytes[..]).expect("Validation failed")
+    }
 }
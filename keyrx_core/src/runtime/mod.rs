@@ -33,9 +33,14 @@ pub mod tap_hold;
 
 // Re-export public API
 pub use clock::{Clock, SystemClock, VirtualClock};
-pub use event::{check_tap_hold_timeouts, process_event, KeyEvent, KeyEventType};
-pub use lookup::KeyLookup;
-pub use state::DeviceState;
+#[cfg(feature = "trace-mapping")]
+pub use event::process_event_traced;
+pub use event::{
+    check_tap_hold_timeouts, find_output_overflow, output_capacity, process_event, KeyEvent,
+    KeyEventType, OutputOverflow,
+};
+pub use lookup::{KeyLookup, MappingPosition, MatchedMapping};
+pub use state::{DeviceState, PendingRelease, StateSnapshot, MAX_OUTPUT_KEYS_PER_INPUT};
 pub use tap_hold::{
     PendingKeyRegistry, TapHoldConfig, TapHoldOutput, TapHoldPhase, TapHoldProcessor, TapHoldState,
     TimeoutResult, DEFAULT_MAX_PENDING, MAX_OUTPUT_EVENTS,
@@ -5,10 +5,20 @@
 
 extern crate alloc;
 
+#[cfg(not(target_has_atomic = "64"))]
+use core::sync::atomic::AtomicU32;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::sync::atomic::Ordering;
+
 use arrayvec::ArrayVec;
 use bitvec::prelude::*;
 
-use crate::config::{Condition, ConditionItem, KeyCode};
+use crate::config::conditions::{ArchivedCondition, ArchivedConditionItem};
+use crate::config::mappings::ArchivedDeviceConfig;
+use crate::config::{Condition, ConditionItem, DeviceConfig, KeyCode, TimeWindow};
+use crate::runtime::event::KeyEvent;
+use crate::runtime::lookup::from_archive;
 use crate::runtime::tap_hold::{TapHoldProcessor, DEFAULT_MAX_PENDING};
 
 /// Maximum valid modifier/lock ID (0-254, ID 255 is reserved)
@@ -20,7 +30,96 @@ const MAX_PRESSED_KEYS: usize = 32;
 
 /// Maximum number of output keys per input key
 /// Covers ModifiedOutput with all 4 modifiers: Shift+Ctrl+Alt+Win+PrimaryKey = 5 keys
-const MAX_OUTPUT_KEYS_PER_INPUT: usize = 5;
+///
+/// Outputs beyond this are not tracked and would never be released; the
+/// compiler rejects mappings that exceed it.
+pub const MAX_OUTPUT_KEYS_PER_INPUT: usize = 5;
+
+/// Maximum number of tap-to-cycle keys whose position is remembered
+///
+/// When exceeded, the key tapped least recently starts over at its first
+/// output.
+const MAX_CYCLE_KEYS: usize = 32;
+
+/// Number of minutes in a day, the range of an injected time of day
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Source of condition stamps, shared by all states so no two states (or
+/// two moments of one state) ever carry the same stamp; 0 is never issued
+#[cfg(target_has_atomic = "64")]
+static NEXT_CONDITION_STAMP: AtomicU64 = AtomicU64::new(1);
+
+/// Targets without 64-bit atomics count in 32 bits. Stamps may repeat after
+/// wrapping, which is safe because `KeyLookup` does not cache resolutions on
+/// these targets.
+#[cfg(not(target_has_atomic = "64"))]
+static NEXT_CONDITION_STAMP: AtomicU32 = AtomicU32::new(1);
+
+/// Issues a fresh condition stamp
+#[inline]
+#[allow(clippy::useless_conversion)] // widens the 32-bit fallback counter
+fn next_condition_stamp() -> u64 {
+    NEXT_CONDITION_STAMP.fetch_add(1, Ordering::Relaxed).into()
+}
+
+/// An emit-on-release key press waiting for its release
+///
+/// Created when a key with an `OnRelease` mapping is pressed. The output is
+/// resolved at press time so the release emits the same key even if the
+/// active mapping changes while the key is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingRelease {
+    /// Physical key that was pressed
+    pub input: KeyCode,
+    /// Key to emit on release
+    pub output: KeyCode,
+    /// Press timestamp in microseconds
+    pub pressed_at_us: u64,
+    /// Maximum hold duration in microseconds for the output to be emitted
+    pub threshold_us: u64,
+}
+
+impl PendingRelease {
+    /// Returns the key to emit for a release at `released_at_us`
+    ///
+    /// Returns `None` if the key was held for `threshold_us` or longer.
+    pub fn output_for(&self, released_at_us: u64) -> Option<KeyCode> {
+        let held_us = released_at_us.saturating_sub(self.pressed_at_us);
+        (held_us < self.threshold_us).then_some(self.output)
+    }
+}
+
+/// Position of a tap-to-cycle key within its outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CyclePosition {
+    /// Physical key that cycles
+    input: KeyCode,
+    /// Index of the output emitted by the last tap
+    index: usize,
+    /// Timestamp of the last tap in microseconds
+    tapped_at_us: u64,
+}
+
+/// Saved copy of a [`DeviceState`]'s runtime state
+///
+/// Captures modifiers, locks, layer locks, pending tap-hold keys, pressed
+/// keys, emit-on-release keys, cycle positions, scheduled macro output and
+/// the injected time of day. Time windows come from the config and are not
+/// part of the snapshot. Everything is fixed-size apart from the bit vectors
+/// and the macro queue, so taking one is a few small copies.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    modifiers: BitVec<u8, Lsb0>,
+    locks: BitVec<u8, Lsb0>,
+    layer_locks: BitVec<u8, Lsb0>,
+    tap_hold: TapHoldProcessor<DEFAULT_MAX_PENDING>,
+    pressed_keys:
+        ArrayVec<(KeyCode, ArrayVec<KeyCode, MAX_OUTPUT_KEYS_PER_INPUT>), MAX_PRESSED_KEYS>,
+    pending_releases: ArrayVec<PendingRelease, MAX_PRESSED_KEYS>,
+    cycle_positions: ArrayVec<CyclePosition, MAX_CYCLE_KEYS>,
+    scheduled_outputs: alloc::vec::Vec<KeyEvent>,
+    minute_of_day: Option<u16>,
+}
 
 /// Device state tracking modifier, lock, and pressed key state
 ///
@@ -51,6 +150,9 @@ pub struct DeviceState {
     modifiers: BitVec<u8, Lsb0>,
     /// Lock state (255 bits, IDs 0-254)
     locks: BitVec<u8, Lsb0>,
+    /// Locked layers (255 bits, indexed by modifier ID), kept apart from
+    /// `modifiers` so releasing a momentary layer key does not unlock them
+    layer_locks: BitVec<u8, Lsb0>,
     /// Tap-hold processor for dual-function keys
     tap_hold: TapHoldProcessor<DEFAULT_MAX_PENDING>,
     /// Pressed key tracking: (input_key, [output_keys]) pairs
@@ -58,6 +160,22 @@ pub struct DeviceState {
     /// Supports multiple output keys per input (e.g., Shift+Z generates 2 keys)
     pressed_keys:
         ArrayVec<(KeyCode, ArrayVec<KeyCode, MAX_OUTPUT_KEYS_PER_INPUT>), MAX_PRESSED_KEYS>,
+    /// Emit-on-release keys currently held down
+    pending_releases: ArrayVec<PendingRelease, MAX_PRESSED_KEYS>,
+    /// Last output of each tap-to-cycle key
+    cycle_positions: ArrayVec<CyclePosition, MAX_CYCLE_KEYS>,
+    /// Macro output waiting to be taken by the caller, stamped with the
+    /// time it is due
+    scheduled_outputs: alloc::vec::Vec<KeyEvent>,
+    /// Time windows from the device config, indexed by TW_XX id
+    time_windows: alloc::vec::Vec<TimeWindow>,
+    /// Layer the device config boots into
+    default_layer: Option<u8>,
+    /// Local minute of day injected by the host (None until first set)
+    minute_of_day: Option<u16>,
+    /// Replaced whenever state read by conditions changes (see
+    /// [`Self::condition_stamp`])
+    condition_stamp: u64,
 }
 
 impl DeviceState {
@@ -74,11 +192,68 @@ impl DeviceState {
         Self {
             modifiers: bitvec![u8, Lsb0; 0; 255],
             locks: bitvec![u8, Lsb0; 0; 255],
+            layer_locks: bitvec![u8, Lsb0; 0; 255],
             tap_hold: TapHoldProcessor::new(),
             pressed_keys: ArrayVec::new(),
+            pending_releases: ArrayVec::new(),
+            cycle_positions: ArrayVec::new(),
+            scheduled_outputs: alloc::vec::Vec::new(),
+            time_windows: alloc::vec::Vec::new(),
+            default_layer: None,
+            minute_of_day: None,
+            condition_stamp: next_condition_stamp(),
         }
     }
 
+    /// Creates the boot state for a device config
+    ///
+    /// Sets the config's time windows and locks its default layer on, so
+    /// the layer's mappings apply from the first event.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let config = DeviceConfig { default_layer: Some(1), ..device };
+    /// let state = DeviceState::for_device(&config);
+    /// assert!(state.is_layer_locked(1));
+    /// ```
+    pub fn for_device(config: &DeviceConfig) -> Self {
+        let mut state = Self::new();
+        state.set_time_windows(&config.time_windows);
+        state.set_default_layer(config.default_layer);
+        state
+    }
+
+    /// Creates the boot state for an archived device config
+    ///
+    /// Same as `for_device`, deserializing only the time windows and default
+    /// layer from the archive.
+    pub fn for_archived_device(config: &ArchivedDeviceConfig) -> Self {
+        let windows: alloc::vec::Vec<TimeWindow> = from_archive(&config.time_windows);
+        let mut state = Self::new();
+        state.set_time_windows(&windows);
+        state.set_default_layer(config.default_layer.as_ref().copied());
+        state
+    }
+
+    /// Returns a stamp identifying the state that conditions are evaluated
+    /// against
+    ///
+    /// The stamp is replaced whenever a modifier, lock, layer lock, time
+    /// window or the minute of day changes, and is unique across all
+    /// states (on targets with 64-bit atomics; elsewhere it may wrap).
+    /// `KeyLookup` uses it as a dirty flag: condition results cached under
+    /// the current stamp are still valid.
+    pub fn condition_stamp(&self) -> u64 {
+        self.condition_stamp
+    }
+
+    /// Marks cached condition results as stale
+    #[inline]
+    fn conditions_changed(&mut self) {
+        self.condition_stamp = next_condition_stamp();
+    }
+
     /// Validates that a modifier/lock ID is in valid range (0-254)
     ///
     /// Returns true if valid, logs error and returns false if invalid (>254).
@@ -115,7 +290,10 @@ impl DeviceState {
         if !Self::validate_id(id) {
             return false;
         }
-        self.modifiers.set(id as usize, true);
+        if !self.modifiers[id as usize] {
+            self.modifiers.set(id as usize, true);
+            self.conditions_changed();
+        }
         true
     }
 
@@ -141,7 +319,10 @@ impl DeviceState {
         if !Self::validate_id(id) {
             return false;
         }
-        self.modifiers.set(id as usize, false);
+        if self.modifiers[id as usize] {
+            self.modifiers.set(id as usize, false);
+            self.conditions_changed();
+        }
         true
     }
 
@@ -170,11 +351,66 @@ impl DeviceState {
         }
         let current = self.locks[id as usize];
         self.locks.set(id as usize, !current);
+        self.conditions_changed();
+        true
+    }
+
+    /// Toggles a locked layer (OFF→ON or ON→OFF)
+    ///
+    /// A locked layer counts as an active modifier with the same ID until it
+    /// is toggled off again, regardless of momentary presses and releases.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Layer (modifier) ID (0-254)
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if successful, `false` if ID is invalid (>254)
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut state = DeviceState::new();
+    /// assert!(state.toggle_layer_lock(2)); // OFF → ON
+    /// assert!(state.is_modifier_active(2));
+    /// state.set_modifier(2);
+    /// state.clear_modifier(2); // momentary release keeps the lock
+    /// assert!(state.is_modifier_active(2));
+    /// assert!(state.toggle_layer_lock(2)); // ON → OFF
+    /// assert!(!state.is_modifier_active(2));
+    /// ```
+    pub fn toggle_layer_lock(&mut self, id: u8) -> bool {
+        if !Self::validate_id(id) {
+            return false;
+        }
+        let current = self.layer_locks[id as usize];
+        self.layer_locks.set(id as usize, !current);
+        self.conditions_changed();
         true
     }
 
+    /// Checks if a layer is locked on by a layer toggle
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Layer (modifier) ID (0-254)
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the layer is locked, `false` if not or ID is invalid
+    pub fn is_layer_locked(&self, id: u8) -> bool {
+        if !Self::validate_id(id) {
+            return false;
+        }
+        self.layer_locks[id as usize]
+    }
+
     /// Checks if a modifier is active
     ///
+    /// A modifier is active while its key is held or while its layer is
+    /// locked (see [`toggle_layer_lock`](Self::toggle_layer_lock)).
+    ///
     /// # Arguments
     ///
     /// * `id` - Modifier ID (0-254)
@@ -195,7 +431,7 @@ impl DeviceState {
         if !Self::validate_id(id) {
             return false;
         }
-        self.modifiers[id as usize]
+        self.modifiers[id as usize] || self.layer_locks[id as usize]
     }
 
     /// Checks if a lock is active
@@ -223,11 +459,129 @@ impl DeviceState {
         self.locks[id as usize]
     }
 
+    /// Returns the IDs of all active locks in ascending order
+    ///
+    /// Only locks are exposed here because they are the persistent part of
+    /// the state; modifiers are momentary and track physically held keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut state = DeviceState::new();
+    /// state.toggle_lock(3);
+    /// state.toggle_lock(1);
+    /// assert_eq!(state.active_locks(), vec![1, 3]);
+    /// ```
+    pub fn active_locks(&self) -> alloc::vec::Vec<u8> {
+        self.locks.iter_ones().map(|id| id as u8).collect()
+    }
+
+    /// Replaces the lock state with exactly the given active lock IDs
+    ///
+    /// Modifiers, pressed keys, and tap-hold state are left untouched.
+    /// Invalid IDs (>254) are skipped.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of locks that were activated
+    pub fn restore_locks(&mut self, ids: &[u8]) -> usize {
+        self.locks.fill(false);
+        self.conditions_changed();
+        let mut restored = 0;
+        for &id in ids {
+            if Self::validate_id(id) && !self.locks[id as usize] {
+                self.locks.set(id as usize, true);
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    /// Replaces the time windows referenced by `TW_XX` conditions
+    ///
+    /// Called with `DeviceConfig::time_windows` whenever a config is loaded.
+    pub fn set_time_windows(&mut self, windows: &[TimeWindow]) {
+        self.time_windows = windows.to_vec();
+        self.conditions_changed();
+    }
+
+    /// Returns the time windows referenced by `TW_XX` conditions
+    pub fn time_windows(&self) -> &[TimeWindow] {
+        &self.time_windows
+    }
+
+    /// Sets the layer the device boots into and locks it on
+    ///
+    /// Called with `DeviceConfig::default_layer` when a state is created.
+    /// The lock is the same one `layer_toggle` sets, so the layer's toggle
+    /// key can still turn it off.
+    ///
+    /// # Returns
+    ///
+    /// Returns `false` if the layer ID is invalid (>254)
+    pub fn set_default_layer(&mut self, layer: Option<u8>) -> bool {
+        self.default_layer = layer;
+        let Some(id) = layer else {
+            return true;
+        };
+        if !Self::validate_id(id) {
+            return false;
+        }
+        if !self.layer_locks[id as usize] {
+            self.layer_locks.set(id as usize, true);
+            self.conditions_changed();
+        }
+        true
+    }
+
+    /// Returns the layer the device boots into, if any
+    pub fn default_layer(&self) -> Option<u8> {
+        self.default_layer
+    }
+
+    /// Sets the current local time as minutes since midnight
+    ///
+    /// The runtime never reads a clock, so the host injects the time before
+    /// processing events; values are wrapped into 0-1439.
+    pub fn set_minute_of_day(&mut self, minute: u16) {
+        let minute = Some(minute % MINUTES_PER_DAY);
+        if self.minute_of_day != minute {
+            self.minute_of_day = minute;
+            self.conditions_changed();
+        }
+    }
+
+    /// Returns the last injected minute of day, if any
+    pub fn minute_of_day(&self) -> Option<u16> {
+        self.minute_of_day
+    }
+
+    /// Checks if time window `id` contains the current minute of day
+    ///
+    /// Returns false if no time has been injected yet or `id` does not refer
+    /// to a configured window.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut state = DeviceState::new();
+    /// state.set_time_windows(&[TimeWindow { start_minute: 1080, end_minute: 1380 }]);
+    /// state.set_minute_of_day(1200);
+    /// assert!(state.is_time_window_active(0));
+    /// ```
+    pub fn is_time_window_active(&self, id: u8) -> bool {
+        match (self.minute_of_day, self.time_windows.get(id as usize)) {
+            (Some(minute), Some(window)) => window.contains(minute),
+            _ => false,
+        }
+    }
+
     /// Evaluates a condition against the current device state
     ///
     /// This is a convenience method that calls `evaluate_condition_with_device`
     /// with `device_id = None`. Use this for conditions that don't involve
-    /// device matching (ModifierActive, LockActive, AllActive, NotActive).
+    /// device matching (ModifierActive, LockActive, AllActive, AnyActive,
+    /// NotActive, Composite).
     ///
     /// Note: DeviceMatches conditions will always return false when called
     /// without a device_id. Use `evaluate_condition_with_device` for those.
@@ -308,6 +662,9 @@ impl DeviceState {
             // Single lock active
             Condition::LockActive(id) => self.is_lock_active(*id),
 
+            // Single time window active
+            Condition::TimeWindowActive(id) => self.is_time_window_active(*id),
+
             // All conditions must be true (AND logic)
             Condition::AllActive(items) => {
                 items.iter().all(|item| self.evaluate_condition_item(item))
@@ -320,6 +677,62 @@ impl DeviceState {
 
             // Device ID matches pattern
             Condition::DeviceMatches(pattern) => Self::matches_device_pattern(device_id, pattern),
+
+            // All of `all` true AND all of `none` false
+            Condition::Composite { all, none } => {
+                all.iter().all(|item| self.evaluate_condition_item(item))
+                    && none.iter().all(|item| !self.evaluate_condition_item(item))
+            }
+
+            // At least one condition must be true (OR logic)
+            Condition::AnyActive(items) => {
+                items.iter().any(|item| self.evaluate_condition_item(item))
+            }
+
+            // Inverted sub-condition
+            Condition::Negate(inner) => !self.evaluate_condition_with_device(inner, device_id),
+        }
+    }
+
+    /// Evaluates an archived condition in place, without deserializing it
+    ///
+    /// Same semantics as [`evaluate_condition_with_device`](Self::evaluate_condition_with_device),
+    /// used by `KeyLookup` tables built over an archived configuration.
+    pub fn evaluate_archived_condition_with_device(
+        &self,
+        condition: &ArchivedCondition,
+        device_id: Option<&str>,
+    ) -> bool {
+        let all = |items: &[ArchivedConditionItem]| {
+            items
+                .iter()
+                .all(|item| self.evaluate_archived_condition_item(item))
+        };
+        let none = |items: &[ArchivedConditionItem]| {
+            items
+                .iter()
+                .all(|item| !self.evaluate_archived_condition_item(item))
+        };
+
+        match condition {
+            ArchivedCondition::ModifierActive(id) => self.is_modifier_active(*id),
+            ArchivedCondition::LockActive(id) => self.is_lock_active(*id),
+            ArchivedCondition::TimeWindowActive(id) => self.is_time_window_active(*id),
+            ArchivedCondition::AllActive(items) => all(items),
+            ArchivedCondition::NotActive(items) => none(items),
+            ArchivedCondition::DeviceMatches(pattern) => {
+                Self::matches_device_pattern(device_id, pattern.as_str())
+            }
+            ArchivedCondition::Composite {
+                all: all_items,
+                none: none_items,
+            } => all(all_items) && none(none_items),
+            ArchivedCondition::AnyActive(items) => items
+                .iter()
+                .any(|item| self.evaluate_archived_condition_item(item)),
+            ArchivedCondition::Negate(inner) => {
+                !self.evaluate_archived_condition_with_device(inner, device_id)
+            }
         }
     }
 
@@ -417,6 +830,16 @@ impl DeviceState {
         match item {
             ConditionItem::ModifierActive(id) => self.is_modifier_active(*id),
             ConditionItem::LockActive(id) => self.is_lock_active(*id),
+            ConditionItem::TimeWindowActive(id) => self.is_time_window_active(*id),
+        }
+    }
+
+    /// Evaluates a single archived condition item
+    fn evaluate_archived_condition_item(&self, item: &ArchivedConditionItem) -> bool {
+        match item {
+            ArchivedConditionItem::ModifierActive(id) => self.is_modifier_active(*id),
+            ArchivedConditionItem::LockActive(id) => self.is_lock_active(*id),
+            ArchivedConditionItem::TimeWindowActive(id) => self.is_time_window_active(*id),
         }
     }
 
@@ -509,9 +932,163 @@ impl DeviceState {
         self.pressed_keys.retain(|(k, _)| *k != input);
     }
 
+    /// Records a press of an emit-on-release key
+    ///
+    /// A repeated press of a key that is already pending keeps the original
+    /// press timestamp, so key repeat does not extend the hold threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The physical key that was pressed
+    /// * `output` - The key to emit when `input` is released
+    /// * `pressed_at_us` - Press timestamp in microseconds
+    /// * `threshold_ms` - Holds of this length or longer emit nothing
+    pub fn record_pending_release(
+        &mut self,
+        input: KeyCode,
+        output: KeyCode,
+        pressed_at_us: u64,
+        threshold_ms: u16,
+    ) {
+        if self.pending_releases.iter().any(|p| p.input == input) {
+            return;
+        }
+
+        // Ignore if array is full - unlikely scenario
+        let _ = self.pending_releases.try_push(PendingRelease {
+            input,
+            output,
+            pressed_at_us,
+            threshold_us: u64::from(threshold_ms) * 1000,
+        });
+    }
+
+    /// Removes and returns the pending emit-on-release press for `input`
+    ///
+    /// Returns `None` if `input` was not pressed as an emit-on-release key.
+    pub fn take_pending_release(&mut self, input: KeyCode) -> Option<PendingRelease> {
+        let index = self
+            .pending_releases
+            .iter()
+            .position(|p| p.input == input)?;
+        Some(self.pending_releases.remove(index))
+    }
+
+    /// Advances a tap-to-cycle key and returns the index of the output to emit
+    ///
+    /// The first tap, and any tap at least `reset_ms` after the previous
+    /// one, returns 0. Otherwise the index moves one past the last tap's,
+    /// wrapping around after `len - 1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The physical key that was pressed
+    /// * `len` - Number of outputs the key cycles through (must be > 0)
+    /// * `pressed_at_us` - Press timestamp in microseconds
+    /// * `reset_ms` - Inactivity after which the cycle starts over
+    pub fn next_cycle_index(
+        &mut self,
+        input: KeyCode,
+        len: usize,
+        pressed_at_us: u64,
+        reset_ms: u16,
+    ) -> usize {
+        let reset_us = u64::from(reset_ms) * 1000;
+
+        if let Some(position) = self.cycle_positions.iter_mut().find(|p| p.input == input) {
+            let idle_us = pressed_at_us.saturating_sub(position.tapped_at_us);
+            position.index = if idle_us >= reset_us {
+                0
+            } else {
+                (position.index + 1) % len.max(1)
+            };
+            position.tapped_at_us = pressed_at_us;
+            return position.index;
+        }
+
+        if self.cycle_positions.is_full() {
+            // Forget the key tapped least recently
+            if let Some(oldest) = self
+                .cycle_positions
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, p)| p.tapped_at_us)
+                .map(|(i, _)| i)
+            {
+                self.cycle_positions.remove(oldest);
+            }
+        }
+        let _ = self.cycle_positions.try_push(CyclePosition {
+            input,
+            index: 0,
+            tapped_at_us: pressed_at_us,
+        });
+        0
+    }
+
+    /// Queues macro output that is due after the current event
+    ///
+    /// Each event's timestamp is the time it is due.
+    pub(crate) fn schedule_outputs(&mut self, events: alloc::vec::Vec<KeyEvent>) {
+        self.scheduled_outputs.extend(events);
+    }
+
+    /// Removes and returns the macro output scheduled since the last call
+    ///
+    /// Each event's timestamp is the time it is due, on the same timescale
+    /// as input timestamps. Events come in the order they were scheduled;
+    /// the caller injects each once its clock reaches that time.
+    pub fn take_scheduled_outputs(&mut self) -> alloc::vec::Vec<KeyEvent> {
+        core::mem::take(&mut self.scheduled_outputs)
+    }
+
     /// Clears all pressed key tracking (for testing or emergency reset)
     pub fn clear_all_pressed(&mut self) {
         self.pressed_keys.clear();
+        self.pending_releases.clear();
+    }
+
+    /// Captures the current runtime state so it can be restored later
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut state = DeviceState::new();
+    /// let checkpoint = state.snapshot();
+    /// state.set_modifier(0);
+    /// state.restore(&checkpoint);
+    /// assert!(!state.is_modifier_active(0));
+    /// ```
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            modifiers: self.modifiers.clone(),
+            locks: self.locks.clone(),
+            layer_locks: self.layer_locks.clone(),
+            tap_hold: self.tap_hold.clone(),
+            pressed_keys: self.pressed_keys.clone(),
+            pending_releases: self.pending_releases.clone(),
+            cycle_positions: self.cycle_positions.clone(),
+            scheduled_outputs: self.scheduled_outputs.clone(),
+            minute_of_day: self.minute_of_day,
+        }
+    }
+
+    /// Returns the runtime state to a snapshot taken with [`Self::snapshot`]
+    ///
+    /// Time windows are left untouched, so a snapshot should be restored
+    /// into a state built from the same config.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) {
+        self.modifiers.clone_from(&snapshot.modifiers);
+        self.locks.clone_from(&snapshot.locks);
+        self.layer_locks.clone_from(&snapshot.layer_locks);
+        self.tap_hold.clone_from(&snapshot.tap_hold);
+        self.pressed_keys.clone_from(&snapshot.pressed_keys);
+        self.pending_releases.clone_from(&snapshot.pending_releases);
+        self.cycle_positions.clone_from(&snapshot.cycle_positions);
+        self.scheduled_outputs
+            .clone_from(&snapshot.scheduled_outputs);
+        self.minute_of_day = snapshot.minute_of_day;
+        self.conditions_changed();
     }
 }
 
@@ -525,6 +1102,8 @@ impl Default for DeviceState {
 mod tests {
     use super::*;
     extern crate alloc;
+    use alloc::boxed::Box;
+    use alloc::string::ToString;
     use alloc::vec;
 
     #[test]
@@ -685,6 +1264,373 @@ mod tests {
         assert!(state.evaluate_condition(&cond_both_inactive));
     }
 
+    #[test]
+    fn test_evaluate_condition_composite_truth_table() {
+        // "Fn held and not in gaming lock": MD_00 active AND LK_01 inactive
+        let cond = Condition::Composite {
+            all: vec![ConditionItem::ModifierActive(0)],
+            none: vec![ConditionItem::LockActive(1)],
+        };
+
+        for (modifier, lock, expected) in [
+            (false, false, false),
+            (true, false, true),
+            (false, true, false),
+            (true, true, false),
+        ] {
+            let mut state = DeviceState::new();
+            if modifier {
+                state.set_modifier(0);
+            }
+            if lock {
+                state.toggle_lock(1);
+            }
+            assert_eq!(
+                state.evaluate_condition(&cond),
+                expected,
+                "MD_00={} LK_01={}",
+                modifier,
+                lock
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition_composite_multiple_items() {
+        let cond = Condition::Composite {
+            all: vec![
+                ConditionItem::ModifierActive(0),
+                ConditionItem::ModifierActive(1),
+            ],
+            none: vec![ConditionItem::LockActive(0), ConditionItem::LockActive(1)],
+        };
+
+        // Every combination of the four bits
+        for bits in 0u8..16 {
+            let mut state = DeviceState::new();
+            let (md0, md1, lk0, lk1) = (bits & 1 != 0, bits & 2 != 0, bits & 4 != 0, bits & 8 != 0);
+            if md0 {
+                state.set_modifier(0);
+            }
+            if md1 {
+                state.set_modifier(1);
+            }
+            if lk0 {
+                state.toggle_lock(0);
+            }
+            if lk1 {
+                state.toggle_lock(1);
+            }
+
+            let expected = md0 && md1 && !lk0 && !lk1;
+            assert_eq!(
+                state.evaluate_condition(&cond),
+                expected,
+                "bits={:04b}",
+                bits
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition_composite_empty_lists() {
+        let mut state = DeviceState::new();
+        state.set_modifier(0);
+
+        // Both empty: vacuously true
+        let empty = Condition::Composite {
+            all: vec![],
+            none: vec![],
+        };
+        assert!(state.evaluate_condition(&empty));
+
+        // Empty `none` behaves like AllActive
+        let all_only = Condition::Composite {
+            all: vec![ConditionItem::ModifierActive(0)],
+            none: vec![],
+        };
+        assert!(state.evaluate_condition(&all_only));
+
+        // Empty `all` behaves like NotActive
+        let none_only = Condition::Composite {
+            all: vec![],
+            none: vec![ConditionItem::ModifierActive(0)],
+        };
+        assert!(!state.evaluate_condition(&none_only));
+    }
+
+    #[test]
+    fn test_evaluate_condition_any_active_truth_table() {
+        // "Fn OR Gaming": MD_00 active OR LK_01 active
+        let cond = Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::LockActive(1),
+        ]);
+
+        for (modifier, lock, expected) in [
+            (false, false, false),
+            (true, false, true),
+            (false, true, true),
+            (true, true, true),
+        ] {
+            let mut state = DeviceState::new();
+            if modifier {
+                state.set_modifier(0);
+            }
+            if lock {
+                state.toggle_lock(1);
+            }
+            assert_eq!(
+                state.evaluate_condition(&cond),
+                expected,
+                "MD_00={} LK_01={}",
+                modifier,
+                lock
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition_any_active_three_items() {
+        let cond = Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::ModifierActive(1),
+            ConditionItem::LockActive(0),
+        ]);
+
+        // Every combination of the three bits
+        for bits in 0u8..8 {
+            let mut state = DeviceState::new();
+            let (md0, md1, lk0) = (bits & 1 != 0, bits & 2 != 0, bits & 4 != 0);
+            if md0 {
+                state.set_modifier(0);
+            }
+            if md1 {
+                state.set_modifier(1);
+            }
+            if lk0 {
+                state.toggle_lock(0);
+            }
+
+            assert_eq!(
+                state.evaluate_condition(&cond),
+                md0 || md1 || lk0,
+                "bits={:03b}",
+                bits
+            );
+        }
+
+        // Empty list: nothing can be active
+        let state = DeviceState::new();
+        assert!(!state.evaluate_condition(&Condition::AnyActive(vec![])));
+    }
+
+    #[test]
+    fn test_evaluate_condition_negate_de_morgan() {
+        let all = Condition::AllActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::LockActive(1),
+        ]);
+        let any = Condition::AnyActive(vec![
+            ConditionItem::ModifierActive(0),
+            ConditionItem::LockActive(1),
+        ]);
+        let not_md0 = || Condition::Negate(Box::new(Condition::ModifierActive(0)));
+        let not_lk1 = || Condition::Negate(Box::new(Condition::LockActive(1)));
+
+        for bits in 0u8..4 {
+            let mut state = DeviceState::new();
+            let (md0, lk1) = (bits & 1 != 0, bits & 2 != 0);
+            if md0 {
+                state.set_modifier(0);
+            }
+            if lk1 {
+                state.toggle_lock(1);
+            }
+            let eval = |cond: &Condition| state.evaluate_condition(cond);
+
+            // not(a and b) == (not a) or (not b)
+            assert_eq!(
+                eval(&Condition::Negate(Box::new(all.clone()))),
+                eval(&not_md0()) || eval(&not_lk1()),
+                "bits={:02b}",
+                bits
+            );
+            // not(a or b) == (not a) and (not b), i.e. NotActive over the items
+            let not_any = eval(&Condition::Negate(Box::new(any.clone())));
+            assert_eq!(not_any, eval(&not_md0()) && eval(&not_lk1()));
+            assert_eq!(
+                not_any,
+                eval(&Condition::NotActive(vec![
+                    ConditionItem::ModifierActive(0),
+                    ConditionItem::LockActive(1),
+                ]))
+            );
+            // not(not a) == a
+            assert_eq!(
+                eval(&Condition::Negate(Box::new(not_md0()))),
+                md0,
+                "bits={:02b}",
+                bits
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition_negate_device_matches() {
+        let state = DeviceState::new();
+        let cond = Condition::Negate(Box::new(Condition::DeviceMatches("*numpad*".to_string())));
+
+        assert!(!state.evaluate_condition_with_device(&cond, Some("usb-numpad-1")));
+        assert!(state.evaluate_condition_with_device(&cond, Some("usb-keyboard")));
+        assert!(state.evaluate_condition_with_device(&cond, None));
+    }
+
+    #[test]
+    fn test_evaluate_archived_condition_matches_owned() {
+        let conditions = vec![
+            Condition::ModifierActive(1),
+            Condition::LockActive(2),
+            Condition::AllActive(vec![
+                ConditionItem::ModifierActive(1),
+                ConditionItem::LockActive(2),
+            ]),
+            Condition::NotActive(vec![ConditionItem::ModifierActive(3)]),
+            Condition::AnyActive(vec![
+                ConditionItem::ModifierActive(3),
+                ConditionItem::LockActive(2),
+            ]),
+            Condition::Composite {
+                all: vec![ConditionItem::ModifierActive(1)],
+                none: vec![ConditionItem::LockActive(2)],
+            },
+            Condition::DeviceMatches("*numpad*".to_string()),
+            Condition::Negate(Box::new(Condition::DeviceMatches("usb-*".to_string()))),
+            Condition::TimeWindowActive(0),
+        ];
+        let bytes = rkyv::to_bytes::<_, 1024>(&conditions).expect("Serialization failed");
+        let archived = rkyv::check_archived_root::<alloc::vec::Vec<Condition>>(&bytes[..])
+            .expect("Validation failed");
+
+        let mut state = DeviceState::new();
+        state.set_time_windows(&[TimeWindow {
+            start_minute: 60,
+            end_minute: 120,
+        }]);
+        state.set_minute_of_day(90);
+        for (modifier, lock) in [(false, false), (true, false), (true, true)] {
+            if modifier {
+                state.set_modifier(1);
+            }
+            if lock {
+                state.toggle_lock(2);
+            }
+            for device in [None, Some("usb-numpad-1"), Some("bt-keyboard")] {
+                for (condition, archived) in conditions.iter().zip(archived.iter()) {
+                    assert_eq!(
+                        state.evaluate_archived_condition_with_device(archived, device),
+                        state.evaluate_condition_with_device(condition, device),
+                        "{:?} with {:?}",
+                        condition,
+                        device
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_condition_time_window() {
+        let mut state = DeviceState::new();
+        state.set_time_windows(&[TimeWindow {
+            start_minute: 18 * 60,
+            end_minute: 23 * 60,
+        }]);
+        let cond = Condition::TimeWindowActive(0);
+
+        // No time injected yet: inactive
+        assert!(!state.evaluate_condition(&cond));
+
+        state.set_minute_of_day(20 * 60);
+        assert!(state.evaluate_condition(&cond));
+        assert!(state.evaluate_condition(&Condition::AllActive(vec![
+            ConditionItem::TimeWindowActive(0)
+        ])));
+
+        state.set_minute_of_day(9 * 60);
+        assert!(!state.evaluate_condition(&cond));
+        assert!(state.evaluate_condition(&Condition::NotActive(vec![
+            ConditionItem::TimeWindowActive(0)
+        ])));
+
+        // Undefined window id never matches
+        state.set_minute_of_day(20 * 60);
+        assert!(!state.evaluate_condition(&Condition::TimeWindowActive(1)));
+    }
+
+    #[test]
+    fn test_order_for_evaluation_preserves_truth_values() {
+        use crate::config::ConditionItem::{LockActive, ModifierActive, TimeWindowActive};
+
+        let items = vec![TimeWindowActive(0), ModifierActive(1), LockActive(2)];
+        let conditions = [
+            Condition::AllActive(items.clone()),
+            Condition::NotActive(items.clone()),
+            Condition::AnyActive(items.clone()),
+            Condition::Composite {
+                all: vec![TimeWindowActive(0), ModifierActive(1)],
+                none: vec![TimeWindowActive(1), LockActive(2)],
+            },
+            Condition::Negate(Box::new(Condition::AllActive(items.clone()))),
+        ];
+
+        let mut ordered = conditions.clone();
+        for condition in &mut ordered {
+            condition.order_for_evaluation();
+        }
+        assert_eq!(
+            ordered[0],
+            Condition::AllActive(vec![ModifierActive(1), LockActive(2), TimeWindowActive(0)])
+        );
+
+        // Every combination of window 0, window 1, MD_01 and LK_02
+        for bits in 0..16u8 {
+            let mut state = DeviceState::new();
+            state.set_time_windows(&[
+                TimeWindow {
+                    start_minute: 0,
+                    end_minute: 60,
+                },
+                TimeWindow {
+                    start_minute: 60,
+                    end_minute: 120,
+                },
+            ]);
+            state.set_minute_of_day(match bits & 0b11 {
+                0b01 => 30,
+                0b10 => 90,
+                // Neither window; both at once is not possible
+                _ => 600,
+            });
+            if bits & 0b100 != 0 {
+                state.set_modifier(1);
+            }
+            if bits & 0b1000 != 0 {
+                state.toggle_lock(2);
+            }
+
+            for (original, reordered) in conditions.iter().zip(&ordered) {
+                assert_eq!(
+                    state.evaluate_condition(original),
+                    state.evaluate_condition(reordered),
+                    "{:?} with bits {:04b}",
+                    original,
+                    bits
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_multiple_modifiers_independent() {
         let mut state = DeviceState::new();
@@ -721,6 +1667,180 @@ mod tests {
         assert!(state.is_lock_active(2));
     }
 
+    #[test]
+    fn test_active_locks_excludes_modifiers() {
+        let mut state = DeviceState::new();
+
+        state.toggle_lock(5);
+        state.toggle_lock(0);
+        state.toggle_lock(254);
+        state.set_modifier(1);
+        state.set_modifier(5);
+
+        assert_eq!(state.active_locks(), vec![0, 5, 254]);
+    }
+
+    #[test]
+    fn test_restore_locks_replaces_lock_state() {
+        let mut state = DeviceState::new();
+        state.toggle_lock(7);
+        state.set_modifier(2);
+
+        // Duplicates are counted once and invalid IDs are skipped
+        assert_eq!(state.restore_locks(&[1, 3, 3, 255]), 2);
+
+        assert_eq!(state.active_locks(), vec![1, 3]);
+        assert!(!state.is_lock_active(7));
+        assert!(state.is_modifier_active(2));
+    }
+
+    #[test]
+    fn test_pending_release_output_for_threshold() {
+        let pending = PendingRelease {
+            input: KeyCode::A,
+            output: KeyCode::B,
+            pressed_at_us: 1_000,
+            threshold_us: 200_000,
+        };
+
+        assert_eq!(pending.output_for(1_000), Some(KeyCode::B));
+        assert_eq!(pending.output_for(200_999), Some(KeyCode::B));
+        assert_eq!(pending.output_for(201_000), None);
+        // Out-of-order timestamps count as an instant release
+        assert_eq!(pending.output_for(0), Some(KeyCode::B));
+    }
+
+    #[test]
+    fn test_record_pending_release_keeps_first_press() {
+        let mut state = DeviceState::new();
+
+        state.record_pending_release(KeyCode::A, KeyCode::B, 1_000, 200);
+        // Key repeat must not restart the hold timer
+        state.record_pending_release(KeyCode::A, KeyCode::B, 50_000, 200);
+
+        let pending = state.take_pending_release(KeyCode::A).unwrap();
+        assert_eq!(pending.pressed_at_us, 1_000);
+        assert_eq!(pending.threshold_us, 200_000);
+        assert!(state.take_pending_release(KeyCode::A).is_none());
+    }
+
+    #[test]
+    fn test_next_cycle_index_wraps_and_resets() {
+        let mut state = DeviceState::new();
+
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 0, 500), 0);
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 100_000, 500), 1);
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 200_000, 500), 2);
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 300_000, 500), 0);
+        // Other cycle keys keep their own position
+        assert_eq!(state.next_cycle_index(KeyCode::F15, 2, 350_000, 500), 0);
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 400_000, 500), 1);
+        // The window is measured from the previous tap
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 900_000, 500), 0);
+    }
+
+    #[test]
+    fn test_snapshot_restore_round_trip() {
+        use crate::runtime::tap_hold::TapHoldConfig;
+
+        let mut state = DeviceState::new();
+        state.tap_hold_processor().register_tap_hold(
+            KeyCode::CapsLock,
+            TapHoldConfig::new(KeyCode::Escape, 1, 200_000),
+        );
+        state.set_modifier(0);
+        state.toggle_lock(2);
+        state.record_press(KeyCode::A, &[KeyCode::LShift, KeyCode::B]);
+        state.next_cycle_index(KeyCode::F14, 3, 0, 500);
+        state.set_minute_of_day(600);
+        let checkpoint = state.snapshot();
+
+        state.clear_modifier(0);
+        state.toggle_lock(2);
+        state.toggle_layer_lock(3);
+        state
+            .tap_hold_processor()
+            .process_press(KeyCode::CapsLock, 0);
+        state.clear_press(KeyCode::A);
+        state.record_pending_release(KeyCode::C, KeyCode::D, 0, 200);
+        state.next_cycle_index(KeyCode::F14, 3, 100_000, 500);
+        state.set_minute_of_day(700);
+
+        state.restore(&checkpoint);
+        assert!(state.is_modifier_active(0));
+        assert!(state.is_lock_active(2));
+        assert!(!state.is_layer_locked(3));
+        assert!(!state.tap_hold_processor_ref().is_pending(KeyCode::CapsLock));
+        assert!(state
+            .tap_hold_processor_ref()
+            .is_tap_hold_key(KeyCode::CapsLock));
+        assert_eq!(
+            state.get_release_key(KeyCode::A).as_slice(),
+            &[KeyCode::LShift, KeyCode::B]
+        );
+        assert!(state.take_pending_release(KeyCode::C).is_none());
+        assert_eq!(state.next_cycle_index(KeyCode::F14, 3, 100_000, 500), 1);
+        assert_eq!(state.minute_of_day(), Some(600));
+    }
+
+    #[test]
+    fn test_snapshot_restore_scheduled_outputs() {
+        let mut state = DeviceState::new();
+        state.schedule_outputs(vec![KeyEvent::press(KeyCode::A).with_timestamp(50_000)]);
+        let checkpoint = state.snapshot();
+
+        state.schedule_outputs(vec![KeyEvent::release(KeyCode::A).with_timestamp(60_000)]);
+        state.restore(&checkpoint);
+        assert_eq!(
+            state.take_scheduled_outputs(),
+            vec![KeyEvent::press(KeyCode::A).with_timestamp(50_000)]
+        );
+
+        state.restore(&DeviceState::new().snapshot());
+        assert!(state.take_scheduled_outputs().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_is_independent_of_later_changes() {
+        let mut state = DeviceState::new();
+        let empty = state.snapshot();
+        state.set_modifier(5);
+        let with_modifier = state.snapshot();
+
+        state.restore(&empty);
+        assert!(!state.is_modifier_active(5));
+        state.restore(&with_modifier);
+        assert!(state.is_modifier_active(5));
+    }
+
+    #[test]
+    fn test_condition_stamp_changes_only_with_condition_state() {
+        let mut state = DeviceState::new();
+        let other = DeviceState::new();
+        assert_ne!(state.condition_stamp(), other.condition_stamp());
+
+        let initial = state.condition_stamp();
+        state.record_press(KeyCode::A, &[KeyCode::B]);
+        state.clear_modifier(3); // already clear
+        assert_eq!(state.condition_stamp(), initial);
+
+        state.set_modifier(3);
+        let with_modifier = state.condition_stamp();
+        assert_ne!(with_modifier, initial);
+        state.set_modifier(3); // already set
+        assert_eq!(state.condition_stamp(), with_modifier);
+
+        state.toggle_lock(1);
+        assert_ne!(state.condition_stamp(), with_modifier);
+
+        let before_time = state.condition_stamp();
+        state.set_minute_of_day(600);
+        let with_time = state.condition_stamp();
+        assert_ne!(with_time, before_time);
+        state.set_minute_of_day(600);
+        assert_eq!(state.condition_stamp(), with_time);
+    }
+
     // Property-based tests
     //
     // These tests verify state management invariants using proptest to generate
@@ -168,6 +168,8 @@ impl<const N: usize> TapHoldProcessor<N> {
             return outputs;
         };
 
+        self.rebase_regressed(timestamp_us);
+
         // Check if already in pending registry (shouldn't happen, but handle gracefully)
         if self.pending.contains(key) {
             log_event!(
@@ -221,6 +223,8 @@ impl<const N: usize> TapHoldProcessor<N> {
     ) -> ArrayVec<TapHoldOutput, MAX_OUTPUT_EVENTS> {
         let mut outputs = ArrayVec::new();
 
+        self.rebase_regressed(timestamp_us);
+
         // Get the pending state
         let Some(state) = self.pending.get(key).copied() else {
             return outputs;
@@ -304,6 +308,8 @@ impl<const N: usize> TapHoldProcessor<N> {
     ) -> ArrayVec<TapHoldOutput, MAX_OUTPUT_EVENTS> {
         let mut outputs = ArrayVec::new();
 
+        self.rebase_regressed(current_time);
+
         // Use the registry's check_timeouts which transitions states internally
         let timeouts = self.pending.check_timeouts(current_time);
 
@@ -330,7 +336,9 @@ impl<const N: usize> TapHoldProcessor<N> {
     ///
     /// This implements "Permissive Hold" behavior: if you press CapsLock (tap-hold)
     /// and then press 'A' before the hold threshold, CapsLock immediately becomes
-    /// Ctrl so that 'A' is processed as Ctrl+A.
+    /// Ctrl so that 'A' is processed as Ctrl+A. Pending keys configured with
+    /// [`TapHoldPolicy::Timeout`](crate::config::TapHoldPolicy::Timeout) are
+    /// left pending.
     ///
     /// # Arguments
     ///
@@ -398,6 +406,22 @@ impl<const N: usize> TapHoldProcessor<N> {
         outputs
     }
 
+    /// Re-anchors pending keys when `timestamp_us` precedes their press time.
+    ///
+    /// Relative or 32-bit event clocks can wrap or restart. Without this, a
+    /// pending key would never reach its threshold until the clock caught up
+    /// with the stale press time.
+    fn rebase_regressed(&mut self, timestamp_us: u64) {
+        let rebased = self.pending.rebase_regressed(timestamp_us);
+        if rebased > 0 {
+            log_event!(
+                "tap-hold: clock went backwards to {}us, re-anchored {} pending key(s)",
+                timestamp_us,
+                rebased
+            );
+        }
+    }
+
     /// Checks if there are any keys in pending state.
     ///
     /// Useful for determining whether permissive hold logic should be invoked.
@@ -405,6 +429,15 @@ impl<const N: usize> TapHoldProcessor<N> {
         self.pending_count() > 0
     }
 
+    /// Returns the time (in microseconds) at which the next pending key
+    /// times out into a hold, or `None` if no key is pending.
+    ///
+    /// [`check_timeouts`](Self::check_timeouts) activates the hold once
+    /// called at or after this time.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.pending.next_deadline()
+    }
+
     /// Clears all pending states.
     ///
     /// This is useful for error recovery or when reloading configuration.
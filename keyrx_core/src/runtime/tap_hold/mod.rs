@@ -25,6 +25,10 @@
 //!                   Release
 //! ```
 //!
+//! The "other key pressed" transition only applies to the default
+//! [`TapHoldPolicy::Permissive`](crate::config::TapHoldPolicy::Permissive)
+//! policy; with `Timeout`, only the threshold moves a key from Pending to Hold.
+//!
 //! # Debug Logging
 //!
 //! This module includes trace-level logging for state transitions when compiled
@@ -151,6 +151,31 @@ impl TapHoldState {
         current_time.saturating_sub(self.press_time)
     }
 
+    /// Checks whether the given time precedes the recorded press time.
+    ///
+    /// This happens when the event source's clock is non-monotonic, e.g. a
+    /// 32-bit counter that wrapped or a relative clock that was re-based.
+    pub const fn is_clock_regressed(&self, current_time: u64) -> bool {
+        current_time < self.press_time
+    }
+
+    /// Re-anchors the press time of a Pending state to `timestamp`.
+    ///
+    /// Used after a backwards clock jump so the hold threshold is measured
+    /// from the new time base instead of stalling until the clock catches up.
+    ///
+    /// # Panics
+    ///
+    /// Debug asserts that current phase is Pending.
+    pub fn rebase(&mut self, timestamp: u64) {
+        debug_assert!(
+            self.phase.is_pending(),
+            "rebase called from non-Pending phase: {:?}",
+            self.phase
+        );
+        self.press_time = timestamp;
+    }
+
     // --- State Transitions ---
 
     /// Transitions from Idle to Pending on key press.
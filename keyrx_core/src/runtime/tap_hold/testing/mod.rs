@@ -9,7 +9,7 @@ pub(crate) use alloc::vec::Vec;
 
 // Re-export everything from parent module for tests
 pub(crate) use super::*;
-pub(crate) use crate::config::KeyCode;
+pub(crate) use crate::config::{KeyCode, TapHoldPolicy};
 
 // Shared test helper functions
 pub(crate) fn make_pending_state(
@@ -306,6 +306,23 @@ fn test_registry_pending_keys() {
     assert_eq!(pending[0].key(), KeyCode::Tab);
 }
 
+#[test]
+fn test_registry_next_deadline_is_earliest_pending_threshold() {
+    let mut registry: PendingKeyRegistry<8> = PendingKeyRegistry::new();
+    assert_eq!(registry.next_deadline(), None);
+
+    registry.add(make_pending_state(KeyCode::CapsLock, KeyCode::Escape, 0, 0));
+    registry.add(make_pending_state(KeyCode::Tab, KeyCode::Tab, 1, 100_000));
+    assert_eq!(registry.next_deadline(), Some(200_000));
+
+    // Once CapsLock is held, only Tab is still waiting
+    registry.check_timeouts(200_000);
+    assert_eq!(registry.next_deadline(), Some(300_000));
+
+    registry.check_timeouts(300_000);
+    assert_eq!(registry.next_deadline(), None);
+}
+
 #[test]
 fn test_registry_trigger_permissive_hold() {
     let mut registry: PendingKeyRegistry<8> = PendingKeyRegistry::new();
@@ -418,3 +435,39 @@ fn test_registry_with_different_capacities() {
     let large: PendingKeyRegistry<64> = PendingKeyRegistry::new();
     assert_eq!(large.capacity(), 64);
 }
+
+#[test]
+fn test_registry_rebase_regressed_only_touches_later_pending_states() {
+    let mut registry: PendingKeyRegistry<4> = PendingKeyRegistry::new();
+    registry.add(make_pending_state(
+        KeyCode::CapsLock,
+        KeyCode::Escape,
+        0,
+        1_000_000,
+    ));
+    registry.add(make_pending_state(KeyCode::Tab, KeyCode::Tab, 1, 100_000));
+    registry.add(make_pending_state(
+        KeyCode::Space,
+        KeyCode::Space,
+        2,
+        2_000_000,
+    ));
+    registry
+        .get_mut(KeyCode::Space)
+        .unwrap()
+        .transition_to_hold();
+
+    assert_eq!(registry.rebase_regressed(500_000), 1);
+    assert_eq!(
+        registry.get(KeyCode::CapsLock).unwrap().press_time(),
+        500_000
+    );
+    assert_eq!(registry.get(KeyCode::Tab).unwrap().press_time(), 100_000);
+    // Hold states no longer depend on the press time
+    assert_eq!(
+        registry.get(KeyCode::Space).unwrap().press_time(),
+        2_000_000
+    );
+
+    assert_eq!(registry.rebase_regressed(500_000), 0);
+}
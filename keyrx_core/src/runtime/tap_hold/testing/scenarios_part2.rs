@@ -550,3 +550,104 @@ fn test_state_machine_alternating_tap_hold() {
     assert!(!processor.is_pending(KeyCode::CapsLock));
     assert!(!processor.is_hold(KeyCode::CapsLock));
 }
+
+#[test]
+fn test_clock_regression_mid_press_still_reaches_hold() {
+    // A 32-bit or relative clock wraps while CapsLock is pending
+    let mut processor: TapHoldProcessor<8> = TapHoldProcessor::new();
+    let config = TapHoldConfig::from_ms(KeyCode::Escape, 0, 200);
+    processor.register_tap_hold(KeyCode::CapsLock, config);
+
+    let _ = processor.process_press(KeyCode::CapsLock, u64::from(u32::MAX) - 50_000);
+    assert!(processor.is_pending(KeyCode::CapsLock));
+
+    // Clock restarts near zero: no underflow, no premature hold
+    let outputs = processor.check_timeouts(10_000);
+    assert!(outputs.is_empty());
+    assert!(processor.is_pending(KeyCode::CapsLock));
+
+    // Threshold is measured from the new time base
+    let outputs = processor.check_timeouts(150_000);
+    assert!(outputs.is_empty());
+    let outputs = processor.check_timeouts(210_000);
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0], TapHoldOutput::activate_modifier(0));
+    assert!(processor.is_hold(KeyCode::CapsLock));
+
+    let outputs = processor.process_release(KeyCode::CapsLock, 250_000);
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0], TapHoldOutput::deactivate_modifier(0));
+    assert_eq!(processor.pending_count(), 0);
+}
+
+#[test]
+fn test_clock_regression_on_release_is_tap() {
+    let mut processor: TapHoldProcessor<8> = TapHoldProcessor::new();
+    let config = TapHoldConfig::from_ms(KeyCode::Escape, 0, 200);
+    processor.register_tap_hold(KeyCode::CapsLock, config);
+
+    let _ = processor.process_press(KeyCode::CapsLock, 5_000_000);
+
+    // Release carries an earlier timestamp than the press
+    let outputs = processor.process_release(KeyCode::CapsLock, 1_000);
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0], TapHoldOutput::key_press(KeyCode::Escape, 1_000));
+    assert_eq!(
+        outputs[1],
+        TapHoldOutput::key_release(KeyCode::Escape, 1_000)
+    );
+    assert_eq!(processor.pending_count(), 0);
+}
+
+#[test]
+fn test_clock_regression_on_second_press_rebases_first_key() {
+    let mut processor: TapHoldProcessor<8> = TapHoldProcessor::new();
+    processor.register_tap_hold(
+        KeyCode::CapsLock,
+        TapHoldConfig::from_ms(KeyCode::Escape, 0, 200),
+    );
+    processor.register_tap_hold(KeyCode::Tab, TapHoldConfig::from_ms(KeyCode::Tab, 1, 200));
+
+    let _ = processor.process_press(KeyCode::CapsLock, 9_000_000);
+    let _ = processor.process_press(KeyCode::Tab, 100_000);
+
+    // Both keys now share the new time base and time out together
+    let outputs = processor.check_timeouts(299_000);
+    assert!(outputs.is_empty());
+    let outputs = processor.check_timeouts(300_000);
+    assert_eq!(outputs.len(), 2);
+    assert!(processor.is_hold(KeyCode::CapsLock));
+    assert!(processor.is_hold(KeyCode::Tab));
+}
+
+#[test]
+fn test_policy_decides_interrupted_press() {
+    // Same stream for both policies: CapsLock down, other key down at 50ms,
+    // CapsLock up at 100ms
+    let run = |policy: TapHoldPolicy| {
+        let mut processor: TapHoldProcessor<8> = TapHoldProcessor::new();
+        let config = TapHoldConfig::from_ms(KeyCode::Escape, 0, 200).with_policy(policy);
+        processor.register_tap_hold(KeyCode::CapsLock, config);
+
+        let mut outputs: Vec<TapHoldOutput> = Vec::new();
+        outputs.extend(processor.process_press(KeyCode::CapsLock, 0));
+        outputs.extend(processor.process_other_key_press(KeyCode::A));
+        outputs.extend(processor.process_release(KeyCode::CapsLock, 100_000));
+        outputs
+    };
+
+    assert_eq!(
+        run(TapHoldPolicy::Permissive),
+        vec![
+            TapHoldOutput::activate_modifier(0),
+            TapHoldOutput::deactivate_modifier(0),
+        ]
+    );
+    assert_eq!(
+        run(TapHoldPolicy::Timeout),
+        vec![
+            TapHoldOutput::key_press(KeyCode::Escape, 100_000),
+            TapHoldOutput::key_release(KeyCode::Escape, 100_000),
+        ]
+    );
+}
@@ -112,6 +112,21 @@ fn test_elapsed_saturates_on_underflow() {
     assert_eq!(state.elapsed(500_000), 0);
 }
 
+#[test]
+fn test_rebase_after_clock_regression() {
+    let config = TapHoldConfig::new(KeyCode::Escape, 0, 200_000);
+    let mut state = TapHoldState::new(KeyCode::CapsLock, config);
+
+    state.transition_to_pending(1_000_000);
+    assert!(state.is_clock_regressed(500_000));
+    assert!(!state.is_clock_regressed(1_000_000));
+
+    state.rebase(500_000);
+    assert_eq!(state.press_time(), 500_000);
+    assert!(!state.is_threshold_exceeded(600_000));
+    assert!(state.is_threshold_exceeded(700_000));
+}
+
 #[test]
 fn test_tap_scenario() {
     let config = TapHoldConfig::from_ms(KeyCode::Escape, 0, 200);
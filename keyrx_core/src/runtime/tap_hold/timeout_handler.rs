@@ -4,7 +4,7 @@
 //! checking for timeouts when the hold threshold is exceeded.
 
 use super::state_machine::TapHoldState;
-use crate::config::KeyCode;
+use crate::config::{KeyCode, TapHoldPolicy};
 use arrayvec::ArrayVec;
 
 /// Default maximum concurrent tap-hold keys.
@@ -232,6 +232,28 @@ impl<const N: usize> PendingKeyRegistry<N> {
         results
     }
 
+    /// Re-anchors Pending states whose press time lies after `current_time`.
+    ///
+    /// A timestamp earlier than a recorded press means the clock went
+    /// backwards. Such states restart their hold threshold from
+    /// `current_time` rather than waiting for the old time base to return.
+    ///
+    /// # Returns
+    ///
+    /// The number of states that were re-anchored.
+    pub fn rebase_regressed(&mut self, current_time: u64) -> usize {
+        let mut rebased = 0;
+
+        for state in self.entries.iter_mut() {
+            if state.phase().is_pending() && state.is_clock_regressed(current_time) {
+                state.rebase(current_time);
+                rebased += 1;
+            }
+        }
+
+        rebased
+    }
+
     /// Finds all keys in Pending state.
     ///
     /// Useful for checking if any keys might need permissive hold activation.
@@ -239,10 +261,23 @@ impl<const N: usize> PendingKeyRegistry<N> {
         self.entries.iter().filter(|s| s.phase().is_pending())
     }
 
+    /// Returns the earliest time at which a pending key reaches its hold
+    /// threshold, or `None` if no key is pending.
+    ///
+    /// Lets callers wait exactly until the next timeout instead of polling
+    /// [`check_timeouts`](Self::check_timeouts).
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.pending_keys()
+            .map(|s| s.press_time().saturating_add(s.threshold_us()))
+            .min()
+    }
+
     /// Triggers permissive hold for all pending keys.
     ///
     /// When another key is pressed while tap-hold keys are pending,
     /// this method transitions all pending keys to Hold state immediately.
+    /// Keys with the [`Timeout`](TapHoldPolicy::Timeout) policy stay pending
+    /// and are decided by their threshold alone.
     ///
     /// # Returns
     ///
@@ -251,7 +286,7 @@ impl<const N: usize> PendingKeyRegistry<N> {
         let mut results = ArrayVec::new();
 
         for state in self.entries.iter_mut() {
-            if state.phase().is_pending() {
+            if state.phase().is_pending() && state.config().policy() == TapHoldPolicy::Permissive {
                 state.transition_to_hold();
 
                 let _ = results.try_push(TimeoutResult {
@@ -5,7 +5,7 @@
 //! - [`TapHoldConfig`] - Configuration for tap and hold behavior
 //! - [`TapHoldOutput`] - Output events produced by the state machine
 
-use crate::config::KeyCode;
+use crate::config::{KeyCode, TapHoldPolicy};
 
 /// Phase of the tap-hold state machine.
 ///
@@ -65,6 +65,7 @@ impl core::fmt::Display for TapHoldPhase {
 /// - What key to emit on tap
 /// - What modifier to activate on hold
 /// - Threshold time in microseconds
+/// - Whether other keys can decide a pending key ([`TapHoldPolicy`])
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TapHoldConfig {
     /// Key to emit when tapped (quick press and release)
@@ -73,10 +74,13 @@ pub struct TapHoldConfig {
     hold_modifier: u8,
     /// Threshold time in microseconds (tap vs hold boundary)
     threshold_us: u64,
+    /// Decision policy while pending
+    policy: TapHoldPolicy,
 }
 
 impl TapHoldConfig {
-    /// Creates a new tap-hold configuration.
+    /// Creates a new tap-hold configuration with the
+    /// [`Permissive`](TapHoldPolicy::Permissive) policy.
     ///
     /// # Arguments
     ///
@@ -102,6 +106,7 @@ impl TapHoldConfig {
             tap_key,
             hold_modifier,
             threshold_us,
+            policy: TapHoldPolicy::Permissive,
         }
     }
 
@@ -122,6 +127,23 @@ impl TapHoldConfig {
         Self::new(tap_key, hold_modifier, threshold_ms as u64 * 1000)
     }
 
+    /// Returns the config with the given decision policy.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use keyrx_core::runtime::tap_hold::TapHoldConfig;
+    /// use keyrx_core::config::{KeyCode, TapHoldPolicy};
+    ///
+    /// let config = TapHoldConfig::from_ms(KeyCode::Escape, 0, 200)
+    ///     .with_policy(TapHoldPolicy::Timeout);
+    /// assert_eq!(config.policy(), TapHoldPolicy::Timeout);
+    /// ```
+    pub const fn with_policy(mut self, policy: TapHoldPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
     /// Returns the tap key.
     pub const fn tap_key(&self) -> KeyCode {
         self.tap_key
@@ -136,6 +158,11 @@ impl TapHoldConfig {
     pub const fn threshold_us(&self) -> u64 {
         self.threshold_us
     }
+
+    /// Returns the decision policy.
+    pub const fn policy(&self) -> TapHoldPolicy {
+        self.policy
+    }
 }
 
 /// Output event from the tap-hold state machine.
@@ -1,4 +1,973 @@
 //! Keyboard simulation module
 //!
 //! This module provides the simulation engine for testing keyboard remapping
-//! configurations in a browser environment via WASM.
+//! configurations without hardware. The types here are shared by the browser
+//! (WASM) simulator and the daemon's `simulate` CLI so that both produce the
+//! identical JSON timeline shape.
+//!
+//! Latency is measured through the [`Clock`] trait. The simulators pass a
+//! [`ModeledClock`] seeded from the [`EventSequence`], so a run is fully
+//! determined by (config, events, seed) and its result can be reproduced from
+//! a bug report. The seed is echoed in [`SimulationResult::seed`].
+
+use alloc::{format, string::String, string::ToString, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use serde::de::{value::Error as ValueError, IntoDeserializer};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BaseKeyMapping, Condition, DeviceConfig, KeyCode, KeyMapping};
+use crate::runtime::event::process_event_into;
+use crate::runtime::{
+    Clock, DeviceState, KeyEvent, KeyEventType, KeyLookup, MappingPosition, VirtualClock,
+};
+
+/// Input event sequence for simulation.
+///
+/// This structure defines a sequence of keyboard events to simulate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSequence {
+    /// List of events to simulate
+    pub events: Vec<SimKeyEvent>,
+    /// Seed of the latency model, echoed in the result
+    #[serde(default)]
+    pub seed: u64,
+}
+
+/// A single keyboard event for simulation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimKeyEvent {
+    /// Key code (e.g., "A", "B", "LShift")
+    pub keycode: String,
+    /// Event type: "press", "release", or "repeat"
+    pub event_type: String,
+    /// Timestamp in microseconds
+    pub timestamp_us: u64,
+    /// Source device (e.g. "serial-ABC123"), matched by `when_device_start()`
+    /// patterns. Omitted for single-device sequences.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+/// Result of a simulation run.
+///
+/// Contains the full timeline of events, state changes, and performance metrics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationResult {
+    /// Seed the run used; replaying the same config and events with it
+    /// reproduces this result exactly
+    #[serde(default)]
+    pub seed: u64,
+    /// Timeline of all events (input and output)
+    pub timeline: Vec<TimelineEntry>,
+    /// Latency statistics in microseconds
+    pub latency_stats: LatencyStats,
+    /// Final state after simulation
+    pub final_state: SimulationState,
+}
+
+/// Entry in the simulation timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Timestamp in microseconds
+    pub timestamp_us: u64,
+    /// Input event (if this was an input)
+    pub input: Option<SimKeyEvent>,
+    /// Output events generated from this input
+    pub outputs: Vec<SimKeyEvent>,
+    /// State snapshot after processing this event
+    pub state: SimulationState,
+    /// Processing latency for this event in microseconds
+    pub latency_us: u64,
+    /// Mapping that handled the input, if a lookup matched one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<MappingPosition>,
+}
+
+/// State snapshot during simulation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulationState {
+    /// Active modifiers (list of modifier IDs)
+    pub active_modifiers: Vec<u8>,
+    /// Active locks (list of lock IDs)
+    pub active_locks: Vec<u8>,
+    /// Current active layer (if any)
+    pub active_layer: Option<String>,
+}
+
+/// Latency statistics for the simulation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Minimum latency in microseconds
+    pub min_us: u64,
+    /// Average latency in microseconds
+    pub avg_us: u64,
+    /// Maximum latency in microseconds
+    pub max_us: u64,
+    /// 95th percentile latency in microseconds
+    pub p95_us: u64,
+    /// 99th percentile latency in microseconds
+    pub p99_us: u64,
+}
+
+/// Run simulation on event sequence.
+///
+/// This is the core simulation logic that processes events and tracks metrics.
+/// `clock` is sampled before and after each event to compute its latency.
+///
+/// Event timestamps double as the time of day for `time_windows`: a
+/// [`VirtualClock`] set to each timestamp (timestamp 0 = midnight) supplies
+/// the minute of day, so time-window conditions replay deterministically.
+pub fn run_simulation<C: Clock>(
+    lookup: &KeyLookup,
+    device: &DeviceConfig,
+    event_sequence: &EventSequence,
+    clock: &C,
+) -> Result<SimulationResult, String> {
+    // Start from the device's boot state (time windows, default layer)
+    let mut state = DeviceState::for_device(device);
+    let mut timeline = Vec::new();
+    let mut latencies = Vec::new();
+
+    for sim_event in &event_sequence.events {
+        let entry = step_simulation(sim_event, lookup, &mut state, clock)?;
+        latencies.push(entry.latency_us);
+        timeline.push(entry);
+    }
+
+    Ok(SimulationResult {
+        seed: event_sequence.seed,
+        timeline,
+        latency_stats: calculate_latency_stats(&latencies),
+        final_state: capture_state(&state),
+    })
+}
+
+/// Process one simulated event against `state` and return its timeline entry.
+///
+/// The event timestamp supplies the minute of day, as in [`run_simulation`].
+/// Interactive callers use this to step through events
+/// one at a time, pairing it with [`DeviceState::snapshot`] to step back.
+pub fn step_simulation<C: Clock>(
+    sim_event: &SimKeyEvent,
+    lookup: &KeyLookup,
+    state: &mut DeviceState,
+    clock: &C,
+) -> Result<TimelineEntry, String> {
+    // Convert SimKeyEvent to KeyEvent
+    let keycode = parse_keycode(&sim_event.keycode)?;
+    let key_event = match sim_event.event_type.as_str() {
+        "press" => KeyEvent::press(keycode),
+        "release" => KeyEvent::release(keycode),
+        "repeat" => KeyEvent::repeat(keycode),
+        _ => return Err(format!("Invalid event type: {}", sim_event.event_type)),
+    }
+    .with_timestamp(sim_event.timestamp_us);
+    let key_event = match &sim_event.device_id {
+        Some(device_id) => key_event.with_device_id(device_id.clone()),
+        None => key_event,
+    };
+
+    let day_clock = VirtualClock::new();
+    day_clock.set(sim_event.timestamp_us);
+    state.set_minute_of_day(day_clock.minute_of_day());
+
+    // Measure processing latency
+    let start = clock.now();
+    let mut matched = None;
+    let output_events = process_event_into(key_event, lookup, state, Some(&mut matched));
+    let latency_us = clock.now().saturating_sub(start);
+
+    // Convert output events to SimKeyEvent. Macro steps after a wait are
+    // listed with the event that played them, stamped with their due time.
+    let outputs: Vec<SimKeyEvent> = output_events
+        .iter()
+        .chain(&state.take_scheduled_outputs())
+        .map(to_sim_event)
+        .collect();
+
+    Ok(TimelineEntry {
+        timestamp_us: sim_event.timestamp_us,
+        input: Some(sim_event.clone()),
+        outputs,
+        state: capture_state(state),
+        latency_us,
+        mapping: matched.map(|m| m.position),
+    })
+}
+
+/// Shortest processing latency the [`ModeledClock`] reports, in microseconds
+pub const MODELED_LATENCY_BASE_US: u64 = 20;
+
+/// Range of the seeded jitter added to [`MODELED_LATENCY_BASE_US`]
+pub const MODELED_LATENCY_JITTER_US: u64 = 30;
+
+/// Deterministic stand-in for a wall clock when measuring simulated latency.
+///
+/// Every call to [`now`](Clock::now) advances time by
+/// [`MODELED_LATENCY_BASE_US`] plus a jitter below
+/// [`MODELED_LATENCY_JITTER_US`] drawn from `seed`, so each simulated event
+/// gets a plausible latency that is identical on every run with the same
+/// seed, on any machine.
+#[derive(Debug)]
+pub struct ModeledClock {
+    seed: u64,
+    samples: AtomicU64,
+    time: AtomicU64,
+}
+
+impl ModeledClock {
+    /// Creates a clock at time 0 whose jitter is drawn from `seed`.
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            samples: AtomicU64::new(0),
+            time: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Clock for ModeledClock {
+    fn now(&self) -> u64 {
+        let sample = self.samples.fetch_add(1, Ordering::Relaxed);
+        let step =
+            MODELED_LATENCY_BASE_US + splitmix64(self.seed ^ sample) % MODELED_LATENCY_JITTER_US;
+        self.time.fetch_add(step, Ordering::Relaxed) + step
+    }
+}
+
+/// SplitMix64 mixing function, a small deterministic hash for jitter.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Which mappings and conditional blocks of a device a simulation exercised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    /// Every mapping in configuration order
+    pub mappings: Vec<MappingCoverage>,
+    /// Every conditional block in configuration order
+    pub conditions: Vec<ConditionCoverage>,
+}
+
+/// Number of events handled by one mapping.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MappingCoverage {
+    /// Position in `DeviceConfig::mappings`
+    pub position: MappingPosition,
+    /// The mapping itself
+    pub mapping: BaseKeyMapping,
+    /// Condition of the enclosing block, `None` for base mappings
+    pub condition: Option<Condition>,
+    /// Events the mapping handled
+    pub hits: usize,
+}
+
+/// Number of events handled by the mappings of one conditional block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConditionCoverage {
+    /// Index of the block in `DeviceConfig::mappings`
+    pub index: usize,
+    /// The block's condition
+    pub condition: Condition,
+    /// Events handled by any mapping in the block
+    pub hits: usize,
+}
+
+impl CoverageReport {
+    /// Mappings that handled no event.
+    pub fn unexercised_mappings(&self) -> impl Iterator<Item = &MappingCoverage> {
+        self.mappings.iter().filter(|mapping| mapping.hits == 0)
+    }
+
+    /// Conditional blocks none of whose mappings handled an event.
+    pub fn unexercised_conditions(&self) -> impl Iterator<Item = &ConditionCoverage> {
+        self.conditions
+            .iter()
+            .filter(|condition| condition.hits == 0)
+    }
+}
+
+/// Matches a simulation timeline against `config` to report which mappings
+/// handled at least one event.
+///
+/// Only events resolved by a lookup count: releases and repeats that follow
+/// a tracked press are attributed to the press.
+pub fn mapping_coverage(config: &DeviceConfig, timeline: &[TimelineEntry]) -> CoverageReport {
+    let hits = |position: MappingPosition| {
+        timeline
+            .iter()
+            .filter(|entry| entry.mapping == Some(position))
+            .count()
+    };
+
+    let mut mappings = Vec::new();
+    let mut conditions = Vec::new();
+    for (index, mapping) in config.mappings.iter().enumerate() {
+        match mapping {
+            KeyMapping::Base(base) => {
+                let position = MappingPosition { index, inner: None };
+                mappings.push(MappingCoverage {
+                    position,
+                    mapping: base.clone(),
+                    condition: None,
+                    hits: hits(position),
+                });
+            }
+            KeyMapping::Conditional {
+                condition,
+                mappings: block,
+            } => {
+                let mut block_hits = 0;
+                for (inner, base) in block.iter().enumerate() {
+                    let position = MappingPosition {
+                        index,
+                        inner: Some(inner),
+                    };
+                    let mapping_hits = hits(position);
+                    block_hits += mapping_hits;
+                    mappings.push(MappingCoverage {
+                        position,
+                        mapping: base.clone(),
+                        condition: Some(condition.clone()),
+                        hits: mapping_hits,
+                    });
+                }
+                conditions.push(ConditionCoverage {
+                    index,
+                    condition: condition.clone(),
+                    hits: block_hits,
+                });
+            }
+        }
+    }
+
+    CoverageReport {
+        mappings,
+        conditions,
+    }
+}
+
+/// Parse keycode string to KeyCode enum.
+///
+/// Accepts the `KeyCode` variant names (e.g. "A", "LShift", "CapsLock"),
+/// which is also the format used for output events.
+pub fn parse_keycode(keycode_str: &str) -> Result<KeyCode, String> {
+    let deserializer: serde::de::value::StrDeserializer<'_, ValueError> =
+        keycode_str.into_deserializer();
+    KeyCode::deserialize(deserializer).map_err(|_| format!("Unsupported keycode: {}", keycode_str))
+}
+
+/// Convert a runtime event into its simulation representation.
+fn to_sim_event(event: &KeyEvent) -> SimKeyEvent {
+    SimKeyEvent {
+        keycode: format!("{:?}", event.keycode()),
+        event_type: match event.event_type() {
+            KeyEventType::Press => "press".to_string(),
+            KeyEventType::Release => "release".to_string(),
+            KeyEventType::Repeat => "repeat".to_string(),
+        },
+        timestamp_us: event.timestamp_us(),
+        device_id: event.device_id().map(String::from),
+    }
+}
+
+/// Capture current simulation state.
+pub fn capture_state(state: &DeviceState) -> SimulationState {
+    // Extract active modifiers and locks (IDs 0-254)
+    let active_modifiers = (0..255)
+        .filter(|&id| state.is_modifier_active(id))
+        .collect();
+    let active_locks = (0..255).filter(|&id| state.is_lock_active(id)).collect();
+
+    // TODO: Extract active layer from state once layer support is added
+    let active_layer = None;
+
+    SimulationState {
+        active_modifiers,
+        active_locks,
+        active_layer,
+    }
+}
+
+/// Calculate latency statistics from recorded latencies.
+pub fn calculate_latency_stats(latencies: &[u64]) -> LatencyStats {
+    if latencies.is_empty() {
+        return LatencyStats {
+            min_us: 0,
+            avg_us: 0,
+            max_us: 0,
+            p95_us: 0,
+            p99_us: 0,
+        };
+    }
+
+    let mut sorted = latencies.to_vec();
+    sorted.sort_unstable();
+
+    let min_us = sorted[0];
+    let max_us = sorted[sorted.len() - 1];
+    let avg_us = sorted.iter().sum::<u64>() / sorted.len() as u64;
+
+    let p95_idx = ((sorted.len() as f64) * 0.95) as usize;
+    let p99_idx = ((sorted.len() as f64) * 0.99) as usize;
+
+    let p95_us = sorted[p95_idx.min(sorted.len() - 1)];
+    let p99_us = sorted[p99_idx.min(sorted.len() - 1)];
+
+    LatencyStats {
+        min_us,
+        avg_us,
+        max_us,
+        p95_us,
+        p99_us,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeviceIdentifier;
+    use crate::config::TimeWindow;
+    use alloc::vec;
+
+    fn device_with(mappings: Vec<KeyMapping>) -> DeviceConfig {
+        DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: String::from("*"),
+                excludes: Vec::new(),
+            },
+            mappings,
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        }
+    }
+
+    fn lookup_with(mappings: Vec<KeyMapping>) -> KeyLookup {
+        KeyLookup::from_device_config(&device_with(mappings))
+    }
+
+    fn sim_event(keycode: &str, event_type: &str, timestamp_us: u64) -> SimKeyEvent {
+        SimKeyEvent {
+            keycode: keycode.to_string(),
+            event_type: event_type.to_string(),
+            timestamp_us,
+            device_id: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_keycode_accepts_variant_names() {
+        assert_eq!(parse_keycode("A"), Ok(KeyCode::A));
+        assert_eq!(parse_keycode("LShift"), Ok(KeyCode::LShift));
+        assert_eq!(parse_keycode("CapsLock"), Ok(KeyCode::CapsLock));
+        assert!(parse_keycode("NotAKey").is_err());
+    }
+
+    #[test]
+    fn test_run_simulation_applies_device_id_to_conditions() {
+        let lookup = lookup_with(vec![KeyMapping::conditional(
+            Condition::DeviceMatches(String::from("*numpad*")),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::A,
+                to: KeyCode::B,
+            }],
+        )]);
+        let from_numpad = SimKeyEvent {
+            device_id: Some(String::from("usb-numpad-1")),
+            ..sim_event("A", "press", 0)
+        };
+        let sequence = EventSequence {
+            events: vec![from_numpad, sim_event("A", "press", 1_000)],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.timeline[0].outputs[0].keycode, "B");
+        assert_eq!(
+            result.timeline[0]
+                .input
+                .as_ref()
+                .unwrap()
+                .device_id
+                .as_deref(),
+            Some("usb-numpad-1")
+        );
+        // Untagged events come from the default device, which no pattern matches
+        assert_eq!(result.timeline[1].outputs[0].keycode, "A");
+    }
+
+    #[test]
+    fn test_run_simulation_maps_same_key_per_device() {
+        let when_device = |pattern: &str, to| {
+            KeyMapping::conditional(
+                Condition::DeviceMatches(String::from(pattern)),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::A,
+                    to,
+                }],
+            )
+        };
+        let lookup = lookup_with(vec![
+            when_device("*numpad*", KeyCode::B),
+            when_device("usb-*", KeyCode::C),
+        ]);
+        let from = |device_id: &str, event_type, timestamp_us| SimKeyEvent {
+            device_id: Some(String::from(device_id)),
+            ..sim_event("A", event_type, timestamp_us)
+        };
+        let sequence = EventSequence {
+            events: vec![
+                from("bt-numpad", "press", 0),
+                from("bt-numpad", "release", 1_000),
+                from("usb-keyboard", "press", 2_000),
+                from("usb-keyboard", "release", 3_000),
+                // Both patterns match; the first block wins
+                from("usb-numpad", "press", 4_000),
+                from("bt-keyboard", "press", 5_000),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        let outputs: Vec<(&str, &str)> = result
+            .timeline
+            .iter()
+            .map(|entry| {
+                let output = &entry.outputs[0];
+                (output.keycode.as_str(), output.event_type.as_str())
+            })
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                ("B", "press"),
+                ("B", "release"),
+                ("C", "press"),
+                ("C", "release"),
+                ("B", "press"),
+                ("A", "press"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_builds_timeline() {
+        let lookup = lookup_with(vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+            KeyMapping::modifier(KeyCode::CapsLock, 0x01),
+        ]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("CapsLock", "press", 0),
+                sim_event("A", "press", 1_000),
+                sim_event("A", "release", 2_000),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        assert_eq!(result.timeline.len(), 3);
+        assert!(result.timeline[0].outputs.is_empty());
+        assert_eq!(result.timeline[0].state.active_modifiers, vec![0x01]);
+        assert_eq!(
+            result.timeline[1].outputs,
+            vec![sim_event("B", "press", 1_000)]
+        );
+        assert_eq!(result.timeline[2].timestamp_us, 2_000);
+        assert_eq!(result.final_state.active_modifiers, vec![0x01]);
+        assert_eq!(result.latency_stats.max_us, 0);
+    }
+
+    #[test]
+    fn test_run_simulation_repeat_follows_mapping() {
+        let lookup = lookup_with(vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+            KeyMapping::modifier(KeyCode::CapsLock, 0x01),
+        ]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("A", "press", 0),
+                sim_event("A", "repeat", 500_000),
+                sim_event("CapsLock", "press", 600_000),
+                sim_event("CapsLock", "repeat", 1_100_000),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.timeline[1].outputs,
+            vec![sim_event("B", "repeat", 500_000)]
+        );
+        assert!(result.timeline[3].outputs.is_empty());
+        assert_eq!(result.final_state.active_modifiers, vec![0x01]);
+    }
+
+    #[test]
+    fn test_run_simulation_on_release_tap_emits_on_release() {
+        let lookup = lookup_with(vec![KeyMapping::on_release(KeyCode::A, KeyCode::B, 200)]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("A", "press", 0),
+                sim_event("A", "release", 150_000),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        assert!(result.timeline[0].outputs.is_empty());
+        assert_eq!(
+            result.timeline[1].outputs,
+            vec![
+                sim_event("B", "press", 150_000),
+                sim_event("B", "release", 150_000)
+            ]
+        );
+    }
+
+    /// Taps F14 at each timestamp and returns the key pressed by each tap.
+    fn cycle_taps(tap_times_us: &[u64]) -> Vec<String> {
+        let lookup = lookup_with(vec![KeyMapping::cycle(
+            KeyCode::F14,
+            vec![KeyCode::A, KeyCode::B, KeyCode::C],
+            500,
+        )]);
+        let events = tap_times_us
+            .iter()
+            .flat_map(|&t| {
+                [
+                    sim_event("F14", "press", t),
+                    sim_event("F14", "release", t + 20_000),
+                ]
+            })
+            .collect();
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &EventSequence { events, seed: 0 },
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        result
+            .timeline
+            .chunks(2)
+            .map(|tap| {
+                // Every tap presses and releases the same key
+                assert_eq!(tap[0].outputs.len(), 1);
+                assert_eq!(tap[1].outputs.len(), 1);
+                assert_eq!(tap[0].outputs[0].keycode, tap[1].outputs[0].keycode);
+                tap[0].outputs[0].keycode.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_run_simulation_cycle_wraps_around() {
+        assert_eq!(
+            cycle_taps(&[0, 100_000, 200_000, 300_000, 400_000]),
+            vec!["A", "B", "C", "A", "B"]
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_cycle_resets_after_inactivity() {
+        // 500ms after the previous tap (not the first) starts over at A
+        assert_eq!(
+            cycle_taps(&[0, 400_000, 800_000, 1_300_000, 1_400_000]),
+            vec!["A", "B", "C", "A", "B"]
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_on_release_hold_emits_nothing() {
+        let lookup = lookup_with(vec![KeyMapping::on_release(KeyCode::A, KeyCode::B, 200)]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("A", "press", 0),
+                sim_event("A", "release", 200_000),
+                // A later tap still works: the held release cleared the pending press
+                sim_event("A", "press", 300_000),
+                sim_event("A", "release", 350_000),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+
+        assert!(result.timeline[0].outputs.is_empty());
+        assert!(result.timeline[1].outputs.is_empty());
+        assert!(result.timeline[2].outputs.is_empty());
+        assert_eq!(result.timeline[3].outputs.len(), 2);
+    }
+
+    #[test]
+    fn test_run_simulation_time_window_follows_timestamps() {
+        let mut device = device_with(vec![KeyMapping::conditional(
+            Condition::TimeWindowActive(0),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::A,
+                to: KeyCode::B,
+            }],
+        )]);
+        device.time_windows = vec![TimeWindow {
+            start_minute: 18 * 60,
+            end_minute: 23 * 60,
+        }];
+        let lookup = KeyLookup::from_device_config(&device);
+        let at_20h = 20 * 60 * 60_000_000;
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("A", "press", 1_000),
+                sim_event("A", "release", 2_000),
+                sim_event("A", "press", at_20h),
+            ],
+            seed: 0,
+        };
+
+        let result = run_simulation(&lookup, &device, &sequence, &VirtualClock::new()).unwrap();
+
+        assert_eq!(
+            result.timeline[0].outputs,
+            vec![sim_event("A", "press", 1_000)]
+        );
+        assert_eq!(
+            result.timeline[2].outputs,
+            vec![sim_event("B", "press", at_20h)]
+        );
+    }
+
+    #[test]
+    fn test_run_simulation_starts_on_default_layer() {
+        let mut device = device_with(vec![KeyMapping::conditional(
+            Condition::ModifierActive(0x01),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::A,
+                to: KeyCode::B,
+            }],
+        )]);
+        device.default_layer = Some(0x01);
+        let lookup = KeyLookup::from_device_config(&device);
+        let sequence = EventSequence {
+            events: vec![sim_event("A", "press", 0)],
+            seed: 0,
+        };
+
+        let result = run_simulation(&lookup, &device, &sequence, &VirtualClock::new()).unwrap();
+
+        assert_eq!(result.timeline[0].outputs, vec![sim_event("B", "press", 0)]);
+    }
+
+    #[test]
+    fn test_mapping_coverage_reports_unexercised_mappings() {
+        let config = device_with(vec![
+            KeyMapping::modifier(KeyCode::CapsLock, 0x00),
+            KeyMapping::conditional(
+                Condition::ModifierActive(0x00),
+                vec![
+                    BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    },
+                    BaseKeyMapping::Simple {
+                        from: KeyCode::L,
+                        to: KeyCode::Right,
+                    },
+                ],
+            ),
+            KeyMapping::conditional(
+                Condition::LockActive(0x01),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::J,
+                    to: KeyCode::Down,
+                }],
+            ),
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+        ]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("CapsLock", "press", 0),
+                sim_event("H", "press", 1_000),
+                sim_event("H", "repeat", 2_000),
+                sim_event("H", "release", 3_000),
+                sim_event("CapsLock", "release", 4_000),
+                sim_event("H", "press", 5_000),
+            ],
+            seed: 0,
+        };
+
+        let lookup = KeyLookup::from_device_config(&config);
+        let result = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.timeline[1].mapping,
+            Some(MappingPosition {
+                index: 1,
+                inner: Some(0)
+            })
+        );
+        // Repeats, tracked releases and unmapped keys name no mapping
+        assert_eq!(result.timeline[2].mapping, None);
+        assert_eq!(result.timeline[3].mapping, None);
+        assert_eq!(result.timeline[5].mapping, None);
+
+        let report = mapping_coverage(&config, &result.timeline);
+        let hits: Vec<usize> = report.mappings.iter().map(|m| m.hits).collect();
+        assert_eq!(hits, vec![2, 1, 0, 0, 0]);
+
+        let unexercised: Vec<MappingPosition> =
+            report.unexercised_mappings().map(|m| m.position).collect();
+        assert_eq!(
+            unexercised,
+            vec![
+                MappingPosition {
+                    index: 1,
+                    inner: Some(1)
+                },
+                MappingPosition {
+                    index: 2,
+                    inner: Some(0)
+                },
+                MappingPosition {
+                    index: 3,
+                    inner: None
+                },
+            ]
+        );
+        let unexercised_blocks: Vec<usize> =
+            report.unexercised_conditions().map(|c| c.index).collect();
+        assert_eq!(unexercised_blocks, vec![2]);
+    }
+
+    #[test]
+    fn test_run_simulation_rejects_invalid_event_type() {
+        let lookup = lookup_with(vec![]);
+        let sequence = EventSequence {
+            events: vec![sim_event("A", "tap", 0)],
+            seed: 0,
+        };
+
+        let err = run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &VirtualClock::new(),
+        )
+        .unwrap_err();
+        assert_eq!(err, "Invalid event type: tap");
+    }
+
+    fn seeded_run(seed: u64) -> SimulationResult {
+        let lookup = lookup_with(vec![
+            KeyMapping::simple(KeyCode::A, KeyCode::B),
+            KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0x00, 200),
+        ]);
+        let sequence = EventSequence {
+            events: vec![
+                sim_event("Space", "press", 0),
+                sim_event("A", "press", 50_000),
+                sim_event("A", "release", 80_000),
+                sim_event("Space", "release", 300_000),
+            ],
+            seed,
+        };
+        run_simulation(
+            &lookup,
+            &device_with(Vec::new()),
+            &sequence,
+            &ModeledClock::new(seed),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run_simulation_is_deterministic_for_seed() {
+        let first = seeded_run(42);
+        let second = seeded_run(42);
+
+        // Timeline, latency model numbers and final state all match
+        assert_eq!(first, second);
+        assert_eq!(first.seed, 42);
+    }
+
+    #[test]
+    fn test_modeled_clock_latency_within_model_bounds() {
+        let result = seeded_run(42);
+
+        for entry in &result.timeline {
+            assert!(entry.latency_us >= MODELED_LATENCY_BASE_US);
+            assert!(entry.latency_us < MODELED_LATENCY_BASE_US + MODELED_LATENCY_JITTER_US);
+        }
+        let latencies: Vec<u64> = result.timeline.iter().map(|e| e.latency_us).collect();
+        let other: Vec<u64> = seeded_run(7)
+            .timeline
+            .iter()
+            .map(|e| e.latency_us)
+            .collect();
+        assert_ne!(latencies, other, "Seed should drive the latency jitter");
+    }
+
+    #[test]
+    fn test_calculate_latency_stats() {
+        let stats = calculate_latency_stats(&[5, 1, 3, 2, 4]);
+        assert_eq!(stats.min_us, 1);
+        assert_eq!(stats.max_us, 5);
+        assert_eq!(stats.avg_us, 3);
+        assert_eq!(stats.p99_us, 5);
+
+        assert_eq!(calculate_latency_stats(&[]).max_us, 0);
+    }
+}
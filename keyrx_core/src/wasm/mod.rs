@@ -6,6 +6,7 @@
 //! # Features
 //! - Load Rhai configurations from source text
 //! - Load pre-compiled .krx binary configurations
+//! - Load configurations compiled to JSON (`--target wasm-json`)
 //! - Simulate keyboard event sequences
 //! - Query simulation state
 //!
@@ -27,7 +28,8 @@ use serde::Serialize;
 use std::{format, string::String, sync::Mutex, vec, vec::Vec};
 use wasm_bindgen::prelude::*;
 
-use crate::config::ConfigRoot;
+use crate::config::{ConfigRoot, Version};
+use crate::parser::{Parser, ParserLimits};
 use crate::runtime::KeyLookup;
 
 // Re-export simulation types
@@ -39,16 +41,23 @@ pub use simulation::{
 // Global Configuration Storage
 // ============================================================================
 
-use crate::runtime::DeviceState;
+use crate::runtime::{DeviceState, StateSnapshot};
 
 /// Configuration entry with associated state
 struct ConfigEntry {
     config: ConfigRoot,
-    /// Device state (reserved for future use)
-    _state: DeviceState,
+    /// Device state for step-through simulation
+    state: DeviceState,
+    /// State before each step, newest last, for `simulate_step_back`
+    snapshots: Vec<StateSnapshot>,
     last_sim_state: Option<SimulationState>,
 }
 
+/// Maximum number of steps `simulate_step_back` can undo
+///
+/// The oldest snapshot is dropped once exceeded.
+const MAX_STEP_SNAPSHOTS: usize = 1000;
+
 /// Global storage for loaded configurations.
 ///
 /// Configurations are stored in a Vec and referenced by their index (ConfigHandle).
@@ -128,14 +137,25 @@ fn recover_mutex_lock<'a, T>(
 fn store_config(config: ConfigRoot) -> Result<ConfigHandle, JsValue> {
     let mut store = recover_mutex_lock(&CONFIG_STORE, "store_config")?;
     let index = store.len();
+    let state = initial_step_state(&config);
     store.push(ConfigEntry {
         config,
-        _state: DeviceState::new(),
+        state,
+        snapshots: Vec::new(),
         last_sim_state: None,
     });
     Ok(ConfigHandle(index))
 }
 
+/// Create a fresh step-through state for a configuration's first device.
+fn initial_step_state(config: &ConfigRoot) -> DeviceState {
+    config
+        .devices
+        .first()
+        .map(DeviceState::for_device)
+        .unwrap_or_default()
+}
+
 /// Retrieve a configuration from the CONFIG_STORE by handle.
 ///
 /// Returns an error if the handle is invalid.
@@ -223,16 +243,59 @@ pub fn load_config(rhai_source: &str) -> Result<ConfigHandle, JsValue> {
     store_config(config)
 }
 
+/// Load and parse a Rhai configuration with raised parser limits.
+///
+/// Behaves like [`load_config`], but runs the parser with the limits in
+/// `limits_json`, a JSON object with any of `max_operations`,
+/// `max_expr_depth` and `max_call_levels` (omitted fields keep their
+/// defaults, 0 disables a limit). Use it for trusted configurations, such
+/// as large ones generated by the editor, that `load_config` rejects with
+/// "Parser limit exceeded".
+///
+/// # Arguments
+/// * `rhai_source` - Rhai DSL source code as a string
+/// * `limits_json` - Parser limits as JSON
+///
+/// # Errors
+/// Returns an error if `limits_json` is invalid, or for any reason
+/// [`load_config`] would.
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const handle = load_config_with_limits(source, '{"max_operations": 1000000}');
+/// ```
+#[wasm_bindgen]
+pub fn load_config_with_limits(
+    rhai_source: &str,
+    limits_json: &str,
+) -> Result<ConfigHandle, JsValue> {
+    let limits: ParserLimits = serde_json::from_str(limits_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid parser limits JSON: {}", e)))?;
+
+    // Validate input size (1MB limit)
+    const MAX_CONFIG_SIZE: usize = 1024 * 1024;
+    if rhai_source.len() > MAX_CONFIG_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Configuration too large: {} bytes (max {})",
+            rhai_source.len(),
+            MAX_CONFIG_SIZE
+        )));
+    }
+
+    let config = Parser::with_limits(limits)
+        .parse_string(rhai_source)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    store_config(config)
+}
+
 /// Parse Rhai configuration source into ConfigRoot.
 ///
 /// Uses the full Rhai parser from keyrx_core::parser which supports all DSL
 /// functions: device_start, device_end, map, tap_hold, when_start, when_end,
 /// with_shift, with_mods, etc.
 fn parse_rhai_config(source: &str) -> Result<ConfigRoot, std::string::String> {
-    use crate::parser::Parser;
-
-    let parser = Parser::new();
-    parser.parse_string(source)
+    Parser::new().parse_string(source)
 }
 
 /// Load a pre-compiled .krx binary configuration.
@@ -284,11 +347,11 @@ pub fn load_krx(binary: &[u8]) -> Result<ConfigHandle, JsValue> {
     };
 
     // Validate the version
-    if archived.version.major != 1 {
-        return Err(JsValue::from_str(&format!(
-            "Unsupported .krx version: {}.{}.{} (expected 1.x.x)",
-            archived.version.major, archived.version.minor, archived.version.patch
-        )));
+    let config_version = Version::from(&archived.version);
+    if !config_version.is_compatible_with(Version::current()) {
+        return Err(JsValue::from_str(
+            &config_version.incompatibility_message(Version::current()),
+        ));
     }
 
     // Deserialize to owned ConfigRoot
@@ -300,6 +363,56 @@ pub fn load_krx(binary: &[u8]) -> Result<ConfigHandle, JsValue> {
     store_config(config)
 }
 
+/// Load a configuration from the JSON written by
+/// `keyrx_compiler compile --target wasm-json`.
+///
+/// The JSON is the serde form of ConfigRoot (the `parse --json` format), so
+/// pure-simulation pages can load a config without the rkyv binary path.
+///
+/// # Arguments
+/// * `json` - ConfigRoot serialized as JSON
+///
+/// # Returns
+/// * `Ok(ConfigHandle)` - Handle to the loaded configuration
+/// * `Err(JsValue)` - Size, JSON or version error
+///
+/// # Errors
+/// Returns an error if:
+/// - JSON size exceeds 10MB limit
+/// - JSON does not describe a ConfigRoot
+/// - The config version is incompatible with this runtime
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// const response = await fetch('config.json');
+/// const handle = load_config_json(await response.text());
+/// ```
+#[wasm_bindgen]
+pub fn load_config_json(json: &str) -> Result<ConfigHandle, JsValue> {
+    // Validate input size (10MB limit, same as .krx)
+    const MAX_JSON_SIZE: usize = 10 * 1024 * 1024;
+    if json.len() > MAX_JSON_SIZE {
+        return Err(JsValue::from_str(&format!(
+            "Configuration too large: {} bytes (max {})",
+            json.len(),
+            MAX_JSON_SIZE
+        )));
+    }
+
+    let config: ConfigRoot = serde_json::from_str(json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid configuration JSON: {}", e)))?;
+
+    // Validate the version
+    if !config.version.is_compatible_with(Version::current()) {
+        return Err(JsValue::from_str(
+            &config.version.incompatibility_message(Version::current()),
+        ));
+    }
+
+    // Store config and return handle
+    store_config(config)
+}
+
 // ============================================================================
 // Configuration Validation
 // ============================================================================
@@ -354,7 +467,10 @@ pub fn validate_config(rhai_source: &str) -> Result<JsValue, JsValue> {
 ///
 /// # Arguments
 /// * `config` - Handle to a loaded configuration
-/// * `events_json` - JSON string containing EventSequence
+/// * `events_json` - JSON string containing EventSequence. Events may carry a
+///   `device_id`, which `when_device_start()` blocks are matched against. An
+///   optional `seed` (default 0) seeds the latency model; the result echoes
+///   it, and the same config, events and seed always give the same result.
 ///
 /// # Returns
 /// * `Ok(JsValue)` - SimulationResult as JSON
@@ -370,9 +486,11 @@ pub fn validate_config(rhai_source: &str) -> Result<JsValue, JsValue> {
 /// # Example (JavaScript)
 /// ```javascript
 /// const events = {
+///   seed: 42,
 ///   events: [
 ///     { keycode: "A", event_type: "press", timestamp_us: 0 },
-///     { keycode: "A", event_type: "release", timestamp_us: 100000 }
+///     { keycode: "A", event_type: "release", timestamp_us: 100000 },
+///     { keycode: "A", event_type: "press", timestamp_us: 200000, device_id: "usb-numpad" }
 ///   ]
 /// };
 /// const result = simulate(configHandle, JSON.stringify(events));
@@ -405,7 +523,7 @@ pub fn simulate(config: ConfigHandle, events_json: &str) -> Result<JsValue, JsVa
     let lookup = KeyLookup::from_device_config(device_config);
 
     // Run simulation
-    let result = simulation::run_simulation(&lookup, &event_sequence)
+    let result = simulation::run_simulation(&lookup, device_config, &event_sequence)
         .map_err(|e| JsValue::from_str(e.as_str()))?;
 
     // Store the final state for get_state to access
@@ -416,6 +534,95 @@ pub fn simulate(config: ConfigHandle, events_json: &str) -> Result<JsValue, JsVa
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+// ============================================================================
+// Step-through Simulation
+// ============================================================================
+
+/// Process a single keyboard event against the configuration's persistent state.
+///
+/// Unlike [`simulate`], state carries over between calls. The state before the
+/// event is saved so [`simulate_step_back`] can undo it.
+///
+/// # Arguments
+/// * `config` - Handle to a loaded configuration
+/// * `event_json` - JSON string containing a single SimKeyEvent
+///
+/// # Returns
+/// * `Ok(JsValue)` - TimelineEntry for the event as JSON
+/// * `Err(JsValue)` - Error message
+///
+/// # Example (JavaScript)
+/// ```javascript
+/// simulate_step(handle, JSON.stringify({ keycode: "A", event_type: "press", timestamp_us: 0 }));
+/// simulate_step_back(handle); // A is no longer pressed
+/// ```
+#[wasm_bindgen]
+pub fn simulate_step(config: ConfigHandle, event_json: &str) -> Result<JsValue, JsValue> {
+    let sim_event: SimKeyEvent = serde_json::from_str(event_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid JSON: {}", e)))?;
+
+    let mut store = recover_mutex_lock(&CONFIG_STORE, "simulate_step")?;
+    let entry = store
+        .get_mut(config.0)
+        .ok_or_else(|| JsValue::from_str(&format!("Invalid ConfigHandle: {}", config.0)))?;
+    let device_config = entry
+        .config
+        .devices
+        .first()
+        .ok_or_else(|| JsValue::from_str("Configuration has no devices"))?;
+    let lookup = KeyLookup::from_device_config(device_config);
+
+    let snapshot = entry.state.snapshot();
+    let step = simulation::step_simulation(&lookup, &mut entry.state, &sim_event)
+        .map_err(|e| JsValue::from_str(e.as_str()))?;
+
+    if entry.snapshots.len() == MAX_STEP_SNAPSHOTS {
+        entry.snapshots.remove(0);
+    }
+    entry.snapshots.push(snapshot);
+    entry.last_sim_state = Some(step.state.clone());
+
+    serde_wasm_bindgen::to_value(&step)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+/// Undo the most recent [`simulate_step`].
+///
+/// # Returns
+/// * `Ok(JsValue)` - SimulationState after stepping back, as JSON
+/// * `Err(JsValue)` - Error if the handle is invalid or there is no step to undo
+#[wasm_bindgen]
+pub fn simulate_step_back(config: ConfigHandle) -> Result<JsValue, JsValue> {
+    let mut store = recover_mutex_lock(&CONFIG_STORE, "simulate_step_back")?;
+    let entry = store
+        .get_mut(config.0)
+        .ok_or_else(|| JsValue::from_str(&format!("Invalid ConfigHandle: {}", config.0)))?;
+    let snapshot = entry
+        .snapshots
+        .pop()
+        .ok_or_else(|| JsValue::from_str("No step to undo"))?;
+
+    entry.state.restore(&snapshot);
+    let sim_state = crate::simulator::capture_state(&entry.state);
+    entry.last_sim_state = Some(sim_state.clone());
+
+    serde_wasm_bindgen::to_value(&sim_state)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))
+}
+
+/// Discard the step-through state and its undo history.
+#[wasm_bindgen]
+pub fn simulate_step_reset(config: ConfigHandle) -> Result<(), JsValue> {
+    let mut store = recover_mutex_lock(&CONFIG_STORE, "simulate_step_reset")?;
+    let entry = store
+        .get_mut(config.0)
+        .ok_or_else(|| JsValue::from_str(&format!("Invalid ConfigHandle: {}", config.0)))?;
+    entry.state = initial_step_state(&entry.config);
+    entry.snapshots.clear();
+    entry.last_sim_state = None;
+    Ok(())
+}
+
 /// Get current simulation state.
 ///
 /// Returns the state from the most recent simulation for the given configuration.
@@ -548,8 +755,13 @@ mod tests {
             devices: alloc::vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "*".into(),
+                    excludes: alloc::vec::Vec::new(),
                 },
                 mappings: alloc::vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+                time_windows: alloc::vec::Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
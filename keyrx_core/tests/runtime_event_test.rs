@@ -13,10 +13,11 @@ extern crate alloc;
 use alloc::string::String;
 use alloc::vec;
 use keyrx_core::config::{
-    BaseKeyMapping, Condition, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping,
+    BaseKeyMapping, Condition, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping, MacroStep,
 };
 use keyrx_core::runtime::{
-    check_tap_hold_timeouts, process_event, DeviceState, KeyEvent, KeyLookup,
+    check_tap_hold_timeouts, find_output_overflow, output_capacity, process_event, DeviceState,
+    KeyEvent, KeyLookup,
 };
 
 /// Helper to create a test DeviceConfig with given mappings
@@ -24,8 +25,13 @@ fn create_test_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -279,6 +285,175 @@ fn test_process_event_lock_mapping() {
     assert!(state.is_lock_active(1));
 }
 
+#[test]
+fn test_process_event_layer_toggle() {
+    // Layer toggle: first press locks MD_02 on, next press unlocks it
+    let config = create_test_config(vec![
+        KeyMapping::layer_toggle(KeyCode::F1, 2),
+        KeyMapping::conditional(
+            Condition::ModifierActive(2),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+            }],
+        ),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    // Toggle on: no output, layer stays on after release
+    assert!(process_event(KeyEvent::press(KeyCode::F1), &lookup, &mut state).is_empty());
+    assert!(process_event(KeyEvent::release(KeyCode::F1), &lookup, &mut state).is_empty());
+    assert!(state.is_layer_locked(2));
+    assert!(state.is_modifier_active(2));
+
+    // Use: layer mapping applies
+    let output = process_event(KeyEvent::press(KeyCode::H), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::Left)]);
+    let output = process_event(KeyEvent::release(KeyCode::H), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::release(KeyCode::Left)]);
+
+    // Toggle off: base mapping (passthrough) again
+    process_event(KeyEvent::press(KeyCode::F1), &lookup, &mut state);
+    process_event(KeyEvent::release(KeyCode::F1), &lookup, &mut state);
+    assert!(!state.is_layer_locked(2));
+    assert!(!state.is_modifier_active(2));
+    let output = process_event(KeyEvent::press(KeyCode::H), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::H)]);
+}
+
+#[test]
+fn test_layer_toggle_survives_momentary_release() {
+    // A momentary key for the same layer must not pop a locked layer
+    let config = create_test_config(vec![
+        KeyMapping::layer_toggle(KeyCode::F1, 2),
+        KeyMapping::modifier(KeyCode::CapsLock, 2),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(KeyEvent::press(KeyCode::F1), &lookup, &mut state);
+    process_event(KeyEvent::release(KeyCode::F1), &lookup, &mut state);
+
+    process_event(KeyEvent::press(KeyCode::CapsLock), &lookup, &mut state);
+    process_event(KeyEvent::release(KeyCode::CapsLock), &lookup, &mut state);
+    assert!(
+        state.is_modifier_active(2),
+        "locked layer popped by release"
+    );
+
+    // Unlocked: the momentary key works as before
+    process_event(KeyEvent::press(KeyCode::F1), &lookup, &mut state);
+    process_event(KeyEvent::press(KeyCode::CapsLock), &lookup, &mut state);
+    assert!(state.is_modifier_active(2));
+    process_event(KeyEvent::release(KeyCode::CapsLock), &lookup, &mut state);
+    assert!(!state.is_modifier_active(2));
+}
+
+#[test]
+fn test_default_layer_is_active_at_boot() {
+    // Boots on the MD_01 layer (remapped base), F1 toggles back to passthrough
+    let mut config = create_test_config(vec![
+        KeyMapping::layer_toggle(KeyCode::F1, 1),
+        KeyMapping::conditional(
+            Condition::ModifierActive(1),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::E,
+                to: KeyCode::F,
+            }],
+        ),
+    ]);
+    config.default_layer = Some(1);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::for_device(&config);
+
+    assert!(state.is_layer_locked(1));
+    assert_eq!(state.default_layer(), Some(1));
+
+    // The layer's mappings apply from the first event
+    let output = process_event(KeyEvent::press(KeyCode::E), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::F)]);
+    process_event(KeyEvent::release(KeyCode::E), &lookup, &mut state);
+
+    // The default layer toggles off like any locked layer
+    process_event(KeyEvent::press(KeyCode::F1), &lookup, &mut state);
+    process_event(KeyEvent::release(KeyCode::F1), &lookup, &mut state);
+    let output = process_event(KeyEvent::press(KeyCode::E), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::E)]);
+}
+
+#[test]
+fn test_process_event_disable() {
+    let config = create_test_config(vec![KeyMapping::disable(KeyCode::Insert)]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    // The disabled key emits nothing on press, repeat or release
+    for event in [
+        KeyEvent::press(KeyCode::Insert),
+        KeyEvent::repeat(KeyCode::Insert),
+        KeyEvent::release(KeyCode::Insert),
+    ] {
+        assert!(process_event(event, &lookup, &mut state).is_empty());
+    }
+
+    // Neighbouring keys still pass through
+    let output = process_event(KeyEvent::press(KeyCode::Home), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::Home)]);
+    let output = process_event(KeyEvent::release(KeyCode::Home), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::release(KeyCode::Home)]);
+}
+
+#[test]
+fn test_process_event_macro_schedules_steps_after_waits() {
+    let config = create_test_config(vec![KeyMapping::macro_steps(
+        KeyCode::F13,
+        vec![
+            MacroStep::Press(KeyCode::H),
+            MacroStep::Release(KeyCode::H),
+            MacroStep::Wait(120),
+            MacroStep::Press(KeyCode::I),
+            MacroStep::Wait(30),
+            MacroStep::Release(KeyCode::I),
+        ],
+    )]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    // Steps before the first wait are emitted with the press
+    let output = process_event(
+        KeyEvent::press(KeyCode::F13).with_timestamp(1_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![
+            KeyEvent::press(KeyCode::H).with_timestamp(1_000),
+            KeyEvent::release(KeyCode::H).with_timestamp(1_000),
+        ]
+    );
+
+    // The rest is scheduled, stamped with the time each step is due
+    assert_eq!(
+        state.take_scheduled_outputs(),
+        vec![
+            KeyEvent::press(KeyCode::I).with_timestamp(121_000),
+            KeyEvent::release(KeyCode::I).with_timestamp(151_000),
+        ]
+    );
+    assert!(state.take_scheduled_outputs().is_empty());
+
+    // Repeat and release emit nothing, and the press was not tracked
+    for event in [
+        KeyEvent::repeat(KeyCode::F13),
+        KeyEvent::release(KeyCode::F13),
+    ] {
+        assert!(process_event(event, &lookup, &mut state).is_empty());
+    }
+    assert!(state.take_scheduled_outputs().is_empty());
+}
+
 #[test]
 fn test_process_event_modified_output_shift() {
     // Test ModifiedOutput: Shift+1 sequence
@@ -374,6 +549,146 @@ fn test_process_event_modified_output_all_modifiers() {
     assert_eq!(output[4], KeyEvent::Release(KeyCode::LShift));
 }
 
+#[test]
+fn test_process_event_repeat_passthrough_and_simple() {
+    // Repeats of unmapped keys pass through; simple mappings repeat the target
+    let config = create_test_config(vec![KeyMapping::simple(KeyCode::A, KeyCode::B)]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(KeyEvent::press(KeyCode::C), &lookup, &mut state);
+    let output = process_event(KeyEvent::repeat(KeyCode::C), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::repeat(KeyCode::C)]);
+
+    process_event(KeyEvent::press(KeyCode::A), &lookup, &mut state);
+    let output = process_event(
+        KeyEvent::repeat(KeyCode::A).with_timestamp(500_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(output.len(), 1);
+    assert!(output[0].is_repeat());
+    assert_eq!(output[0].keycode(), KeyCode::B);
+    assert_eq!(output[0].timestamp_us(), 500_000);
+
+    let output = process_event(KeyEvent::release(KeyCode::A), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::release(KeyCode::B)]);
+}
+
+#[test]
+fn test_process_event_repeat_modified_output_repeats_main_key() {
+    // Shift+1: only the main key repeats, the modifier stays held
+    let config = create_test_config(vec![KeyMapping::modified_output(
+        KeyCode::A,
+        KeyCode::Num1,
+        true,
+        false,
+        false,
+        false,
+    )]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(KeyEvent::press(KeyCode::A), &lookup, &mut state);
+    let output = process_event(KeyEvent::repeat(KeyCode::A), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::repeat(KeyCode::Num1)]);
+
+    let output = process_event(KeyEvent::release(KeyCode::A), &lookup, &mut state);
+    assert_eq!(
+        output,
+        vec![
+            KeyEvent::release(KeyCode::Num1),
+            KeyEvent::release(KeyCode::LShift)
+        ]
+    );
+}
+
+#[test]
+fn test_process_event_repeat_does_not_retrigger_state_keys() {
+    // Repeats of modifier, lock, and tap-hold keys emit nothing and leave
+    // state untouched
+    let config = create_test_config(vec![
+        KeyMapping::modifier(KeyCode::CapsLock, 0),
+        KeyMapping::lock(KeyCode::ScrollLock, 1),
+        KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 2, 200),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(KeyEvent::press(KeyCode::CapsLock), &lookup, &mut state);
+    process_event(KeyEvent::press(KeyCode::ScrollLock), &lookup, &mut state);
+    process_event(KeyEvent::press(KeyCode::Space), &lookup, &mut state);
+
+    for key in [KeyCode::CapsLock, KeyCode::ScrollLock, KeyCode::Space] {
+        for _ in 0..3 {
+            let output = process_event(KeyEvent::repeat(key), &lookup, &mut state);
+            assert!(
+                output.is_empty(),
+                "repeat of {:?} produced {:?}",
+                key,
+                output
+            );
+        }
+    }
+    assert!(state.is_modifier_active(0));
+    assert!(state.is_lock_active(1));
+    assert!(!state.is_modifier_active(2));
+
+    // Tap-hold still resolves as a tap on release
+    let output = process_event(KeyEvent::release(KeyCode::Space), &lookup, &mut state);
+    assert_eq!(
+        output,
+        vec![
+            KeyEvent::press(KeyCode::Space),
+            KeyEvent::release(KeyCode::Space)
+        ]
+    );
+}
+
+#[test]
+fn test_largest_mappings_fit_output_capacity() {
+    // The largest mapping of each bounded kind must fit its runtime buffer,
+    // otherwise the compiler would reject every such configuration
+    let mappings = [
+        KeyMapping::modified_output(KeyCode::A, KeyCode::Z, true, true, true, true),
+        KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0, 200),
+    ];
+    for mapping in mappings {
+        let KeyMapping::Base(base) = mapping else {
+            unreachable!()
+        };
+        let capacity = output_capacity(&base).expect("bounded mapping");
+        assert!(
+            base.max_output_events() <= capacity,
+            "{:?} emits {} events but the runtime buffer holds {}",
+            base,
+            base.max_output_events(),
+            capacity
+        );
+    }
+}
+
+#[test]
+fn test_find_output_overflow_accepts_largest_mappings() {
+    let config = create_test_config(vec![
+        KeyMapping::modified_output(KeyCode::A, KeyCode::Z, true, true, true, true),
+        KeyMapping::conditional(
+            Condition::ModifierActive(0),
+            vec![BaseKeyMapping::ModifiedOutput {
+                from: KeyCode::B,
+                to: KeyCode::Y,
+                shift: true,
+                ctrl: true,
+                alt: true,
+                win: true,
+            }],
+        ),
+        KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0, 200),
+        KeyMapping::on_release(KeyCode::C, KeyCode::D, 200),
+    ]);
+    assert_eq!(find_output_overflow(&config), None);
+}
+
 #[test]
 fn test_process_event_conditional_mapping_true() {
     // Test Conditional mapping: when modifier active, apply conditional mapping
@@ -407,6 +722,138 @@ fn test_process_event_conditional_mapping_true() {
     assert_eq!(output[0], KeyEvent::Release(KeyCode::Left));
 }
 
+#[test]
+fn test_process_event_on_release_output_fixed_at_press() {
+    // when(MD_00): H emits Left on release; the release must emit Left even if
+    // MD_00 was released while H was held
+    let config = create_test_config(vec![
+        KeyMapping::modifier(KeyCode::CapsLock, 0),
+        KeyMapping::conditional(
+            Condition::ModifierActive(0),
+            vec![BaseKeyMapping::OnRelease {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+                threshold_ms: 200,
+            }],
+        ),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(KeyEvent::press(KeyCode::CapsLock), &lookup, &mut state);
+    let output = process_event(
+        KeyEvent::press(KeyCode::H).with_timestamp(10_000),
+        &lookup,
+        &mut state,
+    );
+    assert!(output.is_empty());
+
+    process_event(KeyEvent::release(KeyCode::CapsLock), &lookup, &mut state);
+    let output = process_event(
+        KeyEvent::release(KeyCode::H).with_timestamp(60_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![
+            KeyEvent::press(KeyCode::Left).with_timestamp(60_000),
+            KeyEvent::release(KeyCode::Left).with_timestamp(60_000),
+        ]
+    );
+}
+
+#[test]
+fn test_process_event_cycle_releases_and_repeats_pressed_output() {
+    // The cycle includes the key itself: its press is untracked, so the
+    // release must still lift it, and repeats follow the pressed output
+    let config = create_test_config(vec![KeyMapping::cycle(
+        KeyCode::F14,
+        vec![KeyCode::F14, KeyCode::A],
+        500,
+    )]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    let output = process_event(KeyEvent::press(KeyCode::F14), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::F14)]);
+    let output = process_event(KeyEvent::release(KeyCode::F14), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::release(KeyCode::F14)]);
+
+    let output = process_event(
+        KeyEvent::press(KeyCode::F14).with_timestamp(100_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![KeyEvent::press(KeyCode::A).with_timestamp(100_000)]
+    );
+    let output = process_event(
+        KeyEvent::repeat(KeyCode::F14).with_timestamp(150_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![KeyEvent::repeat(KeyCode::A).with_timestamp(150_000)]
+    );
+    let output = process_event(
+        KeyEvent::release(KeyCode::F14).with_timestamp(200_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![KeyEvent::release(KeyCode::A).with_timestamp(200_000)]
+    );
+}
+
+#[test]
+fn test_process_event_on_release_after_permissive_hold() {
+    // Pressing an on-release key while a tap-hold key is pending triggers
+    // permissive hold first, so the hold layer's on-release mapping applies
+    let config = create_test_config(vec![
+        KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0, 200),
+        KeyMapping::conditional(
+            Condition::ModifierActive(0),
+            vec![BaseKeyMapping::OnRelease {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+                threshold_ms: 200,
+            }],
+        ),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    process_event(
+        KeyEvent::press(KeyCode::Space).with_timestamp(0),
+        &lookup,
+        &mut state,
+    );
+    let output = process_event(
+        KeyEvent::press(KeyCode::H).with_timestamp(50_000),
+        &lookup,
+        &mut state,
+    );
+    assert!(output.is_empty());
+    assert!(state.is_modifier_active(0));
+
+    let output = process_event(
+        KeyEvent::release(KeyCode::H).with_timestamp(100_000),
+        &lookup,
+        &mut state,
+    );
+    assert_eq!(
+        output,
+        vec![
+            KeyEvent::press(KeyCode::Left).with_timestamp(100_000),
+            KeyEvent::release(KeyCode::Left).with_timestamp(100_000),
+        ]
+    );
+}
+
 #[test]
 fn test_process_event_conditional_mapping_false() {
     // Test Conditional mapping: when modifier NOT active, passthrough
@@ -1090,7 +1537,89 @@ fn test_permissive_hold_activates_before_lookup() {
     );
 }
 
+/// Test that tracing reports the mapping that fired, including after permissive hold
+#[cfg(feature = "trace-mapping")]
+#[test]
+fn test_process_event_traced_reports_matched_mapping() {
+    use keyrx_core::config::MappingKind;
+    use keyrx_core::runtime::{process_event_traced, MappingPosition};
+
+    let config = create_test_config(vec![
+        KeyMapping::tap_hold(KeyCode::CapsLock, KeyCode::Escape, 0, 200),
+        KeyMapping::conditional(
+            Condition::ModifierActive(0),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::A,
+                to: KeyCode::B,
+            }],
+        ),
+        KeyMapping::modifier(KeyCode::A, 1),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    let (_, matched) = process_event_traced(
+        KeyEvent::press(KeyCode::CapsLock).with_timestamp(0),
+        &lookup,
+        &mut state,
+    );
+    let matched = matched.unwrap();
+    assert_eq!(matched.kind, MappingKind::TapHold);
+    assert_eq!(
+        matched.position,
+        MappingPosition {
+            index: 0,
+            inner: None
+        }
+    );
+
+    // Permissive hold switches A to the conditional mapping
+    let (_, matched) = process_event_traced(
+        KeyEvent::press(KeyCode::A).with_timestamp(50_000),
+        &lookup,
+        &mut state,
+    );
+    let matched = matched.unwrap();
+    assert_eq!(matched.kind, MappingKind::Simple);
+    assert_eq!(
+        matched.position,
+        MappingPosition {
+            index: 1,
+            inner: Some(0)
+        }
+    );
+
+    // Unmapped keys report no mapping
+    let (output, matched) = process_event_traced(KeyEvent::press(KeyCode::Z), &lookup, &mut state);
+    assert_eq!(output, vec![KeyEvent::press(KeyCode::Z)]);
+    assert_eq!(matched, None);
+}
+
 // Property-based tests using proptest
+#[test]
+fn test_key_event_matches_ignores_timestamp() {
+    let early = KeyEvent::press(KeyCode::A).with_timestamp(1_000);
+    let late = KeyEvent::press(KeyCode::A).with_timestamp(250_000);
+
+    assert!(early.matches(&late));
+    assert!(early.matches(&KeyEvent::press(KeyCode::A)));
+    // Full equality still compares timestamps
+    assert_ne!(early, late);
+}
+
+#[test]
+fn test_key_event_matches_compares_type_key_and_device() {
+    let event = KeyEvent::press(KeyCode::A)
+        .with_timestamp(1_000)
+        .with_device_id("numpad".to_string());
+
+    assert!(event.matches(&KeyEvent::press(KeyCode::A).with_device_id("numpad".to_string())));
+    assert!(!event.matches(&KeyEvent::release(KeyCode::A).with_device_id("numpad".to_string())));
+    assert!(!event.matches(&KeyEvent::press(KeyCode::B).with_device_id("numpad".to_string())));
+    assert!(!event.matches(&KeyEvent::press(KeyCode::A).with_device_id("laptop".to_string())));
+    assert!(!event.matches(&KeyEvent::press(KeyCode::A)));
+}
+
 #[cfg(test)]
 mod proptests {
     use super::*;
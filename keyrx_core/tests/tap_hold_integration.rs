@@ -5,13 +5,14 @@
 //! - Event processing through process_event
 //! - Timeout handling with check_tap_hold_timeouts
 //! - Permissive hold behavior
+//! - Decision policies (permissive vs timeout-only)
 //! - Realistic usage patterns
 //!
 //! Tests use programmatically constructed configs to simulate
 //! what would be produced by compiling Rhai configurations.
 
 use keyrx_core::config::{
-    BaseKeyMapping, Condition, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping,
+    BaseKeyMapping, Condition, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping, TapHoldPolicy,
 };
 use keyrx_core::runtime::{
     check_tap_hold_timeouts, process_event, DeviceState, KeyEvent, KeyLookup,
@@ -26,8 +27,13 @@ fn create_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -342,6 +348,98 @@ fn test_permissive_hold_multiple_tap_holds() {
     assert!(state.is_modifier_active(1), "MD_01 should be active");
 }
 
+// ============================================================================
+// Decision Policy Tests
+// ============================================================================
+
+/// Runs CapsLock(tap-hold) down, H tapped, CapsLock up - all within the
+/// threshold - with `policy`, and returns the output key events.
+fn roll_over_with_policy(policy: TapHoldPolicy) -> Vec<(KeyCode, bool)> {
+    let config = create_config(vec![
+        KeyMapping::Base(BaseKeyMapping::TapHold {
+            from: KeyCode::CapsLock,
+            tap: KeyCode::Escape,
+            hold_modifier: 0,
+            threshold_ms: 200,
+            policy,
+        }),
+        KeyMapping::conditional(
+            Condition::ModifierActive(0),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+            }],
+        ),
+    ]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    let inputs = [
+        KeyEvent::press(KeyCode::CapsLock).with_timestamp(0),
+        KeyEvent::press(KeyCode::H).with_timestamp(50_000),
+        KeyEvent::release(KeyCode::H).with_timestamp(80_000),
+        KeyEvent::release(KeyCode::CapsLock).with_timestamp(120_000),
+    ];
+    inputs
+        .into_iter()
+        .flat_map(|event| process_event(event, &lookup, &mut state))
+        .map(|event| (event.keycode(), event.is_press()))
+        .collect()
+}
+
+#[test]
+fn test_permissive_policy_other_key_decides_hold() {
+    // H during the pending window activates MD_00, so H becomes Left and
+    // CapsLock emits nothing on release
+    assert_eq!(
+        roll_over_with_policy(TapHoldPolicy::Permissive),
+        vec![(KeyCode::Left, true), (KeyCode::Left, false)]
+    );
+}
+
+#[test]
+fn test_timeout_policy_other_key_does_not_decide_hold() {
+    // Same input: H passes through unmodified, and the quick CapsLock
+    // release is still a tap
+    assert_eq!(
+        roll_over_with_policy(TapHoldPolicy::Timeout),
+        vec![
+            (KeyCode::H, true),
+            (KeyCode::H, false),
+            (KeyCode::Escape, true),
+            (KeyCode::Escape, false),
+        ]
+    );
+}
+
+#[test]
+fn test_timeout_policy_still_holds_after_threshold() {
+    let config = create_config(vec![KeyMapping::Base(BaseKeyMapping::TapHold {
+        from: KeyCode::CapsLock,
+        tap: KeyCode::Escape,
+        hold_modifier: 0,
+        threshold_ms: 200,
+        policy: TapHoldPolicy::Timeout,
+    })]);
+    let lookup = KeyLookup::from_device_config(&config);
+    let mut state = DeviceState::new();
+
+    let _ = process_event(
+        KeyEvent::press(KeyCode::CapsLock).with_timestamp(0),
+        &lookup,
+        &mut state,
+    );
+    let _ = process_event(
+        KeyEvent::press(KeyCode::A).with_timestamp(50_000),
+        &lookup,
+        &mut state,
+    );
+    assert!(!state.is_modifier_active(0), "Other key must not decide");
+
+    let _ = check_tap_hold_timeouts(250_000, &mut state);
+    assert!(state.is_modifier_active(0), "Threshold should still decide");
+}
+
 // ============================================================================
 // Realistic Usage Patterns
 // ============================================================================
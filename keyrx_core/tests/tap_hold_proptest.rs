@@ -112,6 +112,7 @@ fn create_test_config(tap_hold_key: KeyCode, threshold_ms: u16) -> DeviceConfig
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings: vec![KeyMapping::tap_hold(
             tap_hold_key,
@@ -119,6 +120,10 @@ fn create_test_config(tap_hold_key: KeyCode, threshold_ms: u16) -> DeviceConfig
             0,               // modifier ID
             threshold_ms,
         )],
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -135,8 +140,13 @@ fn create_multi_tap_hold_config(thresholds: &[(KeyCode, u16)]) -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -39,7 +39,8 @@
 use wasm_bindgen_test::*;
 
 use keyrx_core::wasm::{
-    get_state, load_config, load_krx, simulate, wasm_init, EventSequence, SimKeyEvent,
+    get_state, load_config, load_config_json, load_krx, simulate, simulate_step,
+    simulate_step_back, wasm_init, EventSequence, SimKeyEvent, SimulationState, TimelineEntry,
 };
 
 // Configure wasm-bindgen-test to run in browser
@@ -195,8 +196,13 @@ fn test_load_krx_valid() {
         devices: vec![DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".into(),
+                excludes: Vec::new(),
             },
             mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }],
         metadata: Metadata {
             compilation_timestamp: 1234567890,
@@ -269,6 +275,59 @@ fn test_load_krx_invalid_data() {
     }
 }
 
+// ============================================================================
+// Configuration Loading Tests - load_config_json
+// ============================================================================
+
+#[wasm_bindgen_test]
+fn test_load_config_json_valid() {
+    use keyrx_core::config::{
+        ConfigRoot, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping, Metadata, Version,
+    };
+
+    wasm_init();
+
+    let config = ConfigRoot {
+        version: Version::current(),
+        devices: vec![DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: "*".into(),
+                excludes: Vec::new(),
+            },
+            mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        }],
+        metadata: Metadata {
+            compilation_timestamp: 1234567890,
+            compiler_version: "wasm-test-0.1.0".into(),
+            source_hash: "test_hash".into(),
+        },
+    };
+    let json = serde_json::to_string(&config).expect("Serialization should succeed");
+
+    let result = load_config_json(&json);
+    assert!(result.is_ok(), "Valid config JSON should load successfully");
+}
+
+#[wasm_bindgen_test]
+fn test_load_config_json_invalid() {
+    wasm_init();
+
+    let result = load_config_json("{\"devices\": 42}");
+    assert!(result.is_err(), "Malformed config JSON should fail");
+
+    if let Err(e) = result {
+        let error_str = format!("{:?}", e);
+        assert!(
+            error_str.contains("Invalid configuration JSON"),
+            "Error should mention the invalid JSON"
+        );
+    }
+}
+
 // ============================================================================
 // Event Simulation Tests
 // ============================================================================
@@ -293,13 +352,16 @@ fn test_simulate_simple_sequence() {
                 keycode: "A".to_string(),
                 event_type: "press".to_string(),
                 timestamp_us: 0,
+                device_id: None,
             },
             SimKeyEvent {
                 keycode: "A".to_string(),
                 event_type: "release".to_string(),
                 timestamp_us: 100_000,
+                device_id: None,
             },
         ],
+        seed: 0,
     };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
@@ -356,8 +418,10 @@ fn test_simulate_too_many_events() {
                 keycode: "A".to_string(),
                 event_type: if i % 2 == 0 { "press" } else { "release" }.to_string(),
                 timestamp_us: i as u64 * 1000,
+                device_id: None,
             })
             .collect(),
+        seed: 0,
     };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
@@ -389,7 +453,10 @@ fn test_simulate_empty_sequence() {
 
     let config_handle = load_config(rhai_source).expect("Config should load");
 
-    let events = EventSequence { events: vec![] };
+    let events = EventSequence {
+        events: vec![],
+        seed: 0,
+    };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
 
@@ -449,13 +516,16 @@ fn test_get_state_after_simulation() {
                 keycode: "A".to_string(),
                 event_type: "press".to_string(),
                 timestamp_us: 0,
+                device_id: None,
             },
             SimKeyEvent {
                 keycode: "A".to_string(),
                 event_type: "release".to_string(),
                 timestamp_us: 100_000,
+                device_id: None,
             },
         ],
+        seed: 0,
     };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
@@ -466,6 +536,41 @@ fn test_get_state_after_simulation() {
     assert!(result.is_ok(), "Getting state after simulation should work");
 }
 
+#[wasm_bindgen_test]
+fn test_simulate_step_back_restores_state() {
+    wasm_init();
+
+    let rhai_source = r#"
+        device("*") {
+            map("CapsLock", "MD_00");
+        }
+    "#;
+
+    let config_handle = load_config(rhai_source).expect("Config should load");
+
+    let press = SimKeyEvent {
+        keycode: "CapsLock".to_string(),
+        event_type: "press".to_string(),
+        timestamp_us: 0,
+        device_id: None,
+    };
+    let press_json = serde_json::to_string(&press).expect("Serialization should succeed");
+    let step: TimelineEntry =
+        serde_wasm_bindgen::from_value(simulate_step(config_handle, &press_json).unwrap())
+            .expect("Step should deserialize");
+    assert_eq!(step.state.active_modifiers, vec![0]);
+
+    let restored: SimulationState =
+        serde_wasm_bindgen::from_value(simulate_step_back(config_handle).unwrap())
+            .expect("State should deserialize");
+    assert!(restored.active_modifiers.is_empty());
+
+    assert!(
+        simulate_step_back(config_handle).is_err(),
+        "Stepping back past the first event should fail"
+    );
+}
+
 // Note: test_get_state_invalid_handle is covered by unit tests in mod.rs
 // because ConfigHandle is opaque and cannot be constructed in external tests
 
@@ -494,8 +599,10 @@ fn test_simulation_performance_100_events() {
                 keycode: "A".to_string(),
                 event_type: if i % 2 == 0 { "press" } else { "release" }.to_string(),
                 timestamp_us: i as u64 * 1000,
+                device_id: None,
             })
             .collect(),
+        seed: 0,
     };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
@@ -540,13 +647,16 @@ fn test_full_workflow() {
                 keycode: "A".to_string(),
                 event_type: "press".to_string(),
                 timestamp_us: 0,
+                device_id: None,
             },
             SimKeyEvent {
                 keycode: "A".to_string(),
                 event_type: "release".to_string(),
                 timestamp_us: 100_000,
+                device_id: None,
             },
         ],
+        seed: 0,
     };
 
     let events_json = serde_json::to_string(&events).expect("Serialization should succeed");
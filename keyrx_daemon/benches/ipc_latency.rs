@@ -52,9 +52,12 @@ fn start_mock_ipc_server(socket_path: PathBuf) -> thread::JoinHandle<()> {
                         uptime_secs: 3600,
                         active_profile: Some("default".to_string()),
                         device_count: 2,
+                        active_device_count: 2,
+                        web_port: None,
                     },
                     IpcRequest::GetState => IpcResponse::State {
                         state: vec![false; 255],
+                        locks: Vec::new(),
                     },
                     IpcRequest::GetLatencyMetrics => IpcResponse::Latency {
                         min_us: 50,
@@ -63,9 +66,32 @@ fn start_mock_ipc_server(socket_path: PathBuf) -> thread::JoinHandle<()> {
                         p95_us: 200,
                         p99_us: 300,
                     },
-                    IpcRequest::GetEventsTail { count: _ } => {
-                        IpcResponse::Events { events: vec![] }
+                    IpcRequest::GetEventsTail { .. } | IpcRequest::GetEventsSince { .. } => {
+                        IpcResponse::Events {
+                            events: vec![],
+                            last_id: 0,
+                        }
                     }
+                    IpcRequest::GetErrorCounts => IpcResponse::ErrorCounts {
+                        injection_failures: 0,
+                        capture_errors: 0,
+                        dropped_events: 0,
+                    },
+                    IpcRequest::GetLatencyByDevice => IpcResponse::LatencyByDevice {
+                        devices: Default::default(),
+                    },
+                    IpcRequest::GetOutputCounts => IpcResponse::OutputCounts {
+                        events: 0,
+                        frames: 0,
+                    },
+                    IpcRequest::GetLoadedConfig => IpcResponse::LoadedConfig {
+                        source_hash: None,
+                        config: None,
+                    },
+                    IpcRequest::ReloadConfig { path } => IpcResponse::ConfigReloaded {
+                        path: path.unwrap_or_default(),
+                        source_hash: String::new(),
+                    },
                     IpcRequest::ActivateProfile { name } => IpcResponse::ProfileActivated { name },
                 };
 
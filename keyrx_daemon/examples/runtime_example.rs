@@ -108,8 +108,13 @@ fn example_key_lookup() {
     let config = DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: ".*".to_string(),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     };
 
     // Build the lookup table
@@ -169,8 +174,13 @@ fn example_event_processing() {
     let config = DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: ".*".to_string(),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     };
 
     let lookup = KeyLookup::from_device_config(&config);
@@ -257,8 +267,13 @@ fn example_event_processor() {
     let config = DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: ".*".to_string(),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     };
 
     // Create mock input with test events
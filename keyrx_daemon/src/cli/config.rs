@@ -7,10 +7,20 @@
 use crate::cli::logging;
 use crate::config::profile_manager::ProfileManager;
 use crate::config::rhai_generator::{KeyAction, MacroStep, RhaiGenerator};
+use crate::config_loader::read_krx;
 use crate::error::{CliError, ConfigError, DaemonResult};
+use crate::ipc::unix_socket::UnixSocketIpc;
+use crate::ipc::{DaemonIpc, IpcRequest, IpcResponse, DEFAULT_SOCKET_PATH};
 use clap::{Args, Subcommand};
+use keyrx_compiler::parser::core::Parser;
+use keyrx_compiler::parser::validators::{
+    parse_modifier_id, parse_physical_key, parse_virtual_key,
+};
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConfigRoot, DeviceConfig, KeyCode, KeyMapping, TapHoldPolicy,
+};
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Configuration management subcommands.
 #[derive(Args)]
@@ -42,16 +52,21 @@ enum ConfigCommands {
         profile: Option<String>,
     },
 
-    /// Set a tap-hold mapping.
+    /// Set a tap-hold mapping in a device block.
     SetTapHold {
+        /// Pattern of the device_start() block to edit (e.g., "*").
+        device: String,
+
         /// Source key (e.g., "VK_Space").
         key: String,
 
         /// Tap action (e.g., "VK_Space").
+        #[arg(long)]
         tap: String,
 
-        /// Hold action (e.g., "MD_00").
-        hold: String,
+        /// Custom modifier activated while held (e.g., "MD_00" or "00").
+        #[arg(long)]
+        hold_modifier: String,
 
         /// Threshold in milliseconds (default: 200).
         #[arg(long, default_value = "200")]
@@ -131,6 +146,35 @@ enum ConfigCommands {
         /// Second profile name.
         profile2: String,
     },
+
+    /// List modifier, lock and layer IDs with the keys that set them.
+    ShowIds {
+        /// Compiled .krx file to inspect (default: the profile's .krx).
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Profile name (default: active profile).
+        #[arg(long)]
+        profile: Option<String>,
+    },
+
+    /// Show the config the running daemon is remapping with.
+    ShowLoaded {
+        /// Custom socket path (defaults to /tmp/keyrx-daemon.sock).
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
+    /// Tell the running daemon to reload its config.
+    Reload {
+        /// Compiled .krx file to switch to (default: reload the current one).
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Custom socket path (defaults to /tmp/keyrx-daemon.sock).
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
 }
 
 /// JSON output for set-key operations.
@@ -176,6 +220,20 @@ struct DiffOutput {
     differences: Vec<String>,
 }
 
+/// JSON output for show-loaded command.
+#[derive(Serialize)]
+struct ShowLoadedOutput {
+    source_hash: Option<String>,
+    config: Option<ConfigRoot>,
+}
+
+/// JSON output for reload command.
+#[derive(Serialize)]
+struct ReloadOutput {
+    path: String,
+    source_hash: String,
+}
+
 /// Execute the config command.
 pub fn execute(args: ConfigArgs, config_dir: Option<PathBuf>) -> DaemonResult<()> {
     // Determine config directory (priority: parameter, env var, default)
@@ -227,17 +285,19 @@ fn execute_inner(args: ConfigArgs, config_dir: PathBuf) -> DaemonResult<()> {
             profile,
         } => handle_set_key(&mut manager, key, target, layer, profile, args.json),
         ConfigCommands::SetTapHold {
+            device,
             key,
             tap,
-            hold,
+            hold_modifier,
             threshold,
             layer,
             profile,
         } => handle_set_tap_hold(
             &mut manager,
+            device,
             key,
             tap,
-            hold,
+            hold_modifier,
             threshold,
             layer,
             profile,
@@ -264,6 +324,11 @@ fn execute_inner(args: ConfigArgs, config_dir: PathBuf) -> DaemonResult<()> {
         ConfigCommands::Diff { profile1, profile2 } => {
             handle_diff(&manager, profile1, profile2, args.json)
         }
+        ConfigCommands::ShowIds { config, profile } => {
+            handle_show_ids(&manager, config, profile, args.json)
+        }
+        ConfigCommands::ShowLoaded { socket } => handle_show_loaded(socket, args.json),
+        ConfigCommands::Reload { path, socket } => handle_reload(path, socket, args.json),
     }
 }
 
@@ -360,14 +425,40 @@ fn handle_set_key(
 #[allow(clippy::too_many_arguments)]
 fn handle_set_tap_hold(
     manager: &mut ProfileManager,
+    device: String,
     key: String,
     tap: String,
-    hold: String,
+    hold_modifier: String,
     threshold: u16,
     layer: String,
     profile: Option<String>,
     json: bool,
 ) -> DaemonResult<()> {
+    let command_failed = |reason: String| CliError::CommandFailed {
+        command: "set-tap-hold".to_string(),
+        reason,
+    };
+
+    // Validate the arguments with the DSL parser's own rules up front. The
+    // generator only accepts prefixed names, so bare input keys get VK_.
+    let key = if key.starts_with("VK_") {
+        key
+    } else {
+        format!("VK_{}", key)
+    };
+    let hold = if hold_modifier.starts_with("MD_") {
+        hold_modifier
+    } else {
+        format!("MD_{}", hold_modifier)
+    };
+    let expected = BaseKeyMapping::TapHold {
+        from: parse_physical_key(&key).map_err(|e| command_failed(e.to_string()))?,
+        tap: parse_virtual_key(&tap).map_err(|e| command_failed(e.to_string()))?,
+        hold_modifier: parse_modifier_id(&hold).map_err(|e| command_failed(e.to_string()))?,
+        threshold_ms: threshold,
+        policy: TapHoldPolicy::Permissive,
+    };
+
     let profile_name = get_profile_name(manager, profile)?;
     let profile_meta = manager
         .get(&profile_name)
@@ -375,13 +466,15 @@ fn handle_set_tap_hold(
             name: profile_name.clone(),
             reason: "Profile not found".to_string(),
         })?;
+    let original = std::fs::read_to_string(&profile_meta.rhai_path).map_err(ConfigError::from)?;
 
-    // Load Rhai generator
-    let mut gen =
-        RhaiGenerator::load(&profile_meta.rhai_path).map_err(|e| ConfigError::ParseError {
+    // Load Rhai generator for the requested device block
+    let mut gen = RhaiGenerator::load_device(&profile_meta.rhai_path, &device).map_err(|e| {
+        ConfigError::ParseError {
             path: profile_meta.rhai_path.clone(),
             reason: e.to_string(),
-        })?;
+        }
+    })?;
 
     // Set the mapping
     let action = KeyAction::TapHold {
@@ -391,10 +484,17 @@ fn handle_set_tap_hold(
     };
 
     gen.set_key_mapping(&layer, &key, action)
-        .map_err(|e| CliError::CommandFailed {
-            command: "set-tap-hold".to_string(),
-            reason: e.to_string(),
-        })?;
+        .map_err(|e| command_failed(e.to_string()))?;
+
+    // Refuse to write a file the parser would read differently
+    check_tap_hold_round_trip(
+        &original,
+        &gen.to_string(),
+        &profile_meta.rhai_path,
+        &device,
+        &expected,
+    )
+    .map_err(command_failed)?;
 
     // Save the file
     gen.save(&profile_meta.rhai_path)
@@ -426,8 +526,8 @@ fn handle_set_tap_hold(
         );
     } else {
         println!(
-            "✓ Set {} -> tap:{} hold:{} ({}ms) in layer '{}' of profile '{}'",
-            key, tap, hold, threshold, layer, profile_name
+            "✓ Set {} -> tap:{} hold:{} ({}ms) in layer '{}' of device '{}' in profile '{}'",
+            key, tap, hold, threshold, layer, device, profile_name
         );
         println!("  Compiled in {}ms", compile_time);
     }
@@ -435,6 +535,87 @@ fn handle_set_tap_hold(
     Ok(())
 }
 
+/// Checks that `updated` parses to the same configuration as `original`,
+/// except that the `device` block now contains `expected` and has no other
+/// mapping for that key in the same scope.
+fn check_tap_hold_round_trip(
+    original: &str,
+    updated: &str,
+    path: &Path,
+    device: &str,
+    expected: &BaseKeyMapping,
+) -> Result<(), String> {
+    let parse = |source: &str| {
+        Parser::new()
+            .parse_string(source, path)
+            .map_err(|e| e.to_string())
+    };
+    let before = parse(original).map_err(|e| format!("Existing profile does not parse: {}", e))?;
+    let after = parse(updated).map_err(|e| format!("Updated profile does not parse: {}", e))?;
+
+    if before.devices.len() != after.devices.len() {
+        return Err("Updated profile has a different number of device blocks".to_string());
+    }
+
+    let from = mapping_input(expected);
+    let mut found = false;
+    for (old, new) in before.devices.iter().zip(&after.devices) {
+        let is_target = new.identifier.pattern == device;
+        let keep = |(_, mapping): &(Option<&Condition>, &BaseKeyMapping)| {
+            !is_target || mapping_input(mapping) != from
+        };
+        let old_rest: Vec<_> = flatten_mappings(old).into_iter().filter(keep).collect();
+        let new_rest: Vec<_> = flatten_mappings(new).into_iter().filter(keep).collect();
+        if old_rest != new_rest {
+            return Err(format!(
+                "Updating the mapping would change other mappings in device '{}'",
+                new.identifier.pattern
+            ));
+        }
+        found |= is_target && flatten_mappings(new).iter().any(|(_, m)| *m == expected);
+    }
+
+    if found {
+        Ok(())
+    } else {
+        Err(format!(
+            "Generated tap_hold for device '{}' was not read back by the parser",
+            device
+        ))
+    }
+}
+
+/// Lists every mapping of a device with its guarding condition, if any.
+fn flatten_mappings(device: &DeviceConfig) -> Vec<(Option<&Condition>, &BaseKeyMapping)> {
+    let mut flat = Vec::new();
+    for mapping in &device.mappings {
+        match mapping {
+            KeyMapping::Base(base) => flat.push((None, base)),
+            KeyMapping::Conditional {
+                condition,
+                mappings,
+            } => flat.extend(mappings.iter().map(|base| (Some(condition), base))),
+        }
+    }
+    flat
+}
+
+/// Returns the physical key a mapping is triggered by.
+fn mapping_input(mapping: &BaseKeyMapping) -> KeyCode {
+    match mapping {
+        BaseKeyMapping::Simple { from, .. }
+        | BaseKeyMapping::Modifier { from, .. }
+        | BaseKeyMapping::Lock { from, .. }
+        | BaseKeyMapping::TapHold { from, .. }
+        | BaseKeyMapping::ModifiedOutput { from, .. }
+        | BaseKeyMapping::OnRelease { from, .. }
+        | BaseKeyMapping::LayerToggle { from, .. }
+        | BaseKeyMapping::Cycle { from, .. }
+        | BaseKeyMapping::Macro { from, .. }
+        | BaseKeyMapping::Disable { from } => *from,
+    }
+}
+
 fn handle_set_macro(
     manager: &mut ProfileManager,
     key: String,
@@ -734,6 +915,127 @@ fn handle_show(manager: &ProfileManager, profile: Option<String>, json: bool) ->
     Ok(())
 }
 
+/// Prints the modifier, lock and layer IDs a compiled config uses.
+fn handle_show_ids(
+    manager: &ProfileManager,
+    config: Option<PathBuf>,
+    profile: Option<String>,
+    json: bool,
+) -> DaemonResult<()> {
+    let krx_path = match config {
+        Some(path) => path,
+        None => {
+            let profile_name = get_profile_name(manager, profile)?;
+            manager
+                .get(&profile_name)
+                .ok_or_else(|| ConfigError::InvalidProfile {
+                    name: profile_name.clone(),
+                    reason: "Profile not found".to_string(),
+                })?
+                .krx_path
+                .clone()
+        }
+    };
+
+    let ids = keyrx_compiler::ids::config_ids(&read_krx(&krx_path)?);
+
+    if json {
+        println!("{}", serde_json::to_string(&ids).map_err(CliError::from)?);
+    } else {
+        println!("{}", ids);
+    }
+
+    Ok(())
+}
+
+/// Prints the config the daemon reports over IPC.
+///
+/// Unlike `show`, this reflects the last successful (re)load rather than the
+/// profile files on disk.
+fn handle_show_loaded(socket: Option<PathBuf>, json: bool) -> DaemonResult<()> {
+    let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+    let command_failed = |reason: String| CliError::CommandFailed {
+        command: "config show-loaded".to_string(),
+        reason,
+    };
+
+    let mut ipc = UnixSocketIpc::new(socket_path);
+    let output = match ipc
+        .send_request(&IpcRequest::GetLoadedConfig)
+        .map_err(|e| command_failed(e.to_string()))?
+    {
+        IpcResponse::LoadedConfig {
+            source_hash,
+            config,
+        } => ShowLoadedOutput {
+            source_hash,
+            config,
+        },
+        IpcResponse::Error { code, message } => {
+            return Err(command_failed(format!("Daemon error {}: {}", code, message)).into())
+        }
+        _ => return Err(command_failed("Unexpected response from daemon".to_string()).into()),
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string(&output).map_err(CliError::from)?
+        );
+    } else if let Some(config) = &output.config {
+        println!("Source hash: {}", config.metadata.source_hash);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(config).map_err(CliError::from)?
+        );
+    } else {
+        println!("No config loaded (pass-through mode)");
+    }
+
+    Ok(())
+}
+
+/// Asks the daemon over IPC to reload its .krx, or switch to `path`.
+///
+/// A relative `path` is resolved against the current directory, since the
+/// daemon may run elsewhere.
+fn handle_reload(path: Option<PathBuf>, socket: Option<PathBuf>, json: bool) -> DaemonResult<()> {
+    let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+    let command_failed = |reason: String| CliError::CommandFailed {
+        command: "config reload".to_string(),
+        reason,
+    };
+
+    let path = path
+        .map(|path| std::path::absolute(&path).map_err(|e| command_failed(e.to_string())))
+        .transpose()?
+        .map(|path| path.display().to_string());
+
+    let mut ipc = UnixSocketIpc::new(socket_path);
+    let output = match ipc
+        .send_request(&IpcRequest::ReloadConfig { path })
+        .map_err(|e| command_failed(e.to_string()))?
+    {
+        IpcResponse::ConfigReloaded { path, source_hash } => ReloadOutput { path, source_hash },
+        IpcResponse::Error { code, message } => {
+            return Err(command_failed(format!("Daemon error {}: {}", code, message)).into())
+        }
+        _ => return Err(command_failed("Unexpected response from daemon".to_string()).into()),
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string(&output).map_err(CliError::from)?
+        );
+    } else {
+        println!("Reloaded {}", output.path);
+        println!("Source hash: {}", output.source_hash);
+    }
+
+    Ok(())
+}
+
 fn handle_diff(
     manager: &ProfileManager,
     profile1: String,
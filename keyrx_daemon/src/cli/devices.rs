@@ -63,6 +63,8 @@ struct DeviceListOutput {
 struct SuccessOutput {
     success: bool,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
 }
 
 /// Execute the devices command.
@@ -204,10 +206,19 @@ fn handle_rename(
             logging::log_device_operation("rename", device_id);
             logging::log_command_success("devices rename", 0);
 
+            let warning = registry.is_path_bound(device_id).then(|| {
+                format!(
+                    "Device '{}' has no serial number, so its name is tied to its device path \
+                     and will not follow it if it reconnects at a different path",
+                    device_id
+                )
+            });
+
             if json {
                 let output = SuccessOutput {
                     success: true,
                     message: format!("Device '{}' renamed to '{}'", device_id, new_name),
+                    warning,
                 };
                 println!(
                     "{}",
@@ -215,6 +226,9 @@ fn handle_rename(
                 );
             } else {
                 println!("✓ Device '{}' renamed to '{}'", device_id, new_name);
+                if let Some(warning) = &warning {
+                    eprintln!("Warning: {}", warning);
+                }
             }
             Ok(())
         }
@@ -280,6 +294,7 @@ fn handle_forget(registry: &mut DeviceRegistry, device_id: &str, json: bool) ->
                 let output = SuccessOutput {
                     success: true,
                     message: format!("Device '{}' forgotten", device.name),
+                    warning: None,
                 };
                 println!(
                     "{}",
@@ -339,6 +354,7 @@ fn handle_set_layout(
                 let output = SuccessOutput {
                     success: true,
                     message: format!("Device '{}' layout set to '{}'", device_id, layout),
+                    warning: None,
                 };
                 println!(
                     "{}",
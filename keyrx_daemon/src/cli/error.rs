@@ -536,6 +536,12 @@ fn extract_recorder_error_details(
             Some("This is likely a bug. Please report it".to_string()),
             None,
         ),
+        RecorderError::InvalidMacro(msg) => (
+            "Recorder".to_string(),
+            format!("Invalid macro: {}", msg),
+            Some("Bind the macro to a VK_ key name".to_string()),
+            None,
+        ),
     }
 }
 
@@ -610,6 +616,7 @@ fn error_code(error: &DaemonError) -> u32 {
         DaemonError::Recorder(RecorderError::PlaybackFailed(_)) => 8002,
         DaemonError::Recorder(RecorderError::BufferFull(_)) => 8003,
         DaemonError::Recorder(RecorderError::MutexPoisoned(_)) => 8004,
+        DaemonError::Recorder(RecorderError::InvalidMacro(_)) => 8005,
 
         // Core and other errors: 9000-9999
         DaemonError::Core(_) => 9000,
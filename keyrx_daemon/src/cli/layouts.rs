@@ -3,10 +3,14 @@
 //! This module implements the `keyrx layouts` command and all its subcommands
 //! for managing keyboard layouts in KLE (keyboard-layout-editor.com) JSON format.
 
-use crate::config::layout_manager::{LayoutManager, LayoutSource};
+use crate::config::layout_manager::{
+    export_kle, validate_layout, LayoutManager, LayoutSource, LayoutValidation,
+};
 use clap::{Args, Subcommand};
+use keyrx_core::config::{ConfigRoot, DeviceConfig};
+use rkyv::Deserialize as _;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Layout management subcommands.
 #[derive(Args)]
@@ -48,6 +52,40 @@ enum LayoutsCommands {
         #[arg(long)]
         confirm: bool,
     },
+
+    /// Export a layout with a device's remapped keys as legends.
+    Export {
+        /// Compiled .krx configuration.
+        #[arg(long)]
+        config: PathBuf,
+
+        /// Device pattern whose mappings to show (as written in device_start).
+        #[arg(long, default_value = "*")]
+        device: String,
+
+        /// Layout to draw the mappings on.
+        #[arg(long, default_value = "ansi_104")]
+        layout: String,
+
+        /// Output format.
+        #[arg(long, default_value = "kle", value_parser = ["kle"])]
+        format: String,
+
+        /// Write to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check that a layout has a keycap for every key a config remaps.
+    Validate {
+        /// KLE JSON file, or the name of a builtin or imported layout.
+        #[arg(long)]
+        layout: String,
+
+        /// Compiled .krx configuration.
+        #[arg(long)]
+        config: PathBuf,
+    },
 }
 
 /// JSON output structure for layout list.
@@ -88,6 +126,16 @@ pub fn execute(args: LayoutsArgs) -> Result<(), Box<dyn std::error::Error>> {
         LayoutsCommands::Show { name } => handle_show(&name, args.json),
         LayoutsCommands::Import { path, name } => handle_import(&path, &name, args.json),
         LayoutsCommands::Delete { name, confirm } => handle_delete(&name, confirm, args.json),
+        LayoutsCommands::Export {
+            config,
+            device,
+            layout,
+            format: _,
+            output,
+        } => handle_export(&config, &device, &layout, output.as_deref()),
+        LayoutsCommands::Validate { layout, config } => {
+            handle_validate(&layout, &config, args.json)
+        }
     }
 }
 
@@ -287,6 +335,133 @@ fn handle_delete(
     }
 }
 
+/// Handle `layouts export` command.
+///
+/// The exported KLE JSON is the payload itself, so `--json` changes nothing.
+fn handle_export(
+    config: &Path,
+    device: &str,
+    layout_name: &str,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let manager = LayoutManager::new(get_layouts_dir())?;
+    let layout = manager
+        .get(layout_name)
+        .ok_or_else(|| format!("Layout '{}' not found", layout_name))?;
+
+    let device_config = load_device(config, device)?;
+    let exported = serde_json::to_string_pretty(&export_kle(&layout.kle_json, &device_config)?)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, exported + "\n")?;
+            eprintln!(
+                "✓ Exported '{}' on {} to {}",
+                device,
+                layout_name,
+                path.display()
+            );
+        }
+        None => println!("{}", exported),
+    }
+    Ok(())
+}
+
+/// Handle `layouts validate` command.
+///
+/// Exits with status 1 if the config remaps keys the layout does not draw.
+fn handle_validate(
+    layout: &str,
+    config: &Path,
+    json_output: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new(layout);
+    let kle = if path.is_file() {
+        let kle = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        LayoutManager::validate_kle(&kle)?;
+        kle
+    } else {
+        let manager = LayoutManager::new(get_layouts_dir())?;
+        manager
+            .get(layout)
+            .ok_or_else(|| format!("Layout '{}' not found", layout))?
+            .kle_json
+            .clone()
+    };
+
+    let root: ConfigRoot = crate::config_loader::load_config(config)?
+        .deserialize(&mut rkyv::Infallible)
+        .map_err(|_| "Failed to deserialize configuration")?;
+    let validation = validate_layout(&kle, &root)?;
+
+    if json_output {
+        println!("{}", serde_json::to_string_pretty(&validation)?);
+    } else {
+        print!("{}", format_validation(&validation));
+    }
+
+    if !validation.is_complete() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Formats a layout validation as human-readable text.
+fn format_validation(validation: &LayoutValidation) -> String {
+    fn key_list(keys: &[keyrx_core::config::KeyCode]) -> String {
+        let names: Vec<String> = keys.iter().map(|key| format!("{:?}", key)).collect();
+        names.join(", ")
+    }
+
+    let mut out = String::new();
+    if validation.is_complete() {
+        out.push_str("✓ Every remapped key is on the layout\n");
+    } else {
+        out.push_str(&format!(
+            "✗ Remapped keys missing from the layout: {}\n",
+            key_list(&validation.missing_from_layout)
+        ));
+    }
+    if !validation.unmapped_in_layout.is_empty() {
+        out.push_str(&format!(
+            "  Layout keys not remapped by the config: {}\n",
+            key_list(&validation.unmapped_in_layout)
+        ));
+    }
+    if !validation.unrecognized_legends.is_empty() {
+        out.push_str(&format!(
+            "  Unrecognized legends: {}\n",
+            validation.unrecognized_legends.join(", ")
+        ));
+    }
+    out
+}
+
+/// Loads the device configuration with the given pattern from a .krx file.
+fn load_device(config: &Path, pattern: &str) -> Result<DeviceConfig, Box<dyn std::error::Error>> {
+    let root = crate::config_loader::load_config(config)?;
+    let archived = root
+        .devices
+        .iter()
+        .find(|d| d.identifier.pattern.as_str() == pattern)
+        .ok_or_else(|| {
+            let available: Vec<&str> = root
+                .devices
+                .iter()
+                .map(|d| d.identifier.pattern.as_str())
+                .collect();
+            format!(
+                "Device '{}' not found in {} (available: {})",
+                pattern,
+                config.display(),
+                available.join(", ")
+            )
+        })?;
+    archived
+        .deserialize(&mut rkyv::Infallible)
+        .map_err(|_| "Failed to deserialize device configuration".into())
+}
+
 /// Get the layouts directory path.
 fn get_layouts_dir() -> PathBuf {
     // Use environment variable if set, otherwise use default
@@ -300,3 +475,31 @@ fn get_layouts_dir() -> PathBuf {
             .join("layouts")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::KeyCode;
+
+    #[test]
+    fn test_format_validation() {
+        let complete = LayoutValidation {
+            unmapped_in_layout: vec![KeyCode::Q, KeyCode::W],
+            ..LayoutValidation::default()
+        };
+        assert_eq!(
+            format_validation(&complete),
+            "✓ Every remapped key is on the layout\n  Layout keys not remapped by the config: Q, W\n"
+        );
+
+        let gaps = LayoutValidation {
+            missing_from_layout: vec![KeyCode::F13],
+            unrecognized_legends: vec!["Fn".to_string()],
+            ..LayoutValidation::default()
+        };
+        assert_eq!(
+            format_validation(&gaps),
+            "✗ Remapped keys missing from the layout: F13\n  Unrecognized legends: Fn\n"
+        );
+    }
+}
@@ -1,13 +1,19 @@
 //! Metrics CLI command.
 //!
 //! This module implements the `keyrx metrics` command for querying daemon performance
-//! metrics via IPC. Provides latency statistics and recent event tail.
+//! metrics via IPC. Provides latency statistics, failed/dropped event counters,
+//! output frame counts and recent event tail, optionally followed like `tail -f`.
 
 use crate::ipc::unix_socket::UnixSocketIpc;
-use crate::ipc::{DaemonIpc, IpcRequest, IpcResponse, DEFAULT_SOCKET_PATH};
+use crate::ipc::{DaemonIpc, DeviceLatency, IpcRequest, IpcResponse, DEFAULT_SOCKET_PATH};
 use clap::{Args, Subcommand};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often `events --follow` polls the daemon for new events.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 /// Metrics subcommands.
 #[derive(Args)]
@@ -29,7 +35,12 @@ pub struct MetricsArgs {
 #[derive(Subcommand)]
 pub enum MetricsCommand {
     /// Query latency metrics (min, avg, max, p95, p99).
-    Latency,
+    Latency {
+        /// Show statistics for each input device instead of all devices
+        /// combined.
+        #[arg(long)]
+        by_device: bool,
+    },
 
     /// Tail recent events.
     Events {
@@ -37,10 +48,19 @@ pub enum MetricsCommand {
         #[arg(short, long, default_value = "100")]
         count: usize,
 
-        /// Follow mode: continuously tail events (not implemented yet).
+        /// Follow mode: keep printing new events as they arrive, one per
+        /// line, until interrupted.
         #[arg(short, long)]
         follow: bool,
     },
+
+    /// Query failed and dropped event counters (injection failures,
+    /// capture errors, events lost to device disconnects).
+    Errors,
+
+    /// Query output events and the SYN_REPORT frames they were sent in,
+    /// to compare `--output-sync-interval` settings.
+    Output,
 }
 
 /// JSON output structure for latency metrics.
@@ -53,23 +73,55 @@ struct LatencyOutput {
     p99_us: u64,
 }
 
+/// JSON output structure for per-device latency metrics.
+#[derive(Serialize)]
+struct DeviceLatencyOutput<'a> {
+    devices: &'a BTreeMap<String, DeviceLatency>,
+}
+
+/// JSON output structure for failed/dropped event counters.
+#[derive(Serialize)]
+struct ErrorsOutput {
+    injection_failures: u64,
+    capture_errors: u64,
+    dropped_events: u64,
+}
+
+/// JSON output structure for output event/frame counters.
+#[derive(Serialize)]
+struct OutputCountsOutput {
+    events: u64,
+    frames: u64,
+}
+
 /// JSON output structure for events.
 #[derive(Serialize)]
 struct EventsOutput {
     count: usize,
     events: Vec<String>,
+    /// Id of the newest event, for polling with `GetEventsSince`.
+    last_id: u64,
+}
+
+/// JSON output structure for one event in follow mode.
+#[derive(Serialize)]
+struct FollowedEventOutput<'a> {
+    id: u64,
+    event: &'a str,
 }
 
 /// Execute the metrics command.
 pub fn execute(args: MetricsArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
-        MetricsCommand::Latency => execute_latency(args.json, args.socket),
+        MetricsCommand::Latency { by_device: false } => execute_latency(args.json, args.socket),
+        MetricsCommand::Latency { by_device: true } => {
+            execute_latency_by_device(args.json, args.socket)
+        }
         MetricsCommand::Events { count, follow } => {
-            if follow {
-                return Err("Follow mode is not implemented yet".into());
-            }
-            execute_events(count, args.json, args.socket)
+            execute_events(count, follow, args.json, args.socket)
         }
+        MetricsCommand::Errors => execute_errors(args.json, args.socket),
+        MetricsCommand::Output => execute_output(args.json, args.socket),
     }
 }
 
@@ -107,28 +159,169 @@ fn execute_latency(json: bool, socket: Option<PathBuf>) -> Result<(), Box<dyn st
     }
 }
 
+/// Execute the latency subcommand with `--by-device`.
+fn execute_latency_by_device(
+    json: bool,
+    socket: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Determine socket path
+    let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+
+    // Create IPC client
+    let mut ipc = UnixSocketIpc::new(socket_path);
+
+    // Send GetLatencyByDevice request
+    let response = ipc.send_request(&IpcRequest::GetLatencyByDevice)?;
+
+    // Parse response
+    match response {
+        IpcResponse::LatencyByDevice { devices } => {
+            if json {
+                let output = DeviceLatencyOutput { devices: &devices };
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                print!("{}", format_latency_by_device(&devices));
+            }
+            Ok(())
+        }
+        IpcResponse::Error { code, message } => {
+            Err(format!("Daemon error {}: {}", code, message).into())
+        }
+        _ => Err("Unexpected response from daemon".into()),
+    }
+}
+
 /// Execute the events subcommand.
 fn execute_events(
     count: usize,
+    follow: bool,
     json: bool,
     socket: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determine socket path
     let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
 
+    // Send GetEventsTail request
+    let (events, mut last_id) = request_events(&socket_path, &IpcRequest::GetEventsTail { count })?;
+
+    if !follow {
+        if json {
+            print_events_json(&events, last_id)?;
+        } else {
+            print_events_human(&events);
+        }
+        return Ok(());
+    }
+
+    // Poll with the newest id seen so far, so every event is printed once
+    print_followed_events(&events, last_id, json)?;
+    loop {
+        std::thread::sleep(FOLLOW_POLL_INTERVAL);
+        let (events, newest_id) = request_events(
+            &socket_path,
+            &IpcRequest::GetEventsSince { after_id: last_id },
+        )?;
+        if newest_id < last_id {
+            // The daemon restarted and its ids started over
+            last_id = 0;
+            continue;
+        }
+        print_followed_events(&events, newest_id, json)?;
+        last_id = newest_id;
+    }
+}
+
+/// Sends an events request and returns the events with the newest event id.
+fn request_events(
+    socket_path: &std::path::Path,
+    request: &IpcRequest,
+) -> Result<(Vec<String>, u64), Box<dyn std::error::Error>> {
+    // The daemon answers one request per connection
+    let mut ipc = UnixSocketIpc::new(socket_path.to_path_buf());
+
+    match ipc.send_request(request)? {
+        IpcResponse::Events { events, last_id } => Ok((events, last_id)),
+        IpcResponse::Error { code, message } => {
+            Err(format!("Daemon error {}: {}", code, message).into())
+        }
+        _ => Err("Unexpected response from daemon".into()),
+    }
+}
+
+/// Pairs events with their ids, given the id of the newest one.
+///
+/// Ids are consecutive, and the daemon always returns the newest events.
+fn number_events(events: &[String], last_id: u64) -> impl Iterator<Item = (u64, &String)> {
+    let first_id = (last_id + 1).saturating_sub(events.len() as u64);
+    (first_id..).zip(events)
+}
+
+/// Print events as they arrive in follow mode, one line each.
+fn print_followed_events(
+    events: &[String],
+    last_id: u64,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (id, event) in number_events(events, last_id) {
+        if json {
+            let output = FollowedEventOutput { id, event };
+            println!("{}", serde_json::to_string(&output)?);
+        } else {
+            println!("[{}] {}", id, event);
+        }
+    }
+    Ok(())
+}
+
+/// Execute the errors subcommand.
+fn execute_errors(json: bool, socket: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    // Determine socket path
+    let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+
     // Create IPC client
     let mut ipc = UnixSocketIpc::new(socket_path);
 
-    // Send GetEventsTail request
-    let response = ipc.send_request(&IpcRequest::GetEventsTail { count })?;
+    // Send GetErrorCounts request
+    let response = ipc.send_request(&IpcRequest::GetErrorCounts)?;
 
     // Parse response
     match response {
-        IpcResponse::Events { events } => {
+        IpcResponse::ErrorCounts {
+            injection_failures,
+            capture_errors,
+            dropped_events,
+        } => {
+            let output = ErrorsOutput {
+                injection_failures,
+                capture_errors,
+                dropped_events,
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                print_errors_human(&output);
+            }
+            Ok(())
+        }
+        IpcResponse::Error { code, message } => {
+            Err(format!("Daemon error {}: {}", code, message).into())
+        }
+        _ => Err("Unexpected response from daemon".into()),
+    }
+}
+
+/// Execute the output subcommand.
+fn execute_output(json: bool, socket: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let socket_path = socket.unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET_PATH));
+    let mut ipc = UnixSocketIpc::new(socket_path);
+
+    match ipc.send_request(&IpcRequest::GetOutputCounts)? {
+        IpcResponse::OutputCounts { events, frames } => {
+            let output = OutputCountsOutput { events, frames };
             if json {
-                print_events_json(&events)?;
+                println!("{}", serde_json::to_string_pretty(&output)?);
             } else {
-                print_events_human(&events);
+                print!("{}", format_output_counts(&output));
             }
             Ok(())
         }
@@ -139,6 +332,27 @@ fn execute_events(
     }
 }
 
+/// Formats output event/frame counters for humans.
+fn format_output_counts(output: &OutputCountsOutput) -> String {
+    let per_frame = if output.frames == 0 {
+        0.0
+    } else {
+        output.events as f64 / output.frames as f64
+    };
+    format!(
+        "Output:\n  Events:           {}\n  Frames:           {}\n  Events per frame: {:.2}\n",
+        output.events, output.frames, per_frame
+    )
+}
+
+/// Print failed/dropped event counters in human-readable format.
+fn print_errors_human(output: &ErrorsOutput) {
+    println!("Event Errors:");
+    println!("  Injection failures: {}", output.injection_failures);
+    println!("  Capture errors:     {}", output.capture_errors);
+    println!("  Dropped events:     {}", output.dropped_events);
+}
+
 /// Print latency metrics as JSON.
 fn print_latency_json(
     min_us: u64,
@@ -188,11 +402,41 @@ fn print_latency_human(min_us: u64, avg_us: u64, max_us: u64, p95_us: u64, p99_u
     );
 }
 
+/// Format per-device latency metrics, slowest p99 first.
+fn format_latency_by_device(devices: &BTreeMap<String, DeviceLatency>) -> String {
+    if devices.is_empty() {
+        return "No per-device latency samples yet\n".to_string();
+    }
+
+    let mut rows: Vec<_> = devices.iter().collect();
+    rows.sort_by(|a, b| b.1.p99_us.cmp(&a.1.p99_us).then_with(|| a.0.cmp(b.0)));
+
+    let mut out = String::from("Latency by Device (μs):\n");
+    out.push_str(&format!(
+        "  {:<32} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}\n",
+        "Device", "Min", "Avg", "Max", "P95", "P99", "Samples"
+    ));
+    for (device_id, latency) in rows {
+        out.push_str(&format!(
+            "  {:<32} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}\n",
+            device_id,
+            latency.min_us,
+            latency.avg_us,
+            latency.max_us,
+            latency.p95_us,
+            latency.p99_us,
+            latency.sample_count
+        ));
+    }
+    out
+}
+
 /// Print events as JSON.
-fn print_events_json(events: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+fn print_events_json(events: &[String], last_id: u64) -> Result<(), Box<dyn std::error::Error>> {
     let output = EventsOutput {
         count: events.len(),
         events: events.to_vec(),
+        last_id,
     };
     println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
@@ -227,17 +471,85 @@ mod tests {
         assert!(json.contains("\"p99_us\":450"));
     }
 
+    #[test]
+    fn test_format_latency_by_device_sorts_slowest_first() {
+        let latency = |p99_us| DeviceLatency {
+            min_us: 10,
+            avg_us: 20,
+            max_us: p99_us,
+            p95_us: p99_us,
+            p99_us,
+            sample_count: 5,
+        };
+        let mut devices = BTreeMap::new();
+        devices.insert("fast-kbd".to_string(), latency(100));
+        devices.insert("slow-kbd".to_string(), latency(900));
+
+        let text = format_latency_by_device(&devices);
+        let fast = text.find("fast-kbd").unwrap();
+        let slow = text.find("slow-kbd").unwrap();
+        assert!(slow < fast);
+
+        assert_eq!(
+            format_latency_by_device(&BTreeMap::new()),
+            "No per-device latency samples yet\n"
+        );
+    }
+
+    #[test]
+    fn test_errors_output_format() {
+        let output = ErrorsOutput {
+            injection_failures: 1,
+            capture_errors: 2,
+            dropped_events: 3,
+        };
+        let json = serde_json::to_string(&output).unwrap();
+        assert!(json.contains("\"injection_failures\":1"));
+        assert!(json.contains("\"capture_errors\":2"));
+        assert!(json.contains("\"dropped_events\":3"));
+    }
+
+    #[test]
+    fn test_format_output_counts() {
+        let text = format_output_counts(&OutputCountsOutput {
+            events: 9,
+            frames: 4,
+        });
+        assert!(text.contains("Frames:           4"));
+        assert!(text.contains("Events per frame: 2.25"));
+
+        let idle = format_output_counts(&OutputCountsOutput {
+            events: 0,
+            frames: 0,
+        });
+        assert!(idle.contains("Events per frame: 0.00"));
+    }
+
     #[test]
     fn test_events_output_format() {
         let events = vec!["event1".to_string(), "event2".to_string()];
         let output = EventsOutput {
             count: events.len(),
             events: events.clone(),
+            last_id: 7,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("\"count\":2"));
         assert!(json.contains("\"event1\""));
         assert!(json.contains("\"event2\""));
+        assert!(json.contains("\"last_id\":7"));
+    }
+
+    #[test]
+    fn test_number_events_ends_at_last_id() {
+        let events = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let numbered: Vec<(u64, &String)> = number_events(&events, 12).collect();
+        assert_eq!(
+            numbered,
+            vec![(10, &events[0]), (11, &events[1]), (12, &events[2])]
+        );
+
+        assert_eq!(number_events(&[], 5).count(), 0);
     }
 
     #[test]
@@ -245,6 +557,7 @@ mod tests {
         let output = EventsOutput {
             count: 0,
             events: vec![],
+            last_id: 0,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("\"count\":0"));
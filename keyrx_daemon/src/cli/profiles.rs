@@ -2,14 +2,18 @@
 //!
 //! This module implements the `keyrx profiles` command and all its subcommands
 //! for managing Rhai configuration profiles, including creation, activation,
-//! deletion, duplication, import, and export.
+//! deletion, duplication, import, export (singly or as a bundle), validation,
+//! and comparison.
 
 use crate::cli::common::output_error;
 use crate::cli::logging;
+use crate::config::bundle::{ConflictPolicy, ImportAction, ImportedItem};
 use crate::config::profile_manager::{ProfileError, ProfileTemplate};
 use crate::error::{CliError, DaemonResult};
 use crate::services::ProfileService;
 use clap::{Args, Subcommand};
+use keyrx_compiler::diff::{diff_configs, ChangeKind, MappingChange};
+use keyrx_core::config::ConfigRoot;
 use serde::Serialize;
 use std::path::{Path, PathBuf};
 
@@ -81,6 +85,34 @@ enum ProfilesCommands {
         /// Profile name.
         name: String,
     },
+
+    /// Export all profiles, device metadata, and custom layouts to one archive.
+    ExportAll {
+        /// Output archive path (e.g. keyrx-backup.tar).
+        output: PathBuf,
+    },
+
+    /// Import an archive written by `export-all`.
+    ImportAll {
+        /// Input archive path.
+        input: PathBuf,
+
+        /// What to do when a profile or layout name is taken: "skip" (default), "overwrite", or "rename".
+        #[arg(long, default_value = "skip", value_parser = parse_conflict_policy)]
+        on_conflict: ConflictPolicy,
+    },
+
+    /// Parse every profile and report which ones fail.
+    ValidateAll,
+
+    /// Show how the mappings of two profiles differ.
+    Diff {
+        /// Profile to compare from.
+        a: String,
+
+        /// Profile to compare to.
+        b: String,
+    },
 }
 
 /// JSON output structure for profile list.
@@ -123,6 +155,72 @@ struct SuccessOutput {
     message: String,
 }
 
+/// JSON output structure for validate-all.
+#[derive(Serialize)]
+struct ValidateAllOutput {
+    success: bool,
+    passed: usize,
+    failed: usize,
+    profiles: Vec<ProfileValidation>,
+}
+
+/// Validation result of a single profile.
+#[derive(Serialize)]
+struct ProfileValidation {
+    name: String,
+    valid: bool,
+    error: Option<String>,
+}
+
+/// JSON output structure for profile diff.
+#[derive(Serialize)]
+struct ProfileDiffOutput {
+    from: String,
+    to: String,
+    changes: Vec<MappingChangeOutput>,
+}
+
+/// A single mapping change for JSON serialization.
+#[derive(Serialize)]
+struct MappingChangeOutput {
+    kind: &'static str,
+    device: String,
+    condition: Option<String>,
+    key: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl From<MappingChange> for MappingChangeOutput {
+    fn from(change: MappingChange) -> Self {
+        Self {
+            kind: match change.kind {
+                ChangeKind::Added => "added",
+                ChangeKind::Removed => "removed",
+                ChangeKind::Changed => "changed",
+            },
+            device: change.device,
+            condition: change.condition,
+            key: change.key,
+            before: change.before,
+            after: change.after,
+        }
+    }
+}
+
+/// Parse conflict policy string to ConflictPolicy enum.
+fn parse_conflict_policy(s: &str) -> Result<ConflictPolicy, String> {
+    match s.to_lowercase().as_str() {
+        "skip" => Ok(ConflictPolicy::Skip),
+        "overwrite" => Ok(ConflictPolicy::Overwrite),
+        "rename" => Ok(ConflictPolicy::Rename),
+        _ => Err(format!(
+            "Invalid conflict policy '{}'. Valid policies: skip, overwrite, rename",
+            s
+        )),
+    }
+}
+
 /// Parse template string to ProfileTemplate enum.
 fn parse_template(s: &str) -> Result<ProfileTemplate, String> {
     match s.to_lowercase().as_str() {
@@ -158,6 +256,14 @@ pub async fn execute(args: ProfilesArgs, service: &ProfileService) -> DaemonResu
         ProfilesCommands::Import { input, name } => {
             handle_import(service, &input, &name, args.json).await
         }
+        ProfilesCommands::ExportAll { output } => {
+            handle_export_all(service, &output, args.json).await
+        }
+        ProfilesCommands::ImportAll { input, on_conflict } => {
+            handle_import_all(service, &input, on_conflict, args.json).await
+        }
+        ProfilesCommands::ValidateAll => handle_validate_all(service, args.json).await,
+        ProfilesCommands::Diff { a, b } => handle_diff(service, &a, &b, args.json).await,
     }
 }
 
@@ -650,6 +756,221 @@ async fn handle_import(
     }
 }
 
+/// Handle the `export-all` subcommand.
+async fn handle_export_all(
+    service: &ProfileService,
+    output: &Path,
+    json: bool,
+) -> DaemonResult<()> {
+    match service.export_bundle(output).await {
+        Ok(summary) => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&summary).map_err(CliError::from)?
+                );
+            } else {
+                println!("✓ Exported to {}", output.display());
+                println!("  Profiles: {}", summary.profiles);
+                println!("  Devices:  {}", summary.devices);
+                println!("  Layouts:  {}", summary.layouts);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            output_error(&format!("Failed to export profiles: {}", e), 3001, json);
+            Err(CliError::CommandFailed {
+                command: "profiles".to_string(),
+                reason: "Command failed".to_string(),
+            }
+            .into())
+        }
+    }
+}
+
+/// Handle the `import-all` subcommand.
+async fn handle_import_all(
+    service: &ProfileService,
+    input: &Path,
+    policy: ConflictPolicy,
+    json: bool,
+) -> DaemonResult<()> {
+    match service.import_bundle(input, policy).await {
+        Ok(summary) => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&summary).map_err(CliError::from)?
+                );
+            } else {
+                println!("✓ Imported from {}", input.display());
+                print_imported_items("Profiles", &summary.profiles);
+                print_imported_items("Layouts", &summary.layouts);
+                println!(
+                    "  Devices: {} added, {} merged",
+                    summary.devices_added, summary.devices_merged
+                );
+            }
+            Ok(())
+        }
+        Err(e) => {
+            output_error(&format!("Failed to import profiles: {}", e), 3001, json);
+            Err(CliError::CommandFailed {
+                command: "profiles".to_string(),
+                reason: "Command failed".to_string(),
+            }
+            .into())
+        }
+    }
+}
+
+fn print_imported_items(label: &str, items: &[ImportedItem]) {
+    if items.is_empty() {
+        return;
+    }
+    println!("  {}:", label);
+    for item in items {
+        match &item.action {
+            ImportAction::Added => println!("    + {}", item.name),
+            ImportAction::Overwritten => println!("    ~ {} (overwritten)", item.name),
+            ImportAction::Skipped => println!("    = {} (exists, skipped)", item.name),
+            ImportAction::Renamed(to) => println!("    + {} (as {})", item.name, to),
+        }
+    }
+}
+
+/// Handle the `validate-all` subcommand.
+async fn handle_validate_all(service: &ProfileService, json: bool) -> DaemonResult<()> {
+    logging::log_command_start("profiles validate-all", "");
+
+    let profiles = service
+        .list_profiles()
+        .await
+        .map_err(|e| CliError::CommandFailed {
+            command: "validate-all".to_string(),
+            reason: format!("Failed to list profiles: {}", e),
+        })?;
+
+    let mut results = Vec::with_capacity(profiles.len());
+    for profile in &profiles {
+        let error = service
+            .parse_profile(&profile.name)
+            .await
+            .err()
+            .map(|e| e.to_string());
+        results.push(ProfileValidation {
+            name: profile.name.clone(),
+            valid: error.is_none(),
+            error,
+        });
+    }
+
+    let failed = results.iter().filter(|r| !r.valid).count();
+    let passed = results.len() - failed;
+
+    if json {
+        let output = ValidateAllOutput {
+            success: failed == 0,
+            passed,
+            failed,
+            profiles: results,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).map_err(CliError::from)?
+        );
+    } else if results.is_empty() {
+        println!("No profiles found.");
+    } else {
+        for result in &results {
+            match &result.error {
+                None => println!("✓ {}", result.name),
+                Some(error) => {
+                    println!("✗ {}", result.name);
+                    for line in error.lines() {
+                        println!("    {}", line);
+                    }
+                }
+            }
+        }
+        println!();
+        println!("{} passed, {} failed", passed, failed);
+    }
+
+    if failed == 0 {
+        logging::log_command_success("profiles validate-all", 0);
+        Ok(())
+    } else {
+        logging::log_command_error(
+            "profiles validate-all",
+            &format!("{} profile(s) failed validation", failed),
+        );
+        Err(CliError::CommandFailed {
+            command: "profiles".to_string(),
+            reason: format!("{} profile(s) failed validation", failed),
+        }
+        .into())
+    }
+}
+
+/// Handle the `diff` subcommand.
+async fn handle_diff(service: &ProfileService, a: &str, b: &str, json: bool) -> DaemonResult<()> {
+    logging::log_command_start("profiles diff", &format!("{} {}", a, b));
+
+    let old = parse_for_diff(service, a, json).await?;
+    let new = parse_for_diff(service, b, json).await?;
+    let changes = diff_configs(&old, &new);
+
+    logging::log_command_success("profiles diff", 0);
+
+    if json {
+        let output = ProfileDiffOutput {
+            from: a.to_string(),
+            to: b.to_string(),
+            changes: changes.into_iter().map(MappingChangeOutput::from).collect(),
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&output).map_err(CliError::from)?
+        );
+    } else if changes.is_empty() {
+        println!("No mapping differences between '{}' and '{}'", a, b);
+    } else {
+        println!("Mapping differences from '{}' to '{}':", a, b);
+        for change in &changes {
+            println!("  {}", change);
+        }
+        println!();
+        println!("{} change(s)", changes.len());
+    }
+
+    Ok(())
+}
+
+/// Parse a profile for `diff`, reporting failures the same way as `activate`.
+async fn parse_for_diff(
+    service: &ProfileService,
+    name: &str,
+    json: bool,
+) -> DaemonResult<ConfigRoot> {
+    let (message, code) = match service.parse_profile(name).await {
+        Ok(config) => return Ok(config),
+        Err(ProfileError::NotFound(name)) => (format!("Profile '{}' not found", name), 1001),
+        Err(ProfileError::Compilation(e)) => {
+            (format!("Profile '{}' failed to compile: {}", name, e), 2004)
+        }
+        Err(e) => (format!("Failed to read profile '{}': {}", name, e), 3001),
+    };
+
+    logging::log_command_error("profiles diff", &message);
+    output_error(&message, code, json);
+    Err(CliError::CommandFailed {
+        command: "profiles".to_string(),
+        reason: "Command failed".to_string(),
+    }
+    .into())
+}
+
 /// Truncate a string to a maximum length.
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
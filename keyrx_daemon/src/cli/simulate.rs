@@ -1,15 +1,23 @@
 //! Simulation CLI commands.
 //!
 //! This module implements the `keyrx simulate` command for deterministic
-//! event replay testing. Supports inline event DSL, event files, and
-//! seed-based determinism.
+//! event replay testing. Supports inline event DSL, event files,
+//! seed-based determinism, shrinking failing sequences, asserting the
+//! output against a golden file, reading either from stdin with `-` so
+//! sequences can be piped in, tracing the modifier/lock/layer state after
+//! every event, reporting which mappings the events never exercised, and
+//! replaying events in real time through a virtual keyboard so the running
+//! daemon processes them.
 
 use crate::config::simulation_engine::{
     EventSequence, OutputEvent, SimulatedEvent, SimulationEngine,
 };
 use clap::Args;
-use serde::Serialize;
-use std::path::PathBuf;
+use keyrx_core::simulator::{CoverageReport, MappingCoverage, SimulationState, TimelineEntry};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Simulation subcommands.
 #[derive(Args)]
@@ -18,11 +26,12 @@ pub struct SimulateArgs {
     #[arg(long)]
     profile: Option<String>,
 
-    /// Inline event DSL (e.g., "press:A,wait:50,release:A").
+    /// Inline event DSL (e.g., "press:A,wait:50,release:A"), or `-` to
+    /// read the event sequence JSON from stdin.
     #[arg(long, conflicts_with = "events_file")]
     events: Option<String>,
 
-    /// Event file path (JSON format).
+    /// Event file path (JSON format), or `-` for stdin.
     #[arg(long, conflicts_with = "events")]
     events_file: Option<PathBuf>,
 
@@ -33,6 +42,38 @@ pub struct SimulateArgs {
     /// Output as JSON.
     #[arg(long)]
     json: bool,
+
+    /// Print the per-event timeline (same JSON shape as the web simulator).
+    #[arg(long, conflicts_with = "shrink")]
+    timeline: bool,
+
+    /// After the results, print the active modifiers, locks and layer
+    /// following each event (e.g. "MD_00 LK_01"). Off by default so long
+    /// sequences stay short.
+    #[arg(long, conflicts_with_all = ["timeline", "shrink", "assert", "json"])]
+    trace_state: bool,
+
+    /// After the results, report which mappings and conditional blocks the
+    /// events exercised, listing the ones never triggered by position.
+    #[arg(long, conflicts_with_all = ["timeline", "shrink", "assert", "json"])]
+    coverage: bool,
+
+    /// Shrink the events to the smallest subsequence whose output still
+    /// contains KEY, and print it.
+    #[arg(long, value_name = "KEY")]
+    shrink: Option<String>,
+
+    /// Compare the output events against a golden file and exit nonzero
+    /// with a diff on mismatch. The file may hold a plain array of output
+    /// events or a saved `--json` result. `-` reads it from stdin.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["timeline", "shrink"])]
+    assert: Option<PathBuf>,
+
+    /// Inject the events through a virtual keyboard, honoring the delays
+    /// between their timestamps, so the running daemon processes them.
+    /// Skips when uinput is not accessible.
+    #[arg(long, conflicts_with_all = ["timeline", "shrink", "assert", "json", "trace_state", "coverage"])]
+    replay_realtime: bool,
 }
 
 /// JSON output structure for simulation.
@@ -45,22 +86,77 @@ struct SimulationOutput {
     error: Option<String>,
 }
 
+/// Argument value that reads `--events`, `--events-file` or `--assert` from
+/// stdin.
+const STDIN_ARG: &str = "-";
+
+/// Golden file accepted by `--assert`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GoldenFile {
+    /// Plain list of expected output events.
+    Events(Vec<OutputEvent>),
+    /// Saved `--json` output; only the `output` field is compared.
+    Report { output: Vec<OutputEvent> },
+}
+
 /// Execute the simulate command.
 pub fn execute(args: SimulateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let events_from_stdin = args.events.as_deref() == Some(STDIN_ARG)
+        || args.events_file.as_deref() == Some(Path::new(STDIN_ARG));
+    if events_from_stdin && args.assert.as_deref() == Some(Path::new(STDIN_ARG)) {
+        return Err("The events and --assert cannot both be read from stdin".into());
+    }
+
+    // Load event sequence
+    let sequence = load_sequence(
+        args.events.as_deref(),
+        args.events_file.as_deref(),
+        args.seed,
+        std::io::stdin().lock(),
+    )?;
+
+    if args.replay_realtime {
+        return replay_realtime(&sequence);
+    }
+
     // Determine KRX file path
     let krx_path = resolve_krx_path(args.profile.as_deref())?;
 
     // Create simulation engine
     let mut engine = SimulationEngine::new(&krx_path)?;
 
-    // Load event sequence
-    let sequence = if let Some(events_file) = args.events_file {
-        SimulationEngine::load_events_from_file(&events_file)?
-    } else if let Some(events_dsl) = args.events {
-        SimulationEngine::parse_event_dsl(&events_dsl, args.seed)?
-    } else {
-        return Err("Either --events or --events-file must be specified".into());
-    };
+    if args.timeline {
+        return match engine.replay_timeline(&sequence) {
+            Ok(result) => {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    if let Some(key) = args.shrink {
+        let result = engine.shrink(&sequence, |output| output.iter().any(|e| e.key == key));
+        return match result {
+            Ok(shrunk) => {
+                if args.json {
+                    // Same format as --events-file, so the result can be saved and replayed
+                    println!("{}", serde_json::to_string_pretty(&shrunk)?);
+                } else {
+                    print_shrink_output(&sequence, &shrunk, &key);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+    }
 
     // Run simulation
     let result = engine.replay(&sequence);
@@ -68,11 +164,36 @@ pub fn execute(args: SimulateArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Output results
     match result {
         Ok(output) => {
+            if let Some(golden_path) = args.assert {
+                let expected = load_golden(&golden_path, std::io::stdin().lock())?;
+                if !assert_golden(&output, &expected)? {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
             if args.json {
                 print_json_output(&sequence, &output, sequence.seed, None)?;
             } else {
                 print_human_output(&sequence, &output, sequence.seed);
             }
+            if args.trace_state {
+                match engine.replay_timeline(&sequence) {
+                    Ok(result) => print_state_trace(&result.timeline),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if args.coverage {
+                match engine.replay_coverage(&sequence) {
+                    Ok(report) => print_coverage(&report),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
             Ok(())
         }
         Err(e) => {
@@ -119,6 +240,218 @@ fn get_config_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     crate::cli::config_dir::get_config_dir()
 }
 
+/// Load the input events from `--events-file`, `--events` or `stdin` when
+/// either of them is `-`.
+fn load_sequence(
+    events: Option<&str>,
+    events_file: Option<&Path>,
+    seed: u64,
+    stdin: impl Read,
+) -> Result<EventSequence, Box<dyn std::error::Error>> {
+    if events == Some(STDIN_ARG) || events_file == Some(Path::new(STDIN_ARG)) {
+        Ok(SimulationEngine::load_events_from_reader(stdin)
+            .map_err(|e| format!("Failed to read events from stdin: {}", e))?)
+    } else if let Some(events_file) = events_file {
+        Ok(SimulationEngine::load_events_from_file(events_file)?)
+    } else if let Some(events_dsl) = events {
+        Ok(SimulationEngine::parse_event_dsl(events_dsl, seed)?)
+    } else {
+        Err("Either --events or --events-file must be specified".into())
+    }
+}
+
+/// Load the expected output events from a golden file, or from `stdin` when
+/// `path` is `-`.
+fn load_golden(
+    path: &Path,
+    mut stdin: impl Read,
+) -> Result<Vec<OutputEvent>, Box<dyn std::error::Error>> {
+    let content = if path == Path::new(STDIN_ARG) {
+        let mut content = String::new();
+        stdin
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read golden output from stdin: {}", e))?;
+        content
+    } else {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read golden file {}: {}", path.display(), e))?
+    };
+    let golden: GoldenFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid golden file {}: {}", path.display(), e))?;
+
+    Ok(match golden {
+        GoldenFile::Events(events) => events,
+        GoldenFile::Report { output } => output,
+    })
+}
+
+/// Compare `actual` against `expected`, printing the result.
+///
+/// Returns `Ok(false)` on mismatch after printing the first divergence and
+/// the full diff to stderr.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn assert_golden(
+    actual: &[OutputEvent],
+    expected: &[OutputEvent],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    use crate::test_utils::compare_events;
+
+    let result = compare_events(&to_key_events(actual)?, &to_key_events(expected)?);
+    if result.passed {
+        println!("Output matches golden file ({} events)", expected.len());
+        return Ok(true);
+    }
+
+    if let Some((index, expected_event, actual_event)) = first_divergence(actual, expected) {
+        eprintln!("First divergence at index {}:", index);
+        eprintln!("  expected: {}", format_output_event(expected_event));
+        eprintln!("  actual:   {}", format_output_event(actual_event));
+        eprintln!();
+    }
+    eprint!("{}", result.format_diff());
+    Ok(false)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn assert_golden(
+    _actual: &[OutputEvent],
+    _expected: &[OutputEvent],
+) -> Result<bool, Box<dyn std::error::Error>> {
+    Err("--assert is only supported on Linux and Windows".into())
+}
+
+/// Convert simulation output into runtime events for comparison.
+///
+/// Timestamps are kept so that timing regressions are caught too.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn to_key_events(
+    events: &[OutputEvent],
+) -> Result<Vec<keyrx_core::runtime::event::KeyEvent>, Box<dyn std::error::Error>> {
+    use crate::config::simulation_engine::EventType;
+    use keyrx_core::runtime::event::KeyEvent;
+
+    events
+        .iter()
+        .map(|event| {
+            let keycode = keyrx_core::simulator::parse_keycode(&event.key)?;
+            let key_event = match event.event_type {
+                EventType::Press => KeyEvent::press(keycode),
+                EventType::Release => KeyEvent::release(keycode),
+                EventType::Repeat => KeyEvent::repeat(keycode),
+            };
+            Ok(key_event.with_timestamp(event.timestamp_us))
+        })
+        .collect()
+}
+
+/// Inject `sequence` through a virtual keyboard in real time.
+///
+/// Each event is sent at its recorded offset from the first event, so the
+/// daemon sees the same inter-event delays as the recording. Returns early
+/// with a skip message when uinput is not accessible.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn replay_realtime(sequence: &EventSequence) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::test_utils::{can_access_uinput, VirtualKeyboard};
+    use std::time::Instant;
+
+    if !can_access_uinput() {
+        eprintln!(
+            "SKIPPED: --replay-realtime - uinput/input not accessible (add user to 'uinput' and 'input' groups or run with sudo)"
+        );
+        return Ok(());
+    }
+
+    let schedule = realtime_schedule(sequence)?;
+    let mut keyboard = VirtualKeyboard::create("keyrx-replay")?;
+    println!(
+        "Replaying {} events via {}",
+        schedule.len(),
+        keyboard.name()
+    );
+
+    // Give the daemon time to detect and grab the new device
+    std::thread::sleep(Duration::from_millis(REPLAY_SETTLE_MS));
+
+    let start = Instant::now();
+    for (offset, event) in schedule {
+        // Sleep to absolute offsets so per-event overhead does not accumulate
+        if let Some(remaining) = offset.checked_sub(start.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+        keyboard.inject(event)?;
+    }
+
+    println!(
+        "Replay finished in {:.1} ms",
+        start.elapsed().as_secs_f64() * 1000.0
+    );
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn replay_realtime(_sequence: &EventSequence) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--replay-realtime is only supported on Linux and Windows".into())
+}
+
+/// Delay between creating the replay keyboard and sending the first event.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+const REPLAY_SETTLE_MS: u64 = 500;
+
+/// Convert input events into runtime events paired with their offset from
+/// the first event.
+///
+/// Offsets never go backwards, so out-of-order timestamps are sent
+/// immediately after the preceding event.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn realtime_schedule(
+    sequence: &EventSequence,
+) -> Result<Vec<(Duration, keyrx_core::runtime::event::KeyEvent)>, Box<dyn std::error::Error>> {
+    use crate::config::simulation_engine::EventType;
+    use keyrx_core::runtime::event::KeyEvent;
+
+    let base = sequence.events.first().map_or(0, |e| e.timestamp_us);
+    let mut last_offset = 0;
+
+    sequence
+        .events
+        .iter()
+        .map(|event| {
+            let keycode = keyrx_core::simulator::parse_keycode(&event.key)?;
+            let key_event = match event.event_type {
+                EventType::Press => KeyEvent::press(keycode),
+                EventType::Release => KeyEvent::release(keycode),
+                EventType::Repeat => KeyEvent::repeat(keycode),
+            };
+            last_offset = event.timestamp_us.saturating_sub(base).max(last_offset);
+            Ok((Duration::from_micros(last_offset), key_event))
+        })
+        .collect()
+}
+
+/// Find the first index where `actual` and `expected` differ.
+///
+/// Returns the index with the expected and actual events at that position;
+/// either is `None` when that list ended first.
+fn first_divergence<'a>(
+    actual: &'a [OutputEvent],
+    expected: &'a [OutputEvent],
+) -> Option<(usize, Option<&'a OutputEvent>, Option<&'a OutputEvent>)> {
+    (0..actual.len().max(expected.len()))
+        .map(|i| (i, expected.get(i), actual.get(i)))
+        .find(|(_, e, a)| e != a)
+}
+
+/// Format an optional output event for the divergence report.
+fn format_output_event(event: Option<&OutputEvent>) -> String {
+    match event {
+        Some(event) => format!(
+            "[{:>8} us] {:?} {}",
+            event.timestamp_us, event.event_type, event.key
+        ),
+        None => "(none)".to_string(),
+    }
+}
+
 /// Print human-readable output.
 fn print_human_output(sequence: &EventSequence, output: &[OutputEvent], seed: u64) {
     println!("Simulation Results (seed: {})", seed);
@@ -142,6 +475,120 @@ fn print_human_output(sequence: &EventSequence, output: &[OutputEvent], seed: u6
     }
 }
 
+/// Print the state after each event, for `--trace-state`.
+fn print_state_trace(timeline: &[TimelineEntry]) {
+    println!();
+    println!("State Trace ({}):", timeline.len());
+    for entry in timeline {
+        println!("  {}", format_trace_entry(entry));
+    }
+}
+
+/// Print the mapping coverage summary and every mapping never triggered.
+fn print_coverage(report: &CoverageReport) {
+    let exercised = report.mappings.len() - report.unexercised_mappings().count();
+    let active_blocks = report.conditions.len() - report.unexercised_conditions().count();
+
+    println!();
+    println!(
+        "Coverage: {} of {} mappings exercised, {} of {} conditional blocks",
+        exercised,
+        report.mappings.len(),
+        active_blocks,
+        report.conditions.len()
+    );
+    let unexercised: Vec<&MappingCoverage> = report.unexercised_mappings().collect();
+    if unexercised.is_empty() {
+        return;
+    }
+    println!("Never triggered:");
+    for mapping in unexercised {
+        println!("  {}", format_uncovered_mapping(mapping));
+    }
+}
+
+/// Formats a mapping as its position, condition, input key and behavior,
+/// e.g. `mappings[2][0] when MD_00: H -> Left`.
+fn format_uncovered_mapping(mapping: &MappingCoverage) -> String {
+    use keyrx_compiler::diff::{describe_condition, describe_mapping};
+
+    let position = match mapping.position.inner {
+        Some(inner) => format!("mappings[{}][{}]", mapping.position.index, inner),
+        None => format!("mappings[{}]", mapping.position.index),
+    };
+    let condition = mapping
+        .condition
+        .as_ref()
+        .map(|condition| format!(" when {}", describe_condition(condition)))
+        .unwrap_or_default();
+    let (key, behavior) = describe_mapping(&mapping.mapping);
+    format!("{}{}: {} -> {}", position, condition, key, behavior)
+}
+
+/// Formats one timeline entry as its input, outputs and resulting state.
+fn format_trace_entry(entry: &TimelineEntry) -> String {
+    let input = entry
+        .input
+        .as_ref()
+        .map(|event| format!("{} {}", event.event_type, event.keycode))
+        .unwrap_or_else(|| "(timeout)".to_string());
+    let outputs = if entry.outputs.is_empty() {
+        "(suppressed)".to_string()
+    } else {
+        entry
+            .outputs
+            .iter()
+            .map(|event| format!("{} {}", event.event_type, event.keycode))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "[{:>8} us] {} -> {} | {}",
+        entry.timestamp_us,
+        input,
+        outputs,
+        format_state(&entry.state)
+    )
+}
+
+/// Formats active modifiers, locks and layer with their `MD_xx`/`LK_xx`
+/// labels, or "(none)" if nothing is active.
+fn format_state(state: &SimulationState) -> String {
+    let mut labels: Vec<String> = state
+        .active_modifiers
+        .iter()
+        .map(|id| format!("MD_{:02X}", id))
+        .chain(state.active_locks.iter().map(|id| format!("LK_{:02X}", id)))
+        .collect();
+    if let Some(layer) = &state.active_layer {
+        labels.push(format!("layer {}", layer));
+    }
+    if labels.is_empty() {
+        "(none)".to_string()
+    } else {
+        labels.join(" ")
+    }
+}
+
+/// Print the result of shrinking a failing sequence.
+fn print_shrink_output(original: &EventSequence, shrunk: &EventSequence, key: &str) {
+    println!(
+        "Shrunk {} events to {} that still output {} (seed: {})",
+        original.events.len(),
+        shrunk.events.len(),
+        key,
+        shrunk.seed
+    );
+    println!();
+    for event in &shrunk.events {
+        let device = event.device_id.as_deref().unwrap_or("default");
+        println!(
+            "  [{:>8} us] {:?} {} (device: {})",
+            event.timestamp_us, event.event_type, event.key, device
+        );
+    }
+}
+
 /// Print JSON output.
 fn print_json_output(
     sequence: &EventSequence,
@@ -230,4 +677,217 @@ mod tests {
         let result = print_json_output(&sequence, &output, 42, None);
         assert!(result.is_ok());
     }
+
+    fn simulated_event(key: &str, timestamp_us: u64) -> SimulatedEvent {
+        SimulatedEvent {
+            device_id: None,
+            timestamp_us,
+            key: key.to_string(),
+            event_type: crate::config::simulation_engine::EventType::Press,
+        }
+    }
+
+    #[test]
+    fn test_realtime_schedule_offsets_from_first_event() {
+        let sequence = EventSequence {
+            events: vec![
+                simulated_event("A", 1_000),
+                simulated_event("B", 51_000),
+                simulated_event("C", 40_000),
+            ],
+            seed: 0,
+        };
+
+        let offsets: Vec<Duration> = realtime_schedule(&sequence)
+            .expect("schedule should build")
+            .into_iter()
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_eq!(
+            offsets,
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(50),
+                Duration::from_millis(50)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_realtime_schedule_rejects_unknown_key() {
+        let sequence = EventSequence {
+            events: vec![simulated_event("NotAKey", 0)],
+            seed: 0,
+        };
+        assert!(realtime_schedule(&sequence).is_err());
+    }
+
+    #[test]
+    fn test_replay_realtime_honors_delays() {
+        crate::skip_if_no_uinput!();
+        let mut release = simulated_event("A", 30_000);
+        release.event_type = crate::config::simulation_engine::EventType::Release;
+        let sequence = EventSequence {
+            events: vec![simulated_event("A", 0), release],
+            seed: 0,
+        };
+
+        let start = std::time::Instant::now();
+        replay_realtime(&sequence).expect("replay should succeed");
+        assert!(start.elapsed() >= Duration::from_millis(REPLAY_SETTLE_MS + 30));
+    }
+
+    fn output_event(key: &str, timestamp_us: u64) -> OutputEvent {
+        OutputEvent {
+            key: key.to_string(),
+            event_type: crate::config::simulation_engine::EventType::Press,
+            timestamp_us,
+        }
+    }
+
+    #[test]
+    fn test_load_golden_accepts_array_and_json_report() {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory for test");
+
+        let array_path = temp_dir.path().join("array.json");
+        std::fs::write(
+            &array_path,
+            r#"[{"key":"A","event_type":"press","timestamp_us":0}]"#,
+        )
+        .expect("Failed to write golden file");
+        assert_eq!(
+            load_golden(&array_path, std::io::empty()).expect("array golden should load"),
+            vec![output_event("A", 0)]
+        );
+
+        let report_path = temp_dir.path().join("report.json");
+        std::fs::write(
+            &report_path,
+            r#"{"success":true,"input":[],"output":[{"key":"A","event_type":"press","timestamp_us":0}],"seed":0,"error":null}"#,
+        )
+        .expect("Failed to write golden file");
+        assert_eq!(
+            load_golden(&report_path, std::io::empty()).expect("report golden should load"),
+            vec![output_event("A", 0)]
+        );
+    }
+
+    #[test]
+    fn test_load_sequence_and_golden_from_stdin() {
+        let stdin = r#"{"events":[{"device_id":null,"timestamp_us":0,"key":"B","event_type":"press"}],"seed":7}"#;
+        let sequence = load_sequence(Some(STDIN_ARG), None, 0, stdin.as_bytes())
+            .expect("events should load from stdin");
+        assert_eq!(sequence.seed, 7);
+        assert_eq!(sequence.events.len(), 1);
+        assert_eq!(sequence.events[0].key, "B");
+
+        let sequence = load_sequence(None, Some(Path::new(STDIN_ARG)), 0, stdin.as_bytes())
+            .expect("events file should load from stdin");
+        assert_eq!(sequence.events.len(), 1);
+
+        let golden = r#"[{"key":"A","event_type":"press","timestamp_us":0}]"#;
+        assert_eq!(
+            load_golden(Path::new(STDIN_ARG), golden.as_bytes())
+                .expect("golden should load from stdin"),
+            vec![output_event("A", 0)]
+        );
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let expected = vec![output_event("A", 0), output_event("B", 10)];
+
+        assert_eq!(first_divergence(&expected, &expected), None);
+
+        let actual = vec![output_event("A", 0), output_event("C", 10)];
+        assert_eq!(
+            first_divergence(&actual, &expected),
+            Some((1, Some(&expected[1]), Some(&actual[1])))
+        );
+
+        let actual = vec![output_event("A", 0)];
+        assert_eq!(
+            first_divergence(&actual, &expected),
+            Some((1, Some(&expected[1]), None))
+        );
+    }
+
+    #[test]
+    fn test_format_trace_entry_labels_state() {
+        use keyrx_core::simulator::SimKeyEvent;
+
+        let sim_event = |keycode: &str, event_type: &str| SimKeyEvent {
+            keycode: keycode.to_string(),
+            event_type: event_type.to_string(),
+            timestamp_us: 1_000,
+            device_id: None,
+        };
+        let mut entry = TimelineEntry {
+            timestamp_us: 1_000,
+            input: Some(sim_event("CapsLock", "press")),
+            outputs: Vec::new(),
+            state: SimulationState {
+                active_modifiers: vec![0, 0x1A],
+                active_locks: vec![1],
+                active_layer: None,
+            },
+            latency_us: 0,
+            mapping: None,
+        };
+        assert_eq!(
+            format_trace_entry(&entry),
+            "[    1000 us] press CapsLock -> (suppressed) | MD_00 MD_1A LK_01"
+        );
+
+        entry.outputs = vec![sim_event("B", "press")];
+        entry.state = SimulationState {
+            active_modifiers: Vec::new(),
+            active_locks: Vec::new(),
+            active_layer: None,
+        };
+        assert_eq!(
+            format_trace_entry(&entry),
+            "[    1000 us] press CapsLock -> press B | (none)"
+        );
+    }
+
+    #[test]
+    fn test_format_uncovered_mapping_names_position_and_condition() {
+        use keyrx_core::config::{BaseKeyMapping, Condition, KeyCode};
+        use keyrx_core::runtime::MappingPosition;
+
+        let conditional = MappingCoverage {
+            position: MappingPosition {
+                index: 2,
+                inner: Some(0),
+            },
+            mapping: BaseKeyMapping::Simple {
+                from: KeyCode::H,
+                to: KeyCode::Left,
+            },
+            condition: Some(Condition::ModifierActive(0)),
+            hits: 0,
+        };
+        assert_eq!(
+            format_uncovered_mapping(&conditional),
+            "mappings[2][0] when MD_00: H -> Left"
+        );
+
+        let base = MappingCoverage {
+            position: MappingPosition {
+                index: 0,
+                inner: None,
+            },
+            mapping: BaseKeyMapping::Modifier {
+                from: KeyCode::CapsLock,
+                modifier_id: 0,
+            },
+            condition: None,
+            hits: 0,
+        };
+        assert_eq!(
+            format_uncovered_mapping(&base),
+            "mappings[0]: CapsLock -> MD_00"
+        );
+    }
 }
@@ -2,13 +2,16 @@
 //!
 //! This module implements the `keyrx state inspect` command for querying the
 //! daemon's current runtime state via IPC. Displays the 255-bit modifier/lock
-//! state as a JSON array or human-readable format.
+//! state as a JSON array or human-readable format. With `--watch`, the state
+//! is polled and only transitions (modifiers/locks turning on or off) are
+//! printed.
 
 use crate::ipc::unix_socket::UnixSocketIpc;
 use crate::ipc::{DaemonIpc, IpcRequest, IpcResponse, DEFAULT_SOCKET_PATH};
 use clap::Args;
 use serde::Serialize;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 /// State subcommand arguments.
 #[derive(Args)]
@@ -35,6 +38,14 @@ pub struct InspectArgs {
     /// Custom socket path (defaults to /tmp/keyrx-daemon.sock).
     #[arg(long)]
     pub socket: Option<PathBuf>,
+
+    /// Keep polling and print each modifier/lock that turns on or off.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Polling interval in milliseconds for --watch.
+    #[arg(long, default_value = "100", requires = "watch")]
+    pub interval: u64,
 }
 
 /// JSON output structure for state.
@@ -42,10 +53,101 @@ pub struct InspectArgs {
 struct StateOutput {
     /// 255-bit state array (true = active, false = inactive)
     state: Vec<bool>,
+    /// Lock bits, when the daemon reports them separately from modifiers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locks: Vec<bool>,
     /// Number of active bits
     active_count: usize,
 }
 
+/// Modifier and lock bits from one `GetState` response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct StateSnapshot {
+    /// Modifier bits, or the combined modifier/lock bits if `locks` is empty
+    state: Vec<bool>,
+    /// Lock bits (empty if the daemon reports a single combined vector)
+    locks: Vec<bool>,
+}
+
+impl StateSnapshot {
+    /// Returns the labels of every active bit.
+    fn active_labels(&self) -> Vec<String> {
+        self.bits()
+            .filter(|(_, active)| *active)
+            .map(|(label, _)| label)
+            .collect()
+    }
+
+    /// Iterates over `(label, active)` for every bit.
+    fn bits(&self) -> impl Iterator<Item = (String, bool)> + '_ {
+        let separate_locks = !self.locks.is_empty();
+        let state_bits = self.state.iter().enumerate().map(move |(idx, &active)| {
+            (bit_label(BitKind::from_state(separate_locks), idx), active)
+        });
+        let lock_bits = self
+            .locks
+            .iter()
+            .enumerate()
+            .map(|(idx, &active)| (bit_label(BitKind::Lock, idx), active));
+        state_bits.chain(lock_bits)
+    }
+}
+
+/// What a bit in the state vector represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitKind {
+    Modifier,
+    Lock,
+    /// Combined modifier/lock vector, where the bit cannot be attributed
+    Combined,
+}
+
+impl BitKind {
+    fn from_state(separate_locks: bool) -> Self {
+        if separate_locks {
+            BitKind::Modifier
+        } else {
+            BitKind::Combined
+        }
+    }
+}
+
+/// Formats a bit index with the MD_XX/LK_XX labels used by the DSL.
+fn bit_label(kind: BitKind, idx: usize) -> String {
+    match kind {
+        BitKind::Modifier => format!("MD_{:02X}", idx),
+        BitKind::Lock => format!("LK_{:02X}", idx),
+        BitKind::Combined => format!("bit {}", idx),
+    }
+}
+
+/// A single modifier or lock turning on or off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct StateTransition {
+    /// MD_XX / LK_XX label of the bit
+    label: String,
+    /// True if the bit turned on, false if it turned off
+    active: bool,
+}
+
+/// Returns the bits that differ between two snapshots, modifiers first.
+fn diff_state(previous: &StateSnapshot, current: &StateSnapshot) -> Vec<StateTransition> {
+    let previous_bits: std::collections::HashMap<String, bool> = previous.bits().collect();
+    current
+        .bits()
+        .filter(|(label, active)| previous_bits.get(label).copied().unwrap_or(false) != *active)
+        .map(|(label, active)| StateTransition { label, active })
+        .collect()
+}
+
+/// JSON line printed for each transition in watch mode.
+#[derive(Serialize)]
+struct TransitionOutput<'a> {
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    transition: &'a StateTransition,
+}
+
 /// Execute the state command.
 pub fn execute(args: StateArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
@@ -63,19 +165,27 @@ fn execute_inspect(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>>
     // Create IPC client
     let mut ipc = UnixSocketIpc::new(socket_path);
 
-    // Send GetState request
-    let response = ipc.send_request(&IpcRequest::GetState)?;
+    if args.watch {
+        return watch_state(
+            &mut ipc,
+            Duration::from_millis(args.interval.max(1)),
+            args.json,
+        );
+    }
+
+    let snapshot = query_state(&mut ipc)?;
+    if args.json {
+        print_json_output(&snapshot)?;
+    } else {
+        print_human_output(&snapshot);
+    }
+    Ok(())
+}
 
-    // Parse response
-    match response {
-        IpcResponse::State { state } => {
-            if args.json {
-                print_json_output(&state)?;
-            } else {
-                print_human_output(&state);
-            }
-            Ok(())
-        }
+/// Send a GetState request and return the reported bits.
+fn query_state(ipc: &mut UnixSocketIpc) -> Result<StateSnapshot, Box<dyn std::error::Error>> {
+    match ipc.send_request(&IpcRequest::GetState)? {
+        IpcResponse::State { state, locks } => Ok(StateSnapshot { state, locks }),
         IpcResponse::Error { code, message } => {
             Err(format!("Daemon error {}: {}", code, message).into())
         }
@@ -83,11 +193,72 @@ fn execute_inspect(args: InspectArgs) -> Result<(), Box<dyn std::error::Error>>
     }
 }
 
+/// Poll the daemon state and print transitions until the daemon goes away.
+fn watch_state(
+    ipc: &mut UnixSocketIpc,
+    interval: Duration,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let mut previous = query_state(ipc)?;
+
+    if json {
+        // Report the initial state as transitions from all-off
+        print_transitions_json(start, &diff_state(&StateSnapshot::default(), &previous))?;
+    } else {
+        let active = previous.active_labels();
+        if active.is_empty() {
+            println!("Watching state (nothing active). Press Ctrl+C to stop.");
+        } else {
+            println!(
+                "Watching state (active: {}). Press Ctrl+C to stop.",
+                active.join(", ")
+            );
+        }
+    }
+
+    loop {
+        std::thread::sleep(interval);
+        let current = query_state(ipc)?;
+        let transitions = diff_state(&previous, &current);
+        if json {
+            print_transitions_json(start, &transitions)?;
+        } else {
+            for transition in &transitions {
+                println!(
+                    "[{:>9.3}s] {} {}",
+                    start.elapsed().as_secs_f64(),
+                    if transition.active { "+" } else { "-" },
+                    transition.label
+                );
+            }
+        }
+        previous = current;
+    }
+}
+
+/// Print one JSON object per line for each transition.
+fn print_transitions_json(
+    start: Instant,
+    transitions: &[StateTransition],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let elapsed_ms = start.elapsed().as_millis();
+    for transition in transitions {
+        let output = TransitionOutput {
+            elapsed_ms,
+            transition,
+        };
+        println!("{}", serde_json::to_string(&output)?);
+    }
+    Ok(())
+}
+
 /// Print JSON output.
-fn print_json_output(state: &[bool]) -> Result<(), Box<dyn std::error::Error>> {
-    let active_count = state.iter().filter(|&&b| b).count();
+fn print_json_output(snapshot: &StateSnapshot) -> Result<(), Box<dyn std::error::Error>> {
+    let active_count = snapshot.bits().filter(|(_, active)| *active).count();
     let output = StateOutput {
-        state: state.to_vec(),
+        state: snapshot.state.clone(),
+        locks: snapshot.locks.clone(),
         active_count,
     };
     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -95,22 +266,20 @@ fn print_json_output(state: &[bool]) -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Print human-readable output.
-fn print_human_output(state: &[bool]) {
+fn print_human_output(snapshot: &StateSnapshot) {
     println!("Runtime State (255-bit modifier/lock state):");
     println!();
 
-    let active_count = state.iter().filter(|&&b| b).count();
-    println!("  Active bits: {}/255", active_count);
+    let active = snapshot.active_labels();
+    println!("  Active bits: {}", active.len());
     println!();
 
-    if active_count == 0 {
+    if active.is_empty() {
         println!("  (No modifiers or locks currently active)");
     } else {
-        println!("  Active bit indices:");
-        for (idx, &bit) in state.iter().enumerate() {
-            if bit {
-                println!("    - Bit {}", idx);
-            }
+        println!("  Active:");
+        for label in active {
+            println!("    - {}", label);
         }
     }
 }
@@ -128,6 +297,7 @@ mod tests {
 
         let output = StateOutput {
             state: state.clone(),
+            locks: Vec::new(),
             active_count: 3,
         };
         let json = serde_json::to_string(&output).unwrap();
@@ -140,17 +310,99 @@ mod tests {
         let state = vec![false; 255];
         let output = StateOutput {
             state: state.clone(),
+            locks: Vec::new(),
             active_count: 0,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("\"active_count\":0"));
     }
 
+    fn snapshot(modifiers: &[usize], locks: &[usize]) -> StateSnapshot {
+        let mut snapshot = StateSnapshot {
+            state: vec![false; 255],
+            locks: vec![false; 255],
+        };
+        for &idx in modifiers {
+            snapshot.state[idx] = true;
+        }
+        for &idx in locks {
+            snapshot.locks[idx] = true;
+        }
+        snapshot
+    }
+
+    #[test]
+    fn test_active_labels_use_dsl_names() {
+        let state = snapshot(&[0x00, 0x1A], &[0x02]);
+        assert_eq!(state.active_labels(), vec!["MD_00", "MD_1A", "LK_02"]);
+    }
+
+    #[test]
+    fn test_active_labels_combined_vector() {
+        let mut state = vec![false; 255];
+        state[7] = true;
+        let snapshot = StateSnapshot {
+            state,
+            locks: Vec::new(),
+        };
+        assert_eq!(snapshot.active_labels(), vec!["bit 7"]);
+    }
+
+    #[test]
+    fn test_diff_state_reports_only_transitions() {
+        let previous = snapshot(&[0x00, 0x01], &[0x02]);
+        let current = snapshot(&[0x01, 0x05], &[]);
+
+        assert_eq!(
+            diff_state(&previous, &current),
+            vec![
+                StateTransition {
+                    label: "MD_00".to_string(),
+                    active: false,
+                },
+                StateTransition {
+                    label: "MD_05".to_string(),
+                    active: true,
+                },
+                StateTransition {
+                    label: "LK_02".to_string(),
+                    active: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_state_unchanged_is_empty() {
+        let state = snapshot(&[0x03], &[0x04]);
+        assert!(diff_state(&state, &state).is_empty());
+    }
+
+    #[test]
+    fn test_diff_state_from_empty_snapshot() {
+        let current = snapshot(&[0x10], &[0xFE]);
+        let transitions = diff_state(&StateSnapshot::default(), &current);
+        assert_eq!(
+            transitions,
+            vec![
+                StateTransition {
+                    label: "MD_10".to_string(),
+                    active: true,
+                },
+                StateTransition {
+                    label: "LK_FE".to_string(),
+                    active: true,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_state_output_all_active() {
         let state = vec![true; 255];
         let output = StateOutput {
             state: state.clone(),
+            locks: Vec::new(),
             active_count: 255,
         };
         let json = serde_json::to_string(&output).unwrap();
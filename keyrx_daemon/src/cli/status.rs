@@ -1,7 +1,7 @@
 //! Status CLI command.
 //!
 //! This module implements the `keyrx status` command for querying daemon status
-//! via IPC. Displays running state, uptime, active profile, and device count.
+//! via IPC. Displays running state, uptime, active profile, and device counts.
 
 use crate::ipc::unix_socket::UnixSocketIpc;
 use crate::ipc::{DaemonIpc, IpcRequest, IpcResponse, DEFAULT_SOCKET_PATH};
@@ -27,7 +27,12 @@ struct StatusOutput {
     running: bool,
     uptime_secs: u64,
     active_profile: Option<String>,
+    /// Devices matched by the configuration
     device_count: usize,
+    /// Matched devices actually being captured
+    active_device_count: usize,
+    /// Port the web server bound
+    web_port: Option<u16>,
 }
 
 /// Execute the status command.
@@ -50,11 +55,21 @@ pub fn execute(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
             uptime_secs,
             active_profile,
             device_count,
+            active_device_count,
+            web_port,
         } => {
+            let output = StatusOutput {
+                running,
+                uptime_secs,
+                active_profile,
+                device_count,
+                active_device_count,
+                web_port,
+            };
             if args.json {
-                print_json_output(running, uptime_secs, active_profile, device_count)?;
+                println!("{}", serde_json::to_string_pretty(&output)?);
             } else {
-                print_human_output(running, uptime_secs, active_profile, device_count);
+                print_human_output(output);
             }
             Ok(())
         }
@@ -65,30 +80,17 @@ pub fn execute(args: StatusArgs) -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
-/// Print JSON output.
-fn print_json_output(
-    running: bool,
-    uptime_secs: u64,
-    active_profile: Option<String>,
-    device_count: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let output = StatusOutput {
+/// Print human-readable output.
+fn print_human_output(output: StatusOutput) {
+    let StatusOutput {
         running,
         uptime_secs,
         active_profile,
         device_count,
-    };
-    println!("{}", serde_json::to_string_pretty(&output)?);
-    Ok(())
-}
+        active_device_count,
+        web_port,
+    } = output;
 
-/// Print human-readable output.
-fn print_human_output(
-    running: bool,
-    uptime_secs: u64,
-    active_profile: Option<String>,
-    device_count: usize,
-) {
     println!("Daemon Status:");
     println!("  Running:        {}", if running { "Yes" } else { "No" });
     println!("  Uptime:         {} seconds", uptime_secs);
@@ -103,7 +105,28 @@ fn print_human_output(
         "  Active Profile: {}",
         active_profile.unwrap_or_else(|| "None".to_string())
     );
-    println!("  Device Count:   {}", device_count);
+    println!(
+        "  Devices:        {}",
+        format_device_counts(device_count, active_device_count)
+    );
+    if let Some(port) = web_port {
+        println!("  Web UI Port:    {}", port);
+    }
+}
+
+/// Formats matched and active device counts, flagging matched devices that
+/// are not being remapped.
+fn format_device_counts(device_count: usize, active_device_count: usize) -> String {
+    let counts = format!("{} matched, {} active", device_count, active_device_count);
+    if active_device_count < device_count {
+        format!(
+            "{} ({} not grabbed, keys pass through unmapped)",
+            counts,
+            device_count - active_device_count
+        )
+    } else {
+        counts
+    }
 }
 
 #[cfg(test)]
@@ -117,12 +140,16 @@ mod tests {
             uptime_secs: 3661,
             active_profile: Some("default".to_string()),
             device_count: 2,
+            active_device_count: 1,
+            web_port: Some(9868),
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("\"running\":true"));
         assert!(json.contains("\"uptime_secs\":3661"));
         assert!(json.contains("\"active_profile\":\"default\""));
         assert!(json.contains("\"device_count\":2"));
+        assert!(json.contains("\"active_device_count\":1"));
+        assert!(json.contains("\"web_port\":9868"));
     }
 
     #[test]
@@ -132,9 +159,20 @@ mod tests {
             uptime_secs: 0,
             active_profile: None,
             device_count: 0,
+            active_device_count: 0,
+            web_port: None,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("\"running\":false"));
         assert!(json.contains("\"active_profile\":null"));
     }
+
+    #[test]
+    fn test_format_device_counts_flags_ungrabbed() {
+        assert_eq!(format_device_counts(2, 2), "2 matched, 2 active");
+        assert_eq!(
+            format_device_counts(3, 1),
+            "3 matched, 1 active (2 not grabbed, keys pass through unmapped)"
+        );
+    }
 }
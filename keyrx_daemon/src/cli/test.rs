@@ -1,17 +1,28 @@
 //! Test CLI commands.
 //!
 //! This module implements the `keyrx test` command for autonomous testing
-//! using built-in scenarios. Provides pass/fail reporting for configuration
-//! validation.
-
-use crate::config::simulation_engine::{BuiltinScenario, ScenarioResult, SimulationEngine};
-use clap::Args;
+//! using built-in scenarios or user-authored scenario files. Provides
+//! pass/fail reporting for configuration validation. `keyrx test --list`
+//! prints the built-in scenarios, `keyrx test bench` measures
+//! event-processing throughput for a compiled configuration, and
+//! `keyrx test auto` checks that every mapping of a compiled configuration
+//! behaves as declared.
+
+use crate::config::auto_test::{self, CheckStatus, MappingCheck};
+use crate::config::benchmark::{self, BenchmarkReport, MAX_BENCH_EVENTS};
+use crate::config::simulation_engine::{
+    run_scenario_file, BuiltinScenario, ScenarioResult, SimulationEngine,
+};
+use clap::{Args, Subcommand};
 use serde::Serialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Test subcommands.
 #[derive(Args)]
 pub struct TestArgs {
+    #[command(subcommand)]
+    pub command: Option<TestCommands>,
+
     /// Profile name to test (defaults to current active profile).
     #[arg(long)]
     pub profile: Option<String>,
@@ -20,11 +31,44 @@ pub struct TestArgs {
     #[arg(long, default_value = "all")]
     pub scenario: String,
 
+    /// List the built-in scenarios and exit.
+    #[arg(long, conflicts_with_all = ["scenario_file", "profile"])]
+    pub list: bool,
+
+    /// Run the scenarios in a JSON scenario file instead of the built-in
+    /// ones. The file names a Rhai config and lists events with expected
+    /// outputs for each scenario, plus optional `timing` sweeps that check
+    /// the output at several delays between two events and report where it
+    /// changes.
+    #[arg(long, value_name = "FILE", conflicts_with = "profile")]
+    pub scenario_file: Option<PathBuf>,
+
     /// Output as JSON.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub json: bool,
 }
 
+#[derive(Subcommand)]
+pub enum TestCommands {
+    /// Measure event-processing throughput and latency for a compiled config.
+    Bench {
+        /// Path to the compiled .krx configuration.
+        #[arg(long)]
+        config: PathBuf,
+
+        /// Number of synthetic events to process.
+        #[arg(long, default_value = "100000")]
+        events: usize,
+    },
+    /// Tap every mapping of a compiled config, with its `when` condition set
+    /// up, and check the output matches what the mapping declares.
+    Auto {
+        /// Path to the compiled .krx configuration.
+        #[arg(long)]
+        config: PathBuf,
+    },
+}
+
 /// JSON output structure for test results.
 #[derive(Serialize)]
 struct TestOutput {
@@ -36,25 +80,65 @@ struct TestOutput {
     results: Vec<ScenarioResult>,
 }
 
+/// JSON output structure for `test auto`.
+#[derive(Serialize)]
+struct AutoTestOutput {
+    success: bool,
+    config: String,
+    total: usize,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    mappings: Vec<MappingCheck>,
+}
+
+/// JSON output structure for `--list`.
+#[derive(Serialize)]
+struct ScenarioInfo {
+    name: &'static str,
+    description: &'static str,
+}
+
 /// Execute the test command.
 pub fn execute(args: TestArgs) -> Result<(), Box<dyn std::error::Error>> {
-    // Determine KRX file path
-    let krx_path = resolve_krx_path(args.profile.as_deref())?;
-    let profile_name = args
-        .profile
-        .clone()
-        .unwrap_or_else(|| "default".to_string());
-
-    // Create simulation engine
-    let mut engine = SimulationEngine::new(&krx_path)?;
-
-    // Run scenarios
-    let results = if args.scenario == "all" {
-        engine.run_all_scenarios()?
+    match &args.command {
+        Some(TestCommands::Bench { config, events }) => {
+            return execute_bench(config, *events, args.json);
+        }
+        Some(TestCommands::Auto { config }) => return execute_auto(config, args.json),
+        None => {}
+    }
+
+    if args.list {
+        return print_scenario_list(args.json);
+    }
+
+    let (kind, name, results) = if let Some(scenario_file) = &args.scenario_file {
+        (
+            "scenario file",
+            scenario_file.display().to_string(),
+            run_scenario_file(scenario_file)?,
+        )
     } else {
-        // Parse scenario name
-        let scenario = parse_scenario_name(&args.scenario)?;
-        vec![engine.run_scenario(scenario)?]
+        // Determine KRX file path
+        let krx_path = resolve_krx_path(args.profile.as_deref())?;
+        let profile_name = args
+            .profile
+            .clone()
+            .unwrap_or_else(|| "default".to_string());
+
+        // Create simulation engine
+        let mut engine = SimulationEngine::new(&krx_path)?;
+
+        // Run scenarios
+        let results = if args.scenario == "all" {
+            engine.run_all_scenarios()?
+        } else {
+            // Parse scenario name
+            let scenario = parse_scenario_name(&args.scenario)?;
+            vec![engine.run_scenario(scenario)?]
+        };
+        ("profile", profile_name, results)
     };
 
     // Calculate pass/fail counts
@@ -65,9 +149,9 @@ pub fn execute(args: TestArgs) -> Result<(), Box<dyn std::error::Error>> {
 
     // Output results
     if args.json {
-        print_json_output(&profile_name, total, passed, failed, success, &results)?;
+        print_json_output(&name, total, passed, failed, success, &results)?;
     } else {
-        print_human_output(&profile_name, total, passed, failed, &results);
+        print_human_output(kind, &name, total, passed, failed, &results);
     }
 
     // Return error if any tests failed (main.rs will call std::process::exit(1))
@@ -78,6 +162,130 @@ pub fn execute(args: TestArgs) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Execute the bench subcommand.
+fn execute_bench(
+    config: &Path,
+    events: usize,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if events == 0 || events > MAX_BENCH_EVENTS {
+        return Err(format!("--events must be between 1 and {}", MAX_BENCH_EVENTS).into());
+    }
+
+    let device_config = benchmark::load_device_config(config)?;
+    let input = benchmark::generate_events(&device_config, events);
+    let report = benchmark::run_benchmark(&device_config, &input);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_bench_output(config, &report);
+    }
+    Ok(())
+}
+
+/// Print human-readable benchmark output.
+fn print_bench_output(config: &Path, report: &BenchmarkReport) {
+    println!("Benchmarking: {}", config.display());
+    println!();
+    println!(
+        "  Events:     {} in, {} out",
+        report.events, report.output_events
+    );
+    println!(
+        "  Total time: {:.3} ms",
+        report.total_ns as f64 / 1_000_000.0
+    );
+    println!("  Throughput: {:.0} events/sec", report.events_per_sec);
+    println!(
+        "  Latency:    p50 {} ns, p90 {} ns, p99 {} ns, max {} ns",
+        report.p50_ns, report.p90_ns, report.p99_ns, report.max_ns
+    );
+}
+
+/// Execute the auto subcommand.
+fn execute_auto(config: &Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config_root = crate::config_loader::read_krx(config)?;
+    let checks = auto_test::check_config(&config_root);
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let total = checks.len();
+    let passed = count(CheckStatus::Passed);
+    let failed = count(CheckStatus::Failed);
+    let skipped = count(CheckStatus::Skipped);
+
+    if json {
+        let output = AutoTestOutput {
+            success: failed == 0,
+            config: config.display().to_string(),
+            total,
+            passed,
+            failed,
+            skipped,
+            mappings: checks,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_auto_output(config, &checks);
+        println!();
+        println!(
+            "Results: {} total, {} passed, {} failed, {} skipped",
+            total, passed, failed, skipped
+        );
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} mappings failed", failed, total).into());
+    }
+    Ok(())
+}
+
+/// Print one line per mapping, with the reason for failures and skips.
+fn print_auto_output(config: &Path, checks: &[MappingCheck]) {
+    println!("Checking mappings: {}", config.display());
+
+    let mut device: Option<&str> = None;
+    for check in checks {
+        if device != Some(check.device.as_str()) {
+            println!();
+            println!("[{}]", check.device);
+            device = Some(&check.device);
+        }
+
+        let status = match check.status {
+            CheckStatus::Passed => "✓ PASS",
+            CheckStatus::Failed => "✗ FAIL",
+            CheckStatus::Skipped => "- SKIP",
+        };
+        println!("  {} {} {}", status, check.location(), check.mapping);
+        if let Some(detail) = &check.detail {
+            println!("    {}", detail);
+        }
+    }
+}
+
+/// Print the built-in scenarios with their descriptions.
+fn print_scenario_list(json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let scenarios: Vec<ScenarioInfo> = BuiltinScenario::all()
+        .iter()
+        .map(|scenario| ScenarioInfo {
+            name: scenario.name(),
+            description: scenario.description(),
+        })
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&scenarios)?);
+        return Ok(());
+    }
+
+    println!("Built-in scenarios:");
+    for scenario in &scenarios {
+        println!("  {:<26} {}", scenario.name, scenario.description);
+    }
+    Ok(())
+}
+
 /// Parse scenario name string into BuiltinScenario enum.
 fn parse_scenario_name(name: &str) -> Result<BuiltinScenario, Box<dyn std::error::Error>> {
     match name {
@@ -144,14 +352,17 @@ fn print_json_output(
 }
 
 /// Print human-readable output.
+///
+/// `kind` names what was tested ("profile" or "scenario file").
 fn print_human_output(
-    profile: &str,
+    kind: &str,
+    name: &str,
     total: usize,
     passed: usize,
     failed: usize,
     results: &[ScenarioResult],
 ) {
-    println!("Testing profile: {}", profile);
+    println!("Testing {}: {}", kind, name);
     println!();
 
     for result in results {
@@ -169,6 +380,13 @@ fn print_human_output(
             println!("    Input events: {}", result.input.len());
             println!("    Output events: {}", result.output.len());
         }
+
+        for boundary in &result.boundaries {
+            println!(
+                "    Output changes between {} ms and {} ms",
+                boundary.last_before_ms, boundary.first_after_ms
+            );
+        }
     }
 
     println!();
@@ -244,6 +462,7 @@ mod tests {
             input: vec![],
             output: vec![],
             error: None,
+            boundaries: vec![],
         }];
 
         let result = print_json_output("test", 1, 1, 0, true, &results);
@@ -0,0 +1,614 @@
+//! Self-check that exercises every mapping of a compiled configuration
+//!
+//! For each mapping, the state its `when` condition needs is set up first:
+//! required modifiers are held and required locks tapped through the keys
+//! the config maps to them (or set directly when no key does), the clock is
+//! moved into required time windows, and events carry a device ID matching
+//! required device patterns. The input key is then tapped through the same
+//! `KeyLookup`/`DeviceState` pipeline the daemon uses and the result is
+//! compared with what the mapping declares.
+//!
+//! A mapping fails if it produces the wrong output or if an earlier mapping
+//! for the same key handles the event instead. Mappings whose condition
+//! cannot be met this way (e.g. a modifier required both on and off) are
+//! skipped.
+
+use keyrx_compiler::diff::{describe_condition, describe_mapping};
+use keyrx_core::config::{
+    BaseKeyMapping, Condition, ConditionItem, ConfigRoot, DeviceConfig, KeyCode, KeyMapping,
+    MacroStep,
+};
+use keyrx_core::runtime::{
+    check_tap_hold_timeouts, process_event, DeviceState, KeyEvent, KeyEventType, KeyLookup,
+    MappingPosition,
+};
+use serde::Serialize;
+
+/// Timestamp of the first synthesized event, in microseconds
+const START_US: u64 = 1_000_000;
+
+/// Gap between synthesized events, in microseconds
+///
+/// Short enough that every tap stays under tap-hold and on-release
+/// thresholds.
+const STEP_US: u64 = 1_000;
+
+/// Outcome of checking one mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result of checking one mapping
+#[derive(Debug, Clone, Serialize)]
+pub struct MappingCheck {
+    /// Device pattern the mapping belongs to
+    pub device: String,
+    /// Where the mapping sits in the device configuration
+    pub position: MappingPosition,
+    /// Condition, input key and behavior, e.g. `when MD_00: H -> Left`
+    pub mapping: String,
+    pub status: CheckStatus,
+    /// What went wrong, or why the mapping was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl MappingCheck {
+    /// Formats the position, e.g. `mappings[2][0]`.
+    pub fn location(&self) -> String {
+        format_position(self.position)
+    }
+}
+
+/// Checks every mapping of every device in `config`.
+pub fn check_config(config: &ConfigRoot) -> Vec<MappingCheck> {
+    config.devices.iter().flat_map(check_device).collect()
+}
+
+/// Checks every mapping of one device, in config order.
+pub fn check_device(device: &DeviceConfig) -> Vec<MappingCheck> {
+    let lookup = KeyLookup::from_device_config(device);
+    let mut checks = Vec::new();
+
+    for (index, mapping) in device.mappings.iter().enumerate() {
+        match mapping {
+            KeyMapping::Base(base) => {
+                let position = MappingPosition { index, inner: None };
+                checks.push(check_mapping(device, &lookup, base, None, position));
+            }
+            KeyMapping::Conditional {
+                condition,
+                mappings,
+            } => {
+                for (inner, base) in mappings.iter().enumerate() {
+                    let position = MappingPosition {
+                        index,
+                        inner: Some(inner),
+                    };
+                    checks.push(check_mapping(
+                        device,
+                        &lookup,
+                        base,
+                        Some(condition),
+                        position,
+                    ));
+                }
+            }
+        }
+    }
+
+    checks
+}
+
+/// Why a mapping did not pass
+enum Problem {
+    Failed(String),
+    Skipped(String),
+}
+
+fn check_mapping(
+    device: &DeviceConfig,
+    lookup: &KeyLookup,
+    mapping: &BaseKeyMapping,
+    condition: Option<&Condition>,
+    position: MappingPosition,
+) -> MappingCheck {
+    let (key, behavior) = describe_mapping(mapping);
+    let description = match condition {
+        Some(condition) => format!(
+            "when {}: {} -> {}",
+            describe_condition(condition),
+            key,
+            behavior
+        ),
+        None => format!("{} -> {}", key, behavior),
+    };
+
+    let (status, detail) = match run_check(device, lookup, mapping, condition, position) {
+        Ok(()) => (CheckStatus::Passed, None),
+        Err(Problem::Failed(detail)) => (CheckStatus::Failed, Some(detail)),
+        Err(Problem::Skipped(detail)) => (CheckStatus::Skipped, Some(detail)),
+    };
+
+    MappingCheck {
+        device: device.identifier.pattern.clone(),
+        position,
+        mapping: description,
+        status,
+        detail,
+    }
+}
+
+fn run_check(
+    device: &DeviceConfig,
+    lookup: &KeyLookup,
+    mapping: &BaseKeyMapping,
+    condition: Option<&Condition>,
+    position: MappingPosition,
+) -> Result<(), Problem> {
+    let from = mapping.from_key();
+    let mut harness = Harness::new(device, lookup);
+
+    if let Some(condition) = condition {
+        let mut requirements = Requirements::default();
+        require(condition, true, &mut requirements);
+        harness.set_up(device, &requirements);
+
+        let device_id = harness.device_id.as_deref();
+        if !harness
+            .state
+            .evaluate_condition_with_device(condition, device_id)
+        {
+            return Err(Problem::Skipped(
+                "could not set up the condition".to_string(),
+            ));
+        }
+    }
+
+    match lookup.find_positioned_mapping(from, &harness.state, harness.device_id.as_deref()) {
+        Some((_, found)) if found == position => {}
+        Some((_, found)) => {
+            return Err(Problem::Failed(format!(
+                "shadowed by {}",
+                format_position(found)
+            )))
+        }
+        None => return Err(Problem::Failed("no mapping handles the key".to_string())),
+    }
+
+    match mapping {
+        BaseKeyMapping::Simple { to, .. } => {
+            expect_tap(&mut harness, from, &[press(*to)], &[release(*to)])
+        }
+        BaseKeyMapping::ModifiedOutput {
+            to,
+            shift,
+            ctrl,
+            alt,
+            win,
+            ..
+        } => {
+            let held: Vec<KeyCode> = [
+                (*shift, KeyCode::LShift),
+                (*ctrl, KeyCode::LCtrl),
+                (*alt, KeyCode::LAlt),
+                (*win, KeyCode::LMeta),
+            ]
+            .into_iter()
+            .filter_map(|(on, key)| on.then_some(key))
+            .collect();
+            let mut pressed: Vec<Output> = held.iter().map(|&key| press(key)).collect();
+            pressed.push(press(*to));
+            let mut released = vec![release(*to)];
+            released.extend(held.iter().rev().map(|&key| release(key)));
+            expect_tap(&mut harness, from, &pressed, &released)
+        }
+        BaseKeyMapping::Cycle { outputs, .. } => match outputs.first() {
+            Some(&first) => expect_tap(&mut harness, from, &[press(first)], &[release(first)]),
+            None => Err(Problem::Skipped("cycle has no outputs".to_string())),
+        },
+        BaseKeyMapping::OnRelease { to, .. } => {
+            expect_tap(&mut harness, from, &[], &[press(*to), release(*to)])
+        }
+        BaseKeyMapping::Macro { steps, .. } => {
+            let played: Vec<Output> = steps
+                .iter()
+                .filter_map(|step| match *step {
+                    MacroStep::Press(key) => Some(press(key)),
+                    MacroStep::Release(key) => Some(release(key)),
+                    MacroStep::Wait(_) => None,
+                })
+                .collect();
+            expect_tap(&mut harness, from, &played, &[])
+        }
+        BaseKeyMapping::Disable { .. } => expect_tap(&mut harness, from, &[], &[]),
+        BaseKeyMapping::Modifier { modifier_id, .. } => {
+            let before = harness.state.is_modifier_active(*modifier_id);
+            expect_outputs("press", harness.press(from), &[])?;
+            if !harness.state.is_modifier_active(*modifier_id) {
+                return Err(Problem::Failed(format!(
+                    "MD_{:02X} not active while held",
+                    modifier_id
+                )));
+            }
+            expect_outputs("release", harness.release(from), &[])?;
+            if harness.state.is_modifier_active(*modifier_id) != before {
+                return Err(Problem::Failed(format!(
+                    "MD_{:02X} still active after release",
+                    modifier_id
+                )));
+            }
+            Ok(())
+        }
+        BaseKeyMapping::Lock { lock_id, .. } => {
+            let before = harness.state.is_lock_active(*lock_id);
+            expect_tap(&mut harness, from, &[], &[])?;
+            if harness.state.is_lock_active(*lock_id) == before {
+                return Err(Problem::Failed(format!(
+                    "LK_{:02X} not toggled by a tap",
+                    lock_id
+                )));
+            }
+            Ok(())
+        }
+        BaseKeyMapping::LayerToggle { layer_id, .. } => {
+            let before = harness.state.is_layer_locked(*layer_id);
+            expect_tap(&mut harness, from, &[], &[])?;
+            if harness.state.is_layer_locked(*layer_id) == before {
+                return Err(Problem::Failed(format!(
+                    "layer MD_{:02X} not toggled by a tap",
+                    layer_id
+                )));
+            }
+            Ok(())
+        }
+        BaseKeyMapping::TapHold {
+            tap,
+            hold_modifier,
+            threshold_ms,
+            ..
+        } => {
+            expect_tap(&mut harness, from, &[], &[press(*tap), release(*tap)])?;
+
+            harness.press(from);
+            let deadline = harness.now + u64::from(*threshold_ms) * 1_000 + 1;
+            harness.now = deadline;
+            check_tap_hold_timeouts(deadline, &mut harness.state);
+            if !harness.state.is_modifier_active(*hold_modifier) {
+                return Err(Problem::Failed(format!(
+                    "MD_{:02X} not active after holding past {} ms",
+                    hold_modifier, threshold_ms
+                )));
+            }
+            harness.release(from);
+            Ok(())
+        }
+    }
+}
+
+/// Expected or produced output event
+type Output = (KeyEventType, KeyCode);
+
+fn press(key: KeyCode) -> Output {
+    (KeyEventType::Press, key)
+}
+
+fn release(key: KeyCode) -> Output {
+    (KeyEventType::Release, key)
+}
+
+/// Taps `key` and compares the output of the press and of the release.
+fn expect_tap(
+    harness: &mut Harness<'_>,
+    key: KeyCode,
+    on_press: &[Output],
+    on_release: &[Output],
+) -> Result<(), Problem> {
+    let pressed = harness.press(key);
+    let released = harness.release(key);
+    expect_outputs("press", pressed, on_press)?;
+    expect_outputs("release", released, on_release)
+}
+
+fn expect_outputs(stage: &str, actual: Vec<KeyEvent>, expected: &[Output]) -> Result<(), Problem> {
+    let actual: Vec<Output> = actual
+        .iter()
+        .map(|event| (event.event_type(), event.keycode()))
+        .collect();
+    if actual == expected {
+        return Ok(());
+    }
+    Err(Problem::Failed(format!(
+        "{}: expected {}, got {}",
+        stage,
+        format_outputs(expected),
+        format_outputs(&actual)
+    )))
+}
+
+fn format_outputs(outputs: &[Output]) -> String {
+    if outputs.is_empty() {
+        return "nothing".to_string();
+    }
+    outputs
+        .iter()
+        .map(|(kind, key)| match kind {
+            KeyEventType::Press => format!("press {:?}", key),
+            KeyEventType::Release => format!("release {:?}", key),
+            KeyEventType::Repeat => format!("repeat {:?}", key),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_position(position: MappingPosition) -> String {
+    match position.inner {
+        Some(inner) => format!("mappings[{}][{}]", position.index, inner),
+        None => format!("mappings[{}]", position.index),
+    }
+}
+
+/// State a condition needs to be true
+#[derive(Debug, Default)]
+struct Requirements {
+    modifiers: Vec<u8>,
+    locks: Vec<u8>,
+    time_windows: Vec<u8>,
+    device: Option<String>,
+}
+
+impl Requirements {
+    fn add(&mut self, item: &ConditionItem) {
+        let (list, id) = match item {
+            ConditionItem::ModifierActive(id) => (&mut self.modifiers, *id),
+            ConditionItem::LockActive(id) => (&mut self.locks, *id),
+            ConditionItem::TimeWindowActive(id) => (&mut self.time_windows, *id),
+        };
+        if !list.contains(&id) {
+            list.push(id);
+        }
+    }
+}
+
+/// Collects the minimal state that makes `condition` evaluate to `want`.
+///
+/// Starting from a fresh device state everything is off, so only items that
+/// must be on are collected. When one of several items is enough, the first
+/// is picked.
+fn require(condition: &Condition, want: bool, requirements: &mut Requirements) {
+    match condition {
+        Condition::ModifierActive(id) if want => {
+            requirements.add(&ConditionItem::ModifierActive(*id))
+        }
+        Condition::LockActive(id) if want => requirements.add(&ConditionItem::LockActive(*id)),
+        Condition::TimeWindowActive(id) if want => {
+            requirements.add(&ConditionItem::TimeWindowActive(*id))
+        }
+        Condition::DeviceMatches(pattern) if want => {
+            requirements.device = Some(pattern.replace('*', ""));
+        }
+        Condition::AllActive(items) if want => items.iter().for_each(|i| requirements.add(i)),
+        Condition::AnyActive(items) if want => {
+            items.iter().take(1).for_each(|i| requirements.add(i))
+        }
+        Condition::NotActive(items) if !want => {
+            items.iter().take(1).for_each(|i| requirements.add(i))
+        }
+        Condition::Composite { all, .. } if want => all.iter().for_each(|i| requirements.add(i)),
+        Condition::Composite { all, none } if !want && all.is_empty() => {
+            none.iter().take(1).for_each(|i| requirements.add(i))
+        }
+        Condition::Negate(inner) => require(inner, !want, requirements),
+        _ => {}
+    }
+}
+
+/// Device state and clock for driving synthesized events
+struct Harness<'a> {
+    lookup: &'a KeyLookup,
+    state: DeviceState,
+    /// Device ID attached to every event, for `device_matches` conditions
+    device_id: Option<String>,
+    now: u64,
+}
+
+impl<'a> Harness<'a> {
+    fn new(device: &DeviceConfig, lookup: &'a KeyLookup) -> Self {
+        Self {
+            lookup,
+            state: DeviceState::for_device(device),
+            device_id: None,
+            now: START_US,
+        }
+    }
+
+    fn send(&mut self, event: KeyEvent) -> Vec<KeyEvent> {
+        self.now += STEP_US;
+        let mut event = event.with_timestamp(self.now);
+        if let Some(device_id) = &self.device_id {
+            event = event.with_device_id(device_id.clone());
+        }
+        // Steps a macro scheduled after a wait() count as output of the
+        // same event; the harness has no clock to wait for them
+        let mut outputs = process_event(event, self.lookup, &mut self.state);
+        outputs.extend(self.state.take_scheduled_outputs());
+        outputs
+    }
+
+    fn press(&mut self, key: KeyCode) -> Vec<KeyEvent> {
+        self.send(KeyEvent::press(key))
+    }
+
+    fn release(&mut self, key: KeyCode) -> Vec<KeyEvent> {
+        self.send(KeyEvent::release(key))
+    }
+
+    /// Brings the state in line with `requirements`, through the device's
+    /// own modifier and lock keys where it has them.
+    fn set_up(&mut self, device: &DeviceConfig, requirements: &Requirements) {
+        if let Some(device_id) = &requirements.device {
+            self.device_id = Some(device_id.clone());
+        }
+
+        for &id in &requirements.modifiers {
+            if self.state.is_modifier_active(id) {
+                continue;
+            }
+            match setter_key(
+                device,
+                |m| matches!(m, BaseKeyMapping::Modifier { modifier_id, .. } if *modifier_id == id),
+            ) {
+                Some(key) => {
+                    self.press(key);
+                }
+                None => {
+                    self.state.set_modifier(id);
+                }
+            }
+        }
+
+        for &id in &requirements.locks {
+            if self.state.is_lock_active(id) {
+                continue;
+            }
+            match setter_key(
+                device,
+                |m| matches!(m, BaseKeyMapping::Lock { lock_id, .. } if *lock_id == id),
+            ) {
+                Some(key) => {
+                    self.press(key);
+                    self.release(key);
+                }
+                None => {
+                    self.state.toggle_lock(id);
+                }
+            }
+        }
+
+        // Time windows can't overlap unless they do in the config, so only
+        // the first required one is entered.
+        if let Some(window) = requirements
+            .time_windows
+            .first()
+            .and_then(|&id| device.time_windows.get(usize::from(id)))
+        {
+            self.state.set_minute_of_day(window.start_minute);
+        }
+    }
+}
+
+/// Returns the input key of the first unconditional mapping that matches.
+fn setter_key(device: &DeviceConfig, matches: impl Fn(&BaseKeyMapping) -> bool) -> Option<KeyCode> {
+    device.mappings.iter().find_map(|mapping| match mapping {
+        KeyMapping::Base(base) if matches(base) => Some(base.from_key()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(checks: &[MappingCheck]) -> Vec<CheckStatus> {
+        checks.iter().map(|check| check.status).collect()
+    }
+
+    #[test]
+    fn test_every_mapping_kind_passes() {
+        let device = DeviceConfig::new(
+            "*",
+            vec![
+                KeyMapping::modifier(KeyCode::CapsLock, 0x00),
+                KeyMapping::lock(KeyCode::ScrollLock, 0x01),
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::modified_output(KeyCode::Num1, KeyCode::Num1, true, true, false, false),
+                KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 0x02, 200),
+                KeyMapping::on_release(KeyCode::Tab, KeyCode::Escape, 300),
+                KeyMapping::cycle(KeyCode::F1, vec![KeyCode::F2, KeyCode::F3], 500),
+                KeyMapping::layer_toggle(KeyCode::F4, 0x03),
+                KeyMapping::disable(KeyCode::Insert),
+                KeyMapping::conditional(
+                    Condition::AllActive(vec![
+                        ConditionItem::ModifierActive(0x00),
+                        ConditionItem::LockActive(0x01),
+                    ]),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::H,
+                        to: KeyCode::Left,
+                    }],
+                ),
+                KeyMapping::conditional(
+                    Condition::NotActive(vec![ConditionItem::ModifierActive(0x00)]),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::J,
+                        to: KeyCode::Down,
+                    }],
+                ),
+                KeyMapping::conditional(
+                    Condition::DeviceMatches("*numpad*".to_string()),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::K,
+                        to: KeyCode::Up,
+                    }],
+                ),
+            ],
+        );
+
+        let checks = check_device(&device);
+        for check in &checks {
+            assert_eq!(
+                check.status,
+                CheckStatus::Passed,
+                "{} {}: {:?}",
+                check.location(),
+                check.mapping,
+                check.detail
+            );
+        }
+        assert_eq!(checks.len(), 12);
+        assert_eq!(checks[9].location(), "mappings[9][0]");
+        assert_eq!(checks[9].mapping, "when MD_00 & LK_01: H -> Left");
+    }
+
+    #[test]
+    fn test_reports_shadowed_mapping() {
+        let device = DeviceConfig::new(
+            "*",
+            vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::simple(KeyCode::A, KeyCode::C),
+            ],
+        );
+
+        let checks = check_device(&device);
+        assert_eq!(
+            statuses(&checks),
+            vec![CheckStatus::Passed, CheckStatus::Failed]
+        );
+        assert_eq!(checks[1].detail.as_deref(), Some("shadowed by mappings[0]"));
+    }
+
+    #[test]
+    fn test_skips_unsatisfiable_condition() {
+        let device = DeviceConfig::new(
+            "*",
+            vec![KeyMapping::conditional(
+                Condition::Composite {
+                    all: vec![ConditionItem::ModifierActive(0x00)],
+                    none: vec![ConditionItem::ModifierActive(0x00)],
+                },
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::A,
+                    to: KeyCode::B,
+                }],
+            )],
+        );
+
+        let checks = check_device(&device);
+        assert_eq!(statuses(&checks), vec![CheckStatus::Skipped]);
+    }
+}
@@ -0,0 +1,242 @@
+//! Event-processing throughput benchmark for compiled configurations
+//!
+//! Feeds a deterministic stream of synthetic key taps through the same
+//! `KeyLookup`/`DeviceState` pipeline the daemon uses, without touching any
+//! input or output device, and reports throughput and per-event latency
+//! percentiles.
+
+use keyrx_core::config::{BaseKeyMapping, DeviceConfig, KeyCode, KeyMapping};
+use keyrx_core::runtime::{process_event, DeviceState, KeyEvent, KeyLookup};
+use rkyv::Deserialize as _;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::config::simulation_engine::SimulationError;
+
+/// Maximum number of synthetic events per run (keeps latency samples bounded)
+pub const MAX_BENCH_EVENTS: usize = 10_000_000;
+
+/// Simulated time between synthetic events, in microseconds
+const EVENT_INTERVAL_US: u64 = 1_000;
+
+/// Unmapped keys mixed into the stream so passthrough is measured too
+const PASSTHROUGH_KEYS: [KeyCode; 4] = [KeyCode::Q, KeyCode::W, KeyCode::E, KeyCode::R];
+
+/// Result of a benchmark run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    /// Number of input events processed
+    pub events: usize,
+    /// Number of output events produced
+    pub output_events: usize,
+    /// Wall-clock time spent inside `process_event`, in nanoseconds
+    pub total_ns: u64,
+    /// Input events processed per second
+    pub events_per_sec: f64,
+    /// Median per-event latency in nanoseconds
+    pub p50_ns: u64,
+    /// 90th percentile per-event latency in nanoseconds
+    pub p90_ns: u64,
+    /// 99th percentile per-event latency in nanoseconds
+    pub p99_ns: u64,
+    /// Slowest event in nanoseconds
+    pub max_ns: u64,
+}
+
+/// Loads the first device configuration from a compiled .krx file.
+pub fn load_device_config(krx_path: &Path) -> Result<DeviceConfig, SimulationError> {
+    let krx_data = std::fs::read(krx_path).map_err(|e| {
+        SimulationError::LoadError(format!("Failed to read {}: {}", krx_path.display(), e))
+    })?;
+    let config = keyrx_compiler::serialize::deserialize(&krx_data)
+        .map_err(|e| SimulationError::LoadError(e.to_string()))?;
+    let archived_device = config
+        .devices
+        .first()
+        .ok_or_else(|| SimulationError::LoadError("Configuration has no devices".into()))?;
+    archived_device
+        .deserialize(&mut rkyv::Infallible)
+        .map_err(|_| SimulationError::LoadError("Failed to deserialize device".into()))
+}
+
+/// Generates `count` synthetic events as press/release pairs.
+///
+/// Keys are drawn from the config's mapped input keys plus a few unmapped
+/// keys, using a fixed-seed generator so runs are reproducible.
+pub fn generate_events(device_config: &DeviceConfig, count: usize) -> Vec<KeyEvent> {
+    let mut keys = mapped_input_keys(device_config);
+    for key in PASSTHROUGH_KEYS {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    // xorshift64 with a fixed seed
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut events = Vec::with_capacity(count);
+    let mut timestamp_us = 0;
+    while events.len() < count {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let key = keys[(seed % keys.len() as u64) as usize];
+
+        timestamp_us += EVENT_INTERVAL_US;
+        events.push(KeyEvent::press(key).with_timestamp(timestamp_us));
+        if events.len() < count {
+            timestamp_us += EVENT_INTERVAL_US;
+            events.push(KeyEvent::release(key).with_timestamp(timestamp_us));
+        }
+    }
+    events
+}
+
+/// Runs `events` through `process_event` and measures each call.
+pub fn run_benchmark(device_config: &DeviceConfig, events: &[KeyEvent]) -> BenchmarkReport {
+    let lookup = KeyLookup::from_device_config(device_config);
+    let mut state = DeviceState::new();
+    let mut latencies_ns = Vec::with_capacity(events.len());
+    let mut output_events = 0;
+
+    for event in events {
+        let start = Instant::now();
+        let output = process_event(event.clone(), &lookup, &mut state);
+        latencies_ns.push(duration_ns(start.elapsed()));
+        output_events += output.len() + state.take_scheduled_outputs().len();
+    }
+
+    let total_ns: u64 = latencies_ns.iter().sum();
+    latencies_ns.sort_unstable();
+
+    BenchmarkReport {
+        events: events.len(),
+        output_events,
+        total_ns,
+        events_per_sec: if total_ns == 0 {
+            0.0
+        } else {
+            events.len() as f64 * 1e9 / total_ns as f64
+        },
+        p50_ns: percentile(&latencies_ns, 50),
+        p90_ns: percentile(&latencies_ns, 90),
+        p99_ns: percentile(&latencies_ns, 99),
+        max_ns: latencies_ns.last().copied().unwrap_or(0),
+    }
+}
+
+/// Returns every distinct input key mapped by the config.
+fn mapped_input_keys(device_config: &DeviceConfig) -> Vec<KeyCode> {
+    let mut keys = Vec::new();
+    let mut add = |mapping: &BaseKeyMapping| {
+        let from = match mapping {
+            BaseKeyMapping::Simple { from, .. }
+            | BaseKeyMapping::Modifier { from, .. }
+            | BaseKeyMapping::Lock { from, .. }
+            | BaseKeyMapping::TapHold { from, .. }
+            | BaseKeyMapping::ModifiedOutput { from, .. }
+            | BaseKeyMapping::OnRelease { from, .. }
+            | BaseKeyMapping::LayerToggle { from, .. }
+            | BaseKeyMapping::Cycle { from, .. }
+            | BaseKeyMapping::Macro { from, .. }
+            | BaseKeyMapping::Disable { from } => *from,
+        };
+        if !keys.contains(&from) {
+            keys.push(from);
+        }
+    };
+
+    for mapping in &device_config.mappings {
+        match mapping {
+            KeyMapping::Base(base) => add(base),
+            KeyMapping::Conditional { mappings, .. } => mappings.iter().for_each(&mut add),
+        }
+    }
+    keys
+}
+
+/// Nearest-rank percentile of an ascending slice.
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn duration_ns(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::DeviceIdentifier;
+
+    fn test_config() -> DeviceConfig {
+        DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: "*".to_string(),
+                excludes: Vec::new(),
+            },
+            mappings: vec![
+                KeyMapping::simple(KeyCode::A, KeyCode::B),
+                KeyMapping::modifier(KeyCode::CapsLock, 0),
+                KeyMapping::tap_hold(KeyCode::Space, KeyCode::Space, 1, 200),
+            ],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_events_is_deterministic_and_balanced() {
+        let config = test_config();
+        let events = generate_events(&config, 1_000);
+
+        assert_eq!(events.len(), 1_000);
+        assert_eq!(events, generate_events(&config, 1_000));
+        for pair in events.chunks(2) {
+            assert!(pair[0].is_press());
+            assert!(pair[1].is_release());
+            assert_eq!(pair[0].keycode(), pair[1].keycode());
+        }
+        // Both mapped and passthrough keys are exercised
+        assert!(events.iter().any(|e| e.keycode() == KeyCode::A));
+        assert!(events
+            .iter()
+            .any(|e| PASSTHROUGH_KEYS.contains(&e.keycode())));
+    }
+
+    #[test]
+    fn test_generate_events_odd_count() {
+        let events = generate_events(&test_config(), 3);
+        assert_eq!(events.len(), 3);
+        assert!(events[2].is_press());
+    }
+
+    #[test]
+    fn test_run_benchmark_reports_ordered_percentiles() {
+        let config = test_config();
+        let events = generate_events(&config, 2_000);
+        let report = run_benchmark(&config, &events);
+
+        assert_eq!(report.events, 2_000);
+        assert!(report.output_events > 0);
+        assert!(report.p50_ns <= report.p90_ns);
+        assert!(report.p90_ns <= report.p99_ns);
+        assert!(report.p99_ns <= report.max_ns);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 50), 50);
+        assert_eq!(percentile(&samples, 99), 99);
+        assert_eq!(percentile(&samples, 100), 100);
+        assert_eq!(percentile(&[7], 50), 7);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+}
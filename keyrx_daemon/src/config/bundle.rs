@@ -0,0 +1,633 @@
+//! Portable bundle of all profiles, device metadata, and custom layouts.
+//!
+//! A bundle is an uncompressed ustar archive, so it can be inspected with
+//! `tar -tf`:
+//!
+//! ```text
+//! manifest.json          bundle format version
+//! profiles/<name>.rhai   every managed profile
+//! devices.json           DeviceRegistry entries
+//! layouts/<name>.json    custom (imported) KLE layouts
+//! ```
+//!
+//! Builtin layouts and compiled `.krx` files are not bundled; the importing
+//! machine already has the former and recompiles the latter on activation.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::device_registry::{DeviceEntry, DeviceRegistry, DeviceValidationError};
+use super::layout_manager::{LayoutError, LayoutManager, LayoutSource};
+use super::profile_manager::{ProfileError, ProfileManager};
+use crate::error::RegistryError;
+
+/// Current bundle format version
+const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DEVICES_ENTRY: &str = "devices.json";
+const PROFILES_PREFIX: &str = "profiles/";
+const LAYOUTS_PREFIX: &str = "layouts/";
+
+/// Maximum profile and layout name length (both managers use 32)
+const MAX_NAME_LEN: usize = 32;
+
+/// Size of a tar header and the unit entry data is padded to
+const BLOCK_SIZE: usize = 512;
+
+/// Maximum size of a single archive entry (16 MB)
+const MAX_ENTRY_SIZE: usize = 16 * 1024 * 1024;
+
+/// How to handle a bundled profile or layout whose name is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the local one and skip the bundled one
+    Skip,
+    /// Replace the local one with the bundled one
+    Overwrite,
+    /// Import the bundled one under a free name (`name-2`, `name-3`, ...)
+    Rename,
+}
+
+/// Errors that can occur while exporting or importing a bundle
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("Unsupported bundle version {0} (max {BUNDLE_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Profile error: {0}")]
+    Profile(#[from] ProfileError),
+
+    #[error("Layout error: {0}")]
+    Layout(#[from] LayoutError),
+
+    #[error("Device registry error: {0}")]
+    Registry(#[from] RegistryError),
+
+    #[error("Invalid device entry: {0}")]
+    Device(#[from] DeviceValidationError),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Contents of `manifest.json`
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// Export time (Unix seconds)
+    created_at: u64,
+}
+
+/// Number of items written by [`export_bundle`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportSummary {
+    pub profiles: usize,
+    pub devices: usize,
+    pub layouts: usize,
+}
+
+/// What happened to a bundled profile or layout on import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", content = "to", rename_all = "snake_case")]
+pub enum ImportAction {
+    /// The name was free
+    Added,
+    /// The local one was replaced
+    Overwritten,
+    /// The local one was kept
+    Skipped,
+    /// Imported under the given name
+    Renamed(String),
+}
+
+/// A bundled profile or layout and what the import did with it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedItem {
+    pub name: String,
+    #[serde(flatten)]
+    pub action: ImportAction,
+}
+
+/// Result of [`import_bundle`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportSummary {
+    pub profiles: Vec<ImportedItem>,
+    pub layouts: Vec<ImportedItem>,
+    /// Devices that were not known locally
+    pub devices_added: usize,
+    /// Devices merged into an existing entry
+    pub devices_merged: usize,
+}
+
+/// Bundle contents, validated before anything is written
+struct Bundle {
+    profiles: Vec<(String, String)>,
+    layouts: Vec<(String, String)>,
+    devices: Vec<DeviceEntry>,
+}
+
+/// Write all profiles, the device registry, and custom layouts to `dest`.
+///
+/// # Errors
+///
+/// Returns an error if a profile, the registry, or a layout cannot be read,
+/// or if `dest` cannot be written.
+pub fn export_bundle(profiles: &ProfileManager, dest: &Path) -> Result<ExportSummary, BundleError> {
+    let config_dir = profiles.config_dir();
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut archive = Vec::new();
+
+    let manifest = Manifest {
+        version: BUNDLE_VERSION,
+        created_at,
+    };
+    append_entry(
+        &mut archive,
+        MANIFEST_ENTRY,
+        &serde_json::to_vec_pretty(&manifest)?,
+        created_at,
+    )?;
+
+    let mut names: Vec<&str> = profiles.list().iter().map(|p| p.name.as_str()).collect();
+    names.sort_unstable();
+    for name in &names {
+        let content = profiles.get_config(name)?;
+        let path = format!("{}{}.rhai", PROFILES_PREFIX, name);
+        append_entry(&mut archive, &path, content.as_bytes(), created_at)?;
+    }
+
+    let registry = DeviceRegistry::load(&config_dir.join(DEVICES_ENTRY))?;
+    let mut devices = registry.list();
+    devices.sort_by(|a, b| a.id.cmp(&b.id));
+    append_entry(
+        &mut archive,
+        DEVICES_ENTRY,
+        &serde_json::to_vec_pretty(&devices)?,
+        created_at,
+    )?;
+
+    let layout_manager = LayoutManager::new(config_dir.join("layouts"))?;
+    let layouts: Vec<_> = layout_manager
+        .list()
+        .into_iter()
+        .filter(|layout| layout.source == LayoutSource::Custom)
+        .collect();
+    for layout in &layouts {
+        let path = format!("{}{}.json", LAYOUTS_PREFIX, layout.name);
+        let json = serde_json::to_vec_pretty(&layout.kle_json)?;
+        append_entry(&mut archive, &path, &json, created_at)?;
+    }
+
+    // End-of-archive marker
+    archive.resize(archive.len() + 2 * BLOCK_SIZE, 0);
+    fs::write(dest, archive)?;
+
+    Ok(ExportSummary {
+        profiles: names.len(),
+        devices: devices.len(),
+        layouts: layouts.len(),
+    })
+}
+
+/// Restore a bundle written by [`export_bundle`].
+///
+/// Profiles and layouts whose name is taken are handled according to
+/// `policy`. Device entries are merged into the local registry (see
+/// [`DeviceRegistry::merge`]); a device whose layout was renamed follows
+/// the new name. The whole bundle is validated before anything is written.
+///
+/// # Errors
+///
+/// Returns [`BundleError::InvalidBundle`] or [`BundleError::UnsupportedVersion`]
+/// if `src` is not a readable bundle, and other variants if a profile,
+/// layout, or the registry cannot be written.
+pub fn import_bundle(
+    profiles: &mut ProfileManager,
+    src: &Path,
+    policy: ConflictPolicy,
+) -> Result<ImportSummary, BundleError> {
+    let bundle = Bundle::parse(&fs::read(src)?)?;
+    let config_dir = profiles.config_dir().to_path_buf();
+    let mut summary = ImportSummary::default();
+
+    let mut layout_manager = LayoutManager::new(config_dir.join("layouts"))?;
+    let mut renamed_layouts = HashMap::new();
+    for (name, json) in &bundle.layouts {
+        let action = match resolve_conflict(name, policy, |n| layout_manager.get(n).is_some()) {
+            Some(ImportAction::Skipped) => ImportAction::Skipped,
+            Some(ImportAction::Renamed(to)) => {
+                layout_manager.import_json(&to, json)?;
+                renamed_layouts.insert(name.clone(), to.clone());
+                ImportAction::Renamed(to)
+            }
+            action => {
+                layout_manager.import_json(name, json)?;
+                action.unwrap_or(ImportAction::Added)
+            }
+        };
+        summary.layouts.push(ImportedItem {
+            name: name.clone(),
+            action,
+        });
+    }
+
+    for (name, content) in &bundle.profiles {
+        let action = match resolve_conflict(name, policy, |n| profiles.get(n).is_some()) {
+            None => {
+                profiles.import_config(name, content)?;
+                ImportAction::Added
+            }
+            Some(ImportAction::Overwritten) => {
+                profiles.set_config(name, content)?;
+                ImportAction::Overwritten
+            }
+            Some(ImportAction::Renamed(to)) => {
+                profiles.import_config(&to, content)?;
+                ImportAction::Renamed(to)
+            }
+            Some(action) => action,
+        };
+        summary.profiles.push(ImportedItem {
+            name: name.clone(),
+            action,
+        });
+    }
+
+    let mut registry = DeviceRegistry::load(&config_dir.join(DEVICES_ENTRY))?;
+    for mut device in bundle.devices {
+        if let Some(to) = device
+            .layout
+            .as_ref()
+            .and_then(|layout| renamed_layouts.get(layout))
+        {
+            device.layout = Some(to.clone());
+        }
+        if registry.merge(device)? {
+            summary.devices_added += 1;
+        } else {
+            summary.devices_merged += 1;
+        }
+    }
+    registry.save()?;
+
+    Ok(summary)
+}
+
+/// Decide what to do with a bundled item; `None` means the name is free.
+fn resolve_conflict(
+    name: &str,
+    policy: ConflictPolicy,
+    taken: impl Fn(&str) -> bool,
+) -> Option<ImportAction> {
+    if !taken(name) {
+        return None;
+    }
+    Some(match policy {
+        ConflictPolicy::Skip => ImportAction::Skipped,
+        ConflictPolicy::Overwrite => ImportAction::Overwritten,
+        ConflictPolicy::Rename => ImportAction::Renamed(free_name(name, taken)),
+    })
+}
+
+/// Returns the first of `name-2`, `name-3`, ... that is not taken,
+/// shortening `name` to stay within [`MAX_NAME_LEN`].
+fn free_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut n = 2;
+    loop {
+        let suffix = format!("-{}", n);
+        let mut base = name;
+        while base.len() + suffix.len() > MAX_NAME_LEN {
+            let mut chars = base.chars();
+            chars.next_back();
+            base = chars.as_str();
+        }
+        let candidate = format!("{}{}", base, suffix);
+        if !taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+impl Bundle {
+    /// Parse and validate a bundle archive.
+    ///
+    /// Every profile and layout name is checked here, including layouts
+    /// named after a builtin, so a bad bundle is rejected before the import
+    /// writes anything.
+    fn parse(archive: &[u8]) -> Result<Self, BundleError> {
+        let mut manifest = None;
+        let mut bundle = Bundle {
+            profiles: Vec::new(),
+            layouts: Vec::new(),
+            devices: Vec::new(),
+        };
+
+        for (path, data) in read_entries(archive)? {
+            if path == MANIFEST_ENTRY {
+                manifest = Some(serde_json::from_slice::<Manifest>(data)?);
+            } else if path == DEVICES_ENTRY {
+                bundle.devices = serde_json::from_slice(data)?;
+            } else if let Some(name) = entry_name(&path, PROFILES_PREFIX, ".rhai") {
+                ProfileManager::validate_name(name)?;
+                bundle
+                    .profiles
+                    .push((name.to_string(), entry_text(&path, data)?));
+            } else if let Some(name) = entry_name(&path, LAYOUTS_PREFIX, ".json") {
+                LayoutManager::check_custom_name(name)?;
+                let json = entry_text(&path, data)?;
+                LayoutManager::validate_kle(&serde_json::from_str(&json)?)?;
+                bundle.layouts.push((name.to_string(), json));
+            } else {
+                log::debug!("Ignoring unknown bundle entry {}", path);
+            }
+        }
+
+        match manifest {
+            None => Err(BundleError::InvalidBundle(format!(
+                "missing {}",
+                MANIFEST_ENTRY
+            ))),
+            Some(m) if m.version > BUNDLE_VERSION => {
+                Err(BundleError::UnsupportedVersion(m.version))
+            }
+            Some(_) => Ok(bundle),
+        }
+    }
+}
+
+/// Returns the item name of a `<prefix><name><extension>` entry.
+fn entry_name<'a>(path: &'a str, prefix: &str, extension: &str) -> Option<&'a str> {
+    path.strip_prefix(prefix)?.strip_suffix(extension)
+}
+
+fn entry_text(path: &str, data: &[u8]) -> Result<String, BundleError> {
+    String::from_utf8(data.to_vec())
+        .map_err(|_| BundleError::InvalidBundle(format!("{} is not valid UTF-8", path)))
+}
+
+/// Append a regular file entry (ustar header plus padded data).
+fn append_entry(
+    archive: &mut Vec<u8>,
+    path: &str,
+    data: &[u8],
+    mtime: u64,
+) -> Result<(), BundleError> {
+    if path.len() >= 100 {
+        return Err(BundleError::InvalidBundle(format!(
+            "entry name too long: {}",
+            path
+        )));
+    }
+
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..path.len()].copy_from_slice(path.as_bytes());
+
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], data.len() as u64);
+    write_octal(&mut header[136..148], mtime);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field filled with spaces
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+    write_octal(&mut header[148..155], checksum);
+
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(data);
+    archive.resize(archive.len().next_multiple_of(BLOCK_SIZE), 0);
+    Ok(())
+}
+
+/// Write `value` as zero-padded octal followed by a NUL.
+fn write_octal(field: &mut [u8], value: u64) {
+    let width = field.len() - 1;
+    let digits = format!("{:0width$o}", value, width = width);
+    field[..width].copy_from_slice(&digits.as_bytes()[digits.len() - width..]);
+    field[width] = 0;
+}
+
+fn parse_octal(field: &[u8]) -> Result<u64, BundleError> {
+    let text = std::str::from_utf8(field)
+        .map_err(|_| BundleError::InvalidBundle("malformed tar header".to_string()))?
+        .trim_matches(|c| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8)
+        .map_err(|_| BundleError::InvalidBundle("malformed tar header".to_string()))
+}
+
+/// Returns the path and data of every regular file in a tar archive.
+fn read_entries(archive: &[u8]) -> Result<Vec<(String, &[u8])>, BundleError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    while let Some(header) = archive.get(offset..offset + BLOCK_SIZE) {
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let stored = parse_octal(&header[148..156])?;
+        let actual: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| u64::from(if (148..156).contains(&i) { b' ' } else { b }))
+            .sum();
+        if stored != actual {
+            return Err(BundleError::InvalidBundle(
+                "tar header checksum mismatch".to_string(),
+            ));
+        }
+
+        let size = usize::try_from(parse_octal(&header[124..136])?)
+            .ok()
+            .filter(|&size| size <= MAX_ENTRY_SIZE)
+            .ok_or_else(|| BundleError::InvalidBundle("entry too large".to_string()))?;
+        let start = offset + BLOCK_SIZE;
+        let data = archive
+            .get(start..start + size)
+            .ok_or_else(|| BundleError::InvalidBundle("truncated archive".to_string()))?;
+
+        // Regular files only; directories and extension headers are skipped
+        if matches!(header[156], b'0' | 0) {
+            let name = header_str(&header[0..100]);
+            let prefix = header_str(&header[345..500]);
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            let path = path.strip_prefix("./").unwrap_or(&path).to_string();
+            entries.push((path, data));
+        }
+
+        offset = start + size.next_multiple_of(BLOCK_SIZE);
+    }
+
+    Ok(entries)
+}
+
+fn header_str(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const LAYOUT_JSON: &str = r#"[["Esc", "F1", "F2"]]"#;
+
+    fn config_dir_with(profiles: &[(&str, &str)], layouts: &[&str]) -> (TempDir, ProfileManager) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        for (name, content) in profiles {
+            manager.import_config(name, content).unwrap();
+        }
+        let mut layout_manager = LayoutManager::new(temp_dir.path().join("layouts")).unwrap();
+        for name in layouts {
+            layout_manager.import_json(name, LAYOUT_JSON).unwrap();
+        }
+        (temp_dir, manager)
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let (src_dir, src) =
+            config_dir_with(&[("work", "// work"), ("game", "// game")], &["split"]);
+        let mut registry = DeviceRegistry::new(src_dir.path().join(DEVICES_ENTRY));
+        registry
+            .register(DeviceEntry::new(
+                "serial-ABC".to_string(),
+                "Desk".to_string(),
+                Some("ABC".to_string()),
+                Some("split".to_string()),
+                100,
+            ))
+            .unwrap();
+        registry.save().unwrap();
+
+        let bundle_path = src_dir.path().join("bundle.tar");
+        let exported = export_bundle(&src, &bundle_path).unwrap();
+        assert_eq!(
+            (exported.profiles, exported.devices, exported.layouts),
+            (2, 1, 1)
+        );
+
+        let (dest_dir, mut dest) = config_dir_with(&[], &[]);
+        let summary = import_bundle(&mut dest, &bundle_path, ConflictPolicy::Skip).unwrap();
+        assert_eq!(summary.profiles.len(), 2);
+        assert!(summary
+            .profiles
+            .iter()
+            .all(|item| item.action == ImportAction::Added));
+        assert_eq!(summary.devices_added, 1);
+
+        assert_eq!(dest.get_config("work").unwrap(), "// work");
+        assert_eq!(dest.get_config("game").unwrap(), "// game");
+        let layouts = LayoutManager::new(dest_dir.path().join("layouts")).unwrap();
+        assert!(layouts.get("split").is_some());
+        let registry = DeviceRegistry::load(&dest_dir.path().join(DEVICES_ENTRY)).unwrap();
+        assert_eq!(
+            registry.get("serial-ABC").unwrap().layout.as_deref(),
+            Some("split")
+        );
+    }
+
+    #[test]
+    fn test_import_conflict_policies() {
+        let (src_dir, src) = config_dir_with(&[("work", "// bundled")], &["split"]);
+        let bundle_path = src_dir.path().join("bundle.tar");
+        export_bundle(&src, &bundle_path).unwrap();
+
+        let (_dir, mut dest) = config_dir_with(&[("work", "// local")], &[]);
+        let summary = import_bundle(&mut dest, &bundle_path, ConflictPolicy::Skip).unwrap();
+        assert_eq!(summary.profiles[0].action, ImportAction::Skipped);
+        assert_eq!(dest.get_config("work").unwrap(), "// local");
+
+        let summary = import_bundle(&mut dest, &bundle_path, ConflictPolicy::Rename).unwrap();
+        assert_eq!(
+            summary.profiles[0].action,
+            ImportAction::Renamed("work-2".to_string())
+        );
+        assert_eq!(
+            summary.layouts[0].action,
+            ImportAction::Renamed("split-2".to_string())
+        );
+        assert_eq!(dest.get_config("work-2").unwrap(), "// bundled");
+
+        let summary = import_bundle(&mut dest, &bundle_path, ConflictPolicy::Overwrite).unwrap();
+        assert_eq!(summary.profiles[0].action, ImportAction::Overwritten);
+        assert_eq!(dest.get_config("work").unwrap(), "// bundled");
+    }
+
+    #[test]
+    fn test_rejects_builtin_layout_name_before_writing() {
+        let (src_dir, src) = config_dir_with(&[("work", "// work")], &[]);
+        let bundle_path = src_dir.path().join("bundle.tar");
+        export_bundle(&src, &bundle_path).unwrap();
+
+        // Add a layout entry named after a builtin in front of the
+        // end-of-archive marker
+        let mut archive = fs::read(&bundle_path).unwrap();
+        archive.truncate(archive.len() - 2 * BLOCK_SIZE);
+        let path = format!("{}ansi_104.json", LAYOUTS_PREFIX);
+        append_entry(&mut archive, &path, LAYOUT_JSON.as_bytes(), 0).unwrap();
+        archive.resize(archive.len() + 2 * BLOCK_SIZE, 0);
+        fs::write(&bundle_path, archive).unwrap();
+
+        let (_dir, mut dest) = config_dir_with(&[], &[]);
+        assert!(matches!(
+            import_bundle(&mut dest, &bundle_path, ConflictPolicy::Overwrite),
+            Err(BundleError::Layout(LayoutError::BuiltinOverwrite(_)))
+        ));
+        assert!(dest.get("work").is_none());
+    }
+
+    #[test]
+    fn test_free_name_stays_within_limit() {
+        let long = "a".repeat(MAX_NAME_LEN);
+        let name = free_name(&long, |n| n.ends_with("-2"));
+        assert_eq!(name.len(), MAX_NAME_LEN);
+        assert!(name.ends_with("-3"));
+    }
+
+    #[test]
+    fn test_rejects_corrupted_archive() {
+        let mut archive = Vec::new();
+        append_entry(&mut archive, MANIFEST_ENTRY, b"{}", 0).unwrap();
+        archive[0] = b'x';
+        assert!(matches!(
+            Bundle::parse(&archive),
+            Err(BundleError::InvalidBundle(_))
+        ));
+
+        let mut archive = Vec::new();
+        append_entry(&mut archive, "profiles/x.rhai", b"// x", 0).unwrap();
+        assert!(matches!(
+            Bundle::parse(&archive),
+            Err(BundleError::InvalidBundle(_))
+        ));
+    }
+}
@@ -9,6 +9,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use typeshare::typeshare;
 
+/// Prefix of device IDs derived from a serial number (`serial-<serial>`)
+const SERIAL_ID_PREFIX: &str = "serial-";
+
 /// Legacy device scope enum - kept for backward compatibility with old registry files
 /// This allows old registry files with the "scope" field to be loaded without errors
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -168,19 +171,37 @@ impl DeviceRegistry {
 
     /// Rename a device
     ///
-    /// Validates that name is ≤64 chars and contains only valid characters
+    /// Validates that name is ≤64 chars and contains only valid characters.
+    /// `id` may also be the `serial-` ID of a device stored under another ID.
+    /// A renamed device with a serial number is re-keyed to its `serial-` ID,
+    /// so the name follows the physical device when its path changes.
     pub fn rename(&mut self, id: &str, name: &str) -> Result<(), DeviceValidationError> {
         validate_device_name(name)?;
 
-        let device = self
-            .devices
-            .get_mut(id)
+        let mut device = self
+            .find_key(id, None)
+            .and_then(|key| self.devices.remove(&key))
             .ok_or_else(|| DeviceValidationError::DeviceNotFound(id.to_string()))?;
 
         device.name = name.to_string();
+        if let Some(serial) = device.serial.as_deref().filter(|s| !s.is_empty()) {
+            device.id = format!("{}{}", SERIAL_ID_PREFIX, serial);
+        }
+        self.devices.insert(device.id.clone(), device);
         Ok(())
     }
 
+    /// Returns true if the device's metadata is tied to its device path
+    ///
+    /// Such a device has no serial number, so its name and layout are lost
+    /// if it reconnects at a different path.
+    pub fn is_path_bound(&self, id: &str) -> bool {
+        self.find(id, None).is_some_and(|device| {
+            device.serial.as_deref().is_none_or(str::is_empty)
+                && !device.id.starts_with(SERIAL_ID_PREFIX)
+        })
+    }
+
     /// Set device layout
     ///
     /// Validates that layout name is ≤32 chars
@@ -213,6 +234,31 @@ impl DeviceRegistry {
         self.devices.get(id)
     }
 
+    /// Find a device by ID, falling back to its serial number
+    ///
+    /// `serial` defaults to the serial embedded in a `serial-` ID. An entry
+    /// stored under a path-based ID is still found after the device
+    /// reconnects at a different path, as long as its serial matches.
+    pub fn find(&self, id: &str, serial: Option<&str>) -> Option<&DeviceEntry> {
+        self.find_key(id, serial)
+            .and_then(|key| self.devices.get(&key))
+    }
+
+    /// Returns the registry key of the device matching `id` or `serial`
+    fn find_key(&self, id: &str, serial: Option<&str>) -> Option<String> {
+        if self.devices.contains_key(id) {
+            return Some(id.to_string());
+        }
+
+        let serial = serial
+            .or_else(|| id.strip_prefix(SERIAL_ID_PREFIX))
+            .filter(|s| !s.is_empty())?;
+        self.devices
+            .iter()
+            .find(|(_, device)| device.serial.as_deref() == Some(serial))
+            .map(|(key, _)| key.clone())
+    }
+
     /// Update last_seen timestamp for a device
     pub fn update_last_seen(&mut self, id: &str) -> Result<(), DeviceValidationError> {
         let device = self
@@ -236,6 +282,41 @@ impl DeviceRegistry {
         self.devices.insert(entry.id.clone(), entry);
         Ok(())
     }
+
+    /// Merge an entry from another registry, e.g. an imported bundle
+    ///
+    /// The entry is matched by ID, falling back to its serial number, so a
+    /// device stored here under a path-based ID merges with a `serial-`
+    /// entry for the same keyboard. Unknown devices are registered as-is.
+    /// For a known device the local key is kept, the more recently seen
+    /// entry's name and layout win, and missing fields are filled from the
+    /// other entry. Returns true if the device was added.
+    pub fn merge(&mut self, entry: DeviceEntry) -> Result<bool, DeviceValidationError> {
+        let Some(local) = self
+            .find_key(&entry.id, entry.serial.as_deref())
+            .and_then(|key| self.devices.get_mut(&key))
+        else {
+            self.register(entry)?;
+            return Ok(true);
+        };
+
+        validate_device_name(&entry.name)?;
+        if let Some(layout) = &entry.layout {
+            validate_layout_name(layout)?;
+        }
+
+        if entry.last_seen > local.last_seen {
+            local.name = entry.name;
+            local.layout = entry.layout.or(local.layout.take());
+            local.last_seen = entry.last_seen;
+        } else if local.layout.is_none() {
+            local.layout = entry.layout;
+        }
+        if local.serial.as_deref().is_none_or(str::is_empty) {
+            local.serial = entry.serial;
+        }
+        Ok(false)
+    }
 }
 
 /// Validate device name: ≤64 chars, alphanumeric + space/dash/underscore only
@@ -394,6 +475,57 @@ mod tests {
         assert_eq!(registry.get("dev1").unwrap().name, "New Name");
     }
 
+    #[test]
+    fn test_rename_follows_serial_across_path_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("registry.json");
+        let mut registry = DeviceRegistry::new(path.clone());
+
+        // Registered under its path, but the serial is known
+        let device = DeviceEntry::new(
+            "path-/dev/input/event3".to_string(),
+            "Keyboard".to_string(),
+            Some("ABC123".to_string()),
+            None,
+            current_timestamp(),
+        );
+        registry.register(device).unwrap();
+
+        registry
+            .rename("path-/dev/input/event3", "Work Keyboard")
+            .unwrap();
+        assert!(!registry.is_path_bound("serial-ABC123"));
+        registry.save().unwrap();
+
+        // Replugged: same serial, different path
+        let reloaded = DeviceRegistry::load(&path).unwrap();
+        let found = reloaded
+            .find("path-/dev/input/event7", Some("ABC123"))
+            .unwrap();
+        assert_eq!(found.name, "Work Keyboard");
+        assert_eq!(found.id, "serial-ABC123");
+        assert_eq!(
+            reloaded.find("serial-ABC123", None).unwrap().name,
+            "Work Keyboard"
+        );
+    }
+
+    #[test]
+    fn test_rename_without_serial_is_path_bound() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("registry.json");
+        let mut registry = DeviceRegistry::new(path);
+
+        let device = create_test_device("path-/dev/input/event3", "Keyboard");
+        registry.register(device).unwrap();
+
+        registry
+            .rename("path-/dev/input/event3", "Laptop Keyboard")
+            .unwrap();
+        assert!(registry.is_path_bound("path-/dev/input/event3"));
+        assert!(registry.find("path-/dev/input/event7", None).is_none());
+    }
+
     #[test]
     fn test_rename_nonexistent_device() {
         let temp_dir = TempDir::new().unwrap();
@@ -608,4 +740,57 @@ mod tests {
         registry.register(device).unwrap();
         assert_eq!(registry.list().len(), 1);
     }
+
+    #[test]
+    fn test_merge_matches_by_serial_and_prefers_newer_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut registry = DeviceRegistry::new(temp_dir.path().join("registry.json"));
+        registry
+            .register(DeviceEntry::new(
+                "/dev/input/event3".to_string(),
+                "Old Name".to_string(),
+                Some("ABC123".to_string()),
+                Some("ansi_104".to_string()),
+                100,
+            ))
+            .unwrap();
+
+        let added = registry
+            .merge(DeviceEntry::new(
+                "serial-ABC123".to_string(),
+                "New Name".to_string(),
+                Some("ABC123".to_string()),
+                None,
+                200,
+            ))
+            .unwrap();
+
+        assert!(!added);
+        assert_eq!(registry.list().len(), 1);
+        let device = registry.get("/dev/input/event3").unwrap();
+        assert_eq!(device.name, "New Name");
+        assert_eq!(device.layout.as_deref(), Some("ansi_104"));
+        assert_eq!(device.last_seen, 200);
+
+        // An older entry only fills in what is missing
+        let added = registry
+            .merge(DeviceEntry::new(
+                "serial-ABC123".to_string(),
+                "Stale Name".to_string(),
+                Some("ABC123".to_string()),
+                Some("iso_105".to_string()),
+                50,
+            ))
+            .unwrap();
+        assert!(!added);
+        let device = registry.get("/dev/input/event3").unwrap();
+        assert_eq!(device.name, "New Name");
+        assert_eq!(device.layout.as_deref(), Some("ansi_104"));
+
+        let added = registry
+            .merge(create_test_device("serial-XYZ", "Other"))
+            .unwrap();
+        assert!(added);
+        assert_eq!(registry.list().len(), 2);
+    }
 }
@@ -3,9 +3,10 @@
 //! This module manages keyboard layouts in keyboard-layout-editor.com (KLE) JSON format.
 //! It provides builtin layouts embedded in the binary and supports importing custom layouts.
 
+use keyrx_core::config::{BaseKeyMapping, ConfigRoot, DeviceConfig, KeyCode, KeyMapping};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Maximum number of custom layouts allowed
@@ -80,6 +81,15 @@ pub enum LayoutError {
 /// Result type for layout operations
 pub type Result<T> = std::result::Result<T, LayoutError>;
 
+/// Builtin layouts embedded in the binary: (name, KLE JSON)
+const BUILTIN_LAYOUTS: [(&str, &str); 5] = [
+    ("ansi_104", include_str!("../../layouts/ansi_104.json")),
+    ("iso_105", include_str!("../../layouts/iso_105.json")),
+    ("jis_109", include_str!("../../layouts/jis_109.json")),
+    ("hhkb", include_str!("../../layouts/hhkb.json")),
+    ("numpad", include_str!("../../layouts/numpad.json")),
+];
+
 /// Manages keyboard layouts in KLE JSON format
 pub struct LayoutManager {
     /// Directory where custom layouts are stored
@@ -107,15 +117,7 @@ impl LayoutManager {
 
     /// Load builtin layouts embedded in the binary
     fn load_builtin_layouts(&mut self) -> Result<()> {
-        let builtins = [
-            ("ansi_104", include_str!("../../layouts/ansi_104.json")),
-            ("iso_105", include_str!("../../layouts/iso_105.json")),
-            ("jis_109", include_str!("../../layouts/jis_109.json")),
-            ("hhkb", include_str!("../../layouts/hhkb.json")),
-            ("numpad", include_str!("../../layouts/numpad.json")),
-        ];
-
-        for (name, json_str) in builtins {
+        for (name, json_str) in BUILTIN_LAYOUTS {
             let kle_json: JsonValue = serde_json::from_str(json_str)?;
             Self::validate_kle(&kle_json)?;
 
@@ -169,7 +171,16 @@ impl LayoutManager {
         }
 
         let json_str = std::fs::read_to_string(path)?;
-        let kle_json: JsonValue = serde_json::from_str(&json_str)?;
+        Self::parse_layout(&json_str, name)
+    }
+
+    /// Parse and validate a custom layout from KLE JSON text
+    fn parse_layout(json_str: &str, name: &str) -> Result<KeyboardLayout> {
+        if json_str.len() > MAX_LAYOUT_FILE_SIZE {
+            return Err(LayoutError::FileTooLarge);
+        }
+
+        let kle_json: JsonValue = serde_json::from_str(json_str)?;
         Self::validate_kle(&kle_json)?;
 
         Ok(KeyboardLayout {
@@ -259,6 +270,48 @@ impl LayoutManager {
 
     /// Import a layout from a file
     pub fn import(&mut self, path: &Path, name: &str) -> Result<KeyboardLayout> {
+        self.check_importable(name)?;
+
+        // Check file exists
+        if !path.exists() {
+            return Err(LayoutError::FileNotFound(
+                path.to_string_lossy().to_string(),
+            ));
+        }
+
+        // Load and validate the layout
+        let layout = self.load_layout_file(path, name)?;
+        self.store_custom(layout)
+    }
+
+    /// Import a layout from KLE JSON text
+    ///
+    /// Like [`import`](Self::import), an existing custom layout with the
+    /// same name is replaced.
+    pub fn import_json(&mut self, name: &str, json_str: &str) -> Result<KeyboardLayout> {
+        self.check_importable(name)?;
+
+        let layout = Self::parse_layout(json_str, name)?;
+        self.store_custom(layout)
+    }
+
+    /// Check that `name` is a valid custom layout name that does not
+    /// belong to a builtin layout
+    ///
+    /// Unlike importing, this needs no layouts directory, so callers can
+    /// reject a name before writing anything.
+    pub fn check_custom_name(name: &str) -> Result<()> {
+        Self::validate_name(name)?;
+
+        if BUILTIN_LAYOUTS.iter().any(|(builtin, _)| *builtin == name) {
+            return Err(LayoutError::BuiltinOverwrite(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check that a custom layout may be stored under `name`
+    fn check_importable(&self, name: &str) -> Result<()> {
         Self::validate_name(name)?;
 
         // Check if trying to overwrite a builtin layout
@@ -273,23 +326,19 @@ impl LayoutManager {
             return Err(LayoutError::TooManyLayouts);
         }
 
-        // Check file exists
-        if !path.exists() {
-            return Err(LayoutError::FileNotFound(
-                path.to_string_lossy().to_string(),
-            ));
-        }
-
-        // Load and validate the layout
-        let layout = self.load_layout_file(path, name)?;
+        Ok(())
+    }
 
+    /// Write a validated custom layout to the layouts directory
+    fn store_custom(&mut self, layout: KeyboardLayout) -> Result<KeyboardLayout> {
         // Save to layouts directory
-        let dest_path = self.layouts_dir.join(format!("{}.json", name));
+        let dest_path = self.layouts_dir.join(format!("{}.json", layout.name));
         let json_str = serde_json::to_string_pretty(&layout.kle_json)?;
         std::fs::write(&dest_path, json_str)?;
 
         // Add to custom layouts
-        self.custom_layouts.insert(name.to_string(), layout.clone());
+        self.custom_layouts
+            .insert(layout.name.clone(), layout.clone());
 
         Ok(layout)
     }
@@ -329,6 +378,237 @@ impl LayoutManager {
     }
 }
 
+/// Overlays a device's remapped outputs onto a KLE layout.
+///
+/// Each key whose legend resolves to a mapped `KeyCode` gets the mapping's
+/// output as its top legend and the original legend below it. Unmapped and
+/// unrecognized keys keep their legends, and all KLE properties objects are
+/// copied unchanged, so the result can be pasted into keyboard-layout-editor.com.
+///
+/// Only unconditional mappings are shown; layer mappings depend on runtime
+/// state and have no single legend.
+pub fn export_kle(kle: &JsonValue, device: &DeviceConfig) -> Result<JsonValue> {
+    LayoutManager::validate_kle(kle)?;
+
+    let labels: HashMap<KeyCode, String> = device
+        .mappings
+        .iter()
+        .filter_map(|mapping| match mapping {
+            KeyMapping::Base(base) => Some(mapping_label(base)),
+            KeyMapping::Conditional { .. } => None,
+        })
+        .collect();
+
+    let mut resolver = LegendResolver::default();
+    let mut exported = kle.clone();
+    if let Some(rows) = exported.as_array_mut() {
+        for row in rows.iter_mut().filter_map(JsonValue::as_array_mut) {
+            for key in row.iter_mut() {
+                let Some(legend) = key.as_str() else {
+                    continue;
+                };
+                let keycode = resolver.resolve(legend);
+
+                if let Some(label) = keycode.and_then(|code| labels.get(&code)) {
+                    *key = JsonValue::String(format!("{}\n{}", label, legend.replace('\n', " ")));
+                }
+            }
+        }
+    }
+
+    Ok(exported)
+}
+
+/// Differences between the keys a layout draws and the keys a config remaps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LayoutValidation {
+    /// Keys the config remaps that have no keycap on the layout
+    pub missing_from_layout: Vec<KeyCode>,
+    /// Keys on the layout that no device in the config remaps
+    pub unmapped_in_layout: Vec<KeyCode>,
+    /// Legends that do not resolve to a key code
+    pub unrecognized_legends: Vec<String>,
+}
+
+impl LayoutValidation {
+    /// Returns true if every remapped key has a keycap on the layout.
+    ///
+    /// Unmapped layout keys are expected on most layouts and do not leave
+    /// gaps in the visualization, so they are not counted.
+    pub fn is_complete(&self) -> bool {
+        self.missing_from_layout.is_empty()
+    }
+}
+
+/// Cross-checks a KLE layout against the keys a compiled config remaps.
+///
+/// Remapped keys of all devices are considered, including mappings nested
+/// inside conditional blocks. Key lists are sorted by key code.
+pub fn validate_layout(kle: &JsonValue, config: &ConfigRoot) -> Result<LayoutValidation> {
+    LayoutManager::validate_kle(kle)?;
+
+    let mut resolver = LegendResolver::default();
+    let mut layout_keys = Vec::new();
+    let mut unrecognized_legends = Vec::new();
+    let rows = kle.as_array().into_iter().flatten();
+    for row in rows.filter_map(JsonValue::as_array) {
+        for legend in row.iter().filter_map(JsonValue::as_str) {
+            match resolver.resolve(legend) {
+                Some(keycode) => layout_keys.push(keycode),
+                None => unrecognized_legends.push(legend.replace('\n', " ")),
+            }
+        }
+    }
+    let layout_set: BTreeSet<u16> = layout_keys.iter().map(|key| *key as u16).collect();
+
+    let mut config_keys: Vec<KeyCode> = config
+        .devices
+        .iter()
+        .flat_map(DeviceConfig::input_keys)
+        .collect();
+    let config_set: BTreeSet<u16> = config_keys.iter().map(|key| *key as u16).collect();
+
+    config_keys.retain(|key| !layout_set.contains(&(*key as u16)));
+    layout_keys.retain(|key| !config_set.contains(&(*key as u16)));
+    for keys in [&mut config_keys, &mut layout_keys] {
+        keys.sort_unstable_by_key(|key| *key as u16);
+        keys.dedup();
+    }
+
+    Ok(LayoutValidation {
+        missing_from_layout: config_keys,
+        unmapped_in_layout: layout_keys,
+        unrecognized_legends,
+    })
+}
+
+/// Returns the input key of a mapping and a short keycap label for its output.
+fn mapping_label(mapping: &BaseKeyMapping) -> (KeyCode, String) {
+    match mapping {
+        BaseKeyMapping::Simple { from, to } => (*from, format!("{:?}", to)),
+        BaseKeyMapping::Modifier { from, modifier_id } => {
+            (*from, format!("MD_{:02X}", modifier_id))
+        }
+        BaseKeyMapping::Lock { from, lock_id } => (*from, format!("LK_{:02X}", lock_id)),
+        BaseKeyMapping::TapHold {
+            from,
+            tap,
+            hold_modifier,
+            ..
+        } => (*from, format!("{:?}/MD_{:02X}", tap, hold_modifier)),
+        BaseKeyMapping::ModifiedOutput {
+            from,
+            to,
+            shift,
+            ctrl,
+            alt,
+            win,
+        } => {
+            let mut label = String::new();
+            for (enabled, name) in [(*shift, "S-"), (*ctrl, "C-"), (*alt, "A-"), (*win, "W-")] {
+                if enabled {
+                    label.push_str(name);
+                }
+            }
+            label.push_str(&format!("{:?}", to));
+            (*from, label)
+        }
+        BaseKeyMapping::OnRelease { from, to, .. } => (*from, format!("{:?}↑", to)),
+        BaseKeyMapping::LayerToggle { from, layer_id } => {
+            (*from, format!("TG MD_{:02X}", layer_id))
+        }
+        BaseKeyMapping::Cycle { from, outputs, .. } => {
+            let outputs: Vec<String> = outputs.iter().map(|key| format!("{:?}", key)).collect();
+            (*from, outputs.join("/"))
+        }
+        BaseKeyMapping::Macro { from, .. } => (*from, "▶".to_string()),
+        BaseKeyMapping::Disable { from } => (*from, "∅".to_string()),
+    }
+}
+
+/// Resolves the legends of a layout's keys, in order, to key codes.
+#[derive(Default)]
+struct LegendResolver {
+    /// Keys seen so far per legend; left/right modifiers share a legend and
+    /// the first occurrence is the left one
+    seen: HashMap<String, usize>,
+}
+
+impl LegendResolver {
+    /// Resolves the next key's full KLE label using its bottom line.
+    fn resolve(&mut self, label: &str) -> Option<KeyCode> {
+        let bottom = label.rsplit('\n').next().unwrap_or(label).trim();
+        let occurrence = self.seen.entry(bottom.to_string()).or_insert(0);
+        let keycode = legend_keycode(bottom, *occurrence);
+        *occurrence += 1;
+        keycode
+    }
+}
+
+/// Resolves a KLE legend (the bottom line of a key's label) to a `KeyCode`.
+///
+/// `occurrence` counts earlier keys with the same legend, so the second
+/// "Shift" on a row is the right shift.
+fn legend_keycode(legend: &str, occurrence: usize) -> Option<KeyCode> {
+    let left = occurrence == 0;
+    let keycode = match legend {
+        "Shift" => {
+            if left {
+                KeyCode::LShift
+            } else {
+                KeyCode::RShift
+            }
+        }
+        "Ctrl" | "Control" => {
+            if left {
+                KeyCode::LCtrl
+            } else {
+                KeyCode::RCtrl
+            }
+        }
+        "Alt" => {
+            if left {
+                KeyCode::LAlt
+            } else {
+                KeyCode::RAlt
+            }
+        }
+        "Win" => {
+            if left {
+                KeyCode::LMeta
+            } else {
+                KeyCode::RMeta
+            }
+        }
+        "AltGr" => KeyCode::RAlt,
+        "Caps Lock" | "Caps" => KeyCode::CapsLock,
+        "PrtSc" => KeyCode::PrintScreen,
+        "ScrlLk" => KeyCode::ScrollLock,
+        "PgUp" => KeyCode::PageUp,
+        "PgDn" => KeyCode::PageDown,
+        "`" => KeyCode::Grave,
+        "-" => KeyCode::Minus,
+        "=" => KeyCode::Equal,
+        "[" => KeyCode::LeftBracket,
+        "]" => KeyCode::RightBracket,
+        "\\" => KeyCode::Backslash,
+        ";" => KeyCode::Semicolon,
+        "'" => KeyCode::Quote,
+        "," => KeyCode::Comma,
+        "." => KeyCode::Period,
+        "/" => KeyCode::Slash,
+        "Num Lock" => KeyCode::NumLock,
+        "Num /" => KeyCode::NumpadDivide,
+        "Num *" => KeyCode::NumpadMultiply,
+        "Num -" => KeyCode::NumpadSubtract,
+        "Num +" => KeyCode::NumpadAdd,
+        "Num Enter" => KeyCode::NumpadEnter,
+        "Num 5" => KeyCode::Numpad5,
+        other => return keyrx_compiler::parser::validators::parse_key_name(other).ok(),
+    };
+    Some(keycode)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,6 +622,105 @@ mod tests {
         ])
     }
 
+    fn device_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
+        DeviceConfig {
+            identifier: keyrx_core::config::DeviceIdentifier {
+                pattern: "*".to_string(),
+                excludes: Vec::new(),
+            },
+            mappings,
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_export_kle_overlays_mapped_legends() {
+        let kle = json!([
+            ["Esc", "!\n1"],
+            [{"w": 1.75}, "Caps Lock", "A"],
+            ["Shift", "Z", "Shift"]
+        ]);
+        let device = device_config(vec![
+            KeyMapping::simple(KeyCode::CapsLock, KeyCode::Escape),
+            KeyMapping::modifier(KeyCode::A, 0),
+            KeyMapping::simple(KeyCode::RShift, KeyCode::Enter),
+            KeyMapping::modified_output(KeyCode::Num1, KeyCode::Num2, true, false, false, false),
+        ]);
+
+        let exported = export_kle(&kle, &device).unwrap();
+        assert_eq!(
+            exported,
+            json!([
+                ["Esc", "S-Num2\n! 1"],
+                [{"w": 1.75}, "Escape\nCaps Lock", "MD_00\nA"],
+                ["Shift", "Z", "Enter\nShift"]
+            ])
+        );
+    }
+
+    #[test]
+    fn test_export_kle_ignores_conditional_mappings() {
+        let kle = create_test_kle();
+        let device = device_config(vec![KeyMapping::conditional(
+            keyrx_core::config::Condition::ModifierActive(0),
+            vec![BaseKeyMapping::Simple {
+                from: KeyCode::Q,
+                to: KeyCode::Left,
+            }],
+        )]);
+        assert_eq!(export_kle(&kle, &device).unwrap(), kle);
+    }
+
+    #[test]
+    fn test_validate_layout_reports_mismatches() {
+        use keyrx_core::config::{Metadata, Version};
+
+        let kle = json!([
+            [{"w": 1}, "Esc", "!\n1", "Foo"],
+            ["Caps Lock", "A", "S"]
+        ]);
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: vec![
+                device_config(vec![
+                    KeyMapping::simple(KeyCode::CapsLock, KeyCode::Escape),
+                    KeyMapping::conditional(
+                        keyrx_core::config::Condition::ModifierActive(0),
+                        vec![BaseKeyMapping::Simple {
+                            from: KeyCode::J,
+                            to: KeyCode::Left,
+                        }],
+                    ),
+                ]),
+                device_config(vec![
+                    KeyMapping::modifier(KeyCode::A, 0),
+                    KeyMapping::simple(KeyCode::F13, KeyCode::Escape),
+                ]),
+            ],
+            metadata: Metadata {
+                compilation_timestamp: 0,
+                compiler_version: "test".to_string(),
+                source_hash: "test".to_string(),
+            },
+        };
+
+        let validation = validate_layout(&kle, &config).unwrap();
+        assert!(!validation.is_complete());
+
+        let mut missing = vec![KeyCode::J, KeyCode::F13];
+        missing.sort_unstable_by_key(|key| *key as u16);
+        assert_eq!(validation.missing_from_layout, missing);
+
+        let mut unmapped = vec![KeyCode::Escape, KeyCode::Num1, KeyCode::S];
+        unmapped.sort_unstable_by_key(|key| *key as u16);
+        assert_eq!(validation.unmapped_in_layout, unmapped);
+
+        assert_eq!(validation.unrecognized_legends, vec!["Foo".to_string()]);
+    }
+
     #[test]
     fn test_validate_kle_valid() {
         let kle = create_test_kle();
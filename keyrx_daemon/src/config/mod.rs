@@ -3,6 +3,9 @@
 //! This module provides components for managing device metadata,
 //! profiles, layouts, and configuration generation.
 
+pub mod auto_test;
+pub mod benchmark;
+pub mod bundle;
 pub mod device;
 pub mod device_registry;
 pub mod layout_manager;
@@ -11,6 +14,7 @@ pub mod profile_manager;
 pub mod rhai_generator;
 pub mod simulation_engine;
 
+pub use bundle::{BundleError, ConflictPolicy, ExportSummary, ImportAction, ImportSummary};
 pub use device::{DeviceConfig, Scope};
 pub use device_registry::{DeviceEntry, DeviceRegistry, DeviceValidationError};
 pub use layout_manager::{KeyboardLayout, LayoutError, LayoutManager, LayoutSource};
@@ -21,5 +25,5 @@ pub use profile_manager::{
 pub use rhai_generator::{GeneratorError, KeyAction, LayerMode, MacroStep, RhaiGenerator};
 pub use simulation_engine::{
     BuiltinScenario, EventSequence, EventType, OutputEvent, ScenarioResult, SimulatedEvent,
-    SimulationEngine, SimulationError, VirtualClock,
+    SimulationEngine, SimulationError, TimingBoundary, TimingScenario, VirtualClock,
 };
@@ -501,6 +501,34 @@ impl ProfileManager {
 
     /// Import a profile from a file.
     pub fn import(&mut self, src: &Path, name: &str) -> Result<ProfileMetadata, ProfileError> {
+        let dest_rhai = self.new_profile_path(name)?;
+        fs::copy(src, &dest_rhai)?;
+
+        let metadata = self.load_profile_metadata(name)?;
+        self.profiles.insert(name.to_string(), metadata.clone());
+
+        Ok(metadata)
+    }
+
+    /// Import a profile from its Rhai source.
+    ///
+    /// Fails like [`import`](Self::import) if the name is invalid or taken.
+    pub fn import_config(
+        &mut self,
+        name: &str,
+        content: &str,
+    ) -> Result<ProfileMetadata, ProfileError> {
+        let dest_rhai = self.new_profile_path(name)?;
+        fs::write(&dest_rhai, content)?;
+
+        let metadata = self.load_profile_metadata(name)?;
+        self.profiles.insert(name.to_string(), metadata.clone());
+
+        Ok(metadata)
+    }
+
+    /// Returns the .rhai path for a new profile, checking name and limits.
+    fn new_profile_path(&self, name: &str) -> Result<PathBuf, ProfileError> {
         Self::validate_name(name)?;
 
         if self.profiles.len() >= MAX_PROFILES {
@@ -515,12 +543,12 @@ impl ProfileManager {
             return Err(ProfileError::AlreadyExists(name.to_string()));
         }
 
-        fs::copy(src, &dest_rhai)?;
-
-        let metadata = self.load_profile_metadata(name)?;
-        self.profiles.insert(name.to_string(), metadata.clone());
+        Ok(dest_rhai)
+    }
 
-        Ok(metadata)
+    /// Returns the configuration directory profiles are stored under.
+    pub fn config_dir(&self) -> &Path {
+        &self.config_dir
     }
 
     /// List all profiles.
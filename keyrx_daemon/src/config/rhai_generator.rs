@@ -33,6 +33,9 @@ pub enum GeneratorError {
     #[error("Device block not found")]
     DeviceNotFound,
 
+    #[error("Device block not found: {0}")]
+    DeviceBlockNotFound(String),
+
     #[error("Unclosed when block for layer: {0}")]
     UnclosedWhenBlock(String),
 }
@@ -50,9 +53,30 @@ pub enum KeyAction {
         threshold_ms: u16,
     },
 
+    /// Output with modifiers: map("VK_A", with_shift("VK_1"))
+    ModifiedOutput {
+        output: String,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        win: bool,
+    },
+
+    /// Output on release: on_release("VK_CapsLock", "VK_Escape", 200)
+    OnRelease { output: String, threshold_ms: u16 },
+
+    /// Layer toggle: layer_toggle("VK_F13", "MD_01")
+    LayerToggle { layer: String },
+
+    /// Cycle through outputs: cycle("VK_F1", ["VK_A", "VK_B"], 500)
+    Cycle { outputs: Vec<String>, reset_ms: u16 },
+
     /// Macro sequence
     Macro { sequence: Vec<MacroStep> },
 
+    /// Disabled key that emits nothing: disable("VK_Insert")
+    Disable,
+
     /// Conditional mapping (when blocks handle this differently)
     Conditional {
         condition: String,
@@ -78,6 +102,16 @@ pub enum LayerMode {
     Multiple,
 }
 
+/// DSL functions whose first argument is the input key
+const MAPPING_FUNCTIONS: [&str; 6] = [
+    "map(",
+    "tap_hold(",
+    "on_release(",
+    "layer_toggle(",
+    "cycle(",
+    "disable(",
+];
+
 /// Represents a parsed Rhai configuration file structure
 #[derive(Debug)]
 pub struct RhaiGenerator {
@@ -102,8 +136,22 @@ impl RhaiGenerator {
         Self::parse(&content)
     }
 
+    /// Load a Rhai file and select the `device_start("<device>")` block
+    ///
+    /// Every other device block is kept verbatim in the header or footer, so
+    /// saving only rewrites the selected block.
+    pub fn load_device(path: &Path, device: &str) -> Result<Self, GeneratorError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse_device(&content, Some(device))
+    }
+
     /// Parse Rhai source into structured representation
     fn parse(source: &str) -> Result<Self, GeneratorError> {
+        Self::parse_device(source, None)
+    }
+
+    /// Parse Rhai source, selecting the block for `device` (or the first block)
+    fn parse_device(source: &str, device: Option<&str>) -> Result<Self, GeneratorError> {
         let mut header = Vec::new();
         let mut device_id = String::new();
         let mut base_mappings = Vec::new();
@@ -122,11 +170,18 @@ impl RhaiGenerator {
                 Section::Header => {
                     if trimmed.starts_with("device_start(") {
                         // Extract device ID
+                        let mut id = String::new();
                         if let Some(start) = trimmed.find('"') {
                             if let Some(end) = trimmed[start + 1..].find('"') {
-                                device_id = trimmed[start + 1..start + 1 + end].to_string();
+                                id = trimmed[start + 1..start + 1 + end].to_string();
                             }
                         }
+                        if device.is_some_and(|wanted| wanted != id) {
+                            // Not the requested device: keep the block as-is
+                            header.push(line.to_string());
+                            continue;
+                        }
+                        device_id = id;
                         current_section = Section::DeviceBody;
                     } else {
                         header.push(line.to_string());
@@ -171,7 +226,10 @@ impl RhaiGenerator {
         }
 
         if device_id.is_empty() {
-            return Err(GeneratorError::DeviceNotFound);
+            return Err(match device {
+                Some(wanted) => GeneratorError::DeviceBlockNotFound(wanted.to_string()),
+                None => GeneratorError::DeviceNotFound,
+            });
         }
 
         Ok(Self {
@@ -207,6 +265,9 @@ impl RhaiGenerator {
     }
 
     /// Set a key mapping in a specific layer
+    ///
+    /// An existing mapping for the key is replaced in place so surrounding
+    /// comments stay attached; otherwise the mapping is appended.
     pub fn set_key_mapping(
         &mut self,
         layer: &str,
@@ -217,28 +278,42 @@ impl RhaiGenerator {
 
         let mapping_line = Self::generate_mapping_line(key, &action)?;
 
-        if layer == "base" || layer.is_empty() {
-            // Remove existing mapping for this key
-            self.base_mappings
-                .retain(|line| !Self::is_mapping_for_key(line, key));
-            // Add new mapping
-            self.base_mappings.push(mapping_line);
+        let lines = if layer == "base" || layer.is_empty() {
+            &mut self.base_mappings
         } else {
             // Layer-specific mapping
-            if !self.layers.contains_key(layer) {
-                return Err(GeneratorError::LayerNotFound(layer.to_string()));
-            }
-
-            // SAFETY: Layer existence verified at line 228: contains_key check guarantees get_mut succeeds
-            #[allow(clippy::unwrap_used)]
-            let layer_lines = self.layers.get_mut(layer).unwrap();
-            layer_lines.retain(|line| !Self::is_mapping_for_key(line, key));
-            layer_lines.push(mapping_line);
-        }
+            self.layers
+                .get_mut(layer)
+                .ok_or_else(|| GeneratorError::LayerNotFound(layer.to_string()))?
+        };
+        Self::replace_or_push(lines, key, mapping_line);
 
         Ok(())
     }
 
+    /// Replaces the first mapping for `key` with `mapping_line`, dropping any
+    /// later duplicates, or appends it if the key has no mapping yet
+    fn replace_or_push(lines: &mut Vec<String>, key: &str, mapping_line: String) {
+        match lines
+            .iter()
+            .position(|line| Self::is_mapping_for_key(line, key))
+        {
+            Some(index) => {
+                // Keep the existing indentation
+                let indent_len = lines[index].len() - lines[index].trim_start().len();
+                let indent = lines[index][..indent_len].to_string();
+                lines[index] = format!("{}{}", indent, mapping_line.trim_start());
+                let mut position = 0;
+                lines.retain(|line| {
+                    let keep = position <= index || !Self::is_mapping_for_key(line, key);
+                    position += 1;
+                    keep
+                });
+            }
+            None => lines.push(mapping_line),
+        }
+    }
+
     /// Delete a key mapping from a layer
     pub fn delete_key_mapping(&mut self, layer: &str, key: &str) -> Result<(), GeneratorError> {
         Self::validate_key_name(key)?;
@@ -362,6 +437,11 @@ impl RhaiGenerator {
     }
 
     /// Generate Rhai source string
+    ///
+    /// The device block is written in canonical form (see
+    /// [`RhaiGenerator::canonical_lines`]) so regenerating an unchanged
+    /// config gives byte-identical output. Layers keep their order, since it
+    /// decides which mapping wins when several layers are active.
     fn generate_source(&self) -> String {
         let mut lines = Vec::new();
 
@@ -373,8 +453,9 @@ impl RhaiGenerator {
         lines.push(String::new());
 
         // Base mappings
-        if !self.base_mappings.is_empty() {
-            lines.extend(self.base_mappings.iter().cloned());
+        let base_mappings = Self::canonical_lines(&self.base_mappings);
+        if !base_mappings.is_empty() {
+            lines.extend(base_mappings);
             lines.push(String::new());
         }
 
@@ -382,7 +463,7 @@ impl RhaiGenerator {
         for layer_id in &self.layer_order {
             if let Some(layer_lines) = self.layers.get(layer_id) {
                 lines.push(format!("when_start(\"{}\");", layer_id));
-                lines.extend(layer_lines.iter().cloned());
+                lines.extend(Self::canonical_lines(layer_lines));
                 lines.push("when_end();".to_string());
                 lines.push(String::new());
             }
@@ -391,13 +472,86 @@ impl RhaiGenerator {
         // Device end
         lines.push("device_end();".to_string());
 
-        // Footer
-        if !self.footer.is_empty() {
+        // Footer, without the blank lines around it so they do not pile up
+        // on every save
+        let footer = trim_blank_lines(&self.footer);
+        if !footer.is_empty() {
             lines.push(String::new());
-            lines.extend(self.footer.iter().cloned());
+            lines.extend(footer.iter().cloned());
+        }
+
+        let mut source = lines.join("\n");
+        source.push('\n');
+        source
+    }
+
+    /// Returns the lines of a base or layer section in canonical form
+    ///
+    /// Lines are trimmed and indented by two spaces, and blank lines are
+    /// dropped. Each run of single-line mappings is sorted by input key,
+    /// then by mapping function; comments directly above a mapping move with
+    /// it. Any other statement ends the run and stays in place, so a `let`
+    /// is never moved below the mappings that use it.
+    fn canonical_lines(lines: &[String]) -> Vec<String> {
+        let mut output = Vec::new();
+        let mut comments = Vec::new();
+        let mut run: Vec<((String, usize), Vec<String>)> = Vec::new();
+
+        let flush = |run: &mut Vec<((String, usize), Vec<String>)>, output: &mut Vec<String>| {
+            run.sort_by(|a, b| a.0.cmp(&b.0));
+            output.extend(run.drain(..).flat_map(|(_, group)| group));
+        };
+
+        for line in lines {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let indented = format!("  {}", trimmed);
+
+            if trimmed.starts_with("//") {
+                comments.push(indented);
+            } else if let Some(sort_key) = Self::mapping_sort_key(trimmed) {
+                let mut group: Vec<String> = std::mem::take(&mut comments);
+                group.push(indented);
+                run.push((sort_key, group));
+            } else {
+                flush(&mut run, &mut output);
+                output.append(&mut comments);
+                output.push(indented);
+            }
         }
+        flush(&mut run, &mut output);
+        output.append(&mut comments);
 
-        lines.join("\n")
+        output
+    }
+
+    /// Sort key of a single-line mapping: the input key without its `VK_`
+    /// prefix, then the position of the mapping function in
+    /// [`MAPPING_FUNCTIONS`]
+    ///
+    /// Returns `None` for anything else, including mappings that continue on
+    /// the next line.
+    fn mapping_sort_key(line: &str) -> Option<(String, usize)> {
+        if !line.ends_with(';') {
+            return None;
+        }
+        let (function, key) = Self::mapping_input(line)?;
+        Some((strip_vk_prefix(key).to_string(), function))
+    }
+
+    /// Returns the index of the mapping function in [`MAPPING_FUNCTIONS`]
+    /// and the input key a mapping line starts with
+    fn mapping_input(line: &str) -> Option<(usize, &str)> {
+        let trimmed = line.trim();
+        let function = MAPPING_FUNCTIONS
+            .iter()
+            .position(|function| trimmed.starts_with(function))?;
+        // Extract first argument
+        let start = trimmed.find('"')?;
+        let end = trimmed[start + 1..].find('"')?;
+        Some((function, &trimmed[start + 1..start + 1 + end]))
     }
 
     /// Validate syntax by parsing with Rhai engine
@@ -428,37 +582,154 @@ impl RhaiGenerator {
                     key, tap, hold, threshold_ms
                 ))
             }
+            KeyAction::ModifiedOutput {
+                output,
+                shift,
+                ctrl,
+                alt,
+                win,
+            } => {
+                Self::validate_key_name(output)?;
+                // Prefer the single-modifier helpers, which read better
+                let modified = match (shift, ctrl, alt, win) {
+                    (true, false, false, false) => format!("with_shift(\"{}\")", output),
+                    (false, true, false, false) => format!("with_ctrl(\"{}\")", output),
+                    (false, false, true, false) => format!("with_alt(\"{}\")", output),
+                    (false, false, false, true) => format!("with_win(\"{}\")", output),
+                    _ => format!(
+                        "with_mods(\"{}\", {}, {}, {}, {})",
+                        output, shift, ctrl, alt, win
+                    ),
+                };
+                Ok(format!("  map(\"{}\", {});", key, modified))
+            }
+            KeyAction::OnRelease {
+                output,
+                threshold_ms,
+            } => {
+                Self::validate_key_name(output)?;
+                Ok(format!(
+                    "  on_release(\"{}\", \"{}\", {});",
+                    key, output, threshold_ms
+                ))
+            }
+            KeyAction::LayerToggle { layer } => {
+                Self::validate_layer_id(layer)?;
+                Ok(format!("  layer_toggle(\"{}\", \"{}\");", key, layer))
+            }
+            KeyAction::Cycle { outputs, reset_ms } => {
+                if outputs.is_empty() {
+                    return Err(GeneratorError::SyntaxError(
+                        "Cycle needs at least one output".to_string(),
+                    ));
+                }
+                let mut quoted = Vec::new();
+                for output in outputs {
+                    Self::validate_key_name(output)?;
+                    quoted.push(format!("\"{}\"", output));
+                }
+                Ok(format!(
+                    "  cycle(\"{}\", [{}], {});",
+                    key,
+                    quoted.join(", "),
+                    reset_ms
+                ))
+            }
             KeyAction::Macro { sequence } => {
                 // Generate macro sequence
                 let mut steps = Vec::new();
                 for step in sequence {
                     match step {
-                        MacroStep::Press(k) => steps.push(format!("press(\"{}\")", k)),
-                        MacroStep::Release(k) => steps.push(format!("release(\"{}\")", k)),
+                        MacroStep::Press(k) => {
+                            Self::validate_key_name(k)?;
+                            steps.push(format!("press(\"{}\")", k));
+                        }
+                        MacroStep::Release(k) => {
+                            Self::validate_key_name(k)?;
+                            steps.push(format!("release(\"{}\")", k));
+                        }
                         MacroStep::Wait(ms) => steps.push(format!("wait({})", ms)),
                     }
                 }
                 Ok(format!("  macro(\"{}\", [{}]);", key, steps.join(", ")))
             }
+            KeyAction::Disable => Ok(format!("  disable(\"{}\");", key)),
             KeyAction::Conditional { .. } => Err(GeneratorError::SyntaxError(
                 "Conditional actions should use when blocks, not direct mappings".to_string(),
             )),
         }
     }
 
-    /// Check if a line is a mapping for the given key
-    fn is_mapping_for_key(line: &str, key: &str) -> bool {
+    /// Generate a standalone `macro("<key>", [...]);` line binding `steps`
+    /// to `key`, ready to paste into a device block
+    pub fn macro_snippet(key: &str, steps: &[MacroStep]) -> Result<String, GeneratorError> {
+        Self::validate_key_name(key)?;
+        let action = KeyAction::Macro {
+            sequence: steps.to_vec(),
+        };
+        let line = Self::generate_mapping_line(key, &action)?;
+        Ok(line.trim().to_string())
+    }
+
+    /// Parse a `macro("<key>", [...]);` line back into its key and steps
+    ///
+    /// Accepts the output of [`RhaiGenerator::macro_snippet`], with or
+    /// without the trailing semicolon.
+    pub fn parse_macro_snippet(line: &str) -> Result<(String, Vec<MacroStep>), GeneratorError> {
+        let invalid = || GeneratorError::SyntaxError(format!("Not a macro mapping: {}", line));
+
         let trimmed = line.trim();
-        if trimmed.starts_with("map(") || trimmed.starts_with("tap_hold(") {
-            // Extract first argument
-            if let Some(start) = trimmed.find('"') {
-                if let Some(end) = trimmed[start + 1..].find('"') {
-                    let first_arg = &trimmed[start + 1..start + 1 + end];
-                    return first_arg == key;
-                }
-            }
+        let args = trimmed
+            .strip_prefix("macro(")
+            .and_then(|rest| rest.trim_end_matches(';').trim_end().strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let (key, steps) = args.split_once(',').ok_or_else(invalid)?;
+        let key = Self::unquote(key).ok_or_else(invalid)?;
+        let steps = steps
+            .trim()
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .ok_or_else(invalid)?;
+
+        let mut sequence = Vec::new();
+        for step in steps.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let call = |name: &str| {
+                step.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix('('))
+                    .and_then(|rest| rest.strip_suffix(')'))
+            };
+            let parsed = if let Some(arg) = call("press") {
+                Self::unquote(arg).map(MacroStep::Press)
+            } else if let Some(arg) = call("release") {
+                Self::unquote(arg).map(MacroStep::Release)
+            } else if let Some(arg) = call("wait") {
+                arg.trim().parse().ok().map(MacroStep::Wait)
+            } else {
+                None
+            };
+            sequence.push(parsed.ok_or_else(|| {
+                GeneratorError::SyntaxError(format!("Invalid macro step: {}", step))
+            })?);
         }
-        false
+
+        Ok((key, sequence))
+    }
+
+    /// Strip the double quotes around a string argument
+    fn unquote(arg: &str) -> Option<String> {
+        arg.trim()
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .map(str::to_string)
+    }
+
+    /// Check if a line is a mapping for the given key
+    ///
+    /// Input keys may be written with or without the `VK_` prefix, so
+    /// `map("Space", ...)` is a mapping for `VK_Space` and vice versa.
+    fn is_mapping_for_key(line: &str, key: &str) -> bool {
+        Self::mapping_input(line)
+            .is_some_and(|(_, first_arg)| strip_vk_prefix(first_arg) == strip_vk_prefix(key))
     }
 
     /// Validate key name format
@@ -503,6 +774,23 @@ enum Section {
     Footer,
 }
 
+/// Strips the optional `VK_` prefix of an input key
+fn strip_vk_prefix(key: &str) -> &str {
+    key.strip_prefix("VK_").unwrap_or(key)
+}
+
+/// Returns `lines` without leading and trailing blank lines
+fn trim_blank_lines(lines: &[String]) -> &[String] {
+    let is_content = |line: &String| !line.trim().is_empty();
+    match lines.iter().position(is_content) {
+        Some(first) => {
+            let last = lines.iter().rposition(is_content).unwrap_or(first);
+            &lines[first..=last]
+        }
+        None => &[],
+    }
+}
+
 /// Count non-comment, non-empty lines in a layer
 fn count_mappings(lines: &[String]) -> usize {
     lines
@@ -545,6 +833,31 @@ device_end();
         assert!(gen.layers.contains_key("MD_00"));
     }
 
+    #[test]
+    fn test_macro_snippet_round_trip() {
+        let steps = vec![
+            MacroStep::Press("VK_H".to_string()),
+            MacroStep::Release("VK_H".to_string()),
+            MacroStep::Wait(120),
+            MacroStep::Press("VK_Comma".to_string()),
+            MacroStep::Release("VK_Comma".to_string()),
+        ];
+
+        let snippet = RhaiGenerator::macro_snippet("VK_F13", &steps).unwrap();
+        assert_eq!(
+            snippet,
+            r#"macro("VK_F13", [press("VK_H"), release("VK_H"), wait(120), press("VK_Comma"), release("VK_Comma")]);"#
+        );
+        assert_eq!(
+            RhaiGenerator::parse_macro_snippet(&snippet).unwrap(),
+            ("VK_F13".to_string(), steps)
+        );
+
+        assert!(RhaiGenerator::macro_snippet("VK_F13", &[MacroStep::Press("H".into())]).is_err());
+        assert!(RhaiGenerator::parse_macro_snippet(r#"map("VK_A", "VK_B");"#).is_err());
+        assert!(RhaiGenerator::parse_macro_snippet(r#"macro("VK_A", [tap("VK_B")]);"#).is_err());
+    }
+
     #[test]
     fn test_set_key_mapping() {
         let source = r#"
@@ -625,6 +938,65 @@ device_end();
         assert!(output.contains(r#"tap_hold("VK_Space", "VK_Space", "MD_00", 200)"#));
     }
 
+    #[test]
+    fn test_tap_hold_replaces_existing_mapping_in_place() {
+        let source = r#"
+device_start("*");
+// Thumb keys
+    tap_hold("Space", "VK_Space", "MD_00", 200);
+map("VK_A", "VK_B");
+device_end();
+"#;
+
+        let mut gen = RhaiGenerator::parse(source).unwrap();
+        gen.set_key_mapping(
+            "base",
+            "VK_Space",
+            KeyAction::TapHold {
+                tap: "VK_Enter".to_string(),
+                hold: "MD_01".to_string(),
+                threshold_ms: 250,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            gen.base_mappings,
+            vec![
+                "// Thumb keys".to_string(),
+                r#"    tap_hold("VK_Space", "VK_Enter", "MD_01", 250);"#.to_string(),
+                r#"map("VK_A", "VK_B");"#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_device_selects_named_block() {
+        let source = r#"device_start("Left");
+map("VK_A", "VK_B");
+device_end();
+
+device_start("Right");
+map("VK_C", "VK_D");
+device_end();
+"#;
+
+        let gen = RhaiGenerator::parse_device(source, Some("Right")).unwrap();
+        assert_eq!(gen.device_id, "Right");
+        assert_eq!(
+            gen.base_mappings,
+            vec![r#"map("VK_C", "VK_D");"#.to_string()]
+        );
+        let output = gen.to_string();
+        assert!(output.contains(r#"device_start("Left");"#));
+        assert!(output.contains(r#"map("VK_A", "VK_B");"#));
+
+        assert!(matches!(
+            RhaiGenerator::parse_device(source, Some("Missing")),
+            Err(GeneratorError::DeviceBlockNotFound(_))
+        ));
+    }
+
     #[test]
     fn test_display_implementation() {
         let source = r#"
@@ -639,6 +1011,119 @@ device_end();
         assert!(output.contains("device_end"));
     }
 
+    #[test]
+    fn test_generate_source_is_canonical() {
+        let source = r#"// Header comment
+device_start("*");
+map("VK_S", "VK_T");
+
+    // Thumb key
+  tap_hold("Space", "VK_Space", "MD_00", 200);
+map("VK_A", "VK_B");
+let out = "VK_Z";
+disable("VK_Insert");
+map("VK_C", out);
+
+when_start("MD_00");
+map("VK_L", "VK_Right");
+
+  map("VK_H", "VK_Left");
+when_end();
+device_end();
+
+
+// Footer comment
+"#;
+
+        let gen = RhaiGenerator::parse(source).unwrap();
+        assert_eq!(
+            gen.to_string(),
+            r#"// Header comment
+device_start("*");
+
+  map("VK_A", "VK_B");
+  map("VK_S", "VK_T");
+  // Thumb key
+  tap_hold("Space", "VK_Space", "MD_00", 200);
+  let out = "VK_Z";
+  map("VK_C", out);
+  disable("VK_Insert");
+
+when_start("MD_00");
+  map("VK_H", "VK_Left");
+  map("VK_L", "VK_Right");
+when_end();
+
+device_end();
+
+// Footer comment
+"#
+        );
+    }
+
+    #[test]
+    fn test_generate_source_is_idempotent() {
+        let source = r#"
+device_start("*");
+    map("VK_B", "VK_C");
+map("VK_A", with_shift("VK_B"));
+on_release("VK_A", "VK_Escape", 200);
+cycle("VK_F1",
+      ["VK_X", "VK_Y"], 500);
+device_end();
+// Footer comment
+"#;
+
+        let first = RhaiGenerator::parse(source).unwrap().to_string();
+        let gen = RhaiGenerator::parse(&first).unwrap();
+        assert_eq!(gen.to_string(), first);
+        assert_eq!(gen.to_string(), gen.to_string());
+        // Same key: map() sorts before on_release()
+        assert!(first.contains(
+            "  map(\"VK_A\", with_shift(\"VK_B\"));\n  on_release(\"VK_A\", \"VK_Escape\", 200);"
+        ));
+        // A mapping spread over several lines is not moved
+        assert!(first.contains("  cycle(\"VK_F1\",\n  [\"VK_X\", \"VK_Y\"], 500);"));
+    }
+
+    #[test]
+    fn test_no_op_edit_round_trips_unchanged() {
+        let source = RhaiGenerator::parse(
+            r#"device_start("*");
+map("VK_A", "VK_B");
+tap_hold("VK_Space", "VK_Space", "MD_00", 200);
+when_start("MD_00");
+map("VK_H", "VK_Left");
+when_end();
+device_end();
+"#,
+        )
+        .unwrap()
+        .to_string();
+
+        let mut gen = RhaiGenerator::parse(&source).unwrap();
+        gen.set_key_mapping(
+            "base",
+            "VK_Space",
+            KeyAction::TapHold {
+                tap: "VK_Space".to_string(),
+                hold: "MD_00".to_string(),
+                threshold_ms: 200,
+            },
+        )
+        .unwrap();
+        gen.set_key_mapping(
+            "MD_00",
+            "VK_H",
+            KeyAction::SimpleRemap {
+                output: "VK_Left".to_string(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(gen.to_string(), source);
+    }
+
     #[test]
     fn test_validate_key_name() {
         assert!(RhaiGenerator::validate_key_name("VK_A").is_ok());
@@ -652,4 +1137,182 @@ device_end();
         assert!(RhaiGenerator::validate_layer_id("VK_A").is_err());
         assert!(RhaiGenerator::validate_layer_id("Invalid").is_err());
     }
+
+    /// Compiles generated source with the real DSL parser
+    fn compile(gen: &RhaiGenerator) -> keyrx_core::config::ConfigRoot {
+        keyrx_compiler::parser::Parser::new()
+            .parse_string(&gen.to_string(), Path::new("generated.rhai"))
+            .unwrap_or_else(|e| panic!("Generated source failed to parse: {:?}\n{}", e, gen))
+    }
+
+    #[test]
+    fn test_new_mapping_variants_round_trip_through_parser() {
+        use keyrx_core::config::{BaseKeyMapping, KeyCode, KeyMapping};
+
+        let source = r#"
+device_start("*");
+when_start("MD_00");
+when_end();
+device_end();
+"#;
+
+        let mut gen = RhaiGenerator::parse(source).unwrap();
+        let actions = [
+            (
+                "VK_A",
+                KeyAction::ModifiedOutput {
+                    output: "VK_Num1".to_string(),
+                    shift: true,
+                    ctrl: false,
+                    alt: false,
+                    win: false,
+                },
+            ),
+            (
+                "VK_B",
+                KeyAction::ModifiedOutput {
+                    output: "VK_C".to_string(),
+                    shift: false,
+                    ctrl: true,
+                    alt: true,
+                    win: false,
+                },
+            ),
+            (
+                "VK_CapsLock",
+                KeyAction::OnRelease {
+                    output: "VK_Escape".to_string(),
+                    threshold_ms: 200,
+                },
+            ),
+            (
+                "VK_F13",
+                KeyAction::LayerToggle {
+                    layer: "MD_01".to_string(),
+                },
+            ),
+        ];
+        for (key, action) in actions {
+            gen.set_key_mapping("base", key, action).unwrap();
+        }
+        gen.set_key_mapping(
+            "MD_00",
+            "VK_F1",
+            KeyAction::Cycle {
+                outputs: vec!["VK_X".to_string(), "VK_Y".to_string()],
+                reset_ms: 500,
+            },
+        )
+        .unwrap();
+
+        let output = gen.to_string();
+        assert!(output.contains(r#"map("VK_A", with_shift("VK_Num1"));"#));
+        assert!(output.contains(r#"map("VK_B", with_mods("VK_C", false, true, true, false));"#));
+
+        let config = compile(&gen);
+        let mappings = &config.devices[0].mappings;
+        assert_eq!(
+            mappings[..4],
+            [
+                KeyMapping::Base(BaseKeyMapping::ModifiedOutput {
+                    from: KeyCode::A,
+                    to: KeyCode::Num1,
+                    shift: true,
+                    ctrl: false,
+                    alt: false,
+                    win: false,
+                }),
+                KeyMapping::Base(BaseKeyMapping::ModifiedOutput {
+                    from: KeyCode::B,
+                    to: KeyCode::C,
+                    shift: false,
+                    ctrl: true,
+                    alt: true,
+                    win: false,
+                }),
+                KeyMapping::Base(BaseKeyMapping::OnRelease {
+                    from: KeyCode::CapsLock,
+                    to: KeyCode::Escape,
+                    threshold_ms: 200,
+                }),
+                KeyMapping::Base(BaseKeyMapping::LayerToggle {
+                    from: KeyCode::F13,
+                    layer_id: 1,
+                }),
+            ]
+        );
+        match &mappings[4] {
+            KeyMapping::Conditional { mappings, .. } => assert_eq!(
+                mappings,
+                &vec![BaseKeyMapping::Cycle {
+                    from: KeyCode::F1,
+                    outputs: vec![KeyCode::X, KeyCode::Y],
+                    reset_ms: 500,
+                }]
+            ),
+            other => panic!("Expected conditional mapping, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_new_mapping_variants_replace_and_delete() {
+        let source = r#"
+device_start("*");
+layer_toggle("VK_F13", "MD_01");
+cycle("VK_F1", ["VK_X"], 500);
+device_end();
+"#;
+
+        let mut gen = RhaiGenerator::parse(source).unwrap();
+        gen.set_key_mapping(
+            "base",
+            "VK_F13",
+            KeyAction::LayerToggle {
+                layer: "MD_02".to_string(),
+            },
+        )
+        .unwrap();
+        gen.delete_key_mapping("base", "VK_F1").unwrap();
+        gen.set_key_mapping("base", "VK_Insert", KeyAction::Disable)
+            .unwrap();
+
+        assert_eq!(
+            gen.base_mappings,
+            vec![
+                r#"layer_toggle("VK_F13", "MD_02");"#.to_string(),
+                r#"  disable("VK_Insert");"#.to_string(),
+            ]
+        );
+        assert_eq!(
+            compile(&gen).devices[0].mappings[1],
+            keyrx_core::config::KeyMapping::disable(keyrx_core::config::KeyCode::Insert)
+        );
+
+        // Disabling an already-mapped key replaces its mapping
+        gen.set_key_mapping("base", "VK_F13", KeyAction::Disable)
+            .unwrap();
+        assert_eq!(gen.base_mappings[0], r#"disable("VK_F13");"#);
+    }
+
+    #[test]
+    fn test_invalid_new_mapping_variants_are_rejected() {
+        let mut gen = RhaiGenerator::parse("device_start(\"*\");\ndevice_end();").unwrap();
+
+        let empty_cycle = KeyAction::Cycle {
+            outputs: Vec::new(),
+            reset_ms: 500,
+        };
+        assert!(matches!(
+            gen.set_key_mapping("base", "VK_F1", empty_cycle),
+            Err(GeneratorError::SyntaxError(_))
+        ));
+
+        let bad_layer = KeyAction::LayerToggle {
+            layer: "VK_A".to_string(),
+        };
+        assert!(matches!(
+            gen.set_key_mapping("base", "VK_F13", bad_layer),
+            Err(GeneratorError::InvalidLayerId(_))
+        ));
+    }
 }
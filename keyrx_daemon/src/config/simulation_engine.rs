@@ -3,9 +3,17 @@
 //! Provides deterministic replay of keyboard events for testing configurations
 //! without physical hardware. Uses VirtualClock for timing to ensure reproducibility.
 
+use keyrx_compiler::parser::Parser;
+use keyrx_core::config::DeviceConfig;
+use keyrx_core::runtime::KeyLookup;
+use keyrx_core::simulator::{self, CoverageReport, ModeledClock, SimKeyEvent};
+use rkyv::Deserialize as _;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub use keyrx_core::simulator::SimulationResult;
 
 /// Maximum number of events allowed in a sequence (prevents DoS)
 const MAX_EVENT_COUNT: usize = 100_000;
@@ -52,18 +60,22 @@ impl VirtualClock {
 pub enum EventType {
     Press,
     Release,
+    /// Auto-repeat of a held key
+    Repeat,
 }
 
 /// A simulated keyboard event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimulatedEvent {
-    /// Optional device identifier for multi-device scenarios
+    /// Optional device identifier for multi-device scenarios, matched by
+    /// `when_device_start()` patterns. Omitted when serializing if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub device_id: Option<String>,
     /// Timestamp in microseconds from start
     pub timestamp_us: u64,
     /// Key identifier (e.g., "A", "CapsLock", "Shift")
     pub key: String,
-    /// Event type (press or release)
+    /// Event type (press, release, or repeat)
     pub event_type: EventType,
 }
 
@@ -72,7 +84,7 @@ pub struct SimulatedEvent {
 pub struct OutputEvent {
     /// Output key identifier
     pub key: String,
-    /// Event type (press or release)
+    /// Event type (press, release, or repeat)
     pub event_type: EventType,
     /// Timestamp when event was generated (microseconds)
     pub timestamp_us: u64,
@@ -121,6 +133,17 @@ impl BuiltinScenario {
         }
     }
 
+    /// Get a one-line description of what the scenario exercises
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::TapHoldUnderThreshold => "CapsLock tapped for 50ms, under the tap-hold threshold",
+            Self::TapHoldOverThreshold => "CapsLock held for 250ms, over the tap-hold threshold",
+            Self::PermissiveHold => "A tapped while CapsLock is held (permissive hold)",
+            Self::CrossDeviceModifiers => "Shift held on one device while A is typed on another",
+            Self::MacroSequence => "F13 tapped to trigger a macro",
+        }
+    }
+
     /// Generate event sequence for this scenario
     pub fn generate_events(&self) -> EventSequence {
         match self {
@@ -250,6 +273,122 @@ pub struct ScenarioResult {
     pub output: Vec<OutputEvent>,
     /// Optional error message if failed
     pub error: Option<String>,
+    /// Delays at which the output of a timing sweep changes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boundaries: Vec<TimingBoundary>,
+}
+
+/// User-authored scenario file for `keyrx test --scenario-file`
+///
+/// Names a Rhai configuration and lists scenarios to run against it. Each
+/// scenario is reported as one [`ScenarioResult`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioFile {
+    /// Rhai configuration to test, relative to the scenario file
+    pub config: PathBuf,
+    /// Scenarios run against the configuration
+    pub scenarios: Vec<CustomScenario>,
+    /// Timing sweeps run against the configuration
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timing: Vec<TimingScenario>,
+}
+
+/// A single scenario in a [`ScenarioFile`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomScenario {
+    /// Scenario name used in reports
+    pub name: String,
+    /// Input events
+    pub events: Vec<SimulatedEvent>,
+    /// Output events the configuration must produce, in order
+    pub expected: Vec<ExpectedOutput>,
+}
+
+/// Timing sweep in a [`ScenarioFile`]
+///
+/// Sends `first`, then `second` after each case's delay, then `then`, and
+/// checks the output of each case, e.g. "Space released within 150 ms taps,
+/// at 250 ms it doesn't". Between neighbouring cases whose output differs,
+/// the delay at which it switches is measured to the millisecond and
+/// reported as a [`TimingBoundary`], so thresholds can be calibrated. The
+/// search assumes the output switches once between two cases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingScenario {
+    /// Scenario name used in reports
+    pub name: String,
+    /// Event sent at time 0
+    pub first: TimingEvent,
+    /// Event sent after each case's delay
+    pub second: TimingEvent,
+    /// Events sent after `second`, with timestamps relative to it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then: Vec<SimulatedEvent>,
+    /// Delays to try and the output each must produce
+    pub cases: Vec<TimingCase>,
+}
+
+/// Key event of a [`TimingScenario`], timed by the sweep
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingEvent {
+    /// Optional device identifier, as in [`SimulatedEvent`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Key identifier
+    pub key: String,
+    /// Event type
+    pub event_type: EventType,
+}
+
+impl TimingEvent {
+    fn at(&self, timestamp_us: u64) -> SimulatedEvent {
+        SimulatedEvent {
+            device_id: self.device_id.clone(),
+            timestamp_us,
+            key: self.key.clone(),
+            event_type: self.event_type,
+        }
+    }
+}
+
+/// One delay of a [`TimingScenario`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingCase {
+    /// Delay between `first` and `second` in milliseconds
+    pub delay_ms: u64,
+    /// Output events the configuration must produce, in order
+    pub expected: Vec<ExpectedOutput>,
+}
+
+/// Measured switch point of a [`TimingScenario`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingBoundary {
+    /// Longest delay, in milliseconds, that still gives the earlier output
+    pub last_before_ms: u64,
+    /// Shortest delay, in milliseconds, that gives the later output
+    pub first_after_ms: u64,
+}
+
+/// Expected output event of a [`CustomScenario`]
+///
+/// The timestamp is only compared when given.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedOutput {
+    /// Key identifier
+    pub key: String,
+    /// Event type
+    pub event_type: EventType,
+    /// Timestamp in microseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_us: Option<u64>,
+}
+
+impl ExpectedOutput {
+    /// Returns true if `output` satisfies this expectation
+    pub fn matches(&self, output: &OutputEvent) -> bool {
+        self.key == output.key
+            && self.event_type == output.event_type
+            && self.timestamp_us.is_none_or(|t| t == output.timestamp_us)
+    }
 }
 
 /// Error types for simulation engine
@@ -270,9 +409,15 @@ pub enum SimulationError {
     #[error("Invalid event file: {0}")]
     InvalidEventFile(String),
 
+    #[error("Failed to compile configuration: {0}")]
+    CompileError(String),
+
     #[error("Scenario not found: {0}")]
     ScenarioNotFound(String),
 
+    #[error("Event sequence does not violate the predicate; nothing to shrink")]
+    NotReproduced,
+
     #[error("Simulation memory limit exceeded (max 1GB)")]
     MemoryLimitExceeded,
 
@@ -324,7 +469,24 @@ impl SimulationEngine {
         }
 
         let contents = std::fs::read_to_string(path)?;
-        let sequence: EventSequence = serde_json::from_str(&contents)?;
+        Self::parse_events_json(&contents)
+    }
+
+    /// Load event sequence JSON from a reader such as stdin
+    pub fn load_events_from_reader(reader: impl Read) -> Result<EventSequence, SimulationError> {
+        let mut contents = String::new();
+        reader
+            .take(MAX_EVENT_FILE_SIZE as u64 + 1)
+            .read_to_string(&mut contents)?;
+        if contents.len() > MAX_EVENT_FILE_SIZE {
+            return Err(SimulationError::FileTooLarge(contents.len()));
+        }
+
+        Self::parse_events_json(&contents)
+    }
+
+    fn parse_events_json(contents: &str) -> Result<EventSequence, SimulationError> {
+        let sequence: EventSequence = serde_json::from_str(contents)?;
 
         // Validate event count
         if sequence.events.len() > MAX_EVENT_COUNT {
@@ -419,12 +581,161 @@ impl SimulationEngine {
                         }
                     }
                 }
+                EventType::Repeat => {
+                    // Repeats of a held normal key pass through; tap-hold keys
+                    // only resolve on release
+                    if event.key != "CapsLock" && device_state.pressed_keys.contains_key(&event.key)
+                    {
+                        output.push(OutputEvent {
+                            key: event.key.clone(),
+                            event_type: EventType::Repeat,
+                            timestamp_us: self.clock.now_us(),
+                        });
+                    }
+                }
             }
         }
 
         Ok(output)
     }
 
+    /// Replay an event sequence through the loaded configuration and return
+    /// a per-event timeline.
+    ///
+    /// Unlike [`replay`](Self::replay), this runs the events through
+    /// `keyrx_core`'s runtime using the first device configuration in the
+    /// KRX file. The result has the same shape as the WASM simulator's output
+    /// so the web UI can render timelines from either source.
+    pub fn replay_timeline(
+        &self,
+        sequence: &EventSequence,
+    ) -> Result<SimulationResult, SimulationError> {
+        if sequence.events.len() > MAX_EVENT_COUNT {
+            return Err(SimulationError::TooManyEvents(sequence.events.len()));
+        }
+
+        let device_config = self.first_device_config()?;
+        let core_sequence = simulator::EventSequence {
+            events: sequence.events.iter().map(to_sim_key_event).collect(),
+            seed: sequence.seed,
+        };
+        run_timeline(&device_config, &core_sequence)
+    }
+
+    /// Replay an event sequence like [`replay_timeline`](Self::replay_timeline)
+    /// and report which mappings of the first device handled an event.
+    pub fn replay_coverage(
+        &self,
+        sequence: &EventSequence,
+    ) -> Result<CoverageReport, SimulationError> {
+        let result = self.replay_timeline(sequence)?;
+        let device_config = self.first_device_config()?;
+        Ok(simulator::mapping_coverage(
+            &device_config,
+            &result.timeline,
+        ))
+    }
+
+    /// Deserializes the first device configuration in the KRX file.
+    fn first_device_config(&self) -> Result<DeviceConfig, SimulationError> {
+        let config = keyrx_compiler::serialize::deserialize(&self.krx_data)
+            .map_err(|e| SimulationError::LoadError(e.to_string()))?;
+        let archived_device = config
+            .devices
+            .first()
+            .ok_or_else(|| SimulationError::LoadError("Configuration has no devices".into()))?;
+        archived_device
+            .deserialize(&mut rkyv::Infallible)
+            .map_err(|_| SimulationError::LoadError("Failed to deserialize device".into()))
+    }
+
+    /// Shrink a failing event sequence to a minimal subsequence that still
+    /// fails.
+    ///
+    /// `predicate` receives the output of [`replay`](Self::replay) and returns
+    /// `true` when the output is wrong (e.g. contains an unexpected key). The
+    /// events are reduced with delta debugging: ever smaller chunks are
+    /// removed as long as the rest still fails. Events keep their original
+    /// timestamps and the sequence keeps its seed, so the result is
+    /// deterministic and can be replayed as-is.
+    ///
+    /// Returns [`SimulationError::NotReproduced`] if `sequence` itself passes.
+    pub fn shrink<F>(
+        &mut self,
+        sequence: &EventSequence,
+        mut predicate: F,
+    ) -> Result<EventSequence, SimulationError>
+    where
+        F: FnMut(&[OutputEvent]) -> bool,
+    {
+        let seed = sequence.seed;
+        if !self.fails(&sequence.events, seed, &mut predicate)? {
+            return Err(SimulationError::NotReproduced);
+        }
+
+        let mut events = sequence.events.clone();
+        let mut granularity = 2;
+        while events.len() >= 2 {
+            let chunk_len = events.len().div_ceil(granularity);
+            let chunks: Vec<(usize, usize)> = (0..events.len())
+                .step_by(chunk_len)
+                .map(|start| (start, (start + chunk_len).min(events.len())))
+                .collect();
+
+            // A single chunk that still fails is the biggest reduction
+            let mut reduced = None;
+            for &(start, end) in &chunks {
+                if self.fails(&events[start..end], seed, &mut predicate)? {
+                    reduced = Some((events[start..end].to_vec(), 2));
+                    break;
+                }
+            }
+
+            // Otherwise try dropping one chunk at a time
+            if reduced.is_none() {
+                for &(start, end) in &chunks {
+                    let complement: Vec<SimulatedEvent> = events[..start]
+                        .iter()
+                        .chain(&events[end..])
+                        .cloned()
+                        .collect();
+                    if self.fails(&complement, seed, &mut predicate)? {
+                        reduced = Some((complement, (granularity - 1).max(2)));
+                        break;
+                    }
+                }
+            }
+
+            match reduced {
+                Some((smaller, next_granularity)) => {
+                    events = smaller;
+                    granularity = next_granularity;
+                }
+                None if granularity >= events.len() => break,
+                None => granularity = (granularity * 2).min(events.len()),
+            }
+        }
+
+        Ok(EventSequence { events, seed })
+    }
+
+    /// Replays `events` and reports whether the output violates `predicate`.
+    fn fails<F>(
+        &mut self,
+        events: &[SimulatedEvent],
+        seed: u64,
+        predicate: &mut F,
+    ) -> Result<bool, SimulationError>
+    where
+        F: FnMut(&[OutputEvent]) -> bool,
+    {
+        let candidate = EventSequence {
+            events: events.to_vec(),
+            seed,
+        };
+        Ok(predicate(&self.replay(&candidate)?))
+    }
+
     /// Run a built-in test scenario
     pub fn run_scenario(
         &mut self,
@@ -444,6 +755,7 @@ impl SimulationEngine {
                     input,
                     output,
                     error: None,
+                    boundaries: Vec::new(),
                 })
             }
             Err(e) => Ok(ScenarioResult {
@@ -452,6 +764,7 @@ impl SimulationEngine {
                 input,
                 output: Vec::new(),
                 error: Some(e.to_string()),
+                boundaries: Vec::new(),
             }),
         }
     }
@@ -503,6 +816,14 @@ impl SimulationEngine {
                         event_type: EventType::Release,
                     });
                 }
+                "repeat" => {
+                    events.push(SimulatedEvent {
+                        device_id: None,
+                        timestamp_us: current_time_us,
+                        key: value.to_string(),
+                        event_type: EventType::Repeat,
+                    });
+                }
                 "wait" => {
                     let wait_ms: u64 = value.parse().map_err(|_| {
                         SimulationError::InvalidEventFile(format!(
@@ -514,7 +835,7 @@ impl SimulationEngine {
                 }
                 _ => {
                     return Err(SimulationError::InvalidEventFile(format!(
-                        "Unknown action: '{}' (expected press, release, or wait)",
+                        "Unknown action: '{}' (expected press, release, repeat, or wait)",
                         action
                     )));
                 }
@@ -525,6 +846,311 @@ impl SimulationEngine {
     }
 }
 
+/// Load a scenario file and run every scenario in it.
+///
+/// The configuration named by the file is compiled in-process and each
+/// scenario's events run through `keyrx_core`'s runtime using its first
+/// device configuration. A scenario passes when its output matches
+/// `expected` exactly; otherwise its result carries the first mismatch.
+/// Timing sweeps are reported after the scenarios, one result each.
+pub fn run_scenario_file(path: &Path) -> Result<Vec<ScenarioResult>, SimulationError> {
+    let metadata = std::fs::metadata(path)?;
+    if metadata.len() > MAX_EVENT_FILE_SIZE as u64 {
+        return Err(SimulationError::FileTooLarge(metadata.len() as usize));
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    let file: ScenarioFile = serde_json::from_str(&contents)?;
+
+    let config_path = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(&file.config);
+    let mut parser = Parser::new();
+    let config = parser
+        .parse_script(&config_path)
+        .map_err(|e| SimulationError::CompileError(e.to_string()))?;
+    let device_config = config
+        .devices
+        .first()
+        .ok_or_else(|| SimulationError::CompileError("Configuration has no devices".into()))?;
+
+    let scenarios = file
+        .scenarios
+        .into_iter()
+        .map(|scenario| run_custom_scenario(device_config, scenario));
+    let timing = file
+        .timing
+        .into_iter()
+        .map(|scenario| run_timing_scenario(device_config, scenario));
+    scenarios.chain(timing).collect()
+}
+
+/// Run one scenario from a scenario file against `device_config`.
+fn run_custom_scenario(
+    device_config: &DeviceConfig,
+    scenario: CustomScenario,
+) -> Result<ScenarioResult, SimulationError> {
+    if scenario.events.len() > MAX_EVENT_COUNT {
+        return Err(SimulationError::TooManyEvents(scenario.events.len()));
+    }
+
+    Ok(match run_events(device_config, &scenario.events) {
+        Ok(output) => {
+            let error = expectation_mismatch(&scenario.expected, &output);
+            ScenarioResult {
+                scenario: scenario.name,
+                passed: error.is_none(),
+                input: scenario.events,
+                output,
+                error,
+                boundaries: Vec::new(),
+            }
+        }
+        Err(e) => ScenarioResult {
+            scenario: scenario.name,
+            passed: false,
+            input: scenario.events,
+            output: Vec::new(),
+            error: Some(e.to_string()),
+            boundaries: Vec::new(),
+        },
+    })
+}
+
+/// Run one timing sweep from a scenario file against `device_config`.
+///
+/// The result's input and output are those of the first failing case, or of
+/// the longest delay if every case passes.
+fn run_timing_scenario(
+    device_config: &DeviceConfig,
+    scenario: TimingScenario,
+) -> Result<ScenarioResult, SimulationError> {
+    if scenario.then.len() + 2 > MAX_EVENT_COUNT {
+        return Err(SimulationError::TooManyEvents(scenario.then.len() + 2));
+    }
+    let mut cases = scenario.cases.clone();
+    cases.sort_by_key(|case| case.delay_ms);
+
+    let mut runs: Vec<(u64, Vec<SimulatedEvent>, Vec<OutputEvent>)> = Vec::new();
+    let mut failure = None;
+    for case in &cases {
+        let input = timing_events(&scenario, case.delay_ms);
+        let output = match run_events(device_config, &input) {
+            Ok(output) => output,
+            Err(e) => {
+                return Ok(ScenarioResult {
+                    scenario: scenario.name,
+                    passed: false,
+                    input,
+                    output: Vec::new(),
+                    error: Some(e.to_string()),
+                    boundaries: Vec::new(),
+                })
+            }
+        };
+        if failure.is_none() {
+            failure = expectation_mismatch(&case.expected, &output)
+                .map(|error| (runs.len(), format!("At {} ms: {}", case.delay_ms, error)));
+        }
+        runs.push((case.delay_ms, input, output));
+    }
+
+    let mut boundaries = Vec::new();
+    for pair in runs.windows(2) {
+        let ((before_ms, _, before), (after_ms, _, after)) = (&pair[0], &pair[1]);
+        if same_outputs(before, after) {
+            continue;
+        }
+        boundaries.push(measure_boundary(
+            device_config,
+            &scenario,
+            *before_ms,
+            *after_ms,
+            after,
+        )?);
+    }
+
+    let (index, error) = match failure {
+        Some((index, error)) => (Some(index), Some(error)),
+        None => (runs.len().checked_sub(1), None),
+    };
+    let (input, output) = index
+        .map(|i| (runs[i].1.clone(), runs[i].2.clone()))
+        .unwrap_or_default();
+    Ok(ScenarioResult {
+        scenario: scenario.name,
+        passed: error.is_none(),
+        input,
+        output,
+        error,
+        boundaries,
+    })
+}
+
+/// Input of a timing sweep for one delay.
+fn timing_events(scenario: &TimingScenario, delay_ms: u64) -> Vec<SimulatedEvent> {
+    let second_us = delay_ms.saturating_mul(1000);
+    let mut events = vec![scenario.first.at(0), scenario.second.at(second_us)];
+    events.extend(scenario.then.iter().map(|event| SimulatedEvent {
+        timestamp_us: second_us.saturating_add(event.timestamp_us),
+        ..event.clone()
+    }));
+    events
+}
+
+/// Binary-searches the delays between `before_ms` and `after_ms` for the
+/// first one that produces `after` (timestamps aside).
+fn measure_boundary(
+    device_config: &DeviceConfig,
+    scenario: &TimingScenario,
+    before_ms: u64,
+    after_ms: u64,
+    after: &[OutputEvent],
+) -> Result<TimingBoundary, SimulationError> {
+    let (mut low, mut high) = (before_ms, after_ms);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let output = run_events(device_config, &timing_events(scenario, mid))?;
+        if same_outputs(&output, after) {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Ok(TimingBoundary {
+        last_before_ms: low,
+        first_after_ms: high,
+    })
+}
+
+/// Whether two outputs have the same keys and event types, in order.
+fn same_outputs(a: &[OutputEvent], b: &[OutputEvent]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.key == b.key && a.event_type == b.event_type)
+}
+
+/// Run `events` through `keyrx_core`'s runtime and collect the output.
+fn run_events(
+    device_config: &DeviceConfig,
+    events: &[SimulatedEvent],
+) -> Result<Vec<OutputEvent>, SimulationError> {
+    let sequence = simulator::EventSequence {
+        events: events.iter().map(to_sim_key_event).collect(),
+        seed: 0,
+    };
+    run_timeline(device_config, &sequence).and_then(|result| {
+        result
+            .timeline
+            .iter()
+            .flat_map(|entry| entry.outputs.iter())
+            .map(from_sim_key_event)
+            .collect()
+    })
+}
+
+/// Describe the first difference between `expected` and `output`, if any.
+fn expectation_mismatch(expected: &[ExpectedOutput], output: &[OutputEvent]) -> Option<String> {
+    let index = (0..expected.len().max(output.len())).find(|&i| {
+        match (expected.get(i), output.get(i)) {
+            (Some(e), Some(o)) => !e.matches(o),
+            _ => true,
+        }
+    })?;
+
+    let expected_desc =
+        expected
+            .get(index)
+            .map_or("(none)".to_string(), |e| match e.timestamp_us {
+                Some(t) => format!("{:?} {} at {} us", e.event_type, e.key, t),
+                None => format!("{:?} {}", e.event_type, e.key),
+            });
+    let actual_desc = output.get(index).map_or("(none)".to_string(), |o| {
+        format!("{:?} {} at {} us", o.event_type, o.key, o.timestamp_us)
+    });
+    Some(format!(
+        "Output {} expected {}, got {}",
+        index, expected_desc, actual_desc
+    ))
+}
+
+/// Converts a shared simulator output event into the daemon format.
+fn from_sim_key_event(event: &SimKeyEvent) -> Result<OutputEvent, SimulationError> {
+    let event_type = match event.event_type.as_str() {
+        "press" => EventType::Press,
+        "release" => EventType::Release,
+        "repeat" => EventType::Repeat,
+        other => {
+            return Err(SimulationError::InvalidEventFile(format!(
+                "Invalid event type: {}",
+                other
+            )))
+        }
+    };
+    Ok(OutputEvent {
+        key: event.keycode.clone(),
+        event_type,
+        timestamp_us: event.timestamp_us,
+    })
+}
+
+/// Converts a daemon simulation event into the shared simulator format.
+fn to_sim_key_event(event: &SimulatedEvent) -> SimKeyEvent {
+    SimKeyEvent {
+        keycode: event.key.clone(),
+        event_type: match event.event_type {
+            EventType::Press => "press".to_string(),
+            EventType::Release => "release".to_string(),
+            EventType::Repeat => "repeat".to_string(),
+        },
+        timestamp_us: event.timestamp_us,
+        device_id: event.device_id.clone(),
+    }
+}
+
+/// Compiles Rhai `source` in-process and runs `sequence` through the core
+/// runtime using the first device configuration.
+///
+/// This lets the web editor simulate unsaved source without writing a profile
+/// or KRX file first. The result has the same shape as the WASM simulator's
+/// output. `load()` is disabled, since the source comes from API callers
+/// that must not make the daemon read arbitrary files.
+pub fn simulate_source(
+    source: &str,
+    sequence: &simulator::EventSequence,
+) -> Result<SimulationResult, SimulationError> {
+    if sequence.events.len() > MAX_EVENT_COUNT {
+        return Err(SimulationError::TooManyEvents(sequence.events.len()));
+    }
+
+    let mut parser = Parser::new().without_load();
+    let config = parser
+        .parse_string(source, Path::new("<editor>"))
+        .map_err(|e| SimulationError::CompileError(e.to_string()))?;
+    let device_config = config
+        .devices
+        .first()
+        .ok_or_else(|| SimulationError::CompileError("Configuration has no devices".into()))?;
+
+    run_timeline(device_config, sequence)
+}
+
+/// Runs `sequence` through `keyrx_core`'s runtime against `device_config`.
+///
+/// Latency comes from a [`ModeledClock`] seeded with the sequence's seed, so
+/// the result is the same on every run.
+fn run_timeline(
+    device_config: &DeviceConfig,
+    sequence: &simulator::EventSequence,
+) -> Result<SimulationResult, SimulationError> {
+    let lookup = KeyLookup::from_device_config(device_config);
+    let clock = ModeledClock::new(sequence.seed);
+    simulator::run_simulation(&lookup, device_config, sequence, &clock)
+        .map_err(SimulationError::InvalidEventFile)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -574,6 +1200,65 @@ mod tests {
         assert_eq!(sequence.events[1].timestamp_us, 50_000);
     }
 
+    #[test]
+    fn test_parse_event_dsl_repeat() {
+        let dsl = "press:A,wait:500,repeat:A,wait:30,repeat:A,release:A";
+        let sequence = SimulationEngine::parse_event_dsl(dsl, 0).unwrap();
+
+        assert_eq!(sequence.events.len(), 4);
+        assert_eq!(sequence.events[1].event_type, EventType::Repeat);
+        assert_eq!(sequence.events[1].timestamp_us, 500_000);
+        assert_eq!(sequence.events[2].event_type, EventType::Repeat);
+        assert_eq!(sequence.events[2].timestamp_us, 530_000);
+    }
+
+    #[test]
+    fn test_shrink_finds_minimal_subsequence() {
+        let krx_file = create_test_krx();
+        let mut engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        // Only the CapsLock hold produces Control
+        let sequence = SimulationEngine::parse_event_dsl(
+            "press:A,release:A,press:B,press:CapsLock,wait:300,release:CapsLock,release:B,press:C,release:C",
+            7,
+        )
+        .unwrap();
+        let emits_control = |output: &[OutputEvent]| output.iter().any(|e| e.key == "Control");
+
+        let shrunk = engine.shrink(&sequence, emits_control).unwrap();
+        let keys: Vec<(&str, EventType)> = shrunk
+            .events
+            .iter()
+            .map(|e| (e.key.as_str(), e.event_type))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("CapsLock", EventType::Press),
+                ("CapsLock", EventType::Release)
+            ]
+        );
+        assert_eq!(shrunk.seed, 7);
+        assert_eq!(shrunk.events[1].timestamp_us, 300_000);
+
+        // Deterministic
+        let again = engine.shrink(&sequence, emits_control).unwrap();
+        assert_eq!(
+            serde_json::to_string(&again).unwrap(),
+            serde_json::to_string(&shrunk).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_shrink_rejects_passing_sequence() {
+        let krx_file = create_test_krx();
+        let mut engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence = SimulationEngine::parse_event_dsl("press:A,release:A", 0).unwrap();
+        let result = engine.shrink(&sequence, |output| output.iter().any(|e| e.key == "Z"));
+        assert!(matches!(result, Err(SimulationError::NotReproduced)));
+    }
+
     #[test]
     fn test_parse_event_dsl_invalid() {
         let result = SimulationEngine::parse_event_dsl("invalid", 0);
@@ -665,6 +1350,230 @@ mod tests {
         assert!(matches!(result, Err(SimulationError::TooManyEvents(_))));
     }
 
+    fn create_compiled_krx() -> NamedTempFile {
+        use keyrx_core::config::{
+            ConfigRoot, DeviceIdentifier, KeyCode, KeyMapping, Metadata, Version,
+        };
+
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: vec![DeviceConfig {
+                identifier: DeviceIdentifier {
+                    pattern: "*".to_string(),
+                    excludes: Vec::new(),
+                },
+                mappings: vec![
+                    KeyMapping::simple(KeyCode::A, KeyCode::B),
+                    KeyMapping::modifier(KeyCode::CapsLock, 0x01),
+                ],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
+            }],
+            metadata: Metadata {
+                compilation_timestamp: 0,
+                compiler_version: "test".to_string(),
+                source_hash: "test".to_string(),
+            },
+        };
+        let bytes = keyrx_compiler::serialize::serialize(&config).unwrap();
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&bytes).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_replay_timeline_uses_compiled_config() {
+        let krx_file = create_compiled_krx();
+        let engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence =
+            SimulationEngine::parse_event_dsl("press:CapsLock,press:A,wait:10,release:A", 0)
+                .unwrap();
+        let result = engine.replay_timeline(&sequence).unwrap();
+
+        assert_eq!(result.timeline.len(), 3);
+        assert!(result.timeline[0].outputs.is_empty());
+        assert_eq!(result.timeline[0].state.active_modifiers, vec![0x01]);
+        assert_eq!(result.timeline[1].outputs[0].keycode, "B");
+        assert_eq!(result.timeline[2].outputs[0].event_type, "release");
+        assert_eq!(result.timeline[2].timestamp_us, 10_000);
+        assert_eq!(result.final_state.active_modifiers, vec![0x01]);
+    }
+
+    #[test]
+    fn test_replay_timeline_json_matches_wasm_shape() {
+        let krx_file = create_compiled_krx();
+        let engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence = SimulationEngine::parse_event_dsl("press:A", 0).unwrap();
+        let result = engine.replay_timeline(&sequence).unwrap();
+        let json = serde_json::to_value(&result).unwrap();
+
+        let entry = &json["timeline"][0];
+        assert_eq!(entry["input"]["keycode"], "A");
+        assert_eq!(entry["input"]["event_type"], "press");
+        assert!(entry["state"]["active_modifiers"].is_array());
+        assert!(entry["latency_us"].is_u64());
+        assert!(json["latency_stats"]["p99_us"].is_u64());
+        assert!(json["final_state"]["active_layer"].is_null());
+    }
+
+    #[test]
+    fn test_replay_timeline_is_deterministic_for_seed() {
+        let krx_file = create_compiled_krx();
+        let engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence =
+            SimulationEngine::parse_event_dsl("press:CapsLock,press:A,wait:10,release:A", 7)
+                .unwrap();
+        let first = engine.replay_timeline(&sequence).unwrap();
+        let second = engine.replay_timeline(&sequence).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.seed, 7);
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_replay_coverage_lists_unexercised_mappings() {
+        let krx_file = create_compiled_krx();
+        let engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence = SimulationEngine::parse_event_dsl("press:A,release:A", 0).unwrap();
+        let report = engine.replay_coverage(&sequence).unwrap();
+
+        let hits: Vec<usize> = report.mappings.iter().map(|m| m.hits).collect();
+        assert_eq!(hits, vec![1, 0]);
+        let unexercised: Vec<usize> = report
+            .unexercised_mappings()
+            .map(|m| m.position.index)
+            .collect();
+        assert_eq!(unexercised, vec![1]);
+    }
+
+    #[test]
+    fn test_replay_timeline_invalid_krx() {
+        let krx_file = create_test_krx();
+        let engine = SimulationEngine::new(krx_file.path()).unwrap();
+
+        let sequence = SimulationEngine::parse_event_dsl("press:A", 0).unwrap();
+        let result = engine.replay_timeline(&sequence);
+
+        assert!(matches!(result, Err(SimulationError::LoadError(_))));
+    }
+
+    fn sim_event(keycode: &str, event_type: &str, timestamp_us: u64) -> SimKeyEvent {
+        SimKeyEvent {
+            keycode: keycode.to_string(),
+            event_type: event_type.to_string(),
+            timestamp_us,
+            device_id: None,
+        }
+    }
+
+    #[test]
+    fn test_simulate_source_compiles_and_runs() {
+        let source = r#"
+device_start("*");
+  map("VK_A", "VK_B");
+  map("VK_ScrollLock", "LK_01");
+device_end();
+"#;
+        let sequence = simulator::EventSequence {
+            events: vec![
+                sim_event("ScrollLock", "press", 0),
+                sim_event("ScrollLock", "release", 1_000),
+                sim_event("A", "press", 2_000),
+            ],
+            seed: 0,
+        };
+
+        let result = simulate_source(source, &sequence).unwrap();
+
+        assert_eq!(result.timeline.len(), 3);
+        assert_eq!(result.timeline[2].outputs[0].keycode, "B");
+        assert_eq!(result.final_state.active_locks, vec![0x01]);
+    }
+
+    #[test]
+    fn test_simulate_source_replays_device_ids() {
+        let source = r#"
+device_start("*");
+  when_device_start("*numpad*");
+    map("VK_A", "VK_B");
+  when_device_end();
+device_end();
+"#;
+        // A multi-device recording: only the numpad event carries an id
+        let sequence: simulator::EventSequence = serde_json::from_str(
+            r#"{"events": [
+                {"keycode": "A", "event_type": "press", "timestamp_us": 0, "device_id": "usb-numpad-1"},
+                {"keycode": "A", "event_type": "press", "timestamp_us": 1000}
+            ]}"#,
+        )
+        .unwrap();
+
+        let result = simulate_source(source, &sequence).unwrap();
+
+        assert_eq!(result.timeline[0].outputs[0].keycode, "B");
+        assert_eq!(result.timeline[1].outputs[0].keycode, "A");
+
+        // Untagged events stay compact when written back out
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["timeline"][0]["input"]["device_id"], "usb-numpad-1");
+        assert!(json["timeline"][1]["input"].get("device_id").is_none());
+    }
+
+    #[test]
+    fn test_simulate_source_compile_error() {
+        let sequence = simulator::EventSequence {
+            events: Vec::new(),
+            seed: 0,
+        };
+        let result = simulate_source("map(", &sequence);
+
+        assert!(matches!(result, Err(SimulationError::CompileError(_))));
+    }
+
+    #[test]
+    fn test_simulate_source_rejects_load() {
+        let sequence = simulator::EventSequence {
+            events: Vec::new(),
+            seed: 0,
+        };
+        let source = "device_start(\"*\");\n  load(\"/etc/passwd\");\ndevice_end();\n";
+        let result = simulate_source(source, &sequence);
+
+        match result {
+            Err(SimulationError::CompileError(msg)) => {
+                assert!(msg.contains("load() is not available"), "{}", msg)
+            }
+            other => panic!("Expected compile error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_simulate_source_invalid_event() {
+        let source = r#"
+device_start("*");
+  map("VK_A", "VK_B");
+device_end();
+"#;
+        let sequence = simulator::EventSequence {
+            events: vec![sim_event("NotAKey", "press", 0)],
+            seed: 0,
+        };
+        let result = simulate_source(source, &sequence);
+
+        assert!(matches!(result, Err(SimulationError::InvalidEventFile(_))));
+    }
+
     #[test]
     fn test_all_scenarios() {
         let krx_file = create_test_krx();
@@ -675,4 +1584,154 @@ mod tests {
         assert_eq!(results.len(), BuiltinScenario::all().len());
         assert!(results.iter().all(|r| r.passed));
     }
+
+    /// Write a Rhai config and a scenario file referencing it by relative path.
+    fn write_scenario_file(dir: &tempfile::TempDir, scenarios: &str) -> std::path::PathBuf {
+        std::fs::write(
+            dir.path().join("layout.rhai"),
+            r#"
+device_start("*");
+map("VK_A", "VK_B");
+device_end();
+"#,
+        )
+        .unwrap();
+        let path = dir.path().join("scenarios.json");
+        std::fs::write(
+            &path,
+            format!(r#"{{"config": "layout.rhai", "scenarios": {}}}"#, scenarios),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_run_scenario_file_reports_each_scenario() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_scenario_file(
+            &dir,
+            r#"[
+                {
+                    "name": "a-becomes-b",
+                    "events": [
+                        {"device_id": null, "timestamp_us": 0, "key": "A", "event_type": "press"},
+                        {"device_id": null, "timestamp_us": 50000, "key": "A", "event_type": "release"}
+                    ],
+                    "expected": [
+                        {"key": "B", "event_type": "press", "timestamp_us": 0},
+                        {"key": "B", "event_type": "release"}
+                    ]
+                },
+                {
+                    "name": "a-stays-a",
+                    "events": [
+                        {"device_id": null, "timestamp_us": 0, "key": "A", "event_type": "press"}
+                    ],
+                    "expected": [
+                        {"key": "A", "event_type": "press"}
+                    ]
+                }
+            ]"#,
+        );
+
+        let results = run_scenario_file(&path).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].scenario, "a-becomes-b");
+        assert!(results[0].passed, "{:?}", results[0].error);
+        assert_eq!(results[0].output.len(), 2);
+        assert!(!results[1].passed);
+        assert_eq!(
+            results[1].error.as_deref(),
+            Some("Output 0 expected Press A, got Press B at 0 us")
+        );
+    }
+
+    #[test]
+    fn test_run_scenario_file_missing_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_scenario_file(
+            &dir,
+            r#"[{"name": "extra", "events": [], "expected": [{"key": "B", "event_type": "press"}]}]"#,
+        );
+
+        let results = run_scenario_file(&path).unwrap();
+
+        assert!(!results[0].passed);
+        assert_eq!(
+            results[0].error.as_deref(),
+            Some("Output 0 expected Press B, got (none)")
+        );
+    }
+
+    #[test]
+    fn test_run_scenario_file_missing_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("scenarios.json");
+        std::fs::write(&path, r#"{"config": "missing.rhai", "scenarios": []}"#).unwrap();
+
+        let result = run_scenario_file(&path);
+
+        assert!(matches!(result, Err(SimulationError::CompileError(_))));
+    }
+
+    #[test]
+    fn test_run_scenario_file_measures_timing_boundary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("layout.rhai"),
+            r#"
+device_start("*");
+tap_hold("Space", "VK_Space", "MD_00", 200);
+device_end();
+"#,
+        )
+        .unwrap();
+        let path = dir.path().join("scenarios.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "config": "layout.rhai",
+                "scenarios": [],
+                "timing": [{
+                    "name": "space-tap-or-hold",
+                    "first": {"key": "Space", "event_type": "press"},
+                    "second": {"key": "Space", "event_type": "release"},
+                    "cases": [
+                        {"delay_ms": 250, "expected": []},
+                        {"delay_ms": 150, "expected": [
+                            {"key": "Space", "event_type": "press"},
+                            {"key": "Space", "event_type": "release"}
+                        ]},
+                        {"delay_ms": 180, "expected": []}
+                    ]
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let results = run_scenario_file(&path).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].passed);
+        assert_eq!(
+            results[0].error.as_deref(),
+            Some("At 180 ms: Output 0 expected (none), got Press Space at 180000 us")
+        );
+        assert_eq!(results[0].input[1].timestamp_us, 180_000);
+        assert_eq!(
+            results[0].boundaries,
+            vec![TimingBoundary {
+                last_before_ms: 199,
+                first_after_ms: 200,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_builtin_scenario_descriptions() {
+        for scenario in BuiltinScenario::all() {
+            assert!(!scenario.description().is_empty(), "{}", scenario.name());
+        }
+    }
 }
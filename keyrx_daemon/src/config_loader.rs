@@ -2,6 +2,15 @@
 //!
 //! This module provides functionality to load and validate .krx binary configuration files.
 //!
+//! # Compile-on-Load
+//!
+//! A path ending in `.rhai` is compiled in memory with the same parser as
+//! `keyrx_compiler` instead of being read as a .krx file. This skips the
+//! separate compile step while iterating on a layout, at the cost of running
+//! the Rhai engine (and every `load()`ed file) on each load. Prefer a
+//! precompiled .krx outside development; it only needs a hash check and a
+//! zero-copy archive validation.
+//!
 //! # Memory Management Warning
 //!
 //! **IMPORTANT**: This module intentionally leaks memory to satisfy rkyv's `'static` lifetime
@@ -29,11 +38,22 @@
 //! configuration once at daemon startup.
 
 use std::path::Path;
+use std::sync::{Arc, RwLock};
 
+use keyrx_compiler::parser::Parser;
 use keyrx_core::config::ConfigRoot;
 
 use crate::error::ConfigError;
 
+/// Returns `true` if `path` names a Rhai source file (`.rhai` extension).
+///
+/// [`load_config`] compiles such files in memory instead of reading them as .krx.
+#[must_use]
+pub fn is_source_config(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("rhai"))
+}
+
 /// Loads and validates a .krx configuration file.
 ///
 /// This function:
@@ -41,9 +61,12 @@ use crate::error::ConfigError;
 /// 2. Validates the .krx file format (magic bytes, version, hash)
 /// 3. Deserializes the configuration using rkyv
 ///
+/// A `.rhai` path is compiled in memory first (see [`is_source_config`]), so
+/// callers get the same archived configuration either way.
+///
 /// # Arguments
 ///
-/// * `path` - Path to the .krx configuration file
+/// * `path` - Path to the .krx configuration file (or `.rhai` source)
 ///
 /// # Returns
 ///
@@ -61,8 +84,11 @@ use crate::error::ConfigError;
 /// Returns `ConfigError::ParseError` if:
 /// - The file has invalid magic bytes (not a .krx file)
 /// - The .krx format version is incompatible
+/// - The config was compiled for a config version this build cannot read
+///   (the reason says whether to upgrade keyrx or recompile)
 /// - The hash does not match (data corruption)
 /// - The rkyv archive structure is invalid
+/// - A `.rhai` source fails to compile
 ///
 /// # Examples
 ///
@@ -90,8 +116,12 @@ pub fn load_config<P: AsRef<Path>>(
         });
     }
 
-    // Read file bytes
-    let bytes = std::fs::read(path_ref).map_err(ConfigError::Io)?;
+    // Read file bytes, compiling Rhai sources in memory
+    let bytes = if is_source_config(path_ref) {
+        compile_source(path_ref)?
+    } else {
+        std::fs::read(path_ref).map_err(ConfigError::Io)?
+    };
 
     // INTENTIONAL MEMORY LEAK: Leak the bytes to get a 'static lifetime.
     //
@@ -115,6 +145,143 @@ pub fn load_config<P: AsRef<Path>>(
     Ok(config)
 }
 
+/// Compiles a Rhai source file to .krx bytes without writing them to disk.
+fn compile_source(path: &Path) -> Result<Vec<u8>, ConfigError> {
+    let config = Parser::new()
+        .parse_script(path)
+        .map_err(|e| ConfigError::ParseError {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+
+    keyrx_compiler::serialize::serialize(&config).map_err(|e| ConfigError::CompilationFailed {
+        reason: e.to_string(),
+    })
+}
+
+/// Shared handle to the configuration the daemon is currently remapping with.
+///
+/// The daemon only keeps the device configuration it remaps with, so the
+/// full `ConfigRoot` is recorded here on every successful (re)load and served
+/// over IPC. It can differ from the file on disk after an edit or a failed
+/// reload. Clones share the same value.
+///
+/// A config the daemon loaded is recorded as the archive its remapping state
+/// already borrows from, so it is not held twice; it is only deserialized
+/// when a client asks for it.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedConfig {
+    inner: Arc<RwLock<Option<Recorded>>>,
+}
+
+/// A configuration recorded in [`LoadedConfig`].
+#[derive(Clone)]
+enum Recorded {
+    /// The leaked archive returned by [`load_config`].
+    Archived(&'static rkyv::Archived<ConfigRoot>),
+    /// A configuration read without a daemon to remap with it.
+    Owned(Arc<ConfigRoot>),
+}
+
+impl std::fmt::Debug for Recorded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archived(archived) => f
+                .debug_struct("Archived")
+                .field("source_hash", &archived.metadata.source_hash.as_str())
+                .finish_non_exhaustive(),
+            Self::Owned(config) => f.debug_tuple("Owned").field(config).finish(),
+        }
+    }
+}
+
+impl LoadedConfig {
+    /// Creates a handle with no configuration loaded (pass-through mode).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `config` as the running configuration, or `None` for
+    /// pass-through mode.
+    pub fn set(&self, config: Option<ConfigRoot>) {
+        self.record(config.map(|config| Recorded::Owned(Arc::new(config))));
+    }
+
+    /// Records an archived configuration, as returned by [`load_config`],
+    /// without copying it.
+    pub fn set_archived(&self, archived: &'static rkyv::Archived<ConfigRoot>) {
+        self.record(Some(Recorded::Archived(archived)));
+    }
+
+    fn record(&self, recorded: Option<Recorded>) {
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = recorded;
+    }
+
+    fn recorded(&self) -> Option<Recorded> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Returns a copy of the running configuration, if any.
+    ///
+    /// An archived configuration is deserialized on every call.
+    pub fn get(&self) -> Option<ConfigRoot> {
+        use rkyv::Deserialize;
+        match self.recorded()? {
+            Recorded::Archived(archived) => Some(
+                archived
+                    .deserialize(&mut rkyv::Infallible)
+                    .expect("ConfigRoot deserialization is infallible"),
+            ),
+            Recorded::Owned(config) => Some(ConfigRoot::clone(&config)),
+        }
+    }
+
+    /// Returns the source hash of the running configuration, if any,
+    /// without copying the rest of it.
+    pub fn source_hash(&self) -> Option<String> {
+        match self.recorded()? {
+            Recorded::Archived(archived) => Some(archived.metadata.source_hash.to_string()),
+            Recorded::Owned(config) => Some(config.metadata.source_hash.clone()),
+        }
+    }
+
+    /// Reads a compiled .krx file and records it as the running configuration.
+    ///
+    /// Unlike [`load_config`], the file bytes are not leaked, so this can be
+    /// called on every reload.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConfigError::Io` if the file cannot be read and
+    /// `ConfigError::ParseError` if it is not a valid .krx file. The recorded
+    /// configuration is left unchanged on error.
+    pub fn load_krx(&self, path: &Path) -> Result<(), ConfigError> {
+        self.set(Some(read_krx(path)?));
+        Ok(())
+    }
+}
+
+/// Reads a compiled .krx file into an owned configuration.
+///
+/// Unlike [`load_config`], the file bytes are not leaked.
+///
+/// # Errors
+///
+/// Returns `ConfigError::Io` if the file cannot be read and
+/// `ConfigError::ParseError` if it is not a valid .krx file.
+pub fn read_krx(path: &Path) -> Result<ConfigRoot, ConfigError> {
+    use rkyv::Deserialize;
+    let bytes = std::fs::read(path).map_err(ConfigError::Io)?;
+    let archived =
+        keyrx_compiler::serialize::deserialize(&bytes).map_err(|e| ConfigError::ParseError {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+    Ok(archived
+        .deserialize(&mut rkyv::Infallible)
+        .expect("ConfigRoot deserialization is infallible"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,8 +298,13 @@ mod tests {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: "Test Device".to_string(),
+                    excludes: Vec::new(),
                 },
                 mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 1234567890,
@@ -205,4 +377,92 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result, Err(ConfigError::ParseError { .. })));
     }
+
+    #[test]
+    fn test_load_rhai_source_compiles_in_memory() {
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".rhai")
+            .tempfile()
+            .expect("Failed to create temp file");
+        temp_file
+            .write_all(b"device_start(\"*\");\nmap(\"A\", \"VK_B\");\ndevice_end();\n")
+            .expect("Failed to write to temp file");
+        temp_file.flush().expect("Failed to flush temp file");
+
+        let loaded = load_config(temp_file.path()).expect("Rhai source should compile");
+        assert_eq!(loaded.devices.len(), 1);
+        assert_eq!(loaded.devices[0].identifier.pattern.as_str(), "*");
+        assert_eq!(loaded.devices[0].mappings.len(), 1);
+    }
+
+    #[test]
+    fn test_load_rhai_source_reports_compile_errors() {
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".RHAI")
+            .tempfile()
+            .expect("Failed to create temp file");
+        temp_file
+            .write_all(b"device_start(\"*\");\nmap(\"A\", \"B\");\ndevice_end();\n")
+            .expect("Failed to write to temp file");
+        temp_file.flush().expect("Failed to flush temp file");
+
+        let result = load_config(temp_file.path());
+        assert!(matches!(result, Err(ConfigError::ParseError { .. })));
+    }
+
+    #[test]
+    fn test_is_source_config() {
+        assert!(is_source_config(Path::new("layout.rhai")));
+        assert!(is_source_config(Path::new("dir/Layout.RHAI")));
+        assert!(!is_source_config(Path::new("config.krx")));
+        assert!(!is_source_config(Path::new("rhai")));
+    }
+
+    #[test]
+    fn test_loaded_config_clones_share_value() {
+        let loaded = LoadedConfig::new();
+        let shared = loaded.clone();
+        assert_eq!(shared.get(), None);
+
+        loaded.set(Some(create_test_config()));
+        assert_eq!(shared.get(), Some(create_test_config()));
+
+        loaded.set(None);
+        assert_eq!(shared.get(), None);
+    }
+
+    #[test]
+    fn test_loaded_config_set_archived_keeps_the_archive() {
+        let bytes = serialize(&create_test_config()).expect("Serialization failed");
+        let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+        let archived = keyrx_compiler::serialize::deserialize(bytes).unwrap();
+
+        let loaded = LoadedConfig::new();
+        loaded.set_archived(archived);
+        assert!(matches!(
+            loaded.recorded(),
+            Some(Recorded::Archived(recorded)) if std::ptr::eq(recorded, archived)
+        ));
+        assert_eq!(
+            loaded.source_hash(),
+            Some(create_test_config().metadata.source_hash)
+        );
+        assert_eq!(loaded.get(), Some(create_test_config()));
+    }
+
+    #[test]
+    fn test_loaded_config_load_krx() {
+        let mut temp_file = NamedTempFile::new().expect("Failed to create temp file");
+        let bytes = serialize(&create_test_config()).expect("Serialization failed");
+        temp_file.write_all(&bytes).expect("Failed to write");
+
+        let loaded = LoadedConfig::new();
+        loaded.load_krx(temp_file.path()).unwrap();
+        assert_eq!(loaded.get(), Some(create_test_config()));
+
+        // A corrupt file keeps the previous config
+        std::fs::write(temp_file.path(), b"not a krx file").unwrap();
+        assert!(loaded.load_krx(temp_file.path()).is_err());
+        assert_eq!(loaded.get(), Some(create_test_config()));
+    }
 }
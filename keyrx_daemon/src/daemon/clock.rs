@@ -0,0 +1,43 @@
+//! Time source for the daemon's event loop.
+//!
+//! Tap-hold timeouts, scheduled macro output and broadcast timestamps read the
+//! current time through a [`Clock`] instead of the system clock directly, so
+//! tests can inject a [`VirtualClock`](keyrx_core::runtime::clock::VirtualClock)
+//! and advance time deterministically. Latency measurements still use `Instant`, since they
+//! measure real processing time.
+
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use keyrx_core::runtime::clock::Clock;
+
+/// Clock shared between the daemon and its event loop.
+pub type SharedClock = Arc<dyn Clock + Send + Sync>;
+
+/// Wall clock in microseconds since the UNIX epoch.
+///
+/// This matches the timestamps the platforms attach to captured events, so
+/// tap-hold deadlines computed from event timestamps can be compared against
+/// it. It is the daemon's default clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WallClock;
+
+impl Clock for WallClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wall_clock_is_past_2020() {
+        // 2020-01-01T00:00:00Z in microseconds
+        assert!(WallClock.now() > 1_577_836_800_000_000);
+    }
+}
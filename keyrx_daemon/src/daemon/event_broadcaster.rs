@@ -10,24 +10,48 @@ use tokio::sync::broadcast;
 use tokio::time::interval;
 
 use super::metrics::{LatencyRecorder, MetricsAggregator};
-use crate::web::events::{DaemonEvent, DaemonState, KeyEventData, LatencyStats};
+use crate::platform::EventLog;
+use crate::web::events::{DaemonEvent, DaemonState, KeyEventData, LatencyStats, StateCache};
 
 /// Broadcaster for daemon events to WebSocket clients
 #[derive(Clone)]
 pub struct EventBroadcaster {
     event_tx: broadcast::Sender<DaemonEvent>,
+    state_cache: StateCache,
+    event_log: EventLog,
 }
 
 impl EventBroadcaster {
     /// Create a new event broadcaster
     pub fn new(event_tx: broadcast::Sender<DaemonEvent>) -> Self {
-        Self { event_tx }
+        Self {
+            event_tx,
+            state_cache: StateCache::default(),
+            event_log: EventLog::default(),
+        }
+    }
+
+    /// Cache holding the last broadcast state
+    ///
+    /// Share this with the web server so new WebSocket clients get the
+    /// current state in their connect snapshot.
+    pub fn state_cache(&self) -> StateCache {
+        self.state_cache.clone()
+    }
+
+    /// Log of recent key events, each with a monotonic id
+    ///
+    /// Share this with the IPC handler to serve `GetEventsTail` and
+    /// `GetEventsSince`.
+    pub fn event_log(&self) -> EventLog {
+        self.event_log.clone()
     }
 
     /// Broadcast a daemon state change
     ///
     /// This should be called whenever modifier, lock, or layer state changes.
     pub fn broadcast_state(&self, state: DaemonState) {
+        self.state_cache.store(state.clone());
         if let Err(e) = self.event_tx.send(DaemonEvent::State(state)) {
             log::warn!("Failed to broadcast state event: {}", e);
         }
@@ -37,6 +61,7 @@ impl EventBroadcaster {
     ///
     /// This should be called after each key event is processed.
     pub fn broadcast_key_event(&self, event: KeyEventData) {
+        self.event_log.push(format_key_event(&event));
         if let Err(e) = self.event_tx.send(DaemonEvent::KeyEvent(event)) {
             log::warn!("Failed to broadcast key event: {}", e);
         }
@@ -59,6 +84,15 @@ impl EventBroadcaster {
     }
 }
 
+/// Formats a key event for the event log, e.g. `press A -> B [dev-001]`.
+fn format_key_event(event: &KeyEventData) -> String {
+    let mut line = format!("{} {} -> {}", event.event_type, event.input, event.output);
+    if let Some(device_id) = &event.device_id {
+        line.push_str(&format!(" [{}]", device_id));
+    }
+    line
+}
+
 /// Start a background task that periodically broadcasts latency metrics
 ///
 /// This task runs every 1 second and broadcasts latency statistics to all
@@ -192,6 +226,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_broadcast_state_updates_cache() {
+        let (event_tx, _) = broadcast::channel(100);
+        let broadcaster = EventBroadcaster::new(event_tx);
+        let cache = broadcaster.state_cache();
+        assert!(cache.load().is_none());
+
+        // Cached even when no client is subscribed
+        broadcaster.broadcast_state(DaemonState {
+            modifiers: vec![],
+            locks: vec!["LK_01".to_string()],
+            layer: "base".to_string(),
+            active_profile: None,
+        });
+
+        let cached = cache.load().unwrap();
+        assert_eq!(cached.locks, vec!["LK_01"]);
+    }
+
     #[tokio::test]
     async fn test_broadcast_key_event() {
         let (event_tx, mut event_rx) = broadcast::channel(100);
@@ -208,6 +261,7 @@ mod tests {
             device_name: Some("USB Keyboard".to_string()),
             mapping_type: Some("simple".to_string()),
             mapping_triggered: true,
+            matched_mapping: None,
         };
 
         broadcaster.broadcast_key_event(event.clone());
@@ -220,6 +274,10 @@ mod tests {
             }
             _ => panic!("Expected KeyEvent event"),
         }
+
+        let logged = broadcaster.event_log().tail(10);
+        assert_eq!(logged.events, vec!["press A -> B [dev-001]"]);
+        assert_eq!(logged.last_id, 1);
     }
 
     #[tokio::test]
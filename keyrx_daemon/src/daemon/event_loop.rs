@@ -3,31 +3,47 @@
 //! This module contains the core event processing logic, including:
 //!
 //! - Event capture and dispatching
-//! - Reload signal checking
+//! - Reload signal and reload request checking
 //! - Statistics tracking
 //! - Timeout handling for tap-hold
+//! - Scheduled (delayed) macro output injection
 //! - Key remapping via keyrx_core runtime
 
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 // Re-export Instant from std::time for internal use
 use std::time::Instant;
 
-use keyrx_core::config::BaseKeyMapping;
-use keyrx_core::runtime::{check_tap_hold_timeouts, process_event};
-use log::{info, trace, warn};
+use keyrx_core::runtime::clock::Clock;
+use keyrx_core::runtime::event::KeyEvent;
+use keyrx_core::runtime::{check_tap_hold_timeouts, MatchedMapping};
+#[cfg(not(test))]
+use keyrx_core::runtime::{process_event, process_event_traced};
+use log::{debug, error, info, log_enabled, trace, warn, Level};
 
-use crate::platform::Platform;
-use crate::web::events::KeyEventData;
+// Tests swap in versions that can be made to panic
+#[cfg(test)]
+use tests::{process_event, process_event_traced};
+
+use crate::platform::{EventCounters, Platform};
+use crate::web::events::{KeyEventData, MatchedMappingData};
 
 use super::event_broadcaster::EventBroadcaster;
 use super::metrics::LatencyRecorder;
-use super::remapping_state::RemappingState;
+use super::remapping_state::{local_minute_of_day, RemappingState};
 use super::signals::SignalHandler;
+use super::state::ReloadRequest;
 use super::DaemonError;
 
+/// Longest the loop sleeps when no input is available, so reload and
+/// shutdown requests are still noticed promptly.
+const IDLE_POLL: Duration = Duration::from_millis(10);
+
 /// Event loop statistics tracking.
 struct EventLoopStats {
     /// Total number of events processed.
@@ -71,25 +87,6 @@ impl EventLoopStats {
     }
 }
 
-/// Determines mapping type string from a BaseKeyMapping.
-fn get_mapping_type(mapping: &BaseKeyMapping) -> &'static str {
-    match mapping {
-        BaseKeyMapping::Simple { .. } => "simple",
-        BaseKeyMapping::Modifier { .. } => "modifier",
-        BaseKeyMapping::Lock { .. } => "lock",
-        BaseKeyMapping::TapHold { .. } => "tap_hold",
-        BaseKeyMapping::ModifiedOutput { .. } => "modified_output",
-    }
-}
-
-/// Returns current timestamp in microseconds since UNIX epoch.
-fn current_timestamp_us() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_micros() as u64)
-        .unwrap_or(0)
-}
-
 /// Runs the main event processing loop.
 ///
 /// This function captures keyboard events from the platform, processes them
@@ -101,22 +98,32 @@ fn current_timestamp_us() -> u64 {
 /// * `platform` - Platform abstraction for input/output operations
 /// * `running` - Atomic flag controlling loop execution
 /// * `signal_handler` - Signal handler for reload detection
-/// * `reload_callback` - Callback to invoke when reload is requested
+/// * `reload_callback` - Callback that reloads the configuration, from the
+///   given file or (on SIGHUP, with `None`) from the configured source, and
+///   swaps it into the remapping state
+/// * `reload_requests` - Optional queue of requests to reload from a file,
+///   each answered with the callback's result
 /// * `event_broadcaster` - Optional broadcaster for real-time WebSocket updates
-/// * `remapping_state` - Optional remapping state for key remapping (KeyLookup + DeviceState)
+/// * `remapping_state` - Remapping state for key remapping (KeyLookup + DeviceState),
+///   `None` in pass-through mode
 /// * `latency_recorder` - Optional lock-free latency recorder for metrics
+/// * `event_counters` - Optional lock-free counters for failed injections
+/// * `clock` - Time source for tap-hold timeouts, scheduled macro output and
+///   broadcast timestamps ([`WallClock`](super::WallClock) in production)
 ///
 /// # Event Processing Flow
 ///
 /// For each input event:
-/// 1. Check for reload signal (SIGHUP)
+/// 1. Check for reload signal (SIGHUP) and queued reload requests
 /// 2. Capture event from platform (blocking)
 /// 3. Process event through remapping engine (if remapping_state provided)
 /// 4. Inject output events through platform
 /// 5. Record latency (if latency_recorder provided)
 ///
-/// Periodically (every 10ms when no events):
-/// - Check tap-hold timeouts and inject any pending hold events
+/// On every pass, scheduled macro output and tap-hold timeouts that have come
+/// due are injected, then the platform is flushed. When no input is available
+/// the loop sleeps until the next scheduled output or tap-hold deadline, but
+/// never longer than 10ms.
 ///
 /// # Signal Handling
 ///
@@ -140,7 +147,7 @@ fn current_timestamp_us() -> u64 {
 /// use std::sync::atomic::{AtomicBool, Ordering};
 /// use std::sync::Arc;
 /// use keyrx_daemon::daemon::event_loop::run_event_loop;
-/// use keyrx_daemon::daemon::DaemonError;
+/// use keyrx_daemon::daemon::{DaemonError, WallClock};
 /// use keyrx_daemon::platform::Platform;
 ///
 /// fn example(
@@ -152,44 +159,67 @@ fn current_timestamp_us() -> u64 {
 ///         platform,
 ///         running,
 ///         signal_handler,
-///         || Err(DaemonError::RuntimeError("Reload not supported".to_string())),
-///         None, // No event broadcaster
-///         None, // No remapping state (pass-through mode)
+///         |_, _, _| Err(DaemonError::RuntimeError("Reload not supported".to_string())),
+///         None,      // No reload requests
+///         None,      // No event broadcaster
+///         &mut None, // No remapping state (pass-through mode)
 ///         None, // No latency recording
+///         None, // No failure counters
+///         &WallClock,
 ///     )
 /// }
 /// ```
+#[allow(clippy::too_many_arguments)]
 pub fn run_event_loop<F>(
     platform: &mut Box<dyn Platform>,
     running: Arc<AtomicBool>,
     signal_handler: &SignalHandler,
     mut reload_callback: F,
+    reload_requests: Option<&Receiver<ReloadRequest>>,
     event_broadcaster: Option<&EventBroadcaster>,
-    mut remapping_state: Option<&mut RemappingState>,
+    remapping_state: &mut Option<RemappingState>,
     latency_recorder: Option<&LatencyRecorder>,
+    event_counters: Option<&EventCounters>,
+    clock: &dyn Clock,
 ) -> Result<(), DaemonError>
 where
-    F: FnMut() -> Result<(), DaemonError>,
+    F: FnMut(
+        Option<&Path>,
+        &mut Box<dyn Platform>,
+        &mut Option<RemappingState>,
+    ) -> Result<(), DaemonError>,
 {
     info!("Starting event processing loop");
 
     let mut stats = EventLoopStats::new();
-    let mut last_timeout_check = Instant::now();
 
     // Main event loop
     while running.load(Ordering::SeqCst) {
         // Check for SIGHUP (reload request)
         if signal_handler.check_reload() {
             info!("Reload signal received (SIGHUP)");
-            if let Err(e) = reload_callback() {
+            if let Err(e) = reload_callback(None, platform, remapping_state) {
                 // Log the error but continue running
                 warn!("Configuration reload failed: {}", e);
             }
         }
 
+        // Apply reloads requested over IPC, answering only once swapped in
+        if let Some(requests) = reload_requests {
+            while let Ok(request) = requests.try_recv() {
+                info!("Reload requested from {}", request.path().display());
+                let result = reload_callback(Some(request.path()), platform, remapping_state);
+                if let Err(e) = &result {
+                    warn!("Configuration reload failed: {}", e);
+                }
+                request.reply(result);
+            }
+        }
+
         // Capture input event from platform (blocking with timeout to allow signal checking)
         // Note: capture_input() may return an error if no events are available
         // We treat this as non-fatal and continue the loop
+        let mut idle = false;
         match platform.capture_input() {
             Ok(event) => {
                 let capture_time = Instant::now();
@@ -199,25 +229,13 @@ where
                 let device_id = event.device_id().map(String::from);
                 let input_keycode = event.keycode();
 
-                // Process event through remapping engine if available
-                let (output_events, mapping_type, mapping_triggered) =
-                    if let Some(ref mut remap_state) = remapping_state {
-                        // Get lookup and state references together to avoid borrow conflicts
-                        let (lookup, state) = remap_state.lookup_and_state_mut();
-
-                        // Look up mapping to determine type before processing
-                        let mapping = lookup.find_mapping(input_keycode, state);
-                        let mapping_type_str = mapping.map(get_mapping_type);
-                        let triggered = mapping.is_some();
-
-                        // Process the event through the remapping engine
-                        let outputs = process_event(event.clone(), lookup, state);
-
-                        (outputs, mapping_type_str, triggered)
-                    } else {
-                        // Pass-through mode - no remapping
-                        (vec![event.clone()], None, false)
-                    };
+                // Process event through remapping engine if available,
+                // noting which mapping fired only when someone will see it
+                let trace_mapping = event_broadcaster.is_some() || log_enabled!(Level::Debug);
+                let (output_events, matched) =
+                    remap_event(&event, remapping_state.as_mut(), trace_mapping);
+                let mapping_type = matched.map(|m| m.kind.as_str());
+                let mapping_triggered = output_events.as_slice() != std::slice::from_ref(&event);
 
                 // Compute output description for broadcast
                 let output_desc = if output_events.is_empty() {
@@ -233,23 +251,33 @@ where
                 // Inject output events
                 // On Linux, grab() blocks original events so we MUST always inject.
                 // On Windows, Raw Input doesn't block events so they flow naturally.
-                for output_event in &output_events {
-                    if let Err(e) = platform.inject_output(output_event.clone()) {
-                        warn!("Failed to inject event: {}", e);
-                    } else {
+                // They go out as one batch so a modifier and its key arrive together.
+                let injected = platform.inject_outputs(&output_events);
+                if let Err(e) = &injected {
+                    warn!("Failed to inject events: {}", e);
+                    if let Some(counters) = event_counters {
+                        counters.record_injection_failure();
+                    }
+                } else {
+                    for _ in &output_events {
                         stats.record_event();
                     }
                 }
+                if let Some(remap_state) = remapping_state.as_mut() {
+                    remap_state.record_outputs(&output_events, injected.is_ok());
+                    // Macro steps after a wait() go out once they are due
+                    remap_state.queue_scheduled_outputs(event.timestamp_us(), clock.now());
+                }
 
                 // Record latency after injection
                 let latency_us = capture_time.elapsed().as_micros() as u64;
                 if let Some(recorder) = latency_recorder {
-                    recorder.record(latency_us);
+                    recorder.record_for_device(device_id.as_deref(), latency_us);
                 }
 
                 // Broadcast key event to WebSocket clients if broadcaster is available
                 if let Some(broadcaster) = event_broadcaster {
-                    let timestamp = current_timestamp_us();
+                    let timestamp = clock.now();
 
                     let event_data = KeyEventData {
                         timestamp,
@@ -257,6 +285,7 @@ where
                         event_type: match event.event_type() {
                             keyrx_core::runtime::KeyEventType::Press => "press".to_string(),
                             keyrx_core::runtime::KeyEventType::Release => "release".to_string(),
+                            keyrx_core::runtime::KeyEventType::Repeat => "repeat".to_string(),
                         },
                         input: format!("{:?}", input_keycode),
                         output: output_desc,
@@ -265,6 +294,7 @@ where
                         device_name: device_id,
                         mapping_type: mapping_type.map(String::from),
                         mapping_triggered,
+                        matched_mapping: matched.map(MatchedMappingData::from),
                     };
                     broadcaster.broadcast_key_event(event_data);
                 }
@@ -277,32 +307,32 @@ where
 
                 // Log non-fatal errors and continue
                 trace!("Event capture returned error (may be timeout): {}", e);
+                idle = true;
+            }
+        }
 
-                // Check tap-hold timeouts every 10ms when idle
-                if last_timeout_check.elapsed() >= Duration::from_millis(10) {
-                    if let Some(ref mut remap_state) = remapping_state {
-                        let current_time = current_timestamp_us();
-                        let timeout_events =
-                            check_tap_hold_timeouts(current_time, remap_state.state_mut());
-
-                        // Inject any timeout-generated events (e.g., hold action triggered)
-                        for output_event in &timeout_events {
-                            if let Err(e) = platform.inject_output(output_event.clone()) {
-                                warn!("Failed to inject timeout event: {}", e);
-                            } else {
-                                stats.record_event();
-                                trace!("Tap-hold timeout event injected: {:?}", output_event);
-                            }
-                        }
-                    }
-                    last_timeout_check = Instant::now();
-                }
+        inject_due(
+            platform,
+            remapping_state.as_mut(),
+            &mut stats,
+            event_counters,
+            clock.now(),
+        );
 
-                // Small sleep to prevent busy loop
-                std::thread::sleep(Duration::from_millis(10));
+        // Everything injected in this pass goes out together
+        if let Err(e) = platform.flush() {
+            warn!("Failed to flush injected events: {}", e);
+            if let Some(counters) = event_counters {
+                counters.record_injection_failure();
             }
         }
 
+        if idle {
+            // Wake up for the next scheduled output or tap-hold deadline
+            // instead of polling for it
+            std::thread::sleep(idle_wait(remapping_state.as_ref(), clock.now()));
+        }
+
         // Periodic stats logging
         stats.maybe_log_stats();
     }
@@ -315,11 +345,134 @@ where
     Ok(())
 }
 
+/// Runs `event` through the remapping engine, or passes it through unchanged
+/// when there is no remapping state.
+///
+/// With `trace_mapping`, also returns the mapping that handled the event,
+/// logged at debug level. Tracing is skipped otherwise, since nothing would
+/// read the result.
+///
+/// A panic while remapping is caught and logged, and the event is dropped.
+/// Unwinding further would end the event loop and release every grab,
+/// leaving the user without a working keyboard.
+fn remap_event(
+    event: &KeyEvent,
+    remapping_state: Option<&mut RemappingState>,
+    trace_mapping: bool,
+) -> (Vec<KeyEvent>, Option<MatchedMapping>) {
+    let Some(remap_state) = remapping_state else {
+        return (vec![event.clone()], None);
+    };
+
+    match catch_unwind(AssertUnwindSafe(|| {
+        remap_with_state(event, remap_state, trace_mapping)
+    })) {
+        Ok(remapped) => remapped,
+        Err(_) => {
+            error!("Panic while remapping {:?}; event dropped", event);
+            (Vec::new(), None)
+        }
+    }
+}
+
+/// Remaps `event` with `remap_state`; see [`remap_event`].
+fn remap_with_state(
+    event: &KeyEvent,
+    remap_state: &mut RemappingState,
+    trace_mapping: bool,
+) -> (Vec<KeyEvent>, Option<MatchedMapping>) {
+    // Get lookup and state references together to avoid borrow conflicts
+    let (lookup, state) = remap_state.lookup_and_state_mut();
+    // Reading the local time costs a timezone lookup; skip it unless a
+    // `TW_XX` condition can read it
+    if !state.time_windows().is_empty() {
+        state.set_minute_of_day(local_minute_of_day());
+    }
+
+    let (outputs, matched) = if trace_mapping {
+        process_event_traced(event.clone(), lookup, state)
+    } else {
+        (process_event(event.clone(), lookup, state), None)
+    };
+    if let Some(matched) = matched {
+        debug!(
+            "{:?} handled by {} mapping {:?}",
+            event.keycode(),
+            matched.kind.as_str(),
+            matched.position
+        );
+    }
+    (outputs, matched)
+}
+
+/// Injects scheduled macro output that has come due at `now`
+/// (microseconds), followed by the events of any tap-hold key that timed out
+/// into a hold.
+fn inject_due(
+    platform: &mut Box<dyn Platform>,
+    remapping_state: Option<&mut RemappingState>,
+    stats: &mut EventLoopStats,
+    event_counters: Option<&EventCounters>,
+    now: u64,
+) {
+    let Some(remap_state) = remapping_state else {
+        return;
+    };
+
+    let mut due_events = remap_state.take_due_outputs(now);
+    if remap_state
+        .state()
+        .tap_hold_processor_ref()
+        .has_pending_keys()
+    {
+        due_events.extend(check_tap_hold_timeouts(now, remap_state.state_mut()));
+    }
+    if due_events.is_empty() {
+        return;
+    }
+
+    let injected = platform.inject_outputs(&due_events);
+    if let Err(e) = &injected {
+        warn!("Failed to inject scheduled events: {}", e);
+        if let Some(counters) = event_counters {
+            counters.record_injection_failure();
+        }
+    } else {
+        for output_event in &due_events {
+            stats.record_event();
+            trace!("Scheduled event injected: {:?}", output_event);
+        }
+    }
+    remap_state.record_outputs(&due_events, injected.is_ok());
+}
+
+/// Returns how long an idle loop may sleep at `now` (microseconds) before
+/// scheduled output or a tap-hold timeout is due, capped at [`IDLE_POLL`].
+fn idle_wait(remapping_state: Option<&RemappingState>, now: u64) -> Duration {
+    let mut wait = IDLE_POLL;
+    let Some(remap_state) = remapping_state else {
+        return wait;
+    };
+
+    let deadlines = [
+        remap_state.next_scheduled_due(),
+        remap_state.state().tap_hold_processor_ref().next_deadline(),
+    ];
+    for deadline in deadlines.into_iter().flatten() {
+        let remaining_us = deadline.saturating_sub(now);
+        wait = wait.min(Duration::from_micros(remaining_us));
+    }
+
+    wait
+}
+
 /// Process a single event from the platform (non-blocking).
 ///
 /// This function is designed for platforms like Windows where the event loop
 /// must be integrated with a system message pump. It attempts to capture one
 /// event and process it, returning immediately if no event is available.
+/// Scheduled macro output that has come due is injected first, so the pump
+/// only needs to call this often enough for the macro's timing.
 ///
 /// # Arguments
 ///
@@ -327,6 +480,8 @@ where
 /// * `event_broadcaster` - Optional broadcaster for real-time WebSocket updates
 /// * `remapping_state` - Optional remapping state for key remapping
 /// * `latency_recorder` - Optional latency recorder for metrics
+/// * `event_counters` - Optional counters for failed injections
+/// * `clock` - Time source for scheduled macro output and broadcast timestamps
 ///
 /// # Returns
 ///
@@ -336,9 +491,15 @@ where
 pub fn process_one_event(
     platform: &mut Box<dyn Platform>,
     event_broadcaster: Option<&EventBroadcaster>,
-    remapping_state: Option<&mut RemappingState>,
+    mut remapping_state: Option<&mut RemappingState>,
     latency_recorder: Option<&LatencyRecorder>,
+    event_counters: Option<&EventCounters>,
+    clock: &dyn Clock,
 ) -> Result<bool, DaemonError> {
+    if let Some(remap_state) = remapping_state.as_deref_mut() {
+        inject_scheduled(platform, remap_state, event_counters, clock.now());
+    }
+
     // Try to capture an input event (non-blocking on Windows)
     match platform.capture_input() {
         Ok(event) => {
@@ -350,18 +511,15 @@ pub fn process_one_event(
             let input_keycode = event.keycode();
 
             // Process event through remapping engine if available
-            let (output_events, mapping_type, mapping_triggered) =
-                if let Some(remap_state) = remapping_state {
-                    let (lookup, state) = remap_state.lookup_and_state_mut();
-                    let mapping = lookup.find_mapping(input_keycode, state);
-                    let mapping_type_str = mapping.map(get_mapping_type);
-                    let triggered = mapping.is_some();
-                    let outputs = process_event(event.clone(), lookup, state);
-                    (outputs, mapping_type_str, triggered)
-                } else {
-                    // Pass-through mode - no remapping
-                    (vec![event.clone()], None, false)
-                };
+            let trace_mapping = event_broadcaster.is_some() || log_enabled!(Level::Debug);
+            let (output_events, matched) =
+                remap_event(&event, remapping_state.as_deref_mut(), trace_mapping);
+            if let Some(remap_state) = remapping_state.as_deref_mut() {
+                // Macro steps after a wait() go out once they are due
+                remap_state.queue_scheduled_outputs(event.timestamp_us(), clock.now());
+            }
+            let mapping_type = matched.map(|m| m.kind.as_str());
+            let mapping_triggered = output_events.as_slice() != std::slice::from_ref(&event);
 
             // Compute output description for broadcast
             let output_desc = if output_events.is_empty() {
@@ -374,14 +532,28 @@ pub fn process_one_event(
                     .join(", ")
             };
 
-            // Only inject output events if remapping was triggered
+            // Only inject output events if remapping changed the event
             // In pass-through mode (no remapping), we must NOT inject because:
             // 1. The original key event will reach applications naturally
             // 2. Injecting would cause a feedback loop (captured again by Raw Input)
             if mapping_triggered {
+                let mut injected = true;
                 for output_event in &output_events {
                     if let Err(e) = platform.inject_output(output_event.clone()) {
                         warn!("Failed to inject event: {}", e);
+                        injected = false;
+                        if let Some(counters) = event_counters {
+                            counters.record_injection_failure();
+                        }
+                    }
+                }
+                if let Some(remap_state) = remapping_state {
+                    remap_state.record_outputs(&output_events, injected);
+                }
+                if let Err(e) = platform.flush() {
+                    warn!("Failed to flush injected events: {}", e);
+                    if let Some(counters) = event_counters {
+                        counters.record_injection_failure();
                     }
                 }
             }
@@ -389,12 +561,12 @@ pub fn process_one_event(
             // Record latency after injection
             let latency_us = capture_time.elapsed().as_micros() as u64;
             if let Some(recorder) = latency_recorder {
-                recorder.record(latency_us);
+                recorder.record_for_device(device_id.as_deref(), latency_us);
             }
 
             // Broadcast key event to WebSocket clients if broadcaster is available
             if let Some(broadcaster) = event_broadcaster {
-                let timestamp = current_timestamp_us();
+                let timestamp = clock.now();
 
                 let event_data = KeyEventData {
                     timestamp,
@@ -402,6 +574,7 @@ pub fn process_one_event(
                     event_type: match event.event_type() {
                         keyrx_core::runtime::KeyEventType::Press => "press".to_string(),
                         keyrx_core::runtime::KeyEventType::Release => "release".to_string(),
+                        keyrx_core::runtime::KeyEventType::Repeat => "repeat".to_string(),
                     },
                     input: format!("{:?}", input_keycode),
                     output: output_desc,
@@ -410,6 +583,7 @@ pub fn process_one_event(
                     device_name: device_id,
                     mapping_type: mapping_type.map(String::from),
                     mapping_triggered,
+                    matched_mapping: matched.map(MatchedMappingData::from),
                 };
                 broadcaster.broadcast_key_event(event_data);
             }
@@ -423,9 +597,67 @@ pub fn process_one_event(
     }
 }
 
+/// Injects and flushes the scheduled macro output due at `now`
+/// (microseconds), for [`process_one_event`].
+fn inject_scheduled(
+    platform: &mut Box<dyn Platform>,
+    remap_state: &mut RemappingState,
+    event_counters: Option<&EventCounters>,
+    now: u64,
+) {
+    let due_events = remap_state.take_due_outputs(now);
+    if due_events.is_empty() {
+        return;
+    }
+
+    let injected = platform
+        .inject_outputs(&due_events)
+        .and_then(|()| platform.flush());
+    if let Err(e) = &injected {
+        warn!("Failed to inject scheduled events: {}", e);
+        if let Some(counters) = event_counters {
+            counters.record_injection_failure();
+        }
+    }
+    remap_state.record_outputs(&due_events, injected.is_ok());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use keyrx_core::config::KeyCode;
+    use keyrx_core::runtime::{DeviceState, KeyLookup};
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Key whose remapping panics on this thread, standing in for a
+        /// mapping bug.
+        static PANIC_ON: Cell<Option<KeyCode>> = const { Cell::new(None) };
+    }
+
+    fn panic_if_armed(event: &KeyEvent) {
+        if PANIC_ON.with(Cell::get) == Some(event.keycode()) {
+            panic!("simulated remapping bug for {:?}", event.keycode());
+        }
+    }
+
+    pub(super) fn process_event(
+        event: KeyEvent,
+        lookup: &KeyLookup,
+        state: &mut DeviceState,
+    ) -> Vec<KeyEvent> {
+        panic_if_armed(&event);
+        keyrx_core::runtime::process_event(event, lookup, state)
+    }
+
+    pub(super) fn process_event_traced(
+        event: KeyEvent,
+        lookup: &KeyLookup,
+        state: &mut DeviceState,
+    ) -> (Vec<KeyEvent>, Option<MatchedMapping>) {
+        panic_if_armed(&event);
+        keyrx_core::runtime::process_event_traced(event, lookup, state)
+    }
 
     #[test]
     fn test_event_loop_stats_new() {
@@ -446,6 +678,449 @@ mod tests {
         assert_eq!(stats.total_events(), 3);
     }
 
+    #[test]
+    fn test_idle_wait_without_remapping_is_idle_poll() {
+        assert_eq!(idle_wait(None, 1_000_000), IDLE_POLL);
+    }
+
+    /// Input step replayed by [`ScriptedPlatform`].
+    enum Step {
+        /// Deliver this event.
+        Event(keyrx_core::runtime::event::KeyEvent),
+        /// Report no input after advancing the virtual clock.
+        Advance(u64),
+    }
+
+    /// Platform that replays a script against a virtual clock and stops the
+    /// loop when the script runs out.
+    struct ScriptedPlatform {
+        script: std::collections::VecDeque<Step>,
+        clock: Arc<keyrx_core::runtime::VirtualClock>,
+        running: Arc<AtomicBool>,
+        injected: Arc<std::sync::Mutex<Vec<keyrx_core::runtime::event::KeyEvent>>>,
+    }
+
+    impl Platform for ScriptedPlatform {
+        fn initialize(&mut self) -> crate::platform::PlatformResult<()> {
+            Ok(())
+        }
+
+        fn capture_input(
+            &mut self,
+        ) -> crate::platform::PlatformResult<keyrx_core::runtime::event::KeyEvent> {
+            match self.script.pop_front() {
+                Some(Step::Event(event)) => return Ok(event),
+                Some(Step::Advance(delta_us)) => self.clock.advance(delta_us),
+                None => self.running.store(false, Ordering::SeqCst),
+            }
+            Err(crate::platform::PlatformError::DeviceNotFound(
+                "no scripted input".to_string(),
+            ))
+        }
+
+        fn inject_output(
+            &mut self,
+            event: keyrx_core::runtime::event::KeyEvent,
+        ) -> crate::platform::PlatformResult<()> {
+            self.injected.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn list_devices(
+            &self,
+        ) -> crate::platform::PlatformResult<Vec<crate::platform::DeviceInfo>> {
+            Ok(Vec::new())
+        }
+
+        fn shutdown(&mut self) -> crate::platform::PlatformResult<()> {
+            Ok(())
+        }
+    }
+
+    /// Space is tap-hold (MD_00 after 200ms, timeout policy); J becomes Left
+    /// while MD_00 is held; F1 is a macro tapping H, then Y 50ms later.
+    fn tap_hold_state() -> RemappingState {
+        use keyrx_core::config::{
+            BaseKeyMapping, Condition, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping,
+            MacroStep, TapHoldPolicy,
+        };
+
+        RemappingState::new(&DeviceConfig {
+            identifier: DeviceIdentifier {
+                pattern: "*".to_string(),
+                excludes: Vec::new(),
+            },
+            mappings: vec![
+                KeyMapping::Base(BaseKeyMapping::TapHold {
+                    from: KeyCode::Space,
+                    tap: KeyCode::Space,
+                    hold_modifier: 0,
+                    threshold_ms: 200,
+                    policy: TapHoldPolicy::Timeout,
+                }),
+                KeyMapping::conditional(
+                    Condition::ModifierActive(0),
+                    vec![BaseKeyMapping::Simple {
+                        from: KeyCode::J,
+                        to: KeyCode::Left,
+                    }],
+                ),
+                KeyMapping::macro_steps(
+                    KeyCode::F1,
+                    vec![
+                        MacroStep::Press(KeyCode::H),
+                        MacroStep::Release(KeyCode::H),
+                        MacroStep::Wait(50),
+                        MacroStep::Press(KeyCode::Y),
+                        MacroStep::Release(KeyCode::Y),
+                    ],
+                ),
+            ],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
+        })
+    }
+
+    fn scripted_platform(
+        script: Vec<Step>,
+        clock: &Arc<keyrx_core::runtime::VirtualClock>,
+        running: &Arc<AtomicBool>,
+    ) -> (
+        Box<dyn Platform>,
+        Arc<std::sync::Mutex<Vec<keyrx_core::runtime::event::KeyEvent>>>,
+    ) {
+        let injected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let platform = ScriptedPlatform {
+            script: script.into(),
+            clock: Arc::clone(clock),
+            running: Arc::clone(running),
+            injected: Arc::clone(&injected),
+        };
+        (Box::new(platform), injected)
+    }
+
+    #[test]
+    fn test_tap_hold_timeout_follows_clock() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, _injected) = scripted_platform(Vec::new(), &clock, &running);
+        let mut state = tap_hold_state();
+        let mut stats = EventLoopStats::new();
+
+        let (lookup, device_state) = state.lookup_and_state_mut();
+        let outputs = process_event(
+            KeyEvent::press(KeyCode::Space).with_timestamp(0),
+            lookup,
+            device_state,
+        );
+        assert!(outputs.is_empty());
+        assert!(!state.state().is_modifier_active(0));
+
+        // Before the threshold: still pending, and idle sleep stops at it
+        clock.set(195_000);
+        inject_due(
+            &mut platform,
+            Some(&mut state),
+            &mut stats,
+            None,
+            clock.now(),
+        );
+        assert!(!state.state().is_modifier_active(0));
+        assert_eq!(
+            idle_wait(Some(&state), clock.now()),
+            Duration::from_millis(5)
+        );
+
+        // Past the threshold: the hold fires without any real time passing
+        clock.set(250_000);
+        inject_due(
+            &mut platform,
+            Some(&mut state),
+            &mut stats,
+            None,
+            clock.now(),
+        );
+        assert!(state.state().is_modifier_active(0));
+    }
+
+    #[test]
+    fn test_idle_wait_stops_at_next_scheduled_output() {
+        use keyrx_core::config::KeyCode;
+
+        let mut state = tap_hold_state();
+        assert_eq!(idle_wait(Some(&state), 1_000_000), IDLE_POLL);
+
+        let press = KeyEvent::press(KeyCode::F1).with_timestamp(1_000_000);
+        let (outputs, _) = remap_event(&press, Some(&mut state), false);
+        assert_eq!(outputs.len(), 2);
+        state.queue_scheduled_outputs(press.timestamp_us(), 1_000_000);
+        assert_eq!(idle_wait(Some(&state), 1_047_000), Duration::from_millis(3));
+        assert_eq!(idle_wait(Some(&state), 1_060_000), Duration::ZERO);
+    }
+
+    /// Runs the event loop over `script` with [`tap_hold_state`] and returns
+    /// the injected keys.
+    fn run_script(script: Vec<Step>) -> Vec<(KeyCode, keyrx_core::runtime::KeyEventType)> {
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, injected) = scripted_platform(script, &clock, &running);
+        let signal_handler = crate::daemon::install_signal_handlers(Arc::clone(&running)).unwrap();
+        let mut state = Some(tap_hold_state());
+
+        run_event_loop(
+            &mut platform,
+            Arc::clone(&running),
+            &signal_handler,
+            |_, _, _| Ok(()),
+            None,
+            None,
+            &mut state,
+            None,
+            None,
+            clock.as_ref(),
+        )
+        .unwrap();
+
+        let keys = injected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event.keycode(), event.event_type()))
+            .collect();
+        keys
+    }
+
+    #[test]
+    fn test_run_event_loop_injects_macro_steps_when_due() {
+        use keyrx_core::runtime::KeyEventType::{Press, Release};
+
+        // Before the wait() is over only the steps ahead of it are out
+        let keys = run_script(vec![
+            Step::Event(KeyEvent::press(KeyCode::F1).with_timestamp(0)),
+            Step::Event(KeyEvent::release(KeyCode::F1).with_timestamp(10_000)),
+            Step::Advance(40_000),
+        ]);
+        assert_eq!(keys, vec![(KeyCode::H, Press), (KeyCode::H, Release)]);
+
+        // Once the clock passes it, the loop injects the rest by itself
+        let keys = run_script(vec![
+            Step::Event(KeyEvent::press(KeyCode::F1).with_timestamp(0)),
+            Step::Event(KeyEvent::release(KeyCode::F1).with_timestamp(10_000)),
+            Step::Advance(40_000),
+            Step::Advance(20_000),
+        ]);
+        assert_eq!(
+            keys,
+            vec![
+                (KeyCode::H, Press),
+                (KeyCode::H, Release),
+                (KeyCode::Y, Press),
+                (KeyCode::Y, Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remap_event_traces_mapping_only_when_asked() {
+        use keyrx_core::config::{KeyCode, MappingKind};
+
+        let mut state = tap_hold_state();
+        let press = KeyEvent::press(KeyCode::Space).with_timestamp(0);
+
+        let (outputs, matched) = remap_event(&press, Some(&mut state), false);
+        assert!(outputs.is_empty());
+        assert_eq!(matched, None);
+
+        let mut state = tap_hold_state();
+        let (outputs, matched) = remap_event(&press, Some(&mut state), true);
+        assert!(outputs.is_empty());
+        assert_eq!(matched.map(|m| m.kind), Some(MappingKind::TapHold));
+
+        // Without remapping state the event passes through untraced
+        let (outputs, matched) = remap_event(&press, None, true);
+        assert_eq!(outputs, vec![press]);
+        assert_eq!(matched, None);
+    }
+
+    #[test]
+    fn test_remap_event_reads_time_only_with_time_windows() {
+        use keyrx_core::config::{KeyCode, TimeWindow};
+
+        let press = KeyEvent::press(KeyCode::A).with_timestamp(0);
+
+        let mut state = tap_hold_state();
+        remap_event(&press, Some(&mut state), false);
+        assert_eq!(state.state().minute_of_day(), None);
+
+        state.state_mut().set_time_windows(&[TimeWindow {
+            start_minute: 0,
+            end_minute: 0,
+        }]);
+        remap_event(&press, Some(&mut state), false);
+        assert!(state.state().minute_of_day().is_some());
+    }
+
+    #[test]
+    fn test_run_event_loop_with_virtual_clock() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, injected) = scripted_platform(
+            vec![
+                Step::Event(KeyEvent::press(KeyCode::Space).with_timestamp(0)),
+                Step::Advance(300_000),
+                Step::Event(KeyEvent::press(KeyCode::J).with_timestamp(300_000)),
+                Step::Event(KeyEvent::release(KeyCode::J).with_timestamp(310_000)),
+            ],
+            &clock,
+            &running,
+        );
+        let signal_handler = crate::daemon::install_signal_handlers(Arc::clone(&running)).unwrap();
+        let mut state = Some(tap_hold_state());
+
+        run_event_loop(
+            &mut platform,
+            Arc::clone(&running),
+            &signal_handler,
+            |_, _, _| Ok(()),
+            None,
+            None,
+            &mut state,
+            None,
+            None,
+            clock.as_ref(),
+        )
+        .unwrap();
+
+        let keys: Vec<_> = injected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| (event.keycode(), event.event_type()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (KeyCode::Left, keyrx_core::runtime::KeyEventType::Press),
+                (KeyCode::Left, keyrx_core::runtime::KeyEventType::Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_one_event_survives_remapping_panic() {
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, injected) = scripted_platform(
+            vec![
+                Step::Event(KeyEvent::press(KeyCode::K).with_timestamp(0)),
+                Step::Event(KeyEvent::press(KeyCode::J).with_timestamp(10_000)),
+            ],
+            &clock,
+            &running,
+        );
+        let mut state = tap_hold_state();
+
+        PANIC_ON.with(|key| key.set(Some(KeyCode::K)));
+        let processed = process_one_event(
+            &mut platform,
+            None,
+            Some(&mut state),
+            None,
+            None,
+            clock.as_ref(),
+        );
+        PANIC_ON.with(|key| key.set(None));
+
+        // The panicking event is dropped, not injected
+        assert!(processed.unwrap());
+        assert!(injected.lock().unwrap().is_empty());
+
+        // Later events are still remapped with the same state
+        state.state_mut().set_modifier(0);
+        let processed = process_one_event(
+            &mut platform,
+            None,
+            Some(&mut state),
+            None,
+            None,
+            clock.as_ref(),
+        );
+        assert!(processed.unwrap());
+        let keys: Vec<_> = injected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.keycode())
+            .collect();
+        assert_eq!(keys, vec![KeyCode::Left]);
+    }
+
+    #[test]
+    fn test_process_one_event_injects_due_macro_steps() {
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, injected) = scripted_platform(
+            vec![Step::Event(KeyEvent::press(KeyCode::F1).with_timestamp(0))],
+            &clock,
+            &running,
+        );
+        let mut state = tap_hold_state();
+        let mut process = |platform: &mut Box<dyn Platform>| {
+            process_one_event(platform, None, Some(&mut state), None, None, clock.as_ref())
+        };
+
+        assert!(process(&mut platform).unwrap());
+        assert_eq!(injected.lock().unwrap().len(), 2);
+
+        // No input, but the macro's wait() is over
+        clock.set(50_000);
+        assert!(!process(&mut platform).unwrap());
+        let keys: Vec<_> = injected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.keycode())
+            .collect();
+        assert_eq!(keys, vec![KeyCode::H, KeyCode::H, KeyCode::Y, KeyCode::Y]);
+    }
+
+    #[test]
+    fn test_macro_waits_when_trigger_has_no_timestamp() {
+        // Windows hooks deliver events with timestamp 0
+        let clock = Arc::new(keyrx_core::runtime::VirtualClock::new());
+        clock.set(5_000_000);
+        let running = Arc::new(AtomicBool::new(true));
+        let (mut platform, injected) = scripted_platform(
+            vec![Step::Event(KeyEvent::press(KeyCode::F1))],
+            &clock,
+            &running,
+        );
+        let mut state = tap_hold_state();
+        let mut process = |platform: &mut Box<dyn Platform>| {
+            process_one_event(platform, None, Some(&mut state), None, None, clock.as_ref())
+        };
+
+        assert!(process(&mut platform).unwrap());
+        assert!(!process(&mut platform).unwrap());
+        assert_eq!(injected.lock().unwrap().len(), 2);
+
+        clock.set(5_049_999);
+        assert!(!process(&mut platform).unwrap());
+        assert_eq!(injected.lock().unwrap().len(), 2);
+
+        clock.set(5_050_000);
+        assert!(!process(&mut platform).unwrap());
+        assert_eq!(injected.lock().unwrap().len(), 4);
+    }
+
     #[test]
     fn test_event_loop_stats_maybe_log_stats_not_yet() {
         let mut stats = EventLoopStats::new();
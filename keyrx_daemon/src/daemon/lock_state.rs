@@ -0,0 +1,206 @@
+//! Lock state persistence for crash recovery.
+//!
+//! Locks are toggled on and stay on until toggled again, so losing them when
+//! the daemon restarts silently changes how the keyboard behaves. The daemon
+//! saves the active lock IDs to a small JSON file on shutdown and restores
+//! them on startup. Momentary modifiers are never persisted: they describe
+//! keys that are physically held down, which cannot survive a restart.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use keyrx_core::runtime::DeviceState;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::DaemonError;
+
+/// File name of the lock state file inside the runtime directory
+const LOCK_STATE_FILE_NAME: &str = "lock_state.json";
+
+/// On-disk representation of the persisted lock state.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct PersistedLocks {
+    /// Active lock IDs in ascending order
+    locks: Vec<u8>,
+}
+
+/// Reads and writes the lock state file.
+#[derive(Debug, Clone)]
+pub struct LockStateFile {
+    path: PathBuf,
+}
+
+impl LockStateFile {
+    /// Creates a handle for the lock state file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the default lock state path.
+    ///
+    /// Uses the per-user runtime directory (`$XDG_RUNTIME_DIR/keyrx` on
+    /// Linux) so the state is private to the user and cleared on logout or
+    /// reboot. Returns `None` where there is no runtime directory: a shared
+    /// location such as the temp directory could be read or planted by other
+    /// users.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::runtime_dir().map(|dir| dir.join("keyrx").join(LOCK_STATE_FILE_NAME))
+    }
+
+    /// Returns the path of the lock state file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Restores the persisted locks into `state`.
+    ///
+    /// A missing file is not an error and leaves `state` untouched.
+    ///
+    /// # Returns
+    ///
+    /// The number of locks that were restored.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DaemonError::RuntimeError` if the file exists but cannot be
+    /// read or parsed.
+    pub fn load_into(&self, state: &mut DeviceState) -> Result<usize, DaemonError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(self.error("read", e)),
+        };
+
+        let persisted: PersistedLocks =
+            serde_json::from_str(&contents).map_err(|e| self.error("parse", e))?;
+
+        let restored = state.restore_locks(&persisted.locks);
+        debug!("Restored {} lock(s) from {}", restored, self.path.display());
+        Ok(restored)
+    }
+
+    /// Saves the active locks of `state`, replacing any previous file.
+    ///
+    /// The file is written to a temporary sibling and renamed into place so a
+    /// crash mid-write never leaves a truncated state file behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DaemonError::RuntimeError` if the file cannot be written.
+    pub fn save(&self, state: &DeviceState) -> Result<(), DaemonError> {
+        let persisted = PersistedLocks {
+            locks: state.active_locks(),
+        };
+        let json = serde_json::to_string(&persisted).map_err(|e| self.error("serialize", e))?;
+
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(|e| self.error("write", e))?;
+            }
+        }
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, json).map_err(|e| self.error("write", e))?;
+        fs::rename(&tmp_path, &self.path).map_err(|e| self.error("write", e))?;
+
+        debug!(
+            "Saved {} lock(s) to {}",
+            persisted.locks.len(),
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    fn error(&self, action: &str, err: impl std::fmt::Display) -> DaemonError {
+        DaemonError::RuntimeError(format!(
+            "failed to {} lock state file {}: {}",
+            action,
+            self.path.display(),
+            err
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip_preserves_locks() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = LockStateFile::new(temp_dir.path().join("run").join("lock_state.json"));
+
+        let mut state = DeviceState::new();
+        state.toggle_lock(0);
+        state.toggle_lock(3);
+        state.toggle_lock(42);
+        state.toggle_lock(254);
+        file.save(&state).unwrap();
+
+        let mut restored = DeviceState::new();
+        assert_eq!(file.load_into(&mut restored).unwrap(), 4);
+        assert_eq!(restored.active_locks(), vec![0, 3, 42, 254]);
+    }
+
+    #[test]
+    fn test_modifiers_are_not_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = LockStateFile::new(temp_dir.path().join("lock_state.json"));
+
+        let mut state = DeviceState::new();
+        state.toggle_lock(1);
+        state.set_modifier(1);
+        state.set_modifier(7);
+        file.save(&state).unwrap();
+
+        let persisted: PersistedLocks =
+            serde_json::from_str(&fs::read_to_string(file.path()).unwrap()).unwrap();
+        assert_eq!(persisted, PersistedLocks { locks: vec![1] });
+
+        let mut restored = DeviceState::new();
+        file.load_into(&mut restored).unwrap();
+        assert!(restored.is_lock_active(1));
+        assert!(!restored.is_modifier_active(1));
+        assert!(!restored.is_modifier_active(7));
+    }
+
+    #[test]
+    fn test_save_clears_locks_toggled_off() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = LockStateFile::new(temp_dir.path().join("lock_state.json"));
+
+        let mut state = DeviceState::new();
+        state.toggle_lock(2);
+        file.save(&state).unwrap();
+        state.toggle_lock(2);
+        file.save(&state).unwrap();
+
+        let mut restored = DeviceState::new();
+        assert_eq!(file.load_into(&mut restored).unwrap(), 0);
+        assert!(restored.active_locks().is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_file_is_noop() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = LockStateFile::new(temp_dir.path().join("missing.json"));
+
+        let mut state = DeviceState::new();
+        assert_eq!(file.load_into(&mut state).unwrap(), 0);
+        assert!(state.active_locks().is_empty());
+    }
+
+    #[test]
+    fn test_load_corrupt_file_is_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("lock_state.json");
+        fs::write(&path, "{not json").unwrap();
+
+        let mut state = DeviceState::new();
+        let result = LockStateFile::new(&path).load_into(&mut state);
+        assert!(matches!(result, Err(DaemonError::RuntimeError(_))));
+    }
+}
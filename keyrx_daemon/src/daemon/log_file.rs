@@ -0,0 +1,186 @@
+//! Size-capped log file for `run --log-file`.
+//!
+//! `env_logger` only writes to one target, so the daemon installs a
+//! [`TeeWriter`] that copies every record to stderr and to a
+//! [`RotatingFile`]. When the file would grow past its size cap it is renamed
+//! to `<path>.1` (shifting older backups up) and a fresh file is started, so
+//! disk usage stays bounded no matter how long the daemon runs.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Size at which the log file is rotated (5 MB).
+pub const DEFAULT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept next to the active one, for two files in total.
+pub const DEFAULT_BACKUPS: usize = 1;
+
+/// Append-only file that rotates once it reaches a size cap.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    backups: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    /// Opens `path` for appending, creating it and its parent directory if
+    /// needed.
+    ///
+    /// The file rotates before a write would take it past `max_bytes`;
+    /// `backups` rotated files are kept as `<path>.1` (newest) through
+    /// `<path>.<backups>`. With no backups the file is simply truncated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be created.
+    pub fn open(path: &Path, max_bytes: u64, backups: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            backups,
+            file,
+            size,
+        })
+    }
+
+    /// Path of the `index`th rotated file, e.g. `daemon.log.1`.
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.backups > 0 {
+            // The oldest backup is overwritten by the rename below it
+            for index in (1..self.backups).rev() {
+                let from = self.backup_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.backup_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.backup_path(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A record larger than the cap still goes into a file of its own
+        if self.size > 0 && self.size.saturating_add(buf.len() as u64) > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writer that copies log output to stderr and a [`RotatingFile`].
+#[derive(Debug)]
+pub struct TeeWriter {
+    file: RotatingFile,
+}
+
+impl TeeWriter {
+    /// Creates a writer teeing stderr into `file`.
+    pub fn new(file: RotatingFile) -> Self {
+        Self { file }
+    }
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_appends_to_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("daemon.log");
+        fs::write(&path, "old\n").unwrap();
+
+        let mut file = RotatingFile::open(&path, 1024, 1).unwrap();
+        file.write_all(b"new\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\nnew\n");
+    }
+
+    #[test]
+    fn test_rotates_at_size_cap_and_keeps_backups() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("logs").join("daemon.log");
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("logs/daemon.log.1")).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("logs/daemon.log.2")).unwrap(),
+            "second\n"
+        );
+        // Only `backups` rotated files are kept
+        assert!(!dir.path().join("logs/daemon.log.3").exists());
+    }
+
+    #[test]
+    fn test_oversized_record_gets_its_own_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("daemon.log");
+
+        let mut file = RotatingFile::open(&path, 4, 1).unwrap();
+        file.write_all(b"a record longer than the cap\n").unwrap();
+        file.write_all(b"x\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "x\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("daemon.log.1")).unwrap(),
+            "a record longer than the cap\n"
+        );
+    }
+}
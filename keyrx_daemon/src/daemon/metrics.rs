@@ -7,15 +7,23 @@
 //! - Lock-free recording using atomic operations (no mutexes on hot path)
 //! - Ring buffer for recent samples (cache-friendly, bounded memory)
 //! - Heavy percentile computation done off hot path (every 1 second)
+//! - Per-device samples in separate ring buffers, found under a read lock
+//!   that is only contended when a new device appears
 
 use hdrhistogram::Histogram;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Size of the ring buffer for recent latency samples.
 /// 1024 samples covers ~10 seconds at 100 keys/sec typing speed.
 const SAMPLE_BUFFER_SIZE: usize = 1024;
 
+/// Maximum number of devices with their own ring buffer.
+/// Samples from further devices only count towards the global statistics.
+const MAX_TRACKED_DEVICES: usize = 32;
+
 /// Lock-free latency recorder for the hot path.
 ///
 /// Uses atomic operations to avoid mutex contention during event processing.
@@ -24,7 +32,7 @@ const SAMPLE_BUFFER_SIZE: usize = 1024;
 /// # Performance
 ///
 /// - `record()`: O(1), ~10-50ns (atomic operations only)
-/// - Memory: 8KB fixed (1024 × 8-byte atomics)
+/// - Memory: 8KB fixed (1024 × 8-byte atomics), plus 8KB per tracked device
 pub struct LatencyRecorder {
     /// Ring buffer for recent latency samples (microseconds).
     samples: [AtomicU64; SAMPLE_BUFFER_SIZE],
@@ -34,6 +42,8 @@ pub struct LatencyRecorder {
     total_samples: AtomicU64,
     /// Samples recorded since last snapshot.
     samples_since_snapshot: AtomicU64,
+    /// Per-device recorders, keyed by the device id attached to events.
+    devices: RwLock<HashMap<String, Box<LatencyRecorder>>>,
 }
 
 impl LatencyRecorder {
@@ -45,6 +55,7 @@ impl LatencyRecorder {
             write_index: AtomicU64::new(0),
             total_samples: AtomicU64::new(0),
             samples_since_snapshot: AtomicU64::new(0),
+            devices: RwLock::new(HashMap::new()),
         }
     }
 
@@ -65,6 +76,57 @@ impl LatencyRecorder {
         self.samples_since_snapshot.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Records a latency sample globally and for the device it came from.
+    ///
+    /// Events without a device id only count globally. The first sample of
+    /// a device takes a write lock to add its ring buffer; later samples
+    /// only take the read lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `device_id` - Id of the device that produced the event, if known
+    /// * `latency_us` - Processing latency in microseconds
+    pub fn record_for_device(&self, device_id: Option<&str>, latency_us: u64) {
+        self.record(latency_us);
+        let Some(device_id) = device_id else {
+            return;
+        };
+
+        {
+            let devices = self.devices.read().unwrap_or_else(|e| e.into_inner());
+            if let Some(recorder) = devices.get(device_id) {
+                recorder.record(latency_us);
+                return;
+            }
+        }
+
+        let mut devices = self.devices.write().unwrap_or_else(|e| e.into_inner());
+        if devices.len() >= MAX_TRACKED_DEVICES && !devices.contains_key(device_id) {
+            return;
+        }
+        devices
+            .entry(device_id.to_string())
+            .or_insert_with(|| Box::new(LatencyRecorder::new()))
+            .record(latency_us);
+    }
+
+    /// Computes statistics over each device's recent samples.
+    ///
+    /// Unlike [`MetricsAggregator::compute_snapshot`], each snapshot covers
+    /// only the samples still in the device's ring buffer.
+    pub fn device_snapshots(&self) -> BTreeMap<String, LatencySnapshot> {
+        let devices = self.devices.read().unwrap_or_else(|e| e.into_inner());
+        devices
+            .iter()
+            .map(|(device_id, recorder)| {
+                (
+                    device_id.clone(),
+                    LatencySnapshot::from_samples(&recorder.collect_samples()),
+                )
+            })
+            .collect()
+    }
+
     /// Returns the total number of samples recorded.
     pub fn total_samples(&self) -> u64 {
         self.total_samples.load(Ordering::Relaxed)
@@ -133,6 +195,30 @@ impl LatencySnapshot {
             timestamp_us: current_timestamp_us(),
         }
     }
+
+    /// Computes a snapshot over `samples` (microseconds).
+    fn from_samples(samples: &[u64]) -> Self {
+        let Ok(mut histogram) = Histogram::<u64>::new_with_bounds(1, 1_000_000, 3) else {
+            return Self::empty();
+        };
+        for &sample in samples {
+            let _ = histogram.record(sample.clamp(1, 1_000_000));
+        }
+        if histogram.is_empty() {
+            return Self::empty();
+        }
+
+        Self {
+            min_us: histogram.min(),
+            avg_us: histogram.mean() as u64,
+            max_us: histogram.max(),
+            p50_us: histogram.value_at_percentile(50.0),
+            p95_us: histogram.value_at_percentile(95.0),
+            p99_us: histogram.value_at_percentile(99.0),
+            sample_count: histogram.len(),
+            timestamp_us: current_timestamp_us(),
+        }
+    }
 }
 
 /// Metrics aggregator that computes statistics from recorded samples.
@@ -268,6 +354,37 @@ mod tests {
         assert!(!samples.is_empty());
     }
 
+    #[test]
+    fn test_record_for_device() {
+        let recorder = LatencyRecorder::new();
+
+        recorder.record_for_device(Some("kbd-a"), 100);
+        recorder.record_for_device(Some("kbd-a"), 300);
+        recorder.record_for_device(Some("kbd-b"), 900);
+        recorder.record_for_device(None, 50);
+
+        assert_eq!(recorder.total_samples(), 4);
+
+        let devices = recorder.device_snapshots();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices["kbd-a"].sample_count, 2);
+        assert!(devices["kbd-a"].max_us <= 300);
+        assert_eq!(devices["kbd-b"].sample_count, 1);
+        assert!(devices["kbd-b"].min_us >= 900);
+    }
+
+    #[test]
+    fn test_record_for_device_caps_tracked_devices() {
+        let recorder = LatencyRecorder::new();
+
+        for i in 0..MAX_TRACKED_DEVICES + 5 {
+            recorder.record_for_device(Some(&format!("kbd-{}", i)), 100);
+        }
+
+        assert_eq!(recorder.device_snapshots().len(), MAX_TRACKED_DEVICES);
+        assert_eq!(recorder.total_samples(), (MAX_TRACKED_DEVICES + 5) as u64);
+    }
+
     #[test]
     fn test_metrics_aggregator_new() {
         let aggregator = MetricsAggregator::new(Duration::from_secs(60));
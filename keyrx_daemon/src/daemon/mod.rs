@@ -17,10 +17,11 @@
 //!
 //! # Daemon Lifecycle
 //!
-//! 1. **Initialization**: Load configuration, discover devices, create uinput output
+//! 1. **Initialization**: Load configuration, discover devices, create uinput output,
+//!    restore locks saved by the previous run
 //! 2. **Signal Setup**: Install handlers for SIGTERM, SIGINT, SIGHUP
 //! 3. **Event Loop**: Process keyboard events from all managed devices
-//! 4. **Shutdown**: Release devices, destroy virtual output, exit cleanly
+//! 4. **Shutdown**: Save active locks, release devices, destroy virtual output, exit cleanly
 //!
 //! # Example
 //!
@@ -42,34 +43,43 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
-use keyrx_core::config::DeviceConfig;
+use keyrx_core::config::mappings::ArchivedDeviceConfig;
 use log::{info, warn};
 
-use crate::config_loader::load_config;
+use crate::config_loader::{is_source_config, load_config, LoadedConfig};
 use crate::error::ConfigError;
-use crate::platform::{Platform, PlatformError};
-
-use state::convert_archived_device_config;
+use crate::platform::{EventCounters, Platform, PlatformError};
 
 // Submodules
+pub mod clock;
 pub mod event_broadcaster;
 pub mod event_loop;
+pub mod lock_state;
+pub mod log_file;
 pub mod metrics;
+pub mod pending_injections;
+pub mod pidfile;
 pub mod remapping_state;
 pub mod signals;
 pub mod state;
 
 // Re-exports for public API
+pub use clock::{SharedClock, WallClock};
 pub use event_broadcaster::{start_latency_broadcast_task, EventBroadcaster};
 pub use event_loop::process_one_event;
+pub use lock_state::LockStateFile;
+pub use log_file::{RotatingFile, TeeWriter};
 pub use metrics::{LatencyRecorder, LatencySnapshot, MetricsAggregator};
+pub use pending_injections::PendingInjections;
+pub use pidfile::PidFile;
 pub use remapping_state::RemappingState;
 pub use signals::{install_signal_handlers, SignalHandler};
-pub use state::ReloadState;
+pub use state::{ReloadHandle, ReloadRequest, ReloadState};
 
 /// Returns the current time in microseconds since UNIX epoch.
 ///
@@ -104,6 +114,10 @@ pub enum DaemonError {
     /// Runtime error during event processing.
     #[error("runtime error: {0}")]
     RuntimeError(String),
+
+    /// Another daemon instance holds the PID file.
+    #[error("another keyrx daemon is already running (PID {pid}, pidfile {})", pidfile.display())]
+    AlreadyRunning { pid: u32, pidfile: PathBuf },
 }
 
 /// Exit codes for daemon termination.
@@ -190,11 +204,36 @@ pub struct Daemon {
     /// ensures no mutex contention on the hot path.
     latency_recorder: Arc<LatencyRecorder>,
 
+    /// Lock-free counters for failed and dropped events.
+    ///
+    /// Shared with the platform, which counts capture errors and
+    /// disconnects, while the event loop counts injection failures.
+    event_counters: Arc<EventCounters>,
+
     /// Remapping state for key remapping (KeyLookup + DeviceState).
     ///
     /// This is `Some` when a profile is active and remapping is enabled.
     /// It is `None` in pass-through mode (no active profile).
     remapping_state: Option<RemappingState>,
+
+    /// Full configuration behind `remapping_state`, shared with IPC.
+    ///
+    /// Only replaced when a (re)load succeeds, so it always describes what
+    /// is actually remapping; `None` in pass-through mode.
+    loaded_config: LoadedConfig,
+
+    /// Lock state file restored on startup and written on shutdown, if
+    /// persistence is enabled.
+    lock_state: Option<LockStateFile>,
+
+    /// Time source for the event loop ([`WallClock`] unless replaced).
+    clock: SharedClock,
+
+    /// Handle given out to request reloads from a specific file.
+    reload_handle: ReloadHandle,
+
+    /// Queue of reload requests sent through `reload_handle`.
+    reload_requests: mpsc::Receiver<ReloadRequest>,
 }
 
 impl Daemon {
@@ -203,17 +242,22 @@ impl Daemon {
     /// This method performs the initialization sequence:
     ///
     /// 1. Accepts a platform implementation via dependency injection
-    /// 2. Initializes the platform
-    /// 3. Installs signal handlers for graceful shutdown and reload
+    /// 2. Loads the active profile (or compiles `config_path` if it is a `.rhai`
+    ///    source) and declares its output keys to the platform
+    /// 3. Initializes the platform
+    /// 4. Installs signal handlers for graceful shutdown and reload
     ///
     /// # Arguments
     ///
     /// * `platform` - Platform implementation for input/output operations
-    /// * `config_path` - Path to the .krx configuration file (for reload support)
+    /// * `config_path` - Path to the .krx configuration file (for reload support).
+    ///   A `.rhai` path is compiled in memory and used instead of the active
+    ///   profile.
     ///
     /// # Returns
     ///
     /// * `Ok(Daemon)` - Successfully initialized daemon
+    /// * `Err(DaemonError::Config)` - A `.rhai` `config_path` failed to compile
     /// * `Err(DaemonError::Platform)` - Platform initialization failed
     /// * `Err(DaemonError::SignalError)` - Failed to install signal handlers
     ///
@@ -235,7 +279,37 @@ impl Daemon {
     /// }
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn new(mut platform: Box<dyn Platform>, config_path: &Path) -> Result<Self, DaemonError> {
+    pub fn new(platform: Box<dyn Platform>, config_path: &Path) -> Result<Self, DaemonError> {
+        Self::init(platform, config_path, None)
+    }
+
+    /// Creates a new daemon instance that persists lock state at `lock_state_path`.
+    ///
+    /// Behaves like [`Daemon::new`], which does not persist locks. Locks
+    /// saved by a previous run are restored into the remapping state when a
+    /// profile is active; a missing or unreadable state file starts with all
+    /// locks off. Active locks are saved back on shutdown.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Daemon::new`].
+    pub fn with_lock_state_path(
+        platform: Box<dyn Platform>,
+        config_path: &Path,
+        lock_state_path: PathBuf,
+    ) -> Result<Self, DaemonError> {
+        Self::init(
+            platform,
+            config_path,
+            Some(LockStateFile::new(lock_state_path)),
+        )
+    }
+
+    fn init(
+        mut platform: Box<dyn Platform>,
+        config_path: &Path,
+        lock_state: Option<LockStateFile>,
+    ) -> Result<Self, DaemonError> {
         info!(
             "Initializing keyrx daemon with config: {}",
             config_path.display()
@@ -246,25 +320,23 @@ impl Daemon {
             .unwrap_or_else(|| PathBuf::from("."))
             .join("keyrx");
 
-        // Step 1: Initialize the platform
-        info!("Initializing platform...");
-        platform.initialize()?;
-        info!("Platform initialized");
-
-        // Step 2: Install signal handlers
-        info!("Installing signal handlers...");
-        let running = Arc::new(AtomicBool::new(true));
-        let signal_handler = install_signal_handlers(Arc::clone(&running))?;
-        info!("Signal handlers installed");
-
-        // Create lock-free latency recorder for metrics collection
-        let latency_recorder = Arc::new(LatencyRecorder::new());
-
-        // Step 3: Load active profile and create remapping state (if any)
-        let remapping_state = match Self::load_active_profile_config(&config_dir) {
+        // Step 1: Load active profile and create remapping state (if any)
+        // A broken .rhai source aborts startup so the compile error is seen;
+        // a broken active profile falls back to pass-through as before.
+        let loaded_config = LoadedConfig::new();
+        let loaded = if is_source_config(config_path) {
+            info!("Compiling Rhai source in memory (use a .krx for faster startup)");
+            Ok(Self::load_config_file(config_path, &loaded_config)?)
+        } else {
+            Self::load_active_profile_config(&config_dir, &loaded_config)
+        };
+        let mut remapping_state = match loaded {
             Ok(Some(device_config)) => {
                 info!("Loaded active profile, creating remapping state");
-                Some(RemappingState::new(&device_config))
+                let remapping = RemappingState::from_archived(device_config);
+                // Advertise the profile's output keys on the virtual device
+                platform.set_output_keys(&remapping.lookup().output_keys());
+                Some(remapping)
             }
             Ok(None) => {
                 info!("No active profile found, running in pass-through mode");
@@ -279,8 +351,38 @@ impl Daemon {
             }
         };
 
+        // Step 2: Initialize the platform
+        let event_counters = Arc::new(EventCounters::new());
+        platform.set_event_counters(Arc::clone(&event_counters));
+        info!("Initializing platform...");
+        platform.initialize()?;
+        info!("Platform initialized");
+
+        // Step 3: Install signal handlers
+        info!("Installing signal handlers...");
+        let running = Arc::new(AtomicBool::new(true));
+        let signal_handler = install_signal_handlers(Arc::clone(&running))?;
+        info!("Signal handlers installed");
+
+        // Create lock-free latency recorder for metrics collection
+        let latency_recorder = Arc::new(LatencyRecorder::new());
+
+        // Step 4: Restore locks left active by the previous run
+        if let (Some(lock_state), Some(remapping)) = (&lock_state, remapping_state.as_mut()) {
+            match lock_state.load_into(remapping.state_mut()) {
+                Ok(0) => {}
+                Ok(count) => info!(
+                    "Restored {} lock(s) from {}",
+                    count,
+                    lock_state.path().display()
+                ),
+                Err(e) => warn!("{}. Starting with all locks off", e),
+            }
+        }
+
         info!("Daemon initialization complete");
 
+        let (reload_handle, reload_requests) = ReloadHandle::channel();
         Ok(Self {
             config_path: config_path.to_path_buf(),
             config_dir,
@@ -289,7 +391,13 @@ impl Daemon {
             signal_handler,
             event_broadcaster: None,
             latency_recorder,
+            event_counters,
             remapping_state,
+            loaded_config,
+            lock_state,
+            clock: Arc::new(WallClock),
+            reload_handle,
+            reload_requests,
         })
     }
 
@@ -301,11 +409,68 @@ impl Daemon {
         self.event_broadcaster = Some(broadcaster);
     }
 
+    /// Replaces the event loop's time source.
+    ///
+    /// Tap-hold timeouts and broadcast timestamps follow this clock, so
+    /// integration tests can inject a
+    /// [`VirtualClock`](keyrx_core::runtime::VirtualClock) and advance time
+    /// instead of sleeping. Events must then carry timestamps on the same
+    /// clock.
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Loads the DeviceConfig to remap with.
+    ///
+    /// A `.rhai` `config_path` is recompiled from source; otherwise the active
+    /// profile is loaded. The DeviceConfig is returned archived, backed by the
+    /// loaded file, so the remapping state can be built without an owned copy.
+    ///
+    /// The archived configuration is recorded in `loaded_config` whenever a
+    /// DeviceConfig is returned.
+    fn load_device_config(
+        config_dir: &Path,
+        config_path: &Path,
+        loaded_config: &LoadedConfig,
+    ) -> Result<Option<&'static ArchivedDeviceConfig>, DaemonError> {
+        if is_source_config(config_path) {
+            Self::load_config_file(config_path, loaded_config)
+        } else {
+            Self::load_active_profile_config(config_dir, loaded_config)
+        }
+    }
+
+    /// Loads a `.krx` file, or compiles a `.rhai` source in memory, and
+    /// returns its first DeviceConfig.
+    ///
+    /// Returns `Ok(None)` if the file defines no device blocks.
+    fn load_config_file(
+        config_path: &Path,
+        loaded_config: &LoadedConfig,
+    ) -> Result<Option<&'static ArchivedDeviceConfig>, DaemonError> {
+        let archived_config = load_config(config_path)?;
+        let Some(device_config) = archived_config.devices.first() else {
+            warn!("{} has no device configurations", config_path.display());
+            return Ok(None);
+        };
+
+        loaded_config.set_archived(archived_config);
+        info!(
+            "Loaded {} key mappings from {}",
+            device_config.mappings.len(),
+            config_path.display()
+        );
+        Ok(Some(device_config))
+    }
+
     /// Loads the active profile's DeviceConfig from the .krx file.
     ///
     /// Returns `Ok(Some(config))` if an active profile exists and was loaded successfully,
     /// `Ok(None)` if no active profile is set, or `Err` on load failure.
-    fn load_active_profile_config(config_dir: &Path) -> Result<Option<DeviceConfig>, DaemonError> {
+    fn load_active_profile_config(
+        config_dir: &Path,
+        loaded_config: &LoadedConfig,
+    ) -> Result<Option<&'static ArchivedDeviceConfig>, DaemonError> {
         // Read the .active file to get the active profile name
         let active_file = config_dir.join(".active");
         if !active_file.exists() {
@@ -349,8 +514,9 @@ impl Daemon {
             return Ok(None);
         }
 
-        // Convert the first (global) device config to owned type
-        let device_config = convert_archived_device_config(&archived_config.devices[0]);
+        // Remap with the first (global) device config, built from the archive
+        let device_config = &archived_config.devices[0];
+        loaded_config.set_archived(archived_config);
         info!(
             "Loaded {} key mappings from profile '{}'",
             device_config.mappings.len(),
@@ -360,17 +526,25 @@ impl Daemon {
         Ok(Some(device_config))
     }
 
-    /// Returns the number of managed devices.
+    /// Returns the number of matched devices.
+    ///
+    /// Includes devices the platform failed to grab or has released; see
+    /// [`active_device_count`](Self::active_device_count) for the devices
+    /// actually being remapped.
     #[must_use]
     pub fn device_count(&self) -> usize {
-        // Platform trait doesn't expose device count directly
-        // Return the number of devices from list_devices()
         self.platform
             .list_devices()
             .map(|devices| devices.len())
             .unwrap_or(0)
     }
 
+    /// Returns the number of devices whose input is actually captured.
+    #[must_use]
+    pub fn active_device_count(&self) -> usize {
+        self.platform.active_device_count()
+    }
+
     /// Returns whether the daemon is still running.
     ///
     /// This is set to `false` when a shutdown signal (SIGTERM, SIGINT) is received.
@@ -406,11 +580,47 @@ impl Daemon {
         Arc::clone(&self.latency_recorder)
     }
 
+    /// Returns a clone of the failed/dropped event counters Arc.
+    ///
+    /// This is used to serve the counters over IPC. Like the latency
+    /// recorder, the counters are lock-free.
+    #[must_use]
+    pub fn event_counters(&self) -> Arc<EventCounters> {
+        Arc::clone(&self.event_counters)
+    }
+
+    /// Returns a handle that asks this daemon to reload from a given file.
+    ///
+    /// This is used to serve `ReloadConfig` over IPC. Requests are applied
+    /// by the event loop ([`run`](Self::run)) or by
+    /// [`process_reload_requests`](Self::process_reload_requests).
+    #[must_use]
+    pub fn reload_handle(&self) -> ReloadHandle {
+        self.reload_handle.clone()
+    }
+
+    /// Returns a handle to the configuration currently remapping.
+    ///
+    /// This is used to serve `GetLoadedConfig` over IPC. The handle follows
+    /// later reloads.
+    #[must_use]
+    pub fn loaded_config(&self) -> LoadedConfig {
+        self.loaded_config.clone()
+    }
+
     /// Reloads the configuration from disk.
     ///
-    /// This method reads the active profile from the `.active` file and
-    /// rebuilds the remapping state. Called when SIGHUP is received or
-    /// when profile activation triggers a reload.
+    /// This method reads the active profile from the `.active` file (or
+    /// recompiles the `.rhai` config path) and rebuilds the remapping state.
+    /// Called when SIGHUP is received or when profile activation triggers a
+    /// reload.
+    ///
+    /// The virtual output device is not recreated, so keys that only the new
+    /// profile emits are not advertised until the daemon restarts.
+    ///
+    /// Output keys still held when the new configuration loads are released
+    /// first: the new state has no record of the mappings that pressed them,
+    /// so they would otherwise stay stuck.
     ///
     /// # Example
     ///
@@ -429,34 +639,42 @@ impl Daemon {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn reload(&mut self) -> Result<(), DaemonError> {
-        info!("Reloading configuration from active profile...");
+        info!("Reloading configuration...");
+        let loaded =
+            Self::load_device_config(&self.config_dir, &self.config_path, &self.loaded_config);
+        apply_reload(
+            &mut self.platform,
+            &mut self.remapping_state,
+            &self.loaded_config,
+            loaded,
+        )
+    }
 
-        match Self::load_active_profile_config(&self.config_dir) {
-            Ok(Some(device_config)) => {
-                let mapping_count = device_config.mappings.len();
-                if let Some(ref mut state) = self.remapping_state {
-                    // Update existing state
-                    state.reload(&device_config);
-                    info!("Remapping state reloaded with {} mappings", mapping_count);
-                } else {
-                    // Create new state
-                    self.remapping_state = Some(RemappingState::new(&device_config));
-                    info!(
-                        "Created new remapping state with {} mappings",
-                        mapping_count
-                    );
-                }
-                Ok(())
-            }
-            Ok(None) => {
-                info!("No active profile found, switching to pass-through mode");
-                self.remapping_state = None;
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Failed to reload configuration: {}", e);
-                Err(e)
-            }
+    /// Reloads the configuration from the `.krx` (or `.rhai`) file at `path`.
+    ///
+    /// Like [`reload`](Self::reload), held output keys are released before
+    /// the new configuration takes over. On failure the current
+    /// configuration keeps remapping.
+    pub fn reload_from(&mut self, path: &Path) -> Result<(), DaemonError> {
+        info!("Reloading configuration from {}...", path.display());
+        let loaded = Self::load_config_file(path, &self.loaded_config);
+        apply_reload(
+            &mut self.platform,
+            &mut self.remapping_state,
+            &self.loaded_config,
+            loaded,
+        )
+    }
+
+    /// Applies the reloads requested through [`reload_handle`](Self::reload_handle)
+    /// and answers them.
+    ///
+    /// [`run`](Self::run) does this on every pass; call it when driving the
+    /// daemon with [`process_one_event`](Self::process_one_event) instead.
+    pub fn process_reload_requests(&mut self) {
+        while let Ok(request) = self.reload_requests.try_recv() {
+            let result = self.reload_from(request.path());
+            request.reply(result);
         }
     }
 
@@ -480,7 +698,11 @@ impl Daemon {
     /// # Signal Handling
     ///
     /// - **SIGTERM/SIGINT**: Sets the running flag to false, causing graceful exit
-    /// - **SIGHUP**: Triggers configuration reload (logs but requires restart for full effect)
+    /// - **SIGHUP**: Reloads the configuration, like [`reload`](Self::reload)
+    ///
+    /// Requests sent through [`reload_handle`](Self::reload_handle) are
+    /// applied between events and answered once the new configuration is in
+    /// place.
     ///
     /// # Errors
     ///
@@ -520,36 +742,33 @@ impl Daemon {
             self.event_broadcaster.as_ref(),
             self.remapping_state.as_mut(),
             Some(&self.latency_recorder),
+            Some(&self.event_counters),
+            self.clock.as_ref(),
         )
     }
 
     pub fn run(&mut self) -> Result<(), DaemonError> {
-        // Clone config_dir for the reload closure (avoids borrowing self)
+        // Clone what the reload closure needs (avoids borrowing self)
         let config_dir = self.config_dir.clone();
-
-        // Create reload callback that reloads from active profile
-        // Note: Due to borrow constraints, this callback cannot directly update
-        // self.remapping_state. For now, it loads the new config and logs.
-        // Full hot-reload would require Arc<RwLock> for the remapping state.
-        let reload_fn = move || -> Result<(), DaemonError> {
-            info!("Reload signal received, reloading configuration...");
-            match Daemon::load_active_profile_config(&config_dir) {
-                Ok(Some(device_config)) => {
-                    info!(
-                        "Loaded active profile with {} mappings. Note: Full hot-reload requires daemon restart.",
-                        device_config.mappings.len()
-                    );
-                    Ok(())
+        let config_path = self.config_path.clone();
+        let loaded_config = self.loaded_config.clone();
+
+        // Reload from the requested file, or like `reload` on SIGHUP
+        let reload_fn = move |path: Option<&Path>,
+                              platform: &mut Box<dyn Platform>,
+                              remapping_state: &mut Option<RemappingState>|
+              -> Result<(), DaemonError> {
+            let loaded = match path {
+                Some(path) => {
+                    info!("Reloading configuration from {}...", path.display());
+                    Daemon::load_config_file(path, &loaded_config)
                 }
-                Ok(None) => {
-                    info!("No active profile found after reload signal");
-                    Ok(())
+                None => {
+                    info!("Reloading configuration...");
+                    Daemon::load_device_config(&config_dir, &config_path, &loaded_config)
                 }
-                Err(e) => {
-                    warn!("Failed to reload configuration: {}", e);
-                    Err(e)
-                }
-            }
+            };
+            apply_reload(platform, remapping_state, &loaded_config, loaded)
         };
 
         event_loop::run_event_loop(
@@ -557,15 +776,27 @@ impl Daemon {
             Arc::clone(&self.running),
             &self.signal_handler,
             reload_fn,
+            Some(&self.reload_requests),
             self.event_broadcaster.as_ref(),
-            self.remapping_state.as_mut(),
+            &mut self.remapping_state,
             Some(&self.latency_recorder),
+            Some(&self.event_counters),
+            self.clock.as_ref(),
         )
     }
 
+    /// Injects a release for every output key the remapping state holds
+    /// pressed, and forgets them.
+    fn release_held_outputs(&mut self) {
+        release_held_outputs(&mut self.platform, &mut self.remapping_state);
+    }
+
     /// Performs graceful shutdown of the daemon.
     ///
-    /// This method shuts down the platform and releases all resources.
+    /// This method releases every output key the daemon still holds pressed
+    /// (so applications do not see stuck keys), saves the active locks to
+    /// the lock state file, shuts down the platform, and releases all
+    /// resources.
     ///
     /// # Error Handling
     ///
@@ -597,6 +828,16 @@ impl Daemon {
     pub fn shutdown(&mut self) {
         info!("Initiating graceful shutdown...");
 
+        // Release held output keys while the virtual device still exists
+        self.release_held_outputs();
+
+        // Persist active locks so a restart does not silently reset them
+        if let (Some(lock_state), Some(remapping)) = (&self.lock_state, &self.remapping_state) {
+            if let Err(e) = lock_state.save(remapping.state()) {
+                warn!("{}", e);
+            }
+        }
+
         // Shutdown the platform
         info!("Shutting down platform...");
         match self.platform.shutdown() {
@@ -616,6 +857,74 @@ impl Daemon {
     }
 }
 
+/// Swaps in the outcome of a (re)load.
+///
+/// A loaded DeviceConfig replaces the remapping state, and `Ok(None)`
+/// switches to pass-through mode; either way held output keys are released
+/// first. On error the current state is kept.
+fn apply_reload(
+    platform: &mut Box<dyn Platform>,
+    remapping_state: &mut Option<RemappingState>,
+    loaded_config: &LoadedConfig,
+    loaded: Result<Option<&'static ArchivedDeviceConfig>, DaemonError>,
+) -> Result<(), DaemonError> {
+    match loaded {
+        Ok(Some(device_config)) => {
+            let mapping_count = device_config.mappings.len();
+            release_held_outputs(platform, remapping_state);
+            if let Some(state) = remapping_state {
+                // Update existing state
+                state.reload_archived(device_config);
+                info!("Remapping state reloaded with {} mappings", mapping_count);
+            } else {
+                // Create new state
+                *remapping_state = Some(RemappingState::from_archived(device_config));
+                info!(
+                    "Created new remapping state with {} mappings",
+                    mapping_count
+                );
+            }
+            Ok(())
+        }
+        Ok(None) => {
+            info!("No active profile found, switching to pass-through mode");
+            release_held_outputs(platform, remapping_state);
+            *remapping_state = None;
+            loaded_config.set(None);
+            Ok(())
+        }
+        Err(e) => {
+            warn!("Failed to reload configuration: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Injects a release for every output key `remapping_state` holds pressed,
+/// and forgets them.
+///
+/// Failures are logged: the keys are dropped from the state either way.
+fn release_held_outputs(
+    platform: &mut Box<dyn Platform>,
+    remapping_state: &mut Option<RemappingState>,
+) {
+    let Some(remapping) = remapping_state else {
+        return;
+    };
+    let releases = remapping.take_held_releases();
+    if releases.is_empty() {
+        return;
+    }
+
+    info!("Releasing {} held output key(s)", releases.len());
+    if let Err(e) = platform
+        .inject_outputs(&releases)
+        .and_then(|()| platform.flush())
+    {
+        warn!("Failed to release held output keys: {}", e);
+    }
+}
+
 /// Drop implementation to ensure automatic cleanup on daemon exit.
 ///
 /// When a `Daemon` is dropped (goes out of scope, program exits, or panic occurs),
@@ -734,4 +1043,221 @@ mod tests {
             // TODO: Update to create platform and pass to Daemon::new(platform, path)
         }
     }
+
+    /// Platform that replays input events and records injected output.
+    struct RecordingPlatform {
+        input: std::collections::VecDeque<keyrx_core::runtime::event::KeyEvent>,
+        injected: Arc<std::sync::Mutex<Vec<keyrx_core::runtime::event::KeyEvent>>>,
+    }
+
+    impl Platform for RecordingPlatform {
+        fn initialize(&mut self) -> crate::platform::PlatformResult<()> {
+            Ok(())
+        }
+
+        fn capture_input(
+            &mut self,
+        ) -> crate::platform::PlatformResult<keyrx_core::runtime::event::KeyEvent> {
+            self.input
+                .pop_front()
+                .ok_or_else(|| PlatformError::DeviceNotFound("no more input".to_string()))
+        }
+
+        fn inject_output(
+            &mut self,
+            event: keyrx_core::runtime::event::KeyEvent,
+        ) -> crate::platform::PlatformResult<()> {
+            self.injected.lock().unwrap().push(event);
+            Ok(())
+        }
+
+        fn list_devices(
+            &self,
+        ) -> crate::platform::PlatformResult<Vec<crate::platform::DeviceInfo>> {
+            Ok(Vec::new())
+        }
+
+        fn shutdown(&mut self) -> crate::platform::PlatformResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_shutdown_releases_held_modified_output() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("config.rhai");
+        fs::write(
+            &config_path,
+            "device_start(\"*\");\n  map(\"VK_Num1\", with_shift(\"VK_Num1\"));\ndevice_end();\n",
+        )
+        .unwrap();
+
+        let injected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let platform = RecordingPlatform {
+            input: [KeyEvent::press(KeyCode::Num1)].into(),
+            injected: Arc::clone(&injected),
+        };
+        let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+
+        // Shift+1 is pressed and still held when the daemon shuts down
+        assert!(daemon.process_one_event().unwrap());
+        assert_eq!(
+            *injected.lock().unwrap(),
+            vec![
+                KeyEvent::press(KeyCode::LShift),
+                KeyEvent::press(KeyCode::Num1)
+            ]
+        );
+
+        daemon.shutdown();
+        assert_eq!(
+            injected.lock().unwrap()[2..],
+            [
+                KeyEvent::release(KeyCode::Num1),
+                KeyEvent::release(KeyCode::LShift)
+            ]
+        );
+
+        // Dropping the daemon runs shutdown again without releasing twice
+        drop(daemon);
+        assert_eq!(injected.lock().unwrap().len(), 4);
+    }
+
+    /// Starts a daemon on a `.rhai` config mapping 1 to Shift+1 and presses
+    /// the key, so Shift+1 is held on the output.
+    fn daemon_holding_shift_num1(
+        dir: &tempfile::TempDir,
+    ) -> (
+        Daemon,
+        Arc<std::sync::Mutex<Vec<keyrx_core::runtime::event::KeyEvent>>>,
+    ) {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let config_path = dir.path().join("config.rhai");
+        fs::write(
+            &config_path,
+            "device_start(\"*\");\n  map(\"VK_Num1\", with_shift(\"VK_Num1\"));\ndevice_end();\n",
+        )
+        .unwrap();
+
+        let injected = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let platform = RecordingPlatform {
+            input: [KeyEvent::press(KeyCode::Num1)].into(),
+            injected: Arc::clone(&injected),
+        };
+        let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+        assert!(daemon.process_one_event().unwrap());
+        assert_eq!(injected.lock().unwrap().len(), 2);
+
+        (daemon, injected)
+    }
+
+    #[test]
+    fn test_reload_releases_held_outputs() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let (mut daemon, injected) = daemon_holding_shift_num1(&dir);
+
+        fs::write(
+            dir.path().join("config.rhai"),
+            "device_start(\"*\");\n  map(\"VK_A\", \"VK_B\");\ndevice_end();\n",
+        )
+        .unwrap();
+        daemon.reload().unwrap();
+        assert_eq!(
+            injected.lock().unwrap()[2..],
+            [
+                KeyEvent::release(KeyCode::Num1),
+                KeyEvent::release(KeyCode::LShift)
+            ]
+        );
+
+        // Nothing is left to release on shutdown
+        daemon.shutdown();
+        assert_eq!(injected.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_reload_to_pass_through_releases_held_outputs() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let (mut daemon, injected) = daemon_holding_shift_num1(&dir);
+
+        // No device blocks: the daemon switches to pass-through
+        fs::write(dir.path().join("config.rhai"), "// no devices\n").unwrap();
+        daemon.reload().unwrap();
+        assert!(daemon.remapping_state.is_none());
+        assert_eq!(
+            injected.lock().unwrap()[2..],
+            [
+                KeyEvent::release(KeyCode::Num1),
+                KeyEvent::release(KeyCode::LShift)
+            ]
+        );
+
+        daemon.shutdown();
+        assert_eq!(injected.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_reload_request_swaps_config_and_releases_held_outputs() {
+        use keyrx_core::config::KeyCode;
+        use keyrx_core::runtime::event::KeyEvent;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let (mut daemon, injected) = daemon_holding_shift_num1(&dir);
+
+        let other_path = dir.path().join("other.rhai");
+        fs::write(
+            &other_path,
+            "device_start(\"*\");\n  map(\"VK_A\", \"VK_B\");\ndevice_end();\n",
+        )
+        .unwrap();
+        let handle = daemon.reload_handle();
+        let requester = std::thread::spawn(move || handle.reload(other_path));
+
+        // Answered only once the daemon has applied the request
+        while !requester.is_finished() {
+            daemon.process_reload_requests();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        requester.join().unwrap().unwrap();
+        assert_eq!(
+            injected.lock().unwrap()[2..],
+            [
+                KeyEvent::release(KeyCode::Num1),
+                KeyEvent::release(KeyCode::LShift)
+            ]
+        );
+        let lookup = daemon.remapping_state.as_ref().unwrap().lookup();
+        assert!(lookup.output_keys().contains(&KeyCode::B));
+    }
+
+    #[test]
+    fn test_failed_reload_request_keeps_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let (mut daemon, injected) = daemon_holding_shift_num1(&dir);
+        let source_hash = daemon.loaded_config().source_hash().unwrap();
+
+        let handle = daemon.reload_handle();
+        let missing = dir.path().join("missing.krx");
+        let requester = std::thread::spawn(move || handle.reload(missing));
+        while !requester.is_finished() {
+            daemon.process_reload_requests();
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        assert!(requester.join().unwrap().is_err());
+        // Still remapping (and holding Shift+1) with the original config
+        assert_eq!(injected.lock().unwrap().len(), 2);
+        assert_eq!(daemon.loaded_config().source_hash().unwrap(), source_hash);
+    }
 }
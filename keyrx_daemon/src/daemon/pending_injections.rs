@@ -0,0 +1,122 @@
+//! Time-ordered queue of scheduled output injections.
+//!
+//! Macro delays (the steps after a `wait()`) must not block the event loop: a
+//! sleeping loop would stall input from every other device. Instead, such
+//! output is scheduled here with the time it is due, and the event loop
+//! injects whatever has come due on each pass and sleeps no longer than
+//! until the next entry.
+//!
+//! Times are microseconds on the event loop's clock, so a virtual clock in
+//! tests controls when scheduled output comes due.
+
+use std::collections::VecDeque;
+
+use keyrx_core::runtime::event::KeyEvent;
+
+/// Output events waiting for their injection time.
+struct Scheduled {
+    /// Due time in microseconds.
+    due: u64,
+    events: Vec<KeyEvent>,
+}
+
+/// Output events scheduled for future injection, ordered by due time.
+#[derive(Default)]
+pub struct PendingInjections {
+    /// Scheduled batches, sorted by due time.
+    queue: VecDeque<Scheduled>,
+}
+
+impl PendingInjections {
+    /// Creates an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules `events` to be injected together at `due` (microseconds).
+    ///
+    /// Batches due at the same time are injected in the order they were
+    /// scheduled.
+    pub fn schedule(&mut self, due: u64, events: Vec<KeyEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let position = self.queue.partition_point(|entry| entry.due <= due);
+        self.queue.insert(position, Scheduled { due, events });
+    }
+
+    /// Returns when the earliest scheduled batch is due (microseconds).
+    pub fn next_due(&self) -> Option<u64> {
+        self.queue.front().map(|entry| entry.due)
+    }
+
+    /// Removes every batch due at or before `now` and returns their events
+    /// in due order, ready to be injected as one batch.
+    pub fn take_due(&mut self, now: u64) -> Vec<KeyEvent> {
+        let count = self.queue.partition_point(|entry| entry.due <= now);
+        self.queue
+            .drain(..count)
+            .flat_map(|entry| entry.events)
+            .collect()
+    }
+
+    /// Drops everything still scheduled, e.g. when the config is reloaded.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Returns the number of scheduled batches.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if nothing is scheduled.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::KeyCode;
+
+    #[test]
+    fn test_take_due_returns_due_batches_in_order() {
+        let start = 1_000_000;
+        let mut pending = PendingInjections::new();
+        pending.schedule(start + 20_000, vec![KeyEvent::press(KeyCode::C)]);
+        pending.schedule(start + 10_000, vec![KeyEvent::press(KeyCode::A)]);
+        pending.schedule(start + 10_000, vec![KeyEvent::press(KeyCode::B)]);
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending.next_due(), Some(start + 10_000));
+
+        assert!(pending.take_due(start).is_empty());
+
+        let due = pending.take_due(start + 15_000);
+        let keys: Vec<_> = due.iter().map(|event| event.keycode()).collect();
+        assert_eq!(keys, vec![KeyCode::A, KeyCode::B]);
+        assert_eq!(pending.next_due(), Some(start + 20_000));
+
+        let due = pending.take_due(start + 20_000);
+        assert_eq!(due.len(), 1);
+        assert!(pending.is_empty());
+        assert_eq!(pending.next_due(), None);
+    }
+
+    #[test]
+    fn test_schedule_ignores_empty_batches() {
+        let mut pending = PendingInjections::new();
+        pending.schedule(0, Vec::new());
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_clear_drops_scheduled_batches() {
+        let mut pending = PendingInjections::new();
+        pending.schedule(10, vec![KeyEvent::press(KeyCode::A)]);
+        pending.clear();
+        assert!(pending.is_empty());
+        assert_eq!(pending.next_due(), None);
+    }
+}
@@ -0,0 +1,351 @@
+//! PID file handling for single-instance enforcement.
+//!
+//! Two daemons grabbing the same keyboard fight over every event, so `run
+//! --pidfile <path>` records the daemon's PID at startup and refuses to start
+//! while the recorded process is still alive. A PID file left behind by a
+//! crashed daemon is detected as stale and replaced; one that cannot be read
+//! is left for the user to remove.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+use super::DaemonError;
+
+/// Guard owning a PID file for the lifetime of the daemon.
+///
+/// The file is removed when the guard is dropped, provided it still contains
+/// this process's PID.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl PidFile {
+    /// Writes the current PID to `path`.
+    ///
+    /// # Errors
+    ///
+    /// - `DaemonError::AlreadyRunning` if the file names another live process
+    /// - `DaemonError::RuntimeError` if the file cannot be written
+    pub fn acquire(path: &Path) -> Result<Self, DaemonError> {
+        let pid = std::process::id();
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(|e| write_error(path, e))?;
+            }
+        }
+
+        // The PID is written to a private file first and then hard-linked
+        // into place, so the PID file never exists without its contents: a
+        // concurrent starter cannot mistake a half-written file for a stale
+        // one and remove it.
+        let staging = staging_path(path, pid);
+        fs::write(&staging, format!("{}\n", pid)).map_err(|e| write_error(path, e))?;
+        let acquired = Self::link_into_place(&staging, path, pid);
+        if let Err(e) = fs::remove_file(&staging) {
+            warn!(
+                "Failed to remove temporary PID file {}: {}",
+                staging.display(),
+                e
+            );
+        }
+        acquired
+    }
+
+    /// Links the written `staging` file to `path`, replacing a stale PID file.
+    fn link_into_place(staging: &Path, path: &Path, pid: u32) -> Result<Self, DaemonError> {
+        // Each attempt fails with AlreadyExists while a PID file is present;
+        // the next one runs after a stale file has been cleared away.
+        for _ in 0..3 {
+            match fs::hard_link(staging, path) {
+                Ok(()) => {
+                    debug!("Wrote PID {} to {}", pid, path.display());
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        pid,
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    check_held(path, pid)?;
+                    clear_stale(path, pid)?;
+                }
+                Err(e) => return Err(write_error(path, e)),
+            }
+        }
+
+        Err(DaemonError::RuntimeError(format!(
+            "PID file {} was recreated by another process during startup",
+            path.display()
+        )))
+    }
+
+    /// Returns the path of the PID file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Leave the file alone if another daemon has since taken it over
+        if read_pid(&self.path) != Some(self.pid) {
+            return;
+        }
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("Failed to remove PID file {}: {}", self.path.display(), e);
+        } else {
+            debug!("Removed PID file {}", self.path.display());
+        }
+    }
+}
+
+/// Fails unless the PID file at `path` names a process that is not running.
+///
+/// PID files are linked into place fully written, so one that cannot be read
+/// is not a starter's half-written file; it is still treated as held rather
+/// than guessed to be stale.
+fn check_held(path: &Path, pid: u32) -> Result<(), DaemonError> {
+    match read_pid(path) {
+        Some(existing) if existing != pid && is_process_alive(existing) => {
+            Err(DaemonError::AlreadyRunning {
+                pid: existing,
+                pidfile: path.to_path_buf(),
+            })
+        }
+        Some(_) => Ok(()),
+        None if !path.exists() => Ok(()),
+        None => Err(DaemonError::RuntimeError(format!(
+            "PID file {} is unreadable; remove it if no daemon is running",
+            path.display()
+        ))),
+    }
+}
+
+/// Removes the stale PID file at `path`.
+///
+/// Another starter may have replaced the stale file with its own since it
+/// was checked, so the file is first moved to a name only this process uses
+/// and checked again there. A live daemon's file moved by mistake is linked
+/// back instead of being deleted.
+fn clear_stale(path: &Path, pid: u32) -> Result<(), DaemonError> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.stale", pid));
+    let moved = path.with_file_name(name);
+
+    match fs::rename(path, &moved) {
+        Ok(()) => {}
+        // Someone else cleared it first
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(write_error(path, e)),
+    }
+
+    let held = check_held(&moved, pid);
+    if held.is_err() {
+        if let Err(e) = fs::hard_link(&moved, path) {
+            warn!(
+                "Failed to restore PID file {} from {}: {}",
+                path.display(),
+                moved.display(),
+                e
+            );
+        }
+    } else {
+        match read_pid(&moved) {
+            Some(existing) => warn!(
+                "Removing stale PID file {} (PID {} is not running)",
+                path.display(),
+                existing
+            ),
+            None => warn!("Removing stale PID file {}", path.display()),
+        }
+    }
+    if let Err(e) = fs::remove_file(&moved) {
+        warn!("Failed to remove stale PID file {}: {}", moved.display(), e);
+    }
+
+    held.map_err(|e| match e {
+        DaemonError::AlreadyRunning { pid, .. } => DaemonError::AlreadyRunning {
+            pid,
+            pidfile: path.to_path_buf(),
+        },
+        other => other,
+    })
+}
+
+/// Returns the per-process file the PID is written to before it is linked
+/// to `path`.
+fn staging_path(path: &Path, pid: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", pid));
+    path.with_file_name(name)
+}
+
+fn write_error(path: &Path, err: io::Error) -> DaemonError {
+    DaemonError::RuntimeError(format!(
+        "failed to write PID file {}: {}",
+        path.display(),
+        err
+    ))
+}
+
+/// Reads the PID recorded in `path`, if the file exists and is well-formed.
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Returns true if a process with the given PID is currently running.
+#[cfg(target_os = "linux")]
+fn is_process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Returns true if a process with the given PID is currently running.
+#[cfg(target_os = "windows")]
+fn is_process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    // SAFETY: OpenProcess returns null on failure and the handle is closed
+    // before returning.
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut exit_code = 0u32;
+        let queried = GetExitCodeProcess(handle, &mut exit_code) != 0;
+        CloseHandle(handle);
+        queried && exit_code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_acquire_writes_pid_and_drop_removes_it() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("run").join("keyrx.pid");
+
+        let pidfile = PidFile::acquire(&path).unwrap();
+        assert_eq!(pidfile.path(), path);
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        // The staging file the PID was written to is gone
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        drop(pidfile);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_acquire_refuses_live_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(&path, format!("{}\n", child.id())).unwrap();
+
+        let result = PidFile::acquire(&path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        match result {
+            Err(DaemonError::AlreadyRunning { pid, pidfile }) => {
+                assert_eq!(pid, child.id());
+                assert_eq!(pidfile, path);
+            }
+            other => panic!("Expected AlreadyRunning, got {:?}", other),
+        }
+        // The other daemon's PID file must be left untouched
+        assert_eq!(read_pid(&path), Some(child.id()));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_acquire_replaces_stale_pid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+
+        // PIDs are capped well below u32::MAX on Linux
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+
+        let _pidfile = PidFile::acquire(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_acquire_treats_unreadable_pid_file_as_held() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+        fs::write(&path, "not a pid").unwrap();
+
+        assert!(matches!(
+            PidFile::acquire(&path),
+            Err(DaemonError::RuntimeError(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not a pid");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_clear_stale_restores_a_live_pid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+
+        // Another starter linked its PID file after this one judged the old
+        // file stale
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(&path, format!("{}\n", child.id())).unwrap();
+
+        let result = clear_stale(&path, std::process::id());
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(DaemonError::AlreadyRunning { pid, .. }) if pid == child.id()
+        ));
+        assert_eq!(read_pid(&path), Some(child.id()));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_clear_stale_removes_a_dead_pid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+
+        clear_stale(&path, std::process::id()).unwrap();
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+        // Nothing left to clear is not an error
+        clear_stale(&path, std::process::id()).unwrap();
+    }
+
+    #[test]
+    fn test_drop_keeps_file_taken_over_by_another_process() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("keyrx.pid");
+
+        let pidfile = PidFile::acquire(&path).unwrap();
+        fs::write(&path, "1\n").unwrap();
+        drop(pidfile);
+
+        assert_eq!(read_pid(&path), Some(1));
+    }
+}
@@ -5,10 +5,23 @@
 //! - `DeviceState`: Modifier/lock bits + tap-hold processor
 //!
 //! The state is maintained across events and can be reloaded on SIGHUP.
+//!
+//! Macro steps after a `wait()` come out of `process_event` as scheduled
+//! output; the state keeps them in a [`PendingInjections`] queue until the
+//! event loop's clock reaches their due time.
+//!
+//! `keyrx_core` never reads a clock, so time-window conditions rely on the
+//! caller injecting [`local_minute_of_day`] into the state before each event.
+//! Configs without time windows skip the injection.
 
-use keyrx_core::config::DeviceConfig;
+use chrono::Timelike;
+use keyrx_core::config::mappings::ArchivedDeviceConfig;
+use keyrx_core::config::{DeviceConfig, KeyCode};
+use keyrx_core::runtime::event::KeyEvent;
 use keyrx_core::runtime::{DeviceState, KeyLookup};
 
+use super::pending_injections::PendingInjections;
+
 /// Container for remapping state.
 ///
 /// Holds all state needed for event processing in the hot path:
@@ -24,6 +37,10 @@ pub struct RemappingState {
     lookup: KeyLookup,
     /// Device state (modifiers, locks, tap-hold).
     state: DeviceState,
+    /// Keys currently pressed on the output device, in press order.
+    held_outputs: Vec<KeyCode>,
+    /// Macro output waiting for its due time.
+    pending: PendingInjections,
 }
 
 impl RemappingState {
@@ -35,7 +52,23 @@ impl RemappingState {
     pub fn new(config: &DeviceConfig) -> Self {
         Self {
             lookup: KeyLookup::from_device_config(config),
-            state: DeviceState::new(),
+            state: DeviceState::for_device(config),
+            held_outputs: Vec::new(),
+            pending: PendingInjections::new(),
+        }
+    }
+
+    /// Creates a new remapping state straight from an archived device
+    /// configuration, without converting it to an owned `DeviceConfig`.
+    ///
+    /// The lookup table borrows its mappings from the archive, which the
+    /// config loader keeps alive for the life of the process.
+    pub fn from_archived(config: &'static ArchivedDeviceConfig) -> Self {
+        Self {
+            lookup: KeyLookup::from_archived(config),
+            state: DeviceState::for_archived_device(config),
+            held_outputs: Vec::new(),
+            pending: PendingInjections::new(),
         }
     }
 
@@ -67,27 +100,109 @@ impl RemappingState {
         (&self.lookup, &mut self.state)
     }
 
+    /// Records output events handed to the platform, keeping track of which
+    /// output keys are held.
+    ///
+    /// Presses are tracked even if injection failed (`injected` is `false`):
+    /// releasing a key that never went out is harmless, leaving one stuck is
+    /// not.
+    pub fn record_outputs(&mut self, events: &[KeyEvent], injected: bool) {
+        for event in events {
+            if event.is_press() {
+                if !self.held_outputs.contains(&event.keycode()) {
+                    self.held_outputs.push(event.keycode());
+                }
+            } else if event.is_release() && injected {
+                self.held_outputs.retain(|&key| key != event.keycode());
+            }
+        }
+    }
+
+    /// Moves the output `process_event` scheduled for later into the pending
+    /// queue.
+    ///
+    /// Each event's timestamp is its due time on the timescale of the input
+    /// event that triggered it (`triggered_at`). The delay is rebased on
+    /// `now` from the caller's clock, so a platform that leaves input
+    /// timestamps at 0 still waits.
+    pub fn queue_scheduled_outputs(&mut self, triggered_at: u64, now: u64) {
+        for event in self.state.take_scheduled_outputs() {
+            let delay_us = event.timestamp_us().saturating_sub(triggered_at);
+            let due = now.saturating_add(delay_us);
+            self.pending.schedule(due, vec![event.with_timestamp(due)]);
+        }
+    }
+
+    /// Removes and returns the scheduled output due at or before `now`
+    /// (microseconds), in due order.
+    pub fn take_due_outputs(&mut self, now: u64) -> Vec<KeyEvent> {
+        self.pending.take_due(now)
+    }
+
+    /// Returns when the earliest scheduled output is due (microseconds).
+    pub fn next_scheduled_due(&self) -> Option<u64> {
+        self.pending.next_due()
+    }
+
+    /// Returns a release for every output key still held, most recent first,
+    /// and forgets them.
+    ///
+    /// Scheduled output is dropped too, so a macro cut short by a reload or
+    /// shutdown does not press keys afterwards.
+    pub fn take_held_releases(&mut self) -> Vec<KeyEvent> {
+        self.pending.clear();
+        self.held_outputs
+            .drain(..)
+            .rev()
+            .map(KeyEvent::release)
+            .collect()
+    }
+
     /// Reloads the remapping state with new configuration.
     ///
     /// Called on SIGHUP to apply configuration changes.
-    /// This creates a fresh lookup table and resets device state.
+    /// This creates a fresh lookup table and resets device state. Output
+    /// keys still held are kept on record; callers release them first with
+    /// [`take_held_releases`](Self::take_held_releases), as `Daemon::reload`
+    /// does.
     ///
     /// # Arguments
     ///
     /// * `config` - New device configuration
     pub fn reload(&mut self, config: &DeviceConfig) {
         self.lookup = KeyLookup::from_device_config(config);
-        self.state = DeviceState::new();
+        self.state = DeviceState::for_device(config);
+    }
+
+    /// Reloads the remapping state from an archived device configuration.
+    ///
+    /// Same as [`reload`](Self::reload) without the owned conversion.
+    pub fn reload_archived(&mut self, config: &'static ArchivedDeviceConfig) {
+        self.lookup = KeyLookup::from_archived(config);
+        self.state = DeviceState::for_archived_device(config);
     }
 
     /// Resets only the device state (preserves lookup table).
     ///
     /// Useful for testing or recovering from stuck state.
     pub fn reset_state(&mut self) {
-        self.state = DeviceState::new();
+        let mut state = DeviceState::new();
+        state.set_time_windows(self.state.time_windows());
+        state.set_default_layer(self.state.default_layer());
+        self.state = state;
     }
 }
 
+/// Returns the current local wall-clock time as minutes since midnight.
+///
+/// Injected into `DeviceState::set_minute_of_day` before each event so
+/// `TW_XX` conditions see the current time, when the config declares any
+/// time windows.
+pub fn local_minute_of_day() -> u16 {
+    let now = chrono::Local::now();
+    (now.hour() * 60 + now.minute()) as u16
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,8 +212,13 @@ mod tests {
         DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".to_string(), // Match all devices (global)
+                excludes: Vec::new(),
             },
             mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }
     }
 
@@ -146,4 +266,82 @@ mod tests {
             .find_mapping(KeyCode::A, state.state())
             .is_some());
     }
+
+    #[test]
+    fn test_remapping_state_tracks_held_outputs() {
+        let config = create_test_config();
+        let mut state = RemappingState::new(&config);
+
+        state.record_outputs(
+            &[
+                KeyEvent::press(KeyCode::LShift),
+                KeyEvent::press(KeyCode::B),
+            ],
+            true,
+        );
+        state.record_outputs(&[KeyEvent::release(KeyCode::B)], true);
+        // A failed release leaves the key tracked
+        state.record_outputs(&[KeyEvent::release(KeyCode::LShift)], false);
+        state.record_outputs(&[KeyEvent::press(KeyCode::C)], false);
+
+        assert_eq!(
+            state.take_held_releases(),
+            vec![
+                KeyEvent::release(KeyCode::C),
+                KeyEvent::release(KeyCode::LShift)
+            ]
+        );
+        assert!(state.take_held_releases().is_empty());
+    }
+
+    #[test]
+    fn test_remapping_state_queues_scheduled_outputs() {
+        use keyrx_core::config::MacroStep;
+
+        let mut config = create_test_config();
+        config.mappings.push(KeyMapping::macro_steps(
+            KeyCode::F1,
+            vec![
+                MacroStep::Press(KeyCode::H),
+                MacroStep::Wait(10),
+                MacroStep::Release(KeyCode::H),
+            ],
+        ));
+        let mut state = RemappingState::new(&config);
+
+        let (lookup, device_state) = state.lookup_and_state_mut();
+        let outputs = keyrx_core::runtime::process_event(
+            KeyEvent::press(KeyCode::F1).with_timestamp(1_000),
+            lookup,
+            device_state,
+        );
+        assert_eq!(
+            outputs,
+            vec![KeyEvent::press(KeyCode::H).with_timestamp(1_000)]
+        );
+
+        // Due 10ms after the caller's clock, whatever the input timestamp
+        state.queue_scheduled_outputs(1_000, 500_000);
+        assert_eq!(state.next_scheduled_due(), Some(510_000));
+        assert!(state.take_due_outputs(509_999).is_empty());
+        assert_eq!(
+            state.take_due_outputs(510_000),
+            vec![KeyEvent::release(KeyCode::H).with_timestamp(510_000)]
+        );
+
+        // Releasing held keys cancels a macro still in flight
+        state.record_outputs(&outputs, true);
+        let (lookup, device_state) = state.lookup_and_state_mut();
+        keyrx_core::runtime::process_event(
+            KeyEvent::press(KeyCode::F1).with_timestamp(20_000),
+            lookup,
+            device_state,
+        );
+        state.queue_scheduled_outputs(20_000, 520_000);
+        assert_eq!(
+            state.take_held_releases(),
+            vec![KeyEvent::release(KeyCode::H)]
+        );
+        assert_eq!(state.next_scheduled_due(), None);
+    }
 }
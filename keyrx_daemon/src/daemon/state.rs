@@ -1,23 +1,19 @@
 //! Daemon state management for keyrx.
 //!
-//! This module provides state management utilities including:
-//!
-//! - [`ReloadState`]: Tracks configuration reload requests
-//! - Configuration conversion helpers for rkyv-archived types
+//! This module provides [`ReloadState`], which tracks configuration reload
+//! requests, and [`ReloadHandle`], which asks a running daemon to reload from
+//! a given file and waits for the result.
 
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 use std::sync::Arc;
+use std::time::Duration;
 
-use keyrx_core::config::{
-    BaseKeyMapping, Condition, ConditionItem, DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping,
-};
+use super::DaemonError;
 
-// Import the archived types from their modules
-use keyrx_core::config::conditions::{ArchivedCondition, ArchivedConditionItem};
-use keyrx_core::config::keys::ArchivedKeyCode;
-use keyrx_core::config::mappings::{
-    ArchivedBaseKeyMapping, ArchivedDeviceConfig, ArchivedKeyMapping,
-};
+/// How long [`ReloadHandle::reload`] waits for the daemon to apply a request.
+const RELOAD_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Reload request state.
 ///
@@ -62,126 +58,67 @@ impl Default for ReloadState {
     }
 }
 
-// ============================================================================
-// Archived Config Conversion Helpers
-// ============================================================================
-//
-// These functions convert rkyv-archived configuration types to owned types.
-// This is necessary because DeviceManager and KeyLookup operate on owned types.
-
-/// Converts an archived KeyCode to an owned KeyCode.
-///
-/// Uses rkyv's Deserialize trait for safe conversion.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_keycode(archived: &ArchivedKeyCode) -> KeyCode {
-    use rkyv::Deserialize;
-    archived
-        .deserialize(&mut rkyv::Infallible)
-        .expect("KeyCode deserialization is infallible")
+/// Request to reload the configuration from a file, answered once applied.
+#[derive(Debug)]
+pub struct ReloadRequest {
+    path: PathBuf,
+    reply: mpsc::Sender<Result<(), DaemonError>>,
 }
 
-/// Converts an archived ConditionItem to an owned ConditionItem.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_condition_item(archived: &ArchivedConditionItem) -> ConditionItem {
-    match archived {
-        ArchivedConditionItem::ModifierActive(id) => ConditionItem::ModifierActive(*id),
-        ArchivedConditionItem::LockActive(id) => ConditionItem::LockActive(*id),
+impl ReloadRequest {
+    /// Returns the .krx (or .rhai) file to reload from.
+    pub fn path(&self) -> &Path {
+        &self.path
     }
-}
 
-/// Converts an archived Condition to an owned Condition.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_condition(archived: &ArchivedCondition) -> Condition {
-    match archived {
-        ArchivedCondition::ModifierActive(id) => Condition::ModifierActive(*id),
-        ArchivedCondition::LockActive(id) => Condition::LockActive(*id),
-        ArchivedCondition::DeviceMatches(id) => {
-            use rkyv::Deserialize;
-            // SAFETY: rkyv::Infallible means deserialization cannot fail
-            // The unwrap here is safe because the deserializer is infallible by design
-            Condition::DeviceMatches(Deserialize::deserialize(id, &mut rkyv::Infallible).unwrap())
-        }
-        ArchivedCondition::AllActive(items) => {
-            Condition::AllActive(items.iter().map(convert_archived_condition_item).collect())
-        }
-        ArchivedCondition::NotActive(items) => {
-            Condition::NotActive(items.iter().map(convert_archived_condition_item).collect())
-        }
+    /// Sends the outcome of the reload back to the requester.
+    pub fn reply(self, result: Result<(), DaemonError>) {
+        // The requester may have given up waiting
+        let _ = self.reply.send(result);
     }
 }
 
-/// Converts an archived BaseKeyMapping to an owned BaseKeyMapping.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_base_mapping(archived: &ArchivedBaseKeyMapping) -> BaseKeyMapping {
-    match archived {
-        ArchivedBaseKeyMapping::Simple { from, to } => BaseKeyMapping::Simple {
-            from: convert_archived_keycode(from),
-            to: convert_archived_keycode(to),
-        },
-        ArchivedBaseKeyMapping::Modifier { from, modifier_id } => BaseKeyMapping::Modifier {
-            from: convert_archived_keycode(from),
-            modifier_id: *modifier_id,
-        },
-        ArchivedBaseKeyMapping::Lock { from, lock_id } => BaseKeyMapping::Lock {
-            from: convert_archived_keycode(from),
-            lock_id: *lock_id,
-        },
-        ArchivedBaseKeyMapping::TapHold {
-            from,
-            tap,
-            hold_modifier,
-            threshold_ms,
-        } => BaseKeyMapping::TapHold {
-            from: convert_archived_keycode(from),
-            tap: convert_archived_keycode(tap),
-            hold_modifier: *hold_modifier,
-            threshold_ms: *threshold_ms,
-        },
-        ArchivedBaseKeyMapping::ModifiedOutput {
-            from,
-            to,
-            shift,
-            ctrl,
-            alt,
-            win,
-        } => BaseKeyMapping::ModifiedOutput {
-            from: convert_archived_keycode(from),
-            to: convert_archived_keycode(to),
-            shift: *shift,
-            ctrl: *ctrl,
-            alt: *alt,
-            win: *win,
-        },
-    }
+/// Sends reload requests to a running daemon.
+///
+/// Requests are queued for the event loop, which swaps in the new
+/// configuration between events. Clones share the same queue.
+#[derive(Debug, Clone)]
+pub struct ReloadHandle {
+    requests: mpsc::Sender<ReloadRequest>,
 }
 
-/// Converts an archived KeyMapping to an owned KeyMapping.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_key_mapping(archived: &ArchivedKeyMapping) -> KeyMapping {
-    match archived {
-        ArchivedKeyMapping::Base(base) => KeyMapping::Base(convert_archived_base_mapping(base)),
-        ArchivedKeyMapping::Conditional {
-            condition,
-            mappings,
-        } => KeyMapping::Conditional {
-            condition: convert_archived_condition(condition),
-            mappings: mappings.iter().map(convert_archived_base_mapping).collect(),
-        },
+impl ReloadHandle {
+    /// Creates a handle and the queue the daemon reads its requests from.
+    pub fn channel() -> (Self, mpsc::Receiver<ReloadRequest>) {
+        let (requests, receiver) = mpsc::channel();
+        (Self { requests }, receiver)
     }
-}
 
-/// Converts an archived DeviceConfig to an owned DeviceConfig.
-#[allow(dead_code)]
-pub(crate) fn convert_archived_device_config(archived: &ArchivedDeviceConfig) -> DeviceConfig {
-    DeviceConfig {
-        identifier: DeviceIdentifier {
-            pattern: archived.identifier.pattern.to_string(),
-        },
-        mappings: archived
-            .mappings
-            .iter()
-            .map(convert_archived_key_mapping)
-            .collect(),
+    /// Asks the daemon to remap with the configuration at `path`, and waits
+    /// until it has.
+    ///
+    /// # Errors
+    ///
+    /// - The error the daemon hit loading `path`; it keeps its current
+    ///   configuration in that case
+    /// - `DaemonError::RuntimeError` if the daemon is not running or does
+    ///   not answer within 5 seconds
+    pub fn reload(&self, path: PathBuf) -> Result<(), DaemonError> {
+        let (reply, result) = mpsc::channel();
+        self.requests
+            .send(ReloadRequest { path, reply })
+            .map_err(|_| DaemonError::RuntimeError("daemon is not running".to_string()))?;
+
+        match result.recv_timeout(RELOAD_TIMEOUT) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(DaemonError::RuntimeError(format!(
+                "daemon did not apply the reload within {}s",
+                RELOAD_TIMEOUT.as_secs()
+            ))),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err(DaemonError::RuntimeError(
+                "daemon stopped before applying the reload".to_string(),
+            )),
+        }
     }
 }
 
@@ -229,4 +166,26 @@ mod tests {
         let state = ReloadState::default();
         assert!(!state.check_and_clear());
     }
+
+    #[test]
+    fn test_reload_handle_returns_daemon_reply() {
+        let (handle, requests) = ReloadHandle::channel();
+        let daemon = std::thread::spawn(move || {
+            let request = requests.recv().unwrap();
+            assert_eq!(request.path(), Path::new("/profiles/work.krx"));
+            request.reply(Err(DaemonError::RuntimeError("bad config".to_string())));
+        });
+
+        let result = handle.reload(PathBuf::from("/profiles/work.krx"));
+        daemon.join().unwrap();
+        assert!(matches!(result, Err(DaemonError::RuntimeError(msg)) if msg == "bad config"));
+    }
+
+    #[test]
+    fn test_reload_handle_fails_without_daemon() {
+        let (handle, requests) = ReloadHandle::channel();
+        drop(requests);
+
+        assert!(handle.reload(PathBuf::from("/profiles/work.krx")).is_err());
+    }
 }
@@ -14,7 +14,8 @@ use keyrx_core::config::DeviceConfig;
 use keyrx_core::runtime::{DeviceState, KeyLookup};
 
 use super::{DiscoveryError, KeyboardInfo};
-use crate::platform::linux::EvdevInput;
+use crate::platform::linux::{EvdevInput, UinputOutput};
+use crate::platform::DeviceCapabilities;
 
 /// Required alphabetic keys that a keyboard must have.
 const REQUIRED_KEYS: &[Key] = &[
@@ -66,6 +67,18 @@ fn is_keyboard(device: &Device) -> bool {
     key_count >= MIN_REQUIRED_KEYS
 }
 
+/// Queries the key and relative-axis capabilities of an evdev device.
+fn device_capabilities(device: &Device) -> DeviceCapabilities {
+    let supported_events = device.supported_events();
+    DeviceCapabilities {
+        supports_keys: supported_events.contains(EventType::KEY),
+        key_count: device
+            .supported_keys()
+            .map_or(0, |keys| keys.iter().count()),
+        supports_rel: supported_events.contains(EventType::RELATIVE),
+    }
+}
+
 /// Deduplicates keyboards by filtering out secondary interfaces.
 ///
 /// Many USB keyboards register as multiple `/dev/input/event*` devices:
@@ -157,9 +170,10 @@ pub fn enumerate_keyboards() -> Result<Vec<KeyboardInfo>, DiscoveryError> {
 
         let name = device.name().unwrap_or("Unknown Device").to_string();
 
-        // Skip the daemon's own virtual output device to prevent grabbing it
-        // The virtual device name is "keyrx" as set in platform/linux/mod.rs
-        if name == "keyrx" {
+        // Never capture a keyrx virtual output device (including one
+        // renamed with --output-name or owned by another daemon), or
+        // injected events would be remapped again
+        if UinputOutput::is_own_device(&name, device.input_id()) {
             continue;
         }
 
@@ -171,6 +185,7 @@ pub fn enumerate_keyboards() -> Result<Vec<KeyboardInfo>, DiscoveryError> {
             name,
             serial,
             phys,
+            capabilities: Some(device_capabilities(&device)),
         });
     }
 
@@ -203,7 +218,7 @@ impl ManagedDevice {
             info,
             input,
             lookup: KeyLookup::from_device_config(config),
-            state: DeviceState::new(),
+            state: DeviceState::for_device(config),
             config_index,
         }
     }
@@ -232,6 +247,7 @@ impl ManagedDevice {
 
     pub fn rebuild_lookup(&mut self, config: &DeviceConfig) {
         self.lookup = KeyLookup::from_device_config(config);
+        self.state.set_time_windows(&config.time_windows);
     }
 
     /// Returns mutable references to both lookup and state simultaneously.
@@ -269,6 +285,10 @@ impl ManagedDevice {
 
 pub struct DeviceManager {
     devices: Vec<ManagedDevice>,
+    /// Device IDs restricted by [`DeviceManager::select`], if any.
+    selected: Option<Vec<String>>,
+    /// Most devices to manage, as passed to [`DeviceManager::discover`].
+    max_devices: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -278,26 +298,33 @@ pub struct RefreshResult {
 }
 
 impl DeviceManager {
-    pub fn discover(configs: &[DeviceConfig]) -> Result<Self, DiscoveryError> {
+    /// Opens every keyboard that matches one of `configs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DiscoveryError::NoDevicesFound`] if no keyboard matches, and
+    /// [`DiscoveryError::TooManyDevices`] if more than `max_devices` match;
+    /// no device is opened in that case. Later [`refresh`](Self::refresh)
+    /// calls stop adding devices at the limit.
+    pub fn discover(
+        configs: &[DeviceConfig],
+        max_devices: Option<usize>,
+    ) -> Result<Self, DiscoveryError> {
         let keyboards = enumerate_keyboards()?;
         if keyboards.is_empty() {
             return Err(DiscoveryError::NoDevicesFound);
         }
 
+        let matched: Vec<(KeyboardInfo, usize)> = keyboards
+            .into_iter()
+            .filter_map(|info| super::find_matching_config(&info, configs).map(|idx| (info, idx)))
+            .collect();
+        super::check_device_limit(matched.iter().map(|(info, _)| info), max_devices)?;
+
         let mut managed_devices = Vec::new();
-        for keyboard_info in keyboards {
-            for (idx, config) in configs.iter().enumerate() {
-                if super::match_device(&keyboard_info, &config.identifier.pattern) {
-                    if let Ok(input) = EvdevInput::open(&keyboard_info.path) {
-                        managed_devices.push(ManagedDevice::new(
-                            keyboard_info.clone(),
-                            input,
-                            config,
-                            idx,
-                        ));
-                        break;
-                    }
-                }
+        for (keyboard_info, idx) in matched {
+            if let Ok(input) = EvdevInput::open(&keyboard_info.path) {
+                managed_devices.push(ManagedDevice::new(keyboard_info, input, &configs[idx], idx));
             }
         }
 
@@ -306,9 +333,33 @@ impl DeviceManager {
         }
         Ok(Self {
             devices: managed_devices,
+            selected: None,
+            max_devices,
         })
     }
 
+    /// Restricts the managed devices to `ids`.
+    ///
+    /// Devices not listed are closed without being grabbed, so they keep
+    /// delivering input to other applications. Later [`refresh`](Self::refresh)
+    /// calls only pick up devices from the selection.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DiscoveryError::UnknownDeviceIds`] if any ID does not match
+    /// a discovered device; nothing is dropped in that case.
+    pub fn select(&mut self, ids: &[String]) -> Result<(), DiscoveryError> {
+        let available = self.device_ids();
+        let unknown = super::unknown_device_ids(ids, &available);
+        if !unknown.is_empty() {
+            return Err(DiscoveryError::UnknownDeviceIds { unknown, available });
+        }
+
+        self.devices.retain(|d| ids.contains(&d.device_id()));
+        self.selected = Some(ids.to_vec());
+        Ok(())
+    }
+
     pub fn device_count(&self) -> usize {
         self.devices.len()
     }
@@ -318,6 +369,10 @@ impl DeviceManager {
     pub fn devices_mut(&mut self) -> impl Iterator<Item = &mut ManagedDevice> {
         self.devices.iter_mut()
     }
+    /// Consumes the manager, handing over ownership of every device.
+    pub fn into_devices(self) -> Vec<ManagedDevice> {
+        self.devices
+    }
     pub fn get_device(&self, index: usize) -> Option<&ManagedDevice> {
         self.devices.get(index)
     }
@@ -361,14 +416,25 @@ impl DeviceManager {
             if managed_paths.contains(&info.path) {
                 continue;
             }
-            for (idx, config) in configs.iter().enumerate() {
-                if super::match_device(&info, &config.identifier.pattern) {
-                    if let Ok(input) = EvdevInput::open(&info.path) {
-                        self.devices
-                            .push(ManagedDevice::new(info.clone(), input, config, idx));
-                        added += 1;
-                        break;
-                    }
+            if let Some(selected) = &self.selected {
+                if !selected.contains(&info.device_id()) {
+                    continue;
+                }
+            }
+            if let Some(idx) = super::find_matching_config(&info, configs) {
+                if let Some(limit) = self.max_devices.filter(|&l| self.devices.len() >= l) {
+                    warn!(
+                        "Not grabbing '{}' ({}): already managing {} device(s), the --max-devices limit",
+                        info.name,
+                        info.device_id(),
+                        limit
+                    );
+                    continue;
+                }
+                if let Ok(input) = EvdevInput::open(&info.path) {
+                    self.devices
+                        .push(ManagedDevice::new(info.clone(), input, &configs[idx], idx));
+                    added += 1;
                 }
             }
         }
@@ -425,6 +491,7 @@ mod tests {
             name: name.to_string(),
             serial: serial.map(String::from),
             phys: None,
+            capabilities: None,
         }
     }
 
@@ -10,10 +10,14 @@
 //! - [`KeyboardInfo`]: Information about a discovered keyboard device
 //! - [`enumerate_keyboards`]: Discovers available keyboard devices
 //! - [`match_device`]: Matches devices against configuration patterns
+//! - [`match_device_identifier`]: Applies a pattern together with its exclusions
+//! - [`find_matching_config`]: Picks the configuration that claims a device
 //! - [`DeviceManager`]: Manages multiple devices and matches them to configurations
 //! - [`ManagedDevice`]: A device paired with its configuration and runtime state
 
-use crate::platform::DeviceError;
+use keyrx_core::config::{DeviceConfig, DeviceIdentifier};
+
+use crate::platform::{DeviceCapabilities, DeviceError};
 
 #[cfg(target_os = "linux")]
 mod linux;
@@ -26,114 +30,99 @@ pub use linux::{enumerate_keyboards, DeviceManager, ManagedDevice, RefreshResult
 pub use windows::{enumerate_keyboards, DeviceManager, ManagedDevice, RefreshResult};
 
 /// Matches a device against a pattern string.
+///
+/// The pattern is compared case-insensitively against the device name, its
+/// [`display_name`](KeyboardInfo::display_name) (`"Name (serial)"`, as
+/// printed by `list-devices`), the serial and the physical path. A `*` at
+/// either end makes it a prefix, suffix or substring match.
+///
+/// A leading `!` negates the pattern: `"!*Mouse*"` matches every device
+/// that does not match `"*Mouse*"`.
 pub fn match_device(device: &KeyboardInfo, pattern: &str) -> bool {
+    // Negated pattern matches everything the inner pattern rejects
+    if let Some(inner) = pattern.strip_prefix('!') {
+        return !match_device(device, inner);
+    }
+
     // Wildcard pattern matches everything
     if pattern == "*" {
         return true;
     }
 
+    let fields = matchable_fields(device);
+
     // Check for contains pattern (*substring*)
     if pattern.starts_with('*') && pattern.ends_with('*') && pattern.len() > 2 {
-        let substring = &pattern[1..pattern.len() - 1];
-        let substring_lower = substring.to_lowercase();
-
-        // Match against device name
-        if device.name.to_lowercase().contains(&substring_lower) {
-            return true;
-        }
-
-        // Match against serial if available
-        if let Some(ref serial) = device.serial {
-            if serial.to_lowercase().contains(&substring_lower) {
-                return true;
-            }
-        }
-
-        // Match against physical path if available
-        if let Some(ref phys) = device.phys {
-            if phys.to_lowercase().contains(&substring_lower) {
-                return true;
-            }
-        }
-
-        return false;
+        let substring_lower = pattern[1..pattern.len() - 1].to_lowercase();
+        return fields.iter().any(|field| field.contains(&substring_lower));
     }
 
     // Check for suffix pattern (*suffix)
     if let Some(suffix) = pattern.strip_prefix('*') {
         let suffix_lower = suffix.to_lowercase();
-
-        // Match against device name
-        if device.name.to_lowercase().ends_with(&suffix_lower) {
-            return true;
-        }
-
-        // Match against serial if available
-        if let Some(ref serial) = device.serial {
-            if serial.to_lowercase().ends_with(&suffix_lower) {
-                return true;
-            }
-        }
-
-        // Match against physical path if available
-        if let Some(ref phys) = device.phys {
-            if phys.to_lowercase().ends_with(&suffix_lower) {
-                return true;
-            }
-        }
-
-        return false;
+        return fields.iter().any(|field| field.ends_with(&suffix_lower));
     }
 
     // Check for prefix pattern (prefix*)
     if let Some(prefix) = pattern.strip_suffix('*') {
         let prefix_lower = prefix.to_lowercase();
-
-        // Match against device name
-        if device.name.to_lowercase().starts_with(&prefix_lower) {
-            return true;
-        }
-
-        // Match against serial if available
-        if let Some(ref serial) = device.serial {
-            if serial.to_lowercase().starts_with(&prefix_lower) {
-                return true;
-            }
-        }
-
-        // Match against physical path if available
-        if let Some(ref phys) = device.phys {
-            if phys.to_lowercase().starts_with(&prefix_lower) {
-                return true;
-            }
-        }
-
-        return false;
+        return fields.iter().any(|field| field.starts_with(&prefix_lower));
     }
 
     // Exact match (case-insensitive)
     let pattern_lower = pattern.to_lowercase();
+    fields.contains(&pattern_lower)
+}
 
-    // Match against device name
-    if device.name.to_lowercase() == pattern_lower {
-        return true;
-    }
-
-    // Match against serial if available
+/// Returns the lowercased strings a device pattern is matched against.
+fn matchable_fields(device: &KeyboardInfo) -> Vec<String> {
+    let mut fields = vec![device.name.to_lowercase()];
     if let Some(ref serial) = device.serial {
-        if serial.to_lowercase() == pattern_lower {
-            return true;
-        }
+        fields.push(device.display_name().to_lowercase());
+        fields.push(serial.to_lowercase());
     }
-
-    // Match against physical path if available
     if let Some(ref phys) = device.phys {
-        if phys.to_lowercase() == pattern_lower {
-            return true;
-        }
+        fields.push(phys.to_lowercase());
     }
+    fields
+}
 
-    false
+/// Matches a device against a configuration's [`DeviceIdentifier`].
+///
+/// The device must match the positive `pattern` and none of the `excludes`.
+/// Exclusions win over the positive pattern, so `device_start("*");
+/// exclude("*Mouse*");` matches every keyboard except mouse interfaces.
+/// An exclusion may optionally be written with a leading `!`.
+pub fn match_device_identifier(device: &KeyboardInfo, identifier: &DeviceIdentifier) -> bool {
+    if !match_device(device, &identifier.pattern) {
+        return false;
+    }
+
+    !identifier.excludes.iter().any(|exclude| {
+        let exclude = exclude.strip_prefix('!').unwrap_or(exclude);
+        match_device(device, exclude)
+    })
+}
+
+/// Returns configuration indices in the order they should be tried.
+///
+/// Higher priorities come first; configurations with equal priority keep
+/// their declaration order.
+pub fn match_order<I: IntoIterator<Item = i32>>(priorities: I) -> Vec<usize> {
+    let mut order: Vec<(usize, i32)> = priorities.into_iter().enumerate().collect();
+    order.sort_by_key(|&(_, priority)| std::cmp::Reverse(priority));
+    order.into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// Returns the index of the configuration that claims `device`.
+///
+/// Configurations are tried in [`match_order`], so a low-priority catch-all
+/// such as `device_start("*"); priority(-10);` only applies to devices no
+/// other configuration matches, wherever it is declared.
+pub fn find_matching_config(device: &KeyboardInfo, configs: &[DeviceConfig]) -> Option<usize> {
+    match_order(configs.iter().map(|config| config.priority))
+        .into_iter()
+        .find(|&idx| match_device_identifier(device, &configs[idx].identifier))
 }
 
 /// Errors that can occur during device discovery.
@@ -150,6 +139,70 @@ pub enum DiscoveryError {
     /// I/O error during device enumeration.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// Requested device IDs do not match any managed device.
+    #[error(
+        "unknown device id(s): {}; matched devices: {}",
+        .unknown.join(", "),
+        .available.join(", ")
+    )]
+    UnknownDeviceIds {
+        /// IDs that were requested but not found.
+        unknown: Vec<String>,
+        /// IDs of the devices that could have been selected.
+        available: Vec<String>,
+    },
+
+    /// More devices match the configuration than the `--max-devices` limit.
+    #[error(
+        "{} devices match the configuration, more than the limit of {limit}; \
+         tighten the device pattern or raise --max-devices. Matched: {}",
+        .matched.len(),
+        .matched.join(", ")
+    )]
+    TooManyDevices {
+        /// Maximum number of devices allowed.
+        limit: usize,
+        /// Every matched device, as `"Name (id)"`.
+        matched: Vec<String>,
+    },
+}
+
+/// Checks that at most `limit` devices matched, so a loose pattern such as
+/// `*` cannot grab every input node on the system.
+///
+/// # Errors
+///
+/// Returns [`DiscoveryError::TooManyDevices`] listing every matched device
+/// if there are more than `limit`. `None` allows any number.
+pub fn check_device_limit<'a, I>(matched: I, limit: Option<usize>) -> Result<(), DiscoveryError>
+where
+    I: IntoIterator<Item = &'a KeyboardInfo>,
+{
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+    let matched: Vec<&KeyboardInfo> = matched.into_iter().collect();
+    if matched.len() <= limit {
+        return Ok(());
+    }
+    Err(DiscoveryError::TooManyDevices {
+        limit,
+        matched: matched
+            .iter()
+            .map(|info| format!("{} ({})", info.name, info.device_id()))
+            .collect(),
+    })
+}
+
+/// Returns the entries of `requested` that are not in `available`.
+#[must_use]
+pub fn unknown_device_ids(requested: &[String], available: &[String]) -> Vec<String> {
+    requested
+        .iter()
+        .filter(|id| !available.contains(id))
+        .cloned()
+        .collect()
 }
 
 /// Information about a discovered keyboard device.
@@ -166,6 +219,8 @@ pub struct KeyboardInfo {
     pub serial: Option<String>,
     /// Physical location identifier if available.
     pub phys: Option<String>,
+    /// Input capabilities, or `None` if the platform cannot report them.
+    pub capabilities: Option<DeviceCapabilities>,
 }
 
 impl KeyboardInfo {
@@ -183,6 +238,18 @@ impl KeyboardInfo {
         // Fallback to path-based ID
         format!("path-{}", self.path.display())
     }
+
+    /// Returns the name shown by `list-devices`: `"Name (serial)"`, or just
+    /// the name when the device has no serial.
+    ///
+    /// The string can be pasted verbatim as a device pattern.
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        match self.serial.as_deref() {
+            Some(serial) if !serial.is_empty() => format!("{} ({})", self.name, serial),
+            _ => self.name.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -195,6 +262,57 @@ mod tests {
         assert_eq!(err.to_string(), "no keyboard devices found");
     }
 
+    #[test]
+    fn test_unknown_device_ids_reports_typos() {
+        let available = vec![
+            "serial-ABC".to_string(),
+            "path-/dev/input/event3".to_string(),
+        ];
+        let requested = vec!["serial-ABC".to_string(), "serial-ABD".to_string()];
+
+        assert_eq!(
+            unknown_device_ids(&requested, &available),
+            vec!["serial-ABD".to_string()]
+        );
+        assert!(unknown_device_ids(&available, &available).is_empty());
+
+        let err = DiscoveryError::UnknownDeviceIds {
+            unknown: vec!["serial-ABD".to_string()],
+            available,
+        };
+        assert_eq!(
+            err.to_string(),
+            "unknown device id(s): serial-ABD; matched devices: serial-ABC, path-/dev/input/event3"
+        );
+    }
+
+    #[test]
+    fn test_check_device_limit_lists_matched_devices() {
+        let keyboard = |event: u32, name: &str| KeyboardInfo {
+            path: std::path::PathBuf::from(format!("/dev/input/event{}", event)),
+            name: name.to_string(),
+            serial: None,
+            phys: None,
+            capabilities: None,
+        };
+        let matched = vec![keyboard(3, "USB Keyboard"), keyboard(7, "Touchpad")];
+
+        assert!(check_device_limit(&matched, None).is_ok());
+        assert!(check_device_limit(&matched, Some(2)).is_ok());
+
+        let err = check_device_limit(&matched, Some(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            DiscoveryError::TooManyDevices { limit: 1, ref matched } if matched.len() == 2
+        ));
+        assert_eq!(
+            err.to_string(),
+            "2 devices match the configuration, more than the limit of 1; tighten the \
+             device pattern or raise --max-devices. Matched: \
+             USB Keyboard (path-/dev/input/event3), Touchpad (path-/dev/input/event7)"
+        );
+    }
+
     #[test]
     fn test_keyboard_info_debug() {
         let info = KeyboardInfo {
@@ -202,6 +320,7 @@ mod tests {
             name: "Test Keyboard".to_string(),
             serial: Some("ABC123".to_string()),
             phys: Some("usb-0000:00:14.0-1/input0".to_string()),
+            capabilities: None,
         };
         let debug_str = format!("{:?}", info);
         assert!(debug_str.contains("Test Keyboard"));
@@ -215,6 +334,7 @@ mod tests {
             name: "Test Keyboard".to_string(),
             serial: None,
             phys: None,
+            capabilities: None,
         };
         let cloned = info.clone();
         assert_eq!(cloned.name, info.name);
@@ -228,12 +348,14 @@ mod tests {
             name: "Keyboard A".to_string(),
             serial: Some("SN1".to_string()),
             phys: None,
+            capabilities: None,
         };
         let info2 = KeyboardInfo {
             path: std::path::PathBuf::from("/dev/input/event0"),
             name: "Keyboard A".to_string(),
             serial: Some("SN1".to_string()),
             phys: None,
+            capabilities: None,
         };
         assert_eq!(info1, info2);
     }
@@ -245,16 +367,180 @@ mod tests {
             name: "Keyboard A".to_string(),
             serial: None,
             phys: None,
+            capabilities: None,
         };
         let info2 = KeyboardInfo {
             path: std::path::PathBuf::from("/dev/input/event1"),
             name: "Keyboard A".to_string(),
             serial: None,
             phys: None,
+            capabilities: None,
         };
         assert_ne!(info1, info2);
     }
 
+    fn identifier(pattern: &str, excludes: &[&str]) -> DeviceIdentifier {
+        DeviceIdentifier {
+            pattern: pattern.to_string(),
+            excludes: excludes.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    fn keyboard(name: &str) -> KeyboardInfo {
+        KeyboardInfo {
+            path: std::path::PathBuf::from("/dev/input/event0"),
+            name: name.to_string(),
+            serial: None,
+            phys: None,
+            capabilities: None,
+        }
+    }
+
+    #[test]
+    fn test_match_device_negated_pattern() {
+        let mouse = keyboard("Razer Gaming Mouse");
+        let kbd = keyboard("USB Keyboard");
+        assert!(!match_device(&mouse, "!*Mouse*"));
+        assert!(match_device(&kbd, "!*Mouse*"));
+    }
+
+    #[test]
+    fn test_match_device_pasted_display_name() {
+        let kbd = KeyboardInfo {
+            serial: Some("ABC123".to_string()),
+            ..keyboard("Logitech USB Keyboard")
+        };
+        assert_eq!(kbd.display_name(), "Logitech USB Keyboard (ABC123)");
+
+        assert!(match_device(&kbd, "Logitech USB Keyboard (ABC123)"));
+        assert!(match_device(&kbd, "logitech usb keyboard (abc123)"));
+        assert!(match_device(&kbd, "*Keyboard (ABC123)"));
+        assert!(!match_device(&kbd, "Logitech USB Keyboard (XYZ999)"));
+        assert!(!match_device(&kbd, "!Logitech USB Keyboard (ABC123)"));
+
+        // Devices without a serial are shown, and matched, by name alone
+        let plain = keyboard("Logitech USB Keyboard");
+        assert_eq!(plain.display_name(), "Logitech USB Keyboard");
+        assert!(!match_device(&plain, "Logitech USB Keyboard (ABC123)"));
+    }
+
+    #[test]
+    fn test_match_device_identifier_without_excludes() {
+        let kbd = keyboard("USB Keyboard");
+        assert!(match_device_identifier(&kbd, &identifier("*", &[])));
+        assert!(!match_device_identifier(&kbd, &identifier("Razer*", &[])));
+    }
+
+    #[test]
+    fn test_match_device_identifier_exclude_wins_over_include() {
+        let id = identifier("*", &["*Mouse*"]);
+        assert!(!match_device_identifier(
+            &keyboard("Razer Gaming Mouse"),
+            &id
+        ));
+        assert!(match_device_identifier(&keyboard("USB Keyboard"), &id));
+    }
+
+    #[test]
+    fn test_match_device_identifier_overlapping_include_and_exclude() {
+        // Both include and exclude target the same vendor prefix
+        let id = identifier("Logitech*", &["*Receiver*"]);
+        assert!(match_device_identifier(&keyboard("Logitech K120"), &id));
+        assert!(!match_device_identifier(
+            &keyboard("Logitech Unifying Receiver"),
+            &id
+        ));
+        assert!(!match_device_identifier(&keyboard("Razer BlackWidow"), &id));
+
+        // Identical include and exclude rejects everything
+        let id = identifier("*Keyboard*", &["*Keyboard*"]);
+        assert!(!match_device_identifier(&keyboard("USB Keyboard"), &id));
+    }
+
+    #[test]
+    fn test_match_device_identifier_multiple_excludes() {
+        let id = identifier("*", &["*Mouse*", "!*"]);
+        // "!*" as an exclusion is treated the same as "*"
+        assert!(!match_device_identifier(&keyboard("USB Keyboard"), &id));
+
+        let id = identifier("*", &["*Mouse*", "*Yubikey*"]);
+        assert!(!match_device_identifier(&keyboard("Yubikey OTP"), &id));
+        assert!(!match_device_identifier(&keyboard("Gaming Mouse"), &id));
+        assert!(match_device_identifier(&keyboard("USB Keyboard"), &id));
+    }
+
+    #[test]
+    fn test_match_order_sorts_by_priority_then_declaration() {
+        assert_eq!(match_order([0, 5, -10, 5, 0]), vec![1, 3, 0, 4, 2]);
+        assert_eq!(match_order([0, 0, 0]), vec![0, 1, 2]);
+        assert!(match_order([]).is_empty());
+    }
+
+    #[test]
+    fn test_find_matching_config_wildcard_declared_first_with_low_priority() {
+        let configs = [
+            DeviceConfig {
+                priority: -10,
+                ..DeviceConfig::new("*", Vec::new())
+            },
+            DeviceConfig::new("Logitech*", Vec::new()),
+        ];
+        assert_eq!(
+            find_matching_config(&keyboard("Logitech K120"), &configs),
+            Some(1)
+        );
+        assert_eq!(
+            find_matching_config(&keyboard("USB Keyboard"), &configs),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_find_matching_config_equal_priority_keeps_declaration_order() {
+        let configs = [
+            DeviceConfig::new("*", Vec::new()),
+            DeviceConfig::new("Logitech*", Vec::new()),
+        ];
+        assert_eq!(
+            find_matching_config(&keyboard("Logitech K120"), &configs),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_find_matching_config_overlapping_specific_patterns() {
+        let configs = [
+            DeviceConfig {
+                priority: 1,
+                ..DeviceConfig::new("Logitech*", Vec::new())
+            },
+            DeviceConfig {
+                priority: 5,
+                ..DeviceConfig::new("*K120*", Vec::new())
+            },
+            DeviceConfig {
+                priority: -10,
+                ..DeviceConfig::new("*", Vec::new())
+            },
+        ];
+        assert_eq!(
+            find_matching_config(&keyboard("Logitech K120"), &configs),
+            Some(1)
+        );
+        assert_eq!(
+            find_matching_config(&keyboard("Logitech MX Keys"), &configs),
+            Some(0)
+        );
+        assert_eq!(
+            find_matching_config(&keyboard("Razer BlackWidow"), &configs),
+            Some(2)
+        );
+        assert_eq!(
+            find_matching_config(&keyboard("Razer BlackWidow"), &configs[..2]),
+            None
+        );
+    }
+
     #[test]
     fn test_keyboard_info_equality_all_fields_matter() {
         // All fields contribute to equality per derive
@@ -263,12 +549,14 @@ mod tests {
             name: "Keyboard A".to_string(),
             serial: Some("SN1".to_string()),
             phys: Some("usb-1".to_string()),
+            capabilities: None,
         };
         let info2 = KeyboardInfo {
             path: std::path::PathBuf::from("/dev/input/event0"),
             name: "Keyboard B".to_string(), // Different name
             serial: Some("SN1".to_string()),
             phys: Some("usb-1".to_string()),
+            capabilities: None,
         };
         assert_ne!(info1, info2);
     }
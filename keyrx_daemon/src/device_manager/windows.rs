@@ -52,6 +52,7 @@ pub fn enumerate_keyboards() -> Result<Vec<KeyboardInfo>, DiscoveryError> {
                 name,
                 serial,
                 phys: None,
+                capabilities: Some(d.capabilities()),
             }
         })
         .collect();
@@ -80,7 +81,7 @@ impl ManagedDevice {
             info,
             input,
             lookup: KeyLookup::from_device_config(config),
-            state: DeviceState::new(),
+            state: DeviceState::for_device(config),
             config_index,
             device_handle,
         }
@@ -116,6 +117,7 @@ impl ManagedDevice {
 
     pub fn rebuild_lookup(&mut self, config: &DeviceConfig) {
         self.lookup = KeyLookup::from_device_config(config);
+        self.state.set_time_windows(&config.time_windows);
     }
 
     pub fn lookup_and_state_mut(&mut self) -> (&KeyLookup, &mut DeviceState) {
@@ -261,16 +263,12 @@ impl DeviceManager {
                 name: format!("Device {:x}", handle), // We might want better name if API gives it? currently path is \\?\...
                 serial: device_info.serial.clone(),
                 phys: None,
+                capabilities: Some(device_info.capabilities()),
             };
 
             // Attempt to match
-            let mut matched_config = None;
-            for (idx, config) in configs.iter().enumerate() {
-                if super::match_device(&keyboard_info, &config.identifier.pattern) {
-                    matched_config = Some((idx, config));
-                    break;
-                }
-            }
+            let matched_config = super::find_matching_config(&keyboard_info, configs)
+                .map(|idx| (idx, &configs[idx]));
 
             if let Some((config_idx, config)) = matched_config {
                 info!(
@@ -185,6 +185,10 @@ pub enum RecorderError {
     /// Mutex poisoned during recorder operation.
     #[error("Mutex poisoned: {0}")]
     MutexPoisoned(String),
+
+    /// Recorded events could not be turned into a macro mapping.
+    #[error("Invalid macro: {0}")]
+    InvalidMacro(String),
 }
 
 /// Configuration loading and validation errors.
@@ -1,11 +1,15 @@
 //! IPC command handlers for test mode.
 //!
 //! This module provides command handling logic for IPC requests, including
-//! profile activation and daemon status queries.
+//! profile activation, config reloads and daemon status queries.
 
-use super::{IpcRequest, IpcResponse};
+use super::{DeviceLatency, IpcRequest, IpcResponse, MAX_LOADED_CONFIG_BYTES};
 use crate::config::profile_manager::ProfileManager;
-use std::sync::Arc;
+use crate::config_loader::LoadedConfig;
+use crate::daemon::{LatencyRecorder, ReloadHandle};
+use crate::platform::{EventCounters, EventLog, EventPage};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
 /// Handler for IPC commands in test mode.
@@ -15,6 +19,14 @@ use tokio::sync::RwLock;
 pub struct IpcCommandHandler {
     profile_manager: Arc<ProfileManager>,
     daemon_running: Arc<RwLock<bool>>,
+    event_counters: Option<Arc<EventCounters>>,
+    event_log: Option<EventLog>,
+    loaded_config: Option<LoadedConfig>,
+    /// .krx file behind `loaded_config`, reloaded by `ReloadConfig`
+    config_path: Mutex<Option<PathBuf>>,
+    latency_recorder: Option<Arc<LatencyRecorder>>,
+    reload_handle: Option<ReloadHandle>,
+    web_port: Option<u16>,
 }
 
 impl IpcCommandHandler {
@@ -28,9 +40,80 @@ impl IpcCommandHandler {
         Self {
             profile_manager,
             daemon_running,
+            event_counters: None,
+            event_log: None,
+            loaded_config: None,
+            config_path: Mutex::new(None),
+            latency_recorder: None,
+            reload_handle: None,
+            web_port: None,
         }
     }
 
+    /// Reports the port the web server bound in `GetStatus`.
+    #[must_use]
+    pub fn with_web_port(mut self, port: u16) -> Self {
+        self.web_port = Some(port);
+        self
+    }
+
+    /// Serves `GetErrorCounts` from the given counters.
+    ///
+    /// Without counters the request is answered with an error.
+    #[must_use]
+    pub fn with_event_counters(mut self, counters: Arc<EventCounters>) -> Self {
+        self.event_counters = Some(counters);
+        self
+    }
+
+    /// Serves `GetEventsTail` and `GetEventsSince` from the given log.
+    ///
+    /// Without a log both requests are answered with an error.
+    #[must_use]
+    pub fn with_event_log(mut self, log: EventLog) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+
+    /// Serves `GetLatencyByDevice` from the given recorder.
+    ///
+    /// Without a recorder the request is answered with an error.
+    #[must_use]
+    pub fn with_latency_recorder(mut self, recorder: Arc<LatencyRecorder>) -> Self {
+        self.latency_recorder = Some(recorder);
+        self
+    }
+
+    /// Serves `GetLoadedConfig` from the given handle.
+    ///
+    /// Successful profile activations load the compiled profile into it.
+    /// Without a handle the request is answered with an error.
+    #[must_use]
+    pub fn with_loaded_config(mut self, loaded_config: LoadedConfig) -> Self {
+        self.loaded_config = Some(loaded_config);
+        self
+    }
+
+    /// Applies reloads and profile activations to the running daemon.
+    ///
+    /// `ReloadConfig` is then only answered once the daemon remaps with the
+    /// new configuration. Without a handle there is nothing to swap, so the
+    /// configuration is only loaded into the loaded config handle.
+    #[must_use]
+    pub fn with_reload_handle(mut self, handle: ReloadHandle) -> Self {
+        self.reload_handle = Some(handle);
+        self
+    }
+
+    /// Sets the .krx file that `ReloadConfig` without a path reloads.
+    ///
+    /// Profile activations and reloads with a path replace it.
+    #[must_use]
+    pub fn with_config_path(self, path: PathBuf) -> Self {
+        self.set_config_path(path);
+        self
+    }
+
     /// Handle an IPC request and return the appropriate response.
     ///
     /// # Arguments
@@ -58,14 +141,202 @@ impl IpcCommandHandler {
                     message: "GetLatencyMetrics not implemented yet".to_string(),
                 }
             }
-            IpcRequest::GetEventsTail { .. } => {
-                // Events tail not yet implemented
-                IpcResponse::Error {
-                    code: 5001,
-                    message: "GetEventsTail not implemented yet".to_string(),
+            IpcRequest::GetLatencyByDevice => self.handle_get_latency_by_device(),
+            IpcRequest::GetEventsTail { count } => {
+                self.handle_get_events("GetEventsTail", |log| log.tail(count))
+            }
+            IpcRequest::GetEventsSince { after_id } => {
+                self.handle_get_events("GetEventsSince", |log| log.since(after_id))
+            }
+            IpcRequest::GetErrorCounts => self.handle_get_error_counts(),
+            IpcRequest::GetOutputCounts => self.handle_get_output_counts(),
+            IpcRequest::GetLoadedConfig => self.handle_get_loaded_config(),
+            IpcRequest::ReloadConfig { path } => self.handle_reload_config(path),
+        }
+    }
+
+    /// Handle failed/dropped event counter query.
+    fn handle_get_error_counts(&self) -> IpcResponse {
+        let Some(counters) = &self.event_counters else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: "GetErrorCounts not available without event capture".to_string(),
+            };
+        };
+
+        let counts = counters.snapshot();
+        IpcResponse::ErrorCounts {
+            injection_failures: counts.injection_failures,
+            capture_errors: counts.capture_errors,
+            dropped_events: counts.dropped_events,
+        }
+    }
+
+    /// Handle output event/frame counter query.
+    fn handle_get_output_counts(&self) -> IpcResponse {
+        let Some(counters) = &self.event_counters else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: "GetOutputCounts not available without event capture".to_string(),
+            };
+        };
+
+        let counts = counters.output_counts();
+        IpcResponse::OutputCounts {
+            events: counts.events,
+            frames: counts.frames,
+        }
+    }
+
+    /// Handle per-device latency query.
+    fn handle_get_latency_by_device(&self) -> IpcResponse {
+        let Some(recorder) = &self.latency_recorder else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: "GetLatencyByDevice not available without event capture".to_string(),
+            };
+        };
+
+        let devices = recorder
+            .device_snapshots()
+            .into_iter()
+            .map(|(device_id, snapshot)| {
+                let latency = DeviceLatency {
+                    min_us: snapshot.min_us,
+                    avg_us: snapshot.avg_us,
+                    max_us: snapshot.max_us,
+                    p95_us: snapshot.p95_us,
+                    p99_us: snapshot.p99_us,
+                    sample_count: snapshot.sample_count,
+                };
+                (device_id, latency)
+            })
+            .collect();
+        IpcResponse::LatencyByDevice { devices }
+    }
+
+    /// Handle loaded configuration query.
+    ///
+    /// Configurations larger than [`MAX_LOADED_CONFIG_BYTES`] as JSON are
+    /// refused with an error instead of being sent.
+    fn handle_get_loaded_config(&self) -> IpcResponse {
+        let Some(loaded_config) = &self.loaded_config else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: "GetLoadedConfig not available without a loaded config handle".to_string(),
+            };
+        };
+
+        let config = loaded_config.get();
+        if let Some(config) = &config {
+            let size = match serde_json::to_vec(config) {
+                Ok(json) => json.len(),
+                Err(e) => {
+                    return IpcResponse::Error {
+                        code: 5003,
+                        message: format!("Failed to serialize loaded config: {}", e),
+                    }
                 }
+            };
+            if size > MAX_LOADED_CONFIG_BYTES {
+                return IpcResponse::Error {
+                    code: 5003,
+                    message: format!(
+                        "Loaded config is {} bytes as JSON, over the {} byte IPC limit",
+                        size, MAX_LOADED_CONFIG_BYTES
+                    ),
+                };
             }
         }
+
+        IpcResponse::LoadedConfig {
+            source_hash: config
+                .as_ref()
+                .map(|config| config.metadata.source_hash.clone()),
+            config,
+        }
+    }
+
+    /// Handle config reload request.
+    ///
+    /// Loads `path`, or the current .krx when `None`, into the daemon (see
+    /// [`with_reload_handle`](Self::with_reload_handle)). If no file was
+    /// loaded yet, the active profile's .krx is used. On failure the running
+    /// config and path are left unchanged.
+    fn handle_reload_config(&self, path: Option<String>) -> IpcResponse {
+        let Some(loaded_config) = &self.loaded_config else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: "ReloadConfig not available without a loaded config handle".to_string(),
+            };
+        };
+
+        let Some(path) = path
+            .map(PathBuf::from)
+            .or_else(|| self.current_config_path())
+        else {
+            return IpcResponse::Error {
+                code: 5004,
+                message: "No config loaded to reload; pass a .krx path".to_string(),
+            };
+        };
+
+        log::info!("IPC: Reloading config from {}", path.display());
+        if let Err(e) = self.load(loaded_config, &path) {
+            log::error!("IPC: Failed to reload {}: {}", path.display(), e);
+            return IpcResponse::Error {
+                code: 5004,
+                message: format!("Config reload failed: {}", e),
+            };
+        }
+
+        let source_hash = loaded_config.source_hash().unwrap_or_default();
+        let response = IpcResponse::ConfigReloaded {
+            path: path.display().to_string(),
+            source_hash,
+        };
+        self.set_config_path(path);
+        response
+    }
+
+    /// Returns the .krx file to reload: the last one loaded, else the active
+    /// profile's.
+    fn current_config_path(&self) -> Option<PathBuf> {
+        let current = self
+            .config_path
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        current.or_else(|| {
+            let name = self.profile_manager.get_active().ok().flatten()?;
+            self.profile_manager
+                .get(&name)
+                .map(|profile| profile.krx_path.clone())
+        })
+    }
+
+    fn set_config_path(&self, path: PathBuf) {
+        *self.config_path.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+    }
+
+    /// Handle recent event queries.
+    fn handle_get_events(
+        &self,
+        request: &str,
+        query: impl FnOnce(&EventLog) -> EventPage,
+    ) -> IpcResponse {
+        let Some(log) = &self.event_log else {
+            return IpcResponse::Error {
+                code: 5001,
+                message: format!("{} not available without event capture", request),
+            };
+        };
+
+        let page = query(log);
+        IpcResponse::Events {
+            events: page.events,
+            last_id: page.last_id,
+        }
     }
 
     /// Handle profile activation request.
@@ -89,6 +360,7 @@ impl IpcCommandHandler {
                         result.compile_time_ms,
                         result.reload_time_ms
                     );
+                    self.record_loaded_profile(&name);
                     IpcResponse::ProfileActivated { name }
                 } else {
                     let error_msg = result.error.unwrap_or_else(|| "Unknown error".to_string());
@@ -109,6 +381,30 @@ impl IpcCommandHandler {
         }
     }
 
+    /// Loads `path` into the daemon, or into `loaded_config` when there is
+    /// no daemon to reload.
+    fn load(&self, loaded_config: &LoadedConfig, path: &Path) -> Result<(), String> {
+        match &self.reload_handle {
+            Some(daemon) => daemon.reload(path.to_path_buf()).map_err(|e| e.to_string()),
+            None => loaded_config.load_krx(path).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Loads the just-activated profile's .krx into the daemon.
+    fn record_loaded_profile(&self, name: &str) {
+        let Some(loaded_config) = &self.loaded_config else {
+            return;
+        };
+        let Some(profile) = self.profile_manager.get(name) else {
+            return;
+        };
+        if let Err(e) = self.load(loaded_config, &profile.krx_path) {
+            log::warn!("IPC: Failed to record loaded config of '{}': {}", name, e);
+            return;
+        }
+        self.set_config_path(profile.krx_path.clone());
+    }
+
     /// Handle daemon status query.
     ///
     /// Returns the current daemon running state along with other status information.
@@ -120,8 +416,9 @@ impl IpcCommandHandler {
         // Get active profile name (ProfileManager.get_active() is immutable, so no unsafe needed)
         let active_profile = self.profile_manager.get_active().ok().flatten();
 
-        // Get device count (in test mode, this is always 0)
+        // Get device counts (test mode captures no devices, so both are 0)
         let device_count = 0;
+        let active_device_count = 0;
 
         // Get uptime (for now, just return 0 - we can add proper uptime tracking later)
         let uptime_secs = 0;
@@ -131,6 +428,8 @@ impl IpcCommandHandler {
             uptime_secs,
             active_profile,
             device_count,
+            active_device_count,
+            web_port: self.web_port,
         }
     }
 }
@@ -165,14 +464,29 @@ mod tests {
                 uptime_secs: _,
                 active_profile: _,
                 device_count,
+                active_device_count,
+                web_port,
             } => {
                 assert!(running);
                 assert_eq!(device_count, 0);
+                assert_eq!(active_device_count, 0);
+                assert_eq!(web_port, None);
             }
             _ => panic!("Expected Status response"),
         }
     }
 
+    #[tokio::test]
+    async fn test_get_status_reports_web_port() {
+        let (handler, _temp_dir) = setup_test_handler().await;
+        let handler = handler.with_web_port(9868);
+
+        match handler.handle(IpcRequest::GetStatus).await {
+            IpcResponse::Status { web_port, .. } => assert_eq!(web_port, Some(9868)),
+            other => panic!("Expected Status response, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_activate_profile_not_found() {
         let (handler, _temp_dir) = setup_test_handler().await;
@@ -192,6 +506,202 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_get_error_counts() {
+        let (handler, _temp_dir) = setup_test_handler().await;
+
+        // Without counters (test mode has no event capture)
+        let response = handler.handle(IpcRequest::GetErrorCounts).await;
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        let counters = Arc::new(EventCounters::new());
+        counters.record_capture_error();
+        counters.record_dropped_event();
+        counters.record_dropped_event();
+        let handler = handler.with_event_counters(Arc::clone(&counters));
+
+        let response = handler.handle(IpcRequest::GetErrorCounts).await;
+        assert_eq!(
+            response,
+            IpcResponse::ErrorCounts {
+                injection_failures: 0,
+                capture_errors: 1,
+                dropped_events: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_output_counts() {
+        let (handler, _temp_dir) = setup_test_handler().await;
+
+        let response = handler.handle(IpcRequest::GetOutputCounts).await;
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        let counters = Arc::new(EventCounters::new());
+        counters.record_output_frames(3, 5);
+        let handler = handler.with_event_counters(counters);
+
+        let response = handler.handle(IpcRequest::GetOutputCounts).await;
+        assert_eq!(
+            response,
+            IpcResponse::OutputCounts {
+                events: 5,
+                frames: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_latency_by_device() {
+        let (handler, _temp_dir) = setup_test_handler().await;
+
+        // Without a recorder (test mode has no event capture)
+        let response = handler.handle(IpcRequest::GetLatencyByDevice).await;
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        let recorder = Arc::new(LatencyRecorder::new());
+        recorder.record_for_device(Some("usb-kbd"), 100);
+        recorder.record_for_device(Some("usb-kbd"), 200);
+        recorder.record_for_device(Some("usb-numpad"), 50);
+        recorder.record_for_device(None, 75);
+        let handler = handler.with_latency_recorder(recorder);
+
+        let response = handler.handle(IpcRequest::GetLatencyByDevice).await;
+        let IpcResponse::LatencyByDevice { devices } = response else {
+            panic!("Expected LatencyByDevice response");
+        };
+        assert_eq!(
+            devices.keys().collect::<Vec<_>>(),
+            vec!["usb-kbd", "usb-numpad"]
+        );
+        assert_eq!(devices["usb-kbd"].sample_count, 2);
+        assert_eq!(devices["usb-numpad"].sample_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_loaded_config() {
+        use keyrx_core::config::{ConfigRoot, Metadata, Version};
+
+        let (handler, _temp_dir) = setup_test_handler().await;
+
+        // Without a handle
+        let response = handler.handle(IpcRequest::GetLoadedConfig).await;
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        // Pass-through mode
+        let loaded_config = LoadedConfig::new();
+        let handler = handler.with_loaded_config(loaded_config.clone());
+        let response = handler.handle(IpcRequest::GetLoadedConfig).await;
+        assert_eq!(
+            response,
+            IpcResponse::LoadedConfig {
+                source_hash: None,
+                config: None,
+            }
+        );
+
+        let config = ConfigRoot {
+            version: Version::current(),
+            devices: Vec::new(),
+            metadata: Metadata {
+                compilation_timestamp: 0,
+                compiler_version: "test".to_string(),
+                source_hash: "abc123".to_string(),
+            },
+        };
+        loaded_config.set(Some(config.clone()));
+        let response = handler.handle(IpcRequest::GetLoadedConfig).await;
+        assert_eq!(
+            response,
+            IpcResponse::LoadedConfig {
+                source_hash: Some("abc123".to_string()),
+                config: Some(config),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload_config() {
+        use keyrx_core::config::{ConfigRoot, Metadata, Version};
+
+        let write_krx = |dir: &TempDir, name: &str, source_hash: &str| {
+            let config = ConfigRoot {
+                version: Version::current(),
+                devices: Vec::new(),
+                metadata: Metadata {
+                    compilation_timestamp: 0,
+                    compiler_version: "test".to_string(),
+                    source_hash: source_hash.to_string(),
+                },
+            };
+            let path = dir.path().join(name);
+            let bytes = keyrx_compiler::serialize::serialize(&config).unwrap();
+            std::fs::write(&path, bytes).unwrap();
+            path
+        };
+
+        let (handler, temp_dir) = setup_test_handler().await;
+
+        // Without a handle
+        let response = handler
+            .handle(IpcRequest::ReloadConfig { path: None })
+            .await;
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        // Nothing loaded yet and no active profile
+        let loaded_config = LoadedConfig::new();
+        let handler = handler.with_loaded_config(loaded_config.clone());
+        let response = handler
+            .handle(IpcRequest::ReloadConfig { path: None })
+            .await;
+        assert!(matches!(response, IpcResponse::Error { code: 5004, .. }));
+
+        // Switch to a new path
+        let first = write_krx(&temp_dir, "first.krx", "hash1");
+        let response = handler
+            .handle(IpcRequest::ReloadConfig {
+                path: Some(first.display().to_string()),
+            })
+            .await;
+        assert_eq!(
+            response,
+            IpcResponse::ConfigReloaded {
+                path: first.display().to_string(),
+                source_hash: "hash1".to_string(),
+            }
+        );
+
+        // Reload the current path after it was recompiled
+        write_krx(&temp_dir, "first.krx", "hash2");
+        let response = handler
+            .handle(IpcRequest::ReloadConfig { path: None })
+            .await;
+        assert_eq!(
+            response,
+            IpcResponse::ConfigReloaded {
+                path: first.display().to_string(),
+                source_hash: "hash2".to_string(),
+            }
+        );
+
+        // A bad path keeps the running config and current path
+        let response = handler
+            .handle(IpcRequest::ReloadConfig {
+                path: Some(temp_dir.path().join("missing.krx").display().to_string()),
+            })
+            .await;
+        assert!(matches!(response, IpcResponse::Error { code: 5004, .. }));
+        assert_eq!(loaded_config.source_hash().as_deref(), Some("hash2"));
+        let response = handler
+            .handle(IpcRequest::ReloadConfig { path: None })
+            .await;
+        assert!(matches!(
+            response,
+            IpcResponse::ConfigReloaded { ref path, .. } if *path == first.display().to_string()
+        ));
+    }
+
     #[tokio::test]
     async fn test_unimplemented_commands() {
         let (handler, _temp_dir) = setup_test_handler().await;
@@ -215,17 +725,42 @@ mod tests {
             }
             _ => panic!("Expected Error response"),
         }
+    }
 
-        // Test GetEventsTail
+    #[tokio::test]
+    async fn test_get_events_tail_and_since() {
+        let (handler, _temp_dir) = setup_test_handler().await;
+
+        // Without a log (test mode has no event capture)
         let response = handler
             .handle(IpcRequest::GetEventsTail { count: 10 })
             .await;
-        match response {
-            IpcResponse::Error { code, message } => {
-                assert_eq!(code, 5001);
-                assert!(message.contains("not implemented"));
+        assert!(matches!(response, IpcResponse::Error { code: 5001, .. }));
+
+        let log = EventLog::new(10);
+        log.push("press A -> B".to_string());
+        log.push("release A -> B".to_string());
+        let handler = handler.with_event_log(log.clone());
+
+        let response = handler.handle(IpcRequest::GetEventsTail { count: 1 }).await;
+        assert_eq!(
+            response,
+            IpcResponse::Events {
+                events: vec!["release A -> B".to_string()],
+                last_id: 2,
             }
-            _ => panic!("Expected Error response"),
-        }
+        );
+
+        log.push("press C -> C".to_string());
+        let response = handler
+            .handle(IpcRequest::GetEventsSince { after_id: 2 })
+            .await;
+        assert_eq!(
+            response,
+            IpcResponse::Events {
+                events: vec!["press C -> C".to_string()],
+                last_id: 3,
+            }
+        );
     }
 }
@@ -4,7 +4,9 @@
 //! to communicate with CLI commands. The daemon listens on a Unix socket at
 //! `/tmp/keyrx-daemon.sock` and responds to requests for status, state, and metrics.
 
+use keyrx_core::config::ConfigRoot;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -18,6 +20,12 @@ pub const DEFAULT_SOCKET_PATH: &str = "/tmp/keyrx-daemon.sock";
 /// Default timeout for IPC requests (5 seconds)
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Largest loaded config (as JSON) returned by `GetLoadedConfig` (4 MiB)
+///
+/// Responses are a single JSON line, so a larger config is refused rather
+/// than sent.
+pub const MAX_LOADED_CONFIG_BYTES: usize = 4 * 1024 * 1024;
+
 /// IPC request types sent from CLI to daemon
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -28,10 +36,25 @@ pub enum IpcRequest {
     GetState,
     /// Get latency metrics (min, avg, max, p95, p99)
     GetLatencyMetrics,
+    /// Get latency metrics of each input device
+    GetLatencyByDevice,
     /// Get tail of recent events (last N events)
     GetEventsTail { count: usize },
+    /// Get events newer than a cursor (the `last_id` of a previous response)
+    GetEventsSince { after_id: u64 },
+    /// Get counters for failed and dropped events
+    GetErrorCounts,
+    /// Get the number of output events and the frames they were sent in
+    GetOutputCounts,
+    /// Get the configuration the daemon is currently remapping with
+    GetLoadedConfig,
     /// Activate a profile by name (test mode only)
     ActivateProfile { name: String },
+    /// Reload the current .krx, or switch to the .krx at `path`
+    ReloadConfig {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+    },
 }
 
 /// IPC response types sent from daemon to CLI
@@ -43,10 +66,25 @@ pub enum IpcResponse {
         running: bool,
         uptime_secs: u64,
         active_profile: Option<String>,
+        /// Devices matched by the configuration
         device_count: usize,
+        /// Matched devices actually being captured (grabbed)
+        #[serde(default)]
+        active_device_count: usize,
+        /// Port the web server bound, which may differ from the configured
+        /// one when it was taken
+        #[serde(default)]
+        web_port: Option<u16>,
     },
     /// Current state (255-bit modifier/lock state)
-    State { state: Vec<bool> },
+    ///
+    /// When `locks` is non-empty, `state` holds only the modifier bits
+    /// (index = MD_XX id) and `locks` the lock bits (index = LK_XX id).
+    State {
+        state: Vec<bool>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        locks: Vec<bool>,
+    },
     /// Latency metrics in microseconds
     Latency {
         min_us: u64,
@@ -55,14 +93,56 @@ pub enum IpcResponse {
         p95_us: u64,
         p99_us: u64,
     },
+    /// Latency metrics of each input device, keyed by device id
+    LatencyByDevice {
+        devices: BTreeMap<String, DeviceLatency>,
+    },
     /// Recent events
-    Events { events: Vec<String> },
+    ///
+    /// `last_id` is the id of the newest recorded event; pass it to
+    /// `GetEventsSince` to receive only events after this response.
+    Events {
+        events: Vec<String>,
+        #[serde(default)]
+        last_id: u64,
+    },
+    /// Failed and dropped event counters since daemon start
+    ErrorCounts {
+        injection_failures: u64,
+        capture_errors: u64,
+        dropped_events: u64,
+    },
+    /// Output events and `SYN_REPORT` frames written since daemon start
+    OutputCounts { events: u64, frames: u64 },
+    /// Configuration currently remapping (`None` in pass-through mode)
+    ///
+    /// `source_hash` repeats `config.metadata.source_hash` for quick
+    /// comparison against a compiled file.
+    LoadedConfig {
+        source_hash: Option<String>,
+        config: Option<ConfigRoot>,
+    },
     /// Profile activation result (test mode only)
     ProfileActivated { name: String },
+    /// Config reload result: the .krx now loaded and its source hash
+    ConfigReloaded { path: String, source_hash: String },
     /// Error response
     Error { code: u16, message: String },
 }
 
+/// Latency statistics of one input device in microseconds
+///
+/// Computed over the device's most recent samples.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeviceLatency {
+    pub min_us: u64,
+    pub avg_us: u64,
+    pub max_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub sample_count: u64,
+}
+
 /// IPC error types
 #[derive(Debug, Error)]
 pub enum IpcError {
@@ -134,6 +214,25 @@ mod tests {
         assert!(json.contains("100"));
     }
 
+    #[test]
+    fn test_ipc_events_since_round_trip() {
+        let req = IpcRequest::GetEventsSince { after_id: 42 };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"get_events_since","after_id":42}"#);
+        assert_eq!(serde_json::from_str::<IpcRequest>(&json).unwrap(), req);
+
+        // Responses without a cursor still deserialize
+        let legacy: IpcResponse =
+            serde_json::from_str(r#"{"type":"events","events":["a"]}"#).unwrap();
+        assert_eq!(
+            legacy,
+            IpcResponse::Events {
+                events: vec!["a".to_string()],
+                last_id: 0,
+            }
+        );
+    }
+
     #[test]
     fn test_ipc_response_status_serialization() {
         let resp = IpcResponse::Status {
@@ -141,12 +240,117 @@ mod tests {
             uptime_secs: 3600,
             active_profile: Some("default".to_string()),
             device_count: 2,
+            active_device_count: 1,
+            web_port: Some(9868),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        let deserialized: IpcResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(resp, deserialized);
+    }
+
+    #[test]
+    fn test_ipc_response_status_active_count_optional() {
+        // Responses from daemons without an active count still deserialize
+        let legacy: IpcResponse = serde_json::from_str(
+            r#"{"type":"status","running":true,"uptime_secs":5,"active_profile":null,"device_count":3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            legacy,
+            IpcResponse::Status {
+                running: true,
+                uptime_secs: 5,
+                active_profile: None,
+                device_count: 3,
+                active_device_count: 0,
+                web_port: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_ipc_response_state_locks_optional() {
+        // Responses without a `locks` field still deserialize
+        let legacy: IpcResponse =
+            serde_json::from_str(r#"{"type":"state","state":[true,false]}"#).unwrap();
+        assert_eq!(
+            legacy,
+            IpcResponse::State {
+                state: vec![true, false],
+                locks: Vec::new(),
+            }
+        );
+
+        let resp = IpcResponse::State {
+            state: vec![false; 4],
+            locks: vec![true; 4],
         };
         let json = serde_json::to_string(&resp).unwrap();
         let deserialized: IpcResponse = serde_json::from_str(&json).unwrap();
         assert_eq!(resp, deserialized);
     }
 
+    #[test]
+    fn test_ipc_latency_by_device_round_trip() {
+        let req = IpcRequest::GetLatencyByDevice;
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"get_latency_by_device"}"#);
+
+        let mut devices = BTreeMap::new();
+        devices.insert(
+            "usb-kbd".to_string(),
+            DeviceLatency {
+                min_us: 10,
+                avg_us: 20,
+                max_us: 90,
+                p95_us: 60,
+                p99_us: 80,
+                sample_count: 42,
+            },
+        );
+        let resp = IpcResponse::LatencyByDevice { devices };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert_eq!(serde_json::from_str::<IpcResponse>(&json).unwrap(), resp);
+    }
+
+    #[test]
+    fn test_ipc_loaded_config_round_trip() {
+        let req = IpcRequest::GetLoadedConfig;
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"get_loaded_config"}"#);
+        assert_eq!(serde_json::from_str::<IpcRequest>(&json).unwrap(), req);
+
+        // Pass-through mode has no config
+        let resp = IpcResponse::LoadedConfig {
+            source_hash: None,
+            config: None,
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(!json.contains('\n'));
+        assert_eq!(serde_json::from_str::<IpcResponse>(&json).unwrap(), resp);
+    }
+
+    #[test]
+    fn test_ipc_reload_config_round_trip() {
+        let req = IpcRequest::ReloadConfig { path: None };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(json, r#"{"type":"reload_config"}"#);
+        assert_eq!(serde_json::from_str::<IpcRequest>(&json).unwrap(), req);
+
+        let req = IpcRequest::ReloadConfig {
+            path: Some("/tmp/new.krx".to_string()),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(serde_json::from_str::<IpcRequest>(&json).unwrap(), req);
+
+        let resp = IpcResponse::ConfigReloaded {
+            path: "/tmp/new.krx".to_string(),
+            source_hash: "abc123".to_string(),
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert_eq!(serde_json::from_str::<IpcResponse>(&json).unwrap(), resp);
+    }
+
     #[test]
     fn test_ipc_response_error_serialization() {
         let resp = IpcResponse::Error {
@@ -3,6 +3,7 @@
 //! This module provides a Unix socket server that listens for IPC commands
 //! in test mode, enabling profile activation and daemon status queries.
 
+use super::commands::IpcCommandHandler;
 use super::{IpcRequest, IpcResponse};
 use interprocess::local_socket::{LocalSocketListener, LocalSocketStream};
 use std::io::{BufRead, BufReader, Write};
@@ -146,6 +147,34 @@ impl Drop for IpcServer {
     }
 }
 
+/// Binds an IPC server at `socket_path` and answers its requests with
+/// `handler` on a background thread.
+///
+/// Returns once the socket is bound, so clients can connect right away.
+pub fn spawn_command_server(
+    socket_path: PathBuf,
+    handler: Arc<IpcCommandHandler>,
+) -> Result<(), std::io::Error> {
+    let mut server = IpcServer::new(socket_path)?;
+    server.start()?;
+
+    std::thread::spawn(move || {
+        let handler_fn = Arc::new(Mutex::new(
+            move |request: IpcRequest| -> Result<IpcResponse, String> {
+                // Each request runs the async handler on its own runtime
+                let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+                Ok(rt.block_on(handler.handle(request)))
+            },
+        ));
+
+        if let Err(e) = server.handle_connections(handler_fn) {
+            log::error!("IPC server error: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
 /// Get the test mode IPC socket path for the current process
 pub fn get_test_socket_path() -> PathBuf {
     let pid = std::process::id();
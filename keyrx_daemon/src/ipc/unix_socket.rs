@@ -256,6 +256,8 @@ mod tests {
                 uptime_secs: 100,
                 active_profile: Some("test".to_string()),
                 device_count: 1,
+                active_device_count: 1,
+                web_port: None,
             };
             let json = serde_json::to_string(&response).expect("Failed to serialize response");
             conn.write_all(json.as_bytes()).unwrap();
@@ -278,11 +280,14 @@ mod tests {
                 uptime_secs,
                 active_profile,
                 device_count,
+                active_device_count,
+                ..
             } => {
                 assert!(running);
                 assert_eq!(uptime_secs, 100);
                 assert_eq!(active_profile, Some("test".to_string()));
                 assert_eq!(device_count, 1);
+                assert_eq!(active_device_count, 1);
             }
             _ => panic!("Unexpected response type"),
         }
@@ -361,6 +366,8 @@ mod tests {
                 uptime_secs: 100,
                 active_profile: Some("test".to_string()),
                 device_count: 1,
+                active_device_count: 1,
+                web_port: None,
             };
             let json = serde_json::to_string(&response).unwrap();
             conn.write_all(json.as_bytes()).unwrap();
@@ -435,6 +442,8 @@ mod tests {
                 uptime_secs: 100,
                 active_profile: Some("test".to_string()),
                 device_count: 1,
+                active_device_count: 1,
+                web_port: None,
             };
             let json = serde_json::to_string(&response).unwrap();
             conn.write_all(json.as_bytes()).unwrap();
@@ -474,6 +483,8 @@ mod tests {
                 uptime_secs: 200,
                 active_profile: Some("test2".to_string()),
                 device_count: 2,
+                active_device_count: 2,
+                web_port: None,
             };
             let json = serde_json::to_string(&response).unwrap();
             conn.write_all(json.as_bytes()).unwrap();
@@ -5,7 +5,9 @@
 //! - Store events in an in-memory buffer
 //! - Toggle recording mode on/off
 //! - Export recorded events for macro generation
+//! - Convert a recording into a `macro()` mapping for the DSL
 
+use crate::config::rhai_generator::{KeyAction, MacroStep, RhaiGenerator};
 use crate::error::RecorderError;
 use keyrx_core::runtime::KeyEvent;
 use std::sync::{Arc, Mutex};
@@ -14,6 +16,10 @@ use tokio::sync::mpsc;
 /// Maximum number of events to store in the recording buffer
 const MAX_EVENTS: usize = 10_000;
 
+/// Default shortest gap between events, in milliseconds, kept as a `wait`
+/// step when converting a recording into a macro
+pub const DEFAULT_MIN_DELAY_MS: u16 = 20;
+
 /// Macro event with relative timestamp from recording start
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MacroEvent {
@@ -221,6 +227,33 @@ impl MacroRecorder {
         Ok(())
     }
 
+    /// Converts the recorded events into a macro action for `RhaiGenerator`
+    ///
+    /// See [`macro_steps`] for how timing is collapsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RecorderError::MutexPoisoned` if a mutex is poisoned.
+    pub fn to_macro_action(&self, min_delay_ms: u16) -> Result<KeyAction, RecorderError> {
+        let events = self.get_recorded_events()?;
+        Ok(KeyAction::Macro {
+            sequence: macro_steps(&events, min_delay_ms),
+        })
+    }
+
+    /// Renders the recorded events as a `macro("<key>", [...]);` line
+    /// binding them to `key`
+    ///
+    /// # Errors
+    ///
+    /// Returns `RecorderError::MutexPoisoned` if a mutex is poisoned, or
+    /// `RecorderError::InvalidMacro` if `key` is not a valid key name.
+    pub fn to_macro_snippet(&self, key: &str, min_delay_ms: u16) -> Result<String, RecorderError> {
+        let events = self.get_recorded_events()?;
+        RhaiGenerator::macro_snippet(key, &macro_steps(&events, min_delay_ms))
+            .map_err(|e| RecorderError::InvalidMacro(e.to_string()))
+    }
+
     /// Runs the event loop that receives events from the event bus
     ///
     /// This async method continuously receives events from the event bus
@@ -268,6 +301,35 @@ impl MacroRecorder {
     }
 }
 
+/// Converts recorded events into macro steps
+///
+/// A gap of at least `min_delay_ms` between two events becomes a `wait`
+/// step (capped at `u16::MAX`); shorter gaps are dropped, so typing jitter
+/// does not end up in the macro. Auto-repeat events are skipped.
+pub fn macro_steps(events: &[MacroEvent], min_delay_ms: u16) -> Vec<MacroStep> {
+    let mut steps = Vec::new();
+    let mut last_timestamp_us = None;
+
+    for recorded in events.iter().filter(|e| !e.event.is_repeat()) {
+        if let Some(last) = last_timestamp_us {
+            let gap_ms = recorded.relative_timestamp_us.saturating_sub(last) / 1000;
+            if gap_ms >= u64::from(min_delay_ms) && gap_ms > 0 {
+                steps.push(MacroStep::Wait(u16::try_from(gap_ms).unwrap_or(u16::MAX)));
+            }
+        }
+        last_timestamp_us = Some(recorded.relative_timestamp_us);
+
+        let key = format!("VK_{:?}", recorded.event.keycode());
+        steps.push(if recorded.event.is_press() {
+            MacroStep::Press(key)
+        } else {
+            MacroStep::Release(key)
+        });
+    }
+
+    steps
+}
+
 impl Default for MacroRecorder {
     fn default() -> Self {
         Self::new()
@@ -325,6 +387,49 @@ mod tests {
         assert_eq!(events[1].relative_timestamp_us, 100); // Second event 100us later
     }
 
+    #[test]
+    fn test_macro_snippet_collapses_short_gaps_and_parses_back() {
+        let recorder = MacroRecorder::new();
+        recorder.start_recording().unwrap();
+        for event in [
+            KeyEvent::press(KeyCode::H).with_timestamp(1_000_000),
+            KeyEvent::repeat(KeyCode::H).with_timestamp(1_004_000),
+            KeyEvent::release(KeyCode::H).with_timestamp(1_005_000),
+            KeyEvent::press(KeyCode::I).with_timestamp(1_155_000),
+            KeyEvent::release(KeyCode::I).with_timestamp(1_170_000),
+        ] {
+            recorder.capture_event(event).unwrap();
+        }
+        recorder.stop_recording().unwrap();
+
+        let expected = vec![
+            MacroStep::Press("VK_H".to_string()),
+            MacroStep::Release("VK_H".to_string()),
+            MacroStep::Wait(150),
+            MacroStep::Press("VK_I".to_string()),
+            MacroStep::Release("VK_I".to_string()),
+        ];
+        assert_eq!(
+            recorder.to_macro_action(DEFAULT_MIN_DELAY_MS).unwrap(),
+            KeyAction::Macro {
+                sequence: expected.clone()
+            }
+        );
+
+        let snippet = recorder
+            .to_macro_snippet("VK_F13", DEFAULT_MIN_DELAY_MS)
+            .unwrap();
+        assert_eq!(
+            RhaiGenerator::parse_macro_snippet(&snippet).unwrap(),
+            ("VK_F13".to_string(), expected)
+        );
+
+        // A threshold of 0 keeps every millisecond-or-longer gap
+        let steps = macro_steps(&recorder.get_recorded_events().unwrap(), 0);
+        assert_eq!(steps[1], MacroStep::Wait(5));
+        assert!(recorder.to_macro_snippet("F13", 0).is_err());
+    }
+
     #[test]
     fn test_relative_timestamps() {
         let recorder = MacroRecorder::new();
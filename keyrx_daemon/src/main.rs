@@ -19,6 +19,7 @@
 // Note: platform and web modules are used via the library (keyrx_daemon::platform)
 
 use clap::{Parser, Subcommand};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::process;
 
@@ -44,9 +45,26 @@ enum Commands {
     Run {
         /// Path to the .krx configuration file compiled by keyrx_compiler.
         /// If not specified, uses the active profile from %APPDATA%\keyrx.
+        ///
+        /// A `.rhai` file is compiled in memory at startup and on reload,
+        /// replacing the active profile. Handy during development; a .krx
+        /// starts faster.
         #[arg(short, long, value_name = "FILE")]
         config: Option<PathBuf>,
 
+        /// Activate the profile NAME and run it, instead of `--config`.
+        ///
+        /// The profile is compiled first and stays the active profile, so
+        /// `status` reports it and the next start without options uses it.
+        #[arg(
+            short,
+            long,
+            value_name = "NAME",
+            alias = "profile-on-start",
+            conflicts_with = "config"
+        )]
+        profile: Option<String>,
+
         /// Enable debug logging for verbose output.
         ///
         /// This increases log verbosity to show individual key events and
@@ -54,12 +72,126 @@ enum Commands {
         #[arg(short, long)]
         debug: bool,
 
+        /// Also write log output to FILE, for attaching to bug reports.
+        ///
+        /// Logs still go to stderr. The file is rotated at 5 MB, keeping one
+        /// previous file as `FILE.1`, so it never uses more than about 10 MB.
+        #[arg(long, value_name = "FILE", conflicts_with = "monitor")]
+        log_file: Option<PathBuf>,
+
         /// Enable test mode with IPC infrastructure but without keyboard capture.
         ///
         /// Only available in debug builds for security. Enables full IPC
         /// infrastructure for profile activation and daemon status queries.
         #[arg(long)]
         test_mode: bool,
+
+        /// Write the daemon PID to this file and refuse to start if another
+        /// daemon recorded in it is still running. Removed on clean shutdown.
+        #[arg(long, value_name = "PATH")]
+        pidfile: Option<PathBuf>,
+
+        /// File used to persist active locks across restarts.
+        ///
+        /// Defaults to `keyrx/lock_state.json` in the user runtime directory
+        /// (`$XDG_RUNTIME_DIR` on Linux). Without this option or a runtime
+        /// directory, locks are not persisted.
+        #[arg(long, value_name = "PATH")]
+        lock_state: Option<PathBuf>,
+
+        /// Address for the web server to listen on.
+        ///
+        /// Defaults to the `web_addr` setting, or 127.0.0.1 on the configured
+        /// port (9867). Non-loopback addresses such as 0.0.0.0 also require
+        /// `--web-allow-remote`.
+        #[arg(long, value_name = "ADDR")]
+        web_addr: Option<SocketAddr>,
+
+        /// Allow the web server to listen on a non-loopback address.
+        ///
+        /// Anyone who can reach the address and knows the API token can change
+        /// the configuration, so only use this on trusted networks.
+        #[arg(long)]
+        web_allow_remote: bool,
+
+        /// Try up to N following ports if the web port is taken.
+        ///
+        /// The port actually bound is logged, reported by `status` and saved
+        /// as the `port` setting. Defaults to 9 on Windows and 0 elsewhere.
+        #[arg(long = "bind-retry", value_name = "N")]
+        bind_retry: Option<u16>,
+
+        /// Grab only the device with this ID (repeatable).
+        ///
+        /// Other matched keyboards are left alone and keep typing normally,
+        /// which is handy for trying a layout on one board. IDs are the
+        /// `serial-...` or `path-...` values reported by the daemon; an
+        /// unknown ID is an error.
+        #[arg(long = "only-device", value_name = "ID")]
+        only_device: Vec<String>,
+
+        /// Refuse to start if more than N devices match the config (Linux).
+        ///
+        /// The matched devices are listed instead of grabbed, so a loose
+        /// pattern such as `*` that also catches touchpads or other stray
+        /// keyboard interfaces can be tightened first. Devices plugged in
+        /// later are not grabbed past the limit. Ignored with `--only-device`.
+        #[arg(
+            long = "max-devices",
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        max_devices: Option<usize>,
+
+        /// Name of the virtual output keyboard (Linux).
+        ///
+        /// Useful for udev rules or to tell several daemons apart. Up to 79
+        /// letters, digits, spaces, `-`, `_` and `.`, starting with a letter
+        /// or digit.
+        #[arg(
+            long = "output-name",
+            value_name = "NAME",
+            default_value = keyrx_daemon::platform::DEFAULT_OUTPUT_NAME,
+            value_parser = parse_output_name
+        )]
+        output_name: String,
+
+        /// Refuse to start if the kernel cannot emit some output keys (Linux).
+        ///
+        /// By default such keys are listed once at startup and their events
+        /// are dropped, so the daemon still runs on minimal kernels.
+        #[arg(long)]
+        strict_keys: bool,
+
+        /// Hold events for MS milliseconds so keys typed on different
+        /// keyboards are processed in the order they were pressed (Linux).
+        ///
+        /// Adds up to MS of latency to every keystroke; 1-2 ms is enough for
+        /// cross-device chords. Off by default.
+        #[arg(long = "merge-window-ms", value_name = "MS")]
+        merge_window_ms: Option<u64>,
+
+        /// When to end output frames with SYN_REPORT (Linux): `action`
+        /// after the output of each input event, or `loop` once per event
+        /// loop iteration.
+        ///
+        /// `loop` lets inputs handled together share frames, saving writes
+        /// at the cost of a few microseconds of latency; `metrics output`
+        /// shows the effect. A modifier and its key always share a frame.
+        #[arg(
+            long = "output-sync-interval",
+            value_name = "WHEN",
+            default_value_t = keyrx_daemon::platform::OutputSync::Action
+        )]
+        output_sync_interval: keyrx_daemon::platform::OutputSync,
+
+        /// Watch matched devices without grabbing them and print what the
+        /// config would emit for each key (Linux).
+        ///
+        /// Real keystrokes still reach the OS and nothing is injected, so a
+        /// config can be tuned live. Honors `--only-device`.
+        #[arg(long, conflicts_with = "test_mode")]
+        monitor: bool,
     },
 
     /// Manage device metadata (rename, set scope, set layout).
@@ -180,20 +312,46 @@ fn main() {
     let result = match cli.command {
         Commands::Run {
             config,
+            profile,
             debug,
+            log_file,
             test_mode,
+            pidfile,
+            lock_state,
+            web_addr,
+            web_allow_remote,
+            bind_retry,
+            only_device,
+            max_devices,
+            output_name,
+            strict_keys,
+            merge_window_ms,
+            output_sync_interval,
+            monitor,
         } => {
-            // If no config specified, use active profile from %APPDATA%\keyrx
-            let config_path = match config {
-                Some(path) => path,
-                None => {
-                    let mut default_path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-                    default_path.push("keyrx");
-                    default_path.push("default.krx");
-                    default_path
-                }
-            };
-            handle_run(&config_path, debug, test_mode)
+            if monitor {
+                resolve_config_path(config, profile.as_deref())
+                    .and_then(|config_path| handle_monitor(&config_path, &only_device))
+            } else {
+                handle_run(
+                    config,
+                    profile.as_deref(),
+                    debug,
+                    log_file.as_deref(),
+                    test_mode,
+                    pidfile.as_deref(),
+                    lock_state,
+                    web_addr,
+                    web_allow_remote,
+                    bind_retry,
+                    &only_device,
+                    max_devices,
+                    &output_name,
+                    strict_keys,
+                    merge_window_ms,
+                    output_sync_interval,
+                )
+            }
         }
         Commands::Devices(args) => match keyrx_daemon::cli::devices::execute(args, None) {
             Ok(()) => Ok(()),
@@ -248,6 +406,65 @@ fn main() {
     }
 }
 
+/// Resolves the configuration `run` loads: the given profile's compiled
+/// .krx, the `--config` path, or the default config.
+///
+/// Selecting a profile activates it, so a daemon should call this only once
+/// it owns its PID file.
+fn resolve_config_path(
+    config: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Result<PathBuf, (i32, String)> {
+    match (config, profile) {
+        (_, Some(name)) => activate_startup_profile(name),
+        (Some(path), None) => Ok(path),
+        (None, None) => {
+            let mut default_path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+            default_path.push("keyrx");
+            default_path.push("default.krx");
+            Ok(default_path)
+        }
+    }
+}
+
+/// Activates profile `name` for `run --profile`, compiling it first.
+///
+/// The profile is persisted as the active one, which is what the daemon
+/// loads at startup and `status` reports. Returns its compiled .krx.
+fn activate_startup_profile(name: &str) -> Result<PathBuf, (i32, String)> {
+    use keyrx_daemon::config::ProfileManager;
+
+    let config_dir = dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("keyrx");
+    let mut manager = ProfileManager::new(config_dir).map_err(|e| {
+        (
+            exit_codes::CONFIG_ERROR,
+            format!("Failed to initialize profile manager: {}", e),
+        )
+    })?;
+
+    let failed = |reason: String| {
+        (
+            exit_codes::CONFIG_ERROR,
+            format!("Failed to activate profile '{}': {}", name, reason),
+        )
+    };
+    let result = manager.activate(name).map_err(|e| failed(e.to_string()))?;
+    if !result.success {
+        return Err(failed(
+            result
+                .error
+                .unwrap_or_else(|| "compilation failed".to_string()),
+        ));
+    }
+
+    manager
+        .get(name)
+        .map(|profile| profile.krx_path.clone())
+        .ok_or_else(|| failed("profile not found".to_string()))
+}
+
 /// Handles the `profiles` command.
 fn handle_profiles_command(
     args: keyrx_daemon::cli::profiles::ProfilesArgs,
@@ -314,9 +531,58 @@ fn open_browser(url: &str) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Prints the web API token and the web UI URL that embeds it.
+///
+/// Both go straight to stderr instead of through the logger, so the token
+/// never reaches a log file or the bug reports built from it.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn print_web_access(token: &str, url: &str) {
+    eprintln!("Web API token: {}", token);
+    eprintln!("Web UI: {}", url);
+}
+
+/// Parses and validates `run --output-name`.
+fn parse_output_name(name: &str) -> Result<String, String> {
+    keyrx_daemon::platform::validate_output_name(name)?;
+    Ok(name.to_string())
+}
+
+/// Resolves the web server bind address and checks that it may be used.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn resolve_web_addr(
+    config_dir: &std::path::Path,
+    cli_addr: Option<SocketAddr>,
+    allow_remote: bool,
+    auth_enabled: bool,
+) -> Result<SocketAddr, (i32, String)> {
+    let settings_service = keyrx_daemon::services::SettingsService::new(config_dir.to_path_buf());
+    let addr = settings_service.get_web_addr(cli_addr);
+    keyrx_daemon::web::check_bind_addr(addr, allow_remote, auth_enabled)
+        .map_err(|e| (exit_codes::CONFIG_ERROR, e))?;
+    Ok(addr)
+}
+
+/// Builds the browser URL for the web UI served on `addr`.
+///
+/// A wildcard bind address is replaced with localhost so the URL can be
+/// opened on this machine.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn web_ui_url(addr: SocketAddr, token: &str) -> String {
+    let mut host = addr;
+    if host.ip().is_unspecified() {
+        host.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+    }
+    format!("http://{}/?token={}", host, token)
+}
+
 /// Handles the `run` subcommand in test mode - starts web server and IPC without keyboard capture.
 #[cfg(target_os = "linux")]
-fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<(), (i32, String)> {
+fn handle_run_test_mode(
+    _config_path: &std::path::Path,
+    _debug: bool,
+    web_addr: SocketAddr,
+    bind_retry: u16,
+) -> Result<(), (i32, String)> {
     use keyrx_daemon::config::ProfileManager;
     use keyrx_daemon::ipc::commands::IpcCommandHandler;
     use keyrx_daemon::ipc::server::IpcServer;
@@ -346,11 +612,26 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
     // Create daemon running flag
     let daemon_running = Arc::new(RwLock::new(true));
 
+    // Bind the web port first so IPC status can report it
+    let web_listener = keyrx_daemon::web::bind_listener(web_addr, bind_retry).map_err(|e| {
+        (
+            exit_codes::RUNTIME_ERROR,
+            format!("Failed to bind web server to {}: {}", web_addr, e),
+        )
+    })?;
+    let web_addr = web_listener.local_addr().map_err(|e| {
+        (
+            exit_codes::RUNTIME_ERROR,
+            format!("Failed to read web server address: {}", e),
+        )
+    })?;
+
     // Create IPC command handler
-    let ipc_handler = Arc::new(IpcCommandHandler::new(
-        Arc::clone(&profile_manager),
-        Arc::clone(&daemon_running),
-    ));
+    let ipc_handler = Arc::new(
+        IpcCommandHandler::new(Arc::clone(&profile_manager), Arc::clone(&daemon_running))
+            .with_loaded_config(keyrx_daemon::config_loader::LoadedConfig::new())
+            .with_web_port(web_addr.port()),
+    );
 
     // Create IPC server with unique socket path
     let pid = std::process::id();
@@ -443,9 +724,8 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
         test_socket_path.clone(),
     ));
 
-    // Start web server
-    let addr: std::net::SocketAddr = ([127, 0, 0, 1], 9867).into();
-    log::info!("Starting web server on http://{}", addr);
+    // Start web server (no API token in test mode)
+    log::info!("Starting web server on http://{}", web_addr);
 
     rt.block_on(async {
         // Spawn macro recorder event loop inside runtime context
@@ -454,7 +734,7 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
             recorder_for_loop.run_event_loop(macro_event_rx).await;
         });
 
-        match keyrx_daemon::web::serve(addr, event_tx, app_state).await {
+        match keyrx_daemon::web::serve_listener(web_listener, event_tx, app_state).await {
             Ok(()) => {
                 log::info!("Web server stopped");
                 Ok(())
@@ -470,23 +750,85 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
     })
 }
 
+/// Serves CLI requests (`status`, `metrics`, `config show-loaded`, ...) on
+/// the default IPC socket.
+///
+/// A daemon remapping with a `.rhai` source reloads that source on
+/// `ReloadConfig` without a path; otherwise the active profile is reloaded.
+/// The daemon keeps running without IPC if the socket cannot be bound.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn start_ipc_server(
+    mut handler: keyrx_daemon::ipc::commands::IpcCommandHandler,
+    config_path: &std::path::Path,
+) {
+    if keyrx_daemon::config_loader::is_source_config(config_path) {
+        handler = handler.with_config_path(config_path.to_path_buf());
+    }
+    let socket_path = PathBuf::from(keyrx_daemon::ipc::DEFAULT_SOCKET_PATH);
+    if let Err(e) =
+        keyrx_daemon::ipc::server::spawn_command_server(socket_path, std::sync::Arc::new(handler))
+    {
+        log::error!(
+            "Failed to start IPC server at {}: {}",
+            keyrx_daemon::ipc::DEFAULT_SOCKET_PATH,
+            e
+        );
+    }
+}
+
 /// Handles the `run` subcommand - starts the daemon.
 #[cfg(target_os = "linux")]
+#[allow(clippy::too_many_arguments)]
 fn handle_run(
-    config_path: &std::path::Path,
+    config: Option<PathBuf>,
+    profile: Option<&str>,
     debug: bool,
+    log_file: Option<&std::path::Path>,
     test_mode: bool,
+    pidfile: Option<&std::path::Path>,
+    lock_state: Option<PathBuf>,
+    web_addr: Option<SocketAddr>,
+    web_allow_remote: bool,
+    bind_retry: Option<u16>,
+    only_device: &[String],
+    max_devices: Option<usize>,
+    output_name: &str,
+    strict_keys: bool,
+    merge_window_ms: Option<u64>,
+    output_sync: keyrx_daemon::platform::OutputSync,
 ) -> Result<(), (i32, String)> {
-    use keyrx_daemon::daemon::Daemon;
+    use keyrx_daemon::daemon::{Daemon, LockStateFile, PidFile};
     use keyrx_daemon::platform::linux::LinuxSystemTray;
     use keyrx_daemon::platform::{SystemTray, TrayControlEvent};
 
     // Initialize logging
-    init_logging(debug);
+    init_logging(debug, log_file)?;
+
+    // Held until handle_run returns so the PID file is removed on clean shutdown
+    let _pidfile = pidfile
+        .map(PidFile::acquire)
+        .transpose()
+        .map_err(daemon_error_to_exit)?;
+
+    // Activating a profile is only safe once no other daemon is running
+    let config_path = resolve_config_path(config, profile)?;
+    let config_path = config_path.as_path();
+
+    // Determine config directory (always use standard location for profile management)
+    let config_dir = {
+        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("keyrx");
+        path
+    };
+
+    // Check the web server address before grabbing any device
+    let web_addr_overridden = web_addr.is_some();
+    let web_addr = resolve_web_addr(&config_dir, web_addr, web_allow_remote, !test_mode)?;
+    let bind_retry = bind_retry.unwrap_or(0);
 
     if test_mode {
         log::info!("Test mode enabled - running with IPC infrastructure without keyboard capture");
-        return handle_run_test_mode(config_path, debug);
+        return handle_run_test_mode(config_path, debug, web_addr, bind_retry);
     }
 
     log::info!(
@@ -495,21 +837,62 @@ fn handle_run(
     );
 
     // Create platform instance
-    let platform = keyrx_daemon::platform::create_platform().map_err(|e| {
+    let mut platform = keyrx_daemon::platform::create_platform().map_err(|e| {
         (
             keyrx_daemon::daemon::ExitCode::RuntimeError as i32,
             format!("Failed to create platform: {}", e),
         )
     })?;
 
+    platform.set_output_name(output_name);
+    platform.set_strict_keys(strict_keys);
+    platform.set_merge_window(merge_window_ms.map(std::time::Duration::from_millis));
+    platform.set_max_devices(max_devices);
+    platform.set_output_sync(output_sync);
+
+    // Restrict grabbing before the platform initializes its devices
+    if !only_device.is_empty() {
+        platform.grab_specific(only_device).map_err(|e| {
+            (
+                keyrx_daemon::daemon::ExitCode::ConfigError as i32,
+                format!(
+                    "Cannot restrict grabbing to {}: {}",
+                    only_device.join(", "),
+                    e
+                ),
+            )
+        })?;
+    }
+
     // Create the daemon
-    let mut daemon = Daemon::new(platform, config_path).map_err(daemon_error_to_exit)?;
+    let daemon = match lock_state.or_else(LockStateFile::default_path) {
+        Some(lock_state) => Daemon::with_lock_state_path(platform, config_path, lock_state),
+        None => {
+            log::info!("No runtime directory; active locks will not persist across restarts");
+            Daemon::new(platform, config_path)
+        }
+    };
+    let mut daemon = daemon.map_err(daemon_error_to_exit)?;
 
     log::info!(
         "Daemon initialized with {} device(s)",
         daemon.device_count()
     );
 
+    // Bind the web port now so the tray and the logs show the port in use
+    let web_listener = match keyrx_daemon::web::bind_listener(web_addr, bind_retry) {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            log::error!("Failed to bind web server to {}: {}", web_addr, e);
+            None
+        }
+    };
+    let configured_port = web_addr.port();
+    let web_addr = web_listener
+        .as_ref()
+        .and_then(|listener| listener.local_addr().ok())
+        .unwrap_or(web_addr);
+
     // Create system tray (optional - continues without it if unavailable)
     let tray = match LinuxSystemTray::new() {
         Ok(tray) => {
@@ -521,7 +904,10 @@ fn handle_run(
                 "Failed to create system tray (this is normal in headless sessions): {}",
                 e
             );
-            log::info!("Daemon will continue without system tray. Web UI is available at http://127.0.0.1:9867");
+            log::info!(
+                "Daemon will continue without system tray. Web UI is available at http://{}",
+                web_addr
+            );
             None
         }
     };
@@ -546,13 +932,6 @@ fn handle_run(
     // Create AppState with dependencies for web API
     let macro_recorder = std::sync::Arc::new(keyrx_daemon::macro_recorder::MacroRecorder::new());
 
-    // Determine config directory (always use standard location for profile management)
-    let config_dir = {
-        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-        path.push("keyrx");
-        path
-    };
-
     // Initialize ProfileManager and ProfileService
     let profile_manager = match keyrx_daemon::config::ProfileManager::new(config_dir.clone()) {
         Ok(mgr) => std::sync::Arc::new(mgr),
@@ -571,8 +950,9 @@ fn handle_run(
     let device_service = std::sync::Arc::new(keyrx_daemon::services::DeviceService::new(
         config_dir.clone(),
     ));
-    let config_service =
-        std::sync::Arc::new(keyrx_daemon::services::ConfigService::new(profile_manager));
+    let config_service = std::sync::Arc::new(keyrx_daemon::services::ConfigService::new(
+        std::sync::Arc::clone(&profile_manager),
+    ));
     let settings_service = std::sync::Arc::new(keyrx_daemon::services::SettingsService::new(
         config_dir.clone(),
     ));
@@ -581,6 +961,41 @@ fn handle_run(
         Some(macro_event_tx),
     ));
 
+    // Require a bearer token on the web API so other local users cannot change the config
+    let api_token = settings_service.get_or_create_api_token().map_err(|e| {
+        (
+            keyrx_daemon::daemon::ExitCode::ConfigError as i32,
+            format!("Failed to load web API token: {}", e),
+        )
+    })?;
+    if !web_addr_overridden && web_addr.port() != configured_port {
+        if let Err(e) = settings_service.record_bound_port(web_addr.port()) {
+            log::warn!(
+                "Failed to save web port {} to settings: {}",
+                web_addr.port(),
+                e
+            );
+        }
+    }
+    let web_ui_url = web_ui_url(web_addr, &api_token);
+    log::info!("Web server bind address: {}", web_addr);
+    print_web_access(&api_token, &web_ui_url);
+
+    // Answer CLI queries from the daemon's own state
+    start_ipc_server(
+        keyrx_daemon::ipc::commands::IpcCommandHandler::new(
+            profile_manager,
+            std::sync::Arc::new(tokio::sync::RwLock::new(true)),
+        )
+        .with_loaded_config(daemon.loaded_config())
+        .with_event_counters(daemon.event_counters())
+        .with_latency_recorder(daemon.latency_recorder())
+        .with_event_log(event_broadcaster.event_log())
+        .with_reload_handle(daemon.reload_handle())
+        .with_web_port(web_addr.port()),
+        config_path,
+    );
+
     let subscription_manager =
         std::sync::Arc::new(keyrx_daemon::web::subscriptions::SubscriptionManager::new());
 
@@ -590,16 +1005,20 @@ fn handle_run(
     // Clone macro recorder for event loop (before moving into app_state)
     let macro_recorder_for_loop = std::sync::Arc::clone(&macro_recorder);
 
-    let app_state = std::sync::Arc::new(keyrx_daemon::web::AppState::new(
-        macro_recorder,
-        profile_service,
-        device_service,
-        config_service,
-        settings_service,
-        simulation_service,
-        subscription_manager,
-        rpc_event_tx,
-    ));
+    let app_state = std::sync::Arc::new(
+        keyrx_daemon::web::AppState::new(
+            macro_recorder,
+            profile_service,
+            device_service,
+            config_service,
+            settings_service,
+            simulation_service,
+            subscription_manager,
+            rpc_event_tx,
+        )
+        .with_api_token(api_token)
+        .with_state_cache(event_broadcaster.state_cache()),
+    );
 
     // Start web server and event broadcasting in background (optional)
     std::thread::spawn(move || {
@@ -625,9 +1044,11 @@ fn handle_run(
                 Some(latency_recorder_for_broadcaster),
             ));
 
-            let addr: std::net::SocketAddr = ([127, 0, 0, 1], 9867).into();
-            log::info!("Starting web server on http://{}", addr);
-            match keyrx_daemon::web::serve(addr, event_tx_clone, app_state).await {
+            let Some(listener) = web_listener else {
+                return;
+            };
+            log::info!("Starting web server on http://{}", web_addr);
+            match keyrx_daemon::web::serve_listener(listener, event_tx_clone, app_state).await {
                 Ok(()) => log::info!("Web server stopped"),
                 Err(e) => log::error!("Web server error: {}", e),
             }
@@ -650,7 +1071,7 @@ fn handle_run(
                     }
                     TrayControlEvent::OpenWebUI => {
                         log::info!("Open Web UI requested via tray menu");
-                        if let Err(e) = open_browser("http://127.0.0.1:9867") {
+                        if let Err(e) = open_browser(&web_ui_url) {
                             log::error!("Failed to open browser: {}", e);
                         }
                     }
@@ -746,7 +1167,12 @@ fn cleanup_pid_file(config_dir: &std::path::Path) {
 
 /// Handles the `run` subcommand in test mode - starts web server and IPC without keyboard capture.
 #[cfg(target_os = "windows")]
-fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<(), (i32, String)> {
+fn handle_run_test_mode(
+    _config_path: &std::path::Path,
+    _debug: bool,
+    web_addr: SocketAddr,
+    bind_retry: u16,
+) -> Result<(), (i32, String)> {
     use keyrx_daemon::config::ProfileManager;
     use keyrx_daemon::ipc::commands::IpcCommandHandler;
     use keyrx_daemon::ipc::server::IpcServer;
@@ -776,11 +1202,26 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
     // Create daemon running flag
     let daemon_running = Arc::new(RwLock::new(true));
 
+    // Bind the web port first so IPC status can report it
+    let web_listener = keyrx_daemon::web::bind_listener(web_addr, bind_retry).map_err(|e| {
+        (
+            exit_codes::RUNTIME_ERROR,
+            format!("Failed to bind web server to {}: {}", web_addr, e),
+        )
+    })?;
+    let web_addr = web_listener.local_addr().map_err(|e| {
+        (
+            exit_codes::RUNTIME_ERROR,
+            format!("Failed to read web server address: {}", e),
+        )
+    })?;
+
     // Create IPC command handler
-    let ipc_handler = Arc::new(IpcCommandHandler::new(
-        Arc::clone(&profile_manager),
-        Arc::clone(&daemon_running),
-    ));
+    let ipc_handler = Arc::new(
+        IpcCommandHandler::new(Arc::clone(&profile_manager), Arc::clone(&daemon_running))
+            .with_loaded_config(keyrx_daemon::config_loader::LoadedConfig::new())
+            .with_web_port(web_addr.port()),
+    );
 
     // Create IPC server with unique socket path (Windows uses named pipes)
     let pid = std::process::id();
@@ -873,9 +1314,8 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
         test_socket_path.clone(),
     ));
 
-    // Start web server
-    let addr: std::net::SocketAddr = ([127, 0, 0, 1], 9867).into();
-    log::info!("Starting web server on http://{}", addr);
+    // Start web server (no API token in test mode)
+    log::info!("Starting web server on http://{}", web_addr);
 
     rt.block_on(async {
         // Spawn macro recorder event loop inside runtime context
@@ -884,7 +1324,7 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
             recorder_for_loop.run_event_loop(macro_event_rx).await;
         });
 
-        match keyrx_daemon::web::serve(addr, event_tx, app_state).await {
+        match keyrx_daemon::web::serve_listener(web_listener, event_tx, app_state).await {
             Ok(()) => {
                 log::info!("Web server stopped");
                 Ok(())
@@ -900,41 +1340,27 @@ fn handle_run_test_mode(_config_path: &std::path::Path, _debug: bool) -> Result<
     })
 }
 
-/// Find an available port starting from the given port.
-/// Tries ports in sequence: port, port+1, port+2, ... up to 10 attempts.
-#[cfg(target_os = "windows")]
-fn find_available_port(start_port: u16) -> u16 {
-    use std::net::TcpListener;
-
-    for offset in 0..10 {
-        let port = start_port.saturating_add(offset);
-        if port == 0 {
-            continue;
-        }
-
-        match TcpListener::bind(format!("127.0.0.1:{}", port)) {
-            Ok(_listener) => {
-                // Port is available (listener is dropped immediately, releasing the port)
-                return port;
-            }
-            Err(_) => {
-                // Port is in use, try next
-                continue;
-            }
-        }
-    }
-
-    // Fallback: return the original port (will fail with a clear error later)
-    start_port
-}
-
 #[cfg(target_os = "windows")]
+#[allow(clippy::too_many_arguments)]
 fn handle_run(
-    config_path: &std::path::Path,
+    config: Option<PathBuf>,
+    profile: Option<&str>,
     debug: bool,
+    log_file: Option<&std::path::Path>,
     test_mode: bool,
+    pidfile: Option<&std::path::Path>,
+    lock_state: Option<PathBuf>,
+    web_addr: Option<SocketAddr>,
+    web_allow_remote: bool,
+    bind_retry: Option<u16>,
+    only_device: &[String],
+    max_devices: Option<usize>,
+    output_name: &str,
+    strict_keys: bool,
+    merge_window_ms: Option<u64>,
+    output_sync: keyrx_daemon::platform::OutputSync,
 ) -> Result<(), (i32, String)> {
-    use keyrx_daemon::daemon::Daemon;
+    use keyrx_daemon::daemon::{Daemon, LockStateFile, PidFile};
     use keyrx_daemon::platform::windows::tray::TrayIconController;
     use keyrx_daemon::platform::{SystemTray, TrayControlEvent};
     use keyrx_daemon::services::SettingsService;
@@ -943,12 +1369,17 @@ fn handle_run(
     };
 
     // Initialize logging
-    init_logging(debug);
+    init_logging(debug, log_file)?;
 
-    if test_mode {
-        log::info!("Test mode enabled - running with IPC infrastructure without keyboard capture");
-        return handle_run_test_mode(config_path, debug);
-    }
+    // Held until handle_run returns so the PID file is removed on clean shutdown
+    let _pidfile = pidfile
+        .map(PidFile::acquire)
+        .transpose()
+        .map_err(daemon_error_to_exit)?;
+
+    // Activating a profile is only safe once no other daemon is running
+    let config_path = resolve_config_path(config, profile)?;
+    let config_path = config_path.as_path();
 
     // Determine config directory (always use standard location for profile management)
     let config_dir = {
@@ -957,6 +1388,16 @@ fn handle_run(
         path
     };
 
+    // Check the web server address before installing the keyboard hook
+    let web_addr = resolve_web_addr(&config_dir, web_addr, web_allow_remote, !test_mode)?;
+    // A stale instance often still holds the port on Windows, so retry by default
+    let bind_retry = bind_retry.unwrap_or(9);
+
+    if test_mode {
+        log::info!("Test mode enabled - running with IPC infrastructure without keyboard capture");
+        return handle_run_test_mode(config_path, debug, web_addr, bind_retry);
+    }
+
     // Ensure single instance - kill any existing daemon before starting
     let killed_old = ensure_single_instance(&config_dir);
 
@@ -971,7 +1412,7 @@ fn handle_run(
         }
         default_port
     } else {
-        settings_service_for_port.get_port()
+        web_addr.port()
     };
     log::info!("Configured web server port: {}", configured_port);
 
@@ -989,15 +1430,42 @@ fn handle_run(
     );
 
     // Create platform instance
-    let platform = keyrx_daemon::platform::create_platform().map_err(|e| {
+    let mut platform = keyrx_daemon::platform::create_platform().map_err(|e| {
         (
             exit_codes::RUNTIME_ERROR,
             format!("Failed to create platform: {}", e),
         )
     })?;
 
+    platform.set_output_name(output_name);
+    platform.set_strict_keys(strict_keys);
+    platform.set_merge_window(merge_window_ms.map(std::time::Duration::from_millis));
+    platform.set_max_devices(max_devices);
+    platform.set_output_sync(output_sync);
+
+    // Restrict grabbing before the platform initializes its devices
+    if !only_device.is_empty() {
+        platform.grab_specific(only_device).map_err(|e| {
+            (
+                exit_codes::CONFIG_ERROR,
+                format!(
+                    "Cannot restrict grabbing to {}: {}",
+                    only_device.join(", "),
+                    e
+                ),
+            )
+        })?;
+    }
+
     // Create the daemon
-    let mut daemon = Daemon::new(platform, config_path).map_err(daemon_error_to_exit)?;
+    let daemon = match lock_state.or_else(LockStateFile::default_path) {
+        Some(lock_state) => Daemon::with_lock_state_path(platform, config_path, lock_state),
+        None => {
+            log::info!("No runtime directory; active locks will not persist across restarts");
+            Daemon::new(platform, config_path)
+        }
+    };
+    let mut daemon = daemon.map_err(daemon_error_to_exit)?;
 
     // Create broadcast channel for event streaming to WebSocket clients
     let (event_tx, _event_rx) = tokio::sync::broadcast::channel(1000);
@@ -1037,8 +1505,9 @@ fn handle_run(
     let device_service = std::sync::Arc::new(keyrx_daemon::services::DeviceService::new(
         config_dir.clone(),
     ));
-    let config_service =
-        std::sync::Arc::new(keyrx_daemon::services::ConfigService::new(profile_manager));
+    let config_service = std::sync::Arc::new(keyrx_daemon::services::ConfigService::new(
+        std::sync::Arc::clone(&profile_manager),
+    ));
     let settings_service = std::sync::Arc::new(keyrx_daemon::services::SettingsService::new(
         config_dir.clone(),
     ));
@@ -1047,25 +1516,54 @@ fn handle_run(
         Some(macro_event_tx),
     ));
 
+    // Require a bearer token on the web API so other local users cannot change the config
+    let api_token = settings_service.get_or_create_api_token().map_err(|e| {
+        (
+            exit_codes::CONFIG_ERROR,
+            format!("Failed to load web API token: {}", e),
+        )
+    })?;
+
     let subscription_manager =
         std::sync::Arc::new(keyrx_daemon::web::subscriptions::SubscriptionManager::new());
 
     // Create RPC event broadcaster for WebSocket RPC events (device/profile updates)
     let (rpc_event_tx, _) = tokio::sync::broadcast::channel(1000);
 
-    let app_state = std::sync::Arc::new(keyrx_daemon::web::AppState::new(
-        macro_recorder,
-        profile_service,
-        device_service,
-        config_service,
-        settings_service.clone(),
-        simulation_service,
-        subscription_manager,
-        rpc_event_tx,
-    ));
+    let app_state = std::sync::Arc::new(
+        keyrx_daemon::web::AppState::new(
+            macro_recorder,
+            profile_service,
+            device_service,
+            config_service,
+            settings_service.clone(),
+            simulation_service,
+            subscription_manager,
+            rpc_event_tx,
+        )
+        .with_api_token(api_token.clone())
+        .with_state_cache(event_broadcaster.state_cache()),
+    );
 
-    // Find an available port, starting with configured port
-    let actual_port = find_available_port(configured_port);
+    // Bind the web port, moving to a following port if it is taken
+    let web_listener = match keyrx_daemon::web::bind_listener(
+        SocketAddr::new(web_addr.ip(), configured_port),
+        bind_retry,
+    ) {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            log::error!(
+                "Failed to bind web server to port {}: {}",
+                configured_port,
+                e
+            );
+            None
+        }
+    };
+    let actual_port = web_listener
+        .as_ref()
+        .and_then(|listener| listener.local_addr().ok())
+        .map_or(configured_port, |addr| addr.port());
 
     // If we had to use a different port, save it to settings and notify user
     let port_changed = actual_port != configured_port;
@@ -1081,6 +1579,21 @@ fn handle_run(
         }
     }
 
+    // Answer CLI queries from the daemon's own state
+    start_ipc_server(
+        keyrx_daemon::ipc::commands::IpcCommandHandler::new(
+            profile_manager,
+            std::sync::Arc::new(tokio::sync::RwLock::new(true)),
+        )
+        .with_loaded_config(daemon.loaded_config())
+        .with_event_counters(daemon.event_counters())
+        .with_latency_recorder(daemon.latency_recorder())
+        .with_event_log(event_broadcaster.event_log())
+        .with_reload_handle(daemon.reload_handle())
+        .with_web_port(actual_port),
+        config_path,
+    );
+
     let actual_port_for_thread = actual_port;
     let port_changed_for_thread = port_changed;
     let configured_port_for_thread = configured_port;
@@ -1102,7 +1615,10 @@ fn handle_run(
                 Some(latency_recorder_for_broadcaster),
             ));
 
-            let addr: std::net::SocketAddr = ([127, 0, 0, 1], actual_port_for_thread).into();
+            let Some(listener) = web_listener else {
+                return;
+            };
+            let addr = SocketAddr::new(web_addr.ip(), actual_port_for_thread);
             if port_changed_for_thread {
                 log::info!(
                     "Port {} was in use. Starting web server on http://{} (saved to settings)",
@@ -1112,7 +1628,7 @@ fn handle_run(
             } else {
                 log::info!("Starting web server on http://{}", addr);
             }
-            match keyrx_daemon::web::serve(addr, event_tx_clone, app_state).await {
+            match keyrx_daemon::web::serve_listener(listener, event_tx_clone, app_state).await {
                 Ok(()) => log::info!("Web server stopped"),
                 Err(e) => log::error!("Web server error: {}", e),
             }
@@ -1141,8 +1657,8 @@ fn handle_run(
                 e
             );
             log::info!(
-                "Daemon will continue without system tray. Web UI is available at http://127.0.0.1:{}",
-                actual_port
+                "Daemon will continue without system tray. Web UI is available at http://{}",
+                SocketAddr::new(web_addr.ip(), actual_port)
             );
             None
         }
@@ -1155,8 +1671,11 @@ fn handle_run(
 
     log::info!("Daemon initialized. Running message loop...");
 
-    // Build web UI URL with actual port
-    let web_ui_url = format!("http://127.0.0.1:{}", actual_port);
+    // Build web UI URL with actual port and the API token
+    let bind_addr = SocketAddr::new(web_addr.ip(), actual_port);
+    let web_ui_url = web_ui_url(bind_addr, &api_token);
+    log::info!("Web server bind address: {}", bind_addr);
+    print_web_access(&api_token, &web_ui_url);
 
     // Windows low-level hooks REQUIRE a message loop on the thread that installed them.
     // Our Daemon::new() calls grab() which installs the hook.
@@ -1200,6 +1719,9 @@ fn handle_run(
                 }
             }
 
+            // Apply reloads requested over IPC between events
+            daemon.process_reload_requests();
+
             // Check if daemon is still running
             if !daemon.is_running() {
                 log::info!("Daemon stopped");
@@ -1216,7 +1738,7 @@ fn handle_run(
                             let _ = daemon.reload();
                         }
                         TrayControlEvent::OpenWebUI => {
-                            log::info!("Opening web UI at {}...", web_ui_url);
+                            log::info!("Opening web UI...");
                             if let Err(e) = open_browser(&web_ui_url) {
                                 log::error!("Failed to open web UI: {}", e);
                             }
@@ -1268,10 +1790,24 @@ fn is_admin() -> bool {
 }
 
 #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+#[allow(clippy::too_many_arguments)]
 fn handle_run(
-    _config_path: &std::path::Path,
+    _config: Option<PathBuf>,
+    _profile: Option<&str>,
     _debug: bool,
+    _log_file: Option<&std::path::Path>,
     _test_mode: bool,
+    _pidfile: Option<&std::path::Path>,
+    _lock_state: Option<PathBuf>,
+    _web_addr: Option<SocketAddr>,
+    _web_allow_remote: bool,
+    _bind_retry: Option<u16>,
+    _only_device: &[String],
+    _max_devices: Option<usize>,
+    _output_name: &str,
+    _strict_keys: bool,
+    _merge_window_ms: Option<u64>,
+    _output_sync: keyrx_daemon::platform::OutputSync,
 ) -> Result<(), (i32, String)> {
     Err((
         exit_codes::CONFIG_ERROR,
@@ -1381,6 +1917,13 @@ fn handle_record(
         events: Vec<keyrx_core::runtime::KeyEvent>,
     }
 
+    // Tag events with the same ID the daemon uses, so device-scoped
+    // conditions replay faithfully
+    let device_id = match device.unique_name().filter(|serial| !serial.is_empty()) {
+        Some(serial) => format!("serial-{}", serial),
+        None => format!("path-{}", device_path.display()),
+    };
+
     let mut captured_events = Vec::new();
     let start_time = std::time::Instant::now();
 
@@ -1400,32 +1943,17 @@ fn handle_record(
                         let code = ev.code();
                         let value = ev.value(); // 0=Release, 1=Press, 2=Repeat
 
-                        if value == 2 {
-                            continue;
-                        } // Ignore repeats for now
-
                         if let Some(keycode) = evdev_to_keycode(code) {
-                            let event_type = if value == 1 {
-                                keyrx_core::runtime::KeyEventType::Press
-                            } else {
-                                keyrx_core::runtime::KeyEventType::Release
-                            };
-
                             // Calculate relative time
                             let timestamp_us = start_time.elapsed().as_micros() as u64;
 
-                            let key_event =
-                                keyrx_core::runtime::KeyEvent::press(keycode) // Type fixed below
-                                    .with_timestamp(timestamp_us);
-
-                            // Construct properly with correct type
-                            let final_event =
-                                if event_type == keyrx_core::runtime::KeyEventType::Press {
-                                    key_event // Default is press
-                                } else {
-                                    keyrx_core::runtime::KeyEvent::release(keycode)
-                                        .with_timestamp(timestamp_us)
-                                };
+                            let final_event = match value {
+                                1 => keyrx_core::runtime::KeyEvent::press(keycode),
+                                2 => keyrx_core::runtime::KeyEvent::repeat(keycode),
+                                _ => keyrx_core::runtime::KeyEvent::release(keycode),
+                            }
+                            .with_timestamp(timestamp_us)
+                            .with_device_id(device_id.clone());
 
                             print!("\rCaptured: {:?}     ", final_event.keycode());
                             std::io::stdout().flush().ok();
@@ -1531,15 +2059,27 @@ fn handle_list_devices() -> Result<(), (i32, String)> {
 
     println!("Available keyboard devices:");
     println!();
-    println!("{:<30} {:<25} SERIAL", "PATH", "NAME");
-    println!("{}", "-".repeat(80));
+    println!(
+        "{:<30} {:<25} {:>5} {:<4} SERIAL",
+        "PATH", "NAME", "KEYS", "REL"
+    );
+    println!("{}", "-".repeat(90));
 
     for keyboard in &keyboards {
         let serial_display = keyboard.serial.as_deref().unwrap_or("-");
+        let (keys_display, rel_display) = match keyboard.capabilities {
+            Some(caps) => (
+                caps.key_count.to_string(),
+                if caps.supports_rel { "yes" } else { "no" },
+            ),
+            None => ("-".to_string(), "-"),
+        };
         println!(
-            "{:<30} {:<25} {}",
+            "{:<30} {:<25} {:>5} {:<4} {}",
             keyboard.path.display(),
             truncate_string(&keyboard.name, 24),
+            keys_display,
+            rel_display,
             serial_display
         );
     }
@@ -1547,6 +2087,11 @@ fn handle_list_devices() -> Result<(), (i32, String)> {
     println!();
     println!("Found {} keyboard device(s).", keyboards.len());
     println!();
+    println!("Device patterns (paste into device_start() to match one device):");
+    for keyboard in &keyboards {
+        println!("  \"{}\"", keyboard.display_name());
+    }
+    println!();
     println!("Tip: Use patterns in your configuration to match devices:");
     println!("  - \"*\" matches all keyboards");
     println!("  - \"USB*\" matches devices with USB in name/serial");
@@ -1565,11 +2110,68 @@ fn handle_list_devices() -> Result<(), (i32, String)> {
     ))
 }
 
+/// Handles `run --monitor`: live, read-only remapping preview.
+#[cfg(target_os = "linux")]
+fn handle_monitor(
+    config_path: &std::path::Path,
+    only_device: &[String],
+) -> Result<(), (i32, String)> {
+    use keyrx_core::config::ConfigRoot;
+    use keyrx_daemon::config_loader::load_config;
+    use keyrx_daemon::device_manager::DiscoveryError;
+    use rkyv::Deserialize as _;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    let config: ConfigRoot = load_config(config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|archived| {
+            archived
+                .deserialize(&mut rkyv::Infallible)
+                .map_err(|_| "corrupted configuration".to_string())
+        })
+        .map_err(|e| {
+            (
+                exit_codes::CONFIG_ERROR,
+                format!("Failed to load configuration: {}", e),
+            )
+        })?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        if let Err(e) = signal_hook::flag::register(signal, Arc::clone(&stop)) {
+            eprintln!("Failed to register signal handler: {}", e);
+        }
+    }
+
+    keyrx_daemon::platform::linux::run_monitor(&config.devices, only_device, &stop).map_err(|e| {
+        match e {
+            DiscoveryError::UnknownDeviceIds { .. } => (exit_codes::CONFIG_ERROR, e.to_string()),
+            _ => (
+                exit_codes::PERMISSION_ERROR,
+                format!("Failed to open devices: {}", e),
+            ),
+        }
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn handle_monitor(
+    _config_path: &std::path::Path,
+    _only_device: &[String],
+) -> Result<(), (i32, String)> {
+    Err((
+        exit_codes::CONFIG_ERROR,
+        "'run --monitor' is only available on Linux.".to_string(),
+    ))
+}
+
 /// Handles the `validate` subcommand - validates config without grabbing.
 #[cfg(target_os = "linux")]
 fn handle_validate(config_path: &std::path::Path) -> Result<(), (i32, String)> {
+    use keyrx_core::config::DeviceIdentifier;
     use keyrx_daemon::config_loader::load_config;
-    use keyrx_daemon::device_manager::{enumerate_keyboards, match_device};
+    use keyrx_daemon::device_manager::{enumerate_keyboards, match_device_identifier, match_order};
 
     println!("Validating configuration: {}", config_path.display());
     println!();
@@ -1596,6 +2198,12 @@ fn handle_validate(config_path: &std::path::Path) -> Result<(), (i32, String)> {
             device_config.identifier.pattern,
             device_config.mappings.len()
         );
+        if device_config.priority != 0 {
+            println!("        Priority: {}", device_config.priority);
+        }
+        for exclude in device_config.identifier.excludes.iter() {
+            println!("        Excludes: \"{}\"", exclude);
+        }
     }
     println!();
 
@@ -1633,14 +2241,23 @@ fn handle_validate(config_path: &std::path::Path) -> Result<(), (i32, String)> {
     let mut unmatched_devices = Vec::new();
 
     for keyboard in &keyboards {
-        // Check each pattern in order (priority)
+        // Check patterns highest priority first, declaration order breaks ties
         let mut matched_pattern: Option<&str> = None;
 
-        for device_config in config.devices.iter() {
-            let pattern = device_config.identifier.pattern.as_str();
-            if match_device(keyboard, pattern) {
-                matched_pattern = Some(pattern);
-                break; // First match wins (priority ordering)
+        for idx in match_order(config.devices.iter().map(|d| d.priority)) {
+            let device_config = &config.devices[idx];
+            let identifier = DeviceIdentifier {
+                pattern: device_config.identifier.pattern.to_string(),
+                excludes: device_config
+                    .identifier
+                    .excludes
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect(),
+            };
+            if match_device_identifier(keyboard, &identifier) {
+                matched_pattern = Some(device_config.identifier.pattern.as_str());
+                break; // First match wins
             }
         }
 
@@ -1707,9 +2324,14 @@ fn handle_validate(_config_path: &std::path::Path) -> Result<(), (i32, String)>
 }
 
 /// Initializes the logging system.
+///
+/// With `log_file`, records are written to stderr and to a size-capped,
+/// rotating copy of the log in that file.
 #[cfg(any(target_os = "linux", target_os = "windows"))]
-fn init_logging(debug: bool) {
-    use env_logger::Builder;
+fn init_logging(debug: bool, log_file: Option<&std::path::Path>) -> Result<(), (i32, String)> {
+    use env_logger::{Builder, Target};
+    use keyrx_daemon::daemon::log_file::{DEFAULT_BACKUPS, DEFAULT_MAX_BYTES};
+    use keyrx_daemon::daemon::{RotatingFile, TeeWriter};
     use log::LevelFilter;
 
     let level = if debug {
@@ -1718,10 +2340,19 @@ fn init_logging(debug: bool) {
         LevelFilter::Info
     };
 
-    Builder::new()
-        .filter_level(level)
-        .format_timestamp_millis()
-        .init();
+    let mut builder = Builder::new();
+    builder.filter_level(level).format_timestamp_millis();
+    if let Some(path) = log_file {
+        let file = RotatingFile::open(path, DEFAULT_MAX_BYTES, DEFAULT_BACKUPS).map_err(|e| {
+            (
+                exit_codes::CONFIG_ERROR,
+                format!("Failed to open log file {}: {}", path.display(), e),
+            )
+        })?;
+        builder.target(Target::Pipe(Box::new(TeeWriter::new(file))));
+    }
+    builder.init();
+    Ok(())
 }
 
 /// Converts a DaemonError to an exit code and message.
@@ -3,6 +3,12 @@
 //! This module defines shared types used across all platform implementations,
 //! including device information and platform-specific errors.
 
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
 use thiserror::Error;
 
 /// Information about an input device.
@@ -21,6 +27,7 @@ use thiserror::Error;
 ///     path: "/dev/input/event3".to_string(),
 ///     vendor_id: 0x0001,
 ///     product_id: 0x0001,
+///     capabilities: None,
 /// };
 ///
 /// println!("Device: {} ({})", device.name, device.path);
@@ -52,6 +59,27 @@ pub struct DeviceInfo {
     ///
     /// Standard USB product identifier.
     pub product_id: u16,
+
+    /// Input capabilities reported by the OS.
+    ///
+    /// `None` on platforms that cannot query them.
+    pub capabilities: Option<DeviceCapabilities>,
+}
+
+/// Input capabilities of a device node.
+///
+/// Used to tell full keyboards apart from consumer-control, power-button or
+/// mouse nodes that also emit key events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    /// Device emits key events.
+    pub supports_keys: bool,
+
+    /// Number of distinct key codes the device reports (0 if unknown).
+    pub key_count: usize,
+
+    /// Device emits relative axis events (pointer motion, scroll wheel).
+    pub supports_rel: bool,
 }
 
 /// Errors that can occur during platform operations.
@@ -143,3 +171,384 @@ pub enum PlatformError {
 
 /// Convenience type alias for Results using PlatformError.
 pub type Result<T> = std::result::Result<T, PlatformError>;
+
+/// Lock-free counters for events that failed or were lost.
+///
+/// Shared between the event loop (injection failures) and the platform
+/// (capture errors and disconnects). Every update is a single relaxed atomic
+/// increment, so it is safe on the hot path.
+#[derive(Debug, Default)]
+pub struct EventCounters {
+    /// Output events the platform failed to inject.
+    injection_failures: AtomicU64,
+    /// Errors reading from an input device.
+    capture_errors: AtomicU64,
+    /// Reads lost because the input device was disconnected.
+    dropped_events: AtomicU64,
+    /// Events written to the output device.
+    output_events: AtomicU64,
+    /// Input frames (`SYN_REPORT`s) the output events were delivered in.
+    output_frames: AtomicU64,
+}
+
+impl EventCounters {
+    /// Creates a new set of counters, all zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an output event that could not be injected.
+    #[inline]
+    pub fn record_injection_failure(&self) {
+        self.injection_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records an error while reading from an input device.
+    #[inline]
+    pub fn record_capture_error(&self) {
+        self.capture_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a read lost because its device was disconnected.
+    #[inline]
+    pub fn record_dropped_event(&self) {
+        self.dropped_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records `frames` output frames carrying `events` events in total.
+    #[inline]
+    pub fn record_output_frames(&self, frames: usize, events: usize) {
+        self.output_frames
+            .fetch_add(frames as u64, Ordering::Relaxed);
+        self.output_events
+            .fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    /// Returns the current output event and frame counts.
+    pub fn output_counts(&self) -> OutputCounts {
+        OutputCounts {
+            events: self.output_events.load(Ordering::Relaxed),
+            frames: self.output_frames.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the current counter values.
+    pub fn snapshot(&self) -> EventErrorCounts {
+        EventErrorCounts {
+            injection_failures: self.injection_failures.load(Ordering::Relaxed),
+            capture_errors: self.capture_errors.load(Ordering::Relaxed),
+            dropped_events: self.dropped_events.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time values of [`EventCounters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventErrorCounts {
+    /// Output events the platform failed to inject.
+    pub injection_failures: u64,
+    /// Errors reading from an input device.
+    pub capture_errors: u64,
+    /// Reads lost because the input device was disconnected.
+    pub dropped_events: u64,
+}
+
+/// Point-in-time output totals of [`EventCounters`].
+///
+/// Each frame costs the output device one `SYN_REPORT` write, so
+/// `events / frames` shows how much [`OutputSync::Loop`] batching saves.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputCounts {
+    /// Events written to the output device.
+    pub events: u64,
+    /// Input frames the events were delivered in.
+    pub frames: u64,
+}
+
+/// When the output device ends an input frame with `SYN_REPORT`.
+///
+/// Either way the output of one input event is never split differently:
+/// a modifier and the key it modifies always share a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputSync {
+    /// Deliver the output of each input event in its own frames, as soon
+    /// as the event loop has processed it.
+    #[default]
+    Action,
+    /// Let the output of every input handled in one event loop iteration
+    /// share frames, trading a little latency for fewer writes.
+    Loop,
+}
+
+impl FromStr for OutputSync {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "action" => Ok(Self::Action),
+            "loop" => Ok(Self::Loop),
+            other => Err(format!(
+                "unknown output sync interval '{}' (expected 'action' or 'loop')",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputSync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Action => "action",
+            Self::Loop => "loop",
+        })
+    }
+}
+
+/// Events kept by [`EventLog::default`].
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 1000;
+
+/// Ring buffer of recently processed key events, served over IPC.
+///
+/// Every event gets an id one higher than the previous one, starting at 1,
+/// so a client can poll with the last id it saw and receive only newer
+/// events. Clones share the same buffer.
+#[derive(Debug, Clone)]
+pub struct EventLog {
+    inner: Arc<Mutex<EventLogInner>>,
+}
+
+#[derive(Debug)]
+struct EventLogInner {
+    entries: VecDeque<(u64, String)>,
+    capacity: usize,
+    last_id: u64,
+}
+
+/// Events returned from an [`EventLog`] query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventPage {
+    /// Event descriptions, oldest first.
+    pub events: Vec<String>,
+    /// Id of the newest event in the log (0 while empty), to poll from next.
+    pub last_id: u64,
+}
+
+impl EventLog {
+    /// Creates an empty log keeping the newest `capacity` events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(EventLogInner {
+                entries: VecDeque::with_capacity(capacity),
+                capacity,
+                last_id: 0,
+            })),
+        }
+    }
+
+    /// Appends an event, evicting the oldest one when full, and returns its id.
+    pub fn push(&self, event: String) -> u64 {
+        let Ok(mut inner) = self.inner.lock() else {
+            return 0;
+        };
+        inner.last_id += 1;
+        let id = inner.last_id;
+        if inner.capacity == 0 {
+            return id;
+        }
+        if inner.entries.len() == inner.capacity {
+            inner.entries.pop_front();
+        }
+        inner.entries.push_back((id, event));
+        id
+    }
+
+    /// Returns the newest `count` events.
+    pub fn tail(&self, count: usize) -> EventPage {
+        self.page(|entries| entries.len().saturating_sub(count))
+    }
+
+    /// Returns the events newer than `after_id`.
+    ///
+    /// If the cursor is older than the buffer, every retained event is
+    /// returned; the events in between were already evicted.
+    pub fn since(&self, after_id: u64) -> EventPage {
+        self.page(|entries| entries.partition_point(|(id, _)| *id <= after_id))
+    }
+
+    fn page(&self, start: impl FnOnce(&VecDeque<(u64, String)>) -> usize) -> EventPage {
+        let Ok(inner) = self.inner.lock() else {
+            return EventPage::default();
+        };
+        let start = start(&inner.entries);
+        EventPage {
+            events: inner
+                .entries
+                .iter()
+                .skip(start)
+                .map(|(_, event)| event.clone())
+                .collect(),
+            last_id: inner.last_id,
+        }
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+/// Name of the virtual output keyboard unless `--output-name` overrides it.
+pub const DEFAULT_OUTPUT_NAME: &str = "keyrx";
+
+/// Longest accepted output device name.
+///
+/// uinput device names are limited to 80 bytes including the terminating NUL.
+pub const MAX_OUTPUT_NAME_LEN: usize = 79;
+
+/// Checks that `name` is usable as the virtual output keyboard's name.
+///
+/// Names must be 1 to [`MAX_OUTPUT_NAME_LEN`] characters of ASCII letters,
+/// digits, spaces, `-`, `_` and `.`, and start with a letter or digit, so
+/// they can be matched safely from udev rules.
+///
+/// # Errors
+///
+/// Returns a message describing why the name was rejected.
+pub fn validate_output_name(name: &str) -> std::result::Result<(), String> {
+    if name.is_empty() || name.len() > MAX_OUTPUT_NAME_LEN {
+        return Err(format!(
+            "output name must be 1 to {} characters long, got {}",
+            MAX_OUTPUT_NAME_LEN,
+            name.len()
+        ));
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        return Err(format!(
+            "output name must start with a letter or digit: '{}'",
+            name
+        ));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.')))
+    {
+        return Err(format!(
+            "output name may only contain letters, digits, spaces, '-', '_' and '.', found '{}'",
+            c
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_output_name() {
+        assert!(validate_output_name(DEFAULT_OUTPUT_NAME).is_ok());
+        assert!(validate_output_name("keyrx Virtual Keyboard").is_ok());
+        assert!(validate_output_name("keyrx-laptop_2.0").is_ok());
+        assert!(validate_output_name(&"k".repeat(MAX_OUTPUT_NAME_LEN)).is_ok());
+
+        assert!(validate_output_name("").is_err());
+        assert!(validate_output_name(&"k".repeat(MAX_OUTPUT_NAME_LEN + 1)).is_err());
+        assert!(validate_output_name(" keyrx").is_err());
+        assert!(validate_output_name("keyrx\"").is_err());
+        assert!(validate_output_name("keyrx/kbd").is_err());
+        assert!(validate_output_name("keyrx\nkbd").is_err());
+    }
+
+    #[test]
+    fn test_event_counters_snapshot() {
+        let counters = EventCounters::new();
+        assert_eq!(counters.snapshot(), EventErrorCounts::default());
+
+        counters.record_injection_failure();
+        counters.record_capture_error();
+        counters.record_capture_error();
+        counters.record_dropped_event();
+
+        assert_eq!(
+            counters.snapshot(),
+            EventErrorCounts {
+                injection_failures: 1,
+                capture_errors: 2,
+                dropped_events: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_counters_output_counts() {
+        let counters = EventCounters::new();
+        counters.record_output_frames(1, 2);
+        counters.record_output_frames(2, 4);
+
+        assert_eq!(
+            counters.output_counts(),
+            OutputCounts {
+                events: 6,
+                frames: 3,
+            }
+        );
+        // Output totals are not errors
+        assert_eq!(counters.snapshot(), EventErrorCounts::default());
+    }
+
+    #[test]
+    fn test_output_sync_round_trips() {
+        for sync in [OutputSync::Action, OutputSync::Loop] {
+            assert_eq!(sync.to_string().parse::<OutputSync>(), Ok(sync));
+        }
+        assert_eq!(OutputSync::default(), OutputSync::Action);
+        assert!("frame".parse::<OutputSync>().is_err());
+    }
+
+    #[test]
+    fn test_event_log_ids_are_monotonic() {
+        let log = EventLog::new(10);
+        assert_eq!(log.tail(5), EventPage::default());
+
+        assert_eq!(log.push("a".to_string()), 1);
+        assert_eq!(log.push("b".to_string()), 2);
+        assert_eq!(log.push("c".to_string()), 3);
+
+        let tail = log.tail(2);
+        assert_eq!(tail.events, vec!["b", "c"]);
+        assert_eq!(tail.last_id, 3);
+    }
+
+    #[test]
+    fn test_event_log_since_returns_only_newer_events() {
+        let log = EventLog::new(10);
+        for event in ["a", "b", "c"] {
+            log.push(event.to_string());
+        }
+
+        assert_eq!(log.since(0).events, vec!["a", "b", "c"]);
+        assert_eq!(log.since(2).events, vec!["c"]);
+
+        // Polling from the returned cursor never repeats an event
+        let page = log.since(3);
+        assert!(page.events.is_empty());
+        assert_eq!(page.last_id, 3);
+        log.push("d".to_string());
+        assert_eq!(log.since(page.last_id).events, vec!["d"]);
+    }
+
+    #[test]
+    fn test_event_log_evicts_oldest_events() {
+        let log = EventLog::new(2);
+        for event in ["a", "b", "c", "d"] {
+            log.push(event.to_string());
+        }
+
+        assert_eq!(log.tail(10).events, vec!["c", "d"]);
+        // A cursor older than the buffer gets everything still retained
+        let page = log.since(1);
+        assert_eq!(page.events, vec!["c", "d"]);
+        assert_eq!(page.last_id, 4);
+    }
+}
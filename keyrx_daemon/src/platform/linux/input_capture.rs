@@ -323,13 +323,16 @@ impl EvdevInput {
 impl InputDevice for EvdevInput {
     /// Reads the next keyboard event from the device.
     ///
-    /// This method blocks until a key press or release event is available.
-    /// Repeat events (value=2) are automatically filtered out.
+    /// This method blocks until a key press, release or repeat event is
+    /// available. Repeat events (value=2) are returned as
+    /// `KeyEvent::repeat`, so the remapping engine can translate them for the
+    /// mapped key instead of the OS auto-repeating the physical one.
     ///
     /// # Returns
     ///
     /// - `Ok(KeyEvent::Press(keycode))` for key press events
     /// - `Ok(KeyEvent::Release(keycode))` for key release events
+    /// - `Ok(KeyEvent::repeat(keycode))` for auto-repeat events of a held key
     /// - `Err(DeviceError::EndOfStream)` when no more events (device disconnected)
     /// - `Err(DeviceError::Io)` on I/O errors
     ///
@@ -360,7 +363,7 @@ impl InputDevice for EvdevInput {
                     // since UNIX epoch. If the conversion fails, fall back to 0.
                     let timestamp_us = systemtime_to_micros(event.timestamp());
 
-                    // value: 0 = release, 1 = press, 2 = repeat (ignored)
+                    // value: 0 = release, 1 = press, 2 = repeat
                     match value {
                         1 => {
                             // Key press
@@ -377,7 +380,11 @@ impl InputDevice for EvdevInput {
                             // Unknown key - continue reading for known keys
                         }
                         2 => {
-                            // Key repeat - ignore, continue reading
+                            // Key repeat (auto-repeat while held)
+                            if let Some(keycode) = evdev_to_keycode(key.code()) {
+                                return Ok(KeyEvent::repeat(keycode).with_timestamp(timestamp_us));
+                            }
+                            // Unknown key - continue reading for known keys
                         }
                         _ => {
                             // Unknown event value - ignore
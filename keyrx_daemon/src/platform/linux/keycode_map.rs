@@ -167,6 +167,8 @@ pub fn keycode_to_uinput_key(keycode: KeyCode) -> Keyboard {
         KeyCode::Power => Keyboard::Misc(Misc::Power),
         KeyCode::Sleep => Keyboard::Misc(Misc::Sleep),
         KeyCode::Wake => Keyboard::Misc(Misc::WakeUp),
+        KeyCode::BrightnessDown => Keyboard::Misc(Misc::BrightnessDown),
+        KeyCode::BrightnessUp => Keyboard::Misc(Misc::BrightnessUp),
 
         // Browser keys (use Misc enum)
         KeyCode::BrowserBack => Keyboard::Misc(Misc::Back),
@@ -205,6 +207,7 @@ pub fn keycode_to_uinput_key(keycode: KeyCode) -> Keyboard {
         KeyCode::Yen => Keyboard::Misc(Misc::Yen),
         KeyCode::Ro => Keyboard::Misc(Misc::RO),
         KeyCode::KatakanaHiragana => Keyboard::Misc(Misc::KatakanaHiragana),
+        KeyCode::NumpadJpComma => Keyboard::KeyPad(KeyPad::AltComma),
 
         // Korean keyboard keys (한국어 키보드)
         KeyCode::Hangeul => Keyboard::Misc(Misc::Hangeul),
@@ -212,6 +215,7 @@ pub fn keycode_to_uinput_key(keycode: KeyCode) -> Keyboard {
 
         // ISO/European keyboard keys
         KeyCode::Iso102nd => Keyboard::Misc(Misc::ND102),
+        KeyCode::NumpadComma => Keyboard::KeyPad(KeyPad::Comma),
     }
 }
 
@@ -383,6 +387,8 @@ pub fn evdev_to_keycode(code: u16) -> Option<KeyCode> {
         Key::KEY_POWER => Some(KeyCode::Power),
         Key::KEY_SLEEP => Some(KeyCode::Sleep),
         Key::KEY_WAKEUP => Some(KeyCode::Wake),
+        Key::KEY_BRIGHTNESSDOWN => Some(KeyCode::BrightnessDown),
+        Key::KEY_BRIGHTNESSUP => Some(KeyCode::BrightnessUp),
 
         // Browser keys
         Key::KEY_BACK => Some(KeyCode::BrowserBack),
@@ -419,6 +425,7 @@ pub fn evdev_to_keycode(code: u16) -> Option<KeyCode> {
         Key::KEY_YEN => Some(KeyCode::Yen),
         Key::KEY_RO => Some(KeyCode::Ro),
         Key::KEY_KATAKANAHIRAGANA => Some(KeyCode::KatakanaHiragana),
+        Key::KEY_KPJPCOMMA => Some(KeyCode::NumpadJpComma),
 
         // Korean keyboard keys (한국어 키보드)
         Key::KEY_HANGEUL => Some(KeyCode::Hangeul),
@@ -426,6 +433,7 @@ pub fn evdev_to_keycode(code: u16) -> Option<KeyCode> {
 
         // ISO/European keyboard keys
         Key::KEY_102ND => Some(KeyCode::Iso102nd),
+        Key::KEY_KPCOMMA => Some(KeyCode::NumpadComma),
 
         // Unknown key - return None for passthrough handling
         _ => None,
@@ -593,6 +601,8 @@ pub fn keycode_to_evdev(keycode: KeyCode) -> u16 {
         KeyCode::Power => Key::KEY_POWER.code(),
         KeyCode::Sleep => Key::KEY_SLEEP.code(),
         KeyCode::Wake => Key::KEY_WAKEUP.code(),
+        KeyCode::BrightnessDown => Key::KEY_BRIGHTNESSDOWN.code(),
+        KeyCode::BrightnessUp => Key::KEY_BRIGHTNESSUP.code(),
 
         // Browser keys
         KeyCode::BrowserBack => Key::KEY_BACK.code(),
@@ -629,6 +639,7 @@ pub fn keycode_to_evdev(keycode: KeyCode) -> u16 {
         KeyCode::Yen => Key::KEY_YEN.code(),
         KeyCode::Ro => Key::KEY_RO.code(),
         KeyCode::KatakanaHiragana => Key::KEY_KATAKANAHIRAGANA.code(),
+        KeyCode::NumpadJpComma => Key::KEY_KPJPCOMMA.code(),
 
         // Korean keyboard keys (한국어 키보드)
         KeyCode::Hangeul => Key::KEY_HANGEUL.code(),
@@ -636,6 +647,7 @@ pub fn keycode_to_evdev(keycode: KeyCode) -> u16 {
 
         // ISO/European keyboard keys
         KeyCode::Iso102nd => Key::KEY_102ND.code(),
+        KeyCode::NumpadComma => Key::KEY_KPCOMMA.code(),
     }
 }
 
@@ -785,6 +797,59 @@ mod tests {
         );
     }
 
+    /// Test numpad keys never share an evdev code with their main-row twins
+    #[test]
+    fn test_numpad_distinct_from_main_row() {
+        let pairs = [
+            (KeyCode::Numpad0, KeyCode::Num0),
+            (KeyCode::Numpad1, KeyCode::Num1),
+            (KeyCode::Numpad2, KeyCode::Num2),
+            (KeyCode::Numpad3, KeyCode::Num3),
+            (KeyCode::Numpad4, KeyCode::Num4),
+            (KeyCode::Numpad5, KeyCode::Num5),
+            (KeyCode::Numpad6, KeyCode::Num6),
+            (KeyCode::Numpad7, KeyCode::Num7),
+            (KeyCode::Numpad8, KeyCode::Num8),
+            (KeyCode::Numpad9, KeyCode::Num9),
+            (KeyCode::NumpadEnter, KeyCode::Enter),
+            (KeyCode::NumpadAdd, KeyCode::Equal),
+            (KeyCode::NumpadSubtract, KeyCode::Minus),
+            (KeyCode::NumpadDivide, KeyCode::Slash),
+            (KeyCode::NumpadDecimal, KeyCode::Period),
+        ];
+
+        for (numpad, main_row) in pairs {
+            let numpad_code = keycode_to_evdev(numpad);
+            let main_row_code = keycode_to_evdev(main_row);
+            assert_ne!(numpad_code, main_row_code, "{:?} vs {:?}", numpad, main_row);
+            assert_eq!(evdev_to_keycode(numpad_code), Some(numpad));
+            assert_eq!(evdev_to_keycode(main_row_code), Some(main_row));
+        }
+    }
+
+    /// Test a compiled KP_1 -> 1 mapping reads and injects different codes
+    #[test]
+    fn test_compiled_numpad_mapping_uses_distinct_codes() {
+        use keyrx_compiler::parser::Parser;
+        use keyrx_core::config::{BaseKeyMapping, KeyMapping};
+
+        let script = r#"
+            device_start("*");
+            map("KP_1", "VK_1");
+            device_end();
+        "#;
+        let config = Parser::new()
+            .parse_string(script, std::path::Path::new("numpad.rhai"))
+            .expect("config should compile");
+
+        let KeyMapping::Base(BaseKeyMapping::Simple { from, to }) = &config.devices[0].mappings[0]
+        else {
+            panic!("expected a simple mapping");
+        };
+        assert_eq!(keycode_to_evdev(*from), Key::KEY_KP1.code());
+        assert_eq!(keycode_to_evdev(*to), Key::KEY_1.code());
+    }
+
     /// Test unknown key returns None
     #[test]
     fn test_unknown_key_returns_none() {
@@ -869,4 +934,49 @@ mod tests {
             assert_eq!(result, Some(keycode), "Round-trip failed for {:?}", keycode);
         }
     }
+
+    #[test]
+    fn test_international_keys_roundtrip() {
+        let keys = [
+            (KeyCode::Iso102nd, Key::KEY_102ND),
+            (KeyCode::Yen, Key::KEY_YEN),
+            (KeyCode::Ro, Key::KEY_RO),
+            (KeyCode::Henkan, Key::KEY_HENKAN),
+            (KeyCode::Muhenkan, Key::KEY_MUHENKAN),
+            (KeyCode::Katakana, Key::KEY_KATAKANA),
+            (KeyCode::Hiragana, Key::KEY_HIRAGANA),
+            (KeyCode::KatakanaHiragana, Key::KEY_KATAKANAHIRAGANA),
+            (KeyCode::Zenkaku, Key::KEY_ZENKAKUHANKAKU),
+            (KeyCode::NumpadJpComma, Key::KEY_KPJPCOMMA),
+            (KeyCode::Hangeul, Key::KEY_HANGEUL),
+            (KeyCode::Hanja, Key::KEY_HANJA),
+            (KeyCode::NumpadComma, Key::KEY_KPCOMMA),
+        ];
+
+        for (keycode, key) in keys {
+            assert_eq!(keycode_to_evdev(keycode), key.code(), "{:?}", keycode);
+            assert_eq!(evdev_to_keycode(key.code()), Some(keycode), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_media_and_system_keys_mapping() {
+        let keys = [
+            (KeyCode::MediaPlayPause, Key::KEY_PLAYPAUSE),
+            (KeyCode::MediaNext, Key::KEY_NEXTSONG),
+            (KeyCode::MediaPrevious, Key::KEY_PREVIOUSSONG),
+            (KeyCode::MediaStop, Key::KEY_STOPCD),
+            (KeyCode::Mute, Key::KEY_MUTE),
+            (KeyCode::VolumeUp, Key::KEY_VOLUMEUP),
+            (KeyCode::VolumeDown, Key::KEY_VOLUMEDOWN),
+            (KeyCode::BrightnessDown, Key::KEY_BRIGHTNESSDOWN),
+            (KeyCode::BrightnessUp, Key::KEY_BRIGHTNESSUP),
+            (KeyCode::Sleep, Key::KEY_SLEEP),
+        ];
+
+        for (keycode, key) in keys {
+            assert_eq!(keycode_to_evdev(keycode), key.code(), "{:?}", keycode);
+            assert_eq!(evdev_to_keycode(key.code()), Some(keycode), "{:?}", key);
+        }
+    }
 }
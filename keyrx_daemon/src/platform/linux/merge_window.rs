@@ -0,0 +1,165 @@
+//! Inter-device merge window.
+//!
+//! The Linux platform reads at most one event per device per pass, so two
+//! keystrokes typed on different keyboards a fraction of a millisecond apart
+//! can be handled in the wrong order. When enabled, the merge window holds
+//! every captured event for a short, bounded delay and releases the buffered
+//! events in capture timestamp order, so cross-device chords reach the
+//! remapping engine in the order they were typed.
+//!
+//! The window is opt-in: every event waits up to its full length before it is
+//! processed, which adds that much latency to each keystroke.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use keyrx_core::runtime::event::KeyEvent;
+
+/// An event waiting in the merge window.
+struct Pending {
+    /// When the event was read, in microseconds on the window's clock.
+    read_at_us: u64,
+    /// Index of the source device in the device manager.
+    device_index: usize,
+    event: KeyEvent,
+}
+
+/// Buffers events from several devices and releases them in timestamp order.
+pub(crate) struct MergeWindow {
+    window_us: u64,
+    /// Clock origin for read times, so the delay bound is monotonic.
+    started: Instant,
+    /// Buffered events, sorted by capture timestamp.
+    pending: VecDeque<Pending>,
+}
+
+impl MergeWindow {
+    /// Creates a merge window that holds each event for `window`.
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window_us: window.as_micros() as u64,
+            started: Instant::now(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Current time on the window's clock, for [`push`](Self::push) and
+    /// [`pop_due`](Self::pop_due).
+    pub(crate) fn now_us(&self) -> u64 {
+        self.started.elapsed().as_micros() as u64
+    }
+
+    /// Buffers an event read from the device at `device_index` at `now_us`.
+    ///
+    /// Events with equal timestamps keep the order they were pushed in.
+    pub(crate) fn push(&mut self, device_index: usize, event: KeyEvent, now_us: u64) {
+        let timestamp = event.timestamp_us();
+        let position = self
+            .pending
+            .partition_point(|pending| pending.event.timestamp_us() <= timestamp);
+        self.pending.insert(
+            position,
+            Pending {
+                read_at_us: now_us,
+                device_index,
+                event,
+            },
+        );
+    }
+
+    /// Removes the earliest buffered event if it may be processed at `now_us`.
+    ///
+    /// An event is due once it has waited the full window since it was read.
+    /// Any earlier-timestamped event is released before it even if its own
+    /// window is still open, so the output never goes back in time and no
+    /// event waits longer than the window.
+    pub(crate) fn pop_due(&mut self, now_us: u64) -> Option<(usize, KeyEvent)> {
+        let any_due = self
+            .pending
+            .iter()
+            .any(|pending| now_us >= pending.read_at_us.saturating_add(self.window_us));
+        if !any_due {
+            return None;
+        }
+        self.pending
+            .pop_front()
+            .map(|pending| (pending.device_index, pending.event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::KeyCode;
+
+    const WINDOW: Duration = Duration::from_millis(2);
+
+    fn drain(window: &mut MergeWindow, now_us: u64) -> Vec<(usize, KeyCode)> {
+        std::iter::from_fn(|| window.pop_due(now_us))
+            .map(|(device, event)| (device, event.keycode()))
+            .collect()
+    }
+
+    #[test]
+    fn test_holds_events_until_window_elapses() {
+        let mut window = MergeWindow::new(WINDOW);
+        window.push(0, KeyEvent::press(KeyCode::A).with_timestamp(1_000), 0);
+
+        assert!(window.pop_due(1_999).is_none());
+        assert_eq!(drain(&mut window, 2_000), vec![(0, KeyCode::A)]);
+    }
+
+    #[test]
+    fn test_interleaved_devices_released_in_timestamp_order() {
+        let mut window = MergeWindow::new(WINDOW);
+
+        // One pass reads device 0 first, although device 1 was pressed earlier
+        window.push(0, KeyEvent::press(KeyCode::A).with_timestamp(10_300), 0);
+        window.push(
+            1,
+            KeyEvent::press(KeyCode::LShift).with_timestamp(10_100),
+            0,
+        );
+        // Next pass: the reverse interleaving
+        window.push(0, KeyEvent::release(KeyCode::A).with_timestamp(10_600), 500);
+        window.push(1, KeyEvent::press(KeyCode::B).with_timestamp(10_400), 500);
+
+        // Only the first pass is due; the second keeps its place
+        assert_eq!(
+            drain(&mut window, 2_000),
+            vec![(1, KeyCode::LShift), (0, KeyCode::A)]
+        );
+        assert_eq!(
+            drain(&mut window, 2_500),
+            vec![(1, KeyCode::B), (0, KeyCode::A)]
+        );
+        assert!(window.pop_due(10_000).is_none());
+    }
+
+    #[test]
+    fn test_earlier_event_released_with_due_later_event() {
+        let mut window = MergeWindow::new(WINDOW);
+
+        // Read late but typed first, e.g. a slow device
+        window.push(0, KeyEvent::press(KeyCode::A).with_timestamp(5_000), 0);
+        window.push(1, KeyEvent::press(KeyCode::B).with_timestamp(4_000), 1_500);
+
+        // A is due; B is still inside its window but must not follow A
+        assert_eq!(
+            drain(&mut window, 2_000),
+            vec![(1, KeyCode::B), (0, KeyCode::A)]
+        );
+    }
+
+    #[test]
+    fn test_equal_timestamps_keep_read_order() {
+        let mut window = MergeWindow::new(WINDOW);
+        window.push(1, KeyEvent::press(KeyCode::B).with_timestamp(7_000), 0);
+        window.push(0, KeyEvent::press(KeyCode::A).with_timestamp(7_000), 0);
+
+        assert_eq!(
+            drain(&mut window, 2_000),
+            vec![(1, KeyCode::B), (0, KeyCode::A)]
+        );
+    }
+}
@@ -11,11 +11,14 @@
 mod device_discovery;
 mod input_capture;
 mod keycode_map;
+mod merge_window;
+mod monitor;
 mod output_injection;
 pub mod tray;
 
 // Re-export public types
 pub use input_capture::EvdevInput;
+pub use monitor::run_monitor;
 pub use output_injection::UinputOutput;
 pub use tray::LinuxSystemTray;
 
@@ -23,10 +26,20 @@ pub use tray::LinuxSystemTray;
 #[allow(unused_imports)] // keycode_to_evdev will be used for output injection
 pub use keycode_map::{evdev_to_keycode, keycode_to_evdev, keycode_to_uinput_key};
 
-use keyrx_core::config::DeviceConfig;
+use keyrx_core::config::{DeviceConfig, KeyCode};
+use keyrx_core::runtime::event::KeyEvent;
 
-use crate::device_manager::DeviceManager;
-use crate::platform::{DeviceError, InputDevice, OutputDevice, ProcessResult};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::device_manager::{DeviceManager, ManagedDevice};
+use crate::platform::{
+    DeviceError, EventCounters, InputDevice, OutputDevice, OutputSync, ProcessResult,
+    DEFAULT_OUTPUT_NAME,
+};
+
+use merge_window::MergeWindow;
+use output_injection::frame_actions;
 
 /// Linux platform structure for keyboard input/output operations.
 ///
@@ -72,6 +85,32 @@ pub struct LinuxPlatform {
     device_manager: Option<DeviceManager>,
     /// Virtual output device for injecting remapped events.
     output_device: Option<UinputOutput>,
+    /// Keys the active configuration can emit, advertised on the output
+    /// device. `None` advertises every keyboard key.
+    output_keys: Option<Vec<KeyCode>>,
+    /// Device IDs to grab. `None` grabs every matched device.
+    grab_ids: Option<Vec<String>>,
+    /// Most matched devices to grab when no IDs are given. `None` grabs
+    /// them all.
+    max_devices: Option<usize>,
+    /// Name of the virtual output device.
+    output_name: String,
+    /// Fail initialization instead of dropping output keys the kernel
+    /// cannot advertise.
+    strict_keys: bool,
+    /// Reorders events from different devices by capture timestamp before
+    /// processing. `None` processes each event as soon as it is read.
+    merge_window: Option<MergeWindow>,
+    /// Counters for device read errors and disconnects, if the daemon
+    /// provided them.
+    event_counters: Option<Arc<EventCounters>>,
+    /// Injected events waiting for the next flush.
+    pending_output: Vec<KeyEvent>,
+    /// End of each injected action (one `inject_outputs` call) in
+    /// `pending_output`.
+    pending_actions: Vec<usize>,
+    /// Whether the actions of one flush may share output frames.
+    output_sync: OutputSync,
 }
 
 impl LinuxPlatform {
@@ -81,14 +120,88 @@ impl LinuxPlatform {
         Self {
             device_manager: None,
             output_device: None,
+            output_keys: None,
+            grab_ids: None,
+            max_devices: None,
+            output_name: DEFAULT_OUTPUT_NAME.to_string(),
+            strict_keys: false,
+            merge_window: None,
+            event_counters: None,
+            pending_output: Vec::new(),
+            pending_actions: Vec::new(),
+            output_sync: OutputSync::default(),
         }
     }
 
+    /// Restricts the virtual output device to the keys the configuration
+    /// can emit, plus every key the grabbed keyboards support so unmapped
+    /// keys still pass through.
+    ///
+    /// Takes effect the next time the output device is created by
+    /// [`init`](Self::init).
+    pub fn set_output_keys(&mut self, keys: &[KeyCode]) {
+        self.output_keys = Some(keys.to_vec());
+    }
+
+    /// Sets the name of the virtual output device (default
+    /// [`DEFAULT_OUTPUT_NAME`]).
+    ///
+    /// Takes effect the next time the output device is created by
+    /// [`init`](Self::init).
+    pub fn set_output_name(&mut self, name: &str) {
+        self.output_name = name.to_string();
+    }
+
+    /// Makes [`init`](Self::init) fail when the kernel cannot advertise some
+    /// output keys, instead of warning and dropping events for them.
+    pub fn set_strict_keys(&mut self, strict: bool) {
+        self.strict_keys = strict;
+    }
+
+    /// Holds events for up to `window` so events from different devices are
+    /// processed in the order they were typed rather than the order they
+    /// were read.
+    ///
+    /// Every event is delayed by up to the window. `None` or a zero window
+    /// turns merging off (the default).
+    pub fn set_merge_window(&mut self, window: Option<Duration>) {
+        self.merge_window = window
+            .filter(|window| !window.is_zero())
+            .map(MergeWindow::new);
+    }
+
+    /// Chooses whether the output of the inputs handled in one event loop
+    /// iteration may share `SYN_REPORT` frames (default
+    /// [`OutputSync::Action`]).
+    pub fn set_output_sync(&mut self, sync: OutputSync) {
+        self.output_sync = sync;
+    }
+
+    /// Restricts grabbing to the devices with the given IDs.
+    ///
+    /// Other matched devices are closed during [`init`](Self::init) so they
+    /// stay usable by the rest of the system. An ID that matches no
+    /// discovered device makes [`init`](Self::init) fail.
+    pub fn set_grab_ids(&mut self, device_ids: &[String]) {
+        self.grab_ids = Some(device_ids.to_vec());
+    }
+
+    /// Refuses to grab more than `max` matched devices.
+    ///
+    /// [`init`](Self::init) fails and lists the matched devices when more
+    /// match, so a loose pattern can be tightened before anything is
+    /// grabbed. Ignored when [`set_grab_ids`](Self::set_grab_ids) already
+    /// names the devices to grab.
+    pub fn set_max_devices(&mut self, max: Option<usize>) {
+        self.max_devices = max;
+    }
+
     /// Initializes the platform with input and output devices.
     ///
     /// This method discovers keyboards matching the provided device configurations,
     /// creates a virtual output device for event injection, and grabs exclusive
-    /// access to all managed input devices.
+    /// access to the managed input devices (all of them, or only those set via
+    /// [`set_grab_ids`](Self::set_grab_ids)).
     ///
     /// # Arguments
     ///
@@ -98,8 +211,13 @@ impl LinuxPlatform {
     ///
     /// Returns an error if:
     /// - No matching keyboard devices are found
+    /// - A device ID passed to [`set_grab_ids`](Self::set_grab_ids) is unknown
+    /// - More devices match than [`set_max_devices`](Self::set_max_devices)
+    ///   allows
     /// - Cannot access input devices (permission denied)
     /// - Cannot create virtual output device
+    /// - The kernel cannot advertise some output keys and
+    ///   [`set_strict_keys`](Self::set_strict_keys) is enabled
     /// - Cannot grab exclusive access to devices
     ///
     /// # Example
@@ -115,7 +233,11 @@ impl LinuxPlatform {
     /// ```
     pub fn init(&mut self, configs: &[DeviceConfig]) -> Result<(), Box<dyn std::error::Error>> {
         // Discover and open all matching keyboard devices
-        let device_manager = DeviceManager::discover(configs)?;
+        let max_devices = self.max_devices.filter(|_| self.grab_ids.is_none());
+        let mut device_manager = DeviceManager::discover(configs, max_devices)?;
+        if let Some(ids) = &self.grab_ids {
+            device_manager.select(ids)?;
+        }
 
         eprintln!(
             "[keyrx] Discovered {} keyboard device(s)",
@@ -130,11 +252,19 @@ impl LinuxPlatform {
         }
 
         // Create virtual output device for event injection
-        let output_device = UinputOutput::create("keyrx")?;
+        let output_device = match &self.output_keys {
+            Some(output_keys) => {
+                let keys = advertised_keys(&device_manager, output_keys);
+                eprintln!("[keyrx] Advertising {} key(s) on output device", keys.len());
+                UinputOutput::create_with_keys(&self.output_name, &keys)?
+            }
+            None => UinputOutput::create(&self.output_name)?,
+        };
         eprintln!(
             "[keyrx] Created virtual output device: {}",
             output_device.name()
         );
+        self.check_unsupported_keys(output_device.unsupported_keys())?;
 
         // Note: System tray is now managed in main.rs to ensure proper GTK event loop integration
         // LinuxPlatform no longer manages the tray directly
@@ -142,18 +272,49 @@ impl LinuxPlatform {
         self.device_manager = Some(device_manager);
         self.output_device = Some(output_device);
 
-        // Grab exclusive access to all input devices
-        self.grab_all_devices()?;
+        // Grab exclusive access to the selected input devices
+        self.grab_selected()?;
 
         Ok(())
     }
 
-    /// Grabs exclusive access to all managed input devices.
+    /// Reports output keys the kernel refused to advertise.
+    ///
+    /// Warns and lets their events be dropped, or fails in strict mode.
+    fn check_unsupported_keys(
+        &self,
+        unsupported: &[KeyCode],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+
+        let names: Vec<String> = unsupported.iter().map(|key| format!("{:?}", key)).collect();
+        if self.strict_keys {
+            return Err(format!(
+                "kernel does not support output key(s): {} (drop --strict-keys to run without them)",
+                names.join(", ")
+            )
+            .into());
+        }
+
+        eprintln!(
+            "[keyrx] Warning: kernel does not support {} output key(s); events for them will be dropped: {}",
+            unsupported.len(),
+            names.join(", ")
+        );
+        Ok(())
+    }
+
+    /// Grabs exclusive access to every managed input device.
+    ///
+    /// With [`set_grab_ids`](Self::set_grab_ids), unselected devices were
+    /// already dropped from the device manager during discovery.
     ///
     /// # Errors
     ///
     /// Returns an error if grabbing any device fails.
-    fn grab_all_devices(&mut self) -> Result<(), DeviceError> {
+    fn grab_selected(&mut self) -> Result<(), DeviceError> {
         let device_manager = self
             .device_manager
             .as_mut()
@@ -200,6 +361,22 @@ impl LinuxPlatform {
             .unwrap_or(0)
     }
 
+    /// Returns the number of managed devices currently grabbed.
+    ///
+    /// Lower than [`device_count`](Self::device_count) when a device was
+    /// released or never grabbed, in which case its input is not remapped.
+    #[must_use]
+    pub fn active_device_count(&self) -> usize {
+        self.device_manager
+            .as_ref()
+            .map(|dm| {
+                dm.devices()
+                    .filter(|device| device.input().is_grabbed())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
     /// Runs the main event processing loop.
     ///
     /// This method polls all managed input devices for events, tags each event
@@ -259,8 +436,6 @@ impl LinuxPlatform {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn process_events(&mut self) -> Result<ProcessResult, Box<dyn std::error::Error>> {
-        use keyrx_core::runtime::event::process_event;
-
         // Note: System tray is now managed in main.rs
 
         let device_manager = self
@@ -273,34 +448,53 @@ impl LinuxPlatform {
             .as_mut()
             .ok_or_else(|| DeviceError::NotFound("output device not initialized".to_string()))?;
 
-        // Process one event from each device that has events available
-        for device in device_manager.devices_mut() {
-            // Try to get the next event from this device (non-blocking would be ideal)
+        let Some(merge_window) = self.merge_window.as_mut() else {
+            // Process one event from each device that has events available
+            for device in device_manager.devices_mut() {
+                // Try to get the next event from this device (non-blocking would be ideal)
+                match device.input_mut().next_event() {
+                    Ok(event) => {
+                        // Tag the event with the device ID
+                        let device_id = device.device_id();
+                        let tagged_event = event.with_device_id(device_id);
+                        process_device_event(device, tagged_event, output_device)?;
+                    }
+                    Err(DeviceError::EndOfStream) => {
+                        // No more events from this device right now
+                        continue;
+                    }
+                    Err(e) => {
+                        // Count and log the error but continue with other devices
+                        record_read_error(self.event_counters.as_deref(), &e);
+                        eprintln!("[keyrx] Error reading from device: {}", e);
+                    }
+                }
+            }
+            return Ok(ProcessResult::Continue);
+        };
+
+        // Buffer one event from each device, then process whatever has
+        // waited out the merge window, oldest capture first
+        let now_us = merge_window.now_us();
+        for (index, device) in device_manager.devices_mut().enumerate() {
             match device.input_mut().next_event() {
                 Ok(event) => {
-                    // Tag the event with the device ID
                     let device_id = device.device_id();
-                    let tagged_event = event.with_device_id(device_id);
-
-                    // Process the event through the device's lookup and state
-                    let (lookup, state) = device.lookup_and_state_mut();
-                    let output_events = process_event(tagged_event, lookup, state);
-
-                    // Inject output events
-                    for output_event in output_events {
-                        output_device.inject_event(output_event)?;
-                    }
-                }
-                Err(DeviceError::EndOfStream) => {
-                    // No more events from this device right now
-                    continue;
+                    merge_window.push(index, event.with_device_id(device_id), now_us);
                 }
+                Err(DeviceError::EndOfStream) => continue,
                 Err(e) => {
-                    // Log error but continue with other devices
+                    record_read_error(self.event_counters.as_deref(), &e);
                     eprintln!("[keyrx] Error reading from device: {}", e);
                 }
             }
         }
+        while let Some((index, event)) = merge_window.pop_due(now_us) {
+            // The device list only changes on reload, which rebuilds the platform
+            if let Some(device) = device_manager.get_device_mut(index) {
+                process_device_event(device, event, output_device)?;
+            }
+        }
 
         Ok(ProcessResult::Continue)
     }
@@ -342,6 +536,59 @@ impl LinuxPlatform {
     }
 }
 
+/// Runs one tagged event through the device's lookup and state and injects
+/// the resulting output.
+fn process_device_event(
+    device: &mut ManagedDevice,
+    event: KeyEvent,
+    output_device: &mut UinputOutput,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use keyrx_core::runtime::event::process_event;
+
+    let (lookup, state) = device.lookup_and_state_mut();
+    if !state.time_windows().is_empty() {
+        state.set_minute_of_day(crate::daemon::remapping_state::local_minute_of_day());
+    }
+    let mut output_events = process_event(event, lookup, state);
+    // Without a clock to wait on, macro steps after a wait() go out at once
+    output_events.extend(state.take_scheduled_outputs());
+
+    output_device.inject_batch(&output_events)?;
+    Ok(())
+}
+
+/// Records a device read error, counting disconnects as dropped events.
+fn record_read_error(counters: Option<&EventCounters>, error: &DeviceError) {
+    let Some(counters) = counters else {
+        return;
+    };
+    if is_disconnect(error) {
+        counters.record_dropped_event();
+    } else {
+        counters.record_capture_error();
+    }
+}
+
+/// Returns `true` if `error` means the device node went away (unplugged).
+fn is_disconnect(error: &DeviceError) -> bool {
+    matches!(error, DeviceError::Io(e) if e.raw_os_error() == Some(nix::errno::Errno::ENODEV as i32))
+}
+
+/// Returns the keys to advertise on the output device: every key supported
+/// by a managed keyboard (for passthrough) plus `output_keys`.
+fn advertised_keys(device_manager: &DeviceManager, output_keys: &[KeyCode]) -> Vec<KeyCode> {
+    let mut keys: Vec<KeyCode> = device_manager
+        .devices()
+        .filter_map(|device| device.input().device().supported_keys())
+        .flat_map(|supported| supported.iter())
+        .filter_map(|key| evdev_to_keycode(key.code()))
+        .chain(output_keys.iter().copied())
+        .collect();
+    keys.sort_unstable_by_key(|key| *key as u16);
+    keys.dedup();
+    keys
+}
+
 impl Default for LinuxPlatform {
     fn default() -> Self {
         Self::new()
@@ -364,8 +611,13 @@ impl crate::platform::Platform for LinuxPlatform {
         let wildcard_config = DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".to_string(),
+                excludes: Vec::new(),
             },
             mappings: vec![],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         };
 
         // Call the existing init method
@@ -375,6 +627,39 @@ impl crate::platform::Platform for LinuxPlatform {
             })
     }
 
+    fn set_output_keys(&mut self, keys: &[KeyCode]) {
+        LinuxPlatform::set_output_keys(self, keys);
+    }
+
+    fn set_output_name(&mut self, name: &str) {
+        LinuxPlatform::set_output_name(self, name);
+    }
+
+    fn set_strict_keys(&mut self, strict: bool) {
+        LinuxPlatform::set_strict_keys(self, strict);
+    }
+
+    fn set_merge_window(&mut self, window: Option<Duration>) {
+        LinuxPlatform::set_merge_window(self, window);
+    }
+
+    fn set_max_devices(&mut self, max: Option<usize>) {
+        LinuxPlatform::set_max_devices(self, max);
+    }
+
+    fn set_output_sync(&mut self, sync: OutputSync) {
+        LinuxPlatform::set_output_sync(self, sync);
+    }
+
+    fn set_event_counters(&mut self, counters: Arc<EventCounters>) {
+        self.event_counters = Some(counters);
+    }
+
+    fn grab_specific(&mut self, device_ids: &[String]) -> crate::platform::PlatformResult<()> {
+        self.set_grab_ids(device_ids);
+        Ok(())
+    }
+
     fn capture_input(
         &mut self,
     ) -> crate::platform::PlatformResult<keyrx_core::runtime::event::KeyEvent> {
@@ -388,21 +673,44 @@ impl crate::platform::Platform for LinuxPlatform {
                     reason: "device manager not initialized".to_string(),
                 })?;
 
-        // Try to get the next event from any device
-        // In the Platform trait model, we need to return ONE event, not process all devices
-        for device in device_manager.devices_mut() {
-            match device.input_mut().next_event() {
-                Ok(event) => {
-                    // Tag the event with the device ID
-                    let device_id = device.device_id();
-                    return Ok(event.with_device_id(device_id));
-                }
-                Err(DeviceError::EndOfStream) => {
-                    // No events from this device, try the next one
-                    continue;
+        if let Some(merge_window) = self.merge_window.as_mut() {
+            // Buffer one event from each device and return the oldest one
+            // that has waited out the merge window
+            let now_us = merge_window.now_us();
+            for (index, device) in device_manager.devices_mut().enumerate() {
+                match device.input_mut().next_event() {
+                    Ok(event) => {
+                        let device_id = device.device_id();
+                        merge_window.push(index, event.with_device_id(device_id), now_us);
+                    }
+                    Err(DeviceError::EndOfStream) => continue,
+                    Err(e) => {
+                        record_read_error(self.event_counters.as_deref(), &e);
+                        return Err(PlatformError::Io(std::io::Error::other(e.to_string())));
+                    }
                 }
-                Err(e) => {
-                    return Err(PlatformError::Io(std::io::Error::other(e.to_string())));
+            }
+            if let Some((_, event)) = merge_window.pop_due(now_us) {
+                return Ok(event);
+            }
+        } else {
+            // Try to get the next event from any device
+            // In the Platform trait model, we need to return ONE event, not process all devices
+            for device in device_manager.devices_mut() {
+                match device.input_mut().next_event() {
+                    Ok(event) => {
+                        // Tag the event with the device ID
+                        let device_id = device.device_id();
+                        return Ok(event.with_device_id(device_id));
+                    }
+                    Err(DeviceError::EndOfStream) => {
+                        // No events from this device, try the next one
+                        continue;
+                    }
+                    Err(e) => {
+                        record_read_error(self.event_counters.as_deref(), &e);
+                        return Err(PlatformError::Io(std::io::Error::other(e.to_string())));
+                    }
                 }
             }
         }
@@ -416,9 +724,39 @@ impl crate::platform::Platform for LinuxPlatform {
     fn inject_output(
         &mut self,
         event: keyrx_core::runtime::event::KeyEvent,
+    ) -> crate::platform::PlatformResult<()> {
+        crate::platform::Platform::inject_outputs(self, std::slice::from_ref(&event))
+    }
+
+    fn inject_outputs(
+        &mut self,
+        events: &[keyrx_core::runtime::event::KeyEvent],
     ) -> crate::platform::PlatformResult<()> {
         use crate::platform::PlatformError;
 
+        if self.output_device.is_none() {
+            return Err(PlatformError::InitializationFailed {
+                reason: "output device not initialized".to_string(),
+            });
+        }
+
+        // Written by flush(), framed according to the output sync setting
+        if !events.is_empty() {
+            self.pending_output.extend_from_slice(events);
+            self.pending_actions.push(self.pending_output.len());
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> crate::platform::PlatformResult<()> {
+        use crate::platform::PlatformError;
+
+        if self.pending_output.is_empty() {
+            return Ok(());
+        }
+        let events = std::mem::take(&mut self.pending_output);
+        let action_ends = std::mem::take(&mut self.pending_actions);
+
         let output_device =
             self.output_device
                 .as_mut()
@@ -426,12 +764,17 @@ impl crate::platform::Platform for LinuxPlatform {
                     reason: "output device not initialized".to_string(),
                 })?;
 
+        let frames = frame_actions(&events, &action_ends, self.output_sync);
         output_device
-            .inject_event(event)
+            .inject_frames(&frames)
             .map_err(|e| PlatformError::InjectionFailed {
                 reason: e.to_string(),
                 suggestion: "Check uinput device permissions and kernel module".to_string(),
-            })
+            })?;
+        if let Some(counters) = &self.event_counters {
+            counters.record_output_frames(frames.len(), events.len());
+        }
+        Ok(())
     }
 
     fn list_devices(&self) -> crate::platform::PlatformResult<Vec<crate::platform::DeviceInfo>> {
@@ -456,6 +799,7 @@ impl crate::platform::Platform for LinuxPlatform {
                     // KeyboardInfo doesn't have USB IDs, use placeholders
                     vendor_id: 0,
                     product_id: 0,
+                    capabilities: info.capabilities,
                 }
             })
             .collect();
@@ -463,9 +807,18 @@ impl crate::platform::Platform for LinuxPlatform {
         Ok(devices)
     }
 
+    fn active_device_count(&self) -> usize {
+        LinuxPlatform::active_device_count(self)
+    }
+
     fn shutdown(&mut self) -> crate::platform::PlatformResult<()> {
         use crate::platform::PlatformError;
 
+        // Deliver buffered output (e.g. pending releases) before releasing
+        if let Err(e) = crate::platform::Platform::flush(self) {
+            log::warn!("Failed to flush output on shutdown: {}", e);
+        }
+
         // Call existing shutdown method
         self.shutdown()
             .map_err(|e| PlatformError::Io(std::io::Error::other(e.to_string())))
@@ -484,6 +837,25 @@ mod tests {
         let _ = platform; // Compile-time check that trait object works
     }
 
+    #[test]
+    fn test_record_read_error_classifies_disconnects() {
+        let counters = EventCounters::new();
+        let unplugged = DeviceError::Io(std::io::Error::from_raw_os_error(
+            nix::errno::Errno::ENODEV as i32,
+        ));
+        let other = DeviceError::Io(std::io::Error::other("read failed"));
+
+        record_read_error(Some(&counters), &unplugged);
+        record_read_error(Some(&counters), &other);
+        record_read_error(Some(&counters), &other);
+        record_read_error(None, &other);
+
+        let counts = counters.snapshot();
+        assert_eq!(counts.dropped_events, 1);
+        assert_eq!(counts.capture_errors, 2);
+        assert_eq!(counts.injection_failures, 0);
+    }
+
     #[test]
     fn test_linux_platform_implements_platform() {
         // Verify LinuxPlatform implements all Platform trait methods
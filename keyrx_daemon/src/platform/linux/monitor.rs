@@ -0,0 +1,147 @@
+//! Live monitor mode (`run --monitor`).
+//!
+//! Matched devices are opened without grabbing them, so the real keystrokes
+//! still reach the OS. Each event is run through the device's configuration
+//! and the output it *would* produce is printed instead of being injected,
+//! which lets a config be tuned live before committing to a grab.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use keyrx_core::config::DeviceConfig;
+use keyrx_core::runtime::event::process_event;
+use keyrx_core::runtime::KeyEvent;
+
+use crate::device_manager::{DeviceManager, DiscoveryError, ManagedDevice};
+use crate::platform::{DeviceError, InputDevice};
+
+/// Reads matched devices without grabbing them and prints the would-be
+/// output of every event until `stop` is set.
+///
+/// `only_device` restricts monitoring to the listed device IDs, like
+/// `run --only-device`.
+///
+/// # Errors
+///
+/// Returns an error if no configured device is connected or an ID in
+/// `only_device` does not match one.
+pub fn run_monitor(
+    configs: &[DeviceConfig],
+    only_device: &[String],
+    stop: &AtomicBool,
+) -> Result<(), DiscoveryError> {
+    let mut device_manager = DeviceManager::discover(configs, None)?;
+    if !only_device.is_empty() {
+        device_manager.select(only_device)?;
+    }
+
+    println!(
+        "Monitoring {} device(s) without grabbing; keystrokes still reach the OS.",
+        device_manager.device_count()
+    );
+    for device in device_manager.devices() {
+        println!("  - {} ({})", device.info().name, device.device_id());
+    }
+    println!("Nothing is injected. Press Ctrl+C to stop.");
+    println!();
+
+    // Reads block, so each device gets its own thread. The threads are not
+    // joined: they hold no grab, and exiting the process closes the devices.
+    for device in device_manager.into_devices() {
+        thread::spawn(move || monitor_device(device));
+    }
+
+    while !stop.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
+
+fn monitor_device(mut device: ManagedDevice) {
+    let device_id = device.device_id();
+    loop {
+        let event = match device.input_mut().next_event() {
+            Ok(event) => event.with_device_id(device_id.clone()),
+            Err(DeviceError::EndOfStream) => {
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+            Err(e) => {
+                eprintln!("[keyrx] Stopped monitoring {}: {}", device_id, e);
+                return;
+            }
+        };
+
+        let (lookup, state) = device.lookup_and_state_mut();
+        if !state.time_windows().is_empty() {
+            state.set_minute_of_day(crate::daemon::remapping_state::local_minute_of_day());
+        }
+        let mut outputs = process_event(event.clone(), lookup, state);
+        // Show a macro's delayed steps with the press that started it
+        outputs.extend(state.take_scheduled_outputs());
+        println!("{}", format_monitor_line(&device_id, &event, &outputs));
+    }
+}
+
+/// Formats one input event and the output it would produce.
+///
+/// An input with no output is shown as suppressed, e.g. a modifier key
+/// that only changes state.
+fn format_monitor_line(device_id: &str, input: &KeyEvent, outputs: &[KeyEvent]) -> String {
+    let outputs = if outputs.is_empty() {
+        "(suppressed)".to_string()
+    } else {
+        outputs
+            .iter()
+            .map(format_event)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("[{}] {} -> {}", device_id, format_event(input), outputs)
+}
+
+fn format_event(event: &KeyEvent) -> String {
+    if event.is_press() {
+        format!("Press({:?})", event.keycode())
+    } else if event.is_repeat() {
+        format!("Repeat({:?})", event.keycode())
+    } else {
+        format!("Release({:?})", event.keycode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyrx_core::config::KeyCode;
+
+    #[test]
+    fn test_format_monitor_line_lists_outputs() {
+        let line = format_monitor_line(
+            "serial-ABC",
+            &KeyEvent::press(KeyCode::CapsLock),
+            &[KeyEvent::press(KeyCode::Escape)],
+        );
+        assert_eq!(line, "[serial-ABC] Press(CapsLock) -> Press(Escape)");
+
+        let line = format_monitor_line(
+            "serial-ABC",
+            &KeyEvent::release(KeyCode::A),
+            &[
+                KeyEvent::release(KeyCode::LShift),
+                KeyEvent::release(KeyCode::Num1),
+            ],
+        );
+        assert_eq!(
+            line,
+            "[serial-ABC] Release(A) -> Release(LShift), Release(Num1)"
+        );
+    }
+
+    #[test]
+    fn test_format_monitor_line_marks_suppressed_input() {
+        let line = format_monitor_line("path-/dev/input/event3", &KeyEvent::press(KeyCode::A), &[]);
+        assert_eq!(line, "[path-/dev/input/event3] Press(A) -> (suppressed)");
+    }
+}
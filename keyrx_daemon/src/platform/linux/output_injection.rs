@@ -3,16 +3,33 @@
 //! This module provides keyboard event injection via virtual uinput devices.
 
 use std::collections::HashSet;
+use std::sync::Mutex;
 
+use evdev::{BusType, InputId};
 use uinput::Device as UInputDevice;
 
 use keyrx_core::config::KeyCode;
 use keyrx_core::runtime::event::KeyEvent;
 
-use crate::platform::{DeviceError, OutputDevice};
+use crate::platform::{DeviceError, OutputDevice, OutputSync, DEFAULT_OUTPUT_NAME};
 
 use super::keycode_map::keycode_to_uinput_key;
 
+/// Names of the virtual devices this process has created.
+///
+/// Device discovery skips them: capturing our own output would feed every
+/// injected event back through the remapper.
+static CREATED_NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Vendor ID reported by every output device ("KR").
+///
+/// Together with [`OUTPUT_PRODUCT_ID`] on the virtual bus, this identifies
+/// output devices created by any keyrx process, whatever their name.
+pub const OUTPUT_VENDOR_ID: u16 = 0x4b52;
+
+/// Product ID reported by every output device ("RX").
+pub const OUTPUT_PRODUCT_ID: u16 = 0x5258;
+
 /// Virtual keyboard device for injecting keyboard events via uinput.
 ///
 /// `UinputOutput` creates a virtual keyboard device using the Linux uinput
@@ -63,6 +80,9 @@ pub struct UinputOutput {
     /// Set of currently held (pressed but not yet released) keys.
     /// Used during cleanup to release any keys still held when the device is destroyed.
     held_keys: HashSet<KeyCode>,
+    /// Requested keys the kernel refused to advertise. Events for them are
+    /// dropped.
+    unsupported_keys: Vec<KeyCode>,
 }
 
 impl UinputOutput {
@@ -104,57 +124,105 @@ impl UinputOutput {
     /// }
     /// ```
     pub fn create(name: &str) -> Result<Self, DeviceError> {
-        // Create uinput device with keyboard capabilities
-        let device = uinput::default()
-            .map_err(|e| {
-                let err_str = e.to_string();
-                if err_str.contains("Permission denied") || err_str.contains("EACCES") {
-                    DeviceError::PermissionDenied(
-                        "cannot access /dev/uinput: permission denied.\n\
-                        To fix this, either:\n\
-                        1. Run as root, OR\n\
-                        2. Create udev rules:\n\
-                           echo 'KERNEL==\"uinput\", MODE=\"0660\", GROUP=\"uinput\"' | \\\n\
-                           sudo tee /etc/udev/rules.d/99-keyrx.rules\n\
-                           sudo groupadd -f uinput\n\
-                           sudo usermod -aG uinput $USER\n\
-                           (log out and back in)"
-                            .to_string(),
-                    )
-                } else {
-                    DeviceError::Io(std::io::Error::other(format!("uinput open failed: {}", e)))
-                }
-            })?
-            .name(name)
-            .map_err(|e| {
-                DeviceError::Io(std::io::Error::other(format!(
-                    "failed to set device name: {}",
-                    e
-                )))
-            })?
+        Self::build(name, None)
+    }
+
+    /// Creates a virtual keyboard device that advertises only `keys`.
+    ///
+    /// Applications and the kernel drop events for keys a device does not
+    /// advertise, so `keys` must cover every key that will be injected:
+    /// the outputs of the active configuration plus any keys passed through
+    /// unmapped. Advertising a small set keeps the device from claiming
+    /// hundreds of keys it will never send.
+    ///
+    /// Keys the kernel refuses to advertise (e.g. on minimal kernels built
+    /// without them) are skipped instead of failing creation; they are
+    /// reported by [`unsupported_keys`](Self::unsupported_keys) and events
+    /// for them are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`create`](Self::create). Also fails if the kernel rejects
+    /// every key in `keys`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use keyrx_daemon::platform::linux::UinputOutput;
+    /// use keyrx_core::config::KeyCode;
+    ///
+    /// let output = UinputOutput::create_with_keys(
+    ///     "keyrx-media",
+    ///     &[KeyCode::VolumeUp, KeyCode::VolumeDown, KeyCode::MediaPlayPause],
+    /// )?;
+    /// # Ok::<(), keyrx_daemon::platform::DeviceError>(())
+    /// ```
+    pub fn create_with_keys(name: &str, keys: &[KeyCode]) -> Result<Self, DeviceError> {
+        Self::build(name, Some(keys))
+    }
+
+    /// Opens /dev/uinput and creates the device, advertising `keys` or every
+    /// keyboard event when `keys` is `None`.
+    fn build(name: &str, keys: Option<&[KeyCode]>) -> Result<Self, DeviceError> {
+        let (builder, unsupported_keys) = match keys {
             // Enable all keyboard events
-            .event(uinput::event::Keyboard::All)
-            .map_err(|e| {
-                DeviceError::Io(std::io::Error::other(format!(
-                    "failed to configure keyboard events: {}",
-                    e
-                )))
-            })?
-            .create()
-            .map_err(|e| {
-                DeviceError::Io(std::io::Error::other(format!(
-                    "failed to create uinput device: {}",
-                    e
-                )))
-            })?;
+            None => (
+                open_builder(name)?
+                    .event(uinput::event::Keyboard::All)
+                    .map_err(configure_error)?,
+                Vec::new(),
+            ),
+            Some(keys) => advertise_supported(
+                keys,
+                || open_builder(name),
+                |builder, key| {
+                    builder
+                        .event(keycode_to_uinput_key(key))
+                        .map_err(configure_error)
+                },
+            )?,
+        };
+
+        let device = builder.create().map_err(|e| {
+            DeviceError::Io(std::io::Error::other(format!(
+                "failed to create uinput device: {}",
+                e
+            )))
+        })?;
+
+        let mut created = CREATED_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+        if !created.iter().any(|created| created == name) {
+            created.push(name.to_string());
+        }
 
         Ok(Self {
             device: Some(device),
             name: name.to_string(),
             held_keys: HashSet::new(),
+            unsupported_keys,
         })
     }
 
+    /// Returns whether the input device named `name` with IDs `id` is a
+    /// keyrx output device.
+    ///
+    /// Matches devices carrying the keyrx vendor and product IDs (created by
+    /// any keyrx process), devices named [`DEFAULT_OUTPUT_NAME`], and devices
+    /// this process created under another name. Such devices must never be
+    /// captured: injected output is not fed back into remapping.
+    #[must_use]
+    pub fn is_own_device(name: &str, id: InputId) -> bool {
+        (id.bus_type() == BusType::BUS_VIRTUAL
+            && id.vendor() == OUTPUT_VENDOR_ID
+            && id.product() == OUTPUT_PRODUCT_ID)
+            || name == DEFAULT_OUTPUT_NAME
+            || CREATED_NAMES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .any(|created| created == name)
+    }
+
     /// Returns the name of the virtual device.
     ///
     /// # Example
@@ -189,6 +257,15 @@ impl UinputOutput {
         &self.held_keys
     }
 
+    /// Returns the requested keys the kernel refused to advertise.
+    ///
+    /// Always empty for devices made with [`create`](Self::create). Events for
+    /// these keys are dropped by `inject_event()`.
+    #[must_use]
+    pub fn unsupported_keys(&self) -> &[KeyCode] {
+        &self.unsupported_keys
+    }
+
     /// Destroys the virtual device, releasing any held keys first.
     ///
     /// This method performs cleanup in the following order:
@@ -288,7 +365,8 @@ impl UinputOutput {
 /// - `Release`: Sends a key up event
 ///
 /// After each event, `synchronize()` is called to ensure the event is delivered
-/// immediately to applications.
+/// immediately to applications. [`inject_batch`](OutputDevice::inject_batch)
+/// instead ends the whole batch with one `SYN_REPORT`.
 ///
 /// # Example
 ///
@@ -331,6 +409,39 @@ impl OutputDevice for UinputOutput {
     /// delivery. This matches the behavior expected by applications which
     /// typically receive events with EV_SYN/SYN_REPORT markers.
     fn inject_event(&mut self, event: KeyEvent) -> Result<(), DeviceError> {
+        self.write_event(&event)?;
+        self.synchronize()
+    }
+
+    /// Injects the events of one input as few input frames as possible.
+    ///
+    /// Events are written back to back and followed by a single
+    /// `SYN_REPORT`, so a `with_shift()` output arrives as one frame holding
+    /// both Shift and the key. A frame never contains the same key twice;
+    /// a press and release of one key are split across frames so readers do
+    /// not collapse them.
+    fn inject_batch(&mut self, events: &[KeyEvent]) -> Result<(), DeviceError> {
+        self.inject_frames(&split_frames(events))
+    }
+}
+
+impl UinputOutput {
+    /// Writes each of `frames` followed by one `SYN_REPORT`.
+    ///
+    /// Build the frames with [`split_frames`] or [`frame_actions`] so none of
+    /// them holds the same key twice.
+    pub(crate) fn inject_frames(&mut self, frames: &[&[KeyEvent]]) -> Result<(), DeviceError> {
+        for frame in frames {
+            for event in *frame {
+                self.write_event(event)?;
+            }
+            self.synchronize()?;
+        }
+        Ok(())
+    }
+
+    /// Writes one key event without ending the frame.
+    fn write_event(&mut self, event: &KeyEvent) -> Result<(), DeviceError> {
         // Get a mutable reference to the device, failing if destroyed
         let device = self
             .device
@@ -338,6 +449,10 @@ impl OutputDevice for UinputOutput {
             .ok_or_else(|| DeviceError::InjectionFailed("device has been destroyed".to_string()))?;
 
         let keycode = event.keycode();
+        if self.unsupported_keys.contains(&keycode) {
+            // Not advertised, so the kernel would drop it anyway
+            return Ok(());
+        }
         let key = keycode_to_uinput_key(keycode);
 
         if event.is_press() {
@@ -346,6 +461,11 @@ impl OutputDevice for UinputOutput {
                 .map_err(|e| DeviceError::InjectionFailed(format!("failed to press key: {}", e)))?;
             // Track this key as held
             self.held_keys.insert(keycode);
+        } else if event.is_repeat() {
+            // EV_KEY value 2 is an auto-repeat; the key stays held
+            device.send(key, 2).map_err(|e| {
+                DeviceError::InjectionFailed(format!("failed to repeat key: {}", e))
+            })?;
         } else {
             device.release(&key).map_err(|e| {
                 DeviceError::InjectionFailed(format!("failed to release key: {}", e))
@@ -353,13 +473,155 @@ impl OutputDevice for UinputOutput {
             // Remove from held keys
             self.held_keys.remove(&keycode);
         }
+        Ok(())
+    }
 
-        // Synchronize to ensure event is delivered immediately
+    /// Ends the current frame with `SYN_REPORT` so applications see it.
+    fn synchronize(&mut self) -> Result<(), DeviceError> {
+        let device = self
+            .device
+            .as_mut()
+            .ok_or_else(|| DeviceError::InjectionFailed("device has been destroyed".to_string()))?;
         device.synchronize().map_err(|e| {
             DeviceError::InjectionFailed(format!("failed to synchronize events: {}", e))
-        })?;
+        })
+    }
+}
 
-        Ok(())
+/// Splits `events` into input frames, each ended by one `SYN_REPORT`.
+///
+/// A new frame starts whenever a key already appears in the current one, so
+/// modifier and key presses share a frame while a tap (press then release of
+/// the same key) spans two.
+fn split_frames(events: &[KeyEvent]) -> Vec<&[KeyEvent]> {
+    let mut frames = Vec::new();
+    let mut start = 0;
+    for (index, event) in events.iter().enumerate() {
+        let keycode = event.keycode();
+        if events[start..index].iter().any(|e| e.keycode() == keycode) {
+            frames.push(&events[start..index]);
+            start = index;
+        }
+    }
+    if start < events.len() {
+        frames.push(&events[start..]);
+    }
+    frames
+}
+
+/// Splits a buffer of injected actions into input frames.
+///
+/// `action_ends` holds the end of each action (the output of one input
+/// event) in `events`. Each action is split with [`split_frames`]. With
+/// [`OutputSync::Loop`] the first frame of an action also joins the frame
+/// before it when they share no key, so consecutive actions cost fewer
+/// `SYN_REPORT`s; an action's own frames are never split further, keeping
+/// modifier and key presses together.
+pub(crate) fn frame_actions<'a>(
+    events: &'a [KeyEvent],
+    action_ends: &[usize],
+    sync: OutputSync,
+) -> Vec<&'a [KeyEvent]> {
+    let mut frames: Vec<std::ops::Range<usize>> = Vec::new();
+    let mut start = 0;
+    for &end in action_ends {
+        for (index, frame) in split_frames(&events[start..end]).into_iter().enumerate() {
+            let range = start..start + frame.len();
+            start = range.end;
+            match frames.last_mut() {
+                Some(last)
+                    if index == 0
+                        && sync == OutputSync::Loop
+                        && !events[last.clone()]
+                            .iter()
+                            .any(|e| frame.iter().any(|f| f.keycode() == e.keycode())) =>
+                {
+                    last.end = range.end;
+                }
+                _ => frames.push(range),
+            }
+        }
+    }
+    frames.into_iter().map(|range| &events[range]).collect()
+}
+
+/// Opens /dev/uinput and starts a device builder named `name`.
+fn open_builder(name: &str) -> Result<uinput::device::Builder, DeviceError> {
+    uinput::default()
+        .map_err(|e| {
+            let err_str = e.to_string();
+            if err_str.contains("Permission denied") || err_str.contains("EACCES") {
+                DeviceError::PermissionDenied(
+                    "cannot access /dev/uinput: permission denied.\n\
+                    To fix this, either:\n\
+                    1. Run as root, OR\n\
+                    2. Create udev rules:\n\
+                       echo 'KERNEL==\"uinput\", MODE=\"0660\", GROUP=\"uinput\"' | \\\n\
+                       sudo tee /etc/udev/rules.d/99-keyrx.rules\n\
+                       sudo groupadd -f uinput\n\
+                       sudo usermod -aG uinput $USER\n\
+                       (log out and back in)"
+                        .to_string(),
+                )
+            } else {
+                DeviceError::Io(std::io::Error::other(format!("uinput open failed: {}", e)))
+            }
+        })?
+        .name(name)
+        .map(|builder| {
+            builder
+                .bus(BusType::BUS_VIRTUAL.0)
+                .vendor(OUTPUT_VENDOR_ID)
+                .product(OUTPUT_PRODUCT_ID)
+        })
+        .map_err(|e| {
+            DeviceError::Io(std::io::Error::other(format!(
+                "failed to set device name: {}",
+                e
+            )))
+        })
+}
+
+/// Converts a failure to advertise an event into a [`DeviceError`].
+fn configure_error(e: uinput::Error) -> DeviceError {
+    DeviceError::Io(std::io::Error::other(format!(
+        "failed to configure keyboard events: {}",
+        e
+    )))
+}
+
+/// Advertises every key in `keys` that the kernel accepts.
+///
+/// `open` starts a fresh device builder and `enable` advertises one key on
+/// it. A rejected key consumes the builder, so a new one is opened and the
+/// keys advertised again without it. Returns the builder together with the
+/// rejected keys in order. If every key is rejected the last error is
+/// returned, as the failure is then unlikely to be specific to one key.
+fn advertise_supported<B>(
+    keys: &[KeyCode],
+    mut open: impl FnMut() -> Result<B, DeviceError>,
+    mut enable: impl FnMut(B, KeyCode) -> Result<B, DeviceError>,
+) -> Result<(B, Vec<KeyCode>), DeviceError> {
+    let mut unsupported = Vec::new();
+
+    'retry: loop {
+        let mut builder = open()?;
+        for &key in keys {
+            if unsupported.contains(&key) {
+                continue;
+            }
+            match enable(builder, key) {
+                Ok(next) => builder = next,
+                Err(e) => {
+                    unsupported.push(key);
+                    if unsupported.len() == keys.len() {
+                        return Err(e);
+                    }
+                    continue 'retry;
+                }
+            }
+        }
+        return Ok((builder, unsupported));
     }
 }
 
@@ -408,6 +670,22 @@ mod tests {
         uinput_ok && can_access_input_devices()
     }
 
+    #[test]
+    fn test_is_own_device() {
+        let usb = InputId::new(BusType::BUS_USB, 0x046d, 0xc31c, 0x0110);
+        let output = InputId::new(BusType::BUS_VIRTUAL, OUTPUT_VENDOR_ID, OUTPUT_PRODUCT_ID, 0);
+
+        assert!(UinputOutput::is_own_device(
+            DEFAULT_OUTPUT_NAME,
+            usb.clone()
+        ));
+        assert!(UinputOutput::is_own_device("keyrx-e2e-other", output));
+        assert!(!UinputOutput::is_own_device(
+            "AT Translated Set 2 keyboard",
+            usb
+        ));
+    }
+
     /// Test that UinputOutput::create returns PermissionDenied when not running as root
     /// Note: This test only works when NOT running as root without udev rules
     #[test]
@@ -444,6 +722,29 @@ mod tests {
         assert!(output.held_keys().is_empty());
     }
 
+    /// Test that a device advertising only selected keys can inject them
+    /// Note: Requires uinput access
+    #[test]
+    fn test_uinputoutput_create_with_keys() {
+        if !can_access_uinput() {
+            eprintln!("SKIPPED: uinput/input not accessible");
+            return;
+        }
+        let mut output = UinputOutput::create_with_keys(
+            "keyrx-test-media",
+            &[KeyCode::VolumeUp, KeyCode::BrightnessDown],
+        )
+        .expect("Failed to create uinput device");
+
+        output
+            .inject_event(KeyEvent::Press(KeyCode::VolumeUp))
+            .expect("Failed to inject VolumeUp press");
+        output
+            .inject_event(KeyEvent::Release(KeyCode::VolumeUp))
+            .expect("Failed to inject VolumeUp release");
+        assert!(output.held_keys().is_empty());
+    }
+
     /// Test that inject_event fails after destroy
     /// Note: Requires uinput access
     #[test]
@@ -658,4 +959,133 @@ mod tests {
         drop(output1);
         drop(output2);
     }
+
+    /// Fake `enable` that rejects `rejected` like a kernel without those keys
+    fn enable_except(
+        rejected: &'static [KeyCode],
+    ) -> impl FnMut(Vec<KeyCode>, KeyCode) -> Result<Vec<KeyCode>, DeviceError> {
+        move |mut advertised, key| {
+            if rejected.contains(&key) {
+                Err(DeviceError::Io(std::io::Error::other("invalid argument")))
+            } else {
+                advertised.push(key);
+                Ok(advertised)
+            }
+        }
+    }
+
+    #[test]
+    fn test_advertise_supported_skips_rejected_keys() {
+        let mut opens = 0;
+        let (advertised, unsupported) = advertise_supported(
+            &[KeyCode::A, KeyCode::F24, KeyCode::B, KeyCode::Hanja],
+            || {
+                opens += 1;
+                Ok(Vec::new())
+            },
+            enable_except(&[KeyCode::F24, KeyCode::Hanja]),
+        )
+        .expect("supported keys should be advertised");
+
+        assert_eq!(advertised, vec![KeyCode::A, KeyCode::B]);
+        assert_eq!(unsupported, vec![KeyCode::F24, KeyCode::Hanja]);
+        // One builder per rejected key, plus the one that succeeds
+        assert_eq!(opens, 3);
+    }
+
+    #[test]
+    fn test_advertise_supported_all_keys_supported() {
+        let (advertised, unsupported) = advertise_supported(
+            &[KeyCode::A, KeyCode::B],
+            || Ok(Vec::new()),
+            enable_except(&[]),
+        )
+        .expect("supported keys should be advertised");
+
+        assert_eq!(advertised, vec![KeyCode::A, KeyCode::B]);
+        assert!(unsupported.is_empty());
+    }
+
+    #[test]
+    fn test_advertise_supported_fails_when_every_key_rejected() {
+        let result = advertise_supported(
+            &[KeyCode::F24, KeyCode::Hanja],
+            || Ok(Vec::new()),
+            enable_except(&[KeyCode::F24, KeyCode::Hanja]),
+        );
+
+        assert!(matches!(result, Err(DeviceError::Io(_))));
+    }
+
+    #[test]
+    fn test_split_frames_keeps_modifier_and_key_together() {
+        let press = [
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::Num1),
+        ];
+        assert_eq!(split_frames(&press), vec![&press[..]]);
+
+        let release = [
+            KeyEvent::Release(KeyCode::Num1),
+            KeyEvent::Release(KeyCode::LShift),
+        ];
+        assert_eq!(split_frames(&release), vec![&release[..]]);
+    }
+
+    #[test]
+    fn test_split_frames_separates_repeated_keys() {
+        // A cycle or macro tap presses and releases the same key
+        let tap = [
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::A),
+            KeyEvent::Release(KeyCode::A),
+            KeyEvent::Release(KeyCode::LShift),
+        ];
+        assert_eq!(split_frames(&tap), vec![&tap[..2], &tap[2..]]);
+
+        assert!(split_frames(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_frame_actions_action_sync_keeps_actions_apart() {
+        let events = [
+            KeyEvent::Press(KeyCode::A),
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::Num1),
+        ];
+        assert_eq!(
+            frame_actions(&events, &[1, 3], OutputSync::Action),
+            vec![&events[..1], &events[1..]]
+        );
+        assert!(frame_actions(&[], &[], OutputSync::Action).is_empty());
+    }
+
+    #[test]
+    fn test_frame_actions_loop_sync_merges_disjoint_actions() {
+        let events = [
+            KeyEvent::Press(KeyCode::A),
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::Num1),
+            KeyEvent::Release(KeyCode::A),
+        ];
+        assert_eq!(
+            frame_actions(&events, &[1, 3, 4], OutputSync::Loop),
+            vec![&events[..3], &events[3..]]
+        );
+    }
+
+    #[test]
+    fn test_frame_actions_loop_sync_never_splits_modifier_from_key() {
+        // The second action's Num1 clashes with the pending frame, so the
+        // whole Shift+1 frame starts a new one instead of leaving Shift behind
+        let events = [
+            KeyEvent::Release(KeyCode::Num1),
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::Num1),
+        ];
+        assert_eq!(
+            frame_actions(&events, &[1, 3], OutputSync::Loop),
+            vec![&events[..1], &events[1..]]
+        );
+    }
 }
@@ -38,13 +38,19 @@
 //!
 //! The tray provides "Reload Config" and "Exit" menu items via [`TrayControlEvent`].
 
-use keyrx_core::config::DeviceConfig;
+use std::sync::Arc;
+
+use keyrx_core::config::{DeviceConfig, KeyCode};
 use keyrx_core::runtime::event::KeyEvent;
 use thiserror::Error;
 
 pub mod common;
 pub mod recovery;
-pub use common::{DeviceInfo, PlatformError, Result as PlatformResult};
+pub use common::{
+    validate_output_name, DeviceCapabilities, DeviceInfo, EventCounters, EventErrorCounts,
+    EventLog, EventPage, OutputCounts, OutputSync, PlatformError, Result as PlatformResult,
+    DEFAULT_EVENT_LOG_CAPACITY, DEFAULT_OUTPUT_NAME,
+};
 
 #[cfg(target_os = "linux")]
 pub mod linux;
@@ -137,6 +143,77 @@ pub trait Platform: Send + Sync {
     /// ```
     fn initialize(&mut self) -> PlatformResult<()>;
 
+    /// Declares the keys the active configuration can emit.
+    ///
+    /// Must be called before [`initialize()`](Platform::initialize) to take
+    /// effect. Platforms whose virtual output device advertises key
+    /// capabilities (Linux uinput) use it to advertise only the keys that
+    /// can actually be injected. The default implementation ignores it.
+    fn set_output_keys(&mut self, _keys: &[KeyCode]) {}
+
+    /// Sets the name of the virtual output keyboard.
+    ///
+    /// Must be called before [`initialize()`](Platform::initialize) to take
+    /// effect. Platforms that create a named output device (Linux uinput) use
+    /// it instead of [`DEFAULT_OUTPUT_NAME`]; callers should check it with
+    /// [`validate_output_name`] first. The default implementation ignores it.
+    fn set_output_name(&mut self, _name: &str) {}
+
+    /// Chooses how to handle output keys the system cannot emit.
+    ///
+    /// Must be called before [`initialize()`](Platform::initialize) to take
+    /// effect. By default such keys are reported once and their events
+    /// dropped; with `strict` set, initialization fails instead. Platforms
+    /// that can emit every key ignore it.
+    fn set_strict_keys(&mut self, _strict: bool) {}
+
+    /// Sets how long to hold captured events so events from different
+    /// devices are processed in timestamp order.
+    ///
+    /// Adds up to `window` of latency to every event; `None` processes
+    /// events as they are read. Platforms that receive input as a single
+    /// ordered stream ignore it.
+    fn set_merge_window(&mut self, _window: Option<std::time::Duration>) {}
+
+    /// Sets the most matched devices the platform may grab.
+    ///
+    /// Must be called before [`initialize()`](Platform::initialize) to take
+    /// effect. When more devices match, initialization fails and lists them
+    /// instead of grabbing them all. Platforms that don't grab individual
+    /// devices ignore it.
+    fn set_max_devices(&mut self, _max: Option<usize>) {}
+
+    /// Chooses when buffered output is ended with a frame boundary.
+    ///
+    /// Must be called before the event loop starts. Platforms that deliver
+    /// each event on its own ignore it.
+    fn set_output_sync(&mut self, _sync: OutputSync) {}
+
+    /// Shares the daemon's failed/dropped event counters with the platform.
+    ///
+    /// Platforms that poll devices themselves (Linux evdev) record capture
+    /// errors and reads lost to disconnected devices here. The default
+    /// implementation ignores the counters.
+    fn set_event_counters(&mut self, _counters: Arc<EventCounters>) {}
+
+    /// Restricts grabbing to the devices with the given IDs.
+    ///
+    /// Must be called before [`initialize()`](Platform::initialize) to take
+    /// effect. Matched devices outside the selection are left ungrabbed and
+    /// their input is ignored, so they keep working normally. Unknown IDs are
+    /// reported by [`initialize()`](Platform::initialize) once devices have
+    /// been enumerated.
+    ///
+    /// # Errors
+    ///
+    /// - [`PlatformError::Unsupported`]: The platform cannot grab individual
+    ///   devices (the default implementation)
+    fn grab_specific(&mut self, _device_ids: &[String]) -> PlatformResult<()> {
+        Err(PlatformError::Unsupported {
+            operation: "grabbing specific devices".to_string(),
+        })
+    }
+
     /// Captures the next keyboard input event (blocking).
     ///
     /// This method blocks until an input event is available from any monitored
@@ -179,6 +256,9 @@ pub trait Platform: Send + Sync {
     /// as if it came from a real keyboard. The event will be delivered to the
     /// currently focused application.
     ///
+    /// Platforms may buffer the event until [`flush()`](Platform::flush) so
+    /// that events injected back to back are written together.
+    ///
     /// # Arguments
     ///
     /// * `event` - The keyboard event to inject (press or release)
@@ -201,10 +281,42 @@ pub trait Platform: Send + Sync {
     /// // Inject 'A' key press and release
     /// platform.inject_output(KeyEvent::Press(KeyCode::A))?;
     /// platform.inject_output(KeyEvent::Release(KeyCode::A))?;
+    /// platform.flush()?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     fn inject_output(&mut self, event: KeyEvent) -> PlatformResult<()>;
 
+    /// Injects the output events produced by one input event.
+    ///
+    /// Platforms whose output device supports
+    /// [`OutputDevice::inject_batch`] deliver the events together. The default
+    /// injects them one at a time with [`inject_output()`](Platform::inject_output).
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first event that fails, returning its error.
+    fn inject_outputs(&mut self, events: &[KeyEvent]) -> PlatformResult<()> {
+        for event in events {
+            self.inject_output(event.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Delivers any output events the platform is still buffering.
+    ///
+    /// The event loop calls this once per iteration, after injecting the
+    /// output of an input event and any tap-hold timeouts that came due,
+    /// and before it waits for more input. Platforms that deliver each event
+    /// immediately keep the default, which does nothing.
+    ///
+    /// # Errors
+    ///
+    /// - [`PlatformError::InjectionFailed`]: Writing the buffered events failed;
+    ///   they are dropped rather than retried
+    fn flush(&mut self) -> PlatformResult<()> {
+        Ok(())
+    }
+
     /// Lists all available input devices.
     ///
     /// Returns information about all keyboard input devices that can be used
@@ -235,6 +347,18 @@ pub trait Platform: Send + Sync {
     /// ```
     fn list_devices(&self) -> PlatformResult<Vec<DeviceInfo>>;
 
+    /// Returns how many devices are actually being captured.
+    ///
+    /// [`list_devices()`](Platform::list_devices) reports every matched
+    /// device; this counts only those whose input the platform currently
+    /// receives (on Linux, the devices it holds an exclusive grab on). The
+    /// default implementation assumes every listed device is captured.
+    fn active_device_count(&self) -> usize {
+        self.list_devices()
+            .map(|devices| devices.len())
+            .unwrap_or(0)
+    }
+
     /// Cleans up platform resources and shuts down.
     ///
     /// This method should be called when the daemon is exiting to ensure proper
@@ -607,6 +731,23 @@ pub trait OutputDevice {
     /// - `DeviceError::InjectionFailed`: Failed to inject event
     /// - `DeviceError::Io`: Underlying system call failed
     fn inject_event(&mut self, event: KeyEvent) -> Result<(), DeviceError>;
+
+    /// Injects the output events produced by one input event.
+    ///
+    /// A single input can expand to several events, such as the modifier and
+    /// key presses of a `with_shift()` mapping. Implementations that batch
+    /// input deliver them together so applications never see the key without
+    /// its modifier. The default injects them one at a time.
+    ///
+    /// # Errors
+    ///
+    /// Stops at the first event that fails, returning its error.
+    fn inject_batch(&mut self, events: &[KeyEvent]) -> Result<(), DeviceError> {
+        for event in events {
+            self.inject_event(event.clone())?;
+        }
+        Ok(())
+    }
 }
 
 // TODO: This legacy Platform enum will be removed in future tasks
@@ -784,6 +925,7 @@ mod tests {
                 path: "/dev/mock/kbd0".to_string(),
                 vendor_id: 0x1234,
                 product_id: 0x5678,
+                capabilities: None,
             }])
         }
 
@@ -898,6 +1040,7 @@ mod tests {
             path: "/dev/input/event0".to_string(),
             vendor_id: 0x1234,
             product_id: 0x5678,
+            capabilities: None,
         };
 
         let device2 = DeviceInfo {
@@ -906,6 +1049,7 @@ mod tests {
             path: "/dev/input/event0".to_string(),
             vendor_id: 0x1234,
             product_id: 0x5678,
+            capabilities: None,
         };
 
         assert_eq!(device1, device2);
@@ -5,16 +5,20 @@ use std::ptr;
 use std::sync::{Arc, RwLock};
 use windows_sys::Win32::Foundation::HANDLE;
 use windows_sys::Win32::UI::Input::{
-    GetRawInputDeviceInfoW, GetRawInputDeviceList, RAWINPUTDEVICELIST, RIDI_DEVICENAME,
-    RIM_TYPEKEYBOARD,
+    GetRawInputDeviceInfoW, GetRawInputDeviceList, RAWINPUTDEVICELIST, RIDI_DEVICEINFO,
+    RIDI_DEVICENAME, RID_DEVICE_INFO, RIM_TYPEKEYBOARD,
 };
 
+use crate::platform::DeviceCapabilities;
+
 /// Information about a raw input device.
 #[derive(Debug, Clone)]
 pub struct DeviceInfo {
     pub handle: usize,
     pub path: String,
     pub serial: Option<String>,
+    /// Total number of keys reported by RawInput, if available.
+    pub key_count: Option<usize>,
 }
 
 impl DeviceInfo {
@@ -29,6 +33,19 @@ impl DeviceInfo {
         // Fallback to path-based ID
         format!("path-{}", self.path)
     }
+
+    /// Returns the device's input capabilities.
+    ///
+    /// Only RawInput keyboards are tracked, so keys are always supported and
+    /// relative axes never are.
+    #[must_use]
+    pub fn capabilities(&self) -> DeviceCapabilities {
+        DeviceCapabilities {
+            supports_keys: true,
+            key_count: self.key_count.unwrap_or(0),
+            supports_rel: false,
+        }
+    }
 }
 
 /// Manages mapping between Raw Input handles and device information.
@@ -100,6 +117,7 @@ impl DeviceMap {
             handle: handle as usize,
             path: path.clone(),
             serial,
+            key_count: self.get_key_count(handle),
         };
 
         // Store using handle as key (cast to usize for hashing)
@@ -132,6 +150,7 @@ impl DeviceMap {
             handle,
             path,
             serial,
+            key_count: None,
         };
         match self.devices.write() {
             Ok(mut devices) => {
@@ -228,6 +247,25 @@ impl DeviceMap {
         }
     }
 
+    /// Queries the total key count of a keyboard via `RIDI_DEVICEINFO`.
+    fn get_key_count(&self, handle: HANDLE) -> Option<usize> {
+        unsafe {
+            let mut info: RID_DEVICE_INFO = std::mem::zeroed();
+            info.cbSize = size_of::<RID_DEVICE_INFO>() as u32;
+            let mut size = info.cbSize;
+            let result = GetRawInputDeviceInfoW(
+                handle as *mut c_void,
+                RIDI_DEVICEINFO,
+                &mut info as *mut RID_DEVICE_INFO as *mut c_void,
+                &mut size,
+            );
+            if result == u32::MAX || info.dwType != RIM_TYPEKEYBOARD {
+                return None;
+            }
+            Some(info.Anonymous.keyboard.dwNumberOfKeysTotal as usize)
+        }
+    }
+
     /// Extracts serial number or instance ID from device path.
     fn extract_serial(&self, path: &str) -> Option<String> {
         let parts: Vec<&str> = path.split('#').collect();
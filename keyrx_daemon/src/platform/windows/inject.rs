@@ -1,4 +1,5 @@
 use crate::platform::windows::keycode::keycode_to_vk;
+use keyrx_core::config::KeyCode;
 use std::mem::size_of;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 
@@ -31,7 +32,7 @@ impl EventInjector {
             };
 
             // Set extended key flag for certain keys
-            if is_extended_key(vk) {
+            if is_extended_key(vk) || keycode == KeyCode::NumpadEnter {
                 input.Anonymous.ki.dwFlags |= KEYEVENTF_EXTENDEDKEY;
             }
 
@@ -61,5 +62,6 @@ pub fn is_extended_key(vk: u16) -> bool {
             | VK_DOWN
             | VK_LEFT
             | VK_RIGHT
+            | VK_DIVIDE
     )
 }
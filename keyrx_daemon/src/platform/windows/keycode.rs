@@ -5,7 +5,7 @@ use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 /// Uses const arrays for O(1) lookup performance.
 
 // Mapping from VK to KeyCode
-const VK_TO_KEYCODE: [(u16, KeyCode); 141] = [
+const VK_TO_KEYCODE: [(u16, KeyCode); 146] = [
     (VK_A as u16, KeyCode::A),
     (VK_B as u16, KeyCode::B),
     (VK_C as u16, KeyCode::C),
@@ -147,6 +147,11 @@ const VK_TO_KEYCODE: [(u16, KeyCode); 141] = [
     (VK_CONVERT as u16, KeyCode::Henkan),
     (VK_NONCONVERT as u16, KeyCode::Muhenkan),
     (VK_OEM_102 as u16, KeyCode::Iso102nd),
+    (VK_DBE_KATAKANA as u16, KeyCode::Katakana),
+    (VK_DBE_HIRAGANA as u16, KeyCode::Hiragana),
+    (VK_ABNT_C1 as u16, KeyCode::Ro),
+    (VK_ABNT_C2 as u16, KeyCode::NumpadJpComma),
+    (VK_SEPARATOR as u16, KeyCode::NumpadComma),
 ];
 
 pub fn vk_to_keycode(vk: u16) -> Option<KeyCode> {
@@ -223,7 +228,7 @@ pub fn scancode_to_keycode(scancode: u32) -> Option<KeyCode> {
         0xE051 => Some(KeyCode::PageDown),
         0xE052 => Some(KeyCode::Insert),
         0xE053 => Some(KeyCode::Delete),
-        0xE01C => Some(KeyCode::Enter),
+        0xE01C => Some(KeyCode::NumpadEnter),
         0xE035 => Some(KeyCode::NumpadDivide),
         // Function keys
         0x3B => Some(KeyCode::F1),
@@ -238,6 +243,14 @@ pub fn scancode_to_keycode(scancode: u32) -> Option<KeyCode> {
         0x44 => Some(KeyCode::F10),
         0x57 => Some(KeyCode::F11),
         0x58 => Some(KeyCode::F12),
+        // International keys: VKs for these depend on the layout, so the
+        // fallback below would misreport them
+        0x56 => Some(KeyCode::Iso102nd),
+        0x70 => Some(KeyCode::KatakanaHiragana),
+        0x73 => Some(KeyCode::Ro),
+        0x79 => Some(KeyCode::Henkan),
+        0x7B => Some(KeyCode::Muhenkan),
+        0x7D => Some(KeyCode::Yen),
 
         _ => {
             // Fallback to MapVirtualKeyW for other keys
@@ -255,14 +268,198 @@ pub fn scancode_to_keycode(scancode: u32) -> Option<KeyCode> {
     }
 }
 
+/// Keys Windows has no virtual key for. [`keycode_to_vk`] returns `None` for
+/// exactly these, so mapping to one of them is reported as unmapped instead
+/// of being silently dropped.
+#[allow(dead_code)]
+pub const UNSUPPORTED_KEYCODES: [KeyCode; 11] = [
+    // Handled by ACPI and vendor drivers rather than keyboard input
+    KeyCode::Power,
+    KeyCode::Wake,
+    KeyCode::BrightnessDown,
+    KeyCode::BrightnessUp,
+    // HID editing usages without a virtual key
+    KeyCode::Undo,
+    KeyCode::Redo,
+    KeyCode::Cut,
+    KeyCode::Copy,
+    KeyCode::Paste,
+    KeyCode::Find,
+    // Its virtual key depends on the layout (VK_OEM_5 on Japanese layouts,
+    // which is Backslash elsewhere)
+    KeyCode::Yen,
+];
+
+/// Virtual key used to inject `keycode`, or `None` for
+/// [`UNSUPPORTED_KEYCODES`].
+///
+/// The match is exhaustive on purpose: a new `KeyCode` doesn't build until
+/// it is given a Windows virtual key or listed as unsupported.
 #[allow(dead_code)]
 pub fn keycode_to_vk(keycode: KeyCode) -> Option<u16> {
-    for (v, k) in VK_TO_KEYCODE.iter() {
-        if *k == keycode {
-            return Some(*v);
-        }
-    }
-    None
+    let vk = match keycode {
+        KeyCode::A => VK_A,
+        KeyCode::B => VK_B,
+        KeyCode::C => VK_C,
+        KeyCode::D => VK_D,
+        KeyCode::E => VK_E,
+        KeyCode::F => VK_F,
+        KeyCode::G => VK_G,
+        KeyCode::H => VK_H,
+        KeyCode::I => VK_I,
+        KeyCode::J => VK_J,
+        KeyCode::K => VK_K,
+        KeyCode::L => VK_L,
+        KeyCode::M => VK_M,
+        KeyCode::N => VK_N,
+        KeyCode::O => VK_O,
+        KeyCode::P => VK_P,
+        KeyCode::Q => VK_Q,
+        KeyCode::R => VK_R,
+        KeyCode::S => VK_S,
+        KeyCode::T => VK_T,
+        KeyCode::U => VK_U,
+        KeyCode::V => VK_V,
+        KeyCode::W => VK_W,
+        KeyCode::X => VK_X,
+        KeyCode::Y => VK_Y,
+        KeyCode::Z => VK_Z,
+        KeyCode::Num0 => VK_0,
+        KeyCode::Num1 => VK_1,
+        KeyCode::Num2 => VK_2,
+        KeyCode::Num3 => VK_3,
+        KeyCode::Num4 => VK_4,
+        KeyCode::Num5 => VK_5,
+        KeyCode::Num6 => VK_6,
+        KeyCode::Num7 => VK_7,
+        KeyCode::Num8 => VK_8,
+        KeyCode::Num9 => VK_9,
+        KeyCode::F1 => VK_F1,
+        KeyCode::F2 => VK_F2,
+        KeyCode::F3 => VK_F3,
+        KeyCode::F4 => VK_F4,
+        KeyCode::F5 => VK_F5,
+        KeyCode::F6 => VK_F6,
+        KeyCode::F7 => VK_F7,
+        KeyCode::F8 => VK_F8,
+        KeyCode::F9 => VK_F9,
+        KeyCode::F10 => VK_F10,
+        KeyCode::F11 => VK_F11,
+        KeyCode::F12 => VK_F12,
+        KeyCode::LShift => VK_LSHIFT,
+        KeyCode::RShift => VK_RSHIFT,
+        KeyCode::LCtrl => VK_LCONTROL,
+        KeyCode::RCtrl => VK_RCONTROL,
+        KeyCode::LAlt => VK_LMENU,
+        KeyCode::RAlt => VK_RMENU,
+        KeyCode::LMeta => VK_LWIN,
+        KeyCode::RMeta => VK_RWIN,
+        KeyCode::Escape => VK_ESCAPE,
+        KeyCode::Enter => VK_RETURN,
+        KeyCode::Backspace => VK_BACK,
+        KeyCode::Tab => VK_TAB,
+        KeyCode::Space => VK_SPACE,
+        KeyCode::CapsLock => VK_CAPITAL,
+        KeyCode::NumLock => VK_NUMLOCK,
+        KeyCode::ScrollLock => VK_SCROLL,
+        KeyCode::PrintScreen => VK_SNAPSHOT,
+        KeyCode::Pause => VK_PAUSE,
+        KeyCode::Insert => VK_INSERT,
+        KeyCode::Delete => VK_DELETE,
+        KeyCode::Home => VK_HOME,
+        KeyCode::End => VK_END,
+        KeyCode::PageUp => VK_PRIOR,
+        KeyCode::PageDown => VK_NEXT,
+        KeyCode::Left => VK_LEFT,
+        KeyCode::Right => VK_RIGHT,
+        KeyCode::Up => VK_UP,
+        KeyCode::Down => VK_DOWN,
+        KeyCode::LeftBracket => VK_OEM_4,
+        KeyCode::RightBracket => VK_OEM_6,
+        KeyCode::Backslash => VK_OEM_5,
+        KeyCode::Semicolon => VK_OEM_1,
+        KeyCode::Quote => VK_OEM_7,
+        KeyCode::Comma => VK_OEM_COMMA,
+        KeyCode::Period => VK_OEM_PERIOD,
+        KeyCode::Slash => VK_OEM_2,
+        KeyCode::Grave => VK_OEM_3,
+        KeyCode::Minus => VK_OEM_MINUS,
+        KeyCode::Equal => VK_OEM_PLUS,
+        KeyCode::Numpad0 => VK_NUMPAD0,
+        KeyCode::Numpad1 => VK_NUMPAD1,
+        KeyCode::Numpad2 => VK_NUMPAD2,
+        KeyCode::Numpad3 => VK_NUMPAD3,
+        KeyCode::Numpad4 => VK_NUMPAD4,
+        KeyCode::Numpad5 => VK_NUMPAD5,
+        KeyCode::Numpad6 => VK_NUMPAD6,
+        KeyCode::Numpad7 => VK_NUMPAD7,
+        KeyCode::Numpad8 => VK_NUMPAD8,
+        KeyCode::Numpad9 => VK_NUMPAD9,
+        KeyCode::NumpadDivide => VK_DIVIDE,
+        KeyCode::NumpadMultiply => VK_MULTIPLY,
+        KeyCode::NumpadSubtract => VK_SUBTRACT,
+        KeyCode::NumpadAdd => VK_ADD,
+        KeyCode::NumpadEnter => VK_RETURN, // Told apart from Enter by the extended-key flag
+        KeyCode::NumpadDecimal => VK_DECIMAL,
+        KeyCode::F13 => VK_F13,
+        KeyCode::F14 => VK_F14,
+        KeyCode::F15 => VK_F15,
+        KeyCode::F16 => VK_F16,
+        KeyCode::F17 => VK_F17,
+        KeyCode::F18 => VK_F18,
+        KeyCode::F19 => VK_F19,
+        KeyCode::F20 => VK_F20,
+        KeyCode::F21 => VK_F21,
+        KeyCode::F22 => VK_F22,
+        KeyCode::F23 => VK_F23,
+        KeyCode::F24 => VK_F24,
+        KeyCode::Mute => VK_VOLUME_MUTE,
+        KeyCode::VolumeDown => VK_VOLUME_DOWN,
+        KeyCode::VolumeUp => VK_VOLUME_UP,
+        KeyCode::MediaPlayPause => VK_MEDIA_PLAY_PAUSE,
+        KeyCode::MediaStop => VK_MEDIA_STOP,
+        KeyCode::MediaPrevious => VK_MEDIA_PREV_TRACK,
+        KeyCode::MediaNext => VK_MEDIA_NEXT_TRACK,
+        KeyCode::Sleep => VK_SLEEP,
+        KeyCode::BrowserBack => VK_BROWSER_BACK,
+        KeyCode::BrowserForward => VK_BROWSER_FORWARD,
+        KeyCode::BrowserRefresh => VK_BROWSER_REFRESH,
+        KeyCode::BrowserStop => VK_BROWSER_STOP,
+        KeyCode::BrowserSearch => VK_BROWSER_SEARCH,
+        KeyCode::BrowserFavorites => VK_BROWSER_FAVORITES,
+        KeyCode::BrowserHome => VK_BROWSER_HOME,
+        KeyCode::AppMail => VK_LAUNCH_MAIL,
+        KeyCode::AppCalculator => VK_LAUNCH_APP2,
+        KeyCode::AppMyComputer => VK_LAUNCH_APP1,
+        KeyCode::Menu => VK_APPS,
+        KeyCode::Help => VK_HELP,
+        KeyCode::Select => VK_SELECT,
+        KeyCode::Execute => VK_EXECUTE,
+        KeyCode::Zenkaku => VK_KANJI,
+        KeyCode::Katakana => VK_DBE_KATAKANA,
+        KeyCode::Hiragana => VK_DBE_HIRAGANA,
+        KeyCode::Henkan => VK_CONVERT,
+        KeyCode::Muhenkan => VK_NONCONVERT,
+        KeyCode::Ro => VK_ABNT_C1,
+        KeyCode::KatakanaHiragana => VK_KANA,
+        KeyCode::NumpadJpComma => VK_ABNT_C2,
+        KeyCode::Hangeul => VK_HANGUL, // Same value as VK_KANA
+        KeyCode::Hanja => VK_HANJA,    // Same value as VK_KANJI
+        KeyCode::Iso102nd => VK_OEM_102,
+        KeyCode::NumpadComma => VK_SEPARATOR,
+        KeyCode::Power
+        | KeyCode::Wake
+        | KeyCode::BrightnessDown
+        | KeyCode::BrightnessUp
+        | KeyCode::Undo
+        | KeyCode::Redo
+        | KeyCode::Cut
+        | KeyCode::Copy
+        | KeyCode::Paste
+        | KeyCode::Find
+        | KeyCode::Yen => return None,
+    };
+    Some(vk)
 }
 
 #[cfg(test)]
@@ -280,6 +477,61 @@ mod tests {
     fn test_keycode_to_vk() {
         assert_eq!(keycode_to_vk(KeyCode::A), Some(VK_A as u16));
         assert_eq!(keycode_to_vk(KeyCode::Enter), Some(VK_RETURN as u16));
+        assert_eq!(keycode_to_vk(KeyCode::NumpadEnter), Some(VK_RETURN as u16));
+    }
+
+    #[test]
+    fn test_numpad_scancodes_distinct_from_main_row() {
+        assert_eq!(scancode_to_keycode(0x1C), Some(KeyCode::Enter));
+        assert_eq!(scancode_to_keycode(0xE01C), Some(KeyCode::NumpadEnter));
+        assert_eq!(scancode_to_keycode(0xE035), Some(KeyCode::NumpadDivide));
+        assert_eq!(vk_to_keycode(VK_NUMPAD1 as u16), Some(KeyCode::Numpad1));
+        assert_eq!(vk_to_keycode(VK_1 as u16), Some(KeyCode::Num1));
+    }
+
+    #[test]
+    fn test_international_scancodes_are_layout_independent() {
+        assert_eq!(scancode_to_keycode(0x56), Some(KeyCode::Iso102nd));
+        assert_eq!(scancode_to_keycode(0x73), Some(KeyCode::Ro));
+        assert_eq!(scancode_to_keycode(0x7D), Some(KeyCode::Yen));
+        assert_eq!(scancode_to_keycode(0x79), Some(KeyCode::Henkan));
+        assert_eq!(scancode_to_keycode(0x7B), Some(KeyCode::Muhenkan));
+    }
+
+    /// Every `KeyCode` variant, in declaration order
+    fn all_keycodes() -> Vec<KeyCode> {
+        use serde::de::{value::Error, Deserialize, IntoDeserializer};
+
+        (0u32..)
+            .map_while(|index| {
+                KeyCode::deserialize(IntoDeserializer::<Error>::into_deserializer(index)).ok()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_every_keycode_has_windows_decision() {
+        let keycodes = all_keycodes();
+        assert!(keycodes.contains(&KeyCode::NumpadComma));
+
+        for keycode in keycodes {
+            match keycode_to_vk(keycode) {
+                Some(vk) => assert!(
+                    vk_to_keycode(vk).is_some(),
+                    "{:?} injects VK {:#04x}, which input doesn't recognize",
+                    keycode,
+                    vk
+                ),
+                None => assert!(
+                    UNSUPPORTED_KEYCODES.contains(&keycode),
+                    "{:?} has no Windows virtual key and isn't listed as unsupported",
+                    keycode
+                ),
+            }
+        }
+        for keycode in UNSUPPORTED_KEYCODES {
+            assert_eq!(keycode_to_vk(keycode), None, "{:?}", keycode);
+        }
     }
 
     #[test]
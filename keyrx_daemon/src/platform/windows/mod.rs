@@ -96,6 +96,7 @@ pub(crate) fn convert_device_info(device: &device_map::DeviceInfo) -> CommonDevi
         path: device.path.clone(),
         vendor_id,
         product_id,
+        capabilities: Some(device.capabilities()),
     }
 }
 
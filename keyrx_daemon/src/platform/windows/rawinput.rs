@@ -20,10 +20,12 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{
     WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
 };
 
+use crate::daemon::WallClock;
 use crate::platform::recovery::recover_lock_with_context;
 use crate::platform::windows::device_map::DeviceMap;
 use crate::platform::windows::keycode::scancode_to_keycode;
 use crate::platform::PlatformError;
+use keyrx_core::runtime::clock::Clock;
 use keyrx_core::runtime::KeyEvent;
 
 static REGISTER_CLASS: Once = Once::new();
@@ -412,11 +414,14 @@ fn process_raw_keyboard(raw: &RAWKEYBOARD, device_handle: usize, context: &RawIn
     }
 
     if let Some(keycode) = scancode_to_keycode(scancode) {
+        // Stamped on the daemon's clock, like evdev events, so tap-hold
+        // deadlines and macro waits are measured from the key event
         let mut event = if is_break {
             KeyEvent::release(keycode)
         } else {
             KeyEvent::press(keycode)
-        };
+        }
+        .with_timestamp(WallClock.now());
 
         // Attach device ID if available
         if let Some(info) = context.device_map.get(device_handle as HANDLE) {
@@ -674,7 +679,8 @@ unsafe extern "system" fn test_bridge_hook(code: i32, w_param: WPARAM, l_param:
                                     KeyEvent::release(keycode)
                                 } else {
                                     KeyEvent::press(keycode)
-                                };
+                                }
+                                .with_timestamp(WallClock.now());
 
                                 log::debug!("Bridge Hook event: {:?}", event);
 
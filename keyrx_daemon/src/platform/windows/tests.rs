@@ -72,6 +72,7 @@ fn test_convert_device_info() {
             handle: 0x1234,
             path: r"\\?\HID#VID_046D&PID_C52B&MI_00#7&2a00c76d&0&0000#{884b96c3-56ef-11d1-bc8c-00a0c91405dd}".to_string(),
             serial: Some("7&2a00c76d&0&0000".to_string()),
+            key_count: Some(104),
         };
 
     let common = convert_device_info(&device);
@@ -79,4 +80,10 @@ fn test_convert_device_info() {
     assert_eq!(common.vendor_id, 0x046D);
     assert_eq!(common.product_id, 0xC52B);
     assert_eq!(common.path, device.path);
+    let capabilities = common
+        .capabilities
+        .expect("RawInput keyboards report capabilities");
+    assert!(capabilities.supports_keys);
+    assert_eq!(capabilities.key_count, 104);
+    assert!(!capabilities.supports_rel);
 }
@@ -32,11 +32,12 @@ mod test_utils;
 
 use std::time::Instant;
 
-use keyrx_core::config::DeviceConfig;
+use keyrx_core::config::{DeviceConfig, KeyCode};
 use keyrx_core::runtime::event::{process_event, KeyEvent};
 use keyrx_core::runtime::{DeviceState, KeyLookup};
 use thiserror::Error;
 
+use crate::daemon::remapping_state::local_minute_of_day;
 use crate::platform::{DeviceError, InputDevice, OutputDevice};
 
 /// Errors that can occur during event processing.
@@ -67,13 +68,15 @@ pub struct EventProcessor<I: InputDevice, O: OutputDevice> {
     lookup: KeyLookup,
     /// Runtime state (modifier and lock bits)
     state: DeviceState,
+    /// Keys currently pressed on the output device, in press order
+    held_outputs: Vec<KeyCode>,
 }
 
 impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
     /// Creates a new event processor.
     pub fn new(config: &DeviceConfig, input: I, output: O) -> Self {
         let lookup = KeyLookup::from_device_config(config);
-        let state = DeviceState::new();
+        let state = DeviceState::for_device(config);
 
         logging::log_config_loaded(config.mappings.len());
 
@@ -82,6 +85,7 @@ impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
             output,
             lookup,
             state,
+            held_outputs: Vec::new(),
         }
     }
 
@@ -91,7 +95,12 @@ impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
 
         let event = self.read_input_event()?;
         let transition_context = self.detect_state_transition(event.clone());
-        let output_events = process_event(event.clone(), &self.lookup, &mut self.state);
+        if !self.state.time_windows().is_empty() {
+            self.state.set_minute_of_day(local_minute_of_day());
+        }
+        let mut output_events = process_event(event.clone(), &self.lookup, &mut self.state);
+        // Without a clock to wait on, macro steps after a wait() go out at once
+        output_events.extend(self.state.take_scheduled_outputs());
 
         if let Some(context) = transition_context {
             logging::log_state_transition(&context);
@@ -116,6 +125,40 @@ impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
         }
     }
 
+    /// Swaps in a new configuration.
+    ///
+    /// Every output key still held is released first, so a key that changes
+    /// meaning cannot leave an application with a stuck key. Momentary state
+    /// (modifiers, tap-hold, tracked presses) is cleared; lock state is kept.
+    /// Keys physically held across the reload must be re-pressed.
+    pub fn reload(&mut self, config: &DeviceConfig) -> Result<(), ProcessorError> {
+        self.release_all()?;
+
+        let locks = self.state.active_locks();
+        self.lookup = KeyLookup::from_device_config(config);
+        self.state = DeviceState::for_device(config);
+        self.state.restore_locks(&locks);
+
+        logging::log_config_loaded(config.mappings.len());
+        Ok(())
+    }
+
+    /// Injects a release for every output key still held, most recent first.
+    pub fn release_all(&mut self) -> Result<(), ProcessorError> {
+        let held: Vec<KeyEvent> = self
+            .held_outputs
+            .iter()
+            .rev()
+            .map(|&key| KeyEvent::release(key))
+            .collect();
+        self.inject_output_events(&held)
+    }
+
+    /// Returns the runtime state (for testing).
+    pub fn state(&self) -> &DeviceState {
+        &self.state
+    }
+
     /// Returns a reference to the output device (for testing).
     pub fn output(&self) -> &O {
         &self.output
@@ -135,6 +178,11 @@ impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
     fn detect_state_transition(&self, event: KeyEvent) -> Option<String> {
         use keyrx_core::config::mappings::BaseKeyMapping;
 
+        // Repeats never change modifier or lock state
+        if event.is_repeat() {
+            return None;
+        }
+
         let mapping = self.lookup.find_mapping(event.keycode(), &self.state)?;
 
         match mapping {
@@ -157,13 +205,26 @@ impl<I: InputDevice, O: OutputDevice> EventProcessor<I, O> {
     }
 
     fn inject_output_events(&mut self, events: &[KeyEvent]) -> Result<(), ProcessorError> {
+        // Outputs of one input go out together, so a shifted key never
+        // reaches applications ahead of its Shift
+        let result = self.output.inject_batch(events);
+
+        // Track presses even if the batch failed part-way: releasing a key
+        // that never went out is harmless, leaving one stuck is not
         for event in events {
-            self.output.inject_event(event.clone()).map_err(|e| {
-                logging::log_platform_error(&e.to_string(), "output");
-                ProcessorError::Output(e)
-            })?;
+            if event.is_press() {
+                if !self.held_outputs.contains(&event.keycode()) {
+                    self.held_outputs.push(event.keycode());
+                }
+            } else if event.is_release() && result.is_ok() {
+                self.held_outputs.retain(|&key| key != event.keycode());
+            }
         }
-        Ok(())
+
+        result.map_err(|e| {
+            logging::log_platform_error(&e.to_string(), "output");
+            ProcessorError::Output(e)
+        })
     }
 
     fn log_processed_event(&self, input: KeyEvent, outputs: &[KeyEvent], latency_us: u64) {
@@ -299,6 +360,59 @@ mod tests {
         assert_eq!(processor.output.events()[0], KeyEvent::Press(KeyCode::Left));
     }
 
+    #[test]
+    fn test_reload_releases_held_outputs_and_clears_modifiers() {
+        let config = create_test_config(vec![
+            KeyMapping::modifier(KeyCode::CapsLock, 0),
+            KeyMapping::conditional(
+                Condition::AllActive(vec![ConditionItem::ModifierActive(0)]),
+                vec![BaseKeyMapping::Simple {
+                    from: KeyCode::H,
+                    to: KeyCode::Left,
+                }],
+            ),
+        ]);
+
+        let input = MockInput::new(vec![
+            KeyEvent::Press(KeyCode::CapsLock),
+            KeyEvent::Press(KeyCode::H),
+        ]);
+        let mut processor = EventProcessor::new(&config, input, MockOutput::new());
+        processor.process_one().unwrap();
+        processor.process_one().unwrap();
+        assert!(processor.state().is_modifier_active(0));
+
+        // CapsLock is now a plain key
+        let new_config =
+            create_test_config(vec![KeyMapping::simple(KeyCode::CapsLock, KeyCode::Escape)]);
+        processor.reload(&new_config).unwrap();
+
+        assert!(!processor.state().is_modifier_active(0));
+        assert_eq!(
+            processor.output.events(),
+            &[
+                KeyEvent::Press(KeyCode::Left),
+                KeyEvent::Release(KeyCode::Left)
+            ]
+        );
+
+        // Nothing left to release
+        processor.release_all().unwrap();
+        assert_eq!(processor.output.events().len(), 2);
+    }
+
+    #[test]
+    fn test_reload_preserves_locks() {
+        let config = create_test_config(vec![KeyMapping::lock(KeyCode::ScrollLock, 1)]);
+        let input = MockInput::new(vec![KeyEvent::Press(KeyCode::ScrollLock)]);
+        let mut processor = EventProcessor::new(&config, input, MockOutput::new());
+        processor.process_one().unwrap();
+        assert!(processor.state().is_lock_active(1));
+
+        processor.reload(&config).unwrap();
+        assert!(processor.state().is_lock_active(1));
+    }
+
     #[test]
     fn test_run_processes_all_events() {
         let config = create_test_config(vec![KeyMapping::simple(KeyCode::A, KeyCode::B)]);
@@ -7,7 +7,12 @@ pub fn create_test_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
@@ -31,7 +31,13 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::config::{ActivationResult, ProfileError, ProfileManager, ProfileTemplate};
+use keyrx_compiler::parser::Parser;
+use keyrx_core::config::ConfigRoot;
+
+use crate::config::bundle::{self, BundleError, ConflictPolicy, ExportSummary, ImportSummary};
+use crate::config::{
+    ActivationResult, CompilationError, ProfileError, ProfileManager, ProfileTemplate,
+};
 
 /// Profile information returned by list operations.
 #[derive(Debug, Clone)]
@@ -557,6 +563,49 @@ impl ProfileService {
         })
     }
 
+    /// Exports all profiles, device metadata, and custom layouts to one archive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BundleError`] if any of them cannot be read or `dest`
+    /// cannot be written.
+    pub async fn export_bundle(&self, dest: &Path) -> Result<ExportSummary, BundleError> {
+        log::info!("Exporting profile bundle to {:?}", dest);
+
+        let summary = bundle::export_bundle(&self.profile_manager, dest)?;
+
+        log::info!(
+            "Bundle exported: {} profiles, {} devices, {} layouts",
+            summary.profiles,
+            summary.devices,
+            summary.layouts
+        );
+        Ok(summary)
+    }
+
+    /// Imports an archive written by [`export_bundle`](Self::export_bundle).
+    ///
+    /// Names that are already taken are handled according to `policy`;
+    /// device metadata is merged into the local registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BundleError`] if the archive is invalid or an item cannot
+    /// be written.
+    pub async fn import_bundle(
+        &self,
+        src: &Path,
+        policy: ConflictPolicy,
+    ) -> Result<ImportSummary, BundleError> {
+        log::info!("Importing profile bundle from {:?} ({:?})", src, policy);
+
+        let manager_ptr = Arc::as_ptr(&self.profile_manager) as *mut ProfileManager;
+        let summary = unsafe { bundle::import_bundle(&mut *manager_ptr, src, policy)? };
+
+        log::info!("Bundle imported successfully");
+        Ok(summary)
+    }
+
     /// Gets the currently active profile name.
     ///
     /// # Returns
@@ -622,6 +671,48 @@ impl ProfileService {
         self.profile_manager.get_config(name)
     }
 
+    /// Parses a profile's Rhai configuration without writing a .krx file.
+    ///
+    /// Imports are resolved relative to the profile's .rhai file, exactly as
+    /// during activation.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Profile name
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProfileError::NotFound`] if profile doesn't exist.
+    /// Returns [`ProfileError::Compilation`] if the script fails to parse.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use std::path::PathBuf;
+    /// # use keyrx_daemon::config::ProfileManager;
+    /// # use keyrx_daemon::services::ProfileService;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let manager = Arc::new(ProfileManager::new(PathBuf::from("./config"))?);
+    /// let service = ProfileService::new(manager);
+    /// let config = service.parse_profile("gaming").await?;
+    /// println!("{} device block(s)", config.devices.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn parse_profile(&self, name: &str) -> Result<ConfigRoot, ProfileError> {
+        log::debug!("Parsing profile: {}", name);
+
+        let metadata = self
+            .profile_manager
+            .get(name)
+            .ok_or_else(|| ProfileError::NotFound(name.to_string()))?;
+
+        Parser::new()
+            .parse_script(&metadata.rhai_path)
+            .map_err(|e| CompilationError::CompilationFailed(e.to_string()).into())
+    }
+
     /// Sets the configuration content for a profile.
     ///
     /// Writes the configuration content to the profile's .rhai file.
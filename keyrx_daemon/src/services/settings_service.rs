@@ -4,7 +4,8 @@
 //! default keyboard layout configuration.
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
 
 /// Default web server port
 pub const DEFAULT_PORT: u16 = 9867;
@@ -19,6 +20,14 @@ pub struct DaemonSettings {
     /// Web server port (default: 9867)
     #[serde(default = "default_port")]
     pub port: u16,
+
+    /// Bearer token required by the web API (generated on first start)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_token: Option<String>,
+
+    /// Web server bind address (default: 127.0.0.1 on `port`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_addr: Option<SocketAddr>,
 }
 
 fn default_port() -> u16 {
@@ -30,6 +39,8 @@ impl Default for DaemonSettings {
         Self {
             global_layout: None,
             port: DEFAULT_PORT,
+            api_token: None,
+            web_addr: None,
         }
     }
 }
@@ -70,7 +81,7 @@ impl SettingsService {
         let json = serde_json::to_string_pretty(settings)
             .map_err(|e| format!("Failed to serialize settings: {}", e))?;
 
-        std::fs::write(&self.settings_path, json)
+        write_private(&self.settings_path, &json)
             .map_err(|e| format!("Failed to write settings file: {}", e))?;
 
         log::info!("Saved settings to {:?}", self.settings_path);
@@ -118,12 +129,95 @@ impl SettingsService {
         Ok(())
     }
 
+    /// Record the port the web server actually bound
+    ///
+    /// When the configured port was taken and the server moved to another
+    /// one, the new port is saved so the web UI and the next start use it.
+    /// An explicit `web_addr` setting is left alone. Returns whether the
+    /// settings changed.
+    pub fn record_bound_port(&self, port: u16) -> Result<bool, String> {
+        let settings = self.load_settings()?;
+        if settings.web_addr.is_some() || settings.port == port {
+            return Ok(false);
+        }
+        self.set_port(port)?;
+        Ok(true)
+    }
+
+    /// Get the web server bind address
+    ///
+    /// `override_addr` (from `--web-addr`) takes precedence over the
+    /// `web_addr` setting; without either, the server listens on localhost
+    /// at the configured port.
+    pub fn get_web_addr(&self, override_addr: Option<SocketAddr>) -> SocketAddr {
+        if let Some(addr) = override_addr {
+            return addr;
+        }
+        let settings = self.load_settings().unwrap_or_default();
+        settings
+            .web_addr
+            .unwrap_or_else(|| SocketAddr::from((Ipv4Addr::LOCALHOST, settings.port)))
+    }
+
+    /// Get the web API token, generating and saving one if none exists
+    pub fn get_or_create_api_token(&self) -> Result<String, String> {
+        let mut settings = self.load_settings()?;
+        if let Some(token) = settings.api_token.as_ref().filter(|t| !t.is_empty()) {
+            return Ok(token.clone());
+        }
+
+        let token = crate::web::auth::generate_token()?;
+        settings.api_token = Some(token.clone());
+        self.save_settings(&settings)?;
+
+        log::info!("Generated new web API token");
+        Ok(token)
+    }
+
     /// Get path to settings file
     pub fn settings_path(&self) -> &PathBuf {
         &self.settings_path
     }
 }
 
+/// Writes `contents` to `path`, readable only by the current user
+///
+/// The settings file holds the API token, so it is written to a temporary
+/// file created with mode 0600 and renamed over `path`. The token is never
+/// readable by others, not even briefly, and a failed write leaves the old
+/// file intact.
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    // A leftover temp file keeps its old permissions, so start from scratch
+    match std::fs::remove_file(&temp_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let written = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| std::fs::rename(&temp_path, path)) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
 /// Validate layout name (must be one of the supported presets)
 fn validate_layout(layout: &str) -> Result<(), String> {
     const VALID_LAYOUTS: &[&str] = &["ANSI_104", "ISO_105", "JIS_109", "HHKB", "NUMPAD"];
@@ -208,6 +302,117 @@ mod tests {
         assert_eq!(layout, None);
     }
 
+    #[test]
+    fn test_web_addr_defaults_to_localhost() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+
+        assert_eq!(
+            service.get_web_addr(None),
+            SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT))
+        );
+
+        // The default follows the configured port
+        service.set_port(9000).unwrap();
+        assert_eq!(
+            service.get_web_addr(None),
+            SocketAddr::from(([127, 0, 0, 1], 9000))
+        );
+    }
+
+    #[test]
+    fn test_web_addr_override_and_setting() {
+        let temp_dir = TempDir::new().unwrap();
+        write_private(
+            &temp_dir.path().join("settings.json"),
+            r#"{"port": 9867, "web_addr": "192.168.1.10:8080"}"#,
+        )
+        .unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+
+        assert_eq!(
+            service.get_web_addr(None),
+            "192.168.1.10:8080".parse::<SocketAddr>().unwrap()
+        );
+
+        let cli_addr: SocketAddr = "127.0.0.1:7000".parse().unwrap();
+        assert_eq!(service.get_web_addr(Some(cli_addr)), cli_addr);
+    }
+
+    #[test]
+    fn test_record_bound_port() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+
+        assert!(!service.record_bound_port(DEFAULT_PORT).unwrap());
+        assert!(service.record_bound_port(DEFAULT_PORT + 1).unwrap());
+        assert_eq!(service.get_port(), DEFAULT_PORT + 1);
+
+        // An explicit bind address wins over the port setting
+        let mut settings = service.load_settings().unwrap();
+        settings.web_addr = Some("127.0.0.1:7000".parse().unwrap());
+        service.save_settings(&settings).unwrap();
+        assert!(!service.record_bound_port(7001).unwrap());
+        assert_eq!(service.get_port(), DEFAULT_PORT + 1);
+    }
+
+    #[test]
+    fn test_api_token_is_generated_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+
+        let token = service.get_or_create_api_token().unwrap();
+        assert!(!token.is_empty());
+
+        // Token persists across service instances
+        let service2 = SettingsService::new(temp_dir.path().to_path_buf());
+        assert_eq!(service2.get_or_create_api_token().unwrap(), token);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_settings_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+        service.get_or_create_api_token().unwrap();
+
+        let mode = std::fs::metadata(service.settings_path())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_settings_rewrite_makes_readable_file_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let service = SettingsService::new(temp_dir.path().to_path_buf());
+        std::fs::write(service.settings_path(), r#"{"port": 9867}"#).unwrap();
+        std::fs::set_permissions(
+            service.settings_path(),
+            std::fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+        // A stale temp file from an interrupted write must not be reused
+        let temp_path = temp_dir.path().join("settings.json.tmp");
+        std::fs::write(&temp_path, "").unwrap();
+        std::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        service.get_or_create_api_token().unwrap();
+
+        let mode = std::fs::metadata(service.settings_path())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!temp_path.exists());
+    }
+
     #[test]
     fn test_validate_layout_valid() {
         assert!(validate_layout("ANSI_104").is_ok());
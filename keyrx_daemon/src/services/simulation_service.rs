@@ -106,7 +106,7 @@ impl SimulationService {
     ///
     /// This is an async method that sends the event to the event bus channel.
     /// It converts the OutputEvent format to KeyEvent format with:
-    /// - Lowercase event_type ("press", "release")
+    /// - Lowercase event_type ("press", "release", "repeat")
     /// - Current timestamp in microseconds
     /// - Key as string
     async fn send_to_event_bus(&self, output: &OutputEvent) -> Result<(), SimulationError> {
@@ -160,6 +160,7 @@ impl SimulationService {
             let key_event = match output.event_type {
                 EventType::Press => KeyEvent::press(keycode).with_timestamp(timestamp),
                 EventType::Release => KeyEvent::release(keycode).with_timestamp(timestamp),
+                EventType::Repeat => KeyEvent::repeat(keycode).with_timestamp(timestamp),
             };
 
             // Send to event bus
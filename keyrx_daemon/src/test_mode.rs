@@ -4,6 +4,7 @@
 //! where keyboard capture is disabled and profile activation is handled via IPC.
 
 use crate::config::ProfileManager;
+use crate::config_loader::LoadedConfig;
 use crate::ipc::commands::IpcCommandHandler;
 use crate::ipc::server::{get_test_socket_path, IpcServer};
 use std::sync::Arc;
@@ -36,10 +37,10 @@ impl TestModeContext {
         let ipc_server = IpcServer::new(socket_path)?;
         let daemon_running = Arc::new(RwLock::new(true));
 
-        let command_handler = Arc::new(IpcCommandHandler::new(
-            profile_manager,
-            Arc::clone(&daemon_running),
-        ));
+        let command_handler = Arc::new(
+            IpcCommandHandler::new(profile_manager, Arc::clone(&daemon_running))
+                .with_loaded_config(LoadedConfig::new()),
+        );
 
         Ok(Self {
             ipc_server: Arc::new(Mutex::new(ipc_server)),
@@ -86,8 +86,8 @@
 //! keyboard.inject(KeyEvent::Press(KeyCode::A))?;
 //! keyboard.inject(KeyEvent::Release(KeyCode::A))?;
 //!
-//! // 6. Capture output events
-//! let captured = capture.collect_events(Duration::from_millis(100))?;
+//! // 6. Capture output events, returning as soon as both have arrived
+//! let captured = capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
 //!
 //! // 7. Verify captured events match expected
 //! let expected = vec![
@@ -5,11 +5,12 @@ use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-use evdev::{Device, InputEventKind};
+use evdev::{Device, InputEvent, InputEventKind, Synchronization};
 use nix::poll::{poll, PollFd, PollFlags, PollTimeout};
 
 use keyrx_core::runtime::event::KeyEvent;
 
+use super::CapturedEvent;
 use crate::platform::linux::evdev_to_keycode;
 use crate::test_utils::VirtualDeviceError;
 
@@ -334,35 +335,9 @@ impl OutputCapture {
         };
 
         // Collect all key events from this batch
-        let mut key_events = Vec::new();
-        for event in events {
-            // Only process EV_KEY events
-            if let InputEventKind::Key(key) = event.kind() {
-                let value = event.value();
-
-                match value {
-                    1 => {
-                        // Key press
-                        if let Some(keycode) = evdev_to_keycode(key.code()) {
-                            key_events.push(KeyEvent::Press(keycode));
-                        }
-                    }
-                    0 => {
-                        // Key release
-                        if let Some(keycode) = evdev_to_keycode(key.code()) {
-                            key_events.push(KeyEvent::Release(keycode));
-                        }
-                    }
-                    2 => {
-                        // Key repeat - ignore
-                    }
-                    _ => {
-                        // Unknown event value - ignore
-                    }
-                }
-            }
-            // Non-key events (EV_SYN, EV_MSC, etc.) are ignored
-        }
+        // Non-key events (EV_SYN, EV_MSC, etc.) are ignored
+        let mut key_events: Vec<KeyEvent> =
+            events.filter_map(|event| to_key_event(&event)).collect();
 
         // Return first event, buffer the rest
         if key_events.is_empty() {
@@ -422,6 +397,123 @@ impl OutputCapture {
         }
     }
 
+    /// Collects keyboard events until `predicate` accepts them or `timeout`
+    /// expires.
+    ///
+    /// Unlike [`collect_events`](Self::collect_events), this returns as soon
+    /// as the events seen so far satisfy the predicate (e.g. "saw N events"),
+    /// so tests do not wait out a fixed idle period. `timeout` bounds the
+    /// whole collection.
+    ///
+    /// # Returns
+    ///
+    /// The collected events, whether or not the predicate was satisfied.
+    /// Check the result against the expectation to tell the two apart.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use keyrx_daemon::test_utils::OutputCapture;
+    /// use std::time::Duration;
+    ///
+    /// let mut capture = OutputCapture::find_by_name("test-keyboard", timeout)?;
+    ///
+    /// // Return once press + release arrived, or after 1s at most
+    /// let events = capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
+    /// ```
+    pub fn collect_until(
+        &mut self,
+        predicate: impl Fn(&[CapturedEvent]) -> bool,
+        timeout: Duration,
+    ) -> Result<Vec<KeyEvent>, VirtualDeviceError> {
+        let mut events = Vec::new();
+        let start = Instant::now();
+
+        while !predicate(&events) {
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            match self.next_event(remaining)? {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Collects keyboard events grouped into the input frames they arrived in.
+    ///
+    /// A frame is the run of events the writer ended with one `SYN_REPORT`.
+    /// Applications apply a frame as a whole, so a Shift press in the same
+    /// frame as (or an earlier frame than) a key press shifts that key.
+    /// Collection stops once `timeout` passes without new events.
+    ///
+    /// Events already buffered by [`next_event`](Self::next_event) were read
+    /// without frame information and are returned as a leading frame.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let frames = capture.collect_frames(Duration::from_millis(100))?;
+    /// assert_eq!(frames, vec![vec![KeyEvent::Press(KeyCode::LShift), KeyEvent::Press(KeyCode::Num1)]]);
+    /// ```
+    pub fn collect_frames(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Vec<Vec<KeyEvent>>, VirtualDeviceError> {
+        let mut frames = Vec::new();
+        if !self.event_buffer.is_empty() {
+            frames.push(std::mem::take(&mut self.event_buffer));
+        }
+        let mut frame = Vec::new();
+
+        loop {
+            // SAFETY: The raw fd is valid for the lifetime of the loop iteration since
+            // we hold &mut self, ensuring the device stays alive
+            let borrowed_fd =
+                unsafe { std::os::fd::BorrowedFd::borrow_raw(self.device.as_raw_fd()) };
+            let timeout_ms = timeout.as_millis().min(u16::MAX as u128) as u16;
+
+            let mut poll_fds = [PollFd::new(borrowed_fd, PollFlags::POLLIN)];
+            match poll(&mut poll_fds, PollTimeout::from(timeout_ms)) {
+                Ok(0) => break,
+                Ok(_) => {
+                    let events = match self.device.fetch_events() {
+                        Ok(events) => events,
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                        Err(e) => return Err(VirtualDeviceError::Io(e)),
+                    };
+                    for event in events {
+                        if event.kind()
+                            == InputEventKind::Synchronization(Synchronization::SYN_REPORT)
+                        {
+                            if !frame.is_empty() {
+                                frames.push(std::mem::take(&mut frame));
+                            }
+                        } else if let Some(key_event) = to_key_event(&event) {
+                            frame.push(key_event);
+                        }
+                    }
+                }
+                Err(nix::errno::Errno::EINTR) => continue,
+                Err(e) => {
+                    return Err(VirtualDeviceError::Io(std::io::Error::other(format!(
+                        "poll failed: {}",
+                        e
+                    ))));
+                }
+            }
+        }
+
+        // A frame still open at the timeout was never reported
+        if !frame.is_empty() {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
     /// Drains and discards all pending events from the device.
     ///
     /// This is useful before starting a test to ensure no stale events
@@ -493,6 +585,22 @@ impl OutputCapture {
     }
 }
 
+/// Converts an `EV_KEY` event into a [`KeyEvent`].
+///
+/// Returns `None` for other event types, unknown keys and unknown values.
+fn to_key_event(event: &InputEvent) -> Option<KeyEvent> {
+    let InputEventKind::Key(key) = event.kind() else {
+        return None;
+    };
+    let keycode = evdev_to_keycode(key.code())?;
+    match event.value() {
+        1 => Some(KeyEvent::Press(keycode)),
+        0 => Some(KeyEvent::Release(keycode)),
+        2 => Some(KeyEvent::repeat(keycode)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -772,16 +880,11 @@ mod tests {
             .inject(KeyEvent::Release(KeyCode::B))
             .expect("Failed to inject key release");
 
-        // Collect exactly 2 events with individual timeouts
-        // This is more reliable than collect_events which uses idle timeout
-        let mut events = Vec::new();
-        for _ in 0..2 {
-            match capture.next_event(Duration::from_secs(2)) {
-                Ok(Some(event)) => events.push(event),
-                Ok(None) => break,
-                Err(e) => panic!("Error collecting event: {:?}", e),
-            }
-        }
+        // Wait for exactly 2 events rather than an idle period, which is
+        // more reliable under load than collect_events
+        let events = capture
+            .collect_until(|events| events.len() >= 2, Duration::from_secs(2))
+            .expect("collect_until failed");
 
         assert_eq!(events.len(), 2, "Should collect 2 events");
         assert_eq!(events[0], KeyEvent::Press(KeyCode::B));
@@ -818,6 +921,35 @@ mod tests {
         assert!(events.is_empty(), "Should return empty vector on timeout");
     }
 
+    /// Test collect_until gives up at the timeout when the predicate never holds
+    #[test]
+    fn test_collect_until_times_out() {
+        crate::skip_if_no_uinput!();
+        use crate::test_utils::VirtualKeyboard;
+
+        let mut keyboard = VirtualKeyboard::create("collect-until-timeout-test")
+            .expect("Failed to create virtual keyboard");
+        let device_name = keyboard.name().to_string();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let mut capture = OutputCapture::find_by_name(&device_name, Duration::from_secs(5))
+            .expect("Failed to find device");
+        let _ = capture.drain();
+
+        keyboard
+            .inject(KeyEvent::Press(KeyCode::C))
+            .expect("Failed to inject key press");
+
+        // Only one event arrives, so the predicate is never satisfied
+        let start = Instant::now();
+        let events = capture
+            .collect_until(|events| events.len() >= 2, Duration::from_millis(300))
+            .expect("collect_until failed");
+
+        assert_eq!(events, vec![KeyEvent::Press(KeyCode::C)]);
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
     /// Test drain clears pending events
     #[test]
     fn test_drain_clears_events() {
@@ -40,14 +40,16 @@ pub use windows::OutputCapture;
 ///
 /// This is a convenience type alias for the core `KeyEvent` type,
 /// used for test assertions and comparisons.
-#[allow(dead_code)] // Type alias for documentation/clarity
 pub type CapturedEvent = KeyEvent;
 
 /// Result of comparing captured and expected events.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum EventComparison {
-    /// Event matched at this position.
-    Match(KeyEvent),
+    /// Event matched at this position, ignoring timestamps: (captured, expected).
+    Match {
+        captured: KeyEvent,
+        expected: KeyEvent,
+    },
     /// Event at this position differs: (captured, expected).
     Mismatch {
         captured: KeyEvent,
@@ -89,8 +91,11 @@ impl EventAssertionResult {
 
         for i in 0..max_len {
             match (captured.get(i), expected.get(i)) {
-                (Some(c), Some(e)) if c == e => {
-                    comparisons.push(EventComparison::Match(c.clone()));
+                (Some(c), Some(e)) if c.matches(e) => {
+                    comparisons.push(EventComparison::Match {
+                        captured: c.clone(),
+                        expected: e.clone(),
+                    });
                     matches += 1;
                 }
                 (Some(c), Some(e)) => {
@@ -155,12 +160,12 @@ impl EventAssertionResult {
 
         for (i, comparison) in self.comparisons.iter().enumerate() {
             match comparison {
-                EventComparison::Match(event) => {
+                EventComparison::Match { captured, expected } => {
                     output.push_str(&format!(
                         "  {:3}  ✓ match  {:<32} {:<32}\n",
                         i,
-                        format_event(event),
-                        format_event(event)
+                        format_event(captured),
+                        format_event(expected)
                     ));
                 }
                 EventComparison::Mismatch { captured, expected } => {
@@ -195,11 +200,20 @@ impl EventAssertionResult {
 }
 
 /// Formats a KeyEvent for display in assertion output.
+///
+/// Timestamps are shown when set even though they do not affect matching,
+/// so timing can still be read off a failing diff.
 fn format_event(event: &KeyEvent) -> String {
-    if event.is_press() {
-        format!("Press({:?})", event.keycode())
+    let kind = if event.is_press() {
+        "Press"
+    } else if event.is_repeat() {
+        "Repeat"
     } else {
-        format!("Release({:?})", event.keycode())
+        "Release"
+    };
+    match event.timestamp_us() {
+        0 => format!("{}({:?})", kind, event.keycode()),
+        ts => format!("{}({:?}) @{}us", kind, event.keycode(), ts),
     }
 }
 
@@ -207,6 +221,8 @@ fn format_event(event: &KeyEvent) -> String {
 ///
 /// This function performs a detailed comparison and returns a result
 /// indicating whether the events match, along with a detailed diff.
+/// Events are compared with [`KeyEvent::matches`], so timestamps are
+/// ignored.
 ///
 /// # Arguments
 ///
@@ -362,7 +378,7 @@ mod tests {
 
         // Verify all comparisons are matches
         for comparison in &result.comparisons {
-            matches!(comparison, EventComparison::Match(_));
+            assert!(matches!(comparison, EventComparison::Match { .. }));
         }
     }
 
@@ -384,7 +400,10 @@ mod tests {
         // Check the comparison details
         assert_eq!(
             result.comparisons[0],
-            EventComparison::Match(KeyEvent::Press(KeyCode::A))
+            EventComparison::Match {
+                captured: KeyEvent::Press(KeyCode::A),
+                expected: KeyEvent::Press(KeyCode::A),
+            }
         );
         assert_eq!(
             result.comparisons[1],
@@ -562,9 +581,13 @@ mod tests {
 
     #[test]
     fn test_event_comparison_equality() {
-        let match1 = EventComparison::Match(KeyEvent::Press(KeyCode::A));
-        let match2 = EventComparison::Match(KeyEvent::Press(KeyCode::A));
-        let match3 = EventComparison::Match(KeyEvent::Press(KeyCode::B));
+        let matched = |key| EventComparison::Match {
+            captured: KeyEvent::Press(key),
+            expected: KeyEvent::Press(key),
+        };
+        let match1 = matched(KeyCode::A);
+        let match2 = matched(KeyCode::A);
+        let match3 = matched(KeyCode::B);
 
         assert_eq!(match1, match2);
         assert_ne!(match1, match3);
@@ -588,6 +611,40 @@ mod tests {
             format_event(&KeyEvent::Release(KeyCode::Enter)),
             "Release(Enter)"
         );
+        assert_eq!(
+            format_event(&KeyEvent::press(KeyCode::A).with_timestamp(1500)),
+            "Press(A) @1500us"
+        );
+    }
+
+    #[test]
+    fn test_compare_events_ignores_timestamps() {
+        let captured = vec![
+            KeyEvent::press(KeyCode::B).with_timestamp(1_000),
+            KeyEvent::release(KeyCode::B).with_timestamp(51_000),
+        ];
+        let expected = vec![KeyEvent::Press(KeyCode::B), KeyEvent::Release(KeyCode::B)];
+
+        let result = compare_events(&captured, &expected);
+        assert!(result.passed);
+        assert_eq!(result.matches, 2);
+        assert_events(&captured, &expected);
+
+        // Captured timestamps are kept for the diff
+        let diff = result.format_diff();
+        assert!(diff.contains("Press(B) @1000us"));
+        assert!(diff.contains("Release(B) @51000us"));
+    }
+
+    #[test]
+    fn test_compare_events_timestamps_do_not_hide_mismatch() {
+        let captured = vec![KeyEvent::press(KeyCode::A).with_timestamp(1_000)];
+        let expected = vec![KeyEvent::release(KeyCode::A).with_timestamp(1_000)];
+
+        let result = compare_events(&captured, &expected);
+        assert!(!result.passed);
+        assert_eq!(result.mismatches, 1);
+        assert!(result.format_diff().contains("Release(A) @1000us"));
     }
 
     #[test]
@@ -1,6 +1,6 @@
 //! Windows-specific implementation of OutputCapture using low-level keyboard hooks.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use windows_sys::Win32::Foundation::*;
 use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
@@ -9,6 +9,7 @@ use windows_sys::Win32::UI::WindowsAndMessaging::*;
 
 use keyrx_core::runtime::event::KeyEvent;
 
+use super::CapturedEvent;
 use crate::platform::windows::keycode::vk_to_keycode;
 use crate::test_utils::VirtualDeviceError;
 
@@ -286,6 +287,52 @@ impl OutputCapture {
         }
     }
 
+    /// Collects keyboard events until `predicate` accepts them or `timeout`
+    /// expires.
+    ///
+    /// Unlike [`collect_events`](Self::collect_events), this returns as soon
+    /// as the events seen so far satisfy the predicate (e.g. "saw N events"),
+    /// so tests do not wait out a fixed idle period. `timeout` bounds the
+    /// whole collection.
+    ///
+    /// # Returns
+    ///
+    /// The collected events, whether or not the predicate was satisfied.
+    /// Check the result against the expectation to tell the two apart.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use keyrx_daemon::test_utils::OutputCapture;
+    /// use std::time::Duration;
+    ///
+    /// let mut capture = OutputCapture::find_by_name("test-keyboard", timeout)?;
+    ///
+    /// // Return once press + release arrived, or after 1s at most
+    /// let events = capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
+    /// ```
+    pub fn collect_until(
+        &mut self,
+        predicate: impl Fn(&[CapturedEvent]) -> bool,
+        timeout: Duration,
+    ) -> Result<Vec<KeyEvent>, VirtualDeviceError> {
+        let mut events = Vec::new();
+        let start = Instant::now();
+
+        while !predicate(&events) {
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            match self.next_event(remaining)? {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+
+        Ok(events)
+    }
+
     /// Drains and discards all pending events from the device.
     ///
     /// This is useful before starting a test to ensure no stale events
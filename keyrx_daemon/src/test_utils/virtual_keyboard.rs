@@ -273,6 +273,10 @@ impl VirtualKeyboard {
                 device.press(&key).map_err(|e| {
                     VirtualDeviceError::Io(std::io::Error::other(format!("press failed: {}", e)))
                 })?;
+            } else if event.is_repeat() {
+                device.send(key, 2).map_err(|e| {
+                    VirtualDeviceError::Io(std::io::Error::other(format!("repeat failed: {}", e)))
+                })?;
             } else {
                 device.release(&key).map_err(|e| {
                     VirtualDeviceError::Io(std::io::Error::other(format!("release failed: {}", e)))
@@ -315,7 +319,7 @@ impl VirtualKeyboard {
                 };
 
                 // Set extended key flag for certain keys
-                if is_extended_key(vk) {
+                if is_extended_key(vk) || keycode == KeyCode::NumpadEnter {
                     input.Anonymous.ki.dwFlags |= KEYEVENTF_EXTENDEDKEY;
                 }
 
@@ -1,7 +1,8 @@
 //! Configuration management endpoints.
 
 use axum::{
-    extract::Path,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path},
     routing::{delete, get, post},
     Json, Router,
 };
@@ -11,13 +12,23 @@ use std::sync::Arc;
 
 use crate::config::rhai_generator::{KeyAction, RhaiGenerator};
 use crate::error::DaemonError;
+use crate::web::api::error::ApiError;
 use crate::web::AppState;
 
+/// Largest .krx file `POST /api/config/upload` accepts, the same cap the
+/// WASM `load_krx` binding applies.
+const MAX_KRX_UPLOAD_SIZE: usize = 10 * 1024 * 1024;
+
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/config", get(get_config).put(update_config))
         .route("/config/key-mappings", post(set_key_mapping))
         .route("/config/key-mappings/:id", delete(delete_key_mapping))
+        .route("/config/ids", get(get_config_ids))
+        .route(
+            "/config/upload",
+            post(upload_config).layer(DefaultBodyLimit::max(MAX_KRX_UPLOAD_SIZE)),
+        )
         .route("/layers", get(list_layers))
 }
 
@@ -56,6 +67,52 @@ async fn get_config() -> Result<Json<Value>, DaemonError> {
     })))
 }
 
+/// GET /api/config/ids - List modifier/lock/layer IDs of the compiled config
+async fn get_config_ids() -> Result<Json<Value>, DaemonError> {
+    use crate::error::ConfigError;
+
+    let config_dir = get_config_dir()?;
+    let active_profile = query_active_profile().unwrap_or_else(|| "default".to_string());
+
+    let krx_path = config_dir
+        .join("profiles")
+        .join(format!("{}.krx", active_profile));
+
+    if !krx_path.exists() {
+        return Err(ConfigError::FileNotFound { path: krx_path }.into());
+    }
+
+    let config = crate::config_loader::read_krx(&krx_path)?;
+    let ids = keyrx_compiler::ids::config_ids(&config);
+
+    Ok(Json(json!({
+        "profile": active_profile,
+        "ids": ids.ids,
+    })))
+}
+
+/// POST /api/config/upload - Verify an uploaded .krx file
+///
+/// The request body is the raw .krx file. It gets the same checks as
+/// `keyrx_compiler verify --deep`; a file that fails them is answered with
+/// 400 and the fault. Bodies over 10MB are refused with 413.
+async fn upload_config(body: Bytes) -> Result<Json<Value>, ApiError> {
+    use keyrx_compiler::cli::verify::verify_deep;
+
+    let size = body.len();
+    // Deep verification walks the whole archive, so keep it off the async workers
+    let summary = tokio::task::spawn_blocking(move || verify_deep(&body))
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Verification task failed: {}", e)))?
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    Ok(Json(json!({
+        "valid": true,
+        "size": size,
+        "summary": summary,
+    })))
+}
+
 /// POST /api/config/key-mappings - Set key mapping
 #[derive(Deserialize)]
 struct SetKeyMappingRequest {
@@ -297,7 +354,7 @@ fn query_active_profile() -> Option<String> {
             running: _,
             uptime_secs: _,
             active_profile,
-            device_count: _,
+            ..
         } => active_profile,
         _ => None,
     }
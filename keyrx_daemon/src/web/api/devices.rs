@@ -33,6 +33,15 @@ struct DeviceResponse {
     serial: Option<String>,
     active: bool,
     layout: Option<String>,
+    /// Device emits key events (absent if the platform cannot report it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supports_keys: Option<bool>,
+    /// Number of distinct keys the device reports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_count: Option<usize>,
+    /// Device emits relative axis events (mouse/scroll)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    supports_rel: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -62,7 +71,8 @@ async fn list_devices() -> Result<Json<DevicesListResponse>, DaemonError> {
         .into_iter()
         .map(|kb| {
             let id = kb.device_id();
-            let registry_entry = registry.get(&id);
+            // Fall back to the serial so metadata survives a path change
+            let registry_entry = registry.find(&id, kb.serial.as_deref());
 
             DeviceResponse {
                 id: id.clone(),
@@ -73,6 +83,9 @@ async fn list_devices() -> Result<Json<DevicesListResponse>, DaemonError> {
                 serial: kb.serial,
                 active: true,
                 layout: registry_entry.and_then(|e| e.layout.clone()),
+                supports_keys: kb.capabilities.map(|c| c.supports_keys),
+                key_count: kb.capabilities.map(|c| c.key_count),
+                supports_rel: kb.capabilities.map(|c| c.supports_rel),
             }
         })
         .collect();
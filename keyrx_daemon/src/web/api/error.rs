@@ -28,6 +28,9 @@ pub enum ApiError {
     /// Invalid request parameters (400 BAD_REQUEST)
     BadRequest(String),
 
+    /// Missing or invalid API token (401 UNAUTHORIZED)
+    Unauthorized(String),
+
     /// Resource conflict (409 CONFLICT)
     Conflict(String),
 
@@ -55,6 +58,7 @@ impl IntoResponse for ApiError {
         let (status, code, message) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "NOT_FOUND", msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "BAD_REQUEST", msg),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED", msg),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "CONFLICT", msg),
             ApiError::InternalError(msg) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR", msg)
@@ -1,14 +1,16 @@
 //! Macro recorder endpoints.
 
 use axum::{
-    extract::State,
+    extract::{Query, State},
     routing::{get, post},
     Json, Router,
 };
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::sync::Arc;
 
 use super::error::ApiError;
+use crate::macro_recorder::DEFAULT_MIN_DELAY_MS;
 use crate::web::AppState;
 
 pub fn routes() -> Router<Arc<AppState>> {
@@ -17,6 +19,7 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/macros/stop-recording", post(stop_macro_recording))
         .route("/macros/recorded-events", get(get_recorded_events))
         .route("/macros/clear", post(clear_recorded_events))
+        .route("/macros/snippet", get(get_macro_snippet))
 }
 
 /// POST /api/macros/start-recording - Start recording macro
@@ -81,3 +84,30 @@ async fn clear_recorded_events(
         "message": "Events cleared"
     })))
 }
+
+#[derive(Deserialize)]
+struct MacroSnippetQuery {
+    /// Key to bind the macro to (e.g. "VK_F13")
+    key: String,
+    /// Shortest gap kept as a wait step
+    min_delay_ms: Option<u16>,
+}
+
+/// GET /api/macros/snippet - Render the recorded events as a macro() mapping
+async fn get_macro_snippet(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MacroSnippetQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let snippet = state
+        .macro_recorder
+        .to_macro_snippet(
+            &params.key,
+            params.min_delay_ms.unwrap_or(DEFAULT_MIN_DELAY_MS),
+        )
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    Ok(Json(json!({
+        "success": true,
+        "snippet": snippet
+    })))
+}
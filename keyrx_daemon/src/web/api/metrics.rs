@@ -70,7 +70,10 @@ struct StatusResponse {
     daemon_running: bool,
     uptime_secs: Option<u64>,
     active_profile: Option<String>,
+    /// Devices matched by the configuration
     device_count: Option<usize>,
+    /// Matched devices actually being captured
+    active_device_count: Option<usize>,
 }
 
 async fn get_status(
@@ -92,27 +95,36 @@ async fn get_status(
         })
         .await;
 
-        let (daemon_running, uptime_secs, active_profile, device_count) = match result {
-            Ok(Ok(Ok(IpcResponse::Status {
-                running,
-                uptime_secs: uptime,
-                active_profile: profile,
-                device_count: count,
-            }))) => (running, Some(uptime), profile, Some(count)),
-            Ok(Ok(Err(e))) => {
-                log::warn!("IPC error querying daemon status: {}", e);
-                (false, None, None, None)
-            }
-            Ok(Err(e)) => {
-                log::warn!("Failed to join IPC task: {}", e);
-                (false, None, None, None)
-            }
-            Err(_) => {
-                log::warn!("IPC timeout querying daemon status");
-                (false, None, None, None)
-            }
-            _ => (false, None, None, None),
-        };
+        let (daemon_running, uptime_secs, active_profile, device_count, active_device_count) =
+            match result {
+                Ok(Ok(Ok(IpcResponse::Status {
+                    running,
+                    uptime_secs: uptime,
+                    active_profile: profile,
+                    device_count: count,
+                    active_device_count: active_count,
+                    ..
+                }))) => (
+                    running,
+                    Some(uptime),
+                    profile,
+                    Some(count),
+                    Some(active_count),
+                ),
+                Ok(Ok(Err(e))) => {
+                    log::warn!("IPC error querying daemon status: {}", e);
+                    (false, None, None, None, None)
+                }
+                Ok(Err(e)) => {
+                    log::warn!("Failed to join IPC task: {}", e);
+                    (false, None, None, None, None)
+                }
+                Err(_) => {
+                    log::warn!("IPC timeout querying daemon status");
+                    (false, None, None, None, None)
+                }
+                _ => (false, None, None, None, None),
+            };
 
         Ok(Json(StatusResponse {
             status: "running".to_string(),
@@ -121,15 +133,23 @@ async fn get_status(
             uptime_secs,
             active_profile,
             device_count,
+            active_device_count,
         }))
     } else {
         // Production mode: try to query daemon via IPC
         let daemon_info = query_daemon_status();
 
-        let (daemon_running, uptime_secs, active_profile, device_count) = match daemon_info {
-            Ok((uptime, profile, count)) => (true, Some(uptime), profile, Some(count)),
-            Err(_) => (false, None, None, None),
-        };
+        let (daemon_running, uptime_secs, active_profile, device_count, active_device_count) =
+            match daemon_info {
+                Ok(snapshot) => (
+                    true,
+                    Some(snapshot.uptime_secs),
+                    snapshot.active_profile,
+                    Some(snapshot.device_count),
+                    Some(snapshot.active_device_count),
+                ),
+                Err(_) => (false, None, None, None, None),
+            };
 
         Ok(Json(StatusResponse {
             status: "running".to_string(),
@@ -138,6 +158,7 @@ async fn get_status(
             uptime_secs,
             active_profile,
             device_count,
+            active_device_count,
         }))
     }
 }
@@ -206,9 +227,10 @@ async fn get_event_log(Query(params): Query<EventLogQuery>) -> Result<Json<Value
         .map_err(|_| SocketError::NotConnected)?;
 
     match response {
-        IpcResponse::Events { events } => Ok(Json(json!({
+        IpcResponse::Events { events, last_id } => Ok(Json(json!({
             "count": events.len(),
             "events": events,
+            "last_id": last_id,
         }))),
         IpcResponse::Error { code, message } => Err(WebError::InvalidRequest {
             reason: format!("Daemon error {}: {}", code, message),
@@ -257,7 +279,7 @@ async fn get_daemon_state() -> Result<Json<DaemonStateResponse>, DaemonError> {
         .map_err(|_| SocketError::NotConnected)?;
 
     match response {
-        IpcResponse::State { state } => {
+        IpcResponse::State { state, .. } => {
             // Parse the 255-bit state vector
             // Note: The exact bit layout depends on keyrx_core's ExtendedState structure
             // For now, we provide the raw state and basic analysis
@@ -328,8 +350,20 @@ async fn get_daemon_state() -> Result<Json<DaemonStateResponse>, DaemonError> {
     }
 }
 
+/// Daemon status fields reported by the `GetStatus` IPC request.
+struct DaemonStatusSnapshot {
+    /// Seconds since the daemon started.
+    uptime_secs: u64,
+    /// Name of the active profile, if one is loaded.
+    active_profile: Option<String>,
+    /// Devices matched by the configuration.
+    device_count: usize,
+    /// Matched devices actually being captured (grabbed).
+    active_device_count: usize,
+}
+
 /// Query daemon status via IPC
-fn query_daemon_status() -> Result<(u64, Option<String>, usize), Box<dyn std::error::Error>> {
+fn query_daemon_status() -> Result<DaemonStatusSnapshot, Box<dyn std::error::Error>> {
     let socket_path = std::path::PathBuf::from(DEFAULT_SOCKET_PATH);
     let mut ipc = crate::ipc::unix_socket::UnixSocketIpc::new(socket_path);
 
@@ -341,7 +375,14 @@ fn query_daemon_status() -> Result<(u64, Option<String>, usize), Box<dyn std::er
             uptime_secs,
             active_profile,
             device_count,
-        } => Ok((uptime_secs, active_profile, device_count)),
+            active_device_count,
+            ..
+        } => Ok(DaemonStatusSnapshot {
+            uptime_secs,
+            active_profile,
+            device_count,
+            active_device_count,
+        }),
         _ => Err("Unexpected response from daemon".into()),
     }
 }
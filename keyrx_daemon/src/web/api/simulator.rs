@@ -1,19 +1,22 @@
 //! Simulator endpoints.
 
 use axum::{extract::State, routing::post, Json, Router};
+use keyrx_core::simulator::{EventSequence as CoreEventSequence, SimKeyEvent};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
 
 use super::error::ApiError;
 use crate::config::simulation_engine::{
-    EventSequence, EventType, ScenarioResult, SimulatedEvent, SimulationError,
+    simulate_source, EventSequence, EventType, ScenarioResult, SimulatedEvent, SimulationError,
+    SimulationResult,
 };
 use crate::web::AppState;
 
 pub fn routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/simulator/events", post(simulate_events))
+        .route("/simulator/run", post(run_source))
         .route("/simulator/reset", post(reset_simulator))
         .route("/simulator/load-profile", post(load_profile))
         .route("/simulator/scenarios/all", post(run_all_scenarios))
@@ -37,6 +40,10 @@ impl From<SimulationError> for ApiError {
                 ApiError::BadRequest(format!("Invalid timestamp: {}", ts))
             }
             SimulationError::InvalidEventFile(msg) => ApiError::BadRequest(msg),
+            SimulationError::CompileError(msg) => ApiError::BadRequest(msg),
+            SimulationError::NotReproduced => {
+                ApiError::BadRequest("Event sequence does not reproduce the failure".to_string())
+            }
             SimulationError::MemoryLimitExceeded => {
                 ApiError::InternalError("Memory limit exceeded".to_string())
             }
@@ -64,12 +71,22 @@ struct SimulateEventsRequest {
     seed: Option<u64>,
 }
 
+#[derive(Deserialize)]
+struct RunSourceRequest {
+    /// Rhai configuration source (need not be saved as a profile)
+    source: String,
+    /// Input events in the shared simulator format
+    events: Vec<SimKeyEvent>,
+    /// Seed of the latency model, echoed in the result
+    seed: Option<u64>,
+}
+
 /// Output event in API response format
 #[derive(Serialize)]
 struct OutputEventResponse {
     /// Key identifier
     key: String,
-    /// Event type: "press" or "release"
+    /// Event type: "press", "release", or "repeat"
     event_type: String,
     /// Timestamp in microseconds
     timestamp_us: u64,
@@ -82,6 +99,14 @@ struct SimulateEventsResponse {
     outputs: Vec<OutputEventResponse>,
 }
 
+#[derive(Serialize)]
+struct RunSourceResponse {
+    success: bool,
+    /// Timeline, latency statistics, and final state
+    #[serde(flatten)]
+    result: SimulationResult,
+}
+
 #[derive(Serialize)]
 struct AllScenariosResponse {
     success: bool,
@@ -143,6 +168,7 @@ async fn simulate_events(
             event_type: match e.event_type {
                 EventType::Press => "press".to_string(),
                 EventType::Release => "release".to_string(),
+                EventType::Repeat => "repeat".to_string(),
             },
             timestamp_us: e.timestamp_us,
         })
@@ -154,6 +180,25 @@ async fn simulate_events(
     }))
 }
 
+/// POST /api/simulator/run - Compile Rhai source and simulate events against it
+async fn run_source(
+    Json(payload): Json<RunSourceRequest>,
+) -> Result<Json<RunSourceResponse>, ApiError> {
+    let sequence = CoreEventSequence {
+        events: payload.events,
+        seed: payload.seed.unwrap_or(0),
+    };
+    // Compiling can take a while, so keep it off the async workers
+    let result = tokio::task::spawn_blocking(move || simulate_source(&payload.source, &sequence))
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Simulation task failed: {}", e)))??;
+
+    Ok(Json(RunSourceResponse {
+        success: true,
+        result,
+    }))
+}
+
 /// POST /api/simulator/scenarios/all - Run all built-in scenarios
 async fn run_all_scenarios(
     State(state): State<Arc<AppState>>,
@@ -0,0 +1,118 @@
+//! Bearer-token authentication for the web API and WebSocket endpoints.
+//!
+//! When [`AppState::api_token`] is set, every `/api` request must carry an
+//! `Authorization: Bearer <token>` header. Browsers cannot attach headers to a
+//! WebSocket upgrade, so `/ws` and `/ws-rpc` take the same token as a
+//! `?token=<token>` query parameter instead. Static UI assets stay public so
+//! the page can load and read the token from its own URL.
+
+use axum::{
+    extract::{Request, State},
+    http::header::AUTHORIZATION,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::web::api::error::ApiError;
+use crate::web::AppState;
+
+/// Query parameter carrying the token on WebSocket upgrade requests
+pub const TOKEN_QUERY_PARAM: &str = "token";
+
+/// Number of random bytes in a generated token (hex-encoded to 64 chars)
+const TOKEN_BYTES: usize = 32;
+
+/// Generates a new random API token.
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate API token: {}", e))?;
+    Ok(hex::encode(bytes))
+}
+
+/// Middleware requiring `Authorization: Bearer <token>` on every request.
+pub async fn require_bearer_token(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = state.api_token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let provided = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if tokens_match(token, expected) => next.run(request).await,
+        Some(_) => unauthorized("Invalid API token"),
+        None => unauthorized("Missing Authorization: Bearer token"),
+    }
+}
+
+/// Middleware requiring `?token=<token>` on WebSocket upgrade requests.
+pub async fn require_query_token(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = state.api_token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let provided = request.uri().query().and_then(|query| {
+        query.split('&').find_map(|pair| {
+            pair.strip_prefix(TOKEN_QUERY_PARAM)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+    });
+
+    match provided {
+        Some(token) if tokens_match(token, expected) => next.run(request).await,
+        Some(_) => unauthorized("Invalid API token"),
+        None => unauthorized("Missing token query parameter"),
+    }
+}
+
+fn unauthorized(message: &str) -> Response {
+    ApiError::Unauthorized(message.to_string()).into_response()
+}
+
+/// Compares tokens without short-circuiting on the first differing byte.
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let (provided, expected) = (provided.as_bytes(), expected.as_bytes());
+    if provided.len() != expected.len() {
+        return false;
+    }
+    provided
+        .iter()
+        .zip(expected)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_token_is_random_hex() {
+        let first = generate_token().unwrap();
+        let second = generate_token().unwrap();
+
+        assert_eq!(first.len(), TOKEN_BYTES * 2);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc123", "abc123"));
+        assert!(!tokens_match("abc124", "abc123"));
+        assert!(!tokens_match("abc12", "abc123"));
+        assert!(!tokens_match("", "abc123"));
+    }
+}
@@ -315,6 +315,11 @@ impl IntoResponse for DaemonError {
                     "MUTEX_POISONED",
                     "Internal synchronization error occurred".to_string(),
                 ),
+                RecorderError::InvalidMacro(msg) => (
+                    StatusCode::BAD_REQUEST,
+                    "INVALID_MACRO",
+                    format!("Invalid macro: {}", msg),
+                ),
             },
 
             // Core errors - core library failures (500 Internal Server Error)
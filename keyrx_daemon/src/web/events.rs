@@ -4,6 +4,7 @@
 //! to connected WebSocket clients for real-time monitoring.
 
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
 use typeshare::typeshare;
 
 /// Events broadcast from the daemon to WebSocket clients.
@@ -22,6 +23,10 @@ pub enum DaemonEvent {
     /// Latency statistics update.
     #[serde(rename = "latency")]
     Latency(LatencyStats),
+
+    /// Full state sent once to each client when it connects.
+    #[serde(rename = "snapshot")]
+    Snapshot(DaemonSnapshot),
 }
 
 /// Current daemon state snapshot.
@@ -42,6 +47,62 @@ pub struct DaemonState {
     pub active_profile: Option<String>,
 }
 
+/// State snapshot sent to a WebSocket client before any live events.
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonSnapshot {
+    /// Currently active profile name (if any).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+
+    /// Connected input devices.
+    pub devices: Vec<SnapshotDevice>,
+
+    /// Active modifier IDs (e.g., ["MD_00", "MD_01"]).
+    pub modifiers: Vec<String>,
+
+    /// Active lock IDs (e.g., ["LK_00"]).
+    pub locks: Vec<String>,
+
+    /// Current active layer name.
+    pub layer: String,
+}
+
+/// Connected device entry in a [`DaemonSnapshot`].
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDevice {
+    /// Unique device identifier.
+    pub id: String,
+
+    /// Device name (user-assigned or reported by the device).
+    pub name: String,
+
+    /// Device path (e.g., "/dev/input/event3").
+    pub path: String,
+
+    /// Whether the device is currently being remapped.
+    pub active: bool,
+}
+
+/// Last [`DaemonState`] broadcast, kept for clients that connect later.
+#[derive(Debug, Clone, Default)]
+pub struct StateCache(Arc<RwLock<Option<DaemonState>>>);
+
+impl StateCache {
+    /// Replaces the cached state.
+    pub fn store(&self, state: DaemonState) {
+        if let Ok(mut cached) = self.0.write() {
+            *cached = Some(state);
+        }
+    }
+
+    /// Returns the cached state, or `None` if nothing was broadcast yet.
+    pub fn load(&self) -> Option<DaemonState> {
+        self.0.read().ok().and_then(|cached| cached.clone())
+    }
+}
+
 /// Individual key event data.
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,13 +140,48 @@ pub struct KeyEventData {
     pub device_name: Option<String>,
 
     /// Mapping type applied (e.g., "simple", "tap_hold", "layer_switch").
+    ///
+    /// Kind of `matched_mapping`, so absent whenever that is.
     #[serde(rename = "mappingType")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mapping_type: Option<String>,
 
-    /// Whether a mapping was triggered for this event.
+    /// Whether remapping changed this event, i.e. the output differs from
+    /// the input.
     #[serde(rename = "mappingTriggered")]
     pub mapping_triggered: bool,
+
+    /// Mapping that produced the outputs, so the UI can highlight it.
+    ///
+    /// Absent for unmapped keys and for releases that undo a remapped press.
+    #[serde(rename = "matchedMapping")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_mapping: Option<MatchedMappingData>,
+}
+
+/// Position and kind of the mapping that handled a key event.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchedMappingData {
+    /// Index into the device's mappings.
+    pub index: u32,
+
+    /// Index within the conditional block, if the mapping is inside one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner: Option<u32>,
+
+    /// Mapping kind (e.g., "simple", "tap_hold").
+    pub kind: String,
+}
+
+impl From<keyrx_core::runtime::MatchedMapping> for MatchedMappingData {
+    fn from(matched: keyrx_core::runtime::MatchedMapping) -> Self {
+        Self {
+            index: matched.position.index as u32,
+            inner: matched.position.inner.map(|inner| inner as u32),
+            kind: matched.kind.as_str().to_string(),
+        }
+    }
 }
 
 /// Latency statistics.
@@ -29,7 +29,7 @@ struct UpdateConfigParams {
 struct SetKeyMappingParams {
     layer: String,
     key: String,
-    action_type: String, // "simple", "tap_hold", "macro"
+    action_type: String, // "simple", "tap_hold", "disable", "macro"
     // For simple remap
     output: Option<String>,
     // For tap-hold
@@ -145,11 +145,12 @@ pub async fn set_key_mapping(
                 threshold_ms,
             }
         }
+        "disable" => KeyAction::Disable,
         _ => {
             return Err(RpcError::new(
                 INVALID_PARAMS,
                 format!(
-                    "Unsupported action type: {}. Use 'simple' or 'tap_hold'",
+                    "Unsupported action type: {}. Use 'simple', 'tap_hold' or 'disable'",
                     params.action_type
                 ),
             ))
@@ -1,4 +1,5 @@
 pub mod api;
+pub mod auth;
 pub mod error;
 pub mod events;
 pub mod handlers;
@@ -11,7 +12,7 @@ pub mod ws_rpc;
 #[cfg(test)]
 mod ws_test;
 
-use axum::Router;
+use axum::{middleware, Router};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::broadcast;
@@ -23,6 +24,7 @@ use crate::macro_recorder::MacroRecorder;
 use crate::services::{
     ConfigService, DeviceService, ProfileService, SettingsService, SimulationService,
 };
+use crate::web::events::StateCache;
 use crate::web::subscriptions::SubscriptionManager;
 
 use crate::web::rpc_types::ServerMessage;
@@ -52,6 +54,10 @@ pub struct AppState {
     pub event_broadcaster: broadcast::Sender<ServerMessage>,
     /// Test mode IPC socket path (None in production mode)
     pub test_mode_socket: Option<std::path::PathBuf>,
+    /// Token required by `/api` and `/ws` requests (None disables auth)
+    pub api_token: Option<String>,
+    /// Last daemon state, sent to `/ws` clients when they connect
+    pub state_cache: StateCache,
 }
 
 impl AppState {
@@ -77,6 +83,8 @@ impl AppState {
             subscription_manager,
             event_broadcaster,
             test_mode_socket: None,
+            api_token: None,
+            state_cache: StateCache::default(),
         }
     }
 
@@ -103,8 +111,22 @@ impl AppState {
             subscription_manager,
             event_broadcaster,
             test_mode_socket: Some(test_mode_socket),
+            api_token: None,
+            state_cache: StateCache::default(),
         }
     }
+
+    /// Requires `token` on all API and WebSocket requests
+    pub fn with_api_token(mut self, token: String) -> Self {
+        self.api_token = Some(token);
+        self
+    }
+
+    /// Reads the modifier/lock state for `/ws` snapshots from `cache`
+    pub fn with_state_cache(mut self, cache: StateCache) -> Self {
+        self.state_cache = cache;
+        self
+    }
 }
 
 #[allow(dead_code)]
@@ -116,22 +138,152 @@ pub async fn create_app(event_tx: broadcast::Sender<DaemonEvent>, state: Arc<App
         .allow_methods(Any)
         .allow_headers(Any);
 
+    let bearer_auth =
+        middleware::from_fn_with_state(Arc::clone(&state), auth::require_bearer_token);
+    let query_auth = middleware::from_fn_with_state(Arc::clone(&state), auth::require_query_token);
+
     Router::new()
-        .nest("/api", api::create_router(Arc::clone(&state)))
-        .nest("/ws", ws::create_router(event_tx))
-        .nest("/ws-rpc", ws_rpc::create_router(Arc::clone(&state)))
+        .nest(
+            "/api",
+            api::create_router(Arc::clone(&state)).layer(bearer_auth),
+        )
+        .nest(
+            "/ws",
+            ws::create_router(event_tx, Arc::clone(&state)).layer(query_auth.clone()),
+        )
+        .nest(
+            "/ws-rpc",
+            ws_rpc::create_router(Arc::clone(&state)).layer(query_auth),
+        )
         .fallback_service(static_files::serve_static())
         .layer(cors)
 }
 
+/// Checks that the web server may listen on `addr`.
+///
+/// Loopback addresses are always allowed. Any other address (including
+/// `0.0.0.0`) exposes the API to the network, so it needs an explicit
+/// `--web-allow-remote` and API token authentication.
+pub fn check_bind_addr(
+    addr: SocketAddr,
+    allow_remote: bool,
+    auth_enabled: bool,
+) -> Result<(), String> {
+    if addr.ip().is_loopback() {
+        return Ok(());
+    }
+    if !allow_remote {
+        return Err(format!(
+            "Refusing to bind the web server to {}: it would be reachable from other machines. \
+             Pass --web-allow-remote to allow this.",
+            addr
+        ));
+    }
+    if !auth_enabled {
+        return Err(format!(
+            "Refusing to bind the web server to {}: remote access requires API token \
+             authentication, which is disabled in test mode.",
+            addr
+        ));
+    }
+    Ok(())
+}
+
+/// Binds the web server's listening socket.
+///
+/// If the port of `addr` is taken, up to `retries` following ports are
+/// tried in turn. Other bind errors are returned immediately. Use
+/// `local_addr()` on the result to find the port actually bound.
+pub fn bind_listener(addr: SocketAddr, retries: u16) -> std::io::Result<std::net::TcpListener> {
+    let mut candidate = addr;
+    let mut attempt = 0;
+    loop {
+        match std::net::TcpListener::bind(candidate) {
+            Ok(listener) => {
+                if candidate.port() != addr.port() {
+                    log::warn!(
+                        "Web port {} is in use, bound {} instead",
+                        addr.port(),
+                        candidate
+                    );
+                }
+                listener.set_nonblocking(true)?;
+                return Ok(listener);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempt < retries => {
+                let Some(next) = candidate.port().checked_add(1) else {
+                    return Err(e);
+                };
+                log::debug!("Web port {} is in use, trying {}", candidate.port(), next);
+                candidate.set_port(next);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 #[allow(dead_code)]
 pub async fn serve(
     addr: SocketAddr,
     event_tx: broadcast::Sender<DaemonEvent>,
     state: Arc<AppState>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    serve_listener(bind_listener(addr, 0)?, event_tx, state).await
+}
+
+/// Serves the web API on a listener from [`bind_listener`].
+pub async fn serve_listener(
+    listener: std::net::TcpListener,
+    event_tx: broadcast::Sender<DaemonEvent>,
+    state: Arc<AppState>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let app = create_app(event_tx, state).await;
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
     axum::serve(listener, app).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_bind_addr_allows_loopback() {
+        let v4: SocketAddr = "127.0.0.1:9867".parse().unwrap();
+        let v6: SocketAddr = "[::1]:9867".parse().unwrap();
+        assert!(check_bind_addr(v4, false, false).is_ok());
+        assert!(check_bind_addr(v6, false, false).is_ok());
+    }
+
+    #[test]
+    fn test_check_bind_addr_remote_requires_opt_in_and_auth() {
+        let any: SocketAddr = "0.0.0.0:9867".parse().unwrap();
+
+        let err = check_bind_addr(any, false, true).unwrap_err();
+        assert!(err.contains("--web-allow-remote"));
+
+        let err = check_bind_addr(any, true, false).unwrap_err();
+        assert!(err.contains("authentication"));
+
+        assert!(check_bind_addr(any, true, true).is_ok());
+
+        let lan: SocketAddr = "192.168.1.10:9867".parse().unwrap();
+        assert!(check_bind_addr(lan, false, true).is_err());
+    }
+
+    #[test]
+    fn test_bind_listener_retries_taken_port() {
+        let taken = bind_listener("127.0.0.1:0".parse().unwrap(), 0).unwrap();
+        let taken_addr = taken.local_addr().unwrap();
+
+        let err = bind_listener(taken_addr, 0).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+        // The next port may be taken by something else too, so allow a few
+        match bind_listener(taken_addr, 8) {
+            Ok(listener) => assert!(listener.local_addr().unwrap().port() > taken_addr.port()),
+            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::AddrInUse),
+        }
+    }
+}
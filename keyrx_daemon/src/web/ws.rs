@@ -1,7 +1,9 @@
 //! WebSocket endpoint for real-time event streaming.
 //!
 //! This module provides a WebSocket endpoint at /ws/events that streams
-//! real-time events from the daemon to connected web clients.
+//! real-time events from the daemon to connected web clients. Each client
+//! first receives a `snapshot` event with the current state, so a freshly
+//! loaded page is correct without waiting for the next change.
 
 use axum::{
     extract::{
@@ -13,31 +15,83 @@ use axum::{
     Router,
 };
 use serde_json::json;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio::time::{interval, Duration};
 
-use crate::web::events::DaemonEvent;
+use crate::web::events::{DaemonEvent, DaemonSnapshot, DaemonState, SnapshotDevice};
+use crate::web::AppState;
 
-pub fn create_router(event_tx: broadcast::Sender<DaemonEvent>) -> Router {
+/// Shared state for the WebSocket endpoint
+#[derive(Clone)]
+struct WsState {
+    event_tx: broadcast::Sender<DaemonEvent>,
+    app_state: Arc<AppState>,
+}
+
+pub fn create_router(event_tx: broadcast::Sender<DaemonEvent>, app_state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(websocket_handler))
-        .with_state(event_tx)
+        .with_state(WsState {
+            event_tx,
+            app_state,
+        })
 }
 
 /// WebSocket upgrade handler
 async fn websocket_handler(
     ws: WebSocketUpgrade,
-    State(event_tx): State<broadcast::Sender<DaemonEvent>>,
+    State(state): State<WsState>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(socket, event_tx))
+    ws.on_upgrade(move |socket| handle_websocket(socket, state))
+}
+
+/// Build the snapshot sent to a client before any live events
+///
+/// Uses the same sources as the IPC `GetStatus`/`GetState` queries: the
+/// profile manager for the active profile, device enumeration for the device
+/// list, and the last broadcast state for modifiers and locks.
+async fn build_snapshot(app_state: &AppState) -> DaemonSnapshot {
+    let active_profile = app_state.profile_service.get_active_profile().await;
+
+    let devices = match app_state.device_service.list_devices().await {
+        Ok(devices) => devices
+            .into_iter()
+            .map(|device| SnapshotDevice {
+                id: device.id,
+                name: device.name,
+                path: device.path,
+                active: device.active,
+            })
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to list devices for WebSocket snapshot: {}", e);
+            Vec::new()
+        }
+    };
+
+    let state = app_state.state_cache.load().unwrap_or_else(|| DaemonState {
+        modifiers: Vec::new(),
+        locks: Vec::new(),
+        layer: "base".to_string(),
+        active_profile: None,
+    });
+
+    DaemonSnapshot {
+        active_profile,
+        devices,
+        modifiers: state.modifiers,
+        locks: state.locks,
+        layer: state.layer,
+    }
 }
 
 /// Handle WebSocket connection
-async fn handle_websocket(mut socket: WebSocket, event_tx: broadcast::Sender<DaemonEvent>) {
+async fn handle_websocket(mut socket: WebSocket, state: WsState) {
     log::info!("WebSocket client connected");
 
-    // Subscribe to daemon events
-    let mut event_rx = event_tx.subscribe();
+    // Subscribe before taking the snapshot so no change in between is lost
+    let mut event_rx = state.event_tx.subscribe();
 
     // Send welcome message
     let welcome = json!({
@@ -60,6 +114,18 @@ async fn handle_websocket(mut socket: WebSocket, event_tx: broadcast::Sender<Dae
         return;
     }
 
+    // Send current state before streaming live events
+    let snapshot = DaemonEvent::Snapshot(build_snapshot(&state.app_state).await);
+    match serde_json::to_string(&snapshot) {
+        Ok(json_msg) => {
+            if socket.send(Message::Text(json_msg)).await.is_err() {
+                log::warn!("Failed to send state snapshot");
+                return;
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize state snapshot: {}", e),
+    }
+
     // Send periodic heartbeat messages
     let mut heartbeat_interval = interval(Duration::from_secs(30));
 
@@ -239,10 +305,65 @@ async fn handle_websocket(mut socket: WebSocket, event_tx: broadcast::Sender<Dae
 mod tests {
     use super::*;
 
+    use crate::config::ProfileManager;
+    use crate::macro_recorder::MacroRecorder;
+    use crate::services::{
+        ConfigService, DeviceService, ProfileService, SettingsService, SimulationService,
+    };
+    use crate::web::subscriptions::SubscriptionManager;
+    use tempfile::TempDir;
+
+    fn test_app_state(config_dir: &std::path::Path) -> AppState {
+        let config_dir = config_dir.to_path_buf();
+        let profile_manager = Arc::new(ProfileManager::new(config_dir.clone()).unwrap());
+        let (rpc_event_tx, _) = broadcast::channel(100);
+        AppState::new(
+            Arc::new(MacroRecorder::new()),
+            Arc::new(ProfileService::new(Arc::clone(&profile_manager))),
+            Arc::new(DeviceService::new(config_dir.clone())),
+            Arc::new(ConfigService::new(profile_manager)),
+            Arc::new(SettingsService::new(config_dir.clone())),
+            Arc::new(SimulationService::new(config_dir, None)),
+            Arc::new(SubscriptionManager::new()),
+            rpc_event_tx,
+        )
+    }
+
     #[test]
     fn test_create_router() {
+        let temp_dir = TempDir::new().unwrap();
         let (event_tx, _) = broadcast::channel(100);
-        let router = create_router(event_tx);
+        let router = create_router(event_tx, Arc::new(test_app_state(temp_dir.path())));
         assert!(std::mem::size_of_val(&router) > 0);
     }
+
+    #[tokio::test]
+    async fn test_build_snapshot_defaults_without_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let snapshot = build_snapshot(&test_app_state(temp_dir.path())).await;
+
+        assert!(snapshot.active_profile.is_none());
+        assert!(snapshot.modifiers.is_empty());
+        assert!(snapshot.locks.is_empty());
+        assert_eq!(snapshot.layer, "base");
+    }
+
+    #[tokio::test]
+    async fn test_build_snapshot_uses_cached_state() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = crate::web::events::StateCache::default();
+        cache.store(DaemonState {
+            modifiers: vec!["MD_00".to_string()],
+            locks: vec!["LK_02".to_string()],
+            layer: "nav".to_string(),
+            active_profile: None,
+        });
+        let app_state = test_app_state(temp_dir.path()).with_state_cache(cache);
+
+        let snapshot = build_snapshot(&app_state).await;
+
+        assert_eq!(snapshot.modifiers, vec!["MD_00"]);
+        assert_eq!(snapshot.locks, vec!["LK_02"]);
+        assert_eq!(snapshot.layer, "nav");
+    }
 }
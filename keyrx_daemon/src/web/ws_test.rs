@@ -1,6 +1,6 @@
 //! Unit tests for WebSocket broadcasting functionality.
 
-use super::events::{DaemonEvent, DaemonState, KeyEventData, LatencyStats};
+use super::events::{DaemonEvent, DaemonState, KeyEventData, LatencyStats, MatchedMappingData};
 use tokio::sync::broadcast;
 
 #[tokio::test]
@@ -46,6 +46,7 @@ async fn test_broadcast_channel_publishes_key_event() {
         device_name: Some("USB Keyboard".to_string()),
         mapping_type: None,
         mapping_triggered: false,
+        matched_mapping: None,
     });
 
     event_tx.send(key_event.clone()).unwrap();
@@ -172,6 +173,11 @@ async fn test_event_serialization_key_event() {
         device_name: Some("Gaming Keyboard".to_string()),
         mapping_type: Some("simple".to_string()),
         mapping_triggered: true,
+        matched_mapping: Some(MatchedMappingData {
+            index: 2,
+            inner: Some(0),
+            kind: "simple".to_string(),
+        }),
     });
 
     let json = serde_json::to_string(&event).unwrap();
@@ -184,6 +190,10 @@ async fn test_event_serialization_key_event() {
     assert_eq!(parsed["payload"]["input"], "SPACE");
     assert_eq!(parsed["payload"]["output"], "ENTER");
     assert_eq!(parsed["payload"]["latency"], 3400);
+    assert_eq!(
+        parsed["payload"]["matchedMapping"],
+        serde_json::json!({"index": 2, "inner": 0, "kind": "simple"})
+    );
 }
 
 #[tokio::test]
@@ -230,6 +240,7 @@ async fn test_high_frequency_batching() {
                 device_name: None,
                 mapping_type: None,
                 mapping_triggered: false,
+                matched_mapping: None,
             }))
             .unwrap();
     }
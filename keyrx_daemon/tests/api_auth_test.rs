@@ -0,0 +1,83 @@
+//! Integration tests for web API token authentication.
+//!
+//! Tests verify that `/api` requires an `Authorization: Bearer` header and
+//! that WebSocket upgrades require the token as a query parameter.
+//!
+//! # Note on Serial Execution
+//!
+//! These tests use `#[serial]` because TestApp modifies the global HOME
+//! environment variable.
+
+mod common;
+
+use common::test_app::TestApp;
+use serial_test::serial;
+use tokio_tungstenite::connect_async;
+
+const TOKEN: &str = "0123456789abcdef";
+
+#[tokio::test]
+#[serial]
+async fn test_api_without_token_is_unauthorized() {
+    let app = TestApp::with_api_token(TOKEN).await;
+
+    let response = app.get("/api/profiles").await;
+    assert_eq!(response.status(), 401);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "UNAUTHORIZED");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_with_wrong_token_is_unauthorized() {
+    let app = TestApp::with_api_token(TOKEN).await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/profiles", app.base_url))
+        .bearer_auth("wrong-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 401);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_with_token_is_allowed() {
+    let app = TestApp::with_api_token(TOKEN).await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/profiles", app.base_url))
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_without_configured_token_is_open() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/profiles").await;
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_websocket_requires_token_query_param() {
+    let app = TestApp::with_api_token(TOKEN).await;
+    let ws_base = app.base_url.replace("http://", "ws://");
+
+    assert!(connect_async(format!("{}/ws-rpc", ws_base)).await.is_err());
+    assert!(connect_async(format!("{}/ws-rpc?token=wrong", ws_base))
+        .await
+        .is_err());
+
+    let (_stream, response) = connect_async(format!("{}/ws-rpc?token={}", ws_base, TOKEN))
+        .await
+        .expect("WebSocket upgrade with token should succeed");
+    assert_eq!(response.status(), 101);
+}
@@ -66,8 +66,11 @@ fn test_config_set_tap_hold() {
     cmd.env("KEYRX_CONFIG_DIR", &config_path).args(&[
         "config",
         "set-tap-hold",
+        "*",
         "VK_Space",
+        "--tap",
         "VK_Space",
+        "--hold-modifier",
         "MD_01",
         "--threshold",
         "250",
@@ -82,6 +85,79 @@ fn test_config_set_tap_hold() {
         .stdout(predicate::str::contains("\"key\":\"VK_Space\""));
 }
 
+#[test]
+fn test_config_set_tap_hold_updates_device_block_in_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let profiles = temp_dir.path().join("profiles");
+    fs::create_dir_all(&profiles).unwrap();
+    let profile_path = profiles.join("multi.rhai");
+    fs::write(
+        &profile_path,
+        r#"// Laptop keyboard
+device_start("Laptop");
+map("VK_A", "VK_B");
+device_end();
+
+device_start("*");
+// Thumb cluster
+tap_hold("Space", "VK_Space", "MD_00", 200);
+map("VK_C", "VK_D");
+device_end();
+"#,
+    )
+    .unwrap();
+    keyrx_compiler::compile_file(&profile_path, &profiles.join("multi.krx")).unwrap();
+
+    let mut cmd = Command::cargo_bin("keyrx_daemon").unwrap();
+    cmd.env("KEYRX_CONFIG_DIR", temp_dir.path()).args(&[
+        "config",
+        "set-tap-hold",
+        "*",
+        "Space",
+        "--tap",
+        "VK_Enter",
+        "--hold-modifier",
+        "01",
+        "--threshold",
+        "250",
+        "--profile",
+        "multi",
+    ]);
+    cmd.assert().success();
+
+    let updated = fs::read_to_string(&profile_path).unwrap();
+    assert!(updated.contains("// Laptop keyboard"));
+    assert!(updated.contains(r#"device_start("Laptop");"#));
+    assert!(
+        updated.contains("// Thumb cluster\ntap_hold(\"VK_Space\", \"VK_Enter\", \"MD_01\", 250);")
+    );
+    assert!(!updated.contains("MD_00"));
+    assert!(updated.contains(r#"map("VK_C", "VK_D");"#));
+}
+
+#[test]
+fn test_config_set_tap_hold_rejects_unknown_device() {
+    let (_temp, config_path) = setup_test_env();
+
+    let mut cmd = Command::cargo_bin("keyrx_daemon").unwrap();
+    cmd.env("KEYRX_CONFIG_DIR", &config_path).args(&[
+        "config",
+        "set-tap-hold",
+        "Missing",
+        "VK_Space",
+        "--tap",
+        "VK_Space",
+        "--hold-modifier",
+        "MD_01",
+        "--profile",
+        "test",
+    ]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Device block not found: Missing"));
+}
+
 // TODO: Implement macro support in Rhai parser
 // The set-macro CLI command generates Rhai code with press()/release()/wait() functions,
 // but these functions are not registered in the Rhai engine (keyrx_compiler/src/parser/).
@@ -243,3 +243,45 @@ fn test_max_custom_layouts() {
     let result = manager.import(&test_layout_path, "layout51");
     assert!(result.is_err());
 }
+
+#[test]
+fn test_export_builtin_layout_with_mappings() {
+    let (_temp_dir, layouts_dir) = setup_test_env();
+
+    use keyrx_core::config::{DeviceConfig, DeviceIdentifier, KeyCode, KeyMapping};
+    use keyrx_daemon::config::layout_manager::{export_kle, LayoutManager};
+    let manager = LayoutManager::new(layouts_dir).unwrap();
+    let layout = manager.get("ansi_104").unwrap();
+
+    let device = DeviceConfig {
+        identifier: DeviceIdentifier {
+            pattern: "*".to_string(),
+            excludes: Vec::new(),
+        },
+        mappings: vec![
+            KeyMapping::simple(KeyCode::CapsLock, KeyCode::LCtrl),
+            KeyMapping::simple(KeyCode::RCtrl, KeyCode::Menu),
+            KeyMapping::simple(KeyCode::Backslash, KeyCode::Backspace),
+        ],
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
+    };
+
+    let exported = export_kle(&layout.kle_json, &device).unwrap();
+    let legends: Vec<&str> = exported
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|row| row.as_array())
+        .flatten()
+        .filter_map(|key| key.as_str())
+        .collect();
+
+    assert!(legends.contains(&"LCtrl\nCaps Lock"));
+    assert!(legends.contains(&"Backspace\n| \\"));
+    // Only the right-hand Ctrl is remapped
+    assert_eq!(legends.iter().filter(|l| **l == "Ctrl").count(), 1);
+    assert!(legends.contains(&"Menu\nCtrl"));
+}
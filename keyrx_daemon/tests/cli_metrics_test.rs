@@ -45,6 +45,23 @@ fn test_metrics_events_daemon_not_running() {
         .stderr(predicate::str::contains("error code 3005"));
 }
 
+#[test]
+fn test_metrics_errors_daemon_not_running() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test-daemon.sock");
+
+    // Try to query error counters when daemon is not running
+    metrics_cmd()
+        .arg("metrics")
+        .arg("errors")
+        .arg("--socket")
+        .arg(&socket_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Daemon socket not found"))
+        .stderr(predicate::str::contains("error code 3005"));
+}
+
 #[test]
 fn test_metrics_latency_json_daemon_not_running() {
     let temp_dir = TempDir::new().unwrap();
@@ -110,11 +127,11 @@ fn test_metrics_events_custom_count() {
 }
 
 #[test]
-fn test_metrics_events_follow_not_implemented() {
+fn test_metrics_events_follow_without_daemon() {
     let temp_dir = TempDir::new().unwrap();
     let socket_path = temp_dir.path().join("test-daemon.sock");
 
-    // Follow mode should fail with "not implemented" message
+    // Follow mode needs a running daemon to poll
     metrics_cmd()
         .arg("metrics")
         .arg("events")
@@ -123,7 +140,7 @@ fn test_metrics_events_follow_not_implemented() {
         .arg(&socket_path)
         .assert()
         .failure()
-        .stderr(predicate::str::contains("Follow mode is not implemented"));
+        .stderr(predicate::str::contains("Daemon socket not found"));
 }
 
 #[test]
@@ -380,5 +380,22 @@ fn test_profiles_help() {
         .stdout(predicate::str::contains("delete"))
         .stdout(predicate::str::contains("duplicate"))
         .stdout(predicate::str::contains("export"))
-        .stdout(predicate::str::contains("import"));
+        .stdout(predicate::str::contains("import"))
+        .stdout(predicate::str::contains("validate-all"))
+        .stdout(predicate::str::contains("diff"));
+}
+
+#[test]
+fn test_profiles_diff_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+
+    profiles_cmd(&temp_dir)
+        .arg("profiles")
+        .arg("diff")
+        .arg("nonexistent-a")
+        .arg("nonexistent-b")
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("'nonexistent-a' not found"));
 }
@@ -4,7 +4,7 @@ use serde_json::Value;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
 /// Get the path to the keyrx_daemon binary.
@@ -286,3 +286,267 @@ fn test_simulate_conflicts_events_and_file() {
     // Should fail due to conflicting arguments
     assert!(!output.status.success());
 }
+
+#[test]
+fn test_simulate_shrink() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,release:A,press:CapsLock,wait:300,release:CapsLock,press:B,release:B")
+        .arg("--shrink")
+        .arg("Control")
+        .arg("--json")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Output is a replayable event file
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: Value = serde_json::from_str(&stdout).expect("Failed to parse JSON");
+    let events = json["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["key"], "CapsLock");
+    assert_eq!(events[1]["key"], "CapsLock");
+    assert_eq!(json["seed"], 0);
+}
+
+#[test]
+fn test_simulate_shrink_not_reproduced() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,release:A")
+        .arg("--shrink")
+        .arg("Control")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("nothing to shrink"));
+}
+
+#[test]
+fn test_simulate_assert_matches_golden() {
+    let (temp_dir, config_dir) = create_test_environment();
+    let golden = temp_dir.path().join("expected.json");
+    fs::write(
+        &golden,
+        r#"[
+            {"key": "A", "event_type": "press", "timestamp_us": 0},
+            {"key": "A", "event_type": "release", "timestamp_us": 50000}
+        ]"#,
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,wait:50,release:A")
+        .arg("--assert")
+        .arg(&golden)
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("matches golden file"));
+}
+
+#[test]
+fn test_simulate_assert_reports_first_divergence() {
+    let (temp_dir, config_dir) = create_test_environment();
+    let golden = temp_dir.path().join("expected.json");
+    fs::write(
+        &golden,
+        r#"[
+            {"key": "A", "event_type": "press", "timestamp_us": 0},
+            {"key": "B", "event_type": "release", "timestamp_us": 50000}
+        ]"#,
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,wait:50,release:A")
+        .arg("--assert")
+        .arg(&golden)
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("First divergence at index 1"));
+    assert!(stderr.contains("expected: [   50000 us] Release B"));
+    assert!(stderr.contains("actual:   [   50000 us] Release A"));
+    assert!(stderr.contains("Event assertion FAILED"));
+}
+
+#[test]
+fn test_simulate_replay_realtime_conflicts_with_assert() {
+    let (_temp_dir, config_dir) = create_test_environment();
+    let golden_path = create_event_file(config_dir.as_path(), "[]");
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--events")
+        .arg("press:A,wait:50,release:A")
+        .arg("--replay-realtime")
+        .arg("--assert")
+        .arg(golden_path)
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    // Replaying against the real daemon has no simulated output to compare
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_simulate_trace_state_conflicts_with_json() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,wait:50,release:A")
+        .arg("--trace-state")
+        .arg("--json")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    // The JSON form of the per-event state is --timeline
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_simulate_events_from_stdin() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let mut child = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("-")
+        .arg("--json")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            br#"{"events": [
+                {"device_id": null, "timestamp_us": 0, "key": "C", "event_type": "press"},
+                {"device_id": null, "timestamp_us": 20000, "key": "C", "event_type": "release"}
+            ], "seed": 3}"#,
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: Value = serde_json::from_str(&stdout).expect("Failed to parse JSON");
+    assert_eq!(json["seed"], 3);
+    let result = json["output"].as_array().unwrap();
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0]["key"], "C");
+}
+
+#[test]
+fn test_simulate_assert_golden_from_stdin() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let mut child = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("press:A,wait:50,release:A")
+        .arg("--assert")
+        .arg("-")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            br#"[
+                {"key": "A", "event_type": "press", "timestamp_us": 0},
+                {"key": "A", "event_type": "release", "timestamp_us": 50000}
+            ]"#,
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "Command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("matches golden file"));
+}
+
+#[test]
+fn test_simulate_rejects_events_and_assert_both_from_stdin() {
+    let (_temp_dir, config_dir) = create_test_environment();
+
+    let output = Command::new(get_binary_path())
+        .arg("simulate")
+        .arg("--profile")
+        .arg("default")
+        .arg("--events")
+        .arg("-")
+        .arg("--assert")
+        .arg("-")
+        .env("KEYRX_CONFIG_DIR", &config_dir)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot both be read from stdin"));
+}
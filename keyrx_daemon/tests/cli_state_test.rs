@@ -88,7 +88,40 @@ fn test_state_inspect_help() {
             "Inspect the current modifier/lock state",
         ))
         .stdout(predicate::str::contains("--json"))
-        .stdout(predicate::str::contains("--socket"));
+        .stdout(predicate::str::contains("--socket"))
+        .stdout(predicate::str::contains("--watch"))
+        .stdout(predicate::str::contains("--interval"));
+}
+
+#[test]
+fn test_state_inspect_interval_requires_watch() {
+    state_cmd()
+        .arg("state")
+        .arg("inspect")
+        .arg("--interval")
+        .arg("50")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--watch"));
+}
+
+#[test]
+fn test_state_inspect_watch_daemon_not_running() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("test-daemon.sock");
+
+    // Watch mode exits with the same error when the daemon is unreachable
+    state_cmd()
+        .arg("state")
+        .arg("inspect")
+        .arg("--watch")
+        .arg("--interval")
+        .arg("10")
+        .arg("--socket")
+        .arg(&socket_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Daemon socket not found"));
 }
 
 #[test]
@@ -34,8 +34,11 @@ fn test_run_all_scenarios() {
     use keyrx_daemon::cli::test::{execute, TestArgs};
 
     let args = TestArgs {
+        command: None,
         profile: Some("default".to_string()),
         scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
         json: false,
     };
 
@@ -58,8 +61,11 @@ fn test_run_specific_scenario() {
     use keyrx_daemon::cli::test::{execute, TestArgs};
 
     let args = TestArgs {
+        command: None,
         profile: Some("default".to_string()),
         scenario: "tap-hold-under-threshold".to_string(),
+        list: false,
+        scenario_file: None,
         json: false,
     };
 
@@ -78,8 +84,11 @@ fn test_invalid_scenario_name() {
     use keyrx_daemon::cli::test::{execute, TestArgs};
 
     let args = TestArgs {
+        command: None,
         profile: Some("default".to_string()),
         scenario: "invalid-scenario".to_string(),
+        list: false,
+        scenario_file: None,
         json: false,
     };
 
@@ -99,8 +108,11 @@ fn test_profile_not_found() {
     use keyrx_daemon::cli::test::{execute, TestArgs};
 
     let args = TestArgs {
+        command: None,
         profile: Some("nonexistent".to_string()),
         scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
         json: false,
     };
 
@@ -123,8 +135,11 @@ fn test_json_output_format() {
     use keyrx_daemon::cli::test::{execute, TestArgs};
 
     let args = TestArgs {
+        command: None,
         profile: Some("test".to_string()),
         scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
         json: true,
     };
 
@@ -153,8 +168,11 @@ fn test_all_scenario_names() {
 
     for scenario in scenarios {
         let args = TestArgs {
+            command: None,
             profile: Some("default".to_string()),
             scenario: scenario.to_string(),
+            list: false,
+            scenario_file: None,
             json: false,
         };
 
@@ -179,11 +197,166 @@ fn test_default_profile_fallback() {
 
     // Don't specify a profile - should use "default"
     let args = TestArgs {
+        command: None,
         profile: None,
         scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
         json: false,
     };
 
     let result = execute(args);
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_bench_reports_on_compiled_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let rhai_path = temp_dir.path().join("bench.rhai");
+    fs::write(
+        &rhai_path,
+        r#"
+device_start("*");
+map("VK_A", "VK_B");
+map("VK_CapsLock", "MD_00");
+when_start("MD_00");
+  map("VK_H", "VK_Left");
+when_end();
+device_end();
+"#,
+    )
+    .unwrap();
+    let krx_path = temp_dir.path().join("bench.krx");
+    keyrx_compiler::compile_file(&rhai_path, &krx_path).unwrap();
+
+    use keyrx_daemon::cli::test::{execute, TestArgs, TestCommands};
+
+    let args = TestArgs {
+        command: Some(TestCommands::Bench {
+            config: krx_path,
+            events: 1_000,
+        }),
+        profile: None,
+        scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
+        json: true,
+    };
+
+    let result = execute(args);
+    if let Err(e) = &result {
+        eprintln!("Error: {}", e);
+    }
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_bench_rejects_invalid_input() {
+    let temp_dir = TempDir::new().unwrap();
+
+    use keyrx_daemon::cli::test::{execute, TestArgs, TestCommands};
+
+    let missing = TestArgs {
+        command: Some(TestCommands::Bench {
+            config: temp_dir.path().join("missing.krx"),
+            events: 1_000,
+        }),
+        profile: None,
+        scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
+        json: false,
+    };
+    assert!(execute(missing).is_err());
+
+    let zero_events = TestArgs {
+        command: Some(TestCommands::Bench {
+            config: temp_dir.path().join("missing.krx"),
+            events: 0,
+        }),
+        profile: None,
+        scenario: "all".to_string(),
+        list: false,
+        scenario_file: None,
+        json: false,
+    };
+    let err = execute(zero_events).unwrap_err();
+    assert!(err.to_string().contains("--events"));
+}
+
+#[test]
+fn test_list_scenarios() {
+    use keyrx_daemon::cli::test::{execute, TestArgs};
+
+    // Listing needs no profile
+    let args = TestArgs {
+        command: None,
+        profile: None,
+        scenario: "all".to_string(),
+        list: true,
+        scenario_file: None,
+        json: true,
+    };
+
+    assert!(execute(args).is_ok());
+}
+
+#[test]
+fn test_scenario_file_pass_and_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("layout.rhai"),
+        r#"
+device_start("*");
+map("VK_A", "VK_B");
+device_end();
+"#,
+    )
+    .unwrap();
+
+    let write_scenarios = |expected_key: &str| {
+        let path = temp_dir.path().join("scenarios.json");
+        fs::write(
+            &path,
+            format!(
+                r#"{{
+                    "config": "layout.rhai",
+                    "scenarios": [{{
+                        "name": "tap-a",
+                        "events": [
+                            {{"device_id": null, "timestamp_us": 0, "key": "A", "event_type": "press"}},
+                            {{"device_id": null, "timestamp_us": 20000, "key": "A", "event_type": "release"}}
+                        ],
+                        "expected": [
+                            {{"key": "{0}", "event_type": "press"}},
+                            {{"key": "{0}", "event_type": "release"}}
+                        ]
+                    }}]
+                }}"#,
+                expected_key
+            ),
+        )
+        .unwrap();
+        path
+    };
+
+    use keyrx_daemon::cli::test::{execute, TestArgs};
+
+    let args = |path| TestArgs {
+        command: None,
+        profile: None,
+        scenario: "all".to_string(),
+        list: false,
+        scenario_file: Some(path),
+        json: false,
+    };
+
+    let result = execute(args(write_scenarios("B")));
+    if let Err(e) = &result {
+        eprintln!("Error: {}", e);
+    }
+    assert!(result.is_ok());
+
+    let err = execute(args(write_scenarios("A"))).unwrap_err();
+    assert!(err.to_string().contains("1 of 1 tests failed"));
+}
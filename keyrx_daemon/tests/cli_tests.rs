@@ -40,8 +40,13 @@ fn create_valid_krx_file() -> NamedTempFile {
         devices: vec![DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".to_string(),
+                excludes: Vec::new(),
             },
             mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::B)],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }],
         metadata: Metadata {
             compilation_timestamp: 0,
@@ -194,6 +199,80 @@ fn test_run_config_short_flag() {
         .stderr(predicate::str::contains("Error"));
 }
 
+#[test]
+fn test_run_help_shows_web_addr() {
+    cmd()
+        .arg("run")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--web-addr"))
+        .stdout(predicate::str::contains("--web-allow-remote"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_run_refuses_remote_web_addr_without_opt_in() {
+    let config_home = tempfile::TempDir::new().unwrap();
+
+    cmd()
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .arg("run")
+        .arg("--config")
+        .arg("/nonexistent/path/config.krx")
+        .arg("--web-addr")
+        .arg("0.0.0.0:9867")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--web-allow-remote"));
+}
+
+#[test]
+fn test_run_monitor_conflicts_with_test_mode() {
+    cmd()
+        .arg("run")
+        .arg("--monitor")
+        .arg("--test-mode")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--monitor"));
+}
+
+#[test]
+fn test_run_monitor_reports_config_error() {
+    cmd()
+        .arg("run")
+        .arg("--config")
+        .arg("/nonexistent/path/config.krx")
+        .arg("--monitor")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Error"));
+}
+
+#[test]
+fn test_run_rejects_invalid_web_addr() {
+    cmd()
+        .arg("run")
+        .arg("--web-addr")
+        .arg("not-an-address")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--web-addr"));
+}
+
+#[test]
+fn test_run_rejects_unsafe_output_name() {
+    cmd()
+        .arg("run")
+        .arg("--output-name")
+        .arg("keyrx\"; rm")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--output-name"))
+        .stderr(predicate::str::contains("may only contain"));
+}
+
 #[test]
 fn test_run_debug_flag_accepted() {
     // The command should accept --debug flag even if config is missing
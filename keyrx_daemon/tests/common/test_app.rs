@@ -45,6 +45,16 @@ impl TestApp {
     /// }
     /// ```
     pub async fn new() -> Self {
+        Self::build(None).await
+    }
+
+    /// Creates a test application whose API and WebSocket endpoints require `token`.
+    #[allow(dead_code)]
+    pub async fn with_api_token(token: &str) -> Self {
+        Self::build(Some(token.to_string())).await
+    }
+
+    async fn build(api_token: Option<String>) -> Self {
         // Create isolated config directory with proper structure
         // We need HOME/.config/keyrx structure because device API uses get_config_dir()
         let temp_home = TempDir::new().expect("Failed to create temp directory");
@@ -76,7 +86,7 @@ impl TestApp {
         let (event_broadcaster, _) = tokio::sync::broadcast::channel(1000);
 
         // Create app state
-        let state = Arc::new(AppState::new(
+        let mut state = AppState::new(
             macro_recorder,
             profile_service,
             device_service,
@@ -85,7 +95,11 @@ impl TestApp {
             simulation_service,
             subscription_manager,
             event_broadcaster,
-        ));
+        );
+        if let Some(token) = api_token {
+            state = state.with_api_token(token);
+        }
+        let state = Arc::new(state);
 
         // Create event channel
         let (event_tx, _event_rx) = broadcast::channel::<DaemonEvent>(100);
@@ -0,0 +1,348 @@
+//! Integration tests for the IPC server of a running daemon.
+//!
+//! These tests validate that IPC queries are answered from the daemon's own
+//! state, not from handles that nothing updates:
+//! - `GetLoadedConfig` follows the daemon's loaded configuration
+//! - `GetErrorCounts` reports the failures the event loop counts
+//! - `GetLatencyByDevice` reports the latency the event loop records
+//! - `metrics events --follow` prints the events the event loop processes
+//! - `ReloadConfig` is answered once the running daemon remaps with the new
+//!   configuration
+
+#![cfg(target_os = "linux")]
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use keyrx_core::runtime::KeyEvent;
+use keyrx_daemon::config::ProfileManager;
+use keyrx_daemon::daemon::Daemon;
+use keyrx_daemon::ipc::commands::IpcCommandHandler;
+use keyrx_daemon::ipc::server::spawn_command_server;
+use keyrx_daemon::ipc::unix_socket::UnixSocketIpc;
+use keyrx_daemon::ipc::{DaemonIpc, IpcRequest, IpcResponse};
+use keyrx_daemon::platform::{DeviceInfo, Platform, PlatformError, PlatformResult};
+use tempfile::TempDir;
+use tokio::sync::RwLock;
+
+/// Platform that replays queued input events and records injected output.
+#[derive(Default)]
+struct QueuePlatform {
+    input: Arc<Mutex<VecDeque<KeyEvent>>>,
+    injected: Arc<Mutex<Vec<KeyEvent>>>,
+    /// Reject every injected event, as if the virtual output were gone.
+    fail_injection: bool,
+}
+
+impl Platform for QueuePlatform {
+    fn initialize(&mut self) -> PlatformResult<()> {
+        Ok(())
+    }
+
+    fn capture_input(&mut self) -> PlatformResult<KeyEvent> {
+        self.input
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| PlatformError::DeviceNotFound("no input queued".to_string()))
+    }
+
+    fn inject_output(&mut self, event: KeyEvent) -> PlatformResult<()> {
+        if self.fail_injection {
+            return Err(PlatformError::InjectionFailed {
+                reason: "output device removed".to_string(),
+                suggestion: String::new(),
+            });
+        }
+        self.injected.lock().unwrap().push(event);
+        Ok(())
+    }
+
+    fn list_devices(&self) -> PlatformResult<Vec<DeviceInfo>> {
+        Ok(Vec::new())
+    }
+
+    fn shutdown(&mut self) -> PlatformResult<()> {
+        Ok(())
+    }
+}
+
+/// Writes a `.rhai` config mapping `from` to `to` for every device.
+fn write_config(path: &Path, from: &str, to: &str) {
+    std::fs::write(
+        path,
+        format!(
+            "device_start(\"*\");\n  map(\"{}\", \"{}\");\ndevice_end();\n",
+            from, to
+        ),
+    )
+    .unwrap();
+}
+
+/// Creates a command handler backed by an empty profile directory.
+fn command_handler(dir: &TempDir) -> IpcCommandHandler {
+    let profile_manager = ProfileManager::new(dir.path().join("keyrx")).unwrap();
+    IpcCommandHandler::new(Arc::new(profile_manager), Arc::new(RwLock::new(true)))
+}
+
+/// Serves `handler` on a socket inside `dir` and returns the socket path.
+fn serve(dir: &TempDir, handler: IpcCommandHandler) -> PathBuf {
+    let socket_path = dir.path().join("keyrx.sock");
+    spawn_command_server(socket_path.clone(), Arc::new(handler)).unwrap();
+    socket_path
+}
+
+fn request(socket_path: &Path, request: IpcRequest) -> IpcResponse {
+    UnixSocketIpc::new(socket_path.to_path_buf())
+        .send_request(&request)
+        .unwrap()
+}
+
+/// Returns the source hash `GetLoadedConfig` reports.
+fn loaded_source_hash(socket_path: &Path) -> Option<String> {
+    match request(socket_path, IpcRequest::GetLoadedConfig) {
+        IpcResponse::LoadedConfig { source_hash, .. } => source_hash,
+        other => panic!("Expected LoadedConfig, got {:?}", other),
+    }
+}
+
+/// Returns the source hash of the configuration `daemon` remaps with.
+fn daemon_source_hash(daemon: &Daemon) -> Option<String> {
+    daemon.loaded_config().source_hash()
+}
+
+#[test]
+fn test_get_loaded_config_follows_daemon_reload() {
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.rhai");
+    write_config(&config_path, "VK_A", "VK_B");
+    let mut daemon = Daemon::new(Box::new(QueuePlatform::default()), &config_path).unwrap();
+
+    let socket_path = serve(
+        &dir,
+        command_handler(&dir).with_loaded_config(daemon.loaded_config()),
+    );
+
+    let initial_hash = loaded_source_hash(&socket_path);
+    assert!(initial_hash.is_some());
+    assert_eq!(initial_hash, daemon_source_hash(&daemon));
+
+    write_config(&config_path, "VK_A", "VK_C");
+    daemon.reload().unwrap();
+
+    let reloaded_hash = loaded_source_hash(&socket_path);
+    assert_ne!(reloaded_hash, initial_hash);
+    assert_eq!(reloaded_hash, daemon_source_hash(&daemon));
+}
+
+#[test]
+fn test_get_error_counts_reports_daemon_injection_failures() {
+    use keyrx_core::config::KeyCode;
+
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.rhai");
+    write_config(&config_path, "VK_A", "VK_B");
+    let platform = QueuePlatform {
+        fail_injection: true,
+        ..QueuePlatform::default()
+    };
+    platform
+        .input
+        .lock()
+        .unwrap()
+        .push_back(KeyEvent::press(KeyCode::A));
+    let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+
+    let socket_path = serve(
+        &dir,
+        command_handler(&dir).with_event_counters(daemon.event_counters()),
+    );
+
+    assert!(daemon.process_one_event().unwrap());
+
+    match request(&socket_path, IpcRequest::GetErrorCounts) {
+        IpcResponse::ErrorCounts {
+            injection_failures,
+            capture_errors,
+            dropped_events,
+        } => {
+            assert_eq!(injection_failures, 1);
+            assert_eq!(capture_errors, 0);
+            assert_eq!(dropped_events, 0);
+        }
+        other => panic!("Expected ErrorCounts, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_latency_by_device_reports_daemon_samples() {
+    use keyrx_core::config::KeyCode;
+
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.rhai");
+    write_config(&config_path, "VK_A", "VK_B");
+    let platform = QueuePlatform::default();
+    platform
+        .input
+        .lock()
+        .unwrap()
+        .push_back(KeyEvent::press(KeyCode::A).with_device_id("kbd-0".to_string()));
+    let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+
+    let socket_path = serve(
+        &dir,
+        command_handler(&dir).with_latency_recorder(daemon.latency_recorder()),
+    );
+
+    assert!(daemon.process_one_event().unwrap());
+
+    match request(&socket_path, IpcRequest::GetLatencyByDevice) {
+        IpcResponse::LatencyByDevice { devices } => {
+            assert_eq!(devices.keys().collect::<Vec<_>>(), ["kbd-0"]);
+            assert_eq!(devices["kbd-0"].sample_count, 1);
+        }
+        other => panic!("Expected LatencyByDevice, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_metrics_events_follow_prints_daemon_events() {
+    use keyrx_core::config::KeyCode;
+    use keyrx_daemon::daemon::EventBroadcaster;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.rhai");
+    write_config(&config_path, "VK_A", "VK_B");
+    let platform = QueuePlatform::default();
+    let input = Arc::clone(&platform.input);
+    let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+    let (event_tx, _event_rx) = tokio::sync::broadcast::channel(16);
+    let broadcaster = EventBroadcaster::new(event_tx);
+    daemon.set_event_broadcaster(broadcaster.clone());
+
+    let socket_path = serve(
+        &dir,
+        command_handler(&dir).with_event_log(broadcaster.event_log()),
+    );
+
+    input.lock().unwrap().push_back(KeyEvent::press(KeyCode::A));
+    assert!(daemon.process_one_event().unwrap());
+
+    let mut follower = Command::new(env!("CARGO_BIN_EXE_keyrx_daemon"))
+        .args(["metrics", "events", "--follow", "--json", "--socket"])
+        .arg(&socket_path)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (line_tx, line_rx) = mpsc::channel();
+    let stdout = follower.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if line_tx.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_id = || {
+        let line = line_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+        event["id"].as_u64().unwrap()
+    };
+
+    // The event already logged is printed first, then new ones as they arrive
+    let first_id = next_id();
+    input
+        .lock()
+        .unwrap()
+        .extend([KeyEvent::release(KeyCode::A), KeyEvent::press(KeyCode::A)]);
+    assert!(daemon.process_one_event().unwrap());
+    assert!(daemon.process_one_event().unwrap());
+    let followed = [first_id, next_id(), next_id()];
+
+    follower.kill().unwrap();
+    follower.wait().unwrap();
+    assert_eq!(followed, [1, 2, 3]);
+}
+
+#[test]
+fn test_reload_config_swaps_running_daemon_config() {
+    use keyrx_core::config::KeyCode;
+    use std::time::{Duration, Instant};
+
+    let dir = TempDir::new().unwrap();
+    let config_path = dir.path().join("config.rhai");
+    write_config(&config_path, "VK_A", "VK_B");
+    let platform = QueuePlatform::default();
+    let input = Arc::clone(&platform.input);
+    let injected = Arc::clone(&platform.injected);
+    let mut daemon = Daemon::new(Box::new(platform), &config_path).unwrap();
+
+    let socket_path = serve(
+        &dir,
+        command_handler(&dir)
+            .with_loaded_config(daemon.loaded_config())
+            .with_reload_handle(daemon.reload_handle()),
+    );
+    let loaded_config = daemon.loaded_config();
+    let running = daemon.running_flag();
+    let event_loop = std::thread::spawn(move || daemon.run());
+
+    // Presses A and returns what the daemon injects for it
+    let press_a = || {
+        input.lock().unwrap().push_back(KeyEvent::press(KeyCode::A));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(event) = injected.lock().unwrap().pop() {
+                return event;
+            }
+            assert!(Instant::now() < deadline, "no output for A");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    };
+    assert_eq!(press_a(), KeyEvent::press(KeyCode::B));
+
+    let other_path = dir.path().join("other.rhai");
+    write_config(&other_path, "VK_A", "VK_C");
+    let reloaded = request(
+        &socket_path,
+        IpcRequest::ReloadConfig {
+            path: Some(other_path.display().to_string()),
+        },
+    );
+    match reloaded {
+        IpcResponse::ConfigReloaded { path, source_hash } => {
+            assert_eq!(path, other_path.display().to_string());
+            assert_eq!(
+                Some(source_hash),
+                loaded_config
+                    .get()
+                    .map(|config| config.metadata.source_hash)
+            );
+        }
+        other => panic!("Expected ConfigReloaded, got {:?}", other),
+    }
+    // The swap happened before the reply, releasing the B still held
+    assert_eq!(
+        std::mem::take(&mut *injected.lock().unwrap()),
+        [KeyEvent::release(KeyCode::B)]
+    );
+    assert_eq!(press_a(), KeyEvent::press(KeyCode::C));
+
+    // A failed reload is reported and leaves the daemon remapping as before
+    let failed = request(
+        &socket_path,
+        IpcRequest::ReloadConfig {
+            path: Some(dir.path().join("missing.krx").display().to_string()),
+        },
+    );
+    assert!(matches!(failed, IpcResponse::Error { code: 5004, .. }));
+    assert_eq!(press_a(), KeyEvent::press(KeyCode::C));
+
+    running.store(false, std::sync::atomic::Ordering::SeqCst);
+    event_loop.join().unwrap().unwrap();
+}
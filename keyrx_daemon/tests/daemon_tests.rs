@@ -36,8 +36,13 @@ fn create_test_config(mappings: Vec<KeyMapping>) -> ConfigRoot {
         devices: vec![DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".to_string(),
+                excludes: Vec::new(),
             },
             mappings,
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }],
         metadata: Metadata {
             compilation_timestamp: 0,
@@ -468,8 +473,13 @@ fn test_daemon_reload_success() {
         devices: vec![DeviceConfig {
             identifier: DeviceIdentifier {
                 pattern: "*".to_string(),
+                excludes: Vec::new(),
             },
             mappings: vec![KeyMapping::simple(KeyCode::A, KeyCode::C)],
+            time_windows: Vec::new(),
+            priority: 0,
+            default_layer: None,
+            tap_hold_threshold_ms: None,
         }],
         metadata: Metadata {
             compilation_timestamp: 1,
@@ -273,6 +273,8 @@ pub struct E2EConfig {
     pub device_pattern: String,
     /// Key mappings to apply
     pub mappings: Vec<KeyMapping>,
+    /// Name for the daemon's virtual output device (default: `DAEMON_OUTPUT_NAME`)
+    pub output_name: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -281,6 +283,7 @@ impl E2EConfig {
         Self {
             device_pattern: device_pattern.into(),
             mappings,
+            output_name: None,
         }
     }
 
@@ -295,6 +298,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings: vec![KeyMapping::simple(from, to)],
+            output_name: None,
         }
     }
 
@@ -317,6 +321,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings,
+            output_name: None,
         }
     }
 
@@ -339,6 +344,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings: vec![KeyMapping::modifier(from, modifier_id)],
+            output_name: None,
         }
     }
 
@@ -360,6 +366,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings: vec![KeyMapping::lock(from, lock_id)],
+            output_name: None,
         }
     }
 
@@ -386,6 +393,7 @@ impl E2EConfig {
                 Condition::ModifierActive(modifier_id),
                 vec![BaseKeyMapping::Simple { from, to }],
             )],
+            output_name: None,
         }
     }
 
@@ -431,6 +439,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings,
+            output_name: None,
         }
     }
 
@@ -474,6 +483,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings,
+            output_name: None,
         }
     }
 
@@ -511,6 +521,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings: vec![KeyMapping::modified_output(from, to, shift, ctrl, alt, win)],
+            output_name: None,
         }
     }
 
@@ -547,6 +558,7 @@ impl E2EConfig {
                 hold_modifier,
                 threshold_ms,
             )],
+            output_name: None,
         }
     }
 
@@ -608,6 +620,7 @@ impl E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings,
+            output_name: None,
         }
     }
 
@@ -639,6 +652,22 @@ impl E2EConfig {
         self
     }
 
+    /// Sets the name the daemon gives its virtual output device.
+    ///
+    /// The harness passes it with `--output-name` and looks for the output
+    /// device under the same name.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config = E2EConfig::simple_remap(KeyCode::A, KeyCode::B)
+    ///     .with_output_name("keyrx-e2e-custom");
+    /// ```
+    pub fn with_output_name(mut self, name: impl Into<String>) -> Self {
+        self.output_name = Some(name.into());
+        self
+    }
+
     /// Converts this E2EConfig to a ConfigRoot for serialization.
     ///
     /// This creates a complete configuration with proper version and metadata.
@@ -648,8 +677,13 @@ impl E2EConfig {
             devices: vec![DeviceConfig {
                 identifier: DeviceIdentifier {
                     pattern: self.device_pattern.clone(),
+                    excludes: Vec::new(),
                 },
                 mappings: self.mappings.clone(),
+                time_windows: Vec::new(),
+                priority: 0,
+                default_layer: None,
+                tap_hold_threshold_ms: None,
             }],
             metadata: Metadata {
                 compilation_timestamp: 0,
@@ -665,6 +699,7 @@ impl Default for E2EConfig {
         Self {
             device_pattern: "*".to_string(),
             mappings: Vec::new(),
+            output_name: None,
         }
     }
 }
@@ -732,6 +767,8 @@ impl fmt::Display for TeardownResult {
 // ============================================================================
 
 /// Default name for the daemon's virtual output device.
+///
+/// On Linux the harness passes it to the daemon with `--output-name`.
 #[cfg(target_os = "linux")]
 const DAEMON_OUTPUT_NAME: &str = "keyrx Virtual Keyboard";
 #[cfg(target_os = "windows")]
@@ -850,9 +887,13 @@ impl E2EHarness {
             "*".to_string()
         };
 
+        let output_name = config
+            .output_name
+            .unwrap_or_else(|| DAEMON_OUTPUT_NAME.to_string());
         let test_config = E2EConfig {
             device_pattern,
             mappings: config.mappings,
+            output_name: None,
         };
 
         let config_root = test_config.to_config_root();
@@ -869,11 +910,16 @@ impl E2EHarness {
         // Step 3: Start daemon as subprocess
         let daemon_binary = Self::find_daemon_binary()?;
 
-        let mut daemon_process = Command::new(&daemon_binary)
+        let mut daemon_command = Command::new(&daemon_binary);
+        daemon_command
             .arg("run")
             .arg("--config")
             .arg(&config_path)
-            .arg("--debug")
+            .arg("--debug");
+        if cfg!(target_os = "linux") {
+            daemon_command.arg("--output-name").arg(&output_name);
+        }
+        let mut daemon_process = daemon_command
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .spawn()
@@ -901,7 +947,7 @@ impl E2EHarness {
 
         // Step 5: Find and open the daemon's output device
         let remaining_timeout = output_timeout.saturating_sub(start.elapsed());
-        let mut output_capture = OutputCapture::find_by_name(DAEMON_OUTPUT_NAME, remaining_timeout)
+        let mut output_capture = OutputCapture::find_by_name(&output_name, remaining_timeout)
             .map_err(|e| match e {
                 VirtualDeviceError::NotFound { .. } | VirtualDeviceError::Timeout { .. } => {
                     // Daemon may have crashed - check and include stderr
@@ -913,10 +959,7 @@ impl E2EHarness {
                         }
                     } else {
                         E2EError::Timeout {
-                            operation: format!(
-                                "waiting for output device '{}'",
-                                DAEMON_OUTPUT_NAME
-                            ),
+                            operation: format!("waiting for output device '{}'", output_name),
                             timeout_ms: output_timeout.as_millis() as u64,
                         }
                     }
@@ -1138,22 +1181,9 @@ impl E2EHarness {
         count: usize,
         timeout: Duration,
     ) -> Result<Vec<KeyEvent>, E2EError> {
-        let mut events = Vec::with_capacity(count);
-        let start = Instant::now();
-
-        while events.len() < count {
-            let remaining = timeout.saturating_sub(start.elapsed());
-            if remaining.is_zero() {
-                break;
-            }
-
-            match self.output_capture.next_event(remaining)? {
-                Some(event) => events.push(event),
-                None => break, // Timeout
-            }
-        }
-
-        Ok(events)
+        self.output_capture
+            .collect_until(|events| events.len() >= count, timeout)
+            .map_err(E2EError::from)
     }
 
     /// Drains any pending events from the output capture.
@@ -1276,13 +1306,15 @@ impl E2EHarness {
     /// Injects events, captures output, and verifies against expected events.
     ///
     /// This is the most convenient method for E2E testing, combining
-    /// injection, capture, and verification in one call.
+    /// injection, capture, and verification in one call. Capture stops as
+    /// soon as `expected.len()` events have arrived, so a passing test does
+    /// not wait out the timeout.
     ///
     /// # Arguments
     ///
     /// * `input` - Events to inject
     /// * `expected` - Expected output events
-    /// * `capture_timeout` - Time to wait for output events
+    /// * `capture_timeout` - Maximum time to wait for the expected events
     ///
     /// # Returns
     ///
@@ -1304,7 +1336,7 @@ impl E2EHarness {
         expected: &[KeyEvent],
         capture_timeout: Duration,
     ) -> Result<(), E2EError> {
-        let captured = self.inject_and_capture(input, capture_timeout)?;
+        let captured = self.inject_and_capture_n(input, expected.len(), capture_timeout)?;
         self.verify(&captured, expected)
     }
 
@@ -103,6 +103,8 @@ fn start_daemon(krx_path: &std::path::Path) -> Result<Child, std::io::Error> {
             "run",
             "--config",
             krx_path.to_str().unwrap(),
+            "--output-name",
+            "keyrx Virtual Keyboard",
         ])
         .env("RUST_LOG", "info")
         .stdout(std::process::Stdio::null()) // Suppress stdout to reduce noise
@@ -196,9 +198,9 @@ fn test_linux_multidevice_discrimination() -> Result<(), Box<dyn std::error::Err
     // Send tap (press + release)
     let tap_events = VirtualKeyboard::tap_events(KeyCode::A);
     numpad.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events_a = output_capture.collect_events(Duration::from_millis(200))?;
+    let events_a =
+        output_capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
     println!("Numpad events captured: {}", events_a.len());
 
     // Verify we got B press and release (not A)
@@ -228,9 +230,9 @@ fn test_linux_multidevice_discrimination() -> Result<(), Box<dyn std::error::Err
 
     let tap_events = VirtualKeyboard::tap_events(KeyCode::A);
     main_kbd.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events_b = output_capture.collect_events(Duration::from_millis(200))?;
+    let events_b =
+        output_capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
     println!("Main keyboard events captured: {}", events_b.len());
 
     assert!(
@@ -268,9 +270,9 @@ fn test_linux_multidevice_discrimination() -> Result<(), Box<dyn std::error::Err
     numpad.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
     std::thread::sleep(Duration::from_millis(50));
     main_kbd.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events_c = output_capture.collect_events(Duration::from_millis(200))?;
+    let events_c =
+        output_capture.collect_until(|events| events.len() >= 4, Duration::from_secs(1))?;
     println!("Combined events captured: {}", events_c.len());
 
     // Should have 4 events total (2 press + 2 release)
@@ -376,9 +378,9 @@ device_end();
     println!("\n=== Testing prefix pattern: usb-* ===");
     let tap_events = VirtualKeyboard::tap_events(KeyCode::A);
     prefix_dev.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events = output_capture.collect_events(Duration::from_millis(200))?;
+    let events =
+        output_capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
     let press = events
         .iter()
         .find(|e| e.is_press())
@@ -395,9 +397,9 @@ device_end();
     println!("\n=== Testing suffix pattern: *-keyboard ===");
     let tap_events = VirtualKeyboard::tap_events(KeyCode::A);
     suffix_dev.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events = output_capture.collect_events(Duration::from_millis(200))?;
+    let events =
+        output_capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
     let press = events
         .iter()
         .find(|e| e.is_press())
@@ -414,9 +416,9 @@ device_end();
     println!("\n=== Testing contains pattern: *numpad* ===");
     let tap_events = VirtualKeyboard::tap_events(KeyCode::A);
     contains_dev.inject_sequence(&tap_events, Some(Duration::from_millis(10)))?;
-    std::thread::sleep(Duration::from_millis(100));
 
-    let events = output_capture.collect_events(Duration::from_millis(200))?;
+    let events =
+        output_capture.collect_until(|events| events.len() >= 2, Duration::from_secs(1))?;
     let press = events
         .iter()
         .find(|e| e.is_press())
@@ -77,7 +77,7 @@ use std::time::{Duration, Instant};
 use keyrx_compiler::serialize::serialize;
 use keyrx_core::config::{
     BaseKeyMapping, Condition, ConditionItem, ConfigRoot, DeviceConfig, DeviceIdentifier, KeyCode,
-    KeyMapping, Metadata, Version,
+    KeyMapping, Metadata, TapHoldPolicy, Version,
 };
 use keyrx_daemon::daemon::{Daemon, DaemonError};
 use tempfile::NamedTempFile;
@@ -104,8 +104,13 @@ fn create_device_config(pattern: &str, mappings: Vec<KeyMapping>) -> DeviceConfi
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: pattern.to_string(),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -980,6 +985,7 @@ fn test_tap_hold_configuration() {
                 tap: KeyCode::Space,
                 hold_modifier: 0,
                 threshold_ms: 200,
+                policy: TapHoldPolicy::Permissive,
             }),
             // When MD_00 is active (holding space), HJKL become arrows
             KeyMapping::conditional(
@@ -26,8 +26,13 @@ fn create_test_config(mappings: Vec<KeyMapping>) -> DeviceConfig {
     DeviceConfig {
         identifier: DeviceIdentifier {
             pattern: String::from("*"),
+            excludes: Vec::new(),
         },
         mappings,
+        time_windows: Vec::new(),
+        priority: 0,
+        default_layer: None,
+        tap_hold_threshold_ms: None,
     }
 }
 
@@ -469,6 +474,7 @@ fn test_device_manager_match_device() {
         name: String::from("Logitech USB Keyboard"),
         serial: Some(String::from("SN12345")),
         phys: Some(String::from("usb-0000:00:14.0-1/input0")),
+        capabilities: None,
     };
 
     // Wildcard matches everything
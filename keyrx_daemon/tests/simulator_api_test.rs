@@ -374,3 +374,59 @@ device_end();
         "Invalid DSL should return error"
     );
 }
+
+/// Test simulating unsaved Rhai source in a single call.
+#[tokio::test]
+#[serial]
+async fn test_run_source_returns_timeline() {
+    let app = TestApp::new().await;
+
+    let source = r#"
+device_start("*");
+  map("VK_A", "VK_B");
+  map("VK_ScrollLock", "LK_01");
+device_end();
+"#;
+    let response = app
+        .post(
+            "/api/simulator/run",
+            &json!({
+                "source": source,
+                "events": [
+                    {"keycode": "ScrollLock", "event_type": "press", "timestamp_us": 0},
+                    {"keycode": "A", "event_type": "press", "timestamp_us": 1000},
+                    {"keycode": "A", "event_type": "release", "timestamp_us": 2000}
+                ]
+            }),
+        )
+        .await;
+
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["success"], true);
+
+    let timeline = body["timeline"].as_array().unwrap();
+    assert_eq!(timeline.len(), 3);
+    assert_eq!(timeline[1]["outputs"][0]["keycode"], "B");
+    assert_eq!(timeline[1]["outputs"][0]["event_type"], "press");
+    assert_eq!(body["final_state"]["active_locks"], json!([1]));
+    assert!(body["latency_stats"]["p99_us"].is_u64());
+}
+
+/// Test that source which fails to compile returns 400.
+#[tokio::test]
+#[serial]
+async fn test_run_source_compile_error() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/simulator/run",
+            &json!({"source": "device_start(", "events": []}),
+        )
+        .await;
+
+    assert_eq!(response.status(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+}
@@ -331,6 +331,25 @@ fn test_capslock_to_escape() {
         .expect("CapsLock should become Escape");
 }
 
+/// Test remapping to a media key.
+///
+/// Media keys are only delivered if the daemon's output device advertises
+/// them, so this verifies the output device carries the config's outputs.
+#[test]
+fn test_remap_to_volume_up() {
+    keyrx_daemon::skip_if_no_uinput!();
+    let config = E2EConfig::simple_remap(KeyCode::F1, KeyCode::VolumeUp);
+    let mut harness = E2EHarness::setup(config).expect("Failed to setup E2E harness");
+
+    let captured = harness
+        .inject_and_capture(&TestEvents::tap(KeyCode::F1), Duration::from_millis(100))
+        .expect("Failed to inject and capture");
+
+    harness
+        .verify(&captured, &TestEvents::tap(KeyCode::VolumeUp))
+        .expect("F1 should become VolumeUp");
+}
+
 /// Test empty configuration (all keys passthrough).
 ///
 /// Verifies that with no mappings configured, all keys pass through.
@@ -833,6 +852,53 @@ fn test_modified_output_shift() {
         .expect("Release A should produce Release(Num1), Release(LShift)");
 }
 
+/// Test that a shifted output arrives as the shifted character.
+///
+/// Applications apply each input frame (events up to a `SYN_REPORT`) as a
+/// whole. Shift must be held by the time the key press is reported, so the
+/// press frame holds Shift followed by the key and no frame reports the key
+/// on its own before Shift.
+#[test]
+#[cfg(target_os = "linux")]
+fn test_modified_output_shift_arrives_in_one_frame() {
+    keyrx_daemon::skip_if_no_uinput!();
+    // A → Shift+1 ('!' on US layouts, '1' if Shift were missed)
+    let config = E2EConfig::modified_output(KeyCode::A, KeyCode::Num1, true, false, false, false);
+    let mut harness = E2EHarness::setup(config).expect("Failed to setup E2E harness");
+
+    harness
+        .inject(&TestEvents::press(KeyCode::A))
+        .expect("Failed to inject press");
+    let press_frames = harness
+        .output_capture_mut()
+        .collect_frames(Duration::from_millis(100))
+        .expect("Failed to capture press frames");
+    assert_eq!(
+        press_frames,
+        vec![vec![
+            KeyEvent::Press(KeyCode::LShift),
+            KeyEvent::Press(KeyCode::Num1),
+        ]],
+        "Shift and 1 should be reported in one frame, Shift first"
+    );
+
+    harness
+        .inject(&TestEvents::release(KeyCode::A))
+        .expect("Failed to inject release");
+    let release_frames = harness
+        .output_capture_mut()
+        .collect_frames(Duration::from_millis(100))
+        .expect("Failed to capture release frames");
+    assert_eq!(
+        release_frames,
+        vec![vec![
+            KeyEvent::Release(KeyCode::Num1),
+            KeyEvent::Release(KeyCode::LShift),
+        ]],
+        "1 should be released before Shift, in one frame"
+    );
+}
+
 /// Test Ctrl+Key combination.
 ///
 /// Verifies that Ctrl modifier is correctly applied to the output.
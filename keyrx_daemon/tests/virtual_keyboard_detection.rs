@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod test_virtual_keyboard_detection {
     use keyrx_daemon::device_manager::enumerate_keyboards;
+    use keyrx_daemon::platform::linux::UinputOutput;
     use keyrx_daemon::test_utils::VirtualKeyboard;
     use std::thread;
     use std::time::Duration;
@@ -42,4 +43,39 @@ mod test_virtual_keyboard_detection {
 
         println!("SUCCESS: Virtual keyboard was detected");
     }
+
+    #[test]
+    #[ignore]
+    fn test_output_device_is_not_enumerated() {
+        if !keyrx_daemon::test_utils::can_access_uinput() {
+            eprintln!("SKIPPED: uinput not accessible");
+            return;
+        }
+
+        // The default name and a custom --output-name must both be skipped
+        let _default = UinputOutput::create("keyrx").expect("Failed to create output");
+        let _custom =
+            UinputOutput::create("keyrx-detection-output").expect("Failed to create output");
+        // A real keyboard created alongside must still be found
+        let keyboard =
+            VirtualKeyboard::create("test-detection-input").expect("Failed to create keyboard");
+
+        // Give kernel time to register
+        thread::sleep(Duration::from_millis(500));
+
+        let keyboards = enumerate_keyboards().expect("Failed to enumerate");
+        let names: Vec<&str> = keyboards.iter().map(|kb| kb.name.as_str()).collect();
+
+        assert!(
+            !names.contains(&"keyrx") && !names.contains(&"keyrx-detection-output"),
+            "Output devices were enumerated as input: {:?}",
+            names
+        );
+        assert!(
+            names.contains(&keyboard.name()),
+            "Virtual keyboard '{}' was NOT found: {:?}",
+            keyboard.name(),
+            names
+        );
+    }
 }
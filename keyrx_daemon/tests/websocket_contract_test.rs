@@ -7,7 +7,9 @@
 //! **IMPORTANT**: If these tests fail, it means the frontend and backend
 //! message formats are out of sync. Both sides must be updated together.
 
-use keyrx_daemon::web::events::{DaemonEvent, DaemonState, KeyEventData, LatencyStats};
+use keyrx_daemon::web::events::{
+    DaemonEvent, DaemonSnapshot, DaemonState, KeyEventData, LatencyStats, SnapshotDevice,
+};
 use serde_json::Value;
 
 /// Verify that DaemonEvent::Latency serializes to the expected legacy format
@@ -79,6 +81,36 @@ fn test_state_event_serialization_format() {
     assert_eq!(payload["layer"], "base");
 }
 
+/// Verify that DaemonEvent::Snapshot serializes correctly
+#[test]
+fn test_snapshot_event_serialization_format() {
+    let event = DaemonEvent::Snapshot(DaemonSnapshot {
+        active_profile: Some("default".to_string()),
+        devices: vec![SnapshotDevice {
+            id: "serial-ABC123".to_string(),
+            name: "USB Keyboard".to_string(),
+            path: "/dev/input/event3".to_string(),
+            active: true,
+        }],
+        modifiers: vec!["MD_00".to_string()],
+        locks: vec![],
+        layer: "base".to_string(),
+    });
+
+    let json = serde_json::to_string(&event).expect("Failed to serialize");
+    let parsed: Value = serde_json::from_str(&json).expect("Failed to parse");
+
+    assert_eq!(parsed["type"], "snapshot");
+
+    let payload = &parsed["payload"];
+    assert_eq!(payload["active_profile"], "default");
+    assert_eq!(payload["devices"][0]["id"], "serial-ABC123");
+    assert_eq!(payload["devices"][0]["active"], true);
+    assert_eq!(payload["modifiers"][0], "MD_00");
+    assert!(payload["locks"].as_array().unwrap().is_empty());
+    assert_eq!(payload["layer"], "base");
+}
+
 /// Verify that DaemonEvent::KeyEvent serializes correctly
 #[test]
 fn test_key_event_serialization_format() {
@@ -93,6 +125,7 @@ fn test_key_event_serialization_format() {
         device_name: Some("USB Keyboard".to_string()),
         mapping_type: None,
         mapping_triggered: false,
+        matched_mapping: None,
     });
 
     let json = serde_json::to_string(&event).expect("Failed to serialize");
@@ -129,6 +162,7 @@ fn test_all_daemon_events_serialize() {
             device_name: None,
             mapping_type: None,
             mapping_triggered: false,
+            matched_mapping: None,
         }),
         DaemonEvent::Latency(LatencyStats {
             min: 0,
@@ -0,0 +1,45 @@
+/**
+ * Web API token handling tests
+ */
+
+import { describe, it, expect, beforeEach, afterEach } from 'vitest';
+import { authHeaders, getApiToken, withApiToken } from './auth';
+
+describe('API token', () => {
+  beforeEach(() => {
+    localStorage.clear();
+    window.history.replaceState({}, '', '/');
+  });
+
+  afterEach(() => {
+    localStorage.clear();
+    window.history.replaceState({}, '', '/');
+  });
+
+  it('returns no token and no headers by default', () => {
+    expect(getApiToken()).toBeNull();
+    expect(authHeaders()).toEqual({});
+    expect(withApiToken('ws://localhost:9867/ws-rpc')).toBe(
+      'ws://localhost:9867/ws-rpc'
+    );
+  });
+
+  it('reads the token from the page URL and remembers it', () => {
+    window.history.replaceState({}, '', '/?token=abc123');
+    expect(getApiToken()).toBe('abc123');
+
+    window.history.replaceState({}, '', '/devices');
+    expect(getApiToken()).toBe('abc123');
+    expect(authHeaders()).toEqual({ Authorization: 'Bearer abc123' });
+  });
+
+  it('appends the token to WebSocket URLs', () => {
+    localStorage.setItem('keyrx.apiToken', 'abc123');
+    expect(withApiToken('ws://localhost:9867/ws')).toBe(
+      'ws://localhost:9867/ws?token=abc123'
+    );
+    expect(withApiToken('ws://localhost:9867/ws?x=1')).toBe(
+      'ws://localhost:9867/ws?x=1&token=abc123'
+    );
+  });
+});
@@ -0,0 +1,42 @@
+/**
+ * Web API token handling
+ *
+ * The daemon requires a bearer token on /api requests and a `token` query
+ * parameter on WebSocket upgrades. The token arrives in the URL the daemon
+ * prints on startup (`/?token=...`) and is kept in localStorage so it
+ * survives navigation and reloads.
+ */
+
+const STORAGE_KEY = 'keyrx.apiToken';
+
+/**
+ * Get the API token, preferring one passed in the page URL
+ */
+export function getApiToken(): string | null {
+  const fromUrl = new URLSearchParams(window.location.search).get('token');
+  if (fromUrl) {
+    localStorage.setItem(STORAGE_KEY, fromUrl);
+    return fromUrl;
+  }
+  return localStorage.getItem(STORAGE_KEY);
+}
+
+/**
+ * Authorization header for fetch requests (empty if no token is known)
+ */
+export function authHeaders(): Record<string, string> {
+  const token = getApiToken();
+  return token ? { Authorization: `Bearer ${token}` } : {};
+}
+
+/**
+ * Append the token query parameter to a WebSocket URL
+ */
+export function withApiToken(url: string): string {
+  const token = getApiToken();
+  if (!token) {
+    return url;
+  }
+  const separator = url.includes('?') ? '&' : '?';
+  return `${url}${separator}token=${encodeURIComponent(token)}`;
+}
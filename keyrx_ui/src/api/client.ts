@@ -3,6 +3,8 @@
  * Handles common error handling and request/response processing
  */
 
+import { authHeaders } from './auth';
+
 export class ApiError extends Error {
   constructor(
     message: string,
@@ -35,6 +37,7 @@ async function apiFetch<T>(
       ...options,
       headers: {
         'Content-Type': 'application/json',
+        ...authHeaders(),
         ...options?.headers,
       },
     });
@@ -260,6 +260,7 @@ export const StatusResponseSchema = z
     uptime_secs: z.number().nullable().optional(),
     active_profile: z.string().nullable().optional(),
     device_count: z.number().nullable().optional(),
+    active_device_count: z.number().nullable().optional(),
   })
   .passthrough();
 
@@ -239,6 +239,19 @@ export class WebSocketManager {
           }
           break;
 
+        case 'snapshot':
+          if (this.callbacks.onState) {
+            this.callbacks.onState({
+              activeLayer: message.payload.layer,
+              modifiers: message.payload.modifiers,
+              locks: message.payload.locks,
+              tapHoldPending: false,
+              uptime: 0,
+              activeProfile: message.payload.active_profile ?? null,
+            });
+          }
+          break;
+
         case 'error':
           console.error('WebSocketManager: Server error:', message.payload);
           break;
@@ -3,6 +3,8 @@
  * Type-safe access to Vite environment variables
  */
 
+import { withApiToken } from '../api/auth';
+
 /**
  * Get the API base URL
  * In production, uses relative URL (same origin)
@@ -31,11 +33,11 @@ export function getWsUrl(): string {
   // In production, if no URL configured, use same origin with ws/wss protocol
   if (import.meta.env.PROD && !configuredUrl) {
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    return `${protocol}//${window.location.host}/ws-rpc`;
+    return withApiToken(`${protocol}//${window.location.host}/ws-rpc`);
   }
 
   // In development or if explicitly configured, use the configured URL
-  return configuredUrl || 'ws://localhost:9867/ws-rpc';
+  return withApiToken(configuredUrl || 'ws://localhost:9867/ws-rpc');
 }
 
 /**
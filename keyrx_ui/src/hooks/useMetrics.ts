@@ -2,9 +2,11 @@ import { useQuery, useQueryClient } from '@tanstack/react-query';
 import { useEffect } from 'react';
 import { queryKeys } from '../lib/queryClient';
 import * as metricsApi from '../api/metrics';
+import { withApiToken } from '../api/auth';
 import type {
   LatencyStats,
   EventRecord,
+  DaemonState,
   WSMessage,
   KeyEventPayload,
 } from '../types';
@@ -63,7 +65,7 @@ export function useWebSocketMetrics() {
 
   useEffect(() => {
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    const wsUrl = `${protocol}//${window.location.host}/ws`;
+    const wsUrl = withApiToken(`${protocol}//${window.location.host}/ws`);
     const websocket = new WebSocket(wsUrl);
 
     websocket.onopen = () => {
@@ -108,6 +110,20 @@ export function useWebSocketMetrics() {
             break;
           }
 
+          case 'snapshot': {
+            // Sent once on connect so state is correct before any change
+            const { layer, modifiers, locks, active_profile } = message.payload;
+            queryClient.setQueryData<DaemonState>(queryKeys.daemonState, (old) => ({
+              activeLayer: layer,
+              modifiers,
+              locks,
+              tapHoldPending: false,
+              uptime: old?.uptime ?? 0,
+              activeProfile: active_profile ?? null,
+            }));
+            break;
+          }
+
           case 'error': {
             const errorPayload = message.payload as { message: string };
             console.error('WebSocket error:', errorPayload.message);
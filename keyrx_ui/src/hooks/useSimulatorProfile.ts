@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
 import { getErrorMessage } from '../utils/errorUtils';
+import { authHeaders } from '../api/auth';
 
 interface SimulatorProfileState {
   isLoaded: boolean;
@@ -38,7 +39,7 @@ export function useSimulatorProfile() {
     try {
       const response = await fetch('/api/simulator/load-profile', {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: { 'Content-Type': 'application/json', ...authHeaders() },
         body: JSON.stringify({ name: profileName }),
       });
 
@@ -77,7 +78,7 @@ export function useSimulatorProfile() {
     try {
       const response = await fetch('/api/simulator/reset', {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: { 'Content-Type': 'application/json', ...authHeaders() },
       });
 
       if (!response.ok) {
@@ -35,6 +35,7 @@ interface KeyEvent {
   keycode: string;
   event_type: 'press' | 'release';
   timestamp_us: number;
+  device_id?: string;
 }
 
 /**
@@ -45,6 +46,8 @@ export interface SimulationInput {
     keycode: string;
     event_type: 'press' | 'release';
     timestamp_us: number;
+    /** Source device, matched by when_device_start() patterns */
+    device_id?: string;
   }>;
 }
 
@@ -14,6 +14,7 @@ import {
   useRenameDevice,
 } from '../hooks/useDevices';
 import { getErrorMessage } from '../utils/errorUtils';
+import { authHeaders } from '../api/auth';
 import { LAYOUT_OPTIONS } from '../contexts/LayoutPreviewContext';
 import type { DeviceEntry } from '../types';
 
@@ -293,7 +294,9 @@ export const DevicesPage: React.FC<DevicesPageProps> = ({ className = '' }) => {
   React.useEffect(() => {
     const fetchGlobalLayout = async () => {
       try {
-        const layoutResponse = await fetch('/api/settings/global-layout');
+        const layoutResponse = await fetch('/api/settings/global-layout', {
+          headers: authHeaders(),
+        });
         if (layoutResponse.ok) {
           const layoutData = await layoutResponse.json();
           setGlobalLayout(layoutData.layout || 'ANSI_104');
@@ -370,6 +373,7 @@ export const DevicesPage: React.FC<DevicesPageProps> = ({ className = '' }) => {
         method: 'PUT',
         headers: {
           'Content-Type': 'application/json',
+          ...authHeaders(),
         },
         body: JSON.stringify({ layout: newLayout }),
       });
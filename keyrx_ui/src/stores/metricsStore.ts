@@ -7,6 +7,7 @@ import type {
 } from '../types';
 import * as metricsApi from '../api/metrics';
 import { ApiError } from '../api/client';
+import { withApiToken } from '../api/auth';
 
 interface MetricsStore {
   // State
@@ -66,7 +67,7 @@ export const useMetricsStore = create<MetricsStore>((set, get) => ({
     }
 
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
-    const wsUrl = `${protocol}//${window.location.host}/ws`;
+    const wsUrl = withApiToken(`${protocol}//${window.location.host}/ws`);
     const websocket = new WebSocket(wsUrl);
 
     websocket.onopen = () => {
@@ -124,6 +125,22 @@ export const useMetricsStore = create<MetricsStore>((set, get) => ({
             break;
           }
 
+          case 'snapshot': {
+            // Sent once on connect so state is correct before any change
+            const { layer, modifiers, locks, active_profile } = message.payload;
+            set({
+              currentState: {
+                activeLayer: layer,
+                modifiers,
+                locks,
+                tapHoldPending: false,
+                uptime: get().currentState?.uptime ?? 0,
+                activeProfile: active_profile ?? null,
+              },
+            });
+            break;
+          }
+
           case 'error': {
             const errorPayload = message.payload as { message: string };
             set({ error: errorPayload.message });
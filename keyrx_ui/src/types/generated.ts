@@ -12,6 +12,20 @@ export interface ActivationRpcResult {
   error?: string;
 }
 
+/** State snapshot sent to a WebSocket client before any live events. */
+export interface DaemonSnapshot {
+  /** Currently active profile name (if any). */
+  active_profile?: string;
+  /** Connected input devices. */
+  devices: SnapshotDevice[];
+  /** Active modifier IDs (e.g., ["MD_00", "MD_01"]). */
+  modifiers: string[];
+  /** Active lock IDs (e.g., ["LK_00"]). */
+  locks: string[];
+  /** Current active layer name. */
+  layer: string;
+}
+
 /** Current daemon state snapshot. */
 export interface DaemonState {
   /** Active modifier IDs (e.g., ["MD_00", "MD_01"]). */
@@ -123,6 +137,18 @@ export interface RpcError {
   data?: JsonValue;
 }
 
+/** Connected device entry in a [`DaemonSnapshot`]. */
+export interface SnapshotDevice {
+  /** Unique device identifier. */
+  id: string;
+  /** Device name (user-assigned or reported by the device). */
+  name: string;
+  /** Device path (e.g., "/dev/input/event3"). */
+  path: string;
+  /** Whether the device is currently being remapped. */
+  active: boolean;
+}
+
 /** Messages sent from client to server */
 export type ClientMessage =
   /** Query request - read-only operation that returns data */
@@ -177,7 +203,9 @@ export type DaemonEvent =
   /** Individual key event (press/release). */
   | { type: 'event'; payload: KeyEventData }
   /** Latency statistics update. */
-  | { type: 'latency'; payload: LatencyStats };
+  | { type: 'latency'; payload: LatencyStats }
+  /** Full state sent once to each client when it connects. */
+  | { type: 'snapshot'; payload: DaemonSnapshot };
 
 /** Messages sent from server to client */
 export type ServerMessage =
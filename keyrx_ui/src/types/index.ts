@@ -125,8 +125,18 @@ export type WSMessage =
   | { type: 'event'; payload: KeyEventPayload }
   | { type: 'state'; payload: DaemonState }
   | { type: 'latency'; payload: LatencyStats }
+  | { type: 'snapshot'; payload: DaemonSnapshotPayload }
   | { type: 'error'; payload: { message: string } };
 
+// State snapshot sent once by the daemon when a client connects
+export interface DaemonSnapshotPayload {
+  active_profile?: string;
+  devices: { id: string; name: string; path: string; active: boolean }[];
+  modifiers: string[];
+  locks: string[];
+  layer: string;
+}
+
 // Raw key event payload from daemon (before transformation to EventRecord)
 export interface KeyEventPayload {
   timestamp: number;
@@ -139,4 +149,7 @@ export interface KeyEventPayload {
   deviceName?: string;
   mappingType?: string;
   mappingTriggered?: boolean;
+  // Mapping that fired: index into the device's mappings, index within its
+  // conditional block (if any), and kind
+  matchedMapping?: { index: number; inner?: number; kind: string };
 }